@@ -1,18 +1,35 @@
-use std::net::SocketAddr;
+use std::{net::SocketAddr, path::PathBuf};
 
-use clap::{Args, Parser, Subcommand};
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
 
-use crate::metadata::{PKG_DESCRIPTION, PKG_NAME, PKG_VERSION};
+use crate::{
+    metadata::{PKG_DESCRIPTION, PKG_NAME, PKG_VERSION},
+    paths,
+    payload_tools::{generator::TemplateType, naming::NamingProfiles, types::FileType},
+    policy::ToolPolicy,
+};
+
+const DEFAULT_CORS_HEADERS: &str =
+    "Content-Type,Accept,Authorization,Mcp-Session-Id,Mcp-Protocol-Version,Last-Event-ID";
+const DEFAULT_CORS_METHODS: &str = "GET,POST,DELETE,OPTIONS";
 
 #[derive(Parser, Debug, Clone)]
 #[command(name = PKG_NAME)]
 #[command(version = PKG_VERSION)]
 #[command(about = PKG_DESCRIPTION, long_about = None)]
 pub struct Cli {
+    /// Settings file to use instead of settings.json in the config directory
+    #[arg(long, global = true, env = "MCP_CONFIG")]
+    pub config: Option<PathBuf>,
+
     #[command(subcommand)]
     pub command: Command,
 }
 
+// Parsed once at startup, so the size of `Start` doesn't matter.
+#[allow(clippy::large_enum_variant)]
 #[derive(Subcommand, Debug, Clone)]
 pub enum Command {
     /// Start the MCP server
@@ -23,10 +40,322 @@ pub enum Command {
     Shutdown,
     /// Print version information
     Version,
-    /// Print setup guidance for a client (prefers HTTP endpoints)
-    Setup,
-    /// Open an interactive config editor for settings.json
-    Config,
+    /// Configure an MCP client to use this server (prefers HTTP endpoints)
+    Setup(SetupArguments),
+    /// Open an interactive config editor for settings.json, or check it
+    Config(ConfigArguments),
+    /// Print the end of the current log file
+    Logs(LogsArguments),
+    /// Check the environment and suggest fixes for problems found
+    Doctor,
+    /// Manage secrets in the OS keyring, referenced as `${secret:NAME}`
+    Secret(SecretArguments),
+    /// Manage named Payload connections used as `connection` in tools
+    Connection(ConnectionArguments),
+    /// List, describe or call tools in-process, without an MCP client
+    Tool(ToolArguments),
+    /// Validate Payload source files offline
+    Validate(ValidateArguments),
+    /// Re-validate Payload source files whenever they change
+    Watch(WatchArguments),
+    /// Generate a code template, like the `generate_template` tool
+    Generate(GenerateArguments),
+    /// Scaffold a project on disk, like the `scaffold_project` tool
+    Scaffold(ScaffoldArguments),
+}
+
+#[derive(Args, Debug, Clone)]
+pub struct GenerateArguments {
+    /// Template to generate (collection, field, global, config,
+    /// access-control, hook, endpoint, plugin, block, migration, data-access,
+    /// field-encryption, scheduled-publish, security)
+    pub template_type: TemplateType,
+
+    /// JSON file with the template options
+    #[arg(long)]
+    pub options: Option<PathBuf>,
+
+    /// Slug of the generated collection or global
+    #[arg(long)]
+    pub slug: Option<String>,
+
+    /// Name of the generated field, block, hook or plugin
+    #[arg(long)]
+    pub name: Option<String>,
+
+    /// JSON file with an array of field definitions
+    #[arg(long)]
+    pub fields: Option<PathBuf>,
+
+    /// A single option; the value is parsed as JSON when possible
+    #[arg(long, value_name = "KEY=VALUE")]
+    pub set: Vec<String>,
+
+    /// Naming profile applied to slugs and names (defaults to the one in
+    /// settings)
+    #[arg(long)]
+    pub naming_profile: Option<String>,
+
+    /// Write the code to this file instead of printing it
+    #[arg(long)]
+    pub out: Option<PathBuf>,
+
+    /// Overwrite an existing output file
+    #[arg(long, default_value_t = false)]
+    pub force: bool,
+}
+
+#[derive(Args, Debug, Clone)]
+pub struct ScaffoldArguments {
+    /// JSON file with scaffold options, as taken by `scaffold_project`
+    #[arg(long)]
+    pub options: Option<PathBuf>,
+
+    /// Project name; defaults to the output directory name
+    #[arg(long)]
+    pub project_name: Option<String>,
+
+    /// Also generate Vitest suites
+    #[arg(long, default_value_t = false)]
+    pub include_tests: bool,
+
+    /// Directory to write the project into; prints the file structure as
+    /// JSON when omitted
+    #[arg(long)]
+    pub out: Option<PathBuf>,
+
+    /// Overwrite existing files
+    #[arg(long, default_value_t = false)]
+    pub force: bool,
+}
+
+#[derive(Args, Debug, Clone)]
+pub struct ValidateArguments {
+    /// Files, directories or glob patterns to validate
+    #[arg(required_unless_present = "self_check")]
+    pub paths: Vec<String>,
+
+    /// Validate the server's own generated code instead of files: every
+    /// template, scaffold preset and reference example
+    #[arg(long, default_value_t = false, conflicts_with_all = ["paths", "file_type"])]
+    pub self_check: bool,
+
+    /// Validate every file as this type instead of detecting it
+    /// (collection, field, global, config)
+    #[arg(long)]
+    pub file_type: Option<FileType>,
+
+    /// Report format
+    #[arg(long, value_enum, default_value_t = ReportFormat::Pretty)]
+    pub format: ReportFormat,
+
+    /// Exit with status 1 when any file has warnings
+    #[arg(long, default_value_t = false)]
+    pub deny_warnings: bool,
+
+    /// Warn about configs missing csrf, cors or (on Payload 2) rateLimit
+    #[arg(long, default_value_t = false, conflicts_with = "self_check")]
+    pub production: bool,
+
+    /// Files validated in parallel (0 uses one thread per CPU)
+    #[arg(long, short = 'j', default_value_t = 0)]
+    pub jobs: usize,
+}
+
+#[derive(Args, Debug, Clone)]
+pub struct WatchArguments {
+    /// Files, directories or glob patterns to watch
+    #[arg(required = true)]
+    pub paths: Vec<String>,
+
+    /// Validate every file as this type instead of detecting it
+    /// (collection, field, global, config)
+    #[arg(long)]
+    pub file_type: Option<FileType>,
+
+    /// Report format of each batch of changed files
+    #[arg(long, value_enum, default_value_t = ReportFormat::Pretty)]
+    pub format: ReportFormat,
+
+    /// Milliseconds between checks for changed files
+    #[arg(long, default_value_t = 500)]
+    pub interval_ms: u64,
+
+    /// Files validated in parallel (0 uses one thread per CPU)
+    #[arg(long, short = 'j', default_value_t = 0)]
+    pub jobs: usize,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum ReportFormat {
+    Pretty,
+    Json,
+    Sarif,
+    Junit,
+}
+
+#[derive(Args, Debug, Clone)]
+pub struct SetupArguments {
+    /// Client to configure (vscode, claude-desktop, zed, codex, gemini, roo);
+    /// prompts when omitted
+    #[arg(long)]
+    pub client: Option<String>,
+
+    /// Transport the client connects over (stdio, http, sse); prompts when
+    /// omitted
+    #[arg(long)]
+    pub transport: Option<String>,
+
+    /// Print the client configuration without writing any files
+    #[arg(long, default_value_t = false)]
+    pub print_only: bool,
+}
+
+#[derive(Args, Debug, Clone)]
+pub struct ConfigArguments {
+    #[command(subcommand)]
+    pub command: Option<ConfigCommand>,
+}
+
+// Parsed once at startup, so the size of `Explain` doesn't matter.
+#[allow(clippy::large_enum_variant)]
+#[derive(Subcommand, Debug, Clone)]
+pub enum ConfigCommand {
+    /// Check settings.json and report every problem found
+    Validate,
+    /// Show each setting's effective value and the layer that supplied it:
+    /// default, settings file, environment variable or flag
+    Explain(ExplainArguments),
+}
+
+#[derive(Args, Debug, Clone)]
+pub struct ExplainArguments {
+    /// Output format
+    #[arg(long, value_enum, default_value_t = OutputFormat::Pretty)]
+    pub format: OutputFormat,
+
+    /// Flags as passed to `start`, to see how they combine with the rest
+    #[command(flatten)]
+    pub args: CommandArguments,
+}
+
+#[derive(Args, Debug, Clone)]
+pub struct SecretArguments {
+    #[command(subcommand)]
+    pub command: SecretCommand,
+}
+
+#[derive(Subcommand, Debug, Clone)]
+pub enum SecretCommand {
+    /// Store a secret, prompting for its value (or reading it from stdin)
+    Set { name: String },
+    /// Remove a secret
+    Delete { name: String },
+    /// Report whether a secret is stored, without printing it
+    Check { name: String },
+}
+
+#[derive(Args, Debug, Clone)]
+pub struct ConnectionArguments {
+    #[command(subcommand)]
+    pub command: ConnectionCommand,
+}
+
+#[derive(Subcommand, Debug, Clone)]
+pub enum ConnectionCommand {
+    /// Add or replace a connection
+    Add {
+        name: String,
+        /// Base URL of the Payload instance
+        url: String,
+        /// Credential reference such as `${secret:NAME}`; see `secret set`
+        #[arg(long)]
+        api_key: Option<String>,
+    },
+    /// List stored connections
+    List,
+    /// Remove a connection
+    Remove { name: String },
+    /// Check that a connection's credentials resolve and the instance answers
+    Test { name: String },
+}
+
+#[derive(Args, Debug, Clone)]
+pub struct LogsArguments {
+    /// Number of lines to print
+    #[arg(short = 'n', long, default_value_t = 50)]
+    pub lines: usize,
+
+    /// Keep printing lines as they are written
+    #[arg(short, long, default_value_t = false)]
+    pub follow: bool,
+}
+
+#[derive(Args, Debug, Clone)]
+pub struct ToolArguments {
+    /// Print results as pretty text or JSON
+    #[arg(long, value_enum, default_value_t = OutputFormat::Pretty, global = true)]
+    pub format: OutputFormat,
+
+    #[command(subcommand)]
+    pub command: ToolCommand,
+}
+
+#[derive(Subcommand, Debug, Clone)]
+pub enum ToolCommand {
+    /// List available tools
+    List,
+    /// Print a tool's input and output schema
+    Schema { name: String },
+    /// Call a tool: `tool <name> [--file PATH] [--args JSON] [--arg KEY=VALUE]...`
+    #[command(external_subcommand)]
+    Call(Vec<String>),
+}
+
+/// Arguments following a tool name in `tool <name> ...`.
+#[derive(Parser, Debug, Clone)]
+#[command(name = "tool")]
+pub struct ToolCallArguments {
+    /// Tool to call
+    pub name: String,
+
+    /// Read arguments from a file; its contents become `code` for tools
+    /// that validate code
+    #[arg(long)]
+    pub file: Option<PathBuf>,
+
+    /// Arguments as a JSON object
+    #[arg(long)]
+    pub args: Option<String>,
+
+    /// A single argument; the value is parsed as JSON when possible
+    #[arg(long, value_name = "KEY=VALUE")]
+    pub arg: Vec<String>,
+
+    /// Print the result as pretty text or JSON
+    #[arg(long, value_enum)]
+    pub format: Option<OutputFormat>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum OutputFormat {
+    Pretty,
+    Json,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum LogFormat {
+    Pretty,
+    Json,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum LogRotation {
+    Hourly,
+    Daily,
+    Never,
 }
 
 #[derive(Args, Debug, Clone)]
@@ -80,20 +409,138 @@ pub struct CommandArguments {
     pub enable_ws: bool,
 
     /// Unix socket path (unix only)
-    #[arg(long, env = "MCP_UNIX_PATH", default_value = "/tmp/mcp-server.sock")]
+    #[arg(long, env = "MCP_UNIX_PATH", default_value = paths::runtime_file("mcp-payloadcms.sock"))]
     pub unix_path: String,
 
+    /// Enable named-pipe transport (windows only)
+    #[arg(long, env = "MCP_ENABLE_PIPE", default_value_t = false)]
+    pub enable_pipe: bool,
+
+    /// Named pipe path (windows only)
+    #[arg(long, env = "MCP_PIPE_NAME", default_value = r"\\.\pipe\mcp-server")]
+    pub pipe_name: String,
+
     /// PID file path for shutdown coordination
-    #[arg(long, env = "MCP_PID_FILE", default_value = "/tmp/mcp-server-template-rs.pid")]
+    #[arg(long, env = "MCP_PID_FILE", default_value = paths::state_file("mcp-payloadcms.pid"))]
     pub pid_file: String,
 
     /// Runtime info file (used by status/shutdown commands)
-    #[arg(long, env = "MCP_RUNTIME_INFO_FILE", default_value = "/tmp/mcp-server-template-rs.runtime.json")]
+    #[arg(long, env = "MCP_RUNTIME_INFO_FILE", default_value = paths::state_file("mcp-payloadcms.runtime.json"))]
     pub runtime_info_file: String,
 
+    /// Origins allowed to call the HTTP and SSE transports from a browser
+    /// (comma-separated, `*` for any); CORS headers are only sent when set
+    #[arg(long, env = "MCP_CORS_ALLOWED_ORIGINS", value_delimiter = ',')]
+    pub cors_allowed_origins: Vec<String>,
+
+    /// Request headers allowed in CORS preflights (comma-separated)
+    #[arg(
+        long,
+        env = "MCP_CORS_ALLOWED_HEADERS",
+        value_delimiter = ',',
+        default_value = DEFAULT_CORS_HEADERS
+    )]
+    pub cors_allowed_headers: Vec<String>,
+
+    /// Methods allowed in CORS preflights (comma-separated)
+    #[arg(
+        long,
+        env = "MCP_CORS_ALLOWED_METHODS",
+        value_delimiter = ',',
+        default_value = DEFAULT_CORS_METHODS
+    )]
+    pub cors_allowed_methods: Vec<String>,
+
+    /// Requests each connection may send per minute (0 disables the limit)
+    #[arg(long, env = "MCP_RATE_LIMIT_PER_MINUTE", default_value_t = 600)]
+    pub rate_limit_per_minute: u32,
+
+    /// Tool calls executing at once across all connections (0 disables the cap)
+    #[arg(long, env = "MCP_MAX_CONCURRENT_TOOLS", default_value_t = 16)]
+    pub max_concurrent_tools: usize,
+
+    /// Seconds a TCP, Unix, pipe or websocket session may go without a
+    /// request before it is closed (0 keeps idle sessions open)
+    #[arg(long, env = "MCP_IDLE_TIMEOUT_SECS", default_value_t = 3600)]
+    pub idle_timeout_secs: u64,
+
+    /// Connections open at once across all transports (0 disables the cap)
+    #[arg(long, env = "MCP_MAX_CONNECTIONS", default_value_t = 256)]
+    pub max_connections: usize,
+
+    /// Threads validating files in parallel within one tool call (0 uses one
+    /// per CPU)
+    #[arg(long, env = "MCP_VALIDATION_JOBS", default_value_t = 0)]
+    pub validation_jobs: usize,
+
+    /// Seconds collection schemas fetched from a live instance are reused
+    /// before being revalidated (0 disables the schema cache)
+    #[arg(long, env = "MCP_SCHEMA_CACHE_TTL_SECS", default_value_t = 300)]
+    pub schema_cache_ttl_secs: u64,
+
+    /// Also keep cached schemas in the state directory across restarts
+    #[arg(long, env = "MCP_SCHEMA_CACHE_ON_DISK", default_value_t = false)]
+    pub schema_cache_on_disk: bool,
+
+    /// Directory of Handlebars (`<type>.hbs`) or Tera (`<type>.tera`) files
+    /// that replace the built-in template of that type (empty disables
+    /// overrides)
+    #[arg(long, env = "MCP_TEMPLATES_DIR", default_value = paths::config_file("templates"))]
+    pub templates_dir: String,
+
+    /// Naming profile the validator enforces and the generator applies:
+    /// none, payload, snake, or one from `--naming-profiles`
+    #[arg(long, env = "MCP_NAMING_PROFILE", default_value = "none")]
+    pub naming_profile: String,
+
+    /// Custom naming profiles, as JSON, e.g.
+    /// `{"team": {"slugs": "kebab", "fields": "camel", "pluralCollections": true}}`
+    #[arg(long, env = "MCP_NAMING_PROFILES", default_value = "{}")]
+    pub naming_profiles: NamingProfiles,
+
+    /// Seconds to wait for running tool calls to finish on shutdown
+    #[arg(long, env = "MCP_SHUTDOWN_TIMEOUT_SECS", default_value_t = 10)]
+    pub shutdown_timeout_secs: u64,
+
+    /// Project files, directories or glob patterns to re-validate as they
+    /// change (comma-separated); diagnostics go to the log and to clients
+    /// that enabled logging
+    #[arg(long, env = "MCP_WATCH", value_delimiter = ',')]
+    pub watch: Vec<String>,
+
+    /// Milliseconds between checks of the watched files
+    #[arg(long, env = "MCP_WATCH_INTERVAL_MS", default_value_t = 1000)]
+    pub watch_interval_ms: u64,
+
+    /// Tools available per transport, as JSON, e.g.
+    /// `{"http": {"deny": ["scaffold_project"]}, "*": {"allow": ["validate"]}}`
+    #[arg(long, env = "MCP_TOOL_POLICY", default_value = "{}")]
+    pub tool_policy: ToolPolicy,
+
+    /// Log file written in addition to stderr (empty disables file logging);
+    /// rotated files get a date suffix
+    #[arg(long, env = "MCP_LOG_FILE", default_value = paths::state_file("mcp-payloadcms.log"))]
+    pub log_file: String,
+
+    /// Format of the log file
+    #[arg(long, env = "MCP_LOG_FORMAT", value_enum, default_value_t = LogFormat::Pretty)]
+    pub log_format: LogFormat,
+
+    /// How often the log file is rotated
+    #[arg(long, env = "MCP_LOG_ROTATION", value_enum, default_value_t = LogRotation::Daily)]
+    pub log_rotation: LogRotation,
+
+    /// Rotated log files to keep (0 keeps all)
+    #[arg(long, env = "MCP_LOG_MAX_FILES", default_value_t = 7)]
+    pub log_max_files: usize,
+
     /// Run in foreground (skip background/daemon spawn)
     #[arg(long, env = "MCP_FOREGROUND", default_value_t = false, hide = true)]
     pub foreground: bool,
+
+    /// Set on the detached server spawned by `start`; its stdio goes to the log file
+    #[arg(long, default_value_t = false, hide = true)]
+    pub daemon_child: bool,
 }
 
 impl CommandArguments {
@@ -111,10 +558,34 @@ impl CommandArguments {
             http_addr: "0.0.0.0:0".to_string(),
             sse_addr: "0.0.0.0:0".to_string(),
             ws_addr: "0.0.0.0:0".to_string(),
-            unix_path: "/tmp/mcp-server.sock".to_string(),
-            pid_file: "/tmp/mcp-server-template-rs.pid".to_string(),
-            runtime_info_file: "/tmp/mcp-server-template-rs.runtime.json".to_string(),
+            unix_path: paths::runtime_file("mcp-payloadcms.sock"),
+            enable_pipe: false,
+            pipe_name: r"\\.\pipe\mcp-server".to_string(),
+            pid_file: paths::state_file("mcp-payloadcms.pid"),
+            runtime_info_file: paths::state_file("mcp-payloadcms.runtime.json"),
+            cors_allowed_origins: Vec::new(),
+            cors_allowed_headers: split_list(DEFAULT_CORS_HEADERS),
+            cors_allowed_methods: split_list(DEFAULT_CORS_METHODS),
+            rate_limit_per_minute: 600,
+            max_concurrent_tools: 16,
+            idle_timeout_secs: 3600,
+            max_connections: 256,
+            validation_jobs: 0,
+            schema_cache_ttl_secs: 300,
+            schema_cache_on_disk: false,
+            templates_dir: paths::config_file("templates"),
+            naming_profile: "none".to_string(),
+            naming_profiles: NamingProfiles::default(),
+            shutdown_timeout_secs: 10,
+            watch: Vec::new(),
+            watch_interval_ms: 1000,
+            tool_policy: ToolPolicy::default(),
+            log_file: paths::state_file("mcp-payloadcms.log"),
+            log_format: LogFormat::Pretty,
+            log_rotation: LogRotation::Daily,
+            log_max_files: 7,
             foreground: false,
+            daemon_child: false,
         }
     }
 
@@ -123,12 +594,14 @@ impl CommandArguments {
         if !self.enable_stdio
             && !self.enable_tcp
             && !self.enable_unix
+            && !self.enable_pipe
             && !self.enable_http
             && !self.enable_sse
             && !self.enable_ws
         {
             return Err(
-                "Enable at least one transport (stdio, tcp, unix, http, or sse)".to_string(),
+                "Enable at least one transport (stdio, tcp, unix, pipe, http, sse, or ws)"
+                    .to_string(),
             );
         }
 
@@ -152,9 +625,32 @@ impl CommandArguments {
                 .parse::<SocketAddr>()
                 .map_err(|e| format!("Invalid MCP_WS_ADDR '{}': {e}", self.ws_addr))?;
         }
+        for method in &self.cors_allowed_methods {
+            method
+                .parse::<hyper::Method>()
+                .map_err(|e| format!("Invalid MCP_CORS_ALLOWED_METHODS entry '{method}': {e}"))?;
+        }
         if self.enable_unix && self.unix_path.trim().is_empty() {
             return Err("MCP_UNIX_PATH cannot be empty when unix transport is enabled".to_string());
         }
+        if self.enable_pipe && !cfg!(windows) {
+            return Err(
+                "The named-pipe transport is only available on windows; use the unix socket transport instead"
+                    .to_string(),
+            );
+        }
+        if !self.naming_profiles.knows(&self.naming_profile) {
+            return Err(format!("Unknown MCP_NAMING_PROFILE '{}'", self.naming_profile));
+        }
+        if self.enable_pipe && !self.pipe_name.starts_with(r"\\.\pipe\") {
+            return Err(format!(
+                r"Invalid MCP_PIPE_NAME '{}': must start with \\.\pipe\",
+                self.pipe_name
+            ));
+        }
         Ok(())
     }
-}
\ No newline at end of file
+}
+fn split_list(value: &str) -> Vec<String> {
+    value.split(',').map(str::to_string).collect()
+}
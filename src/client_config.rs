@@ -0,0 +1,355 @@
+//! MCP client configuration for the `setup` command: the server entry each
+//! known client expects, where the client keeps it, and merging it into an
+//! existing config file.
+
+use std::{
+    collections::BTreeMap,
+    fs,
+    net::SocketAddr,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+use serde_json::{Map, Value, json};
+
+use crate::{cli::CommandArguments, server::RuntimeInfo};
+
+/// Transports a stdio launch turns off, so a client-spawned server never
+/// competes with a background one for ports or the instance lock.
+const NETWORK_TRANSPORT_ENV: [&str; 6] = [
+    "MCP_ENABLE_TCP",
+    "MCP_ENABLE_UNIX",
+    "MCP_ENABLE_PIPE",
+    "MCP_ENABLE_HTTP",
+    "MCP_ENABLE_SSE",
+    "MCP_ENABLE_WS",
+];
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Client {
+    VsCode,
+    ClaudeDesktop,
+    Zed,
+    Codex,
+    Gemini,
+    Roo,
+}
+
+impl Client {
+    pub const ALL: [Client; 6] = [
+        Client::VsCode,
+        Client::ClaudeDesktop,
+        Client::Zed,
+        Client::Codex,
+        Client::Gemini,
+        Client::Roo,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Client::VsCode => "vscode",
+            Client::ClaudeDesktop => "claude-desktop",
+            Client::Zed => "zed",
+            Client::Codex => "codex",
+            Client::Gemini => "gemini",
+            Client::Roo => "roo",
+        }
+    }
+
+    /// The file the client reads MCP servers from. VS Code and Roo use the
+    /// workspace in the current directory; the others a per-user file.
+    pub fn config_path(&self) -> Option<PathBuf> {
+        let home = dirs::home_dir();
+        match self {
+            Client::VsCode => Some(PathBuf::from(".vscode").join("mcp.json")),
+            Client::Roo => Some(PathBuf::from(".roo").join("mcp.json")),
+            Client::ClaudeDesktop => {
+                dirs::config_dir().map(|dir| dir.join("Claude").join("claude_desktop_config.json"))
+            }
+            Client::Zed if cfg!(windows) => {
+                dirs::config_dir().map(|dir| dir.join("Zed").join("settings.json"))
+            }
+            Client::Zed => home.map(|home| home.join(".config").join("zed").join("settings.json")),
+            Client::Codex => std::env::var_os("CODEX_HOME")
+                .map(PathBuf::from)
+                .or_else(|| home.map(|home| home.join(".codex")))
+                .map(|dir| dir.join("config.toml")),
+            Client::Gemini => home.map(|home| home.join(".gemini").join("settings.json")),
+        }
+    }
+
+    /// Key holding the server map in the client's JSON config.
+    fn servers_key(&self) -> &'static str {
+        match self {
+            Client::VsCode => "servers",
+            Client::Zed => "context_servers",
+            _ => "mcpServers",
+        }
+    }
+}
+
+impl FromStr for Client {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Client::ALL
+            .into_iter()
+            .find(|client| client.as_str().eq_ignore_ascii_case(s))
+            .ok_or_else(|| {
+                let known: Vec<_> = Client::ALL.iter().map(Client::as_str).collect();
+                format!("Unknown client: {s} (known: {})", known.join(", "))
+            })
+    }
+}
+
+/// How a client reaches the server.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Launch {
+    /// Spawn the server and talk over stdin/stdout
+    Stdio {
+        command: String,
+        args: Vec<String>,
+        env: BTreeMap<String, String>,
+    },
+    /// Streamable HTTP endpoint URL
+    Http(String),
+    /// Dedicated SSE endpoint URL
+    Sse(String),
+}
+
+impl Launch {
+    /// Launch for a transport chosen in setup: `stdio`, `http` or `sse`.
+    /// Other transports have no client support.
+    pub fn for_transport(args: &CommandArguments, transport: &str) -> Result<Self, String> {
+        match transport {
+            "stdio" => {
+                let command = std::env::current_exe()
+                    .map(|exe| exe.display().to_string())
+                    .unwrap_or_else(|_| crate::metadata::PKG_NAME.to_string());
+                let mut env: BTreeMap<String, String> = NETWORK_TRANSPORT_ENV
+                    .iter()
+                    .map(|name| (name.to_string(), "false".to_string()))
+                    .collect();
+                env.insert("MCP_ENABLE_STDIO".to_string(), "true".to_string());
+                Ok(Launch::Stdio {
+                    command,
+                    args: vec!["start".to_string(), "--foreground".to_string()],
+                    env,
+                })
+            }
+            "http" => Ok(Launch::Http(format!(
+                "http://{}/mcp",
+                client_address(args, "http", &args.http_addr)?
+            ))),
+            "sse" => Ok(Launch::Sse(format!(
+                "http://{}/sse",
+                client_address(args, "sse", &args.sse_addr)?
+            ))),
+            other => Err(format!(
+                "MCP clients cannot connect over {other}; choose stdio, http or sse"
+            )),
+        }
+    }
+
+    fn url(&self) -> Option<&str> {
+        match self {
+            Launch::Http(url) | Launch::Sse(url) => Some(url),
+            Launch::Stdio { .. } => None,
+        }
+    }
+}
+
+/// Address a client should dial for a transport: wildcard binds become
+/// loopback, and an auto-assigned port is taken from a running server.
+fn client_address(args: &CommandArguments, transport: &str, configured: &str) -> Result<String, String> {
+    let mut addr: SocketAddr = configured
+        .parse()
+        .map_err(|e| format!("Invalid {transport} address '{configured}': {e}"))?;
+    if addr.port() == 0 {
+        addr = RuntimeInfo::read(&args.runtime_info_file)
+            .and_then(|info| {
+                info.endpoints
+                    .into_iter()
+                    .find(|endpoint| endpoint.transport == transport)
+            })
+            .and_then(|endpoint| endpoint.address.parse().ok())
+            .ok_or_else(|| {
+                format!(
+                    "The {transport} port is auto-assigned ({configured}); start the server first or set a fixed port"
+                )
+            })?;
+    }
+    if addr.ip().is_unspecified() {
+        addr.set_ip(if addr.is_ipv4() {
+            [127, 0, 0, 1].into()
+        } else {
+            std::net::Ipv6Addr::LOCALHOST.into()
+        });
+    }
+    Ok(addr.to_string())
+}
+
+/// Bridge for clients that only spawn commands.
+fn mcp_remote(url: &str) -> Value {
+    json!({ "command": "npx", "args": ["-y", "mcp-remote", url] })
+}
+
+/// The server entry in the client's JSON format.
+pub fn entry(client: Client, launch: &Launch) -> Value {
+    if let Launch::Stdio { command, args, env } = launch {
+        let mut entry = json!({ "command": command, "args": args, "env": env });
+        if client == Client::VsCode {
+            entry["type"] = json!("stdio");
+        }
+        return entry;
+    }
+    let url = launch.url().unwrap_or_default();
+    let sse = matches!(launch, Launch::Sse(_));
+    match client {
+        Client::VsCode => json!({ "type": if sse { "sse" } else { "http" }, "url": url }),
+        Client::Roo => json!({ "type": if sse { "sse" } else { "streamable-http" }, "url": url }),
+        Client::Gemini if sse => json!({ "url": url }),
+        Client::Gemini => json!({ "httpUrl": url }),
+        Client::Zed => json!({ "url": url }),
+        Client::ClaudeDesktop | Client::Codex => mcp_remote(url),
+    }
+}
+
+/// The config fragment to add for `name`, as it would appear in the file.
+pub fn snippet(client: Client, name: &str, launch: &Launch) -> String {
+    if client == Client::Codex {
+        return codex_table(name, launch);
+    }
+    let fragment = json!({ client.servers_key(): { name: entry(client, launch) } });
+    serde_json::to_string_pretty(&fragment).unwrap_or_default()
+}
+
+/// Codex keeps servers in TOML tables; JSON string literals are valid TOML
+/// basic strings.
+fn codex_table(name: &str, launch: &Launch) -> String {
+    let quote = |value: &str| Value::from(value).to_string();
+    let key = if !name.is_empty()
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+    {
+        name.to_string()
+    } else {
+        quote(name)
+    };
+    let mut out = format!("[mcp_servers.{key}]\n");
+    match launch {
+        Launch::Stdio { command, args, env } => {
+            out.push_str(&format!("command = {}\n", quote(command)));
+            let args: Vec<_> = args.iter().map(|arg| quote(arg)).collect();
+            out.push_str(&format!("args = [{}]\n", args.join(", ")));
+            if !env.is_empty() {
+                out.push_str(&format!("\n[mcp_servers.{key}.env]\n"));
+                for (key, value) in env {
+                    out.push_str(&format!("{key} = {}\n", quote(value)));
+                }
+            }
+        }
+        Launch::Http(url) => out.push_str(&format!("url = {}\n", quote(url))),
+        Launch::Sse(url) => {
+            out.push_str("command = \"npx\"\n");
+            out.push_str(&format!("args = [\"-y\", \"mcp-remote\", {}]\n", quote(url)));
+        }
+    }
+    out
+}
+
+/// Add or replace the `name` server in the client's config file, keeping
+/// everything else. An existing file is copied to `<file>.bak` first.
+pub fn install(client: Client, path: &Path, name: &str, launch: &Launch) -> Result<(), String> {
+    let existing = match fs::read_to_string(path) {
+        Ok(contents) => Some(contents),
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => None,
+        Err(err) => return Err(format!("Could not read {}: {err}", path.display())),
+    };
+    let updated = match client {
+        Client::Codex => merge_codex(existing.as_deref().unwrap_or_default(), name, launch),
+        _ => merge_json(client, existing.as_deref(), name, launch)
+            .map_err(|e| format!("{}: {e}", path.display()))?,
+    };
+    if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+        fs::create_dir_all(dir).map_err(|e| format!("Could not create {}: {e}", dir.display()))?;
+    }
+    if existing.is_some() {
+        let backup = PathBuf::from(format!("{}.bak", path.display()));
+        fs::copy(path, &backup).map_err(|e| format!("Could not back up {}: {e}", path.display()))?;
+    }
+    fs::write(path, updated).map_err(|e| format!("Could not write {}: {e}", path.display()))
+}
+
+fn merge_json(client: Client, existing: Option<&str>, name: &str, launch: &Launch) -> Result<String, String> {
+    let mut root = match existing.filter(|contents| !contents.trim().is_empty()) {
+        Some(contents) => serde_json::from_str::<Value>(contents).map_err(|e| {
+            format!("not plain JSON ({e}); add the snippet by hand or rerun with --print-only")
+        })?,
+        None => Value::Object(Map::new()),
+    };
+    let root_map = root.as_object_mut().ok_or("expected a JSON object")?;
+    let servers = root_map
+        .entry(client.servers_key())
+        .or_insert_with(|| Value::Object(Map::new()))
+        .as_object_mut()
+        .ok_or_else(|| format!("\"{}\" is not an object", client.servers_key()))?;
+    servers.insert(name.to_string(), entry(client, launch));
+    Ok(format!("{}\n", serde_json::to_string_pretty(&root).unwrap_or_default()))
+}
+
+/// Drop any existing tables for `name` and append the new ones.
+fn merge_codex(existing: &str, name: &str, launch: &Launch) -> String {
+    let quoted = Value::from(name).to_string();
+    let owned = |header: &str| {
+        [format!("[mcp_servers.{name}"), format!("[mcp_servers.{quoted}")]
+            .iter()
+            .any(|prefix| header == format!("{prefix}]") || header == format!("{prefix}.env]"))
+    };
+    let mut kept = Vec::new();
+    let mut skipping = false;
+    for line in existing.lines() {
+        let trimmed = line.trim();
+        if trimmed.starts_with('[') {
+            skipping = owned(trimmed);
+        }
+        if !skipping {
+            kept.push(line);
+        }
+    }
+    let mut out = kept.join("\n").trim_end().to_string();
+    if !out.is_empty() {
+        out.push_str("\n\n");
+    }
+    out.push_str(&codex_table(name, launch));
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_merge_keeps_other_servers() {
+        let launch = Launch::Http("http://127.0.0.1:8080/mcp".to_string());
+        let merged = merge_json(
+            Client::VsCode,
+            Some(r#"{"servers": {"other": {"type": "stdio", "command": "x"}}, "inputs": []}"#),
+            "payload",
+            &launch,
+        )
+        .unwrap();
+        let value: Value = serde_json::from_str(&merged).unwrap();
+        assert_eq!(value["servers"]["payload"]["url"], "http://127.0.0.1:8080/mcp");
+        assert_eq!(value["servers"]["other"]["command"], "x");
+        assert!(value["inputs"].is_array());
+
+        let codex = merge_codex(
+            "model = \"o3\"\n\n[mcp_servers.payload]\nurl = \"http://old\"\n\n[mcp_servers.keep]\ncommand = \"y\"\n",
+            "payload",
+            &launch,
+        );
+        assert!(!codex.contains("http://old"));
+        assert!(codex.contains("[mcp_servers.keep]"));
+        assert!(codex.ends_with("url = \"http://127.0.0.1:8080/mcp\"\n"));
+    }
+}
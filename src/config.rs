@@ -0,0 +1,253 @@
+//! Layered server configuration.
+//!
+//! Each setting comes from the highest layer that sets it: command-line
+//! flags, then `MCP_*` environment variables, then the settings file, then
+//! the built-in defaults. Flags and variables are told apart by clap's value
+//! source rather than by comparing with the defaults, so one set to its
+//! default value still overrides the settings file.
+
+use std::{collections::BTreeMap, fmt, path::Path};
+
+use clap::{ArgMatches, Args, FromArgMatches, parser::ValueSource};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+use crate::{cli::CommandArguments, settings::SettingsFile};
+
+/// Where a setting's value came from, lowest first.
+#[derive(
+    Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize, JsonSchema,
+)]
+#[serde(rename_all = "lowercase")]
+pub enum Layer {
+    Default,
+    File,
+    Env,
+    Cli,
+}
+
+impl Layer {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Default => "default",
+            Self::File => "file",
+            Self::Env => "env",
+            Self::Cli => "cli",
+        }
+    }
+}
+
+impl fmt::Display for Layer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct Setting {
+    /// Settings file key, e.g. `http_addr`
+    pub key: String,
+    /// Effective value, as written in the settings file
+    pub value: Value,
+    /// Layer that supplied `value`
+    pub layer: Layer,
+    /// Settings file, environment variable or flag of `layer`; empty for
+    /// defaults
+    pub origin: String,
+    /// Lower layers, other than the defaults, that also set the key
+    pub overrides: Vec<Layer>,
+}
+
+/// Resolved server arguments and where each setting came from.
+#[derive(Debug, Clone)]
+pub struct Config {
+    pub args: CommandArguments,
+    pub settings: Vec<Setting>,
+}
+
+/// The server arguments as a clap command, for their flags and variables.
+pub fn arguments() -> clap::Command {
+    CommandArguments::augment_args(clap::Command::new("start"))
+}
+
+/// Server arguments from the environment alone, for commands without
+/// server flags.
+pub fn env_matches() -> Result<ArgMatches, String> {
+    arguments()
+        .try_get_matches_from(["start"])
+        .map_err(|err| err.to_string())
+}
+
+/// Values of `settings` that are set, by key.
+fn entries(settings: &SettingsFile) -> Map<String, Value> {
+    match serde_json::to_value(settings) {
+        Ok(Value::Object(map)) => map
+            .into_iter()
+            .filter(|(_, value)| !value.is_null())
+            .collect(),
+        _ => Map::new(),
+    }
+}
+
+/// Resolve the server arguments from every layer. `file` is the settings
+/// file read from `path`, if there is one; `matches` holds the server
+/// arguments of the command, or [`env_matches`].
+pub fn resolve(
+    file: Option<&SettingsFile>,
+    path: &Path,
+    matches: &ArgMatches,
+) -> Result<Config, String> {
+    let parsed = CommandArguments::from_arg_matches(matches).map_err(|err| err.to_string())?;
+    let given = entries(&SettingsFile::from(&parsed));
+
+    let mut layers: BTreeMap<Layer, BTreeMap<String, (Value, String)>> = BTreeMap::new();
+    let defaults = entries(&SettingsFile::from(&CommandArguments::default_settings()));
+    layers.insert(
+        Layer::Default,
+        defaults
+            .into_iter()
+            .map(|(key, value)| (key, (value, String::new())))
+            .collect(),
+    );
+    let origin = path.display().to_string();
+    layers.insert(
+        Layer::File,
+        file.map(entries)
+            .unwrap_or_default()
+            .into_iter()
+            .map(|(key, value)| (key, (value, origin.clone())))
+            .collect(),
+    );
+    for arg in arguments().get_arguments() {
+        let key = arg.get_id().as_str();
+        // Flags that aren't settings, like `--foreground`, have no layers.
+        let Some(value) = given.get(key) else {
+            continue;
+        };
+        let (layer, origin) = match matches.value_source(key) {
+            Some(ValueSource::EnvVariable) => (
+                Layer::Env,
+                arg.get_env()
+                    .map(|name| name.to_string_lossy().into_owned())
+                    .unwrap_or_default(),
+            ),
+            Some(ValueSource::CommandLine) => (
+                Layer::Cli,
+                arg.get_long()
+                    .map(|long| format!("--{long}"))
+                    .unwrap_or_default(),
+            ),
+            _ => continue,
+        };
+        layers
+            .entry(layer)
+            .or_default()
+            .insert(key.to_string(), (value.clone(), origin));
+    }
+
+    let mut merged = Map::new();
+    let mut settings = Vec::new();
+    let keys: Vec<String> = layers[&Layer::Default].keys().cloned().collect();
+    for key in keys {
+        let set: Vec<(Layer, &(Value, String))> = layers
+            .iter()
+            .filter_map(|(layer, values)| values.get(&key).map(|value| (*layer, value)))
+            .collect();
+        let Some(((layer, (value, origin)), lower)) = set.split_last() else {
+            continue;
+        };
+        merged.insert(key.clone(), value.clone());
+        settings.push(Setting {
+            key,
+            value: value.clone(),
+            layer: *layer,
+            origin: origin.clone(),
+            overrides: lower
+                .iter()
+                .rev()
+                .map(|(layer, _)| *layer)
+                .filter(|layer| *layer != Layer::Default)
+                .collect(),
+        });
+    }
+    let merged: SettingsFile =
+        serde_json::from_value(Value::Object(merged)).map_err(|err| err.to_string())?;
+    Ok(Config {
+        args: merged.apply(parsed),
+        settings,
+    })
+}
+
+/// One line per setting with its value, layer and origin.
+pub fn explain(settings: &[Setting]) -> String {
+    let width = settings
+        .iter()
+        .map(|setting| setting.key.len())
+        .max()
+        .unwrap_or(0);
+    settings
+        .iter()
+        .map(|setting| {
+            let mut line = format!(
+                "{:width$}  {}  [{}",
+                setting.key, setting.value, setting.layer
+            );
+            if !setting.origin.is_empty() {
+                line.push_str(&format!(" {}", setting.origin));
+            }
+            line.push(']');
+            if !setting.overrides.is_empty() {
+                let overrides: Vec<&str> = setting
+                    .overrides
+                    .iter()
+                    .map(|layer| layer.as_str())
+                    .collect();
+                line.push_str(&format!(" overrides {}", overrides.join(", ")));
+            }
+            line
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resolve_prefers_flags_set_to_their_default() {
+        let file = SettingsFile {
+            http_addr: Some("127.0.0.1:8080".to_string()),
+            rate_limit_per_minute: Some(60),
+            ..Default::default()
+        };
+        let matches = arguments()
+            .try_get_matches_from(["start", "--http-addr", "0.0.0.0:0"])
+            .unwrap();
+        let config = resolve(Some(&file), Path::new("settings.json"), &matches).unwrap();
+        let setting = |key: &str| {
+            config
+                .settings
+                .iter()
+                .find(|setting| setting.key == key)
+                .unwrap()
+                .clone()
+        };
+
+        assert_eq!(config.args.http_addr, "0.0.0.0:0");
+        assert_eq!(setting("http_addr").layer, Layer::Cli);
+        assert_eq!(setting("http_addr").origin, "--http-addr");
+        assert_eq!(setting("http_addr").overrides, vec![Layer::File]);
+        assert_eq!(config.args.rate_limit_per_minute, 60);
+        assert_eq!(setting("rate_limit_per_minute").layer, Layer::File);
+        assert_eq!(setting("rate_limit_per_minute").origin, "settings.json");
+        assert_eq!(setting("server_name").layer, Layer::Default);
+        assert!(
+            config
+                .settings
+                .iter()
+                .all(|setting| setting.key != "foreground")
+        );
+    }
+}
@@ -0,0 +1,215 @@
+//! Named Payload connections kept in the `connections` section of the
+//! settings file, so tools can take `connection: "staging"` instead of a URL
+//! and API key on every call.
+//!
+//! ```json
+//! { "connections": { "staging": { "url": "https://staging.example.com", "api_key": "${secret:payload-staging}" } } }
+//! ```
+//!
+//! API keys are stored as `${...}` references and resolved on each use, so
+//! the settings file never holds a plaintext credential.
+
+use std::{collections::BTreeMap, fs, path::Path};
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+use crate::{
+    paths,
+    payload_tools::client::{PayloadClient, create_payload_client, payload_client},
+    secrets,
+};
+
+/// Key of the section in the settings file.
+pub const SETTINGS_KEY: &str = "connections";
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct Connection {
+    /// Base URL of the Payload instance
+    pub url: String,
+    /// Credential reference: `${secret:NAME}` or `${PAYLOAD_*}`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub api_key: Option<String>,
+}
+
+impl Connection {
+    /// Check the name and that the API key is a reference rather than a
+    /// plaintext credential.
+    pub fn validate(&self, name: &str) -> Result<(), String> {
+        validate_name(name)?;
+        if self.url.trim().is_empty() {
+            return Err(format!("Connection '{name}' has no url"));
+        }
+        if let Some(api_key) = &self.api_key {
+            if !is_reference(api_key) {
+                return Err(format!(
+                    "The API key of '{name}' must be a reference such as ${{secret:{name}}}; \
+                     store the key with `secret set {name}` instead of saving it in settings"
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// Client with the URL and API key references resolved. Connections can
+    /// be added by tool calls, so they follow the same rules as tool
+    /// arguments: only `PAYLOAD_*` variables and secrets.
+    pub fn client(&self) -> Result<PayloadClient, String> {
+        let url = secrets::expand_argument(&self.url)?;
+        let api_key = self.api_key.as_deref().map(secrets::expand_argument).transpose()?;
+        Ok(payload_client(&url, api_key))
+    }
+}
+
+fn validate_name(name: &str) -> Result<(), String> {
+    let valid = !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+    if valid {
+        Ok(())
+    } else {
+        Err(format!(
+            "Invalid connection name '{name}': use letters, digits, '-' and '_'"
+        ))
+    }
+}
+
+/// A whole value that is one `${...}` reference.
+fn is_reference(value: &str) -> bool {
+    value.starts_with("${") && value.ends_with('}') && value[2..].find('}') == Some(value.len() - 3)
+}
+
+pub type Connections = BTreeMap<String, Connection>;
+
+fn read_settings(path: &Path) -> Result<Map<String, Value>, String> {
+    let contents = match fs::read_to_string(path) {
+        Ok(contents) => contents,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(Map::new()),
+        Err(err) => return Err(format!("Could not read {}: {err}", path.display())),
+    };
+    match serde_json::from_str(&contents) {
+        Ok(Value::Object(map)) => Ok(map),
+        Ok(_) => Err(format!("{} must contain a JSON object", path.display())),
+        Err(err) => Err(format!("Could not parse {}: {err}", path.display())),
+    }
+}
+
+fn write_settings(path: &Path, settings: Map<String, Value>) -> Result<(), String> {
+    paths::ensure_parent(path).map_err(|e| format!("Could not create {}: {e}", path.display()))?;
+    let contents = serde_json::to_string_pretty(&Value::Object(settings)).unwrap_or_default();
+    fs::write(path, contents).map_err(|e| format!("Could not write {}: {e}", path.display()))
+}
+
+/// Connections stored in the settings file at `path`.
+pub fn load(path: &Path) -> Result<Connections, String> {
+    match read_settings(path)?.remove(SETTINGS_KEY) {
+        Some(section) => serde_json::from_value(section)
+            .map_err(|e| format!("Invalid {SETTINGS_KEY} in {}: {e}", path.display())),
+        None => Ok(Connections::new()),
+    }
+}
+
+/// Add or replace a connection, leaving the rest of the file untouched.
+pub fn store(path: &Path, name: &str, connection: &Connection) -> Result<(), String> {
+    connection.validate(name)?;
+    let mut settings = read_settings(path)?;
+    let mut connections = match settings.remove(SETTINGS_KEY) {
+        Some(section) => serde_json::from_value::<Connections>(section)
+            .map_err(|e| format!("Invalid {SETTINGS_KEY} in {}: {e}", path.display()))?,
+        None => Connections::new(),
+    };
+    connections.insert(name.to_string(), connection.clone());
+    settings.insert(SETTINGS_KEY.to_string(), serde_json::to_value(connections).unwrap_or_default());
+    write_settings(path, settings)
+}
+
+/// Remove a connection; `false` when there was none by that name.
+pub fn remove(path: &Path, name: &str) -> Result<bool, String> {
+    let mut settings = read_settings(path)?;
+    let removed = settings
+        .get_mut(SETTINGS_KEY)
+        .and_then(Value::as_object_mut)
+        .is_some_and(|section| section.remove(name).is_some());
+    if removed {
+        write_settings(path, settings)?;
+    }
+    Ok(removed)
+}
+
+/// A connection from the active settings file.
+pub fn get(name: &str) -> Result<Connection, String> {
+    load(paths::active_settings_path())?
+        .remove(name)
+        .ok_or_else(|| format!("No connection named '{name}'; add it with add_connection"))
+}
+
+/// Names of the connections in the active settings file.
+pub fn names() -> Vec<String> {
+    load(paths::active_settings_path())
+        .map(|connections| connections.into_keys().collect())
+        .unwrap_or_default()
+}
+
+/// Client for `target`: a stored connection when one has that name,
+/// otherwise a connection string with `api_key`.
+pub fn client_for(target: &str, api_key: Option<String>) -> Result<PayloadClient, String> {
+    let stored = load(paths::active_settings_path())
+        .ok()
+        .and_then(|mut connections| connections.remove(target));
+    match stored {
+        Some(connection) => connection.client(),
+        None => create_payload_client(target, api_key).map_err(|err| err.to_string()),
+    }
+}
+
+/// Client from tool arguments: the named `connection`, else a
+/// `connection_string`; `None` when neither is given.
+pub fn resolve(
+    connection: Option<&str>,
+    connection_string: Option<&str>,
+    api_key: Option<String>,
+) -> Result<Option<PayloadClient>, String> {
+    match (connection, connection_string) {
+        (Some(_), Some(_)) => Err("Pass either connection or connection_string, not both".to_string()),
+        (Some(name), None) => get(name)?.client().map(Some),
+        (None, Some(connection_string)) => create_payload_client(connection_string, api_key)
+            .map(Some)
+            .map_err(|err| err.to_string()),
+        (None, None) => Ok(None),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_store_and_remove_connections() {
+        let dir = std::env::temp_dir().join(format!("mcp-connections-test-{}", std::process::id()));
+        let path = dir.join("settings.json");
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(&path, r#"{ "http_addr": "${HTTP_ADDR}" }"#).unwrap();
+
+        let staging = Connection {
+            url: "https://staging.example.com".to_string(),
+            api_key: Some("${secret:staging}".to_string()),
+        };
+        store(&path, "staging", &staging).unwrap();
+        assert_eq!(load(&path).unwrap().get("staging"), Some(&staging));
+        assert_eq!(read_settings(&path).unwrap()["http_addr"], "${HTTP_ADDR}");
+
+        let plaintext = Connection {
+            api_key: Some("abc123".to_string()),
+            ..staging.clone()
+        };
+        assert!(store(&path, "prod", &plaintext).is_err());
+        assert!(store(&path, "bad name", &staging).is_err());
+
+        assert!(remove(&path, "staging").unwrap());
+        assert!(!remove(&path, "staging").unwrap());
+        assert!(load(&path).unwrap().is_empty());
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
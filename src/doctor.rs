@@ -0,0 +1,281 @@
+//! The `doctor` command: environment checks with a suggested fix for each
+//! problem found.
+
+use std::{
+    fs,
+    net::{SocketAddr, TcpListener},
+    path::Path,
+};
+
+use sysinfo::{Pid, ProcessesToUpdate, System};
+
+use crate::{
+    cli::CommandArguments,
+    client_config::Client,
+    connections,
+    instance,
+    payload_tools::client::create_payload_client,
+    server::{RuntimeInfo, stop_file_path},
+};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Status {
+    Ok,
+    Warn,
+    Fail,
+}
+
+#[derive(Clone, Debug)]
+pub struct Check {
+    pub name: String,
+    pub status: Status,
+    pub detail: String,
+    /// What to do about a warning or failure
+    pub fix: Option<String>,
+}
+
+impl Check {
+    fn ok(name: impl Into<String>, detail: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            status: Status::Ok,
+            detail: detail.into(),
+            fix: None,
+        }
+    }
+
+    fn warn(name: impl Into<String>, detail: impl Into<String>, fix: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            status: Status::Warn,
+            detail: detail.into(),
+            fix: Some(fix.into()),
+        }
+    }
+
+    fn fail(name: impl Into<String>, detail: impl Into<String>, fix: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            status: Status::Fail,
+            detail: detail.into(),
+            fix: Some(fix.into()),
+        }
+    }
+}
+
+/// Run every check. `settings` is the outcome of parsing the settings file
+/// at `settings_path`.
+pub fn run(args: &CommandArguments, settings_path: &Path, settings: Result<(), String>) -> Vec<Check> {
+    let mut checks = vec![check_settings(args, settings_path, settings)];
+    let server = running_server(args);
+    checks.push(match &server {
+        Some(info) => Check::ok("server", format!("running (pid {})", info.pid)),
+        None => Check::ok("server", "not running"),
+    });
+    checks.extend(check_ports(args, server.as_ref()));
+    checks.extend(check_stale_files(args, server.is_some()));
+    checks.extend(check_payload_connection());
+    checks.extend(check_connections(settings_path));
+    checks.extend(check_clients(args));
+    checks
+}
+
+fn check_settings(args: &CommandArguments, path: &Path, settings: Result<(), String>) -> Check {
+    let name = "settings";
+    if !path.exists() {
+        return Check::ok(
+            name,
+            format!("{} not found; using defaults", path.display()),
+        );
+    }
+    if let Err(err) = settings {
+        return Check::fail(
+            name,
+            format!("{} is invalid: {err}", path.display()),
+            "run `config validate` to list each problem, then fix the file by hand or delete it and run `config` to recreate it",
+        );
+    }
+    match args.validate() {
+        Ok(()) => Check::ok(name, format!("{} is valid", path.display())),
+        Err(err) => Check::fail(name, err, "correct the setting with `config` or in the settings file"),
+    }
+}
+
+/// Runtime info of a server whose process is still alive.
+fn running_server(args: &CommandArguments) -> Option<RuntimeInfo> {
+    RuntimeInfo::read(&args.runtime_info_file).filter(|info| process_alive(info.pid))
+}
+
+fn process_alive(pid: u32) -> bool {
+    let mut sys = System::new();
+    sys.refresh_processes(ProcessesToUpdate::Some(&[Pid::from_u32(pid)]), true);
+    sys.process(Pid::from_u32(pid)).is_some()
+}
+
+fn check_ports(args: &CommandArguments, server: Option<&RuntimeInfo>) -> Vec<Check> {
+    let configured = [
+        ("tcp", args.enable_tcp, &args.tcp_addr, "MCP_TCP_ADDR"),
+        ("http", args.enable_http, &args.http_addr, "MCP_HTTP_ADDR"),
+        ("sse", args.enable_sse, &args.sse_addr, "MCP_SSE_ADDR"),
+        ("ws", args.enable_ws, &args.ws_addr, "MCP_WS_ADDR"),
+    ];
+    let mut checks = Vec::new();
+    for (transport, enabled, address, env) in configured {
+        if !enabled {
+            continue;
+        }
+        let name = format!("{transport} port");
+        let Ok(addr) = address.parse::<SocketAddr>() else {
+            checks.push(Check::fail(
+                name,
+                format!("invalid address '{address}'"),
+                format!("set {env} to host:port"),
+            ));
+            continue;
+        };
+        if addr.port() == 0 {
+            checks.push(Check::ok(name, format!("{address} (auto-assigned)")));
+            continue;
+        }
+        let owned = server.is_some_and(|info| {
+            info.endpoints
+                .iter()
+                .any(|endpoint| endpoint.transport == transport && endpoint.address == addr.to_string())
+        });
+        checks.push(match TcpListener::bind(addr) {
+            Ok(_) => Check::ok(name, format!("{address} is free")),
+            Err(_) if owned => Check::ok(name, format!("{address} is served by the running server")),
+            Err(err) => Check::fail(
+                name,
+                format!("{address} is unavailable: {err}"),
+                format!("stop the process using it or choose another port with {env} or `config`"),
+            ),
+        });
+    }
+    checks
+}
+
+fn check_stale_files(args: &CommandArguments, running: bool) -> Vec<Check> {
+    let mut checks = Vec::new();
+    let pid_file = Path::new(&args.pid_file);
+    if let Ok(contents) = fs::read_to_string(pid_file) {
+        match contents.trim().parse::<u32>() {
+            Ok(pid) if process_alive(pid) => {
+                checks.push(Check::ok("pid file", format!("pid {pid} is running")));
+            }
+            _ => checks.push(stale("pid file", pid_file)),
+        }
+    }
+    let lock = instance::lock_path(&args.pid_file);
+    if lock.exists() && !running {
+        let live = fs::read_to_string(&lock)
+            .ok()
+            .and_then(|pid| pid.trim().parse::<u32>().ok())
+            .is_some_and(process_alive);
+        if !live {
+            checks.push(stale("lock file", &lock));
+        }
+    }
+    let stop = stop_file_path(&args.pid_file);
+    let stop = Path::new(&stop);
+    if stop.exists() {
+        checks.push(stale("stop file", stop));
+    }
+    if !running {
+        let runtime = Path::new(&args.runtime_info_file);
+        if runtime.exists() {
+            checks.push(stale("runtime info", runtime));
+        }
+        let socket = Path::new(&args.unix_path);
+        if cfg!(unix) && socket.exists() {
+            checks.push(stale("unix socket", socket));
+        }
+    }
+    checks
+}
+
+fn stale(name: &str, path: &Path) -> Check {
+    Check::warn(
+        name,
+        format!("{} is left over from a server that is no longer running", path.display()),
+        format!("delete {}", path.display()),
+    )
+}
+
+fn check_payload_connection() -> Option<Check> {
+    let url = std::env::var("PAYLOAD_URL").ok()?;
+    let api_key = std::env::var("PAYLOAD_API_KEY").ok();
+    let name = "payload connection";
+    let result = create_payload_client(&url, api_key).and_then(|client| client.test_connection());
+    Some(match result {
+        Ok(info) => Check::ok(name, format!("PAYLOAD_URL {} is reachable", info.server_url)),
+        Err(err) => Check::warn(
+            name,
+            format!("PAYLOAD_URL {url} is unreachable: {err}"),
+            "start the Payload instance or correct PAYLOAD_URL / PAYLOAD_API_KEY",
+        ),
+    })
+}
+
+/// Stored connections whose credential references do not resolve. No
+/// requests are made; `connection test` does that.
+fn check_connections(settings_path: &Path) -> Vec<Check> {
+    let stored = match connections::load(settings_path) {
+        Ok(stored) => stored,
+        Err(err) => {
+            return vec![Check::fail(
+                "connections",
+                err,
+                "fix the connections section or remove entries with `connection remove`",
+            )];
+        }
+    };
+    stored
+        .into_iter()
+        .map(|(name, connection)| {
+            let check = format!("connection {name}");
+            match connection.client() {
+                Ok(_) => Check::ok(check, connection.url),
+                Err(err) => Check::warn(
+                    check,
+                    err,
+                    format!("store the credential with `secret set` or update it with `connection add {name}`"),
+                ),
+            }
+        })
+        .collect()
+}
+
+fn check_clients(args: &CommandArguments) -> Vec<Check> {
+    let mut configured = Vec::new();
+    let mut checks = Vec::new();
+    for client in Client::ALL {
+        let Some(path) = client.config_path() else {
+            continue;
+        };
+        let Ok(contents) = fs::read_to_string(&path) else {
+            continue;
+        };
+        if contents.contains(&args.server_name) {
+            configured.push(client.as_str());
+        } else {
+            checks.push(Check::ok(
+                format!("{} client", client.as_str()),
+                format!("{} exists but has no {} entry", path.display(), args.server_name),
+            ));
+        }
+    }
+    checks.insert(
+        0,
+        if configured.is_empty() {
+            Check::warn(
+                "clients",
+                "no MCP client is configured for this server",
+                "run `setup` to add it to a client",
+            )
+        } else {
+            Check::ok("clients", format!("configured in {}", configured.join(", ")))
+        },
+    );
+    checks
+}
@@ -0,0 +1,242 @@
+//! The `generate` and `scaffold` commands: run the generators behind the
+//! `generate_*` and `scaffold_project` tools and write their output to disk.
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use serde_json::{Map, Value, json};
+
+use crate::{
+    cli::{GenerateArguments, ScaffoldArguments},
+    payload_tools::{
+        generator::generate_template_with_naming,
+        naming,
+        mcp::scaffold_to_json,
+        scaffolder::{
+            CollectionOption, FieldOption, GlobalOption, ScaffoldOptions, scaffold_project,
+            validate_scaffold_options, write_scaffold,
+        },
+    },
+    tool_cli::parse_assignment,
+};
+
+fn read_json(path: &Path) -> Result<Value, String> {
+    let contents =
+        fs::read_to_string(path).map_err(|e| format!("Could not read {}: {e}", path.display()))?;
+    serde_json::from_str(&contents).map_err(|e| format!("Invalid JSON in {}: {e}", path.display()))
+}
+
+/// Template options from `--options`, then `--slug`/`--name`, `--fields` and
+/// each `--set`, later sources winning.
+pub fn generate_options(args: &GenerateArguments) -> Result<Value, String> {
+    let mut options = match &args.options {
+        Some(path) => match read_json(path)? {
+            Value::Object(map) => map,
+            _ => return Err(format!("{} must contain a JSON object", path.display())),
+        },
+        None => Map::new(),
+    };
+    if let Some(slug) = &args.slug {
+        options.insert("slug".into(), json!(slug));
+    }
+    if let Some(name) = &args.name {
+        options.insert("name".into(), json!(name));
+    }
+    if let Some(path) = &args.fields {
+        let fields = read_json(path)?;
+        if !fields.is_array() {
+            return Err(format!("{} must contain a JSON array of fields", path.display()));
+        }
+        options.insert("fields".into(), fields);
+    }
+    for pair in &args.set {
+        let (key, value) = parse_assignment(pair)?;
+        options.insert(key, value);
+    }
+    Ok(Value::Object(options))
+}
+
+/// Generate the template and write it to `--out`, or return the code when
+/// there is no output path.
+pub fn generate(args: &GenerateArguments) -> Result<String, String> {
+    let naming = naming::profile(args.naming_profile.as_deref())?;
+    let code = generate_template_with_naming(args.template_type, &generate_options(args)?, &naming)?;
+    match &args.out {
+        Some(path) => {
+            write_file(path, &code, args.force)?;
+            Ok(format!("Wrote {} template to {}", args.template_type.as_str(), path.display()))
+        }
+        None => Ok(code),
+    }
+}
+
+fn write_file(path: &Path, contents: &str, force: bool) -> Result<(), String> {
+    if path.exists() && !force {
+        return Err(format!("{} already exists (use --force to overwrite)", path.display()));
+    }
+    if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+        fs::create_dir_all(dir).map_err(|e| format!("Failed to create {}: {e}", dir.display()))?;
+    }
+    fs::write(path, contents).map_err(|e| format!("Failed to write {}: {e}", path.display()))
+}
+
+/// Scaffold a project and write it under `--out`, or return its file
+/// structure as JSON when there is no output directory.
+pub fn scaffold(args: &ScaffoldArguments) -> Result<String, String> {
+    let mut options = match &args.options {
+        Some(path) => {
+            let mut value = read_json(path)?;
+            // The name may come from --project-name or the output directory.
+            if let Some(map) = value.as_object_mut() {
+                map.entry("projectName").or_insert_with(|| json!(""));
+            }
+            serde_json::from_value::<ScaffoldOptions>(value)
+                .map_err(|e| format!("Invalid scaffold options in {}: {e}", path.display()))?
+        }
+        None => ScaffoldOptions::default(),
+    };
+    if let Some(name) = &args.project_name {
+        options.project_name = name.clone();
+    }
+    if options.project_name.trim().is_empty() {
+        if let Some(dir_name) = args.out.as_deref().and_then(Path::file_name) {
+            options.project_name = dir_name.to_string_lossy().into_owned();
+        }
+    }
+    if args.include_tests {
+        options.include_tests = Some(true);
+    }
+    validate_scaffold_options(&options).map_err(|errors| errors.join("; "))?;
+
+    match &args.out {
+        Some(root) => write_project(&options, root, args.force),
+        None => Ok(
+            serde_json::to_string_pretty(&scaffold_to_json(scaffold_project(&options)))
+                .unwrap_or_default(),
+        ),
+    }
+}
+
+/// Scaffold a project from validated `options` into `root` and list the
+/// files written.
+pub fn write_project(options: &ScaffoldOptions, root: &Path, force: bool) -> Result<String, String> {
+    let files = scaffold_project(options);
+    let written: Vec<PathBuf> = write_scaffold(root, &files, force)?;
+    let mut report = format!(
+        "Scaffolded {} into {} ({} files)",
+        options.project_name,
+        root.display(),
+        written.len()
+    );
+    for path in written {
+        let relative = path.strip_prefix(root).unwrap_or(&path);
+        report.push_str(&format!("\n  {}", relative.display()));
+    }
+    Ok(report)
+}
+
+/// Starting points offered by the new-project wizard, as (name, description).
+pub const SCAFFOLD_PRESETS: &[(&str, &str)] = &[
+    ("blank", "Only an auth collection for admin users"),
+    ("blog", "Posts, categories and media"),
+    ("website", "Pages and media, with header and footer globals"),
+    ("ecommerce", "Products, customers and orders; generate_ecommerce adds Stripe"),
+];
+
+fn field(name: &str, field_type: &str, required: bool) -> FieldOption {
+    FieldOption {
+        name: name.to_string(),
+        field_type: field_type.to_string(),
+        required: required.then_some(true),
+        ..Default::default()
+    }
+}
+
+fn collection(name: &str, fields: Vec<FieldOption>) -> CollectionOption {
+    CollectionOption {
+        name: name.to_string(),
+        fields: Some(fields),
+        ..Default::default()
+    }
+}
+
+/// Collections and globals of the preset `name`; unknown names are blank.
+pub fn preset_options(name: &str) -> (Vec<CollectionOption>, Vec<GlobalOption>) {
+    let users = CollectionOption {
+        auth: Some(true),
+        ..collection("users", vec![field("name", "text", false)])
+    };
+    let media = collection("media", vec![field("alt", "text", true)]);
+    match name {
+        "blog" => (
+            vec![
+                users,
+                collection(
+                    "posts",
+                    vec![
+                        field("title", "text", true),
+                        field("slug", "text", true),
+                        field("content", "richText", false),
+                        field("publishedAt", "date", false),
+                    ],
+                ),
+                collection("categories", vec![field("title", "text", true)]),
+                media,
+            ],
+            Vec::new(),
+        ),
+        "website" => (
+            vec![
+                users,
+                collection(
+                    "pages",
+                    vec![
+                        field("title", "text", true),
+                        field("slug", "text", true),
+                        field("content", "richText", false),
+                    ],
+                ),
+                media,
+            ],
+            ["header", "footer"]
+                .into_iter()
+                .map(|name| GlobalOption {
+                    name: name.to_string(),
+                    fields: Some(vec![field("navItems", "array", false)]),
+                    versions: None,
+                })
+                .collect(),
+        ),
+        "ecommerce" => (
+            vec![
+                users,
+                collection(
+                    "products",
+                    vec![
+                        field("name", "text", true),
+                        field("description", "textarea", false),
+                        field("priceInCents", "number", true),
+                        field("active", "checkbox", false),
+                    ],
+                ),
+                CollectionOption {
+                    auth: Some(true),
+                    ..collection("customers", vec![field("name", "text", false)])
+                },
+                collection(
+                    "orders",
+                    vec![
+                        field("totalInCents", "number", true),
+                        field("currency", "text", true),
+                        field("status", "text", true),
+                    ],
+                ),
+                media,
+            ],
+            Vec::new(),
+        ),
+        _ => (vec![users], Vec::new()),
+    }
+}
@@ -1,48 +1,326 @@
-use std::{future::ready, sync::Arc};
+use std::{
+    collections::HashMap,
+    future::ready,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
 
 use rmcp::{
     handler::server::{ServerHandler, tool::ToolRouter, wrapper::Parameters},
-    model::{PaginatedRequestParam as ListResourcesRequest, CallToolResult},
+    model::{PaginatedRequestParam as ListResourcesRequest, CallToolResult, Content, ProgressNotificationParam},
     service::{RequestContext, RoleServer},
-    tool, tool_handler, tool_router,
+    tool, tool_router,
     ErrorData,
 };
 use serde_json::{json, Value};
 
 use crate::{
-    server::ServerState,
+    logging::{level_rank, subscribe as subscribe_logs},
+    metrics,
+    connections::{self, resolve as resolve_connection, Connection},
+    paths,
+    project_runner::{run_project, stop_project, ProjectStatus, StoppedProject},
+    server::{PayloadConnection, ServerState},
+    tools::{
+        explain_config::{explain_config, ConfigExplanation},
+        health::health_summary,
+        ExplainConfigParams, HealthParams,
+    },
     payload_tools::{
         mcp::{
-            EchoParams, ValidateParams, QueryParams, SqlParams,
-            GenerateTemplateParams, GenerateCollectionParams, GenerateFieldParams,
-            ConnectPayloadParams, GetCollectionParams, ListCollectionsParams, ValidateAgainstLiveParams,
+            EchoParams, ValidateParams, ValidateOutput, validate_code, AnalyzeHookParams, AnalyzeHookOutput, analyze_hook_code, AccessMatrixParams, AccessMatrixOutput, access_matrix, validate_project_files, QueryParams, DescribeFieldTypeParams, SearchParams, SearchDocsParams, SearchDocsOutput, GetExampleParams, GetExampleOutput, get_example, SqlParams,
+            GenerateTemplateParams, ListTemplateVariablesParams, ListTemplateVariablesOutput, list_template_variables, GenerateCollectionParams, DraftCollectionParams, DraftCollectionOutput, draft_collection_code, GenerateFieldParams, GenerateEmailConfigParams, ImportFromDatabaseParams, InferSchemaParams, ImportExternalSchemaParams, WpToPayloadSchemaParams, WpExportToSeedParams, ParsePayloadTypesParams, PatchConfigParams, CheckDependenciesParams, EnvAuditParams, env_audit_for, GenerateOpenApiParams, GenerateGraphqlSdlParams, GenerateClientSdkParams, GenerateJsonSchemaParams, SchemaGraphParams, AnalyzeRelationshipDepthParams, relationship_depth, ExportContentParams, AnonymizeExportParams, AnonymizeExportOutput, anonymize_to, BackupInstanceParams, RestoreInstanceParams, I18nAuditParams, ContentAuditParams, BulkUpdateParams, audit_schema, ImportContentParams, ConvertQueryParams, ContentExportOutput, export_to, import_from, SchemaSourceParams, GenerateFormBuilderParams, GeneratePreviewConfigParams, GenerateAuditLogParams, GenerateWorkflowParams, GenerateSeoParams, GenerateRedirectsParams, GenerateSearchParams, GenerateEcommerceParams, GenerateWebhooksParams, GeneratePluginConfigParams, GenerateStorageAdapterParams, GenerateTestsParams,
+            ScaffoldProjectParams, RunProjectParams, StopProjectParams, ConnectPayloadParams, AddConnectionParams, ListConnectionsParams, GetCollectionParams, ListCollectionsParams, ValidateAgainstLiveParams,
+            AddToProjectParams, GenerateMultitenancyParams, MultitenancyOutput, ValidateProjectParams, SelfCheckParams, SelfCheckOutput, self_check_files, StartPlanningParams, SavePlanParams,
+            AddTodoParams, UpdateTodoStatusParams, GetPlanParams, ListTodosParams, PlanHistoryParams,
+            AttachResultParams, planning_result, RegisterSchemaParams, ListSchemaVersionsParams, DiffSchemaVersionsParams,
+            SchemaVersionsOutput, CheckBreakingChangesParams, breaking_change_result,
+            QueryOutput, FieldTypeOutput, SearchOutput, SqlOutput, CodeOutput, ScaffoldOutput, FileStructureOutput,
+            ConnectOutput, ConnectionOutput, ConnectionListOutput, CollectionSchemaOutput, CollectionListOutput, LiveValidationOutput,
+            ProjectValidationOutput, PlanOutput, TodoOutput, TodoListOutput, PlanHistoryOutput,
+            ArtifactsOutput, describe_field_type, generate_plugin_config,
         },
-        client::create_payload_client,
+        client::{create_payload_client, PayloadClient},
         scaffolder::{
-            scaffold_project, validate_scaffold_options, ScaffoldFile, ScaffoldFileStructure,
-            ScaffoldOptions,
+            add_to_project, scaffold_project, validate_scaffold_options,
+            write_scaffold_with_progress, ManifestEntry, ScaffoldFile, ScaffoldFileStructure, ScaffoldOptions,
         },
-        validator::validate_payload_code,
         query::{get_validation_rules_with_examples, query_validation_rules},
-        sql::execute_sql_query,
-        generator::{generate_template, TemplateType},
+        search::{search, search_docs, DEFAULT_SEARCH_LIMIT},
+        sql::execute_prepared_query,
+        completions::complete_argument,
+        generator::{generate_template_with_naming, TemplateType},
+        naming,
+        plugins::PluginConfig,
+        storage_adapters::storage_adapter_config,
+        email::{email_config, EmailConfig},
+        external_schema::{import_external_schema, ExternalSchemaImport},
+        wordpress::{wp_export_to_seed, wp_to_payload_schema, WpSeed},
+        content::ContentImport,
+        backup::{backup_instance, restore_instance, Backup, Restore},
+        db_import::{import_from_database, infer_from_samples, DatabaseImport, SampleInference},
+        i18n_audit::{i18n_audit, I18nAudit},
+        content_audit::{content_audit, ContentAudit},
+        bulk_update::{bulk_update, BulkUpdate},
+        forms::{form_builder_config, FormBuilderConfig},
+        preview::{preview_config, PreviewConfig},
+        audit_log::{audit_log_config, AuditLogConfig},
+        workflow::{workflow_config, WorkflowConfig},
+        seo::{seo_config, SeoConfig},
+        redirects::{redirects_config, RedirectsConfig},
+        site_search::{site_search_config, SiteSearchConfig},
+        ecommerce::{ecommerce_config, EcommerceConfig},
+        webhooks::{webhooks_config, WebhooksConfig},
+        multitenancy::multitenancy_files,
+        json_schema::{json_schemas, JsonSchemaOutput},
+        relationship_depth::DepthAnalysis,
+        schema_graph::{schema_graph, SchemaGraph},
+        where_query::{convert_query, ConvertedQuery},
+        openapi::{openapi_document, OpenApiOutput},
+        graphql::{graphql_sdl, GraphqlSdlOutput},
+        client_sdk::{client_sdk, ClientSdkOutput},
+        config_patch::{patch_config, PatchConfigOutput},
+        dependencies::{check_dependencies, DependencyReport},
+        env_audit::EnvAudit,
+        payload_types::{parse_payload_types, PayloadTypesSchema},
+        schema_model::{live_schema, SchemaModel},
+        prompts::{get_prompt, prompt_definitions},
+        resources::{
+            paginate, parse_live_uri, read_live_resource, read_resource, resource_definitions,
+            resource_templates, ResultStore, INLINE_RESULT_LIMIT, RESOURCES_PAGE_SIZE,
+        },
+        storage::artifacts_from_result,
+        breaking_changes::{check_breaking_changes, BreakingChangeReport},
+        schema_registry::{diff_schema_versions, register_schema, SchemaRegistration, SchemaVersionDiff},
+        testgen::generate_collection_tests,
     },
 };
 
+/// How often subscribed live schemas are re-fetched to detect changes.
+const SCHEMA_POLL_INTERVAL: Duration = Duration::from_secs(15);
+
+const TOOLS_PAGE_SIZE: usize = 25;
+
+/// Transports whose clients run on the server's machine, and so may have
+/// tools write to its disk.
+const LOCAL_TRANSPORTS: [&str; 3] = ["stdio", "unix", "pipe"];
+
 pub struct ToolBoxHandler {
     tool_router: ToolRouter<Self>,
+    state: Arc<ServerState>,
+    /// Key of this session's workspace in [`ServerState`]
+    session_id: u64,
+    /// Transport serving this session, checked against the tool policy
+    transport: &'static str,
+    /// Polling tasks for subscribed `payload://` resources, keyed by URI
+    subscriptions: Arc<Mutex<HashMap<String, tokio::task::AbortHandle>>>,
+    /// Minimum level requested through `logging/setLevel`
+    log_level: Arc<Mutex<rmcp::model::LoggingLevel>>,
+    /// Task relaying log records to this session, started on the first `setLevel`
+    log_forwarder: Mutex<Option<tokio::task::AbortHandle>>,
+    /// Oversized tool results, served as `result://` resources
+    results: ResultStore,
+}
+
+impl Drop for ToolBoxHandler {
+    fn drop(&mut self) {
+        self.state.close_session(self.session_id);
+        metrics::session_closed();
+        if let Ok(subscriptions) = self.subscriptions.lock() {
+            for task in subscriptions.values() {
+                task.abort();
+            }
+        }
+        if let Ok(Some(task)) = self.log_forwarder.lock().map(|mut f| f.take()) {
+            task.abort();
+        }
+    }
 }
 
 impl ToolBoxHandler {
-    pub fn new(_state: Arc<ServerState>) -> Self {
+    pub fn new(state: Arc<ServerState>) -> Self {
+        let session_id = state.open_session();
+        metrics::session_opened();
         Self {
             tool_router: Self::tool_router(),
+            state,
+            session_id,
+            transport: "stdio",
+            subscriptions: Arc::new(Mutex::new(HashMap::new())),
+            log_level: Arc::new(Mutex::new(rmcp::model::LoggingLevel::Info)),
+            log_forwarder: Mutex::new(None),
+            results: ResultStore::default(),
         }
     }
 
+    /// Name the transport serving this session; in-process callers keep the
+    /// default, `stdio`.
+    pub fn with_transport(mut self, transport: &'static str) -> Self {
+        self.transport = transport;
+        self.state.set_session_transport(self.session_id, transport);
+        self
+    }
+
+    pub fn session_id(&self) -> u64 {
+        self.session_id
+    }
+
     pub fn instructions() -> Option<String> {
         Some(include_str!("../docs/instructions.md").to_string())
     }
+
+    /// Client for a live instance, defaulting to the connection stored by
+    /// `connect_payload` when neither `connection` nor `connection_string`
+    /// is given.
+    fn live_client(
+        &self,
+        connection: Option<String>,
+        connection_string: Option<String>,
+        api_key: Option<String>,
+    ) -> Result<PayloadClient, String> {
+        if let Some(client) = resolve_connection(connection.as_deref(), connection_string.as_deref(), api_key.clone())? {
+            return Ok(client);
+        }
+        let connection = self
+            .state
+            .workspace(self.session_id)
+            .connection
+            .ok_or("No connection or connection_string given and no connect_payload in this session")?;
+        connection_client(&connection, api_key)
+    }
+
+    /// The given goal, or the one opened by `start_planning` in this session.
+    fn session_goal(&self, goal_id: Option<String>) -> Result<String, String> {
+        goal_id
+            .or_else(|| self.state.workspace(self.session_id).goal_id)
+            .ok_or_else(|| "No goal_id given and no start_planning in this session".to_string())
+    }
+
+    /// The given project, or the last one scaffolded in this session.
+    fn session_project(&self, project: Option<ScaffoldOptions>) -> Result<ScaffoldOptions, ErrorData> {
+        project
+            .or_else(|| self.state.workspace(self.session_id).project)
+            .ok_or_else(|| ErrorData::invalid_params("No project given and none scaffolded in this session", None))
+    }
+
+    /// The schema a tool was given, read from a live instance, or derived
+    /// from the session project; see [`SchemaSourceParams`].
+    async fn source_schema(&self, source: SchemaSourceParams) -> Result<SchemaModel, ErrorData> {
+        if let Some(schema) = source.schema {
+            return Ok(schema);
+        }
+        if let Some(files) = source.files {
+            return SchemaModel::from_sources(&files)
+                .map_err(|err| ErrorData::invalid_params(err, None));
+        }
+        if source.is_live() {
+            let client = self
+                .live_client(source.connection, source.connection_string, source.api_key)
+                .map_err(|err| ErrorData::invalid_params(err, None))?;
+            // The live client blocks on the collection endpoints.
+            return tokio::task::spawn_blocking(move || live_schema(&client))
+                .await
+                .map_err(|err| ErrorData::internal_error(err.to_string(), None))?
+                .map_err(|err| ErrorData::invalid_params(err, None));
+        }
+        Ok(SchemaModel::from(&self.session_project(source.project)?))
+    }
+
+    /// Write a scaffold under `out_dir` on a blocking thread, sending a
+    /// progress notification per file when the request has a progress token.
+    /// Writing to the server's disk is limited to local transports.
+    async fn write_with_progress(
+        &self,
+        scaffold: ScaffoldFileStructure,
+        out_dir: &str,
+        overwrite: bool,
+        ctx: &RequestContext<RoleServer>,
+    ) -> Result<Vec<ManifestEntry>, ErrorData> {
+        if !LOCAL_TRANSPORTS.contains(&self.transport) {
+            return Err(ErrorData::invalid_params(
+                format!("outDir is not available over the {} transport", self.transport),
+                None,
+            ));
+        }
+        let (sender, mut receiver) = tokio::sync::mpsc::unbounded_channel();
+        let root = std::path::PathBuf::from(out_dir);
+        let writer = tokio::task::spawn_blocking(move || {
+            write_scaffold_with_progress(&root, &scaffold, overwrite, |written, total, entry| {
+                let _ = sender.send((written, total, entry.path.clone()));
+            })
+        });
+        let token = ctx.meta.get_progress_token();
+        while let Some((written, total, path)) = receiver.recv().await {
+            if let Some(token) = &token {
+                let _ = ctx
+                    .peer
+                    .notify_progress(ProgressNotificationParam {
+                        progress_token: token.clone(),
+                        progress: written as f64,
+                        total: Some(total as f64),
+                        message: Some(path),
+                    })
+                    .await;
+            }
+        }
+        writer
+            .await
+            .map_err(|err| ErrorData::internal_error(err.to_string(), None))?
+            .map_err(|err| ErrorData::invalid_params(err, None))
+    }
+
+    /// Build a result carrying `fileStructure`. Trees larger than
+    /// [`INLINE_RESULT_LIMIT`] have their file contents replaced by
+    /// `result://` URIs, with a resource link per file.
+    fn file_structure_result(
+        &self,
+        files: ScaffoldFileStructure,
+        message: Option<String>,
+    ) -> Result<CallToolResult, ErrorData> {
+        let mut file_structure = scaffold_to_json(files);
+        let linked = file_structure.to_string().len() > INLINE_RESULT_LIMIT;
+        let links = if linked {
+            self.results
+                .link_files(&mut file_structure)
+                .map_err(|err| ErrorData::internal_error(err, None))?
+        } else {
+            Vec::new()
+        };
+
+        let mut output = json!({ "fileStructure": file_structure });
+        if let Some(message) = message {
+            output["message"] = json!(message);
+        }
+        if linked {
+            output["linked"] = json!(true);
+        }
+        let mut result = CallToolResult::structured(output);
+        result.content.extend(links.into_iter().map(Content::resource_link));
+        Ok(result)
+    }
+}
+
+/// Client for a session connection; `api_key` overrides a stored direct key.
+fn connection_client(connection: &PayloadConnection, api_key: Option<String>) -> Result<PayloadClient, String> {
+    match connection {
+        PayloadConnection::Named(name) => connections::get(name)?.client(),
+        PayloadConnection::Direct { connection_string, api_key: stored } => {
+            create_payload_client(connection_string, api_key.or_else(|| stored.clone()))
+                .map_err(|err| err.to_string())
+        }
+    }
+}
+
+fn log_scaffold(map: &ScaffoldFileStructure, prefix: &str) {
+    for (name, entry) in map {
+        let path = format!("{prefix}{name}");
+        match entry {
+            ScaffoldFile::File(content) => {
+                tracing::info!("Scaffolded {path} ({} bytes)", content.len());
+            }
+            ScaffoldFile::Directory(dir) => log_scaffold(dir, &format!("{path}/")),
+        }
+    }
 }
 
 fn scaffold_to_json(map: ScaffoldFileStructure) -> Value {
@@ -62,44 +340,216 @@ fn scaffold_to_json(map: ScaffoldFileStructure) -> Value {
 
 #[tool_router]
 impl ToolBoxHandler {
-    #[tool(name = "echo", description = "Echo a message back to the caller")]
+    #[tool(
+        name = "echo",
+        description = "Echo a message back to the caller",
+        annotations(read_only_hint = true, destructive_hint = false, idempotent_hint = true, open_world_hint = false)
+    )]
     fn echo(&self, Parameters(params): Parameters<EchoParams>) -> String {
         format!("Tool echo: {}", params.message)
     }
 
-    #[tool(name = "validate", description = "Validate Payload CMS code")]
+    #[tool(
+        name = "health",
+        description = "Report server status, active transports and open sessions",
+        annotations(read_only_hint = true, destructive_hint = false, idempotent_hint = true, open_world_hint = false)
+    )]
+    fn health(&self, Parameters(params): Parameters<HealthParams>) -> String {
+        health_summary(&self.state, params.verbose)
+    }
+
+    #[tool(
+        name = "explain_config",
+        description = "Show each server setting's effective value and the layer that supplied it: default, settings file, environment variable or command-line flag",
+        annotations(read_only_hint = true, destructive_hint = false, idempotent_hint = true, open_world_hint = false),
+        output_schema = rmcp::handler::server::tool::cached_schema_for_type::<ConfigExplanation>()
+    )]
+    fn explain_config(&self, Parameters(params): Parameters<ExplainConfigParams>) -> Result<CallToolResult, ErrorData> {
+        let output = explain_config(&self.state, &params).map_err(|err| ErrorData::invalid_params(err, None))?;
+        Ok(CallToolResult::structured(json!(output)))
+    }
+
+    #[tool(
+        name = "validate",
+        description = "Validate Payload CMS code",
+        annotations(read_only_hint = true, destructive_hint = false, idempotent_hint = true, open_world_hint = false),
+        output_schema = rmcp::handler::server::tool::cached_schema_for_type::<ValidateOutput>()
+    )]
     fn validate(&self, Parameters(params): Parameters<ValidateParams>) -> Result<CallToolResult, ErrorData> {
-        let result = validate_payload_code(&params.code, params.file_type);
-        Ok(CallToolResult::structured(json!(result)))
+        validate_code(&params)
+            .map(|output| CallToolResult::structured(json!(output)))
+            .map_err(|err| ErrorData::invalid_params(err, None))
+    }
+
+    #[tool(
+        name = "access_matrix",
+        description = "Compute the operation x role x collection matrix of effective permissions (allow, deny or query-constrained) from config sources or a live instance, with a markdown or ASCII table",
+        annotations(read_only_hint = true, destructive_hint = false, idempotent_hint = true, open_world_hint = true),
+        output_schema = rmcp::handler::server::tool::cached_schema_for_type::<AccessMatrixOutput>()
+    )]
+    async fn access_matrix(&self, Parameters(params): Parameters<AccessMatrixParams>) -> Result<CallToolResult, ErrorData> {
+        let client = if params.is_live() || params.files.is_none() {
+            Some(
+                self.live_client(params.connection.clone(), params.connection_string.clone(), params.api_key.clone())
+                    .map_err(|err| ErrorData::invalid_params(err, None))?,
+            )
+        } else {
+            None
+        };
+        // The live client blocks on `/api/access`.
+        let output = tokio::task::spawn_blocking(move || access_matrix(&params, client.as_ref()))
+            .await
+            .map_err(|err| ErrorData::internal_error(err.to_string(), None))?
+            .map_err(|err| ErrorData::invalid_params(err, None))?;
+        Ok(CallToolResult::structured(json!(output)))
     }
 
-    #[tool(name = "query", description = "Query validation rules")]
+    #[tool(
+        name = "analyze_hook",
+        description = "Check a TypeScript hook for missing returns, writes that re-trigger the hook, Local API calls without req, and access control bypasses",
+        annotations(read_only_hint = true, destructive_hint = false, idempotent_hint = true, open_world_hint = false),
+        output_schema = rmcp::handler::server::tool::cached_schema_for_type::<AnalyzeHookOutput>()
+    )]
+    fn analyze_hook(&self, Parameters(params): Parameters<AnalyzeHookParams>) -> Result<CallToolResult, ErrorData> {
+        let output = analyze_hook_code(&params).map_err(|err| ErrorData::invalid_params(err, None))?;
+        Ok(CallToolResult::structured(json!(output)))
+    }
+
+    #[tool(
+        name = "query",
+        description = "Query validation rules",
+        annotations(read_only_hint = true, destructive_hint = false, idempotent_hint = true, open_world_hint = false),
+        output_schema = rmcp::handler::server::tool::cached_schema_for_type::<QueryOutput>()
+    )]
     fn query(&self, Parameters(params): Parameters<QueryParams>) -> Result<CallToolResult, ErrorData> {
         let rules = if params.query.trim().is_empty() {
             get_validation_rules_with_examples(None, params.file_type)
         } else {
             query_validation_rules(&params.query, params.file_type)
         };
-        Ok(CallToolResult::structured(json!({ "rules": rules })))
+        match rules {
+            Ok(rules) => Ok(CallToolResult::structured(json!({ "rules": rules }))),
+            Err(err) => Err(ErrorData::internal_error(err, None)),
+        }
     }
 
-    #[tool(name = "mcp_query", description = "Execute SQL-like queries")]
+    #[tool(
+        name = "describe_field_type",
+        description = "Describe a field type: its options, required options, admin options, storage and constraints",
+        annotations(read_only_hint = true, destructive_hint = false, idempotent_hint = true, open_world_hint = false),
+        output_schema = rmcp::handler::server::tool::cached_schema_for_type::<FieldTypeOutput>()
+    )]
+    fn describe_field_type(&self, Parameters(params): Parameters<DescribeFieldTypeParams>) -> Result<CallToolResult, ErrorData> {
+        match describe_field_type(&params.field_type) {
+            Ok(output) => Ok(CallToolResult::structured(json!(output))),
+            Err(err) => Err(ErrorData::invalid_params(err, None)),
+        }
+    }
+
+    #[tool(
+        name = "search",
+        description = "Ranked full-text search over validation rules, instructions, templates and best practices",
+        annotations(read_only_hint = true, destructive_hint = false, idempotent_hint = true, open_world_hint = false),
+        output_schema = rmcp::handler::server::tool::cached_schema_for_type::<SearchOutput>()
+    )]
+    fn search(&self, Parameters(params): Parameters<SearchParams>) -> Result<CallToolResult, ErrorData> {
+        let results = search(
+            &params.query,
+            params.kinds.as_deref(),
+            params.limit.unwrap_or(DEFAULT_SEARCH_LIMIT),
+        );
+        Ok(CallToolResult::structured(json!({ "results": results })))
+    }
+
+    #[tool(
+        name = "search_docs",
+        description = "Search the bundled Payload best-practice guides served as docs://payload/{topic}",
+        annotations(read_only_hint = true, destructive_hint = false, idempotent_hint = true, open_world_hint = false),
+        output_schema = rmcp::handler::server::tool::cached_schema_for_type::<SearchDocsOutput>()
+    )]
+    fn search_docs(&self, Parameters(params): Parameters<SearchDocsParams>) -> Result<CallToolResult, ErrorData> {
+        let results = search_docs(
+            &params.query,
+            params.topic.as_deref(),
+            params.limit.unwrap_or(DEFAULT_SEARCH_LIMIT),
+        )
+        .map_err(|err| ErrorData::invalid_params(err, None))?;
+        Ok(CallToolResult::structured(json!({ "results": results })))
+    }
+
+    #[tool(
+        name = "get_example",
+        description = "Complete, validated reference configurations (blog, saas, marketplace, docs) searchable by feature tags, returned as ready-to-adapt collection and config code",
+        annotations(read_only_hint = true, destructive_hint = false, idempotent_hint = true, open_world_hint = false),
+        output_schema = rmcp::handler::server::tool::cached_schema_for_type::<GetExampleOutput>()
+    )]
+    fn get_example(&self, Parameters(params): Parameters<GetExampleParams>) -> Result<CallToolResult, ErrorData> {
+        match get_example(&params) {
+            Ok(output) => Ok(CallToolResult::structured(json!(output))),
+            Err(err) => Err(ErrorData::invalid_params(err, None)),
+        }
+    }
+
+    #[tool(
+        name = "mcp_query",
+        description = "Execute SQL-like queries against validation rules or live Payload collections",
+        annotations(read_only_hint = true, destructive_hint = false, idempotent_hint = true, open_world_hint = true),
+        output_schema = rmcp::handler::server::tool::cached_schema_for_type::<SqlOutput>()
+    )]
     fn mcp_query(&self, Parameters(params): Parameters<SqlParams>) -> Result<CallToolResult, ErrorData> {
-        match execute_sql_query(&params.sql) {
+        let client = resolve_connection(
+            params.connection.as_deref(),
+            params.connection_string.as_deref(),
+            params.api_key.clone(),
+        )
+        .map_err(|err| ErrorData::invalid_params(err, None))?;
+        match execute_prepared_query(&params.sql, params.params.as_ref(), client.as_ref()) {
             Ok(results) => Ok(CallToolResult::structured(json!({ "results": results }))),
             Err(err) => Err(ErrorData::internal_error(err, None)),
         }
     }
 
-    #[tool(name = "generate_template", description = "Generate Payload CMS code templates")]
+    #[tool(
+        name = "convert_query",
+        description = "Translate a Payload where query between JSON, the REST query string and Postgres SQL",
+        annotations(read_only_hint = true, destructive_hint = false, idempotent_hint = true, open_world_hint = false),
+        output_schema = rmcp::handler::server::tool::cached_schema_for_type::<ConvertedQuery>()
+    )]
+    fn convert_query(&self, Parameters(params): Parameters<ConvertQueryParams>) -> Result<CallToolResult, ErrorData> {
+        let converted = convert_query(&params.query, params.from).map_err(|err| ErrorData::invalid_params(err, None))?;
+        Ok(CallToolResult::structured(json!(converted)))
+    }
+
+    #[tool(
+        name = "generate_template",
+        description = "Generate Payload CMS code templates",
+        annotations(read_only_hint = true, destructive_hint = false, idempotent_hint = true, open_world_hint = false),
+        output_schema = rmcp::handler::server::tool::cached_schema_for_type::<CodeOutput>()
+    )]
     fn generate_template(&self, Parameters(params): Parameters<GenerateTemplateParams>) -> Result<CallToolResult, ErrorData> {
-        match generate_template(params.template_type, &params.options) {
+        let naming = naming::profile(params.naming_profile.as_deref()).map_err(|err| ErrorData::invalid_params(err, None))?;
+        match generate_template_with_naming(params.template_type, &params.options, &naming) {
             Ok(code) => Ok(CallToolResult::structured(json!({ "code": code }))),
             Err(err) => Err(ErrorData::internal_error(err, None)),
         }
     }
 
-    #[tool(name = "generate_collection", description = "Generate a Payload CMS collection template")]
+    #[tool(
+        name = "list_template_variables",
+        description = "Describe the variables available to user template overrides per template type, and which overrides are active",
+        annotations(read_only_hint = true, destructive_hint = false, idempotent_hint = true, open_world_hint = false),
+        output_schema = rmcp::handler::server::tool::cached_schema_for_type::<ListTemplateVariablesOutput>()
+    )]
+    fn list_template_variables(&self, Parameters(params): Parameters<ListTemplateVariablesParams>) -> Result<CallToolResult, ErrorData> {
+        Ok(CallToolResult::structured(json!(list_template_variables(&params))))
+    }
+
+    #[tool(
+        name = "generate_collection",
+        description = "Generate a Payload CMS collection template",
+        annotations(read_only_hint = true, destructive_hint = false, idempotent_hint = true, open_world_hint = false),
+        output_schema = rmcp::handler::server::tool::cached_schema_for_type::<CodeOutput>()
+    )]
     fn generate_collection(&self, Parameters(params): Parameters<GenerateCollectionParams>) -> Result<CallToolResult, ErrorData> {
         let mut options = serde_json::Map::new();
         options.insert("slug".into(), json!(params.slug));
@@ -110,14 +560,32 @@ impl ToolBoxHandler {
         if let Some(hooks) = params.hooks { options.insert("hooks".into(), json!(hooks)); }
         if let Some(access) = params.access { options.insert("access".into(), json!(access)); }
         if let Some(versions) = params.versions { options.insert("versions".into(), json!(versions)); }
+        if let Some(soft_delete) = params.soft_delete { options.insert("softDelete".into(), json!(soft_delete)); }
 
-        match generate_template(TemplateType::Collection, &Value::Object(options)) {
+        let naming = naming::profile(params.naming_profile.as_deref()).map_err(|err| ErrorData::invalid_params(err, None))?;
+        match generate_template_with_naming(TemplateType::Collection, &Value::Object(options), &naming) {
             Ok(code) => Ok(CallToolResult::structured(json!({ "code": code }))),
             Err(err) => Err(ErrorData::internal_error(err, None)),
         }
     }
 
-    #[tool(name = "generate_field", description = "Generate a Payload CMS field template")]
+    #[tool(
+        name = "draft_collection",
+        description = "Draft a collection from a plain-English description of its content, listing each guess for review",
+        annotations(read_only_hint = true, destructive_hint = false, idempotent_hint = true, open_world_hint = false),
+        output_schema = rmcp::handler::server::tool::cached_schema_for_type::<DraftCollectionOutput>()
+    )]
+    fn draft_collection(&self, Parameters(params): Parameters<DraftCollectionParams>) -> Result<CallToolResult, ErrorData> {
+        let output = draft_collection_code(&params).map_err(|err| ErrorData::invalid_params(err, None))?;
+        Ok(CallToolResult::structured(json!(output)))
+    }
+
+    #[tool(
+        name = "generate_field",
+        description = "Generate a Payload CMS field template",
+        annotations(read_only_hint = true, destructive_hint = false, idempotent_hint = true, open_world_hint = false),
+        output_schema = rmcp::handler::server::tool::cached_schema_for_type::<CodeOutput>()
+    )]
     fn generate_field(&self, Parameters(params): Parameters<GenerateFieldParams>) -> Result<CallToolResult, ErrorData> {
         let mut options = serde_json::Map::new();
         options.insert("name".into(), json!(params.name));
@@ -130,35 +598,819 @@ impl ToolBoxHandler {
         if let Some(validation) = params.validation { options.insert("validation".into(), json!(validation)); }
         if let Some(default_value) = params.default_value { options.insert("defaultValue".into(), default_value); }
 
-        match generate_template(TemplateType::Field, &Value::Object(options)) {
+        let naming = naming::profile(params.naming_profile.as_deref()).map_err(|err| ErrorData::invalid_params(err, None))?;
+        match generate_template_with_naming(TemplateType::Field, &Value::Object(options), &naming) {
             Ok(code) => Ok(CallToolResult::structured(json!({ "code": code }))),
             Err(err) => Err(ErrorData::internal_error(err, None)),
         }
     }
 
-    #[tool(name = "scaffold_project", description = "Scaffold a complete Payload CMS 3 project structure")]
-    fn scaffold_project(&self, Parameters(params): Parameters<ScaffoldOptions>) -> Result<CallToolResult, ErrorData> {
-        if let Err(errors) = validate_scaffold_options(&params) {
+    #[tool(
+        name = "generate_plugin_config",
+        description = "Generate imports, plugin config, dependencies and env vars for an official Payload plugin",
+        annotations(read_only_hint = true, destructive_hint = false, idempotent_hint = true, open_world_hint = false),
+        output_schema = rmcp::handler::server::tool::cached_schema_for_type::<PluginConfig>()
+    )]
+    fn generate_plugin_config(&self, Parameters(params): Parameters<GeneratePluginConfigParams>) -> Result<CallToolResult, ErrorData> {
+        match generate_plugin_config(&params) {
+            Ok(config) => Ok(CallToolResult::structured(json!(config))),
+            Err(err) => Err(ErrorData::invalid_params(err, None)),
+        }
+    }
+
+    #[tool(
+        name = "generate_storage_adapter",
+        description = "Generate the storage adapter config (S3, R2, GCS or Azure) for upload collections, with its dependencies and env vars",
+        annotations(read_only_hint = true, destructive_hint = false, idempotent_hint = true, open_world_hint = false),
+        output_schema = rmcp::handler::server::tool::cached_schema_for_type::<PluginConfig>()
+    )]
+    fn generate_storage_adapter(&self, Parameters(params): Parameters<GenerateStorageAdapterParams>) -> Result<CallToolResult, ErrorData> {
+        match storage_adapter_config(&params.options, "") {
+            Ok(config) => Ok(CallToolResult::structured(json!(config))),
+            Err(err) => Err(ErrorData::invalid_params(err, None)),
+        }
+    }
+
+    #[tool(
+        name = "generate_email_config",
+        description = "Generate the email adapter config (nodemailer or Resend) and auth options with custom verification and forgot-password emails",
+        annotations(read_only_hint = true, destructive_hint = false, idempotent_hint = true, open_world_hint = false),
+        output_schema = rmcp::handler::server::tool::cached_schema_for_type::<EmailConfig>()
+    )]
+    fn generate_email_config(&self, Parameters(params): Parameters<GenerateEmailConfigParams>) -> Result<CallToolResult, ErrorData> {
+        match email_config(&params.options) {
+            Ok(config) => Ok(CallToolResult::structured(json!(config))),
+            Err(err) => Err(ErrorData::invalid_params(err, None)),
+        }
+    }
+
+    #[tool(
+        name = "generate_form_builder",
+        description = "Generate the form-builder plugin config, a React form renderer per field block and a submission endpoint",
+        annotations(read_only_hint = true, destructive_hint = false, idempotent_hint = true, open_world_hint = false),
+        output_schema = rmcp::handler::server::tool::cached_schema_for_type::<FormBuilderConfig>()
+    )]
+    fn generate_form_builder(&self, Parameters(params): Parameters<GenerateFormBuilderParams>) -> Result<CallToolResult, ErrorData> {
+        match form_builder_config(&params.options) {
+            Ok(config) => Ok(CallToolResult::structured(json!(config))),
+            Err(err) => Err(ErrorData::invalid_params(err, None)),
+        }
+    }
+
+    #[tool(
+        name = "generate_preview_config",
+        description = "Generate admin live preview config, draft preview route handlers and the frontend refresh component for selected collections",
+        annotations(read_only_hint = true, destructive_hint = false, idempotent_hint = true, open_world_hint = false),
+        output_schema = rmcp::handler::server::tool::cached_schema_for_type::<PreviewConfig>()
+    )]
+    fn generate_preview_config(&self, Parameters(params): Parameters<GeneratePreviewConfigParams>) -> Result<CallToolResult, ErrorData> {
+        match preview_config(&params.options) {
+            Ok(config) => Ok(CallToolResult::structured(json!(config))),
+            Err(err) => Err(ErrorData::invalid_params(err, None)),
+        }
+    }
+
+    #[tool(
+        name = "generate_audit_log",
+        description = "Generate an audit log collection and afterChange/afterDelete hooks recording who changed what in selected collections, with retention",
+        annotations(read_only_hint = true, destructive_hint = false, idempotent_hint = true, open_world_hint = false),
+        output_schema = rmcp::handler::server::tool::cached_schema_for_type::<AuditLogConfig>()
+    )]
+    fn generate_audit_log(&self, Parameters(params): Parameters<GenerateAuditLogParams>) -> Result<CallToolResult, ErrorData> {
+        match audit_log_config(&params.options) {
+            Ok(config) => Ok(CallToolResult::structured(json!(config))),
+            Err(err) => Err(ErrorData::invalid_params(err, None)),
+        }
+    }
+
+    #[tool(
+        name = "generate_workflow",
+        description = "Generate an approval workflow for a collection: status field, transition validation with role checks and email notifications",
+        annotations(read_only_hint = true, destructive_hint = false, idempotent_hint = true, open_world_hint = false),
+        output_schema = rmcp::handler::server::tool::cached_schema_for_type::<WorkflowConfig>()
+    )]
+    fn generate_workflow(&self, Parameters(params): Parameters<GenerateWorkflowParams>) -> Result<CallToolResult, ErrorData> {
+        match workflow_config(&params.options) {
+            Ok(config) => Ok(CallToolResult::structured(json!(config))),
+            Err(err) => Err(ErrorData::invalid_params(err, None)),
+        }
+    }
+
+    #[tool(
+        name = "generate_seo",
+        description = "Generate SEO meta fields (seo plugin or a field group), sitemap and robots route handlers and a Next.js metadata helper for selected collections",
+        annotations(read_only_hint = true, destructive_hint = false, idempotent_hint = true, open_world_hint = false),
+        output_schema = rmcp::handler::server::tool::cached_schema_for_type::<SeoConfig>()
+    )]
+    fn generate_seo(&self, Parameters(params): Parameters<GenerateSeoParams>) -> Result<CallToolResult, ErrorData> {
+        match seo_config(&params.options) {
+            Ok(config) => Ok(CallToolResult::structured(json!(config))),
+            Err(err) => Err(ErrorData::invalid_params(err, None)),
+        }
+    }
+
+    #[tool(
+        name = "generate_redirects",
+        description = "Generate redirects (plugin or collection), Next.js middleware applying them and optional 404 hit tracking",
+        annotations(read_only_hint = true, destructive_hint = false, idempotent_hint = true, open_world_hint = false),
+        output_schema = rmcp::handler::server::tool::cached_schema_for_type::<RedirectsConfig>()
+    )]
+    fn generate_redirects(&self, Parameters(params): Parameters<GenerateRedirectsParams>) -> Result<CallToolResult, ErrorData> {
+        match redirects_config(&params.options) {
+            Ok(config) => Ok(CallToolResult::structured(json!(config))),
+            Err(err) => Err(ErrorData::invalid_params(err, None)),
+        }
+    }
+
+    #[tool(
+        name = "generate_search",
+        description = "Generate site search on the search plugin, Algolia or Meilisearch, with index sync hooks and a typed search endpoint",
+        annotations(read_only_hint = true, destructive_hint = false, idempotent_hint = true, open_world_hint = false),
+        output_schema = rmcp::handler::server::tool::cached_schema_for_type::<SiteSearchConfig>()
+    )]
+    fn generate_search(&self, Parameters(params): Parameters<GenerateSearchParams>) -> Result<CallToolResult, ErrorData> {
+        match site_search_config(&params.options) {
+            Ok(config) => Ok(CallToolResult::structured(json!(config))),
+            Err(err) => Err(ErrorData::invalid_params(err, None)),
+        }
+    }
+
+    #[tool(
+        name = "generate_ecommerce",
+        description = "Generate a Stripe store: products, customers and orders collections, price sync, a checkout endpoint and webhook handlers",
+        annotations(read_only_hint = true, destructive_hint = false, idempotent_hint = true, open_world_hint = false),
+        output_schema = rmcp::handler::server::tool::cached_schema_for_type::<EcommerceConfig>()
+    )]
+    fn generate_ecommerce(&self, Parameters(params): Parameters<GenerateEcommerceParams>) -> Result<CallToolResult, ErrorData> {
+        match ecommerce_config(&params.options) {
+            Ok(config) => Ok(CallToolResult::structured(json!(config))),
+            Err(err) => Err(ErrorData::invalid_params(err, None)),
+        }
+    }
+
+    #[tool(
+        name = "generate_webhooks",
+        description = "Generate outgoing webhooks: a webhooks collection, hooks queueing signed deliveries with retries, and a delivery log",
+        annotations(read_only_hint = true, destructive_hint = false, idempotent_hint = true, open_world_hint = false),
+        output_schema = rmcp::handler::server::tool::cached_schema_for_type::<WebhooksConfig>()
+    )]
+    fn generate_webhooks(&self, Parameters(params): Parameters<GenerateWebhooksParams>) -> Result<CallToolResult, ErrorData> {
+        match webhooks_config(&params.options) {
+            Ok(config) => Ok(CallToolResult::structured(json!(config))),
+            Err(err) => Err(ErrorData::invalid_params(err, None)),
+        }
+    }
+
+    #[tool(
+        name = "scaffold_project",
+        description = "Scaffold a complete Payload CMS 3 project structure",
+        annotations(read_only_hint = false, destructive_hint = true, idempotent_hint = true, open_world_hint = false),
+        output_schema = rmcp::handler::server::tool::cached_schema_for_type::<ScaffoldOutput>()
+    )]
+    async fn scaffold_project(
+        &self,
+        Parameters(params): Parameters<ScaffoldProjectParams>,
+        ctx: RequestContext<RoleServer>,
+    ) -> Result<CallToolResult, ErrorData> {
+        let ScaffoldProjectParams { options, out_dir, overwrite } = params;
+        if let Err(errors) = validate_scaffold_options(&options) {
+            return Err(ErrorData::invalid_params("Invalid scaffold options", Some(json!({ "errors": errors }))));
+        }
+
+        let scaffold = scaffold_project(&options);
+        log_scaffold(&scaffold, "");
+        let project_name = options.project_name.clone();
+        self.state
+            .update_workspace(self.session_id, |workspace| workspace.project = Some(options));
+        match out_dir {
+            Some(out_dir) => {
+                let manifest = self
+                    .write_with_progress(scaffold, &out_dir, overwrite.unwrap_or(false), &ctx)
+                    .await?;
+                Ok(CallToolResult::structured(json!({
+                    "message": format!("Wrote Payload CMS project {project_name} to {out_dir} ({} files)", manifest.len()),
+                    "manifest": manifest
+                })))
+            }
+            None => {
+                let message = format!("Successfully scaffolded Payload CMS project: {project_name}");
+                self.file_structure_result(scaffold, Some(message))
+            }
+        }
+    }
+
+    #[tool(
+        name = "add_to_project",
+        description = "Add collections, globals, blocks or plugins to a project and re-scaffold it",
+        annotations(read_only_hint = true, destructive_hint = false, idempotent_hint = true, open_world_hint = false),
+        output_schema = rmcp::handler::server::tool::cached_schema_for_type::<ScaffoldOutput>()
+    )]
+    fn add_to_project(&self, Parameters(params): Parameters<AddToProjectParams>) -> Result<CallToolResult, ErrorData> {
+        let mut project = self.session_project(params.project)?;
+        add_to_project(&mut project, params.additions);
+        if let Err(errors) = validate_scaffold_options(&project) {
+            return Err(ErrorData::invalid_params("Invalid scaffold options", Some(json!({ "errors": errors }))));
+        }
+
+        let scaffold = scaffold_project(&project);
+        log_scaffold(&scaffold, "");
+        let message = format!("Updated Payload CMS project: {}", project.project_name);
+        self.state
+            .update_workspace(self.session_id, |workspace| workspace.project = Some(project));
+        self.file_structure_result(scaffold, Some(message))
+    }
+
+    #[tool(
+        name = "run_project",
+        description = "Install a scaffolded project and run its dev server, returning the admin URL once ready",
+        annotations(read_only_hint = false, destructive_hint = false, idempotent_hint = true, open_world_hint = true),
+        output_schema = rmcp::handler::server::tool::cached_schema_for_type::<ProjectStatus>()
+    )]
+    async fn run_project(&self, Parameters(params): Parameters<RunProjectParams>) -> Result<CallToolResult, ErrorData> {
+        if !LOCAL_TRANSPORTS.contains(&self.transport) {
+            return Err(ErrorData::invalid_params(
+                format!("run_project is not available over the {} transport", self.transport),
+                None,
+            ));
+        }
+        let skip_install = !params.install.unwrap_or(true);
+        match run_project(&params.path, skip_install, params.port, params.timeout_seconds).await {
+            Ok(status) => Ok(CallToolResult::structured(json!(status))),
+            Err(err) => Err(ErrorData::invalid_params(err, None)),
+        }
+    }
+
+    #[tool(
+        name = "stop_project",
+        description = "Stop the dev server started by run_project",
+        annotations(read_only_hint = false, destructive_hint = true, idempotent_hint = true, open_world_hint = false),
+        output_schema = rmcp::handler::server::tool::cached_schema_for_type::<StoppedProject>()
+    )]
+    async fn stop_project(&self, Parameters(params): Parameters<StopProjectParams>) -> Result<CallToolResult, ErrorData> {
+        if !LOCAL_TRANSPORTS.contains(&self.transport) {
+            return Err(ErrorData::invalid_params(
+                format!("stop_project is not available over the {} transport", self.transport),
+                None,
+            ));
+        }
+        match stop_project(&params.path).await {
+            Ok(stopped) => Ok(CallToolResult::structured(json!(stopped))),
+            Err(err) => Err(ErrorData::invalid_params(err, None)),
+        }
+    }
+
+    #[tool(
+        name = "generate_multitenancy",
+        description = "Generate a tenants collection, tenant fields, tenant-scoped access and an afterLogin hook, standalone or patched into a project",
+        annotations(read_only_hint = true, destructive_hint = false, idempotent_hint = true, open_world_hint = false),
+        output_schema = rmcp::handler::server::tool::cached_schema_for_type::<MultitenancyOutput>()
+    )]
+    fn generate_multitenancy(&self, Parameters(params): Parameters<GenerateMultitenancyParams>) -> Result<CallToolResult, ErrorData> {
+        let GenerateMultitenancyParams { options, project, patch } = params;
+        if project.is_none() && !patch.unwrap_or(false) {
+            return match multitenancy_files(&options) {
+                Ok(files) => Ok(CallToolResult::structured(json!({ "files": files }))),
+                Err(err) => Err(ErrorData::invalid_params(err, None)),
+            };
+        }
+
+        let mut project = self.session_project(project)?;
+        project.multitenancy = Some(options);
+        if let Err(errors) = validate_scaffold_options(&project) {
             return Err(ErrorData::invalid_params("Invalid scaffold options", Some(json!({ "errors": errors }))));
         }
 
-        let scaffold = scaffold_project(&params);
-        let file_structure = scaffold_to_json(scaffold);
-        Ok(CallToolResult::structured(json!({
-            "message": format!("Successfully scaffolded Payload CMS project: {}", params.project_name),
-            "fileStructure": file_structure
-        })))
+        let scaffold = scaffold_project(&project);
+        log_scaffold(&scaffold, "");
+        let message = format!("Added multi-tenancy to Payload CMS project: {}", project.project_name);
+        self.state
+            .update_workspace(self.session_id, |workspace| workspace.project = Some(project));
+        self.file_structure_result(scaffold, Some(message))
+    }
+
+    #[tool(
+        name = "validate_project",
+        description = "Validate every config, collection, global and block file of a scaffolded project",
+        annotations(read_only_hint = true, destructive_hint = false, idempotent_hint = true, open_world_hint = false),
+        output_schema = rmcp::handler::server::tool::cached_schema_for_type::<ProjectValidationOutput>()
+    )]
+    fn validate_project(&self, Parameters(params): Parameters<ValidateProjectParams>) -> Result<CallToolResult, ErrorData> {
+        let project = self.session_project(params.project)?;
+        let output = validate_project_files(&project, self.state.limits.validation_jobs, params.format);
+        Ok(CallToolResult::structured(json!(output)))
     }
 
-    #[tool(name = "connect_payload", description = "Connect to a live Payload CMS instance and test the connection")]
+    #[tool(
+        name = "self_check",
+        description = "Validate the code this server generates: every template, field type, scaffold preset and reference example",
+        annotations(read_only_hint = true, destructive_hint = false, idempotent_hint = true, open_world_hint = false),
+        output_schema = rmcp::handler::server::tool::cached_schema_for_type::<SelfCheckOutput>()
+    )]
+    fn self_check(&self, Parameters(params): Parameters<SelfCheckParams>) -> Result<CallToolResult, ErrorData> {
+        let output = self_check_files(&params, self.state.limits.validation_jobs);
+        Ok(CallToolResult::structured(json!(output)))
+    }
+
+    #[tool(
+        name = "generate_tests",
+        description = "Generate Vitest integration-test skeletons for collections",
+        annotations(read_only_hint = true, destructive_hint = false, idempotent_hint = true, open_world_hint = false),
+        output_schema = rmcp::handler::server::tool::cached_schema_for_type::<FileStructureOutput>()
+    )]
+    fn generate_tests(&self, Parameters(params): Parameters<GenerateTestsParams>) -> Result<CallToolResult, ErrorData> {
+        match generate_collection_tests(
+            &params.collections,
+            &params.roles.unwrap_or_default(),
+            params.database.as_deref().unwrap_or("mongodb"),
+            params.hooks.unwrap_or(true),
+            params.access.unwrap_or(true),
+        ) {
+            Ok(files) => self.file_structure_result(files, None),
+            Err(err) => Err(ErrorData::invalid_params(err, None)),
+        }
+    }
+
+    #[tool(
+        name = "import_from_database",
+        description = "Propose Payload collections from an existing MongoDB database or Postgres schema",
+        annotations(read_only_hint = true, destructive_hint = false, idempotent_hint = true, open_world_hint = true),
+        output_schema = rmcp::handler::server::tool::cached_schema_for_type::<DatabaseImport>()
+    )]
+    async fn import_from_database(&self, Parameters(params): Parameters<ImportFromDatabaseParams>) -> Result<CallToolResult, ErrorData> {
+        // Connecting runs psql or mongosh on the server's machine.
+        if params.options.snapshot.is_none() && !LOCAL_TRANSPORTS.contains(&self.transport) {
+            return Err(ErrorData::invalid_params(
+                format!("connectionString is not available over the {} transport", self.transport),
+                None,
+            ));
+        }
+        // psql and mongosh block until the database answers.
+        let import = tokio::task::spawn_blocking(move || import_from_database(&params.options))
+            .await
+            .map_err(|err| ErrorData::internal_error(err.to_string(), None))?;
+        match import {
+            Ok(import) => Ok(CallToolResult::structured(json!(import))),
+            Err(err) => Err(ErrorData::invalid_params(err, None)),
+        }
+    }
+
+    #[tool(
+        name = "infer_schema",
+        description = "Infer a Payload collection from sample JSON documents, such as an export from another CMS",
+        annotations(read_only_hint = true, destructive_hint = false, idempotent_hint = true, open_world_hint = false),
+        output_schema = rmcp::handler::server::tool::cached_schema_for_type::<SampleInference>()
+    )]
+    async fn infer_schema(&self, Parameters(params): Parameters<InferSchemaParams>) -> Result<CallToolResult, ErrorData> {
+        match infer_from_samples(&params.options) {
+            Ok(inference) => Ok(CallToolResult::structured(json!(inference))),
+            Err(err) => Err(ErrorData::invalid_params(err, None)),
+        }
+    }
+
+    #[tool(
+        name = "import_external_schema",
+        description = "Convert a Contentful, Strapi or Sanity content model into Payload collections, globals and blocks",
+        annotations(read_only_hint = true, destructive_hint = false, idempotent_hint = true, open_world_hint = false),
+        output_schema = rmcp::handler::server::tool::cached_schema_for_type::<ExternalSchemaImport>()
+    )]
+    async fn import_external_schema(&self, Parameters(params): Parameters<ImportExternalSchemaParams>) -> Result<CallToolResult, ErrorData> {
+        match import_external_schema(&params.options) {
+            Ok(import) => Ok(CallToolResult::structured(json!(import))),
+            Err(err) => Err(ErrorData::invalid_params(err, None)),
+        }
+    }
+
+    #[tool(
+        name = "wp_to_payload_schema",
+        description = "Map WordPress post types, taxonomies and ACF field groups to Payload collections and globals",
+        annotations(read_only_hint = true, destructive_hint = false, idempotent_hint = true, open_world_hint = false),
+        output_schema = rmcp::handler::server::tool::cached_schema_for_type::<ExternalSchemaImport>()
+    )]
+    async fn wp_to_payload_schema(&self, Parameters(params): Parameters<WpToPayloadSchemaParams>) -> Result<CallToolResult, ErrorData> {
+        match wp_to_payload_schema(&params.options) {
+            Ok(import) => Ok(CallToolResult::structured(json!(import))),
+            Err(err) => Err(ErrorData::invalid_params(err, None)),
+        }
+    }
+
+    #[tool(
+        name = "wp_export_to_seed",
+        description = "Turn a WordPress WXR export into a Local API seed script for the collections of wp_to_payload_schema",
+        annotations(read_only_hint = true, destructive_hint = false, idempotent_hint = true, open_world_hint = false),
+        output_schema = rmcp::handler::server::tool::cached_schema_for_type::<WpSeed>()
+    )]
+    async fn wp_export_to_seed(&self, Parameters(params): Parameters<WpExportToSeedParams>) -> Result<CallToolResult, ErrorData> {
+        if params.path.is_some() && !LOCAL_TRANSPORTS.contains(&self.transport) {
+            return Err(ErrorData::invalid_params(
+                format!("path is not available over the {} transport", self.transport),
+                None,
+            ));
+        }
+        match params
+            .source()
+            .and_then(|export| wp_export_to_seed(&export, &params.options))
+        {
+            Ok(seed) => Ok(CallToolResult::structured(json!(seed))),
+            Err(err) => Err(ErrorData::invalid_params(err, None)),
+        }
+    }
+
+    #[tool(
+        name = "patch_config",
+        description = "Apply structured edits to a payload.config.ts (collections, globals, plugins, db adapter, locales), preserving formatting and comments",
+        annotations(read_only_hint = true, destructive_hint = false, idempotent_hint = true, open_world_hint = false),
+        output_schema = rmcp::handler::server::tool::cached_schema_for_type::<PatchConfigOutput>()
+    )]
+    fn patch_config(&self, Parameters(params): Parameters<PatchConfigParams>) -> Result<CallToolResult, ErrorData> {
+        if params.path.is_some() && !LOCAL_TRANSPORTS.contains(&self.transport) {
+            return Err(ErrorData::invalid_params(
+                format!("path is not available over the {} transport", self.transport),
+                None,
+            ));
+        }
+        match params.source().and_then(|source| patch_config(&source, &params.edits)) {
+            Ok(patched) => Ok(CallToolResult::structured(json!(patched))),
+            Err(err) => Err(ErrorData::invalid_params(err, None)),
+        }
+    }
+
+    #[tool(
+        name = "env_audit",
+        description = "Derive the environment variables a project needs, report the ones missing from or unused in its .env, and extend .env.example",
+        annotations(read_only_hint = true, destructive_hint = false, idempotent_hint = true, open_world_hint = false),
+        output_schema = rmcp::handler::server::tool::cached_schema_for_type::<EnvAudit>()
+    )]
+    fn env_audit(&self, Parameters(params): Parameters<EnvAuditParams>) -> Result<CallToolResult, ErrorData> {
+        if params.reads_disk() && !LOCAL_TRANSPORTS.contains(&self.transport) {
+            return Err(ErrorData::invalid_params(
+                format!("env_path and example_path are not available over the {} transport", self.transport),
+                None,
+            ));
+        }
+        let project = match (&params.project, &params.files) {
+            (None, Some(_)) => None,
+            _ => Some(self.session_project(params.project.clone())?),
+        };
+        match env_audit_for(&params, project.as_ref()) {
+            Ok(audit) => Ok(CallToolResult::structured(json!(audit))),
+            Err(err) => Err(ErrorData::invalid_params(err, None)),
+        }
+    }
+
+    #[tool(
+        name = "check_dependencies",
+        description = "Check a project's payload and @payloadcms/* versions against the compatibility matrix and suggest upgrade commands",
+        annotations(read_only_hint = true, destructive_hint = false, idempotent_hint = true, open_world_hint = false),
+        output_schema = rmcp::handler::server::tool::cached_schema_for_type::<DependencyReport>()
+    )]
+    fn check_dependencies(&self, Parameters(params): Parameters<CheckDependenciesParams>) -> Result<CallToolResult, ErrorData> {
+        if params.path.is_some() && !LOCAL_TRANSPORTS.contains(&self.transport) {
+            return Err(ErrorData::invalid_params(
+                format!("path is not available over the {} transport", self.transport),
+                None,
+            ));
+        }
+        match params.source().and_then(|source| check_dependencies(&source)) {
+            Ok(report) => Ok(CallToolResult::structured(json!(report))),
+            Err(err) => Err(ErrorData::invalid_params(err, None)),
+        }
+    }
+
+    #[tool(
+        name = "parse_payload_types",
+        description = "Reconstruct collections, globals and fields from a generated payload-types.ts and validate them",
+        annotations(read_only_hint = true, destructive_hint = false, idempotent_hint = true, open_world_hint = false),
+        output_schema = rmcp::handler::server::tool::cached_schema_for_type::<PayloadTypesSchema>()
+    )]
+    fn parse_payload_types(&self, Parameters(params): Parameters<ParsePayloadTypesParams>) -> Result<CallToolResult, ErrorData> {
+        if params.path.is_some() && !LOCAL_TRANSPORTS.contains(&self.transport) {
+            return Err(ErrorData::invalid_params(
+                format!("path is not available over the {} transport", self.transport),
+                None,
+            ));
+        }
+        match params.source().and_then(|source| parse_payload_types(&source)) {
+            Ok(parsed) => Ok(CallToolResult::structured(json!(parsed))),
+            Err(err) => Err(ErrorData::invalid_params(err, None)),
+        }
+    }
+
+    #[tool(
+        name = "generate_openapi",
+        description = "Generate an OpenAPI 3.1 document for the REST API Payload serves for a schema",
+        annotations(read_only_hint = true, destructive_hint = false, idempotent_hint = true, open_world_hint = true),
+        output_schema = rmcp::handler::server::tool::cached_schema_for_type::<OpenApiOutput>()
+    )]
+    async fn generate_openapi(&self, Parameters(params): Parameters<GenerateOpenApiParams>) -> Result<CallToolResult, ErrorData> {
+        let schema = self.source_schema(params.source).await?;
+        Ok(CallToolResult::structured(json!(openapi_document(&schema, &params.options))))
+    }
+
+    #[tool(
+        name = "generate_graphql_sdl",
+        description = "Generate the GraphQL SDL Payload serves for a schema: types, queries, mutations and where inputs",
+        annotations(read_only_hint = true, destructive_hint = false, idempotent_hint = true, open_world_hint = true),
+        output_schema = rmcp::handler::server::tool::cached_schema_for_type::<GraphqlSdlOutput>()
+    )]
+    async fn generate_graphql_sdl(&self, Parameters(params): Parameters<GenerateGraphqlSdlParams>) -> Result<CallToolResult, ErrorData> {
+        let schema = self.source_schema(params.source).await?;
+        Ok(CallToolResult::structured(json!(graphql_sdl(&schema, &params.options))))
+    }
+
+    #[tool(
+        name = "generate_client_sdk",
+        description = "Generate a typed TypeScript fetch client for a schema: document types and find, findByID, create, update and delete per collection, with auth",
+        annotations(read_only_hint = true, destructive_hint = false, idempotent_hint = true, open_world_hint = true),
+        output_schema = rmcp::handler::server::tool::cached_schema_for_type::<ClientSdkOutput>()
+    )]
+    async fn generate_client_sdk(&self, Parameters(params): Parameters<GenerateClientSdkParams>) -> Result<CallToolResult, ErrorData> {
+        let schema = self.source_schema(params.source).await?;
+        Ok(CallToolResult::structured(json!(client_sdk(&schema, &params.options))))
+    }
+
+    #[tool(
+        name = "generate_json_schema",
+        description = "Convert collections and globals into JSON Schema documents, with $defs for blocks",
+        annotations(read_only_hint = true, destructive_hint = false, idempotent_hint = true, open_world_hint = true),
+        output_schema = rmcp::handler::server::tool::cached_schema_for_type::<JsonSchemaOutput>()
+    )]
+    async fn generate_json_schema(&self, Parameters(params): Parameters<GenerateJsonSchemaParams>) -> Result<CallToolResult, ErrorData> {
+        let schema = self.source_schema(params.source).await?;
+        Ok(CallToolResult::structured(json!(json_schemas(&schema, &params.options))))
+    }
+
+    #[tool(
+        name = "schema_graph",
+        description = "Graph the relationship and upload fields between collections and globals, with optional Mermaid or DOT output",
+        annotations(read_only_hint = true, destructive_hint = false, idempotent_hint = true, open_world_hint = true),
+        output_schema = rmcp::handler::server::tool::cached_schema_for_type::<SchemaGraph>()
+    )]
+    async fn schema_graph(&self, Parameters(params): Parameters<SchemaGraphParams>) -> Result<CallToolResult, ErrorData> {
+        let schema = self.source_schema(params.source).await?;
+        Ok(CallToolResult::structured(json!(schema_graph(&schema, &params.options))))
+    }
+
+    #[tool(
+        name = "analyze_relationship_depth",
+        description = "Find relationship cycles and estimate how many documents a read populates at a given depth",
+        annotations(read_only_hint = true, destructive_hint = false, idempotent_hint = true, open_world_hint = true),
+        output_schema = rmcp::handler::server::tool::cached_schema_for_type::<DepthAnalysis>()
+    )]
+    async fn analyze_relationship_depth(&self, Parameters(params): Parameters<AnalyzeRelationshipDepthParams>) -> Result<CallToolResult, ErrorData> {
+        let schema = self.source_schema(params.source).await?;
+        Ok(CallToolResult::structured(json!(relationship_depth(&schema, &params.options))))
+    }
+
+    #[tool(
+        name = "export_content",
+        description = "Export documents from a live Payload instance as NDJSON, keeping relationships as IDs",
+        annotations(read_only_hint = false, destructive_hint = false, idempotent_hint = false, open_world_hint = true),
+        output_schema = rmcp::handler::server::tool::cached_schema_for_type::<ContentExportOutput>()
+    )]
+    async fn export_content(&self, Parameters(params): Parameters<ExportContentParams>) -> Result<CallToolResult, ErrorData> {
+        if params.path.is_some() && !LOCAL_TRANSPORTS.contains(&self.transport) {
+            return Err(ErrorData::invalid_params(
+                format!("path is not available over the {} transport", self.transport),
+                None,
+            ));
+        }
+        let client = self
+            .live_client(params.connection, params.connection_string, params.api_key)
+            .map_err(|err| ErrorData::invalid_params(err, None))?;
+        let options = params.options;
+        let path = params.path;
+        let mut output = tokio::task::spawn_blocking(move || export_to(&client, &options, path))
+            .await
+            .map_err(|err| ErrorData::internal_error(err.to_string(), None))?
+            .map_err(|err| ErrorData::invalid_params(err, None))?;
+
+        let mut links = Vec::new();
+        if let Some(ndjson) = output.ndjson.take_if(|ndjson| ndjson.len() > INLINE_RESULT_LIMIT) {
+            let mut tree = json!({ "export.ndjson": ndjson });
+            links = self
+                .results
+                .link_files(&mut tree)
+                .map_err(|err| ErrorData::internal_error(err, None))?;
+            output.ndjson = tree["export.ndjson"].as_str().map(str::to_string);
+            output.linked = Some(true);
+        }
+        let mut result = CallToolResult::structured(json!(output));
+        result.content.extend(links.into_iter().map(Content::resource_link));
+        Ok(result)
+    }
+
+    #[tool(
+        name = "anonymize_export",
+        description = "Export documents from a live Payload instance as NDJSON with PII fields replaced by fake values",
+        annotations(read_only_hint = false, destructive_hint = false, idempotent_hint = false, open_world_hint = true),
+        output_schema = rmcp::handler::server::tool::cached_schema_for_type::<AnonymizeExportOutput>()
+    )]
+    async fn anonymize_export(&self, Parameters(params): Parameters<AnonymizeExportParams>) -> Result<CallToolResult, ErrorData> {
+        if params.path.is_some() && !LOCAL_TRANSPORTS.contains(&self.transport) {
+            return Err(ErrorData::invalid_params(
+                format!("path is not available over the {} transport", self.transport),
+                None,
+            ));
+        }
+        let client = self
+            .live_client(params.connection, params.connection_string, params.api_key)
+            .map_err(|err| ErrorData::invalid_params(err, None))?;
+        let options = params.options;
+        let path = params.path;
+        let mut output = tokio::task::spawn_blocking(move || anonymize_to(&client, &options, path))
+            .await
+            .map_err(|err| ErrorData::internal_error(err.to_string(), None))?
+            .map_err(|err| ErrorData::invalid_params(err, None))?;
+
+        let mut links = Vec::new();
+        if let Some(ndjson) = output.export.ndjson.take_if(|ndjson| ndjson.len() > INLINE_RESULT_LIMIT) {
+            let mut tree = json!({ "anonymized.ndjson": ndjson });
+            links = self
+                .results
+                .link_files(&mut tree)
+                .map_err(|err| ErrorData::internal_error(err, None))?;
+            output.export.ndjson = tree["anonymized.ndjson"].as_str().map(str::to_string);
+            output.export.linked = Some(true);
+        }
+        let mut result = CallToolResult::structured(json!(output));
+        result.content.extend(links.into_iter().map(Content::resource_link));
+        Ok(result)
+    }
+
+    #[tool(
+        name = "import_content",
+        description = "Import NDJSON documents into a live Payload instance, creating or upserting them",
+        annotations(read_only_hint = false, destructive_hint = false, idempotent_hint = false, open_world_hint = true),
+        output_schema = rmcp::handler::server::tool::cached_schema_for_type::<ContentImport>()
+    )]
+    async fn import_content(&self, Parameters(params): Parameters<ImportContentParams>) -> Result<CallToolResult, ErrorData> {
+        if params.path.is_some() && !LOCAL_TRANSPORTS.contains(&self.transport) {
+            return Err(ErrorData::invalid_params(
+                format!("path is not available over the {} transport", self.transport),
+                None,
+            ));
+        }
+        let client = self
+            .live_client(params.connection, params.connection_string, params.api_key)
+            .map_err(|err| ErrorData::invalid_params(err, None))?;
+        let ImportContentParams { content, path, options, .. } = params;
+        let import = tokio::task::spawn_blocking(move || import_from(&client, content, path, &options))
+            .await
+            .map_err(|err| ErrorData::internal_error(err.to_string(), None))?;
+        match import {
+            Ok(import) => Ok(CallToolResult::structured(json!(import))),
+            Err(err) => Err(ErrorData::invalid_params(err, None)),
+        }
+    }
+
+    #[tool(
+        name = "backup_instance",
+        description = "Back up collections and globals of a live Payload instance to a timestamped directory",
+        annotations(read_only_hint = false, destructive_hint = false, idempotent_hint = false, open_world_hint = true),
+        output_schema = rmcp::handler::server::tool::cached_schema_for_type::<Backup>()
+    )]
+    async fn backup_instance(&self, Parameters(params): Parameters<BackupInstanceParams>) -> Result<CallToolResult, ErrorData> {
+        if !LOCAL_TRANSPORTS.contains(&self.transport) {
+            return Err(ErrorData::invalid_params(
+                format!("backup_instance is not available over the {} transport", self.transport),
+                None,
+            ));
+        }
+        let client = self
+            .live_client(params.connection, params.connection_string, params.api_key)
+            .map_err(|err| ErrorData::invalid_params(err, None))?;
+        let BackupInstanceParams { dir, options, .. } = params;
+        let backup = tokio::task::spawn_blocking(move || backup_instance(&client, std::path::Path::new(&dir), &options))
+            .await
+            .map_err(|err| ErrorData::internal_error(err.to_string(), None))?;
+        match backup {
+            Ok(backup) => Ok(CallToolResult::structured(json!(backup))),
+            Err(err) => Err(ErrorData::invalid_params(err, None)),
+        }
+    }
+
+    #[tool(
+        name = "restore_instance",
+        description = "Restore a backup from backup_instance into a live Payload instance, or plan it with dryRun",
+        annotations(read_only_hint = false, destructive_hint = true, idempotent_hint = false, open_world_hint = true),
+        output_schema = rmcp::handler::server::tool::cached_schema_for_type::<Restore>()
+    )]
+    async fn restore_instance(&self, Parameters(params): Parameters<RestoreInstanceParams>) -> Result<CallToolResult, ErrorData> {
+        if !LOCAL_TRANSPORTS.contains(&self.transport) {
+            return Err(ErrorData::invalid_params(
+                format!("restore_instance is not available over the {} transport", self.transport),
+                None,
+            ));
+        }
+        let client = self
+            .live_client(params.connection, params.connection_string, params.api_key)
+            .map_err(|err| ErrorData::invalid_params(err, None))?;
+        let RestoreInstanceParams { path, options, .. } = params;
+        let restore = tokio::task::spawn_blocking(move || restore_instance(&client, std::path::Path::new(&path), &options))
+            .await
+            .map_err(|err| ErrorData::internal_error(err.to_string(), None))?;
+        match restore {
+            Ok(restore) => Ok(CallToolResult::structured(json!(restore))),
+            Err(err) => Err(ErrorData::invalid_params(err, None)),
+        }
+    }
+
+    #[tool(
+        name = "i18n_audit",
+        description = "Report localized fields with missing translations per locale in a live Payload instance",
+        annotations(read_only_hint = true, destructive_hint = false, idempotent_hint = true, open_world_hint = true),
+        output_schema = rmcp::handler::server::tool::cached_schema_for_type::<I18nAudit>()
+    )]
+    async fn i18n_audit(&self, Parameters(params): Parameters<I18nAuditParams>) -> Result<CallToolResult, ErrorData> {
+        let client = self
+            .live_client(params.connection, params.connection_string, params.api_key)
+            .map_err(|err| ErrorData::invalid_params(err, None))?;
+        let I18nAuditParams { schema, files, options, .. } = params;
+        let audit = tokio::task::spawn_blocking(move || {
+            let schema = audit_schema(&client, schema, files)?;
+            i18n_audit(&client, &schema, &options)
+        })
+        .await
+        .map_err(|err| ErrorData::internal_error(err.to_string(), None))?;
+        match audit {
+            Ok(audit) => Ok(CallToolResult::structured(json!(audit))),
+            Err(err) => Err(ErrorData::invalid_params(err, None)),
+        }
+    }
+
+    #[tool(
+        name = "content_audit",
+        description = "Report missing required fields, broken references, orphaned uploads, stale drafts and empty collections in a live Payload instance",
+        annotations(read_only_hint = true, destructive_hint = false, idempotent_hint = true, open_world_hint = true),
+        output_schema = rmcp::handler::server::tool::cached_schema_for_type::<ContentAudit>()
+    )]
+    async fn content_audit(&self, Parameters(params): Parameters<ContentAuditParams>) -> Result<CallToolResult, ErrorData> {
+        let client = self
+            .live_client(params.connection, params.connection_string, params.api_key)
+            .map_err(|err| ErrorData::invalid_params(err, None))?;
+        let ContentAuditParams { schema, files, options, .. } = params;
+        let audit = tokio::task::spawn_blocking(move || {
+            let schema = audit_schema(&client, schema, files)?;
+            content_audit(&client, &schema, &options)
+        })
+        .await
+        .map_err(|err| ErrorData::internal_error(err.to_string(), None))?;
+        match audit {
+            Ok(audit) => Ok(CallToolResult::structured(json!(audit))),
+            Err(err) => Err(ErrorData::invalid_params(err, None)),
+        }
+    }
+
+    #[tool(
+        name = "bulk_update",
+        description = "Patch every document matching a where query in a live Payload instance, after a dry run and its confirmation token",
+        annotations(read_only_hint = false, destructive_hint = true, idempotent_hint = true, open_world_hint = true),
+        output_schema = rmcp::handler::server::tool::cached_schema_for_type::<BulkUpdate>()
+    )]
+    async fn bulk_update(&self, Parameters(params): Parameters<BulkUpdateParams>) -> Result<CallToolResult, ErrorData> {
+        let client = self
+            .live_client(params.connection, params.connection_string, params.api_key)
+            .map_err(|err| ErrorData::invalid_params(err, None))?;
+        let options = params.options;
+        let update = tokio::task::spawn_blocking(move || bulk_update(&client, &options))
+            .await
+            .map_err(|err| ErrorData::internal_error(err.to_string(), None))?;
+        match update {
+            Ok(update) => Ok(CallToolResult::structured(json!(update))),
+            Err(err) => Err(ErrorData::invalid_params(err, None)),
+        }
+    }
+
+    #[tool(
+        name = "connect_payload",
+        description = "Connect to a live Payload CMS instance and test the connection",
+        annotations(read_only_hint = true, destructive_hint = false, idempotent_hint = true, open_world_hint = true),
+        output_schema = rmcp::handler::server::tool::cached_schema_for_type::<ConnectOutput>()
+    )]
     async fn connect_payload(&self, Parameters(params): Parameters<ConnectPayloadParams>) -> Result<CallToolResult, ErrorData> {
-        match create_payload_client(&params.connection_string, params.api_key) {
-            Ok(client) => {
+        let connection = match (params.connection, params.connection_string) {
+            (Some(_), Some(_)) => Err("Pass either connection or connection_string, not both".to_string()),
+            (Some(name), None) => Ok(PayloadConnection::Named(name)),
+            (None, Some(connection_string)) => Ok(PayloadConnection::Direct {
+                connection_string,
+                api_key: params.api_key,
+            }),
+            (None, None) => Err("connection or connection_string is required".to_string()),
+        };
+        let client = connection
+            .and_then(|connection| connection_client(&connection, None).map(|client| (connection, client)));
+        match client {
+            Ok((connection, client)) => {
                 match client.test_connection() {
-                    Ok(info) => Ok(CallToolResult::structured(json!({
-                        "success": true,
-                        "server_info": info
-                    }))),
+                    Ok(info) => {
+                        self.state.update_workspace(self.session_id, |workspace| {
+                            workspace.connection = Some(connection)
+                        });
+                        Ok(CallToolResult::structured(json!({
+                            "success": true,
+                            "server_info": info
+                        })))
+                    }
                     Err(err) => Ok(CallToolResult::structured(json!({
                         "success": false,
                         "error": err.to_string()
@@ -172,9 +1424,53 @@ impl ToolBoxHandler {
         }
     }
 
-    #[tool(name = "get_collection_schema", description = "Get collection schema from a live Payload CMS instance")]
+    #[tool(
+        name = "add_connection",
+        description = "Store a named Payload connection (URL and credential reference) in settings",
+        annotations(read_only_hint = false, destructive_hint = false, idempotent_hint = false, open_world_hint = true),
+        output_schema = rmcp::handler::server::tool::cached_schema_for_type::<ConnectionOutput>()
+    )]
+    fn add_connection(&self, Parameters(params): Parameters<AddConnectionParams>) -> Result<CallToolResult, ErrorData> {
+        if !LOCAL_TRANSPORTS.contains(&self.transport) {
+            return Err(ErrorData::invalid_params(
+                format!("add_connection is not available over the {} transport", self.transport),
+                None,
+            ));
+        }
+        let connection = Connection {
+            url: params.url,
+            api_key: params.api_key,
+        };
+        match connections::store(paths::active_settings_path(), &params.name, &connection) {
+            Ok(()) => Ok(CallToolResult::structured(json!(ConnectionOutput {
+                name: params.name,
+                connection,
+            }))),
+            Err(err) => Err(ErrorData::invalid_params(err, None)),
+        }
+    }
+
+    #[tool(
+        name = "list_connections",
+        description = "List stored Payload connections",
+        annotations(read_only_hint = true, destructive_hint = false, idempotent_hint = true, open_world_hint = false),
+        output_schema = rmcp::handler::server::tool::cached_schema_for_type::<ConnectionListOutput>()
+    )]
+    fn list_connections(&self, Parameters(_params): Parameters<ListConnectionsParams>) -> Result<CallToolResult, ErrorData> {
+        match connections::load(paths::active_settings_path()) {
+            Ok(connections) => Ok(CallToolResult::structured(json!(ConnectionListOutput { connections }))),
+            Err(err) => Err(ErrorData::internal_error(err, None)),
+        }
+    }
+
+    #[tool(
+        name = "get_collection_schema",
+        description = "Get collection schema from a live Payload CMS instance",
+        annotations(read_only_hint = true, destructive_hint = false, idempotent_hint = true, open_world_hint = true),
+        output_schema = rmcp::handler::server::tool::cached_schema_for_type::<CollectionSchemaOutput>()
+    )]
     async fn get_collection_schema(&self, Parameters(params): Parameters<GetCollectionParams>) -> Result<CallToolResult, ErrorData> {
-        match create_payload_client(&params.connection_string, params.api_key) {
+        match self.live_client(params.connection, params.connection_string, params.api_key) {
             Ok(client) => {
                 match client.get_collection(&params.slug) {
                     Ok(collection) => Ok(CallToolResult::structured(json!({
@@ -194,9 +1490,14 @@ impl ToolBoxHandler {
         }
     }
 
-    #[tool(name = "list_collections", description = "List all collections from a live Payload CMS instance")]
+    #[tool(
+        name = "list_collections",
+        description = "List all collections from a live Payload CMS instance",
+        annotations(read_only_hint = true, destructive_hint = false, idempotent_hint = true, open_world_hint = true),
+        output_schema = rmcp::handler::server::tool::cached_schema_for_type::<CollectionListOutput>()
+    )]
     async fn list_collections(&self, Parameters(params): Parameters<ListCollectionsParams>) -> Result<CallToolResult, ErrorData> {
-        match create_payload_client(&params.connection_string, params.api_key) {
+        match self.live_client(params.connection, params.connection_string, params.api_key) {
             Ok(client) => {
                 match client.list_collections() {
                     Ok(collections) => Ok(CallToolResult::structured(json!({
@@ -216,9 +1517,14 @@ impl ToolBoxHandler {
         }
     }
 
-    #[tool(name = "validate_against_live", description = "Validate a collection configuration against a live Payload instance")]
+    #[tool(
+        name = "validate_against_live",
+        description = "Validate a collection configuration against a live Payload instance",
+        annotations(read_only_hint = true, destructive_hint = false, idempotent_hint = true, open_world_hint = true),
+        output_schema = rmcp::handler::server::tool::cached_schema_for_type::<LiveValidationOutput>()
+    )]
     async fn validate_against_live(&self, Parameters(params): Parameters<ValidateAgainstLiveParams>) -> Result<CallToolResult, ErrorData> {
-        match create_payload_client(&params.connection_string, params.api_key) {
+        match self.live_client(params.connection, params.connection_string, params.api_key) {
             Ok(client) => {
                 match client.validate_collection_config(&params.slug, &params.config) {
                     Ok(issues) => Ok(CallToolResult::structured(json!({
@@ -237,10 +1543,242 @@ impl ToolBoxHandler {
             })))
         }
     }
+
+    #[tool(
+        name = "start_planning",
+        description = "Start planning a Payload build: create a goal with an empty implementation plan",
+        annotations(read_only_hint = false, destructive_hint = false, idempotent_hint = false, open_world_hint = false),
+        output_schema = rmcp::handler::server::tool::cached_schema_for_type::<PlanOutput>()
+    )]
+    fn start_planning(&self, Parameters(params): Parameters<StartPlanningParams>) -> Result<CallToolResult, ErrorData> {
+        let result = self
+            .state
+            .planning()
+            .and_then(|storage| storage.create_goal(&params.goal));
+        if let Ok((goal, _)) = &result {
+            let goal_id = goal.id.clone();
+            self.state
+                .update_workspace(self.session_id, |workspace| workspace.goal_id = Some(goal_id));
+        }
+        Ok(planning_result(result.map(|(goal, plan)| PlanOutput { goal, plan })))
+    }
+
+    #[tool(
+        name = "save_plan",
+        description = "Save plan text for a goal, adding a todo for each numbered item",
+        annotations(read_only_hint = false, destructive_hint = false, idempotent_hint = false, open_world_hint = false),
+        output_schema = rmcp::handler::server::tool::cached_schema_for_type::<PlanOutput>()
+    )]
+    fn save_plan(&self, Parameters(params): Parameters<SavePlanParams>) -> Result<CallToolResult, ErrorData> {
+        let result = self.session_goal(params.goal_id).and_then(|goal_id| {
+            let storage = self.state.planning()?;
+            storage.save_plan(&goal_id, &params.plan)?;
+            storage.get_plan(&goal_id)
+        });
+        Ok(planning_result(result.map(|(goal, plan)| PlanOutput { goal, plan })))
+    }
+
+    #[tool(
+        name = "add_todo",
+        description = "Add a todo to a goal's implementation plan",
+        annotations(read_only_hint = false, destructive_hint = false, idempotent_hint = false, open_world_hint = false),
+        output_schema = rmcp::handler::server::tool::cached_schema_for_type::<TodoOutput>()
+    )]
+    fn add_todo(&self, Parameters(params): Parameters<AddTodoParams>) -> Result<CallToolResult, ErrorData> {
+        let result = self.session_goal(params.goal_id.clone()).and_then(|goal_id| {
+            self.state.planning()?.add_todo(&goal_id, params.new_todo())
+        });
+        Ok(planning_result(result.map(|todo| TodoOutput { todo })))
+    }
+
+    #[tool(
+        name = "update_todo_status",
+        description = "Mark a todo complete or incomplete",
+        annotations(read_only_hint = false, destructive_hint = false, idempotent_hint = true, open_world_hint = false),
+        output_schema = rmcp::handler::server::tool::cached_schema_for_type::<TodoOutput>()
+    )]
+    fn update_todo_status(&self, Parameters(params): Parameters<UpdateTodoStatusParams>) -> Result<CallToolResult, ErrorData> {
+        let result = self.session_goal(params.goal_id).and_then(|goal_id| {
+            self.state
+                .planning()?
+                .update_todo_status(&goal_id, &params.todo_id, params.is_complete)
+        });
+        Ok(planning_result(result.map(|todo| TodoOutput { todo })))
+    }
+
+    #[tool(
+        name = "get_plan",
+        description = "Get a goal and its implementation plan with todos",
+        annotations(read_only_hint = true, destructive_hint = false, idempotent_hint = true, open_world_hint = false),
+        output_schema = rmcp::handler::server::tool::cached_schema_for_type::<PlanOutput>()
+    )]
+    fn get_plan(&self, Parameters(params): Parameters<GetPlanParams>) -> Result<CallToolResult, ErrorData> {
+        let result = self
+            .session_goal(params.goal_id)
+            .and_then(|goal_id| self.state.planning()?.get_plan(&goal_id));
+        Ok(planning_result(result.map(|(goal, plan)| PlanOutput { goal, plan })))
+    }
+
+    #[tool(
+        name = "list_todos",
+        description = "List todos across all goals, by default only incomplete ones",
+        annotations(read_only_hint = true, destructive_hint = false, idempotent_hint = true, open_world_hint = false),
+        output_schema = rmcp::handler::server::tool::cached_schema_for_type::<TodoListOutput>()
+    )]
+    fn list_todos(&self, Parameters(params): Parameters<ListTodosParams>) -> Result<CallToolResult, ErrorData> {
+        let result = self
+            .state
+            .planning()
+            .and_then(|storage| storage.list_todos(params.incomplete_only.unwrap_or(true)));
+        Ok(planning_result(result.map(|todos| TodoListOutput { todos })))
+    }
+
+    #[tool(
+        name = "plan_history",
+        description = "List snapshots of a goal's plan after each change",
+        annotations(read_only_hint = true, destructive_hint = false, idempotent_hint = true, open_world_hint = false),
+        output_schema = rmcp::handler::server::tool::cached_schema_for_type::<PlanHistoryOutput>()
+    )]
+    fn plan_history(&self, Parameters(params): Parameters<PlanHistoryParams>) -> Result<CallToolResult, ErrorData> {
+        let result = self
+            .session_goal(params.goal_id)
+            .and_then(|goal_id| self.state.planning()?.plan_history(&goal_id));
+        Ok(planning_result(result.map(|revisions| PlanHistoryOutput { revisions })))
+    }
+
+    #[tool(
+        name = "register_schema",
+        description = "Store a snapshot of a schema as the next version under a name, reporting changes since the last one",
+        annotations(read_only_hint = false, destructive_hint = false, idempotent_hint = true, open_world_hint = true),
+        output_schema = rmcp::handler::server::tool::cached_schema_for_type::<SchemaRegistration>()
+    )]
+    async fn register_schema(&self, Parameters(params): Parameters<RegisterSchemaParams>) -> Result<CallToolResult, ErrorData> {
+        let schema = self.source_schema(params.source).await?;
+        let result = self
+            .state
+            .planning()
+            .and_then(|storage| register_schema(storage, &params.name, params.label.as_deref(), &schema));
+        Ok(planning_result(result))
+    }
+
+    #[tool(
+        name = "list_schema_versions",
+        description = "List registered schema versions with their labels and dates",
+        annotations(read_only_hint = true, destructive_hint = false, idempotent_hint = true, open_world_hint = false),
+        output_schema = rmcp::handler::server::tool::cached_schema_for_type::<SchemaVersionsOutput>()
+    )]
+    fn list_schema_versions(&self, Parameters(params): Parameters<ListSchemaVersionsParams>) -> Result<CallToolResult, ErrorData> {
+        let result = self
+            .state
+            .planning()
+            .and_then(|storage| storage.schema_versions(params.name.as_deref()));
+        Ok(planning_result(result.map(|versions| SchemaVersionsOutput { versions })))
+    }
+
+    #[tool(
+        name = "diff_schema_versions",
+        description = "Compare two registered schema versions and generate the data migration between them",
+        annotations(read_only_hint = true, destructive_hint = false, idempotent_hint = true, open_world_hint = false),
+        output_schema = rmcp::handler::server::tool::cached_schema_for_type::<SchemaVersionDiff>()
+    )]
+    fn diff_schema_versions(&self, Parameters(params): Parameters<DiffSchemaVersionsParams>) -> Result<CallToolResult, ErrorData> {
+        let result = self.state.planning().and_then(|storage| {
+            diff_schema_versions(storage, &params.name, params.from, params.to, params.database.as_deref())
+        });
+        Ok(planning_result(result))
+    }
+
+    #[tool(
+        name = "check_breaking_changes",
+        description = "Classify schema changes since a registered version as breaking or non-breaking, with remediation hints; fails on breaking changes",
+        annotations(read_only_hint = true, destructive_hint = false, idempotent_hint = true, open_world_hint = true),
+        output_schema = rmcp::handler::server::tool::cached_schema_for_type::<BreakingChangeReport>()
+    )]
+    async fn check_breaking_changes(&self, Parameters(params): Parameters<CheckBreakingChangesParams>) -> Result<CallToolResult, ErrorData> {
+        let candidate = if params.source.is_given() {
+            Some(self.source_schema(params.source).await?)
+        } else {
+            None
+        };
+        let result = self
+            .state
+            .planning()
+            .and_then(|storage| check_breaking_changes(storage, &params.options, candidate.as_ref()));
+        Ok(breaking_change_result(result, params.fail_on_breaking))
+    }
+
+    #[tool(
+        name = "attach_result",
+        description = "Attach a tool result (scaffolded files, validation report, generated code) to a todo or plan",
+        annotations(read_only_hint = false, destructive_hint = false, idempotent_hint = false, open_world_hint = false),
+        output_schema = rmcp::handler::server::tool::cached_schema_for_type::<ArtifactsOutput>()
+    )]
+    fn attach_result(&self, Parameters(params): Parameters<AttachResultParams>) -> Result<CallToolResult, ErrorData> {
+        let result = self.session_goal(params.goal_id).and_then(|goal_id| {
+            let value = params
+                .result
+                .or_else(|| self.state.workspace(self.session_id).last_results.remove(&params.tool))
+                .ok_or_else(|| format!("No result given and no {} result in this session", params.tool))?;
+            self.state.planning()?.attach_artifacts(
+                &goal_id,
+                params.todo_id.as_deref(),
+                &params.tool,
+                artifacts_from_result(&params.tool, &value),
+            )
+        });
+        Ok(planning_result(result.map(|artifacts| ArtifactsOutput { artifacts })))
+    }
 }
 
-#[tool_handler(router = self.tool_router)]
 impl ServerHandler for ToolBoxHandler {
+    async fn call_tool(
+        &self,
+        req: rmcp::model::CallToolRequestParam,
+        ctx: RequestContext<RoleServer>,
+    ) -> Result<CallToolResult, rmcp::ErrorData> {
+        let name = req.name.to_string();
+        if !self.state.tool_policy.allows(self.transport, &name) {
+            return Err(rmcp::ErrorData::invalid_params(
+                format!("Tool '{name}' is not available over the {} transport", self.transport),
+                None,
+            ));
+        }
+        let context = rmcp::handler::server::tool::ToolCallContext::new(self, req, ctx);
+        let started = std::time::Instant::now();
+        let in_flight = metrics::tool_call_started();
+        let result = self.tool_router.call(context).await;
+        drop(in_flight);
+        let ok = matches!(&result, Ok(result) if result.is_error != Some(true));
+        metrics::record_tool_call(&name, ok, started.elapsed());
+        let result = result?;
+        if let Some(value) = &result.structured_content {
+            if result.is_error != Some(true) {
+                let value = value.clone();
+                self.state.update_workspace(self.session_id, |workspace| {
+                    workspace.last_results.insert(name, value);
+                });
+            }
+        }
+        Ok(result)
+    }
+
+    async fn list_tools(
+        &self,
+        req: Option<ListResourcesRequest>,
+        _ctx: RequestContext<RoleServer>,
+    ) -> Result<rmcp::model::ListToolsResult, rmcp::ErrorData> {
+        let cursor = req.as_ref().and_then(|r| r.cursor.as_deref());
+        let available = self
+            .tool_router
+            .list_all()
+            .into_iter()
+            .filter(|tool| self.state.tool_policy.allows(self.transport, &tool.name))
+            .collect();
+        let (tools, next_cursor) = paginate(available, cursor, TOOLS_PAGE_SIZE)
+            .map_err(|err| rmcp::ErrorData::invalid_params(err, None))?;
+        Ok(rmcp::model::ListToolsResult { tools, next_cursor })
+    }
+
     fn ping(
         &self,
         _ctx: RequestContext<RoleServer>,
@@ -248,14 +1786,188 @@ impl ServerHandler for ToolBoxHandler {
         ready(Ok(()))
     }
 
-    async fn list_resources(
+    async fn list_prompts(
         &self,
         _req: Option<ListResourcesRequest>,
         _ctx: RequestContext<RoleServer>,
+    ) -> Result<rmcp::model::ListPromptsResult, rmcp::ErrorData> {
+        Ok(rmcp::model::ListPromptsResult {
+            prompts: prompt_definitions(),
+            next_cursor: None,
+        })
+    }
+
+    async fn get_prompt(
+        &self,
+        req: rmcp::model::GetPromptRequestParam,
+        _ctx: RequestContext<RoleServer>,
+    ) -> Result<rmcp::model::GetPromptResult, rmcp::ErrorData> {
+        get_prompt(&req.name, req.arguments.as_ref())
+            .map_err(|err| rmcp::ErrorData::invalid_params(err, None))
+    }
+
+    async fn complete(
+        &self,
+        req: rmcp::model::CompleteRequestParam,
+        _ctx: RequestContext<RoleServer>,
+    ) -> Result<rmcp::model::CompleteResult, rmcp::ErrorData> {
+        let context = req
+            .context
+            .and_then(|context| context.arguments)
+            .unwrap_or_default();
+        let rmcp::model::ArgumentInfo { name, value } = req.argument;
+        // Collection slugs may be fetched from a live instance.
+        let (values, total) =
+            tokio::task::spawn_blocking(move || complete_argument(&name, &value, &context))
+                .await
+                .map_err(|err| rmcp::ErrorData::internal_error(err.to_string(), None))?;
+        let has_more = total > values.len();
+        Ok(rmcp::model::CompleteResult {
+            completion: rmcp::model::CompletionInfo {
+                values,
+                total: Some(total as u32),
+                has_more: Some(has_more),
+            },
+        })
+    }
+
+    async fn set_level(
+        &self,
+        req: rmcp::model::SetLevelRequestParam,
+        ctx: RequestContext<RoleServer>,
+    ) -> Result<(), rmcp::ErrorData> {
+        *self
+            .log_level
+            .lock()
+            .map_err(|_| rmcp::ErrorData::internal_error("Logging state poisoned", None))? = req.level;
+
+        let mut forwarder = self
+            .log_forwarder
+            .lock()
+            .map_err(|_| rmcp::ErrorData::internal_error("Logging state poisoned", None))?;
+        if forwarder.is_some() {
+            return Ok(());
+        }
+        let level = self.log_level.clone();
+        let peer = ctx.peer.clone();
+        let mut records = subscribe_logs();
+        let task = tokio::spawn(async move {
+            loop {
+                let record = match records.recv().await {
+                    Ok(record) => record,
+                    Err(tokio::sync::broadcast::error::RecvError::Lagged(_)) => continue,
+                    Err(tokio::sync::broadcast::error::RecvError::Closed) => break,
+                };
+                let threshold = match level.lock() {
+                    Ok(level) => *level,
+                    Err(_) => break,
+                };
+                if level_rank(record.level) < level_rank(threshold) {
+                    continue;
+                }
+                let sent = peer
+                    .notify_logging_message(rmcp::model::LoggingMessageNotificationParam {
+                        level: record.level,
+                        logger: Some(record.target),
+                        data: json!(record.message),
+                    })
+                    .await;
+                if sent.is_err() {
+                    break;
+                }
+            }
+        });
+        *forwarder = Some(task.abort_handle());
+        Ok(())
+    }
+
+    async fn list_resource_templates(
+        &self,
+        _req: Option<ListResourcesRequest>,
+        _ctx: RequestContext<RoleServer>,
+    ) -> Result<rmcp::model::ListResourceTemplatesResult, rmcp::ErrorData> {
+        Ok(rmcp::model::ListResourceTemplatesResult {
+            resource_templates: resource_templates(),
+            next_cursor: None,
+        })
+    }
+
+    async fn subscribe(
+        &self,
+        req: rmcp::model::SubscribeRequestParam,
+        ctx: RequestContext<RoleServer>,
+    ) -> Result<(), rmcp::ErrorData> {
+        // Built-in resources never change, so only live schemas are polled.
+        if parse_live_uri(&req.uri).is_none() {
+            return Ok(());
+        }
+
+        let uri = req.uri.clone();
+        let peer = ctx.peer.clone();
+        let task = tokio::spawn(async move {
+            let mut interval = tokio::time::interval(SCHEMA_POLL_INTERVAL);
+            let mut last: Option<String> = None;
+            loop {
+                interval.tick().await;
+                let fetch_uri = uri.clone();
+                let snapshot =
+                    match tokio::task::spawn_blocking(move || read_live_resource(&fetch_uri)).await {
+                        Ok(Ok(Some(snapshot))) => snapshot,
+                        Ok(Err(err)) => {
+                            tracing::debug!("Polling {uri} failed: {err}");
+                            continue;
+                        }
+                        _ => continue,
+                    };
+                if last.as_ref().is_some_and(|previous| *previous != snapshot) {
+                    let notified = peer
+                        .notify_resource_updated(rmcp::model::ResourceUpdatedNotificationParam {
+                            uri: uri.clone(),
+                        })
+                        .await;
+                    if notified.is_err() {
+                        // The client has gone away.
+                        break;
+                    }
+                }
+                last = Some(snapshot);
+            }
+        });
+
+        let previous = self
+            .subscriptions
+            .lock()
+            .map_err(|_| rmcp::ErrorData::internal_error("Subscription registry poisoned", None))?
+            .insert(req.uri, task.abort_handle());
+        if let Some(previous) = previous {
+            previous.abort();
+        }
+        Ok(())
+    }
+
+    async fn unsubscribe(
+        &self,
+        req: rmcp::model::UnsubscribeRequestParam,
+        _ctx: RequestContext<RoleServer>,
+    ) -> Result<(), rmcp::ErrorData> {
+        let removed = self
+            .subscriptions
+            .lock()
+            .map_err(|_| rmcp::ErrorData::internal_error("Subscription registry poisoned", None))?
+            .remove(&req.uri);
+        if let Some(task) = removed {
+            task.abort();
+        }
+        Ok(())
+    }
+
+    async fn list_resources(
+        &self,
+        req: Option<ListResourcesRequest>,
+        _ctx: RequestContext<RoleServer>,
     ) -> Result<rmcp::model::ListResourcesResult, rmcp::ErrorData> {
         use rmcp::model::{Annotated, RawResource};
-        Ok(rmcp::model::ListResourcesResult {
-            resources: vec![Annotated {
+        let mut resources = vec![Annotated {
                 raw: RawResource {
                     uri: "file://instructions".to_string(),
                     name: "MCP Server Instructions".to_string(),
@@ -266,8 +1978,14 @@ impl ServerHandler for ToolBoxHandler {
                     icons: None,
                 },
                 annotations: None,
-            }],
-            next_cursor: None,
+            }];
+        resources.extend(resource_definitions());
+        let cursor = req.as_ref().and_then(|r| r.cursor.as_deref());
+        let (resources, next_cursor) = paginate(resources, cursor, RESOURCES_PAGE_SIZE)
+            .map_err(|err| rmcp::ErrorData::invalid_params(err, None))?;
+        Ok(rmcp::model::ListResourcesResult {
+            resources,
+            next_cursor,
         })
     }
 
@@ -276,7 +1994,21 @@ impl ServerHandler for ToolBoxHandler {
         req: rmcp::model::ReadResourceRequestParam,
         _ctx: RequestContext<RoleServer>,
     ) -> Result<rmcp::model::ReadResourceResult, rmcp::ErrorData> {
-        if req.uri == "file://instructions" {
+        if parse_live_uri(&req.uri).is_some() {
+            let uri = req.uri.clone();
+            let text = tokio::task::spawn_blocking(move || read_live_resource(&uri))
+                .await
+                .map_err(|err| rmcp::ErrorData::internal_error(err.to_string(), None))?
+                .map_err(|err| rmcp::ErrorData::internal_error(err, None))?
+                .unwrap_or_default();
+            Ok(rmcp::model::ReadResourceResult {
+                contents: vec![rmcp::model::ResourceContents::text(text, req.uri)],
+            })
+        } else if let Some(text) = self.results.read(&req.uri) {
+            Ok(rmcp::model::ReadResourceResult {
+                contents: vec![rmcp::model::ResourceContents::text(text, req.uri)],
+            })
+        } else if req.uri == "file://instructions" {
             Ok(rmcp::model::ReadResourceResult {
                 contents: vec![rmcp::model::ResourceContents::text(
                     Self::instructions().unwrap_or_default(),
@@ -284,10 +2016,57 @@ impl ServerHandler for ToolBoxHandler {
                 )],
             })
         } else {
-            Err(rmcp::ErrorData::invalid_params(
-                format!("Unknown resource URI: {}", req.uri),
-                None,
-            ))
+            match read_resource(&req.uri) {
+                Ok(Some(text)) => Ok(rmcp::model::ReadResourceResult {
+                    contents: vec![rmcp::model::ResourceContents::text(text, req.uri)],
+                }),
+                Ok(None) => Err(rmcp::ErrorData::resource_not_found(
+                    format!("Unknown resource URI: {}", req.uri),
+                    None,
+                )),
+                Err(err) => Err(rmcp::ErrorData::invalid_params(err, None)),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use rmcp::model::Tool;
+
+    use super::*;
+    use crate::payload_tools::mcp::{run_tool, tool_definitions};
+
+    /// The `tool` command lists tools from `tool_definitions` and calls them
+    /// through `run_tool`, so both have to match what the router serves.
+    #[tokio::test]
+    async fn test_tool_command_matches_the_router() {
+        let by_name = |tools: Vec<Tool>| -> BTreeMap<String, Tool> {
+            tools
+                .into_iter()
+                .map(|tool| (tool.name.to_string(), tool))
+                .collect()
+        };
+        let routed = by_name(ToolBoxHandler::tool_router().list_all());
+        let defined = by_name(tool_definitions());
+        assert_eq!(
+            defined.keys().collect::<Vec<_>>(),
+            routed.keys().collect::<Vec<_>>()
+        );
+
+        for (name, tool) in &defined {
+            let served = &routed[name];
+            assert_eq!(tool.input_schema, served.input_schema, "{name} input schema");
+            assert_eq!(
+                json!(tool.annotations),
+                json!(served.annotations),
+                "{name} annotations"
+            );
+            if let Err(err) = run_tool(name, Value::Null).await {
+                assert_ne!(err.message, format!("Unknown tool: {name}"));
+            }
         }
     }
 }
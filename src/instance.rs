@@ -0,0 +1,87 @@
+//! Single-instance lock for servers with network transports.
+
+use std::{
+    fs,
+    io::{ErrorKind, Write},
+    path::{Path, PathBuf},
+};
+
+use sysinfo::{Pid, ProcessesToUpdate, System};
+
+/// Lock file next to the pid file, held for the lifetime of a server.
+pub fn lock_path(pid_file: &str) -> PathBuf {
+    PathBuf::from(format!("{pid_file}.lock"))
+}
+
+/// An exclusively created lock file holding the owner's pid; removed on drop.
+#[derive(Debug)]
+pub struct InstanceLock {
+    path: PathBuf,
+}
+
+impl InstanceLock {
+    /// Create the lock file, replacing it if the process that created it is
+    /// gone. Fails when another live server holds it.
+    pub fn acquire(path: &Path) -> Result<Self, String> {
+        for _ in 0..2 {
+            match fs::OpenOptions::new().write(true).create_new(true).open(path) {
+                Ok(mut file) => {
+                    file.write_all(std::process::id().to_string().as_bytes())
+                        .map_err(|e| format!("Could not write lock file {}: {e}", path.display()))?;
+                    return Ok(Self {
+                        path: path.to_path_buf(),
+                    });
+                }
+                Err(err) if err.kind() == ErrorKind::AlreadyExists => {
+                    if let Some(owner) = live_owner(path) {
+                        return Err(format!(
+                            "Another instance is already running (pid {owner}, lock {})",
+                            path.display()
+                        ));
+                    }
+                    // Stale lock from a server that did not shut down cleanly.
+                    let _ = fs::remove_file(path);
+                }
+                Err(err) => {
+                    return Err(format!("Could not create lock file {}: {err}", path.display()));
+                }
+            }
+        }
+        Err(format!("Could not acquire lock file {}", path.display()))
+    }
+}
+
+impl Drop for InstanceLock {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+/// Pid recorded in a lock file, if that process is still running.
+fn live_owner(path: &Path) -> Option<u32> {
+    let pid: u32 = fs::read_to_string(path).ok()?.trim().parse().ok()?;
+    let mut sys = System::new();
+    sys.refresh_processes(ProcessesToUpdate::Some(&[Pid::from_u32(pid)]), true);
+    sys.process(Pid::from_u32(pid)).map(|_| pid)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lock_is_exclusive_and_recovers_stale() {
+        let path = std::env::temp_dir().join(format!("mcp-lock-test-{}.lock", std::process::id()));
+        let _ = fs::remove_file(&path);
+
+        let lock = InstanceLock::acquire(&path).unwrap();
+        assert!(InstanceLock::acquire(&path).is_err());
+        drop(lock);
+        assert!(!path.exists());
+
+        // A pid that cannot be running counts as stale.
+        fs::write(&path, u32::MAX.to_string()).unwrap();
+        let lock = InstanceLock::acquire(&path).unwrap();
+        drop(lock);
+    }
+}
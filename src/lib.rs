@@ -1,8 +1,26 @@
 pub mod cli;
+pub mod client_config;
+pub mod config;
+pub mod connections;
+pub mod doctor;
 pub mod error;
+pub mod generate_cli;
 pub mod handler;
+pub mod instance;
+pub mod log_file;
+pub mod logging;
 pub mod macros;
 pub mod metadata;
+pub mod metrics;
+pub mod paths;
+pub mod policy;
+pub mod probe;
+pub mod project_runner;
+pub mod secrets;
 pub mod server;
+pub mod settings;
+pub mod tool_cli;
 pub mod tools;
+pub mod validate_cli;
+pub mod watch;
 pub mod payload_tools;
\ No newline at end of file
@@ -0,0 +1,116 @@
+//! Rotating log file for servers running in the background, and lookup of
+//! the current file for the `logs` command.
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use tracing::Subscriber;
+use tracing_appender::{
+    non_blocking::WorkerGuard,
+    rolling::{Builder, Rotation},
+};
+use tracing_subscriber::{EnvFilter, Layer, registry::LookupSpan};
+
+use crate::cli::{CommandArguments, LogFormat, LogRotation};
+
+/// Directory and file name prefix of a configured log path.
+fn split_path(log_file: &str) -> (PathBuf, String) {
+    let path = Path::new(log_file);
+    let dir = path
+        .parent()
+        .filter(|dir| !dir.as_os_str().is_empty())
+        .unwrap_or(Path::new("."));
+    let prefix = path
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    (dir.to_path_buf(), prefix)
+}
+
+type BoxedLayer<S> = Box<dyn Layer<S> + Send + Sync>;
+
+/// A layer writing events to the configured log file, or `None` when file
+/// logging is disabled. Keep the guard alive until exit so buffered lines are
+/// flushed.
+pub fn layer<S>(
+    args: &CommandArguments,
+    filter: EnvFilter,
+) -> Result<Option<(BoxedLayer<S>, WorkerGuard)>, String>
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    if args.log_file.trim().is_empty() {
+        return Ok(None);
+    }
+    let (dir, prefix) = split_path(&args.log_file);
+    fs::create_dir_all(&dir)
+        .map_err(|e| format!("Could not create log directory {}: {e}", dir.display()))?;
+    let mut builder = Builder::new()
+        .rotation(match args.log_rotation {
+            LogRotation::Hourly => Rotation::HOURLY,
+            LogRotation::Daily => Rotation::DAILY,
+            LogRotation::Never => Rotation::NEVER,
+        })
+        .filename_prefix(prefix);
+    if args.log_max_files > 0 {
+        builder = builder.max_log_files(args.log_max_files);
+    }
+    let appender = builder
+        .build(&dir)
+        .map_err(|e| format!("Could not open log file {}: {e}", args.log_file))?;
+    let (writer, guard) = tracing_appender::non_blocking(appender);
+
+    let fmt = tracing_subscriber::fmt::layer()
+        .with_writer(writer)
+        .with_ansi(false);
+    let layer = match args.log_format {
+        LogFormat::Json => fmt.json().with_filter(filter).boxed(),
+        LogFormat::Pretty => fmt.with_filter(filter).boxed(),
+    };
+    Ok(Some((layer, guard)))
+}
+
+/// The log file currently being written for the configured path: its newest
+/// dated sibling when rotation is on, otherwise the path itself (which also
+/// receives a daemon's raw stdout/stderr).
+pub fn current(log_file: &str) -> Option<PathBuf> {
+    let (dir, prefix) = split_path(log_file);
+    let dated = format!("{prefix}.");
+    let newest_dated = fs::read_dir(&dir)
+        .ok()?
+        .filter_map(Result::ok)
+        .filter(|entry| entry.file_name().to_string_lossy().starts_with(&dated))
+        .filter_map(|entry| {
+            let modified = entry.metadata().ok()?.modified().ok()?;
+            Some((modified, entry.path()))
+        })
+        .max_by_key(|(modified, _)| *modified)
+        .map(|(_, path)| path);
+    newest_dated.or_else(|| {
+        let base = dir.join(&prefix);
+        base.is_file().then_some(base)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_current_picks_newest_rotation() {
+        let dir = std::env::temp_dir().join(format!("mcp-log-test-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let base = dir.join("server.log");
+        fs::write(dir.join("server.log.2026-01-01"), "old").unwrap();
+        std::thread::sleep(std::time::Duration::from_millis(20));
+        fs::write(dir.join("server.log.2026-01-02"), "new").unwrap();
+        fs::write(dir.join("other.log"), "unrelated").unwrap();
+        fs::write(&base, "daemon stderr").unwrap();
+
+        let current = current(base.to_str().unwrap()).unwrap();
+        assert_eq!(current, dir.join("server.log.2026-01-02"));
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
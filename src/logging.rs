@@ -0,0 +1,103 @@
+//! Forwarding of `tracing` events to MCP clients.
+//!
+//! [`McpLogLayer`] publishes this crate's events on a broadcast channel. Each
+//! session that calls `logging/setLevel` subscribes to the channel and relays
+//! events at or above its level as `notifications/message`.
+
+use std::sync::OnceLock;
+
+use rmcp::model::LoggingLevel;
+use tokio::sync::broadcast;
+use tracing::{
+    Event, Level, Subscriber,
+    field::{Field, Visit},
+};
+use tracing_subscriber::{
+    filter::filter_fn,
+    layer::{Context, Layer},
+    registry::LookupSpan,
+};
+
+const CHANNEL_CAPACITY: usize = 256;
+
+#[derive(Clone, Debug)]
+pub struct LogRecord {
+    pub level: LoggingLevel,
+    pub target: String,
+    pub message: String,
+}
+
+fn channel() -> &'static broadcast::Sender<LogRecord> {
+    static CHANNEL: OnceLock<broadcast::Sender<LogRecord>> = OnceLock::new();
+    CHANNEL.get_or_init(|| broadcast::channel(CHANNEL_CAPACITY).0)
+}
+
+/// Receive every record published after this call.
+pub fn subscribe() -> broadcast::Receiver<LogRecord> {
+    channel().subscribe()
+}
+
+/// The relay layer, limited to events from this crate.
+pub fn layer<S>() -> impl Layer<S>
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    McpLogLayer.with_filter(filter_fn(|metadata| {
+        metadata.target().starts_with(env!("CARGO_CRATE_NAME"))
+    }))
+}
+
+/// `tracing` layer that publishes events to MCP sessions.
+pub struct McpLogLayer;
+
+impl<S: Subscriber> Layer<S> for McpLogLayer {
+    fn on_event(&self, event: &Event<'_>, _ctx: Context<'_, S>) {
+        let metadata = event.metadata();
+        let sender = channel();
+        if sender.receiver_count() == 0 {
+            return;
+        }
+        let mut visitor = MessageVisitor(String::new());
+        event.record(&mut visitor);
+        let _ = sender.send(LogRecord {
+            level: to_mcp_level(metadata.level()),
+            target: metadata.target().to_string(),
+            message: visitor.0,
+        });
+    }
+}
+
+struct MessageVisitor(String);
+
+impl Visit for MessageVisitor {
+    fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+        if field.name() == "message" {
+            self.0.insert_str(0, &format!("{value:?}"));
+        } else {
+            self.0.push_str(&format!(" {}={value:?}", field.name()));
+        }
+    }
+}
+
+fn to_mcp_level(level: &Level) -> LoggingLevel {
+    match *level {
+        Level::ERROR => LoggingLevel::Error,
+        Level::WARN => LoggingLevel::Warning,
+        Level::INFO => LoggingLevel::Info,
+        Level::DEBUG | Level::TRACE => LoggingLevel::Debug,
+    }
+}
+
+/// Severity rank of an MCP level, from `debug` (0) to `emergency` (7).
+pub fn level_rank(level: LoggingLevel) -> u8 {
+    match level {
+        LoggingLevel::Debug => 0,
+        LoggingLevel::Info => 1,
+        LoggingLevel::Notice => 2,
+        LoggingLevel::Warning => 3,
+        LoggingLevel::Error => 4,
+        LoggingLevel::Critical => 5,
+        LoggingLevel::Alert => 6,
+        LoggingLevel::Emergency => 7,
+    }
+}
@@ -1,109 +1,130 @@
 use std::{
     fs,
-    io::{self, Write},
+    io::{self, Read, Seek, SeekFrom, Write},
     path::Path,
-    process::Command as ProcessCommand,
+    time::{Duration, Instant},
 };
 
-use clap::Parser;
+use clap::{CommandFactory, FromArgMatches, Parser};
 use colored::Colorize;
-use dialoguer::{Confirm, Input, Select, theme::ColorfulTheme};
-use mcp_payloadcms_rs::{cli, metadata, server};
-use serde::{Deserialize, Serialize};
-use sysinfo::{Pid, System};
-
-const SETTINGS_PATH: &str = "settings.json";
-
-fn init_tracing() {
-    let env_filter = tracing_subscriber::EnvFilter::try_from_default_env()
-        .unwrap_or_else(|_| tracing_subscriber::EnvFilter::new("info"));
-    let _ = tracing_subscriber::fmt()
-        .with_env_filter(env_filter)
-        .with_target(false)
-        .with_writer(std::io::stderr)
+use dialoguer::{Confirm, Input, MultiSelect, Select, theme::ColorfulTheme};
+use mcp_payloadcms_rs::{cli, client_config, config, connections, doctor, generate_cli, log_file, logging, metadata, paths, probe, secrets, server, tool_cli, validate_cli, watch};
+use mcp_payloadcms_rs::settings::{SettingsFile, SettingsIssue, read_settings};
+use mcp_payloadcms_rs::payload_tools::{
+    naming,
+    plugins::plugin_names,
+    scaffolder::{CollectionOption, FieldOption, ScaffoldOptions, validate_scaffold_options},
+    schemas::field_type_names,
+    self_check::self_check,
+    template_overrides,
+    types::FileType,
+};
+use sysinfo::{Pid, ProcessesToUpdate, System};
+use tracing_appender::non_blocking::WorkerGuard;
+
+const PROBE_TIMEOUT: Duration = Duration::from_secs(2);
+const DAEMON_READY_TIMEOUT: Duration = Duration::from_secs(15);
+
+fn init_tracing(args: Option<&cli::CommandArguments>) -> Option<WorkerGuard> {
+    use tracing_subscriber::{Layer, layer::SubscriberExt, util::SubscriberInitExt};
+
+    let env_filter = || {
+        tracing_subscriber::EnvFilter::try_from_default_env()
+            .unwrap_or_else(|_| tracing_subscriber::EnvFilter::new("info"))
+    };
+    let (file_layer, guard) = match args.map(|args| log_file::layer(args, env_filter())) {
+        Some(Ok(Some((layer, guard)))) => (Some(layer), Some(guard)),
+        Some(Err(err)) => {
+            eprintln!("{}", format!("Warning: {err}").yellow());
+            (None, None)
+        }
+        _ => (None, None),
+    };
+    // A daemon's stderr is appended to the log file already being written.
+    let daemonized = args.is_some_and(|args| args.daemon_child) && file_layer.is_some();
+    let fmt_layer = (!daemonized).then(|| {
+        tracing_subscriber::fmt::layer()
+            .with_target(false)
+            .with_writer(std::io::stderr)
+            .with_filter(env_filter())
+    });
+    // MCP clients pick their own level with `logging/setLevel`, so the relay
+    // layer is not bound by RUST_LOG.
+    let _ = tracing_subscriber::registry()
+        .with(file_layer)
+        .with(fmt_layer)
+        .with(logging::layer())
         .try_init();
+    guard
 }
 
 #[tokio::main]
 async fn main() {
-    init_tracing();
-    let cli = cli::Cli::parse();
+    let matches = cli::Cli::command().get_matches();
+    let cli = cli::Cli::from_arg_matches(&matches).unwrap_or_else(|err| err.exit());
+    let config_path = paths::settings_path(cli.config.as_deref());
+    if cli.config.is_none() {
+        match paths::migrate_legacy_settings(&config_path) {
+            Ok(Some(legacy)) => eprintln!(
+                "{} moved {} to {}",
+                "Settings".blue().bold(),
+                legacy.display(),
+                config_path.display()
+            ),
+            Ok(None) => {}
+            Err(err) => eprintln!("{}", format!("Warning: {err}").yellow()),
+        }
+    }
+    paths::set_active_settings_path(config_path);
+    // Only a server writes the log file; other commands log to stderr.
+    let _log_guard = match &cli.command {
+        cli::Command::Start(args) if args.foreground => {
+            init_tracing(Some(&resolve_config(matches.subcommand_matches("start")).args))
+        }
+        _ => init_tracing(None),
+    };
+
+    // Commands that generate or validate code outside a server use the
+    // overrides and naming profile from settings.
+    if matches!(
+        cli.command,
+        cli::Command::Tool(_)
+            | cli::Command::Validate(_)
+            | cli::Command::Watch(_)
+            | cli::Command::Generate(_)
+            | cli::Command::Scaffold(_)
+    ) {
+        let settings = effective_settings();
+        template_overrides::configure(&settings.templates_dir);
+        naming::configure(&settings.naming_profile, &settings.naming_profiles);
+    }
 
     match cli.command {
         cli::Command::Start(args) => {
+            let config = resolve_config(matches.subcommand_matches("start"));
             if !args.foreground {
-                let mut child_args: Vec<String> = std::env::args().skip(1).collect();
-                if !child_args.iter().any(|a| a == "--foreground") {
-                    child_args.push("--foreground".to_string());
-                }
-                match std::process::Command::new(std::env::current_exe().unwrap())
-                    .args(child_args)
-                    .spawn()
-                {
-                    Ok(child) => {
-                        eprintln!(
-                            "{} started in background (pid {})",
-                            "OK".green().bold(),
-                            child.id()
-                        );
-                    }
-                    Err(err) => {
-                        eprintln!("{}", format!("Failed to spawn server: {err}").red().bold())
-                    }
-                }
+                daemonize(&config.args);
                 return;
             }
-            let mut effective = load_settings();
-            overlay_args(&mut effective, &args);
+            let effective = config.args.clone();
             if let Err(err) = effective.validate() {
                 eprintln!("{}", format!("Error: {err}").red().bold());
                 return;
             };
 
-            let has_network = effective.enable_tcp
-                || effective.enable_unix
-                || effective.enable_http
-                || effective.enable_sse
-                || effective.enable_ws;
-            if has_network {
-                if let Some(true) = running_status(&effective) {
-                    eprintln!(
-                        "{} {}",
-                        "Error:".red().bold(),
-                        "another instance is already running (non-stdio transport enabled)"
-                    );
-                    eprintln!(
-                        "Stop the existing server or disable network transports to run stdio-only."
-                    );
-                    return;
-                }
-            }
-
-            if let Err(error) = server::start_server(effective.clone()).await {
+            if let Err(error) = server::start_server(config).await {
                 eprintln!("{}", format!("{error}").red().bold());
             } else {
-                if let Err(err) = fs::write(&effective.pid_file, format!("{}", std::process::id()))
-                {
-                    eprintln!(
-                        "{}",
-                        format!("Warning: could not write pid file: {err}").yellow()
-                    );
-                } else {
-                    eprintln!(
-                        "{} pid file written to {}",
-                        "OK".green().bold(),
-                        &effective.pid_file
-                    );
-                }
+                eprintln!("{} server stopped", "OK".green().bold());
                 save_settings(&effective);
             }
         }
         cli::Command::Status => {
-            let args = load_settings();
+            let args = effective_settings();
             status_report(&args);
         }
         cli::Command::Shutdown => {
-            let args = load_settings();
+            let args = effective_settings();
             shutdown_server(&args);
         }
         cli::Command::Version => {
@@ -115,68 +136,178 @@ async fn main() {
             );
         }
 
-        cli::Command::Setup => {
+        cli::Command::Setup(setup) => {
             let mut args = load_settings();
-            interactive_setup(&mut args);
-            save_settings(&args);
+            if interactive_setup(&mut args, &setup) {
+                save_settings(&args);
+            }
         }
-        cli::Command::Config => {
-            let mut args = load_settings();
-            config_tui(&mut args);
-            save_settings(&args);
+        cli::Command::Config(config) => match config.command {
+            Some(cli::ConfigCommand::Validate) => validate_settings_file(),
+            Some(cli::ConfigCommand::Explain(explain)) => {
+                let explain_matches = matches
+                    .subcommand_matches("config")
+                    .and_then(|config| config.subcommand_matches("explain"));
+                explain_config(&resolve_config(explain_matches), explain.format);
+            }
+            None => {
+                let mut args = load_settings();
+                config_tui(&mut args);
+                save_settings(&args);
+            }
+        },
+        cli::Command::Logs(logs) => {
+            let args = effective_settings();
+            tail_logs(&args, &logs);
         }
+        cli::Command::Secret(secret) => run_secret_command(&secret),
+        cli::Command::Connection(connection) => run_connection_command(&connection),
+        cli::Command::Doctor => run_doctor(),
+        cli::Command::Tool(tool) => run_tool_command(tool).await,
+        cli::Command::Validate(validate) => run_validate_command(&validate),
+        cli::Command::Watch(watch) => run_watch_command(watch),
+        cli::Command::Generate(generate) => print_or_exit(generate_cli::generate(&generate)),
+        cli::Command::Scaffold(scaffold) => print_or_exit(generate_cli::scaffold(&scaffold)),
     };
 }
 
-#[derive(Debug, Serialize, Deserialize, Default)]
-struct SettingsFile {
-    server_name: Option<String>,
-    server_description: Option<String>,
-    enable_stdio: Option<bool>,
-    enable_tcp: Option<bool>,
-    enable_unix: Option<bool>,
-    enable_http: Option<bool>,
-    enable_sse: Option<bool>,
-    enable_ws: Option<bool>,
-    tcp_addr: Option<String>,
-    http_addr: Option<String>,
-    sse_addr: Option<String>,
-    ws_addr: Option<String>,
-    unix_path: Option<String>,
-    pid_file: Option<String>,
+fn settings_path() -> &'static Path {
+    paths::active_settings_path()
+}
+
+/// The settings file; `None` when there is none.
+fn try_read_settings() -> Result<Option<SettingsFile>, Vec<SettingsIssue>> {
+    match fs::read_to_string(settings_path()) {
+        Ok(contents) => read_settings(&contents).map(Some),
+        Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(None),
+        Err(err) => Err(vec![SettingsIssue {
+            path: String::new(),
+            message: format!("could not read the file: {err}"),
+        }]),
+    }
+}
+
+/// Settings from the settings file over the defaults; defaults when there
+/// is no file.
+fn try_load_settings() -> Result<cli::CommandArguments, Vec<SettingsIssue>> {
+    let defaults = cli::CommandArguments::default_settings();
+    try_read_settings().map(|file| match file {
+        Some(settings) => settings.apply(defaults),
+        None => defaults,
+    })
+}
+
+fn print_settings_issues(issues: &[SettingsIssue]) {
+    eprintln!(
+        "{}",
+        format!("Error: invalid settings in {}", settings_path().display()).red().bold()
+    );
+    for issue in issues {
+        eprintln!("  {issue}");
+    }
 }
 
+/// The settings file as [`try_read_settings`]; exits when it is invalid
+/// rather than running with settings the user didn't ask for.
+fn read_settings_or_exit() -> Option<SettingsFile> {
+    try_read_settings().unwrap_or_else(|issues| {
+        print_settings_issues(&issues);
+        eprintln!(
+            "{} fix the file, check it with `config validate`, or remove it to use the defaults",
+            "hint:".cyan()
+        );
+        std::process::exit(1);
+    })
+}
+
+/// The settings file over the defaults, for commands that edit the file;
+/// environment variables and flags are left out so they aren't saved.
 fn load_settings() -> cli::CommandArguments {
     let defaults = cli::CommandArguments::default_settings();
-    if let Ok(contents) = fs::read_to_string(SETTINGS_PATH) {
-        if let Ok(settings) = serde_json::from_str::<SettingsFile>(&contents) {
-            return apply_settings(settings, defaults);
+    match read_settings_or_exit() {
+        Some(settings) => settings.apply(defaults),
+        None => defaults,
+    }
+}
+
+/// Every configuration layer. `matches` holds the command's server flags;
+/// without them the environment is the top layer.
+fn resolve_config(matches: Option<&clap::ArgMatches>) -> config::Config {
+    let file = read_settings_or_exit();
+    let resolved = match matches {
+        Some(matches) => config::resolve(file.as_ref(), settings_path(), matches),
+        None => config::env_matches()
+            .and_then(|matches| config::resolve(file.as_ref(), settings_path(), &matches)),
+    };
+    resolved.unwrap_or_else(|err| {
+        eprintln!("{}", format!("Error: {err}").red().bold());
+        std::process::exit(1);
+    })
+}
+
+/// Settings of commands that run against a server or its configuration.
+fn effective_settings() -> cli::CommandArguments {
+    resolve_config(None).args
+}
+
+/// Print each setting with the layer that supplied it.
+fn explain_config(config: &config::Config, format: cli::OutputFormat) {
+    match format {
+        cli::OutputFormat::Pretty => println!("{}", config::explain(&config.settings)),
+        cli::OutputFormat::Json => println!(
+            "{}",
+            serde_json::to_string_pretty(&config.settings).unwrap_or_default()
+        ),
+    }
+}
+
+/// Check the settings file and the settings it resolves to.
+fn validate_settings_file() {
+    let path = settings_path();
+    if !path.exists() {
+        eprintln!(
+            "{} no settings file at {}; the defaults apply",
+            "OK".green().bold(),
+            path.display()
+        );
+        return;
+    }
+    match try_load_settings() {
+        Ok(args) => match args.validate() {
+            Ok(()) => eprintln!("{} {} is valid", "OK".green().bold(), path.display()),
+            Err(err) => {
+                print_settings_issues(&[SettingsIssue {
+                    path: String::new(),
+                    message: err,
+                }]);
+                std::process::exit(1);
+            }
+        },
+        Err(issues) => {
+            print_settings_issues(&issues);
+            std::process::exit(1);
         }
     }
-    defaults
 }
 
 fn save_settings(args: &cli::CommandArguments) {
-    let settings = SettingsFile {
-        server_name: Some(args.server_name.clone()),
-        server_description: Some(args.server_description.clone()),
-        enable_stdio: Some(args.enable_stdio),
-        enable_tcp: Some(args.enable_tcp),
-        enable_unix: Some(args.enable_unix),
-        enable_http: Some(args.enable_http),
-        enable_sse: Some(args.enable_sse),
-        enable_ws: Some(args.enable_ws),
-        tcp_addr: Some(args.tcp_addr.clone()),
-        http_addr: Some(args.http_addr.clone()),
-        sse_addr: Some(args.sse_addr.clone()),
-        ws_addr: Some(args.ws_addr.clone()),
-        unix_path: Some(args.unix_path.clone()),
-        pid_file: Some(args.pid_file.clone()),
-    };
-    if let Err(err) = fs::write(
-        SETTINGS_PATH,
-        serde_json::to_string_pretty(&settings).unwrap_or_default(),
-    ) {
+    let settings = SettingsFile::from(args);
+    let path = settings_path();
+    let mut value = serde_json::to_value(&settings).unwrap_or_default();
+    // Keep `${...}` references from the file instead of their values.
+    if let Some(raw) = fs::read_to_string(path)
+        .ok()
+        .and_then(|contents| serde_json::from_str(&contents).ok())
+    {
+        secrets::preserve_references(&raw, &mut value);
+        // Connections are edited in place by `connection` and `add_connection`.
+        if let (Some(section), Some(map)) = (raw.get(connections::SETTINGS_KEY), value.as_object_mut()) {
+            map.insert(connections::SETTINGS_KEY.to_string(), section.clone());
+        }
+    }
+    if let Err(err) = paths::ensure_parent(path).and_then(|()| {
+        fs::write(path, serde_json::to_string_pretty(&value).unwrap_or_default())
+    }) {
         eprintln!(
             "{}",
             format!("Warning: could not persist settings: {err}").yellow()
@@ -185,104 +316,114 @@ fn save_settings(args: &cli::CommandArguments) {
         eprintln!(
             "{} settings saved to {}",
             "OK".green().bold(),
-            SETTINGS_PATH
+            path.display()
         );
     }
 }
 
-fn apply_settings(
-    settings: SettingsFile,
-    mut base: cli::CommandArguments,
-) -> cli::CommandArguments {
-    if let Some(v) = settings.server_name {
-        base.server_name = v;
-    }
-    if let Some(v) = settings.server_description {
-        base.server_description = v;
-    }
-    if let Some(v) = settings.enable_stdio {
-        base.enable_stdio = v;
-    }
-    if let Some(v) = settings.enable_tcp {
-        base.enable_tcp = v;
-    }
-    if let Some(v) = settings.enable_unix {
-        base.enable_unix = v;
-    }
-    if let Some(v) = settings.enable_http {
-        base.enable_http = v;
-    }
-    if let Some(v) = settings.enable_sse {
-        base.enable_sse = v;
-    }
-    if let Some(v) = settings.enable_ws {
-        base.enable_ws = v;
-    }
-    if let Some(v) = settings.tcp_addr {
-        base.tcp_addr = v;
-    }
-    if let Some(v) = settings.http_addr {
-        base.http_addr = v;
+/// Start the server as a detached child and wait until it reports ready by
+/// writing its pid file.
+fn daemonize(effective: &cli::CommandArguments) {
+    use std::process::{Command, Stdio};
+
+    if let Err(err) = effective.validate() {
+        eprintln!("{}", format!("Error: {err}").red().bold());
+        return;
     }
-    if let Some(v) = settings.sse_addr {
-        base.sse_addr = v;
+    let (stdout, stderr) = match daemon_output(&effective.log_file) {
+        Ok(Some(file)) => match file.try_clone() {
+            Ok(clone) => (Stdio::from(file), Stdio::from(clone)),
+            Err(_) => (Stdio::null(), Stdio::null()),
+        },
+        Ok(None) => (Stdio::null(), Stdio::null()),
+        Err(err) => {
+            eprintln!("{}", format!("Error: {err}").red().bold());
+            return;
+        }
+    };
+    let exe = match std::env::current_exe() {
+        Ok(exe) => exe,
+        Err(err) => {
+            eprintln!("{}", format!("Failed to locate executable: {err}").red().bold());
+            return;
+        }
+    };
+    let mut child_args: Vec<String> = std::env::args().skip(1).collect();
+    for flag in ["--foreground", "--daemon-child"] {
+        if !child_args.iter().any(|a| a == flag) {
+            child_args.push(flag.to_string());
+        }
     }
-    if let Some(v) = settings.ws_addr {
-        base.ws_addr = v;
+
+    let mut command = Command::new(exe);
+    command
+        .args(child_args)
+        .stdin(Stdio::null())
+        .stdout(stdout)
+        .stderr(stderr);
+    // Detach from the terminal so its Ctrl+C and hangup do not reach the server.
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::CommandExt;
+        command.process_group(0);
     }
-    if let Some(v) = settings.unix_path {
-        base.unix_path = v;
+    #[cfg(windows)]
+    {
+        use std::os::windows::process::CommandExt;
+        const DETACHED_PROCESS: u32 = 0x0000_0008;
+        const CREATE_NEW_PROCESS_GROUP: u32 = 0x0000_0200;
+        command.creation_flags(DETACHED_PROCESS | CREATE_NEW_PROCESS_GROUP);
     }
-    if let Some(v) = settings.pid_file {
-        base.pid_file = v;
+    let mut child = match command.spawn() {
+        Ok(child) => child,
+        Err(err) => {
+            eprintln!("{}", format!("Failed to spawn server: {err}").red().bold());
+            return;
+        }
+    };
+
+    let expected = child.id().to_string();
+    let deadline = Instant::now() + DAEMON_READY_TIMEOUT;
+    while Instant::now() < deadline {
+        if let Ok(Some(status)) = child.try_wait() {
+            eprintln!(
+                "{}",
+                format!("Server exited during startup ({status}); see `logs`").red().bold()
+            );
+            return;
+        }
+        if fs::read_to_string(&effective.pid_file).is_ok_and(|pid| pid.trim() == expected) {
+            eprintln!(
+                "{} started in background (pid {}), logging to {}",
+                "OK".green().bold(),
+                expected,
+                effective.log_file
+            );
+            return;
+        }
+        std::thread::sleep(Duration::from_millis(100));
     }
-    base
+    eprintln!(
+        "{}",
+        format!("Server (pid {expected}) did not report ready in time; see `logs`").yellow()
+    );
 }
 
-fn overlay_args(target: &mut cli::CommandArguments, overrides: &cli::CommandArguments) {
-    let defaults = cli::CommandArguments::default_settings();
-    if overrides.server_name != defaults.server_name {
-        target.server_name = overrides.server_name.clone();
-    }
-    if overrides.server_description != defaults.server_description {
-        target.server_description = overrides.server_description.clone();
+/// Append-mode handle on the base log file for a daemon's stdout/stderr.
+fn daemon_output(log_file: &str) -> Result<Option<fs::File>, String> {
+    if log_file.trim().is_empty() {
+        return Ok(None);
     }
-    if overrides.enable_stdio != defaults.enable_stdio {
-        target.enable_stdio = overrides.enable_stdio;
-    }
-    if overrides.enable_tcp != defaults.enable_tcp {
-        target.enable_tcp = overrides.enable_tcp;
-    }
-    if overrides.enable_unix != defaults.enable_unix {
-        target.enable_unix = overrides.enable_unix;
-    }
-    if overrides.enable_http != defaults.enable_http {
-        target.enable_http = overrides.enable_http;
-    }
-    if overrides.enable_sse != defaults.enable_sse {
-        target.enable_sse = overrides.enable_sse;
-    }
-    if overrides.enable_ws != defaults.enable_ws {
-        target.enable_ws = overrides.enable_ws;
-    }
-    if overrides.tcp_addr != defaults.tcp_addr {
-        target.tcp_addr = overrides.tcp_addr.clone();
-    }
-    if overrides.http_addr != defaults.http_addr {
-        target.http_addr = overrides.http_addr.clone();
-    }
-    if overrides.sse_addr != defaults.sse_addr {
-        target.sse_addr = overrides.sse_addr.clone();
-    }
-    if overrides.ws_addr != defaults.ws_addr {
-        target.ws_addr = overrides.ws_addr.clone();
-    }
-    if overrides.unix_path != defaults.unix_path {
-        target.unix_path = overrides.unix_path.clone();
-    }
-    if overrides.pid_file != defaults.pid_file {
-        target.pid_file = overrides.pid_file.clone();
+    if let Some(dir) = Path::new(log_file).parent().filter(|dir| !dir.as_os_str().is_empty()) {
+        fs::create_dir_all(dir)
+            .map_err(|e| format!("Could not create log directory {}: {e}", dir.display()))?;
     }
+    fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(log_file)
+        .map(Some)
+        .map_err(|e| format!("Could not open log file {log_file}: {e}"))
 }
 
 fn status_report(args: &cli::CommandArguments) {
@@ -295,16 +436,56 @@ fn status_report(args: &cli::CommandArguments) {
 
     eprintln!("{} {}", state_icon, state_msg);
     eprintln!(
-        "  {} {}",
+        "  {} {} {}",
         "Version:".blue().bold(),
-        format!("{} {}", metadata::PKG_NAME, metadata::PKG_VERSION)
+        metadata::PKG_NAME,
+        metadata::PKG_VERSION
     );
     eprintln!(
-        "  {} {}",
+        "  {} {} -- {}",
         "Server:".blue().bold(),
-        format!("{} -- {}", args.server_name, args.server_description)
+        args.server_name,
+        args.server_description
     );
 
+    let runtime = server::RuntimeInfo::read(&args.runtime_info_file)
+        .filter(|info| running == Some(true) && !info.endpoints.is_empty());
+    if let Some(info) = runtime {
+        eprintln!("  {} {}", "PID:".blue().bold(), info.pid);
+        if let Ok(started) = chrono::DateTime::parse_from_rfc3339(&info.started_at) {
+            let uptime = chrono::Utc::now().signed_duration_since(started);
+            eprintln!(
+                "  {} {}s (since {})",
+                "Uptime:".blue().bold(),
+                uptime.num_seconds().max(0),
+                info.started_at
+            );
+        }
+        let mut reachable = 0;
+        for endpoint in &info.endpoints {
+            match probe::probe(endpoint, PROBE_TIMEOUT) {
+                Some(result) if result.reachable => {
+                    reachable += 1;
+                    eprintln!("    {} {endpoint} {}", "✅".green().bold(), result.detail);
+                }
+                Some(result) => {
+                    eprintln!("    {} {endpoint} {}", "🟥".red().bold(), result.detail.red());
+                }
+                None => eprintln!("    {} {endpoint} (not probed)", "⬜".normal()),
+            }
+        }
+        let probed = info
+            .endpoints
+            .iter()
+            .filter(|endpoint| endpoint.transport != "stdio")
+            .count();
+        eprintln!(
+            "  {}",
+            format!("{reachable}/{probed} probed endpoints reachable").bold()
+        );
+        return;
+    }
+
     match server::TransportState::from_args(args) {
         Ok(transports) => {
             let endpoints = transports.active_endpoints();
@@ -329,114 +510,161 @@ fn status_report(args: &cli::CommandArguments) {
     }
 }
 
-fn interactive_setup(args: &mut cli::CommandArguments) {
+/// Choose a client and transport, enable the transport in the settings and
+/// write the client's MCP configuration. Returns whether the settings should
+/// be saved, which `--print-only` skips along with the client config.
+fn interactive_setup(args: &mut cli::CommandArguments, setup: &cli::SetupArguments) -> bool {
     eprintln!("{}", "Setup assistant".green().bold());
-    let clients = ["vscode", "codex", "gemini", "zed", "roo", "other"];
-    eprintln!("Known clients:");
-    for (i, c) in clients.iter().enumerate() {
-        eprintln!("{:>2}. {}", i + 1, c);
-    }
-    let client_idx = prompt_choice("Select client [1]: ", &clients, 0);
-    let client = clients[client_idx];
+    let client = match &setup.client {
+        Some(name) if name == "other" => None,
+        Some(name) => match name.parse::<client_config::Client>() {
+            Ok(client) => Some(client),
+            Err(err) => {
+                eprintln!("{}", format!("Error: {err}").red().bold());
+                return false;
+            }
+        },
+        None => {
+            let mut clients: Vec<&str> = client_config::Client::ALL
+                .iter()
+                .map(client_config::Client::as_str)
+                .collect();
+            clients.push("other");
+            eprintln!("Known clients:");
+            for (i, c) in clients.iter().enumerate() {
+                eprintln!("{:>2}. {}", i + 1, c);
+            }
+            let client_idx = prompt_choice("Select client [1]: ", &clients, 0);
+            client_config::Client::ALL.get(client_idx).copied()
+        }
+    };
 
-    let transport_options = vec![
-        (
-            "http+streamable-sse",
-            args.enable_http,
-            args.http_addr.as_str(),
-        ),
+    // Only these transports are reachable from MCP clients.
+    let transport_options = [
+        ("http", args.enable_http, args.http_addr.as_str()),
         ("sse", args.enable_sse, args.sse_addr.as_str()),
-        ("tcp", args.enable_tcp, args.tcp_addr.as_str()),
-        ("unix", args.enable_unix, args.unix_path.as_str()),
-        ("stdio", args.enable_stdio, "stdio"),
-        ("ws", args.enable_ws, args.ws_addr.as_str()),
+        ("stdio", args.enable_stdio, "stdin/stdout"),
     ];
-    let default_transport_idx = transport_options
-        .iter()
-        .position(|(name, enabled, _)| {
-            *enabled && (*name == "http+streamable-sse" || *name == "sse")
-        })
-        .or_else(|| {
-            transport_options
+    let transport = match &setup.transport {
+        Some(name) => name.clone(),
+        None => {
+            let default_transport_idx = transport_options
                 .iter()
                 .position(|(_, enabled, _)| *enabled)
-        })
-        .unwrap_or(0);
-
-    eprintln!("Available transports (enabled marked with *):");
-    for (i, (name, enabled, addr)) in transport_options.iter().enumerate() {
-        let mark = if *enabled { "*" } else { " " };
-        let addr_display = if *name == "stdio" {
-            "".to_string()
-        } else {
-            format!(" ({addr})")
-        };
-        eprintln!("{:>2}. [{}] {}{}", i + 1, mark, name, addr_display);
-    }
-    let transport_idx = prompt_choice(
-        &format!("Select transport [{}]: ", default_transport_idx + 1),
-        &transport_options
-            .iter()
-            .map(|(name, _, _)| *name)
-            .collect::<Vec<_>>(),
-        default_transport_idx,
-    );
-    let (transport_name, _, addr) = &transport_options[transport_idx];
+                .unwrap_or(0);
+            eprintln!("Available transports (enabled marked with *):");
+            for (i, (name, enabled, addr)) in transport_options.iter().enumerate() {
+                let mark = if *enabled { "*" } else { " " };
+                eprintln!("{:>2}. [{}] {} ({})", i + 1, mark, name, addr);
+            }
+            let transport_idx = prompt_choice(
+                &format!("Select transport [{}]: ", default_transport_idx + 1),
+                &transport_options
+                    .iter()
+                    .map(|(name, _, _)| *name)
+                    .collect::<Vec<_>>(),
+                default_transport_idx,
+            );
+            transport_options[transport_idx].0.to_string()
+        }
+    };
+    let launch = match client_config::Launch::for_transport(args, &transport) {
+        Ok(launch) => launch,
+        Err(err) => {
+            eprintln!("{}", format!("Error: {err}").red().bold());
+            return false;
+        }
+    };
 
+    let client_name = client.map_or("other", |client| client.as_str());
     eprintln!(
         "\n{} {} using transport {}",
         "Configuring".green().bold(),
-        client,
-        transport_name
+        client_name,
+        transport
     );
-    match *transport_name {
-        "stdio" => args.enable_stdio = true,
-        "unix" => args.enable_unix = true,
-        "http+streamable-sse" => args.enable_http = true,
-        "sse" => args.enable_sse = true,
-        "tcp" => args.enable_tcp = true,
-        "ws" => args.enable_ws = true,
-        _ => {}
-    }
-    match *transport_name {
-        "stdio" => eprintln!("Use stdio mode; no host/port required."),
-        "unix" => eprintln!("Socket path: {}", addr),
-        _ => {
-            if addr.ends_with(":0") {
-                eprintln!("Address: {} (port auto-assigned at runtime)", addr);
-            } else {
-                eprintln!("Address: {}", addr);
+    if !setup.print_only {
+        match transport.as_str() {
+            "http" => args.enable_http = true,
+            "sse" => args.enable_sse = true,
+            _ => args.enable_stdio = true,
+        }
+    }
+
+    let server_name = if args.server_name.trim().is_empty() {
+        metadata::PKG_NAME.to_string()
+    } else {
+        args.server_name.trim().to_string()
+    };
+    let Some(client) = client else {
+        match &launch {
+            client_config::Launch::Stdio { command, args, env } => {
+                eprintln!("Command: {command} {}", args.join(" "));
+                for (key, value) in env {
+                    eprintln!("  {key}={value}");
+                }
             }
+            client_config::Launch::Http(url) => eprintln!("Streamable HTTP URL: {url}"),
+            client_config::Launch::Sse(url) => eprintln!("SSE URL: {url}"),
         }
+        return !setup.print_only;
+    };
+    let snippet = client_config::snippet(client, &server_name, &launch);
+    let path = client.config_path();
+    match &path {
+        Some(path) => eprintln!("{} {}", "Config file:".blue().bold(), path.display()),
+        None => eprintln!("{}", "Config file location unknown on this system".yellow()),
     }
-    if !matches!(*transport_name, "http+streamable-sse" | "sse" | "stdio") {
-        eprintln!(
-            "{} Non-default transport selected; enabling it in settings.",
-            "Note:".yellow()
-        );
+    println!("{snippet}");
+    if setup.print_only {
+        return false;
     }
-    eprintln!(
-        "{} Settings updated. HTTP/SSE/stdio remain preferred defaults unless you disable them.",
-        "Info".blue().bold()
-    );
+
+    let Some(path) = path else {
+        return true;
+    };
+    let write = Confirm::with_theme(&ColorfulTheme::default())
+        .with_prompt(format!("Add {server_name} to {}?", path.display()))
+        .default(true)
+        .interact()
+        .unwrap_or(false);
+    if write {
+        match client_config::install(client, &path, &server_name, &launch) {
+            Ok(()) => eprintln!("{} wrote {}", "OK".green().bold(), path.display()),
+            Err(err) => eprintln!("{}", format!("Error: {err}").red().bold()),
+        }
+    } else {
+        eprintln!("Skipped writing; add the snippet above by hand.");
+    }
+    true
 }
 
 fn config_tui(args: &mut cli::CommandArguments) {
     let theme = ColorfulTheme::default();
     loop {
-        eprintln!("\n{} {}", "Config".green().bold(), "(settings.json)".blue());
         eprintln!(
-            "  transports: stdio={}, http={}, sse={}, tcp={}, unix={}, ws={}",
+            "\n{} {}",
+            "Config".green().bold(),
+            format!("({})", settings_path().display()).blue()
+        );
+        eprintln!(
+            "  transports: stdio={}, http={}, sse={}, tcp={}, unix={}, pipe={}, ws={}",
             args.enable_stdio,
             args.enable_http,
             args.enable_sse,
             args.enable_tcp,
             args.enable_unix,
+            args.enable_pipe,
             args.enable_ws
         );
         eprintln!(
-            "  addresses: http={}, sse={}, tcp={}, ws={}, unix={}",
-            args.http_addr, args.sse_addr, args.tcp_addr, args.ws_addr, args.unix_path
+            "  addresses: http={}, sse={}, tcp={}, ws={}, unix={}, pipe={}",
+            args.http_addr,
+            args.sse_addr,
+            args.tcp_addr,
+            args.ws_addr,
+            args.unix_path,
+            args.pipe_name
         );
         eprintln!("  pid file: {}", args.pid_file);
         eprintln!(
@@ -449,6 +677,7 @@ fn config_tui(args: &mut cli::CommandArguments) {
             "Toggle transports",
             "Edit addresses",
             "Edit pid file",
+            "New project",
             "Save and exit",
             "Exit without saving",
         ];
@@ -474,13 +703,186 @@ fn config_tui(args: &mut cli::CommandArguments) {
                     args.pid_file = pid;
                 }
             }
-            4 => break,
-            5 => return,
+            4 => new_project_wizard(&theme),
+            5 => break,
+            6 => return,
             _ => {}
         }
     }
 }
 
+/// Walk through the scaffold options and write the project, as the
+/// `scaffold_project` tool would. Any cancelled prompt abandons the project.
+fn new_project_wizard(theme: &ColorfulTheme) {
+    eprintln!("{}", "New project".green().bold());
+    let Some(options) = prompt_scaffold_options(theme) else {
+        eprintln!("Cancelled.");
+        return;
+    };
+    if let Err(errors) = validate_scaffold_options(&options) {
+        for err in errors {
+            eprintln!("{}", format!("Error: {err}").red().bold());
+        }
+        return;
+    }
+
+    let Ok(out) = Input::<String>::with_theme(theme)
+        .with_prompt("Output directory")
+        .default(format!("./{}", options.project_name))
+        .interact_text()
+    else {
+        return;
+    };
+    let root = Path::new(&out);
+    let occupied = fs::read_dir(root).is_ok_and(|mut entries| entries.next().is_some());
+    let force = occupied
+        && Confirm::with_theme(theme)
+            .with_prompt(format!("{} is not empty. Overwrite existing files?", root.display()))
+            .default(false)
+            .interact()
+            .unwrap_or(false);
+    if occupied && !force {
+        eprintln!("Skipped writing the project.");
+        return;
+    }
+    match generate_cli::write_project(&options, root, force) {
+        Ok(report) => eprintln!("{} {report}", "OK".green().bold()),
+        Err(err) => eprintln!("{}", format!("Error: {err}").red().bold()),
+    }
+}
+
+fn prompt_scaffold_options(theme: &ColorfulTheme) -> Option<ScaffoldOptions> {
+    let project_name: String = Input::with_theme(theme)
+        .with_prompt("Project name")
+        .validate_with(|name: &String| {
+            if name.trim().is_empty() {
+                Err("Project name is required")
+            } else {
+                Ok(())
+            }
+        })
+        .interact_text()
+        .ok()?;
+    let description: String = Input::with_theme(theme)
+        .with_prompt("Description")
+        .allow_empty(true)
+        .interact_text()
+        .ok()?;
+    let databases = ["mongodb", "postgres"];
+    let database = Select::with_theme(theme)
+        .with_prompt("Database")
+        .items(databases)
+        .default(0)
+        .interact()
+        .ok()?;
+
+    let presets: Vec<String> = generate_cli::SCAFFOLD_PRESETS
+        .iter()
+        .map(|(name, description)| format!("{name} - {description}"))
+        .collect();
+    let preset = Select::with_theme(theme)
+        .with_prompt("Start from")
+        .items(&presets)
+        .default(0)
+        .interact()
+        .ok()?;
+    let (mut collections, globals) = generate_cli::preset_options(generate_cli::SCAFFOLD_PRESETS[preset].0);
+    eprintln!(
+        "Collections: {}",
+        collections
+            .iter()
+            .map(|collection| collection.name.as_str())
+            .collect::<Vec<_>>()
+            .join(", ")
+    );
+    while Confirm::with_theme(theme)
+        .with_prompt("Add a collection?")
+        .default(false)
+        .interact()
+        .ok()?
+    {
+        collections.push(prompt_collection(theme)?);
+    }
+
+    let plugins: Vec<&str> = plugin_names().collect();
+    let chosen = MultiSelect::with_theme(theme)
+        .with_prompt("Plugins (space to select, enter to confirm)")
+        .items(&plugins)
+        .interact()
+        .ok()?;
+    let include_tests = Confirm::with_theme(theme)
+        .with_prompt("Include integration tests?")
+        .default(false)
+        .interact()
+        .ok()?;
+
+    Some(ScaffoldOptions {
+        project_name: project_name.trim().to_string(),
+        description: Some(description).filter(|description| !description.trim().is_empty()),
+        database: Some(databases[database].to_string()),
+        collections: Some(collections),
+        globals: (!globals.is_empty()).then_some(globals),
+        plugins: (!chosen.is_empty()).then(|| chosen.iter().map(|&i| plugins[i].to_string()).collect()),
+        include_tests: Some(include_tests),
+        ..Default::default()
+    })
+}
+
+fn prompt_collection(theme: &ColorfulTheme) -> Option<CollectionOption> {
+    let name: String = Input::with_theme(theme)
+        .with_prompt("Collection slug")
+        .validate_with(|name: &String| {
+            if name.trim().is_empty() {
+                Err("Slug is required")
+            } else {
+                Ok(())
+            }
+        })
+        .interact_text()
+        .ok()?;
+    let auth = Confirm::with_theme(theme)
+        .with_prompt("Auth collection?")
+        .default(false)
+        .interact()
+        .ok()?;
+    let field_types: Vec<&str> = field_type_names().collect();
+    let mut fields = Vec::new();
+    while Confirm::with_theme(theme)
+        .with_prompt(format!("Add a field to {}?", name.trim()))
+        .default(fields.is_empty())
+        .interact()
+        .ok()?
+    {
+        let field_name: String = Input::with_theme(theme)
+            .with_prompt("Field name")
+            .interact_text()
+            .ok()?;
+        let field_type = Select::with_theme(theme)
+            .with_prompt("Field type")
+            .items(&field_types)
+            .default(0)
+            .interact()
+            .ok()?;
+        let required = Confirm::with_theme(theme)
+            .with_prompt("Required?")
+            .default(false)
+            .interact()
+            .ok()?;
+        fields.push(FieldOption {
+            name: field_name.trim().to_string(),
+            field_type: field_types[field_type].to_string(),
+            required: required.then_some(true),
+            ..Default::default()
+        });
+    }
+    Some(CollectionOption {
+        name: name.trim().to_string(),
+        fields: Some(fields),
+        auth: auth.then_some(true),
+        ..Default::default()
+    })
+}
+
 fn edit_server_metadata(args: &mut cli::CommandArguments, theme: &ColorfulTheme) {
     if let Ok(name) = Input::with_theme(theme)
         .with_prompt("Server name")
@@ -505,6 +907,7 @@ fn toggle_transports(args: &mut cli::CommandArguments, theme: &ColorfulTheme) {
         ("sse", &mut args.enable_sse),
         ("tcp", &mut args.enable_tcp),
         ("unix", &mut args.enable_unix),
+        ("pipe", &mut args.enable_pipe),
         ("ws", &mut args.enable_ws),
     ];
     for (name, flag) in toggles {
@@ -554,6 +957,13 @@ fn edit_addresses(args: &mut cli::CommandArguments, theme: &ColorfulTheme) {
     {
         args.unix_path = val;
     }
+    if let Ok(val) = Input::with_theme(theme)
+        .with_prompt("Named pipe")
+        .default(args.pipe_name.clone())
+        .interact_text()
+    {
+        args.pipe_name = val;
+    }
 }
 
 fn prompt_choice(prompt: &str, options: &[&str], default_idx: usize) -> usize {
@@ -572,30 +982,72 @@ fn prompt_choice(prompt: &str, options: &[&str], default_idx: usize) -> usize {
 
 fn shutdown_server(args: &cli::CommandArguments) {
     let pid_path = Path::new(&args.pid_file);
-    match fs::read_to_string(pid_path) {
-        Ok(contents) => match contents.trim().parse::<i32>() {
-            Ok(pid) => {
-                eprintln!(
-                    "{} sending shutdown to pid {}",
-                    "Shutdown".yellow().bold(),
-                    pid
-                );
-                if let Err(err) = ProcessCommand::new("kill").arg(pid.to_string()).status() {
-                    eprintln!(
-                        "{}",
-                        format!("Failed to signal process: {err}").red().bold()
-                    );
-                }
+    let pid = match fs::read_to_string(pid_path) {
+        Ok(contents) => match contents.trim().parse::<u32>() {
+            Ok(pid) => Pid::from_u32(pid),
+            Err(_) => {
+                eprintln!("{}", "Invalid pid file contents.".red().bold());
+                return;
             }
-            Err(_) => eprintln!("{}", "Invalid pid file contents.".red().bold()),
         },
-        Err(err) => eprintln!(
-            "{}",
-            format!("Could not read pid file {}: {err}", pid_path.display())
-                .red()
-                .bold()
-        ),
+        Err(err) => {
+            eprintln!(
+                "{}",
+                format!("Could not read pid file {}: {err}", pid_path.display())
+                    .red()
+                    .bold()
+            );
+            return;
+        }
+    };
+
+    let mut sys = System::new();
+    sys.refresh_processes(ProcessesToUpdate::Some(&[pid]), true);
+    let Some(process) = sys.process(pid) else {
+        eprintln!("{} pid {} is not running", "Shutdown".yellow().bold(), pid);
+        let _ = fs::remove_file(pid_path);
+        return;
+    };
+    eprintln!(
+        "{} sending shutdown to pid {}",
+        "Shutdown".yellow().bold(),
+        pid
+    );
+    if let Err(err) = request_stop(process, args) {
+        eprintln!("{}", format!("Failed to signal process: {err}").red().bold());
+        return;
     }
+
+    // Allow the drain period plus time to close connections.
+    let deadline = Instant::now() + Duration::from_secs(args.shutdown_timeout_secs + 10);
+    while Instant::now() < deadline {
+        std::thread::sleep(Duration::from_millis(200));
+        sys.refresh_processes(ProcessesToUpdate::Some(&[pid]), true);
+        if sys.process(pid).is_none() {
+            eprintln!("{} server stopped", "OK".green().bold());
+            return;
+        }
+    }
+    eprintln!(
+        "{}",
+        format!("pid {pid} is still running after the shutdown timeout").yellow()
+    );
+}
+
+/// Ask the server to drain and exit: SIGTERM on unix, a stop file elsewhere
+/// (Windows cannot deliver console signals to another process group).
+#[cfg(unix)]
+fn request_stop(process: &sysinfo::Process, _args: &cli::CommandArguments) -> Result<(), String> {
+    match process.kill_with(sysinfo::Signal::Term) {
+        Some(true) => Ok(()),
+        _ => Err("SIGTERM could not be delivered".to_string()),
+    }
+}
+
+#[cfg(not(unix))]
+fn request_stop(_process: &sysinfo::Process, args: &cli::CommandArguments) -> Result<(), String> {
+    let stop_file = server::stop_file_path(&args.pid_file);
+    fs::write(&stop_file, "").map_err(|e| format!("could not write {stop_file}: {e}"))
 }
 
 fn running_status(args: &cli::CommandArguments) -> Option<bool> {
@@ -623,3 +1075,298 @@ fn running_status(args: &cli::CommandArguments) -> Option<bool> {
     }
     Some(false)
 }
+
+/// Print the output of `tool`; exits with status 1 when the call fails or the
+/// tool reports an error.
+async fn run_tool_command(tool: cli::ToolArguments) {
+    let output = match &tool.command {
+        cli::ToolCommand::List => Ok((tool_cli::list(tool.format), false)),
+        cli::ToolCommand::Schema { name } => {
+            tool_cli::schema(name, tool.format).map(|schema| (schema, false))
+        }
+        cli::ToolCommand::Call(raw) => {
+            let call = match cli::ToolCallArguments::try_parse_from(
+                std::iter::once("tool".to_string()).chain(raw.iter().cloned()),
+            ) {
+                Ok(call) => call,
+                Err(err) => err.exit(),
+            };
+            tool_cli::call(&call, call.format.unwrap_or(tool.format)).await
+        }
+    };
+    match output {
+        Ok((text, false)) => println!("{text}"),
+        Ok((text, true)) => {
+            println!("{text}");
+            std::process::exit(1);
+        }
+        Err(err) => {
+            eprintln!("{}", format!("Error: {err}").red().bold());
+            std::process::exit(1);
+        }
+    }
+}
+
+fn run_secret_command(secret: &cli::SecretArguments) {
+    let result = match &secret.command {
+        cli::SecretCommand::Set { name } => read_secret(name)
+            .and_then(|value| secrets::set(name, &value))
+            .map(|()| format!("Stored secret '{name}'; reference it as ${{secret:{name}}}")),
+        cli::SecretCommand::Delete { name } => secrets::delete(name).map(|deleted| {
+            if deleted {
+                format!("Deleted secret '{name}'")
+            } else {
+                format!("No secret named '{name}'")
+            }
+        }),
+        cli::SecretCommand::Check { name } => secrets::get(name).map(|value| match value {
+            Some(_) => format!("Secret '{name}' is stored"),
+            None => format!("Secret '{name}' is not stored"),
+        }),
+    };
+    match result {
+        Ok(message) => eprintln!("{} {message}", "OK".green().bold()),
+        Err(err) => {
+            eprintln!("{}", format!("Error: {err}").red().bold());
+            std::process::exit(1);
+        }
+    }
+}
+
+fn run_connection_command(connection: &cli::ConnectionArguments) {
+    let path = settings_path();
+    let result = match &connection.command {
+        cli::ConnectionCommand::Add { name, url, api_key } => {
+            let stored = connections::Connection {
+                url: url.clone(),
+                api_key: api_key.clone(),
+            };
+            connections::store(path, name, &stored)
+                .map(|()| format!("Saved connection '{name}' to {}", path.display()))
+        }
+        cli::ConnectionCommand::List => connections::load(path).map(|stored| {
+            if stored.is_empty() {
+                return format!("No connections in {}", path.display());
+            }
+            let mut report = format!("Connections in {}", path.display());
+            for (name, connection) in stored {
+                report.push_str(&format!("\n  {name}: {}", connection.url));
+                if let Some(api_key) = connection.api_key {
+                    report.push_str(&format!(" (api key {api_key})"));
+                }
+            }
+            report
+        }),
+        cli::ConnectionCommand::Remove { name } => connections::remove(path, name).map(|removed| {
+            if removed {
+                format!("Removed connection '{name}'")
+            } else {
+                format!("No connection named '{name}'")
+            }
+        }),
+        cli::ConnectionCommand::Test { name } => connections::get(name)
+            .and_then(|connection| connection.client())
+            .and_then(|client| client.test_connection().map_err(|err| err.to_string()))
+            .map(|info| format!("'{name}' reached Payload {} at {}", info.payload_version, info.server_url)),
+    };
+    match result {
+        Ok(message) => eprintln!("{} {message}", "OK".green().bold()),
+        Err(err) => {
+            eprintln!("{}", format!("Error: {err}").red().bold());
+            std::process::exit(1);
+        }
+    }
+}
+
+/// Prompt for a secret without echo, or read one line from piped stdin.
+fn read_secret(name: &str) -> Result<String, String> {
+    use std::io::IsTerminal;
+
+    if io::stdin().is_terminal() {
+        return dialoguer::Password::with_theme(&ColorfulTheme::default())
+            .with_prompt(format!("Value for {name}"))
+            .interact()
+            .map_err(|e| e.to_string());
+    }
+    let mut value = String::new();
+    io::stdin().read_line(&mut value).map_err(|e| e.to_string())?;
+    let value = value.trim_end_matches(['\r', '\n']).to_string();
+    if value.is_empty() {
+        return Err("No secret given on stdin".to_string());
+    }
+    Ok(value)
+}
+
+/// Print the doctor checks; exits with status 1 when any check failed.
+/// Invalid settings are reported as a check, with the defaults used for the
+/// rest.
+fn run_doctor() {
+    let file = try_read_settings().map_err(|issues| {
+        issues.iter().map(ToString::to_string).collect::<Vec<_>>().join("; ")
+    });
+    let resolved = config::env_matches().and_then(|matches| {
+        config::resolve(file.as_ref().ok().and_then(Option::as_ref), settings_path(), &matches)
+    });
+    let args = match &resolved {
+        Ok(config) => config.args.clone(),
+        Err(_) => cli::CommandArguments::default_settings(),
+    };
+    let settings = file.map(|_| ()).and(resolved.map(|_| ()));
+    let checks = doctor::run(&args, settings_path(), settings);
+    for check in &checks {
+        let label = match check.status {
+            doctor::Status::Ok => "OK  ".green().bold(),
+            doctor::Status::Warn => "WARN".yellow().bold(),
+            doctor::Status::Fail => "FAIL".red().bold(),
+        };
+        eprintln!("{label} {}: {}", check.name, check.detail);
+        if let Some(fix) = &check.fix {
+            eprintln!("     {} {fix}", "fix:".cyan());
+        }
+    }
+    if checks.iter().any(|check| check.status == doctor::Status::Fail) {
+        std::process::exit(1);
+    }
+}
+
+fn print_or_exit(output: Result<String, String>) {
+    match output {
+        Ok(text) => println!("{text}"),
+        Err(err) => {
+            eprintln!("{}", format!("Error: {err}").red().bold());
+            std::process::exit(1);
+        }
+    }
+}
+
+/// Validate files on disk, or generated code with `--self-check`; exits with
+/// status 1 when any file is invalid (or has warnings with `--deny-warnings`)
+/// and 2 when no files match.
+fn run_validate_command(validate: &cli::ValidateArguments) {
+    if validate.self_check {
+        let reports: Vec<_> = self_check(validate.jobs)
+            .into_iter()
+            .map(|(path, result)| validate_cli::FileReport {
+                file_type: FileType::from_path(Path::new(&path)),
+                path,
+                result: Some(result),
+            })
+            .collect();
+        println!("{}", validate_cli::render(&reports, validate.format));
+        if validate_cli::has_failures(&reports, validate.deny_warnings) {
+            std::process::exit(1);
+        }
+        return;
+    }
+    let files = match validate_cli::collect_files(&validate.paths) {
+        Ok(files) if files.is_empty() => {
+            eprintln!("{}", "Error: no files to validate".red().bold());
+            std::process::exit(2);
+        }
+        Ok(files) => files,
+        Err(err) => {
+            eprintln!("{}", format!("Error: {err}").red().bold());
+            std::process::exit(2);
+        }
+    };
+    let reports = validate_cli::validate_files(&files, validate.file_type, validate.jobs, validate.production);
+    println!("{}", validate_cli::render(&reports, validate.format));
+    if validate_cli::has_failures(&reports, validate.deny_warnings) {
+        std::process::exit(1);
+    }
+}
+
+/// Validate everything once, then each batch of changed files, until
+/// interrupted.
+fn run_watch_command(args: cli::WatchArguments) {
+    let interval = Duration::from_millis(args.interval_ms.max(50));
+    eprintln!(
+        "{} {} (Ctrl+C to stop)",
+        "Watching".green().bold(),
+        args.paths.join(", ")
+    );
+    let mut watcher = watch::ProjectWatcher::new(args.paths, args.file_type, args.jobs);
+    let mut last_error = None;
+    loop {
+        match watcher.poll() {
+            Ok(update) if !update.is_empty() => {
+                last_error = None;
+                eprintln!("\n{}", chrono::Local::now().format("[%H:%M:%S]").to_string().blue());
+                if !update.reports.is_empty() {
+                    println!("{}", validate_cli::render(&update.reports, args.format));
+                }
+                for path in &update.removed {
+                    eprintln!("{} {}", "GONE".yellow().bold(), path.display());
+                }
+                let summary = format!("{} of {} watched files failing", update.failing, update.total);
+                if update.failing == 0 {
+                    eprintln!("{}", summary.green());
+                } else {
+                    eprintln!("{}", summary.red());
+                }
+            }
+            Ok(_) => {}
+            Err(err) => {
+                if last_error.as_ref() != Some(&err) {
+                    eprintln!("{}", format!("Error: {err}").red().bold());
+                }
+                last_error = Some(err);
+            }
+        }
+        std::thread::sleep(interval);
+    }
+}
+
+fn tail_logs(args: &cli::CommandArguments, logs: &cli::LogsArguments) {
+    let Some(mut path) = log_file::current(&args.log_file) else {
+        eprintln!(
+            "{}",
+            format!("No log file found for {}", args.log_file).yellow()
+        );
+        return;
+    };
+    let bytes = match fs::read(&path) {
+        Ok(bytes) => bytes,
+        Err(err) => {
+            eprintln!(
+                "{}",
+                format!("Could not read {}: {err}", path.display()).red().bold()
+            );
+            return;
+        }
+    };
+    eprintln!("{} {}", "Log:".blue().bold(), path.display());
+    let contents = String::from_utf8_lossy(&bytes);
+    let lines: Vec<&str> = contents.lines().collect();
+    for line in &lines[lines.len().saturating_sub(logs.lines)..] {
+        println!("{line}");
+    }
+    if !logs.follow {
+        return;
+    }
+
+    let mut offset = bytes.len() as u64;
+    loop {
+        std::thread::sleep(Duration::from_millis(500));
+        // Switch to the new file after a rotation.
+        if let Some(current) = log_file::current(&args.log_file) {
+            if current != path {
+                path = current;
+                offset = 0;
+            }
+        }
+        let Ok(mut file) = fs::File::open(&path) else {
+            continue;
+        };
+        let len = file.metadata().map(|meta| meta.len()).unwrap_or(0);
+        if len < offset {
+            offset = 0;
+        }
+        let mut appended = Vec::new();
+        if file.seek(SeekFrom::Start(offset)).is_ok() && file.read_to_end(&mut appended).is_ok() {
+            offset += appended.len() as u64;
+            print!("{}", String::from_utf8_lossy(&appended));
+            let _ = io::stdout().flush();
+        }
+    }
+}
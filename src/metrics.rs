@@ -0,0 +1,152 @@
+//! Process-wide counters exported in Prometheus text format at `/metrics`.
+//!
+//! Tool calls are recorded by the handler; sessions, transport connections
+//! and listener readiness by the server.
+
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::sync::atomic::{AtomicI64, AtomicUsize, Ordering};
+use std::sync::{Mutex, OnceLock};
+use std::time::Duration;
+
+#[derive(Default)]
+struct ToolStats {
+    ok: u64,
+    error: u64,
+    duration_seconds: f64,
+}
+
+#[derive(Default)]
+struct Metrics {
+    tools: Mutex<BTreeMap<String, ToolStats>>,
+    connections: Mutex<BTreeMap<&'static str, u64>>,
+    active_sessions: AtomicI64,
+    ready_transports: AtomicUsize,
+    tool_calls_in_flight: AtomicUsize,
+}
+
+fn metrics() -> &'static Metrics {
+    static METRICS: OnceLock<Metrics> = OnceLock::new();
+    METRICS.get_or_init(Metrics::default)
+}
+
+pub fn record_tool_call(tool: &str, ok: bool, elapsed: Duration) {
+    if let Ok(mut tools) = metrics().tools.lock() {
+        let stats = tools.entry(tool.to_string()).or_default();
+        if ok {
+            stats.ok += 1;
+        } else {
+            stats.error += 1;
+        }
+        stats.duration_seconds += elapsed.as_secs_f64();
+    }
+}
+
+/// Marks a tool call as executing until the returned guard is dropped.
+pub fn tool_call_started() -> InFlightToolCall {
+    metrics().tool_calls_in_flight.fetch_add(1, Ordering::Relaxed);
+    InFlightToolCall(())
+}
+
+pub struct InFlightToolCall(());
+
+impl Drop for InFlightToolCall {
+    fn drop(&mut self) {
+        metrics().tool_calls_in_flight.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+/// Tool calls currently executing; shutdown waits for this to reach zero.
+pub fn tool_calls_in_flight() -> usize {
+    metrics().tool_calls_in_flight.load(Ordering::Relaxed)
+}
+
+pub fn session_opened() {
+    metrics().active_sessions.fetch_add(1, Ordering::Relaxed);
+}
+
+pub fn session_closed() {
+    metrics().active_sessions.fetch_sub(1, Ordering::Relaxed);
+}
+
+pub fn connection_accepted(transport: &'static str) {
+    if let Ok(mut connections) = metrics().connections.lock() {
+        *connections.entry(transport).or_default() += 1;
+    }
+}
+
+/// Called once a transport is listening (or, for stdio, serving).
+pub fn transport_ready() {
+    metrics().ready_transports.fetch_add(1, Ordering::Relaxed);
+}
+
+pub fn ready_transports() -> usize {
+    metrics().ready_transports.load(Ordering::Relaxed)
+}
+
+pub fn active_sessions() -> i64 {
+    metrics().active_sessions.load(Ordering::Relaxed)
+}
+
+/// Render all metrics in the Prometheus text exposition format.
+pub fn render(uptime: Duration) -> String {
+    let metrics = metrics();
+    let mut out = String::new();
+
+    out.push_str("# HELP mcp_tool_calls_total Tool calls by tool and outcome.\n");
+    out.push_str("# TYPE mcp_tool_calls_total counter\n");
+    let mut durations = String::new();
+    if let Ok(tools) = metrics.tools.lock() {
+        for (tool, stats) in tools.iter() {
+            let _ = writeln!(out, "mcp_tool_calls_total{{tool=\"{tool}\",status=\"ok\"}} {}", stats.ok);
+            let _ = writeln!(out, "mcp_tool_calls_total{{tool=\"{tool}\",status=\"error\"}} {}", stats.error);
+            let _ = writeln!(
+                durations,
+                "mcp_tool_call_duration_seconds_sum{{tool=\"{tool}\"}} {}\nmcp_tool_call_duration_seconds_count{{tool=\"{tool}\"}} {}",
+                stats.duration_seconds,
+                stats.ok + stats.error
+            );
+        }
+    }
+    out.push_str("# HELP mcp_tool_call_duration_seconds Time spent executing tools.\n");
+    out.push_str("# TYPE mcp_tool_call_duration_seconds summary\n");
+    out.push_str(&durations);
+
+    out.push_str("# HELP mcp_tool_calls_in_flight Tool calls currently executing.\n");
+    out.push_str("# TYPE mcp_tool_calls_in_flight gauge\n");
+    let _ = writeln!(out, "mcp_tool_calls_in_flight {}", tool_calls_in_flight());
+
+    out.push_str("# HELP mcp_active_sessions Open MCP sessions.\n");
+    out.push_str("# TYPE mcp_active_sessions gauge\n");
+    let _ = writeln!(out, "mcp_active_sessions {}", active_sessions());
+
+    out.push_str("# HELP mcp_transport_connections_total Connections accepted by transport.\n");
+    out.push_str("# TYPE mcp_transport_connections_total counter\n");
+    if let Ok(connections) = metrics.connections.lock() {
+        for (transport, count) in connections.iter() {
+            let _ = writeln!(out, "mcp_transport_connections_total{{transport=\"{transport}\"}} {count}");
+        }
+    }
+
+    out.push_str("# HELP mcp_uptime_seconds Seconds since the server started.\n");
+    out.push_str("# TYPE mcp_uptime_seconds gauge\n");
+    let _ = writeln!(out, "mcp_uptime_seconds {}", uptime.as_secs());
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render() {
+        record_tool_call("validate", true, Duration::from_millis(5));
+        record_tool_call("validate", false, Duration::from_millis(5));
+        connection_accepted("tcp");
+        let text = render(Duration::from_secs(3));
+        assert!(text.contains("mcp_tool_calls_total{tool=\"validate\",status=\"error\"} 1"));
+        assert!(text.contains("mcp_tool_call_duration_seconds_count{tool=\"validate\"} 2"));
+        assert!(text.contains("mcp_transport_connections_total{transport=\"tcp\"} 1"));
+        assert!(text.contains("mcp_uptime_seconds 3"));
+    }
+}
@@ -0,0 +1,142 @@
+//! Where settings and runtime state live: `$XDG_CONFIG_HOME` and
+//! `$XDG_STATE_HOME` (or the platform equivalents from `dirs`), each under an
+//! `mcp-payloadcms` directory.
+
+use std::{
+    env, fs,
+    path::{Path, PathBuf},
+    sync::OnceLock,
+};
+
+const APP_DIR: &str = "mcp-payloadcms";
+/// Settings file of this process, chosen once at startup.
+static ACTIVE_SETTINGS: OnceLock<PathBuf> = OnceLock::new();
+const SETTINGS_FILE: &str = "settings.json";
+/// Settings file read from the working directory by earlier versions.
+const LEGACY_SETTINGS: &str = "settings.json";
+/// Defaults earlier versions saved into settings; dropped on migration so the
+/// new state-directory defaults apply.
+const LEGACY_DEFAULTS: [(&str, &str); 4] = [
+    ("unix_path", "/tmp/mcp-server.sock"),
+    ("pid_file", "/tmp/mcp-server-template-rs.pid"),
+    ("runtime_info_file", "/tmp/mcp-server-template-rs.runtime.json"),
+    ("log_file", "/tmp/mcp-server-template-rs.log"),
+];
+
+/// An absolute directory from an XDG variable; relative values are ignored
+/// as the spec requires.
+fn xdg_dir(var: &str) -> Option<PathBuf> {
+    env::var_os(var)
+        .map(PathBuf::from)
+        .filter(|path| path.is_absolute())
+}
+
+/// Directory holding `settings.json`.
+pub fn config_dir() -> PathBuf {
+    xdg_dir("XDG_CONFIG_HOME")
+        .or_else(dirs::config_dir)
+        .unwrap_or_else(env::temp_dir)
+        .join(APP_DIR)
+}
+
+/// Directory for pid, lock, runtime info and log files.
+pub fn state_dir() -> PathBuf {
+    xdg_dir("XDG_STATE_HOME")
+        .or_else(dirs::state_dir)
+        .or_else(dirs::data_local_dir)
+        .unwrap_or_else(env::temp_dir)
+        .join(APP_DIR)
+}
+
+/// Directory for sockets: `$XDG_RUNTIME_DIR` when the platform has one,
+/// otherwise the state directory.
+pub fn runtime_dir() -> PathBuf {
+    dirs::runtime_dir()
+        .map(|dir| dir.join(APP_DIR))
+        .unwrap_or_else(state_dir)
+}
+
+/// Default path of a file in the config directory, as stored in settings.
+pub fn config_file(name: &str) -> String {
+    config_dir().join(name).display().to_string()
+}
+
+/// Default path of a state file, as stored in settings.
+pub fn state_file(name: &str) -> String {
+    state_dir().join(name).display().to_string()
+}
+
+/// Default path of a runtime file, as stored in settings.
+pub fn runtime_file(name: &str) -> String {
+    runtime_dir().join(name).display().to_string()
+}
+
+/// The settings file: `--config` when given, otherwise `settings.json` in
+/// the config directory.
+pub fn settings_path(config: Option<&Path>) -> PathBuf {
+    config
+        .map(Path::to_path_buf)
+        .unwrap_or_else(|| config_dir().join(SETTINGS_FILE))
+}
+
+/// Make `path` the settings file for the rest of the process; the first call
+/// wins.
+pub fn set_active_settings_path(path: PathBuf) {
+    let _ = ACTIVE_SETTINGS.set(path);
+}
+
+/// The settings file chosen at startup, or the default one.
+pub fn active_settings_path() -> &'static Path {
+    ACTIVE_SETTINGS.get_or_init(|| settings_path(None))
+}
+
+/// Create the parent directory of a file path, if it has one.
+pub fn ensure_parent(path: &Path) -> std::io::Result<()> {
+    match path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+        Some(dir) => fs::create_dir_all(dir),
+        None => Ok(()),
+    }
+}
+
+/// Move a `settings.json` from the working directory to `target` when
+/// `target` does not exist yet, dropping old `/tmp` defaults. The old file is
+/// renamed to `settings.json.migrated`; returns its original path.
+pub fn migrate_legacy_settings(target: &Path) -> Result<Option<PathBuf>, String> {
+    let legacy = PathBuf::from(LEGACY_SETTINGS);
+    if target.exists() || !legacy.is_file() {
+        return Ok(None);
+    }
+    let contents =
+        fs::read_to_string(&legacy).map_err(|e| format!("Could not read {}: {e}", legacy.display()))?;
+    let mut settings: serde_json::Value = serde_json::from_str(&contents)
+        .map_err(|e| format!("Could not parse {}: {e}", legacy.display()))?;
+    if let Some(map) = settings.as_object_mut() {
+        for (key, old_default) in LEGACY_DEFAULTS {
+            if map.get(key).and_then(|value| value.as_str()) == Some(old_default) {
+                map.remove(key);
+            }
+        }
+    }
+    ensure_parent(target).map_err(|e| format!("Could not create {}: {e}", target.display()))?;
+    fs::write(target, serde_json::to_string_pretty(&settings).unwrap_or_default())
+        .map_err(|e| format!("Could not write {}: {e}", target.display()))?;
+    fs::rename(&legacy, format!("{LEGACY_SETTINGS}.migrated"))
+        .map_err(|e| format!("Could not rename {}: {e}", legacy.display()))?;
+    Ok(Some(legacy))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_paths_are_namespaced() {
+        assert!(config_dir().ends_with(APP_DIR));
+        assert!(state_file("server.pid").ends_with("server.pid"));
+        assert_eq!(
+            settings_path(Some(Path::new("custom.json"))),
+            PathBuf::from("custom.json")
+        );
+        assert_eq!(settings_path(None), config_dir().join(SETTINGS_FILE));
+    }
+}
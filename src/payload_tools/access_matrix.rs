@@ -0,0 +1,860 @@
+//! Effective permissions per collection or global operation and role.
+//!
+//! From config sources, each access function is evaluated for an anonymous
+//! visitor and a user of each role. The evaluator understands the usual
+//! shapes of access code: `if (...) return ...` statements, `&&`, `||`,
+//! `!`, ternaries, `Boolean(user)`, role checks such as
+//! `user?.roles?.includes('admin')`, and object literals returned as where
+//! queries. Anything else is reported as `unknown` rather than guessed.
+//! Helpers referenced by name are looked up in the given sources, then in
+//! the helpers the reference examples use.
+//!
+//! From a live instance, `/api/access` gives the permissions of the
+//! connection's user, shown as a single role.
+
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap},
+    sync::OnceLock,
+};
+
+use regex::Regex;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+use crate::payload_tools::{
+    examples::ACCESS_HELPERS, schema_model::config_objects, ts_parser::parse_config_object,
+    types::SourceFile,
+};
+
+pub const COLLECTION_OPERATIONS: [&str; 4] = ["create", "read", "update", "delete"];
+pub const GLOBAL_OPERATIONS: [&str; 2] = ["read", "update"];
+/// Column of requests without a user
+pub const ANONYMOUS: &str = "anonymous";
+/// Column of a signed-in user when the config names no roles
+pub const AUTHENTICATED: &str = "authenticated";
+/// Column of a live instance's matrix unless named otherwise
+pub const LIVE_USER: &str = "connection";
+/// Helpers resolved inside one another before giving up
+const MAX_DEPTH: usize = 8;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum Access {
+    Allow,
+    Deny,
+    /// Allowed for documents matching `where`
+    Query,
+    /// The access code could not be evaluated
+    Unknown,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, JsonSchema)]
+pub struct Permission {
+    pub access: Access,
+    /// Query documents must match, for `query`
+    #[serde(rename = "where", skip_serializing_if = "Option::is_none")]
+    pub where_query: Option<Value>,
+}
+
+impl Permission {
+    fn new(access: Access) -> Self {
+        Self {
+            access,
+            where_query: None,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, JsonSchema)]
+pub struct AccessEntry {
+    pub slug: String,
+    /// `collection` or `global`
+    pub kind: &'static str,
+    pub operation: &'static str,
+    /// Access code as written; absent when Payload's default (signed-in users
+    /// only) applies or the matrix comes from a live instance
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub source: Option<String>,
+    /// Permission per role
+    pub permissions: BTreeMap<String, Permission>,
+}
+
+#[derive(Clone, Debug, Serialize, JsonSchema)]
+pub struct AccessMatrix {
+    /// Columns of the matrix, in order
+    pub roles: Vec<String>,
+    pub entries: Vec<AccessEntry>,
+}
+
+/// How the matrix is rendered for people.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum TableFormat {
+    #[default]
+    Markdown,
+    Ascii,
+}
+
+struct Entity {
+    slug: String,
+    kind: &'static str,
+    access: Map<String, Value>,
+    fields: Vec<Value>,
+    auth: bool,
+}
+
+/// The matrix of the collections and globals in `sources`. Roles default to
+/// the options of a `role` or `roles` field in auth collections plus the
+/// roles the access code checks for.
+pub fn static_matrix(
+    sources: &[SourceFile],
+    roles: Option<&[String]>,
+) -> Result<AccessMatrix, String> {
+    let mut helpers: HashMap<String, String> = ACCESS_HELPERS
+        .iter()
+        .map(|(name, code)| {
+            (
+                name.to_string(),
+                definition_of(code, name).unwrap_or_default(),
+            )
+        })
+        .collect();
+    for source in sources {
+        helpers.extend(helper_definitions(&source.code));
+    }
+    let entities: Vec<Entity> = config_objects(sources)
+        .iter()
+        .filter_map(|(file_type, object)| entity(object, file_type.as_str()))
+        .collect();
+    if entities.is_empty() {
+        return Err("No collections or globals found in the sources".to_string());
+    }
+
+    let sources_of = |entity: &Entity| -> Vec<String> {
+        entity
+            .access
+            .values()
+            .filter_map(Value::as_str)
+            .map(|code| resolve(code, &helpers, 0))
+            .collect()
+    };
+    let roles: Vec<String> = match roles {
+        Some(roles) => roles.to_vec(),
+        None => {
+            let mut found = BTreeSet::new();
+            for entity in &entities {
+                if entity.auth {
+                    found.extend(role_options(&entity.fields));
+                }
+                for code in sources_of(entity) {
+                    found.extend(checked_roles(&code));
+                }
+            }
+            found.into_iter().collect()
+        }
+    };
+    let mut columns = vec![ANONYMOUS.to_string()];
+    if roles.is_empty() {
+        columns.push(AUTHENTICATED.to_string());
+    }
+    columns.extend(roles.iter().cloned());
+
+    let mut entries = Vec::new();
+    for entity in &entities {
+        let operations: &[&'static str] = match entity.kind {
+            "global" => &GLOBAL_OPERATIONS,
+            _ => &COLLECTION_OPERATIONS,
+        };
+        for &operation in operations {
+            let access = entity.access.get(operation);
+            let permissions = columns
+                .iter()
+                .map(|column| {
+                    let viewer = match column.as_str() {
+                        ANONYMOUS => Viewer {
+                            signed_in: false,
+                            role: None,
+                        },
+                        AUTHENTICATED => Viewer {
+                            signed_in: true,
+                            role: None,
+                        },
+                        role => Viewer {
+                            signed_in: true,
+                            role: Some(role),
+                        },
+                    };
+                    (column.clone(), permission(access, &helpers, &viewer))
+                })
+                .collect();
+            entries.push(AccessEntry {
+                slug: entity.slug.clone(),
+                kind: entity.kind,
+                operation,
+                source: access.map(|value| {
+                    value
+                        .as_str()
+                        .map_or_else(|| value.to_string(), str::to_string)
+                }),
+                permissions,
+            });
+        }
+    }
+    Ok(AccessMatrix {
+        roles: columns,
+        entries,
+    })
+}
+
+/// The matrix of a live `/api/access` response, with the connection's user
+/// as the single role `role`.
+pub fn live_matrix(access: &Value, role: &str) -> AccessMatrix {
+    let mut entries = Vec::new();
+    for (key, kind, operations) in [
+        ("collections", "collection", &COLLECTION_OPERATIONS[..]),
+        ("globals", "global", &GLOBAL_OPERATIONS[..]),
+    ] {
+        let Some(entities) = access.get(key).and_then(Value::as_object) else {
+            continue;
+        };
+        for (slug, permissions) in entities {
+            for &operation in operations {
+                let granted = permissions.get(operation);
+                let permission = match granted.and_then(|granted| granted.get("where")) {
+                    Some(query) => Permission {
+                        access: Access::Query,
+                        where_query: Some(query.clone()),
+                    },
+                    None => match granted
+                        .and_then(|granted| granted.get("permission"))
+                        .and_then(Value::as_bool)
+                    {
+                        Some(true) => Permission::new(Access::Allow),
+                        Some(false) => Permission::new(Access::Deny),
+                        None => Permission::new(Access::Unknown),
+                    },
+                };
+                entries.push(AccessEntry {
+                    slug: slug.clone(),
+                    kind,
+                    operation,
+                    source: None,
+                    permissions: BTreeMap::from([(role.to_string(), permission)]),
+                });
+            }
+        }
+    }
+    AccessMatrix {
+        roles: vec![role.to_string()],
+        entries,
+    }
+}
+
+impl AccessMatrix {
+    /// One row per entity and operation, one column per role. Cells read
+    /// `yes`, `no`, `query` or `?`.
+    pub fn render(&self, format: TableFormat) -> String {
+        let mut header = vec!["slug".to_string(), "operation".to_string()];
+        header.extend(self.roles.iter().cloned());
+        let rows: Vec<Vec<String>> = self
+            .entries
+            .iter()
+            .map(|entry| {
+                let mut row = vec![
+                    format!("{} ({})", entry.slug, entry.kind),
+                    entry.operation.to_string(),
+                ];
+                row.extend(self.roles.iter().map(|role| {
+                    match entry
+                        .permissions
+                        .get(role)
+                        .map(|permission| permission.access)
+                    {
+                        Some(Access::Allow) => "yes",
+                        Some(Access::Deny) => "no",
+                        Some(Access::Query) => "query",
+                        _ => "?",
+                    }
+                    .to_string()
+                }));
+                row
+            })
+            .collect();
+        let widths: Vec<usize> = (0..header.len())
+            .map(|col| {
+                rows.iter()
+                    .map(|row| row[col].len())
+                    .chain([header[col].len()])
+                    .max()
+                    .unwrap_or_default()
+            })
+            .collect();
+        let line = |cells: &[String]| {
+            let cells: Vec<String> = cells
+                .iter()
+                .zip(&widths)
+                .map(|(cell, width)| format!("{cell:<width$}"))
+                .collect();
+            format!("| {} |", cells.join(" | "))
+        };
+        let rule = match format {
+            TableFormat::Markdown => {
+                let dashes: Vec<String> = widths.iter().map(|width| "-".repeat(*width)).collect();
+                format!("| {} |", dashes.join(" | "))
+            }
+            TableFormat::Ascii => {
+                let dashes: Vec<String> =
+                    widths.iter().map(|width| "-".repeat(width + 2)).collect();
+                format!("+{}+", dashes.join("+"))
+            }
+        };
+        let mut out = Vec::new();
+        if format == TableFormat::Ascii {
+            out.push(rule.clone());
+        }
+        out.push(line(&header));
+        out.push(rule.clone());
+        out.extend(rows.iter().map(|row| line(row)));
+        if format == TableFormat::Ascii {
+            out.push(rule);
+        }
+        out.join("\n")
+    }
+}
+
+fn entity(object: &Map<String, Value>, kind: &'static str) -> Option<Entity> {
+    Some(Entity {
+        slug: object.get("slug")?.as_str()?.to_string(),
+        kind,
+        access: object
+            .get("access")
+            .and_then(Value::as_object)
+            .cloned()
+            .unwrap_or_default(),
+        fields: object
+            .get("fields")
+            .and_then(Value::as_array)
+            .cloned()
+            .unwrap_or_default(),
+        auth: object
+            .get("auth")
+            .is_some_and(|auth| auth != &Value::Bool(false)),
+    })
+}
+
+/// Options of top-level `role` or `roles` fields.
+fn role_options(fields: &[Value]) -> Vec<String> {
+    fields
+        .iter()
+        .filter(|field| {
+            matches!(
+                field.get("name").and_then(Value::as_str),
+                Some("role" | "roles")
+            )
+        })
+        .filter_map(|field| field.get("options")?.as_array())
+        .flatten()
+        .filter_map(|option| match option {
+            Value::String(value) => Some(value.clone()),
+            option => Some(option.get("value")?.as_str()?.to_string()),
+        })
+        .collect()
+}
+
+/// String literals on lines of `code` that mention a role.
+fn checked_roles(code: &str) -> Vec<String> {
+    static LITERAL: OnceLock<Regex> = OnceLock::new();
+    let literal = LITERAL
+        .get_or_init(|| Regex::new(r#"'([\w-]+)'|"([\w-]+)""#).expect("valid literal pattern"));
+    code.lines()
+        .filter(|line| line.contains("role"))
+        .flat_map(|line| literal.captures_iter(line))
+        .filter_map(|caps| caps.get(1).or_else(|| caps.get(2)))
+        .map(|found| found.as_str().to_string())
+        .collect()
+}
+
+/// `const name = ...` and `function name(...) {...}` definitions in `code`.
+fn helper_definitions(code: &str) -> Vec<(String, String)> {
+    static NAME: OnceLock<Regex> = OnceLock::new();
+    let name = NAME.get_or_init(|| {
+        Regex::new(
+            r"(?m)^\s*(?:export\s+)?(?:const|let|var|(?:async\s+)?function)\s+([A-Za-z_$][\w$]*)",
+        )
+        .expect("valid definition pattern")
+    });
+    name.captures_iter(code)
+        .filter_map(|caps| {
+            let name = caps.get(1)?.as_str();
+            Some((name.to_string(), definition_of(code, name)?))
+        })
+        .collect()
+}
+
+/// The expression `name` is defined as in `code`; a `function` declaration
+/// is returned whole.
+fn definition_of(code: &str, name: &str) -> Option<String> {
+    let pattern = format!(
+        r"(?:const|let|var)\s+{0}\s*(?::[^=]*)?=(?:[^>])|((?:async\s+)?function\s+{0}\b)",
+        regex::escape(name)
+    );
+    let found = Regex::new(&pattern).ok()?.captures(code)?;
+    if let Some(function) = found.get(1) {
+        let open = function.end() + code[function.end()..].find('{')?;
+        let close = matching(code, open)?;
+        return Some(format!("function {}", &code[function.end()..=close]));
+    }
+    let start = found.get(0)?.end() - 1;
+    let end = statement_end(code, start);
+    Some(code[start..end].trim().to_string())
+}
+
+/// Follow a helper name to its definition.
+fn resolve(code: &str, helpers: &HashMap<String, String>, depth: usize) -> String {
+    let trimmed = code.trim();
+    match helpers.get(trimmed) {
+        Some(definition) if depth < MAX_DEPTH && definition != trimmed => {
+            resolve(definition, helpers, depth + 1)
+        }
+        _ => trimmed.to_string(),
+    }
+}
+
+struct Viewer<'a> {
+    signed_in: bool,
+    role: Option<&'a str>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Val {
+    Bool(bool),
+    Query(String),
+    Unknown,
+}
+
+impl Val {
+    fn truthy(&self) -> Option<bool> {
+        match self {
+            Val::Bool(value) => Some(*value),
+            Val::Query(_) => Some(true),
+            Val::Unknown => None,
+        }
+    }
+}
+
+fn permission(
+    access: Option<&Value>,
+    helpers: &HashMap<String, String>,
+    viewer: &Viewer,
+) -> Permission {
+    let value = match access {
+        // Payload's default lets any signed-in user through
+        None => Val::Bool(viewer.signed_in),
+        Some(Value::Bool(value)) => Val::Bool(*value),
+        Some(Value::String(code)) => Evaluator { helpers, viewer }.expr(code, 0),
+        Some(_) => Val::Unknown,
+    };
+    match value {
+        Val::Bool(true) => Permission::new(Access::Allow),
+        Val::Bool(false) => Permission::new(Access::Deny),
+        Val::Query(query) => Permission {
+            access: Access::Query,
+            where_query: Some(parse_config_object(&query).unwrap_or(Value::String(query))),
+        },
+        Val::Unknown => Permission::new(Access::Unknown),
+    }
+}
+
+struct Evaluator<'a> {
+    helpers: &'a HashMap<String, String>,
+    viewer: &'a Viewer<'a>,
+}
+
+impl Evaluator<'_> {
+    fn expr(&self, code: &str, depth: usize) -> Val {
+        if depth > MAX_DEPTH {
+            return Val::Unknown;
+        }
+        let code = strip_parens(code.trim().trim_end_matches(';').trim());
+        let code = code.strip_prefix("async ").map_or(code, str::trim);
+        match code {
+            "true" => return Val::Bool(true),
+            "false" | "undefined" | "null" => return Val::Bool(false),
+            "user" | "req.user" | "req?.user" | "args.req.user" => {
+                return Val::Bool(self.viewer.signed_in);
+            }
+            _ => {}
+        }
+        if let Some(arrow) = find_top_level(code, "=>") {
+            return self.body(&code[arrow + 2..], depth + 1);
+        }
+        if let Some(rest) = code.strip_prefix("function") {
+            let Some(open) = rest.find('{') else {
+                return Val::Unknown;
+            };
+            return self.body(&rest[open..], depth + 1);
+        }
+        if let Some(definition) = self.helpers.get(code) {
+            return self.expr(definition, depth + 1);
+        }
+        if let Some(question) = ternary(code) {
+            let Some(colon) = find_top_level(&code[question + 1..], ":") else {
+                return Val::Unknown;
+            };
+            let (then, otherwise) = (
+                &code[question + 1..question + 1 + colon],
+                &code[question + 2 + colon..],
+            );
+            return match self.expr(&code[..question], depth + 1).truthy() {
+                Some(true) => self.expr(then, depth + 1),
+                Some(false) => self.expr(otherwise, depth + 1),
+                None => Val::Unknown,
+            };
+        }
+        for (operator, stop_when) in [("||", true), ("&&", false)] {
+            let parts = split_top_level(code, operator);
+            if parts.len() > 1 {
+                let mut last = Val::Unknown;
+                for part in parts {
+                    last = self.expr(part, depth + 1);
+                    match last.truthy() {
+                        Some(value) if value == stop_when => return last,
+                        Some(_) => {}
+                        None => return Val::Unknown,
+                    }
+                }
+                return last;
+            }
+        }
+        if let Some(rest) = code.strip_prefix("!!") {
+            return self
+                .expr(rest, depth + 1)
+                .truthy()
+                .map_or(Val::Unknown, Val::Bool);
+        }
+        if let Some(rest) = code.strip_prefix('!') {
+            return self
+                .expr(rest, depth + 1)
+                .truthy()
+                .map_or(Val::Unknown, |value| Val::Bool(!value));
+        }
+        if let Some(inner) = code
+            .strip_prefix("Boolean(")
+            .and_then(|rest| rest.strip_suffix(')'))
+        {
+            return self
+                .expr(inner, depth + 1)
+                .truthy()
+                .map_or(Val::Unknown, Val::Bool);
+        }
+        if code.starts_with('{') {
+            return Val::Query(code.to_string());
+        }
+        self.role_check(code)
+    }
+
+    /// `user?.roles?.includes('admin')`, `user.role === 'editor'` and the
+    /// like; a `!=` comparison inverts the check.
+    fn role_check(&self, code: &str) -> Val {
+        let roles = checked_roles(code);
+        let checks_role = code.contains("role")
+            && !roles.is_empty()
+            && ["includes(", "some(", "==", "indexOf("]
+                .iter()
+                .any(|op| code.contains(op));
+        if !checks_role {
+            return Val::Unknown;
+        }
+        let Some(role) = self.viewer.role.filter(|_| self.viewer.signed_in) else {
+            return Val::Bool(false);
+        };
+        let matches = roles.iter().any(|candidate| candidate == role);
+        Val::Bool(matches != code.contains("!="))
+    }
+
+    /// A function body: a block of statements or a single expression.
+    fn body(&self, code: &str, depth: usize) -> Val {
+        let code = code.trim();
+        if let Some(rest) = code.strip_prefix('{') {
+            let inner = matching(code, 0).map_or(rest, |close| &code[1..close]);
+            return self.block(inner, depth).unwrap_or(Val::Bool(false));
+        }
+        self.expr(code, depth)
+    }
+
+    /// Run statements until one returns; `None` when none does.
+    fn block(&self, code: &str, depth: usize) -> Option<Val> {
+        let mut pos = 0;
+        while pos < code.len() {
+            let rest = code[pos..].trim_start_matches(|ch: char| ch.is_whitespace() || ch == ';');
+            pos = code.len() - rest.len();
+            if rest.is_empty() {
+                break;
+            }
+            if let Some(after_if) = keyword(rest, "if") {
+                let condition = after_if
+                    .find('(')
+                    .map(|at| code.len() - after_if.len() + at)
+                    .and_then(|open| Some((open, matching(code, open)?)));
+                let Some((open, close)) = condition else {
+                    return Some(Val::Unknown);
+                };
+                let condition = self.expr(&code[open + 1..close], depth + 1).truthy();
+                let (then, after_then) = statement(code, close + 1);
+                let (otherwise, after_else) = match keyword(code[after_then..].trim_start(), "else")
+                {
+                    Some(rest) => {
+                        let start = code.len() - rest.len();
+                        let (otherwise, end) = statement(code, start);
+                        (Some(otherwise), end)
+                    }
+                    None => (None, after_then),
+                };
+                let taken = match condition {
+                    Some(true) => Some(then),
+                    Some(false) => otherwise,
+                    None => return Some(Val::Unknown),
+                };
+                if let Some(result) = taken.and_then(|branch| self.block(branch, depth + 1)) {
+                    return Some(result);
+                }
+                pos = after_else;
+            } else if let Some(value) = keyword(rest, "return") {
+                let end = statement_end(code, code.len() - value.len());
+                return Some(self.expr(&code[code.len() - value.len()..end], depth + 1));
+            } else {
+                let end = statement_end(code, pos);
+                // A stray closing bracket ends no statement; step over it
+                pos = if end > pos {
+                    end
+                } else {
+                    pos + rest.chars().next().map_or(1, char::len_utf8)
+                };
+            }
+        }
+        None
+    }
+}
+
+/// `code` after a leading `word`, when it is the whole word.
+fn keyword<'a>(code: &'a str, word: &str) -> Option<&'a str> {
+    let rest = code.strip_prefix(word)?;
+    (!rest.starts_with(|ch: char| ch.is_alphanumeric() || ch == '_' || ch == '$')).then_some(rest)
+}
+
+/// The statement or block starting at `from`, with block braces removed, and
+/// the offset after it.
+fn statement(code: &str, from: usize) -> (&str, usize) {
+    let offset = code[from..].len() - code[from..].trim_start().len();
+    let start = from + offset;
+    if code[start..].starts_with('{') {
+        if let Some(close) = matching(code, start) {
+            return (&code[start + 1..close], close + 1);
+        }
+    }
+    let end = statement_end(code, start);
+    (&code[start..end], end)
+}
+
+/// Offset of the `;` or line break ending the statement at `from`. A line
+/// break continues the statement when either side of it is an operator.
+fn statement_end(code: &str, from: usize) -> usize {
+    let bytes = code.as_bytes();
+    let mut depth = 0usize;
+    let mut i = from;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'\'' | b'"' | b'`' => {
+                i = skip_string(bytes, i);
+                continue;
+            }
+            b'(' | b'[' | b'{' => depth += 1,
+            b')' | b']' | b'}' => {
+                if depth == 0 {
+                    return i;
+                }
+                depth -= 1;
+            }
+            b';' if depth == 0 => return i,
+            b'\n' if depth == 0 => {
+                let before = code[from..i].trim_end();
+                let after = code[i..].trim_start();
+                let continues = before
+                    .ends_with(['|', '&', '?', ':', '(', ',', '=', '>', '+', '!'])
+                    || after.starts_with(['|', '&', '?', ':', '.', ')', '+'])
+                    || before.is_empty();
+                if !continues {
+                    return i;
+                }
+            }
+            _ => {}
+        }
+        i += 1;
+    }
+    bytes.len()
+}
+
+/// Offset after the string literal opening at `start`.
+fn skip_string(bytes: &[u8], start: usize) -> usize {
+    let quote = bytes[start];
+    let mut i = start + 1;
+    while i < bytes.len() && bytes[i] != quote {
+        i += if bytes[i] == b'\\' { 2 } else { 1 };
+    }
+    i + 1
+}
+
+/// Offset of the bracket closing the one at `open`.
+fn matching(code: &str, open: usize) -> Option<usize> {
+    let bytes = code.as_bytes();
+    let mut depth = 0usize;
+    let mut i = open;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'\'' | b'"' | b'`' => {
+                i = skip_string(bytes, i);
+                continue;
+            }
+            b'(' | b'[' | b'{' => depth += 1,
+            b')' | b']' | b'}' => {
+                depth = depth.checked_sub(1)?;
+                if depth == 0 {
+                    return Some(i);
+                }
+            }
+            _ => {}
+        }
+        i += 1;
+    }
+    None
+}
+
+/// Offsets of `pattern` outside brackets and strings.
+fn top_level(code: &str, pattern: &str) -> Vec<usize> {
+    let bytes = code.as_bytes();
+    let mut found = Vec::new();
+    let mut depth = 0usize;
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'\'' | b'"' | b'`' => {
+                i = skip_string(bytes, i);
+                continue;
+            }
+            b'(' | b'[' | b'{' => depth += 1,
+            b')' | b']' | b'}' => depth = depth.saturating_sub(1),
+            _ if depth == 0 && bytes[i..].starts_with(pattern.as_bytes()) => {
+                found.push(i);
+                i += pattern.len();
+                continue;
+            }
+            _ => {}
+        }
+        i += 1;
+    }
+    found
+}
+
+fn find_top_level(code: &str, pattern: &str) -> Option<usize> {
+    top_level(code, pattern).into_iter().next()
+}
+
+fn split_top_level<'a>(code: &'a str, operator: &str) -> Vec<&'a str> {
+    let mut parts = Vec::new();
+    let mut start = 0;
+    for at in top_level(code, operator) {
+        parts.push(&code[start..at]);
+        start = at + operator.len();
+    }
+    parts.push(&code[start..]);
+    parts
+}
+
+/// Offset of a ternary `?`, skipping `?.` and `??`.
+fn ternary(code: &str) -> Option<usize> {
+    top_level(code, "?").into_iter().find(|&at| {
+        let next = code.as_bytes().get(at + 1);
+        let previous = at.checked_sub(1).map(|i| code.as_bytes()[i]);
+        next != Some(&b'.') && next != Some(&b'?') && previous != Some(b'?')
+    })
+}
+
+/// `code` without parentheses wrapping all of it.
+fn strip_parens(code: &str) -> &str {
+    let mut code = code;
+    while code.starts_with('(') && matching(code, 0) == Some(code.len() - 1) {
+        code = code[1..code.len() - 1].trim();
+    }
+    code
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::payload_tools::examples::require_example;
+
+    #[test]
+    fn test_static_matrix_of_example() {
+        let code = require_example("saas").unwrap().code().unwrap();
+        let sources: Vec<SourceFile> = code
+            .files
+            .into_iter()
+            .map(|file| SourceFile {
+                path: Some(file.path),
+                code: file.code,
+            })
+            .collect();
+        let matrix = static_matrix(&sources, None).unwrap();
+        assert_eq!(
+            matrix.roles,
+            ["anonymous", "admin", "member", "owner", "viewer"]
+        );
+
+        let cell = |slug: &str, operation: &str, role: &str| {
+            let entry = matrix
+                .entries
+                .iter()
+                .find(|entry| entry.slug == slug && entry.operation == operation)
+                .unwrap();
+            entry.permissions[role].clone()
+        };
+        assert_eq!(cell("users", "create", "admin").access, Access::Allow);
+        assert_eq!(cell("users", "create", "member").access, Access::Deny);
+        assert_eq!(cell("users", "update", "anonymous").access, Access::Deny);
+        let own = cell("users", "update", "member");
+        assert_eq!(own.access, Access::Query);
+        assert_eq!(
+            own.where_query,
+            Some(serde_json::json!({ "id": { "equals": "user.id" } }))
+        );
+        assert!(!matrix.render(TableFormat::Markdown).contains('?'));
+        assert!(matrix.render(TableFormat::Ascii).starts_with("+-"));
+    }
+
+    #[test]
+    fn test_evaluator_shapes() {
+        let helpers = HashMap::new();
+        let editor = Viewer {
+            signed_in: true,
+            role: Some("editor"),
+        };
+        let anonymous = Viewer {
+            signed_in: false,
+            role: None,
+        };
+        let eval = |code: &str, viewer: &Viewer| {
+            Evaluator {
+                helpers: &helpers,
+                viewer,
+            }
+            .expr(code, 0)
+        };
+        let code = "({ req: { user } }) => user?.role === 'admin' ? true : { author: { equals: user?.id } }";
+        assert!(matches!(eval(code, &editor), Val::Query(_)));
+        let code = "({ req }) => {\n  if (req.user) {\n    return true\n  } else return false\n}";
+        assert_eq!(eval(code, &editor), Val::Bool(true));
+        assert_eq!(eval(code, &anonymous), Val::Bool(false));
+        assert_eq!(eval("({ req }) => isStaff(req)", &editor), Val::Unknown);
+    }
+}
@@ -0,0 +1,370 @@
+//! Anonymized exports of live content for staging and seeding. Documents are
+//! exported as by `export_content` and every value of a scrubbed field is
+//! replaced with a fake of the same kind before the line is written.
+//!
+//! Fakes are derived from an HMAC-SHA256 of the original value keyed with the
+//! seed, so the same email becomes the same fake email in every document and
+//! collection, and the originals can't be recovered by hashing guesses without
+//! the seed. Without a seed a random key is used for each export. Emails,
+//! phones, IPs and tokens are re-derived when their fake is already taken by
+//! another value, so a unique field stays unique.
+
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    io::Write,
+};
+
+use ring::{hmac, rand::SystemRandom};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+
+use crate::payload_tools::{
+    client::PayloadClient,
+    content::{ContentExportOptions, export_content},
+};
+
+const FIRST_NAMES: [&str; 16] = [
+    "Alex", "Blake", "Casey", "Dana", "Elliot", "Frankie", "Harper", "Jamie",
+    "Jordan", "Kai", "Morgan", "Quinn", "Riley", "Sam", "Taylor", "Avery",
+];
+const LAST_NAMES: [&str; 16] = [
+    "Anders", "Brooks", "Carter", "Diaz", "Evans", "Fischer", "Garcia", "Hughes",
+    "Ito", "Jensen", "Kowalski", "Lee", "Moreau", "Nakamura", "Okafor", "Patel",
+];
+const STREETS: [&str; 8] = [
+    "Main St", "Oak Ave", "Maple Rd", "Cedar Ln", "Park Blvd", "Elm St", "Hill Rd", "Lake Dr",
+];
+const WORDS: [&str; 12] = [
+    "lorem", "ipsum", "dolor", "sit", "amet", "consectetur", "adipiscing", "elit",
+    "sed", "do", "eiusmod", "tempor",
+];
+
+/// Times a distinct fake is re-derived before giving up.
+const MAX_ATTEMPTS: u32 = 64;
+
+/// Field names scrubbed when no `fields` are given.
+const DEFAULT_FIELDS: [(&str, FakeKind); 15] = [
+    ("email", FakeKind::Email),
+    ("name", FakeKind::Name),
+    ("fullName", FakeKind::Name),
+    ("firstName", FakeKind::FirstName),
+    ("lastName", FakeKind::LastName),
+    ("phone", FakeKind::Phone),
+    ("phoneNumber", FakeKind::Phone),
+    ("address", FakeKind::Address),
+    ("street", FakeKind::Address),
+    ("ip", FakeKind::Ip),
+    ("ipAddress", FakeKind::Ip),
+    ("apiKey", FakeKind::Token),
+    ("resetPasswordToken", FakeKind::Token),
+    ("_verificationToken", FakeKind::Token),
+    ("token", FakeKind::Token),
+];
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub enum FakeKind {
+    /// `jamie.patel482113@example.com`
+    Email,
+    /// `Jamie Patel`
+    Name,
+    FirstName,
+    LastName,
+    /// `+1-555-0123 x4821`
+    Phone,
+    /// `42 Oak Ave`
+    Address,
+    /// An address from the IPv4 documentation ranges, or from 2001:db8::/32
+    /// for IPv6 values and once those run out
+    Ip,
+    /// 32 hex characters
+    Token,
+    /// Lorem ipsum of about the same number of words
+    Text,
+    /// `null`
+    Remove,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ScrubField {
+    /// Field name, matched at any depth (inside groups, arrays and blocks)
+    pub field: String,
+    pub kind: FakeKind,
+    /// Only scrub the field in this collection
+    pub collection: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct AnonymizeOptions {
+    /// Fields to scrub; defaults to common PII and token names (`email`,
+    /// `name`, `firstName`, `phone`, `address`, `ip`, `apiKey`, ...)
+    pub fields: Option<Vec<ScrubField>>,
+    /// Secret key for the fakes. Reuse it to get the same fakes across
+    /// exports; without it a random key is used, so each export gets fresh
+    /// fakes
+    pub seed: Option<String>,
+    #[serde(flatten)]
+    pub export: ContentExportOptions,
+}
+
+/// Replaces scrubbed values in each NDJSON line written through it.
+pub struct Scrubber<'a, W: Write> {
+    inner: &'a mut W,
+    rules: Vec<ScrubField>,
+    key: hmac::Key,
+    /// Fake given to each original value of a distinct kind
+    fakes: HashMap<(FakeKind, String), Value>,
+    /// Fakes of a distinct kind already handed out
+    taken: HashSet<(FakeKind, String)>,
+    line: Vec<u8>,
+    /// Values replaced per field
+    pub scrubbed: BTreeMap<String, usize>,
+}
+
+impl<'a, W: Write> Scrubber<'a, W> {
+    pub fn new(inner: &'a mut W, options: &AnonymizeOptions) -> Result<Self, String> {
+        let rules = options.fields.clone().unwrap_or_else(|| {
+            DEFAULT_FIELDS
+                .iter()
+                .map(|(field, kind)| ScrubField {
+                    field: field.to_string(),
+                    kind: *kind,
+                    collection: None,
+                })
+                .collect()
+        });
+        let key = match &options.seed {
+            Some(seed) => hmac::Key::new(hmac::HMAC_SHA256, seed.as_bytes()),
+            None => hmac::Key::generate(hmac::HMAC_SHA256, &SystemRandom::new())
+                .map_err(|_| "Failed to generate a random anonymization key".to_string())?,
+        };
+        Ok(Self {
+            inner,
+            rules,
+            key,
+            fakes: HashMap::new(),
+            taken: HashSet::new(),
+            line: Vec::new(),
+            scrubbed: BTreeMap::new(),
+        })
+    }
+
+    fn scrub_line(&mut self, line: &[u8]) -> std::io::Result<()> {
+        let mut entry: Value = serde_json::from_slice(line)
+            .map_err(|err| std::io::Error::new(std::io::ErrorKind::InvalidData, err))?;
+        let collection = entry["collection"].as_str().unwrap_or_default().to_string();
+        if let Some(doc) = entry.get_mut("doc") {
+            self.scrub(&collection, doc)?;
+        }
+        writeln!(self.inner, "{entry}")
+    }
+
+    fn scrub(&mut self, collection: &str, value: &mut Value) -> std::io::Result<()> {
+        match value {
+            Value::Object(map) => {
+                for (key, child) in map.iter_mut() {
+                    let kind = self
+                        .rules
+                        .iter()
+                        .find(|rule| {
+                            rule.field == *key && rule.collection.as_deref().is_none_or(|only| only == collection)
+                        })
+                        .map(|rule| rule.kind);
+                    match kind {
+                        Some(kind) if !child.is_null() => {
+                            *child = self.fake(kind, child)?;
+                            *self.scrubbed.entry(key.clone()).or_default() += 1;
+                        }
+                        _ => self.scrub(collection, child)?,
+                    }
+                }
+            }
+            Value::Array(items) => {
+                for item in items {
+                    self.scrub(collection, item)?;
+                }
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    /// A fake `kind` value standing in for `value`. Arrays are faked per item.
+    fn fake(&mut self, kind: FakeKind, value: &Value) -> std::io::Result<Value> {
+        if let Value::Array(items) = value {
+            return items.iter().map(|item| self.fake(kind, item)).collect();
+        }
+        if !matches!(kind, FakeKind::Email | FakeKind::Phone | FakeKind::Ip | FakeKind::Token) {
+            return Ok(fake(kind, value, &self.key, 0));
+        }
+        let original = (kind, value.to_string());
+        if let Some(fake) = self.fakes.get(&original) {
+            return Ok(fake.clone());
+        }
+        for attempt in 0..MAX_ATTEMPTS {
+            let fake = fake(kind, value, &self.key, attempt);
+            if self.taken.insert((kind, fake.to_string())) {
+                self.fakes.insert(original, fake.clone());
+                return Ok(fake);
+            }
+        }
+        Err(std::io::Error::other(format!("Ran out of distinct {kind:?} fakes")))
+    }
+}
+
+impl<W: Write> Write for Scrubber<'_, W> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.line.extend_from_slice(buf);
+        while let Some(end) = self.line.iter().position(|byte| *byte == b'\n') {
+            let line: Vec<u8> = self.line.drain(..=end).collect();
+            self.scrub_line(&line[..end])?;
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        if !self.line.is_empty() {
+            let line = std::mem::take(&mut self.line);
+            self.scrub_line(&line)?;
+        }
+        self.inner.flush()
+    }
+}
+
+/// The HMAC of `value`, with `attempt` mixed in for re-derived fakes.
+fn hash_value(value: &Value, key: &hmac::Key, attempt: u32) -> [u8; 32] {
+    let mut input = value.to_string().into_bytes();
+    if attempt > 0 {
+        input.extend_from_slice(&attempt.to_be_bytes());
+    }
+    let mut hash = [0; 32];
+    hash.copy_from_slice(hmac::sign(key, &input).as_ref());
+    hash
+}
+
+/// A fake `kind` value standing in for a single `value`.
+fn fake(kind: FakeKind, value: &Value, key: &hmac::Key, attempt: u32) -> Value {
+    let digest = hash_value(value, key, attempt);
+    let hash = u64::from_be_bytes(std::array::from_fn(|index| digest[index]));
+    let first = FIRST_NAMES[(hash % 16) as usize];
+    let last = LAST_NAMES[((hash >> 8) % 16) as usize];
+    match kind {
+        FakeKind::Email => json!(format!(
+            "{}.{}{}@example.com",
+            first.to_lowercase(),
+            last.to_lowercase(),
+            (hash >> 16) % 1_000_000
+        )),
+        FakeKind::Name => json!(format!("{first} {last}")),
+        FakeKind::FirstName => json!(first),
+        FakeKind::LastName => json!(last),
+        FakeKind::Phone => json!(format!("+1-555-01{:02} x{:04}", (hash >> 16) % 100, (hash >> 32) % 10_000)),
+        FakeKind::Address => json!(format!("{} {}", (hash >> 16) % 900 + 1, STREETS[((hash >> 32) % 8) as usize])),
+        // 192.0.2.0/24, 198.51.100.0/24 and 203.0.113.0/24 hold 762 hosts; IPv6
+        // values and anything past the first few attempts go to 2001:db8::/32
+        FakeKind::Ip if value.as_str().is_some_and(|ip| !ip.contains(':')) && attempt < MAX_ATTEMPTS / 2 => {
+            let net = ["192.0.2", "198.51.100", "203.0.113"][((hash >> 16) % 3) as usize];
+            json!(format!("{net}.{}", (hash >> 24) % 254 + 1))
+        }
+        FakeKind::Ip => {
+            let groups: Vec<String> = digest[8..20]
+                .chunks(2)
+                .map(|pair| format!("{:x}", u16::from_be_bytes([pair[0], pair[1]])))
+                .collect();
+            json!(format!("2001:db8:{}", groups.join(":")))
+        }
+        FakeKind::Token => json!(digest[..16].iter().map(|byte| format!("{byte:02x}")).collect::<String>()),
+        FakeKind::Text => {
+            let words = value.as_str().map_or(1, |text| text.split_whitespace().count()).max(1);
+            let text: Vec<&str> = (0..words)
+                .map(|index| WORDS[((hash >> (index % 8 * 4)) as usize + index) % WORDS.len()])
+                .collect();
+            json!(text.join(" "))
+        }
+        FakeKind::Remove => Value::Null,
+    }
+}
+
+/// What [`anonymize_export`] wrote.
+pub struct AnonymizeSummary {
+    /// Documents exported per collection
+    pub documents: BTreeMap<String, usize>,
+    /// Values replaced per field
+    pub scrubbed: BTreeMap<String, usize>,
+}
+
+/// Export `options.export.collections` to `out` with the scrubbed fields
+/// replaced.
+pub fn anonymize_export(
+    client: &PayloadClient,
+    options: &AnonymizeOptions,
+    out: &mut impl Write,
+) -> Result<AnonymizeSummary, String> {
+    let mut scrubber = Scrubber::new(out, options)?;
+    let documents = export_content(client, &options.export, &mut scrubber)?;
+    scrubber.flush().map_err(|err| err.to_string())?;
+    Ok(AnonymizeSummary {
+        documents,
+        scrubbed: scrubber.scrubbed,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_scrubber_replaces_consistently() {
+        let options = AnonymizeOptions {
+            fields: Some(vec![
+                ScrubField { field: "email".to_string(), kind: FakeKind::Email, collection: None },
+                ScrubField { field: "bio".to_string(), kind: FakeKind::Text, collection: Some("users".to_string()) },
+            ]),
+            seed: Some("staging".to_string()),
+            ..Default::default()
+        };
+        let mut out = Vec::new();
+        let mut scrubber = Scrubber::new(&mut out, &options).unwrap();
+        writeln!(scrubber, r#"{{"collection":"users","doc":{{"id":1,"email":"ann@corp.com","bio":"Likes cats a lot"}}}}"#).unwrap();
+        write!(scrubber, r#"{{"collection":"posts","doc":{{"id":2,"author":{{"email":"ann@corp.com"}},"bio":"kept"}}}}"#).unwrap();
+        scrubber.flush().unwrap();
+        let scrubbed = scrubber.scrubbed.clone();
+
+        let lines: Vec<Value> = String::from_utf8(out)
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        let email = lines[0]["doc"]["email"].as_str().unwrap();
+        assert!(email.ends_with("@example.com"));
+        assert_eq!(lines[1]["doc"]["author"]["email"], email);
+        assert_eq!(lines[0]["doc"]["bio"].as_str().unwrap().split(' ').count(), 4);
+        assert_eq!(lines[1]["doc"]["bio"], "kept");
+        assert_eq!(scrubbed, BTreeMap::from([("bio".to_string(), 1), ("email".to_string(), 2)]));
+    }
+
+    #[test]
+    fn test_scrubber_keeps_distinct_values_distinct() {
+        let options = AnonymizeOptions {
+            fields: Some(vec![ScrubField { field: "ip".to_string(), kind: FakeKind::Ip, collection: None }]),
+            ..Default::default()
+        };
+        let mut out = Vec::new();
+        let mut scrubber = Scrubber::new(&mut out, &options).unwrap();
+        for host in 0..1000 {
+            writeln!(scrubber, r#"{{"collection":"logs","doc":{{"ip":"10.0.{}.{}"}}}}"#, host / 256, host % 256).unwrap();
+        }
+        writeln!(scrubber, r#"{{"collection":"logs","doc":{{"ip":"10.0.0.0"}}}}"#).unwrap();
+        scrubber.flush().unwrap();
+
+        let ips: Vec<String> = String::from_utf8(out)
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str::<Value>(line).unwrap()["doc"]["ip"].as_str().unwrap().to_string())
+            .collect();
+        assert_eq!(ips.iter().collect::<HashSet<_>>().len(), 1000);
+        assert_eq!(ips[1000], ips[0]);
+    }
+}
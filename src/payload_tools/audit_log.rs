@@ -0,0 +1,339 @@
+//! An audit trail of document changes: an append-only collection written by
+//! afterChange and afterDelete hooks on the audited collections, recording
+//! the user, the operation and the fields that changed, with optional
+//! pruning of old entries.
+
+use std::collections::BTreeMap;
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::payload_tools::json_schema::schema_name;
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct AuditLogOptions {
+    /// Collections whose changes are recorded
+    pub collections: Vec<String>,
+    /// Slug of the audit log collection (default `audit-logs`)
+    pub slug: Option<String>,
+    /// Auth collection of the users recorded on entries (default `users`)
+    pub users_slug: Option<String>,
+    /// Delete entries older than this many days; kept forever by default
+    pub retention_days: Option<u32>,
+    /// Fields left out of recorded changes, besides timestamps and
+    /// password fields
+    pub ignore_fields: Option<Vec<String>>,
+}
+
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct AuditLogConfig {
+    /// Imports for the config file
+    pub imports: Vec<String>,
+    /// Entry for the config's `collections` array
+    pub collection: String,
+    /// Statement for the config's `onInit`, scheduling pruning; set with
+    /// `retentionDays`
+    pub on_init: Option<String>,
+    /// `hooks` property to merge into each audited collection's config, by
+    /// slug
+    pub collections: BTreeMap<String, String>,
+    /// The collection, hooks and pruning by path from the project root
+    pub files: BTreeMap<String, String>,
+}
+
+/// Fields never recorded: timestamps change on every save, and auth fields
+/// hold secrets.
+const IGNORED_FIELDS: [&str; 7] = [
+    "createdAt",
+    "updatedAt",
+    "password",
+    "hash",
+    "salt",
+    "resetPasswordToken",
+    "_verificationToken",
+];
+
+fn is_slug(value: &str) -> bool {
+    !value.is_empty()
+        && value
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
+
+fn audit_collection(slug: &str, name: &str, users: &str) -> String {
+    format!(
+        "import {{ CollectionConfig }} from 'payload/types';
+
+/** Append-only: entries are written by the audit hooks through the Local API and can't be edited. */
+const {name}: CollectionConfig = {{
+  slug: '{slug}',
+  admin: {{
+    useAsTitle: 'documentId',
+    defaultColumns: ['collection', 'documentId', 'operation', 'user', 'createdAt'],
+    group: 'Admin',
+  }},
+  access: {{
+    read: ({{ req: {{ user }} }}) => Boolean(user),
+    create: () => false,
+    update: () => false,
+    delete: () => false,
+  }},
+  timestamps: true,
+  fields: [
+    {{
+      name: 'collection',
+      type: 'text',
+      required: true,
+      index: true,
+    }},
+    {{
+      name: 'documentId',
+      type: 'text',
+      required: true,
+      index: true,
+    }},
+    {{
+      name: 'operation',
+      type: 'select',
+      required: true,
+      index: true,
+      options: ['create', 'update', 'delete'],
+    }},
+    {{
+      name: 'user',
+      type: 'relationship',
+      relationTo: '{users}',
+      index: true,
+    }},
+    {{
+      name: 'userEmail',
+      type: 'text',
+    }},
+    {{
+      name: 'changes',
+      type: 'json',
+      admin: {{
+        description: 'Changed fields with their values before and after; empty for creates and deletes',
+      }},
+    }},
+  ],
+}};
+
+export default {name};
+"
+    )
+}
+
+fn audit_hooks(slug: &str, users: &str, ignored: &[String]) -> String {
+    let ignored = ignored
+        .iter()
+        .map(|field| format!("'{}'", field.replace('\'', "\\'")))
+        .collect::<Vec<_>>()
+        .join(", ");
+    format!(
+        "import {{ CollectionAfterChangeHook, CollectionAfterDeleteHook, PayloadRequest }} from 'payload/types';
+
+const AUDIT_COLLECTION = '{slug}';
+const IGNORED_FIELDS = new Set([{ignored}]);
+
+type Changes = Record<string, {{ before: unknown; after: unknown }}>;
+
+/** Top-level fields whose values differ, compared as JSON. */
+const diff = (before: Record<string, unknown> = {{}}, after: Record<string, unknown> = {{}}): Changes => {{
+  const changes: Changes = {{}};
+  for (const key of new Set([...Object.keys(before), ...Object.keys(after)])) {{
+    if (IGNORED_FIELDS.has(key)) continue;
+    if (JSON.stringify(before[key]) !== JSON.stringify(after[key])) {{
+      changes[key] = {{ before: before[key], after: after[key] }};
+    }}
+  }}
+  return changes;
+}};
+
+const record = async (
+  req: PayloadRequest,
+  entry: {{ collection: string; documentId: string; operation: 'create' | 'update' | 'delete'; changes?: Changes }},
+): Promise<void> => {{
+  const user = req.user;
+  try {{
+    await req.payload.create({{
+      collection: AUDIT_COLLECTION,
+      data: {{
+        ...entry,
+        user: user && user.collection === '{users}' ? user.id : null,
+        userEmail: user?.email ?? null,
+      }},
+      req,
+    }});
+  }} catch (error) {{
+    // A failed entry is logged rather than failing the change it records.
+    req.payload.logger.error(`Could not write audit log entry: ${{error}}`);
+  }}
+}};
+
+export const auditAfterChange: CollectionAfterChangeHook = async ({{ collection, doc, previousDoc, operation, req }}) => {{
+  const changes = operation === 'update' ? diff(previousDoc, doc) : undefined;
+  if (changes && Object.keys(changes).length === 0) {{
+    return doc;
+  }}
+  await record(req, {{ collection: collection.slug, documentId: String(doc.id), operation, changes }});
+  return doc;
+}};
+
+export const auditAfterDelete: CollectionAfterDeleteHook = async ({{ collection, doc, req }}) => {{
+  await record(req, {{ collection: collection.slug, documentId: String(doc.id), operation: 'delete' }});
+  return doc;
+}};
+"
+    )
+}
+
+fn prune_audit_log(slug: &str, days: u32) -> String {
+    format!(
+        "import type {{ Payload }} from 'payload';
+
+const RETENTION_DAYS = {days};
+const DAY = 24 * 60 * 60 * 1000;
+
+/** Delete audit log entries older than the retention period. */
+export const pruneAuditLog = async (payload: Payload): Promise<void> => {{
+  const cutoff = new Date(Date.now() - RETENTION_DAYS * DAY).toISOString();
+  await payload.delete({{
+    collection: '{slug}',
+    where: {{ createdAt: {{ less_than: cutoff }} }},
+  }});
+}};
+
+/** Prune on start and then daily; call from the config's onInit. */
+export const scheduleAuditLogPruning = (payload: Payload): void => {{
+  const run = () =>
+    pruneAuditLog(payload).catch((error) => payload.logger.error(`Audit log pruning failed: ${{error}}`));
+  void run();
+  setInterval(run, DAY).unref();
+}};
+"
+    )
+}
+
+/// The audit log collection, hooks and retention for the audited
+/// collections.
+pub fn audit_log_config(options: &AuditLogOptions) -> Result<AuditLogConfig, String> {
+    let slug = options.slug.as_deref().unwrap_or("audit-logs");
+    let users = options.users_slug.as_deref().unwrap_or("users");
+    if options.collections.is_empty() {
+        return Err("At least one collection to audit is required".to_string());
+    }
+    for value in options
+        .collections
+        .iter()
+        .map(String::as_str)
+        .chain([slug, users])
+    {
+        if !is_slug(value) {
+            return Err(format!("Invalid collection slug '{value}'"));
+        }
+    }
+    if options
+        .collections
+        .iter()
+        .any(|collection| collection == slug)
+    {
+        return Err(format!(
+            "The audit log collection '{slug}' can't audit itself"
+        ));
+    }
+    if options.retention_days == Some(0) {
+        return Err("retentionDays must be at least 1".to_string());
+    }
+
+    let name = schema_name(slug);
+    let mut ignored: Vec<String> = IGNORED_FIELDS
+        .iter()
+        .map(|field| field.to_string())
+        .collect();
+    for field in options.ignore_fields.iter().flatten() {
+        if !ignored.contains(field) {
+            ignored.push(field.clone());
+        }
+    }
+
+    let mut imports = vec![format!("import {name} from './collections/{name}';")];
+    let mut files = BTreeMap::from([
+        (
+            format!("src/collections/{name}.ts"),
+            audit_collection(slug, &name, users),
+        ),
+        (
+            "src/hooks/auditLog.ts".to_string(),
+            audit_hooks(slug, users, &ignored),
+        ),
+    ]);
+    let on_init = options.retention_days.map(|days| {
+        imports
+            .push("import { scheduleAuditLogPruning } from './hooks/pruneAuditLog';".to_string());
+        files.insert(
+            "src/hooks/pruneAuditLog.ts".to_string(),
+            prune_audit_log(slug, days),
+        );
+        "scheduleAuditLogPruning(payload);".to_string()
+    });
+    let collections = options
+        .collections
+        .iter()
+        .map(|collection| {
+            (
+                collection.clone(),
+                "hooks: {\n    afterChange: [auditAfterChange],\n    afterDelete: [auditAfterDelete],\n  },"
+                    .to_string(),
+            )
+        })
+        .collect();
+
+    Ok(AuditLogConfig {
+        imports,
+        collection: name,
+        on_init,
+        collections,
+        files,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_audit_log_config_with_retention() {
+        let options = AuditLogOptions {
+            collections: vec!["posts".to_string(), "pages".to_string()],
+            retention_days: Some(30),
+            ignore_fields: Some(vec!["views".to_string()]),
+            ..Default::default()
+        };
+        let config = audit_log_config(&options).unwrap();
+        assert_eq!(config.collection, "AuditLogs");
+        assert_eq!(
+            config.on_init.as_deref(),
+            Some("scheduleAuditLogPruning(payload);")
+        );
+        assert_eq!(config.collections.len(), 2);
+        assert!(config.files["src/collections/AuditLogs.ts"].contains("relationTo: 'users'"));
+        assert!(config.files["src/hooks/auditLog.ts"].contains("'_verificationToken', 'views']"));
+        assert!(config.files["src/hooks/pruneAuditLog.ts"].contains("const RETENTION_DAYS = 30;"));
+
+        let without_retention = AuditLogOptions {
+            retention_days: None,
+            ..options.clone()
+        };
+        let config = audit_log_config(&without_retention).unwrap();
+        assert!(config.on_init.is_none());
+        assert!(!config.files.contains_key("src/hooks/pruneAuditLog.ts"));
+
+        let recursive = AuditLogOptions {
+            collections: vec!["audit-logs".to_string()],
+            ..options
+        };
+        assert!(audit_log_config(&recursive).is_err());
+    }
+}
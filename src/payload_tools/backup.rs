@@ -0,0 +1,404 @@
+//! Backups of a live Payload instance as a timestamped directory on disk:
+//!
+//! - `manifest.json`: when the backup was taken and what it holds
+//! - `content.ndjson`: collection documents in the `export_content` format
+//! - `globals.json`: each global's data by slug
+//! - `media.ndjson`: with `media`, the files of upload documents, one
+//!   `{"collection", "id", "filename", "mimeType", "filesize", "url"}` per line
+//!
+//! Restores replay `content.ndjson` through `import_content`, so IDs are kept
+//! or remapped the same way. Media files are listed, not downloaded; copy them
+//! into the target's storage separately.
+
+use std::{
+    collections::BTreeMap,
+    fs,
+    io::{BufRead, BufReader, BufWriter, Write},
+    path::Path,
+};
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value, json};
+
+use crate::payload_tools::{
+    client::PayloadClient,
+    content::{ContentExportOptions, ContentImport, ContentImportOptions, ImportMode, export_content, import_content},
+    schema_model::{SchemaModel, live_schema},
+};
+
+const MANIFEST: &str = "manifest.json";
+const CONTENT: &str = "content.ndjson";
+const GLOBALS: &str = "globals.json";
+const MEDIA: &str = "media.ndjson";
+/// Fields of an upload document recorded in the media manifest.
+const MEDIA_FIELDS: [&str; 4] = ["filename", "mimeType", "filesize", "url"];
+/// Fields Payload sets on a global itself; they are not sent back on restore.
+const GLOBAL_META: [&str; 4] = ["id", "globalType", "createdAt", "updatedAt"];
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct BackupOptions {
+    /// Collections to back up, in restore order; defaults to every collection
+    /// of the live schema
+    pub collections: Option<Vec<String>>,
+    /// Globals to back up; the live schema does not list them
+    pub globals: Option<Vec<String>>,
+    /// Only back up slugs matching one of these glob patterns (e.g. `blog-*`)
+    pub include: Option<Vec<String>>,
+    /// Skip slugs matching one of these glob patterns
+    pub exclude: Option<Vec<String>>,
+    /// Write a manifest of upload documents' files to `media.ndjson`
+    pub media: Option<bool>,
+    /// Documents fetched per request, default 100
+    pub batch_size: Option<usize>,
+    pub locale: Option<String>,
+    /// Back up the latest drafts instead of published versions
+    pub draft: Option<bool>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct RestoreOptions {
+    pub mode: Option<ImportMode>,
+    /// Only restore slugs matching one of these glob patterns
+    pub include: Option<Vec<String>>,
+    /// Skip slugs matching one of these glob patterns
+    pub exclude: Option<Vec<String>>,
+    /// Documents looked up per request in upsert mode, default 100
+    pub batch_size: Option<usize>,
+    /// Locates relationship fields to rewrite when IDs change
+    pub schema: Option<SchemaModel>,
+    /// Return the plan without writing to the instance
+    pub dry_run: Option<bool>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct BackupManifest {
+    /// RFC 3339 time the backup was started
+    pub created_at: String,
+    /// Documents per collection, in restore order
+    pub collections: Vec<CollectionCount>,
+    pub globals: Vec<String>,
+    /// Upload documents listed in `media.ndjson`, when written
+    pub media: Option<usize>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct CollectionCount {
+    pub slug: String,
+    pub documents: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct Backup {
+    /// Directory the backup was written to
+    pub path: String,
+    pub manifest: BackupManifest,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct RestorePlan {
+    /// When the backup was taken
+    pub created_at: String,
+    pub mode: ImportMode,
+    /// Collections restored, in order, with their document counts
+    pub collections: Vec<CollectionCount>,
+    pub globals: Vec<String>,
+    /// Slugs in the backup left out by `include` / `exclude`
+    pub skipped: Vec<String>,
+    /// Upload files to copy into the target's storage by hand
+    pub media: usize,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+pub struct GlobalFailure {
+    pub slug: String,
+    pub error: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+pub struct Restore {
+    pub plan: RestorePlan,
+    /// Set unless `dryRun`
+    pub import: Option<ContentImport>,
+    /// Globals updated
+    pub globals: Vec<String>,
+    pub failures: Vec<GlobalFailure>,
+}
+
+/// Whether `slug` passes the `include` / `exclude` glob patterns. Invalid
+/// patterns match nothing.
+fn selected(slug: &str, include: Option<&[String]>, exclude: Option<&[String]>) -> bool {
+    let matches = |patterns: &[String]| {
+        patterns
+            .iter()
+            .any(|pattern| glob::Pattern::new(pattern).is_ok_and(|pattern| pattern.matches(slug)))
+    };
+    include.is_none_or(matches) && !exclude.is_some_and(matches)
+}
+
+/// Back up `client` into a new `payload-backup-<timestamp>` directory under
+/// `dir`.
+pub fn backup_instance(client: &PayloadClient, dir: &Path, options: &BackupOptions) -> Result<Backup, String> {
+    let now = chrono::Utc::now();
+    let collections = match &options.collections {
+        Some(collections) => collections.clone(),
+        None => live_schema(client)?
+            .collections
+            .into_iter()
+            .map(|collection| collection.slug)
+            .collect(),
+    };
+    let (include, exclude) = (options.include.as_deref(), options.exclude.as_deref());
+    let collections: Vec<String> = collections
+        .into_iter()
+        .filter(|slug| selected(slug, include, exclude))
+        .collect();
+    let globals: Vec<String> = options
+        .globals
+        .iter()
+        .flatten()
+        .filter(|slug| selected(slug, include, exclude))
+        .cloned()
+        .collect();
+    if collections.is_empty() && globals.is_empty() {
+        return Err("Nothing to back up: no collection or global passes the filters".to_string());
+    }
+
+    let path = dir.join(format!("payload-backup-{}", now.format("%Y%m%dT%H%M%SZ")));
+    fs::create_dir_all(&path).map_err(|err| format!("Failed to create {}: {err}", path.display()))?;
+
+    let mut counts = BTreeMap::new();
+    if !collections.is_empty() {
+        let export = ContentExportOptions {
+            collections: collections.clone(),
+            batch_size: options.batch_size,
+            locale: options.locale.clone(),
+            draft: options.draft,
+        };
+        let mut out = BufWriter::new(create(&path.join(CONTENT))?);
+        counts = export_content(client, &export, &mut out)?;
+        out.flush().map_err(|err| format!("Failed to write {CONTENT}: {err}"))?;
+    } else {
+        create(&path.join(CONTENT))?;
+    }
+
+    let mut data = Map::new();
+    for slug in &globals {
+        let mut params = vec![("depth".to_string(), "0".to_string())];
+        if let Some(locale) = &options.locale {
+            params.push(("locale".to_string(), locale.clone()));
+        }
+        if options.draft.unwrap_or(false) {
+            params.push(("draft".to_string(), "true".to_string()));
+        }
+        let global = client.find_global(slug, &params).map_err(|err| err.to_string())?;
+        data.insert(slug.clone(), global);
+    }
+    write_json(&path.join(GLOBALS), &Value::Object(data))?;
+
+    let media = if options.media.unwrap_or(false) {
+        Some(write_media(&path)?)
+    } else {
+        None
+    };
+
+    let manifest = BackupManifest {
+        created_at: now.to_rfc3339(),
+        collections: collections
+            .iter()
+            .map(|slug| CollectionCount {
+                slug: slug.clone(),
+                documents: counts.get(slug).copied().unwrap_or_default(),
+            })
+            .collect(),
+        globals,
+        media,
+    };
+    write_json(&path.join(MANIFEST), &json!(manifest))?;
+    tracing::info!("Backed up {} collections to {}", manifest.collections.len(), path.display());
+    Ok(Backup {
+        path: path.display().to_string(),
+        manifest,
+    })
+}
+
+/// List the files of upload documents in `content.ndjson`: those with a
+/// `filename` and a `mimeType`.
+fn write_media(path: &Path) -> Result<usize, String> {
+    let input = BufReader::new(open(&path.join(CONTENT))?);
+    let mut out = BufWriter::new(create(&path.join(MEDIA))?);
+    let mut count = 0;
+    for line in input.lines() {
+        let line = line.map_err(|err| format!("Failed to read {CONTENT}: {err}"))?;
+        let Ok(entry) = serde_json::from_str::<Value>(&line) else {
+            continue;
+        };
+        let doc = &entry["doc"];
+        if !doc["filename"].is_string() || !doc["mimeType"].is_string() {
+            continue;
+        }
+        let mut file = Map::new();
+        file.insert("collection".to_string(), entry["collection"].clone());
+        file.insert("id".to_string(), doc["id"].clone());
+        for field in MEDIA_FIELDS {
+            file.insert(field.to_string(), doc[field].clone());
+        }
+        writeln!(out, "{}", Value::Object(file)).map_err(|err| format!("Failed to write {MEDIA}: {err}"))?;
+        count += 1;
+    }
+    out.flush().map_err(|err| format!("Failed to write {MEDIA}: {err}"))?;
+    Ok(count)
+}
+
+/// What restoring the backup at `path` with `options` would write.
+pub fn restore_plan(path: &Path, options: &RestoreOptions) -> Result<RestorePlan, String> {
+    let manifest: BackupManifest = read_json(&path.join(MANIFEST))?;
+    let (include, exclude) = (options.include.as_deref(), options.exclude.as_deref());
+    let mut plan = RestorePlan {
+        created_at: manifest.created_at,
+        mode: options.mode.unwrap_or_default(),
+        ..Default::default()
+    };
+    for collection in manifest.collections {
+        if selected(&collection.slug, include, exclude) {
+            plan.collections.push(collection);
+        } else {
+            plan.skipped.push(collection.slug);
+        }
+    }
+    for slug in manifest.globals {
+        if selected(&slug, include, exclude) {
+            plan.globals.push(slug);
+        } else {
+            plan.skipped.push(slug);
+        }
+    }
+    if manifest.media.is_some() {
+        let restored: Vec<&str> = plan.collections.iter().map(|collection| collection.slug.as_str()).collect();
+        for line in BufReader::new(open(&path.join(MEDIA))?).lines() {
+            let line = line.map_err(|err| format!("Failed to read {MEDIA}: {err}"))?;
+            let file: Value = serde_json::from_str(&line).unwrap_or_default();
+            if file["collection"].as_str().is_some_and(|slug| restored.contains(&slug)) {
+                plan.media += 1;
+            }
+        }
+    }
+    Ok(plan)
+}
+
+/// Restore the backup at `path` into `client`: collections in the order they
+/// were backed up, then globals. With `dryRun` only the plan is returned.
+pub fn restore_instance(client: &PayloadClient, path: &Path, options: &RestoreOptions) -> Result<Restore, String> {
+    let plan = restore_plan(path, options)?;
+    let mut restore = Restore {
+        plan,
+        ..Default::default()
+    };
+    if options.dry_run.unwrap_or(false) {
+        return Ok(restore);
+    }
+
+    let import = ContentImportOptions {
+        mode: options.mode,
+        batch_size: options.batch_size,
+        collections: Some(restore.plan.collections.iter().map(|collection| collection.slug.clone()).collect()),
+        schema: options.schema.clone(),
+    };
+    restore.import = Some(import_content(client, BufReader::new(open(&path.join(CONTENT))?), &import)?);
+
+    let mut globals: Map<String, Value> = read_json(&path.join(GLOBALS))?;
+    for slug in &restore.plan.globals {
+        let Some(Value::Object(mut data)) = globals.remove(slug) else {
+            restore.failures.push(GlobalFailure {
+                slug: slug.clone(),
+                error: format!("missing from {GLOBALS}"),
+            });
+            continue;
+        };
+        for field in GLOBAL_META {
+            data.remove(field);
+        }
+        match client.update_global(slug, &Value::Object(data)) {
+            Ok(_) => restore.globals.push(slug.clone()),
+            Err(err) => restore.failures.push(GlobalFailure {
+                slug: slug.clone(),
+                error: err.to_string(),
+            }),
+        }
+    }
+    Ok(restore)
+}
+
+fn create(path: &Path) -> Result<fs::File, String> {
+    fs::File::create(path).map_err(|err| format!("Failed to create {}: {err}", path.display()))
+}
+
+fn open(path: &Path) -> Result<fs::File, String> {
+    fs::File::open(path).map_err(|err| format!("Failed to open {}: {err}", path.display()))
+}
+
+fn write_json(path: &Path, value: &Value) -> Result<(), String> {
+    let text = serde_json::to_string_pretty(value).map_err(|err| err.to_string())?;
+    fs::write(path, text).map_err(|err| format!("Failed to write {}: {err}", path.display()))
+}
+
+fn read_json<T: serde::de::DeserializeOwned>(path: &Path) -> Result<T, String> {
+    let text = fs::read_to_string(path).map_err(|err| format!("Failed to read {}: {err}", path.display()))?;
+    serde_json::from_str(&text).map_err(|err| format!("Invalid {}: {err}", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_restore_plan_filters_and_media() {
+        let dir = std::env::temp_dir().join(format!("mcp-backup-test-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(
+            dir.join(CONTENT),
+            [
+                r#"{"collection":"media","doc":{"id":1,"filename":"a.png","mimeType":"image/png","filesize":10,"url":"/media/a.png"}}"#,
+                r#"{"collection":"posts","doc":{"id":2,"title":"Hello","hero":1}}"#,
+                r#"{"collection":"drafts","doc":{"id":3}}"#,
+            ]
+            .join("\n"),
+        )
+        .unwrap();
+        assert_eq!(write_media(&dir).unwrap(), 1);
+        let manifest = BackupManifest {
+            created_at: "2026-01-01T00:00:00+00:00".to_string(),
+            collections: ["media", "posts", "drafts"]
+                .map(|slug| CollectionCount { slug: slug.to_string(), documents: 1 })
+                .to_vec(),
+            globals: vec!["site".to_string()],
+            media: Some(1),
+        };
+        write_json(&dir.join(MANIFEST), &json!(manifest)).unwrap();
+
+        let options = RestoreOptions {
+            exclude: Some(vec!["draft*".to_string()]),
+            dry_run: Some(true),
+            ..Default::default()
+        };
+        let plan = restore_plan(&dir, &options).unwrap();
+        let slugs: Vec<_> = plan.collections.iter().map(|collection| collection.slug.as_str()).collect();
+        assert_eq!(slugs, vec!["media", "posts"]);
+        assert_eq!(plan.globals, vec!["site"]);
+        assert_eq!(plan.skipped, vec!["drafts"]);
+        assert_eq!(plan.media, 1);
+
+        let options = RestoreOptions {
+            include: Some(vec!["posts".to_string()]),
+            ..Default::default()
+        };
+        let plan = restore_plan(&dir, &options).unwrap();
+        assert_eq!(plan.media, 0);
+        assert_eq!(plan.skipped, vec!["media", "drafts", "site"]);
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,287 @@
+//! Sorting schema changes into breaking and non-breaking, for gating a
+//! deploy on the content model. A change is breaking when existing
+//! documents or API clients stop working with it: a removed field or
+//! collection, a changed value shape, or a new constraint that documents
+//! already stored can fail.
+
+use std::collections::BTreeSet;
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::payload_tools::{
+    schema_model::SchemaModel,
+    schema_registry::{ChangeKind, SchemaChange, SchemaVersion, diff_schemas, version_pair},
+    storage::Storage,
+};
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct BreakingChangeOptions {
+    /// Registry name the versions are stored under
+    pub name: String,
+    /// Baseline version; defaults to the version before `to`, or the latest
+    /// when comparing a candidate schema
+    pub from: Option<u32>,
+    /// Version to check; defaults to the latest. Ignored when a candidate
+    /// schema is given
+    pub to: Option<u32>,
+    /// Collections that hold documents. New required or unique constraints
+    /// only break populated collections; every collection counts as
+    /// populated when this is not given
+    pub populated: Option<Vec<String>>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub enum Severity {
+    Breaking,
+    NonBreaking,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ClassifiedChange {
+    #[serde(flatten)]
+    pub change: SchemaChange,
+    pub severity: Severity,
+    /// How to ship the change without breaking documents or clients
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub remediation: Option<String>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct BreakingChangeReport {
+    pub from: SchemaVersion,
+    /// The version checked; absent for a candidate schema
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub to: Option<SchemaVersion>,
+    /// No breaking changes
+    pub passed: bool,
+    pub breaking: usize,
+    pub non_breaking: usize,
+    /// Breaking changes first
+    pub changes: Vec<ClassifiedChange>,
+}
+
+const BACKFILL: &str = "Give the field a defaultValue or backfill existing documents (see the migration from diff_schema_versions) before making it required";
+const DEPRECATE: &str = "Hide it with admin.hidden for a release, move its data and update clients that read it, then remove it";
+
+fn strings(value: Option<&Value>) -> BTreeSet<&str> {
+    value
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+        .filter_map(Value::as_str)
+        .collect()
+}
+
+/// Severity of `change` and a remediation when it is breaking.
+fn classify_change(change: &SchemaChange, populated: bool) -> (Severity, Option<&'static str>) {
+    use ChangeKind::*;
+
+    let turned_on = change.after == Some(Value::Bool(true));
+    let breaking = |hint| (Severity::Breaking, Some(hint));
+    match change.kind {
+        Removed if change.field.is_none() => breaking(
+            "Export its documents and remove relationships to it first; hide it from the admin for a release before deleting",
+        ),
+        Removed
+            if change
+                .before
+                .as_ref()
+                .is_some_and(|before| before.get("type").is_none()) =>
+        {
+            breaking(
+                "Documents using the block no longer validate; convert or remove those blocks before removing it",
+            )
+        }
+        Removed => breaking(DEPRECATE),
+        Added
+            if populated
+                && change.field.is_some()
+                && change
+                    .after
+                    .as_ref()
+                    .and_then(|after| after.get("required"))
+                    == Some(&Value::Bool(true)) =>
+        {
+            breaking(BACKFILL)
+        }
+        TypeChanged => breaking(
+            "Add a field with the new type, backfill it from the old one, switch clients over, then remove the old field",
+        ),
+        RequiredAdded if populated => breaking(BACKFILL),
+        UniqueAdded if populated => breaking(
+            "Index creation fails on duplicate values; find and fix duplicates before deploying",
+        ),
+        HasManyChanged => breaking(
+            "Stored values change between a single value and a list; migrate documents and update clients together",
+        ),
+        LocalizedChanged => breaking(
+            "Stored values change between a plain value and one per locale; migrate documents in the same deploy",
+        ),
+        OptionsChanged
+            if !strings(change.before.as_ref()).is_subset(&strings(change.after.as_ref())) =>
+        {
+            breaking(
+                "Documents holding a removed option no longer validate; map them to a remaining option first",
+            )
+        }
+        RelationChanged => {
+            let before = strings(change.before.as_ref());
+            let after = strings(change.after.as_ref());
+            if !before.is_subset(&after) {
+                breaking(
+                    "Relationships to a removed collection no longer validate; clear or repoint them first",
+                )
+            } else if (before.len() > 1) != (after.len() > 1) {
+                breaking(
+                    "Values change between an ID and { relationTo, value }; migrate documents and update clients together",
+                )
+            } else {
+                (Severity::NonBreaking, None)
+            }
+        }
+        SettingChanged if !turned_on => breaking(
+            "Turning the setting off removes fields and endpoints clients may use; announce it and update clients first",
+        ),
+        _ => (Severity::NonBreaking, None),
+    }
+}
+
+/// Classify `changes`, breaking ones first; `populated` tells whether a
+/// collection holds documents.
+pub fn classify(
+    changes: Vec<SchemaChange>,
+    populated: impl Fn(&str) -> bool,
+) -> Vec<ClassifiedChange> {
+    let mut classified: Vec<ClassifiedChange> = changes
+        .into_iter()
+        .map(|change| {
+            let (severity, remediation) =
+                classify_change(&change, change.global || populated(&change.slug));
+            ClassifiedChange {
+                change,
+                severity,
+                remediation: remediation.map(str::to_string),
+            }
+        })
+        .collect();
+    classified.sort_by_key(|change| change.severity != Severity::Breaking);
+    classified
+}
+
+/// Check a registered version, or `candidate` when given, against its
+/// baseline in the registry.
+pub fn check_breaking_changes(
+    storage: &Storage,
+    options: &BreakingChangeOptions,
+    candidate: Option<&SchemaModel>,
+) -> Result<BreakingChangeReport, String> {
+    let name = &options.name;
+    let (from, from_schema, to, changes) = match candidate {
+        Some(candidate) => {
+            let (from, from_schema) = storage.schema_version(name, options.from)?;
+            let changes = diff_schemas(&from_schema, candidate);
+            (from, from_schema, None, changes)
+        }
+        None => {
+            let ((from, from_schema), (to, to_schema)) =
+                version_pair(storage, name, options.from, options.to)?;
+            let changes = diff_schemas(&from_schema, &to_schema);
+            (from, from_schema, Some(to), changes)
+        }
+    };
+    let populated = |slug: &str| {
+        from_schema.collection(slug).is_some()
+            && options
+                .populated
+                .as_ref()
+                .is_none_or(|populated| populated.iter().any(|populated| populated == slug))
+    };
+    let changes = classify(changes, populated);
+    let breaking = changes
+        .iter()
+        .filter(|change| change.severity == Severity::Breaking)
+        .count();
+    Ok(BreakingChangeReport {
+        from,
+        to,
+        passed: breaking == 0,
+        breaking,
+        non_breaking: changes.len() - breaking,
+        changes,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::payload_tools::schema_model::{SchemaCollection, SchemaField};
+
+    #[test]
+    fn classifies_changes_by_population() {
+        let from = SchemaModel {
+            collections: vec![
+                SchemaCollection {
+                    slug: "posts".to_string(),
+                    fields: vec![
+                        SchemaField::new("title", "text"),
+                        SchemaField::new("legacy", "text"),
+                        SchemaField {
+                            options: Some(vec!["draft".to_string()]),
+                            ..SchemaField::new("status", "select")
+                        },
+                    ],
+                    ..Default::default()
+                },
+                SchemaCollection {
+                    slug: "pages".to_string(),
+                    fields: vec![SchemaField::new("title", "text")],
+                    ..Default::default()
+                },
+            ],
+            globals: Vec::new(),
+        };
+        let mut to = from.clone();
+        let posts = &mut to.collections[0].fields;
+        posts.remove(1);
+        posts[0].required = true;
+        posts[1].options = Some(vec!["draft".to_string(), "published".to_string()]);
+        posts.push(SchemaField::new("summary", "textarea"));
+        to.collections[1].fields[0].required = true;
+
+        let changes = classify(diff_schemas(&from, &to), |slug| slug == "posts");
+        let summary: Vec<(Severity, &str, Option<&str>)> = changes
+            .iter()
+            .map(|change| {
+                (
+                    change.severity,
+                    change.change.slug.as_str(),
+                    change.change.field.as_deref(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                (Severity::Breaking, "posts", Some("title")),
+                (Severity::Breaking, "posts", Some("legacy")),
+                (Severity::NonBreaking, "posts", Some("status")),
+                (Severity::NonBreaking, "posts", Some("summary")),
+                (Severity::NonBreaking, "pages", Some("title")),
+            ]
+        );
+        assert!(
+            changes[0]
+                .remediation
+                .as_deref()
+                .unwrap()
+                .contains("defaultValue")
+        );
+        assert!(changes[2].remediation.is_none());
+    }
+}
@@ -0,0 +1,234 @@
+//! Bulk updates of a live instance with guardrails. A first call without
+//! `confirm` is always a dry run: it counts the matching documents, shows
+//! what the patch changes on a few of them and returns a confirmation token.
+//! The token hashes the collection, query, patch and matched IDs, so it only
+//! executes the update that was previewed, against the same documents.
+//!
+//! The patch is applied with Payload's bulk `PATCH`, `batchSize` IDs at a
+//! time; top-level keys replace the document's values.
+
+use std::{
+    collections::hash_map::DefaultHasher,
+    hash::{Hash, Hasher},
+};
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+use crate::payload_tools::{
+    client::PayloadClient,
+    content::id_key,
+    where_query::{QueryFormat, convert_query, where_params},
+};
+
+const DEFAULT_BATCH_SIZE: usize = 100;
+const DEFAULT_MAX_DOCUMENTS: usize = 1000;
+const DEFAULT_SAMPLES: usize = 3;
+/// Fields Payload manages itself; a patch may not set them.
+const PROTECTED_FIELDS: [&str; 3] = ["id", "createdAt", "updatedAt"];
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct BulkUpdateOptions {
+    pub collection: String,
+    /// Documents to update: a `where` object, a `where[...]` query string or
+    /// an SQL condition. Required; an empty query is refused.
+    #[serde(rename = "where")]
+    pub where_query: Value,
+    /// Format of `where`, inferred when omitted
+    pub format: Option<QueryFormat>,
+    /// Values to set on every matching document
+    pub patch: Map<String, Value>,
+    /// Token from the dry run; without it nothing is written
+    pub confirm: Option<String>,
+    /// Documents updated per request, default 100
+    pub batch_size: Option<usize>,
+    /// Refuse to update more documents than this, default 1000
+    pub max_documents: Option<usize>,
+    /// Documents shown with their changes in the dry run, default 3
+    pub samples: Option<usize>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct FieldChange {
+    pub field: String,
+    pub before: Value,
+    pub after: Value,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct SampleDiff {
+    pub id: Value,
+    /// Patched fields whose value changes; empty when the patch is a no-op
+    pub changes: Vec<FieldChange>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct BulkUpdateFailure {
+    pub id: Option<Value>,
+    pub error: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct BulkUpdate {
+    pub collection: String,
+    /// Documents matching the query
+    pub matched: usize,
+    /// Nothing was written
+    pub dry_run: bool,
+    pub samples: Vec<SampleDiff>,
+    /// Pass as `confirm` to run this update; set on dry runs
+    pub confirmation_token: Option<String>,
+    pub updated: usize,
+    pub failures: Vec<BulkUpdateFailure>,
+    pub warnings: Vec<String>,
+}
+
+/// The changes `patch` makes to `doc`.
+fn diff(doc: &Value, patch: &Map<String, Value>) -> Vec<FieldChange> {
+    patch
+        .iter()
+        .filter(|(field, after)| doc.get(field.as_str()).unwrap_or(&Value::Null) != *after)
+        .map(|(field, after)| FieldChange {
+            field: field.clone(),
+            before: doc.get(field.as_str()).cloned().unwrap_or(Value::Null),
+            after: after.clone(),
+        })
+        .collect()
+}
+
+/// Ties a confirmation to the collection, query, patch and matched IDs.
+fn confirmation_token(collection: &str, query: &Value, patch: &Map<String, Value>, ids: &[String]) -> String {
+    let mut hasher = DefaultHasher::new();
+    collection.hash(&mut hasher);
+    query.to_string().hash(&mut hasher);
+    Value::Object(patch.clone()).to_string().hash(&mut hasher);
+    ids.hash(&mut hasher);
+    format!("{:016x}", hasher.finish())
+}
+
+/// Preview the update, or run it when `confirm` matches the preview's token.
+pub fn bulk_update(client: &PayloadClient, options: &BulkUpdateOptions) -> Result<BulkUpdate, String> {
+    let query = convert_query(&options.where_query, options.format)?;
+    if query.json.as_object().is_none_or(Map::is_empty) {
+        return Err("where must constrain the documents to update".to_string());
+    }
+    if options.patch.is_empty() {
+        return Err("patch must set at least one field".to_string());
+    }
+    if let Some(field) = PROTECTED_FIELDS.iter().find(|field| options.patch.contains_key(**field)) {
+        return Err(format!("patch may not set {field}"));
+    }
+    let batch_size = options.batch_size.unwrap_or(DEFAULT_BATCH_SIZE).max(1);
+    let max_documents = options.max_documents.unwrap_or(DEFAULT_MAX_DOCUMENTS);
+    let samples = options.samples.unwrap_or(DEFAULT_SAMPLES);
+
+    let mut update = BulkUpdate {
+        collection: options.collection.clone(),
+        warnings: query.warnings.clone(),
+        ..Default::default()
+    };
+    let mut ids = Vec::new();
+    let mut page = 1;
+    loop {
+        let mut params = where_params(&query.json);
+        params.extend([
+            ("limit".to_string(), batch_size.to_string()),
+            ("page".to_string(), page.to_string()),
+            ("depth".to_string(), "0".to_string()),
+            ("sort".to_string(), "id".to_string()),
+        ]);
+        let body = client
+            .find_page(&options.collection, &params)
+            .map_err(|err| err.to_string())?;
+        let total = body["totalDocs"].as_u64().unwrap_or_default() as usize;
+        if total > max_documents {
+            return Err(format!(
+                "{total} documents match, more than maxDocuments ({max_documents}); narrow where or raise maxDocuments"
+            ));
+        }
+        let docs = body["docs"].as_array().cloned().unwrap_or_default();
+        for doc in &docs {
+            if update.samples.len() < samples {
+                update.samples.push(SampleDiff {
+                    id: doc["id"].clone(),
+                    changes: diff(doc, &options.patch),
+                });
+            }
+            ids.extend(doc.get("id").and_then(id_key));
+        }
+        if docs.is_empty() || !body["hasNextPage"].as_bool().unwrap_or(false) {
+            break;
+        }
+        page += 1;
+    }
+    update.matched = ids.len();
+
+    let token = confirmation_token(&options.collection, &query.json, &options.patch, &ids);
+    match &options.confirm {
+        None => {
+            update.dry_run = true;
+            update.confirmation_token = Some(token);
+            return Ok(update);
+        }
+        Some(confirm) if *confirm != token => {
+            return Err(
+                "confirm does not match this update: the query, patch or matching documents changed since the dry run; run it again"
+                    .to_string(),
+            );
+        }
+        Some(_) => {}
+    }
+
+    let patch = Value::Object(options.patch.clone());
+    for chunk in ids.chunks(batch_size) {
+        let params = [("where[id][in]".to_string(), chunk.join(","))];
+        match client.update_where(&options.collection, &params, &patch) {
+            Ok(body) => {
+                update.updated += body["docs"].as_array().map_or(0, Vec::len);
+                for error in body["errors"].as_array().into_iter().flatten() {
+                    update.failures.push(BulkUpdateFailure {
+                        id: error.get("id").cloned(),
+                        error: error["message"].as_str().unwrap_or("Update failed").to_string(),
+                    });
+                }
+            }
+            Err(err) => update.failures.push(BulkUpdateFailure {
+                id: None,
+                error: format!("{} documents from {}: {err}", chunk.len(), chunk[0]),
+            }),
+        }
+    }
+    tracing::info!("Bulk updated {} of {} documents in {}", update.updated, update.matched, options.collection);
+    Ok(update)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_diff_and_confirmation_token() {
+        let patch = json!({ "status": "published", "featured": true });
+        let patch = patch.as_object().unwrap();
+        let doc = json!({ "id": 1, "status": "draft", "featured": true });
+        assert_eq!(
+            diff(&doc, patch),
+            vec![FieldChange {
+                field: "status".to_string(),
+                before: json!("draft"),
+                after: json!("published"),
+            }]
+        );
+
+        let query = json!({ "status": { "equals": "draft" } });
+        let ids = vec!["1".to_string(), "2".to_string()];
+        let token = confirmation_token("posts", &query, patch, &ids);
+        assert_eq!(token, confirmation_token("posts", &query, patch, &ids));
+        assert_ne!(token, confirmation_token("posts", &query, patch, &ids[..1]));
+        assert_ne!(token, confirmation_token("pages", &query, patch, &ids));
+    }
+}
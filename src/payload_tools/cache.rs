@@ -0,0 +1,117 @@
+//! Bounded in-memory caches for deterministic work that agents repeat with
+//! the same inputs: generated templates and compiled `LIKE` patterns.
+//!
+//! Entries are keyed by a 64-bit hash of their inputs and evicted oldest
+//! first once a cache is full.
+
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{HashMap, VecDeque};
+use std::hash::{Hash, Hasher};
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use serde::Serialize;
+
+pub struct MemoCache<V> {
+    capacity: usize,
+    inner: Mutex<Entries<V>>,
+    hits: AtomicU64,
+    misses: AtomicU64,
+}
+
+struct Entries<V> {
+    values: HashMap<u64, V>,
+    /// Keys, oldest first
+    order: VecDeque<u64>,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize)]
+pub struct CacheStats {
+    pub entries: usize,
+    pub capacity: usize,
+    pub hits: u64,
+    pub misses: u64,
+}
+
+impl<V: Clone> MemoCache<V> {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            inner: Mutex::new(Entries {
+                values: HashMap::new(),
+                order: VecDeque::new(),
+            }),
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+        }
+    }
+
+    /// The cached value for `key`, computing and storing it on a miss.
+    /// Errors are returned without being cached.
+    pub fn get_or_try_insert<K: Hash + ?Sized, E>(
+        &self,
+        key: &K,
+        compute: impl FnOnce() -> Result<V, E>,
+    ) -> Result<V, E> {
+        let hash = hash_key(key);
+        if let Some(value) = self.inner.lock().ok().and_then(|inner| inner.values.get(&hash).cloned()) {
+            self.hits.fetch_add(1, Ordering::Relaxed);
+            return Ok(value);
+        }
+        self.misses.fetch_add(1, Ordering::Relaxed);
+        let value = compute()?;
+        if let Ok(mut inner) = self.inner.lock() {
+            if inner.values.insert(hash, value.clone()).is_none() {
+                inner.order.push_back(hash);
+            }
+            while inner.order.len() > self.capacity {
+                if let Some(evicted) = inner.order.pop_front() {
+                    inner.values.remove(&evicted);
+                }
+            }
+        }
+        Ok(value)
+    }
+
+    pub fn stats(&self) -> CacheStats {
+        CacheStats {
+            entries: self.inner.lock().map(|inner| inner.values.len()).unwrap_or(0),
+            capacity: self.capacity,
+            hits: self.hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+        }
+    }
+}
+
+fn hash_key<K: Hash + ?Sized>(key: &K) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    key.hash(&mut hasher);
+    hasher.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_memo_cache_hits_and_evicts() {
+        let cache = MemoCache::new(2);
+        let compute = |value: u32| move || Ok::<_, String>(value);
+        assert_eq!(cache.get_or_try_insert("a", compute(1)), Ok(1));
+        assert_eq!(cache.get_or_try_insert("a", compute(99)), Ok(1));
+        assert!(cache.get_or_try_insert("b", || Err("failed".to_string())).is_err());
+        cache.get_or_try_insert("b", compute(2)).unwrap();
+        cache.get_or_try_insert("c", compute(3)).unwrap();
+        // "a" was evicted, so it is computed again.
+        assert_eq!(cache.get_or_try_insert("a", compute(4)), Ok(4));
+        assert_eq!(
+            cache.stats(),
+            CacheStats {
+                entries: 2,
+                capacity: 2,
+                hits: 1,
+                misses: 5,
+            }
+        );
+    }
+}
@@ -0,0 +1,447 @@
+//! CI pipelines for scaffolded projects, on GitHub Actions or GitLab CI.
+//!
+//! Every pipeline typechecks, checks that the committed `payload-types.ts`
+//! matches `payload generate:types`, lints, and runs the Vitest suites against
+//! a service container of the project's database when tests are included.
+//! Pushes to the deploy branch then run one job per deployment target once
+//! the checks pass.
+
+use std::collections::BTreeMap;
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::payload_tools::{
+    multitenancy::directory,
+    scaffolder::{ScaffoldFile, ScaffoldFileStructure},
+};
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum CiProvider {
+    /// `.github/workflows/ci.yml`
+    #[default]
+    Github,
+    /// `.gitlab-ci.yml`
+    Gitlab,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "kebab-case")]
+pub enum DeployTarget {
+    /// Build the Dockerfile and push the image to a registry
+    Docker,
+    /// Build and deploy with the Vercel CLI
+    Vercel,
+    /// Move the branch Payload Cloud deploys to the tested commit
+    PayloadCloud,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct CiOptions {
+    /// CI service the pipeline is written for (default `github`)
+    pub provider: Option<CiProvider>,
+    /// Deployment jobs to add, run on pushes to `branch`
+    pub deploy: Option<Vec<DeployTarget>>,
+    /// Branch that deploys (default `main`)
+    pub branch: Option<String>,
+    /// Node.js major the jobs run on (default `20`)
+    pub node_version: Option<String>,
+    /// Image the docker target pushes, without a tag; defaults to the
+    /// repository's GitHub or GitLab container registry
+    pub image: Option<String>,
+    /// Branch Payload Cloud deploys from (default `production`)
+    pub cloud_branch: Option<String>,
+}
+
+/// The settings a pipeline is written from.
+struct Pipeline<'a> {
+    provider: CiProvider,
+    deploy: &'a [DeployTarget],
+    branch: &'a str,
+    node: &'a str,
+    image: Option<&'a str>,
+    cloud_branch: &'a str,
+    postgres: bool,
+    typescript: bool,
+    tests: bool,
+}
+
+fn is_branch(name: &str) -> bool {
+    !name.is_empty()
+        && !name.starts_with(['-', '/'])
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.' | '/'))
+}
+
+fn pipeline<'a>(
+    options: &'a CiOptions,
+    database: &str,
+    typescript: bool,
+    include_tests: bool,
+) -> Result<Pipeline<'a>, String> {
+    let pipeline = Pipeline {
+        provider: options.provider.unwrap_or_default(),
+        deploy: options.deploy.as_deref().unwrap_or_default(),
+        branch: options.branch.as_deref().unwrap_or("main"),
+        node: options.node_version.as_deref().unwrap_or("20"),
+        image: options.image.as_deref(),
+        cloud_branch: options.cloud_branch.as_deref().unwrap_or("production"),
+        postgres: database == "postgres",
+        typescript,
+        tests: include_tests,
+    };
+    for branch in [pipeline.branch, pipeline.cloud_branch] {
+        if !is_branch(branch) {
+            return Err(format!("CI branch {branch:?} is not a valid branch name"));
+        }
+    }
+    if pipeline.branch == pipeline.cloud_branch {
+        return Err("cloudBranch must differ from the branch that deploys".to_string());
+    }
+    if !pipeline
+        .node
+        .chars()
+        .all(|c| c.is_ascii_digit() || c == '.')
+        || pipeline.node.is_empty()
+    {
+        return Err(format!(
+            "CI nodeVersion {:?} is not a Node.js version",
+            pipeline.node
+        ));
+    }
+    if let Some(image) = pipeline.image {
+        if image.is_empty()
+            || image.contains([' ', '@'])
+            || image
+                .rsplit('/')
+                .next()
+                .is_some_and(|name| name.contains(':'))
+        {
+            return Err(format!(
+                "CI image {image:?} must be an image name without a tag"
+            ));
+        }
+    }
+    if pipeline.provider == CiProvider::Gitlab
+        && pipeline.deploy.contains(&DeployTarget::PayloadCloud)
+    {
+        return Err(
+            "Payload Cloud deploys from GitHub repositories; use the github provider".to_string(),
+        );
+    }
+    Ok(pipeline)
+}
+
+/// Registry host of `image`; `None` for Docker Hub.
+fn registry(image: &str) -> Option<&str> {
+    let (host, _) = image.split_once('/')?;
+    (host.contains(['.', ':']) || host == "localhost").then_some(host)
+}
+
+fn secret(name: &str) -> String {
+    format!("${{{{ secrets.{name} }}}}")
+}
+
+impl Pipeline<'_> {
+    fn database_env(&self, host: &str) -> (&'static str, String) {
+        if self.postgres {
+            (
+                "DATABASE_URI",
+                format!("postgres://postgres:postgres@{host}:5432/payload-test"),
+            )
+        } else {
+            (
+                "MONGODB_URI",
+                format!("mongodb://{host}:27017/payload-test"),
+            )
+        }
+    }
+
+    fn lint_extensions(&self) -> &'static str {
+        if self.typescript {
+            ".ts,.tsx"
+        } else {
+            ".js,.jsx"
+        }
+    }
+
+    fn github(&self) -> String {
+        let setup = format!(
+            "      - uses: actions/checkout@v4\n      - uses: actions/setup-node@v4\n        with:\n          node-version: {}\n          cache: yarn\n      - run: yarn install --frozen-lockfile\n",
+            self.node
+        );
+        let (db_var, db_uri) = self.database_env("localhost");
+        let mut out = format!(
+            "name: CI\n\non:\n  push:\n    branches: [{}]\n  pull_request:\n\nenv:\n  PAYLOAD_SECRET: ci-payload-secret\n  {db_var}: {db_uri}\n\njobs:\n",
+            self.branch
+        );
+        let mut checks = Vec::new();
+        if self.typescript {
+            checks.push("typecheck");
+            out.push_str(&format!("  typecheck:\n    runs-on: ubuntu-latest\n    steps:\n{setup}      - run: yarn typecheck\n\n"));
+        }
+        checks.push("types");
+        out.push_str(&format!(
+            "  types:\n    name: generated types are current\n    runs-on: ubuntu-latest\n    steps:\n{setup}      - run: yarn generate:types\n      - run: git diff --exit-code -- src/payload-types.ts\n\n"
+        ));
+        checks.push("lint");
+        out.push_str(&format!(
+            "  lint:\n    runs-on: ubuntu-latest\n    steps:\n{setup}      - run: yarn lint\n\n"
+        ));
+        if self.tests {
+            checks.push("integration");
+            let service = if self.postgres {
+                "      postgres:\n        image: postgres:16\n        env:\n          POSTGRES_USER: postgres\n          POSTGRES_PASSWORD: postgres\n          POSTGRES_DB: payload-test\n        ports:\n          - 5432:5432\n        options: >-\n          --health-cmd pg_isready\n          --health-interval 10s\n          --health-timeout 5s\n          --health-retries 5\n"
+            } else {
+                "      mongodb:\n        image: mongo:7\n        ports:\n          - 27017:27017\n"
+            };
+            out.push_str(&format!(
+                "  integration:\n    runs-on: ubuntu-latest\n    services:\n{service}    env:\n      TEST_DATABASE_URI: {db_uri}\n    steps:\n{setup}      - run: yarn test\n\n"
+            ));
+        }
+
+        let gate = format!(
+            "    needs: [{}]\n    if: github.event_name == 'push' && github.ref == 'refs/heads/{}'\n    runs-on: ubuntu-latest\n",
+            checks.join(", "),
+            self.branch
+        );
+        for target in self.deploy {
+            match target {
+                DeployTarget::Docker => {
+                    let image = self.image.unwrap_or("ghcr.io/${{ github.repository }}");
+                    let login = match registry(image) {
+                        Some("ghcr.io") => format!(
+                            "          registry: ghcr.io\n          username: ${{{{ github.actor }}}}\n          password: {}\n",
+                            secret("GITHUB_TOKEN")
+                        ),
+                        host => format!(
+                            "{}          username: {}\n          password: {}\n",
+                            host.map(|host| format!("          registry: {host}\n")).unwrap_or_default(),
+                            secret("REGISTRY_USERNAME"),
+                            secret("REGISTRY_PASSWORD")
+                        ),
+                    };
+                    out.push_str(&format!(
+                        "  deploy-docker:\n{gate}    permissions:\n      contents: read\n      packages: write\n    steps:\n      - uses: actions/checkout@v4\n      - uses: docker/login-action@v3\n        with:\n{login}      - uses: docker/build-push-action@v6\n        with:\n          context: .\n          push: true\n          tags: |\n            {image}:${{{{ github.sha }}}}\n            {image}:latest\n\n"
+                    ));
+                }
+                DeployTarget::Vercel => {
+                    let token = secret("VERCEL_TOKEN");
+                    out.push_str(&format!(
+                        "  deploy-vercel:\n{gate}    env:\n      VERCEL_ORG_ID: {}\n      VERCEL_PROJECT_ID: {}\n    steps:\n{setup}      - run: npx vercel pull --yes --environment=production --token={token}\n      - run: npx vercel build --prod --token={token}\n      - run: npx vercel deploy --prebuilt --prod --token={token}\n\n",
+                        secret("VERCEL_ORG_ID"),
+                        secret("VERCEL_PROJECT_ID")
+                    ));
+                }
+                DeployTarget::PayloadCloud => out.push_str(&format!(
+                    "  # Payload Cloud builds the {cloud} branch; select it in the project's settings.\n  deploy-payload-cloud:\n{gate}    permissions:\n      contents: write\n    steps:\n      - uses: actions/checkout@v4\n        with:\n          fetch-depth: 0\n      - run: git push --force origin HEAD:refs/heads/{cloud}\n\n",
+                    cloud = self.cloud_branch
+                )),
+            }
+        }
+        out.truncate(out.trim_end().len());
+        out.push('\n');
+        out
+    }
+
+    fn gitlab(&self) -> String {
+        let (db_var, _) = self.database_env("localhost");
+        let (_, db_uri) = self.database_env(if self.postgres { "postgres" } else { "mongodb" });
+        let mut out = format!(
+            "stages:\n  - check\n  - test\n  - deploy\n\ndefault:\n  image: node:{}\n  cache:\n    key:\n      files:\n        - yarn.lock\n    paths:\n      - node_modules/\n  before_script:\n    - yarn install --frozen-lockfile\n\nvariables:\n  PAYLOAD_SECRET: ci-payload-secret\n  {db_var}: {db_uri}\n\n",
+            self.node
+        );
+        if self.typescript {
+            out.push_str("typecheck:\n  stage: check\n  script:\n    - yarn typecheck\n\n");
+        }
+        out.push_str(
+            "types:\n  stage: check\n  script:\n    - yarn generate:types\n    - git diff --exit-code -- src/payload-types.ts\n\n",
+        );
+        out.push_str("lint:\n  stage: check\n  script:\n    - yarn lint\n\n");
+        if self.tests {
+            let service = if self.postgres {
+                "    - name: postgres:16\n      alias: postgres\n  variables:\n    POSTGRES_USER: postgres\n    POSTGRES_PASSWORD: postgres\n    POSTGRES_DB: payload-test\n"
+            } else {
+                "    - name: mongo:7\n      alias: mongodb\n  variables:\n"
+            };
+            out.push_str(&format!(
+                "integration:\n  stage: test\n  services:\n{service}    TEST_DATABASE_URI: {db_uri}\n  script:\n    - yarn test\n\n"
+            ));
+        }
+
+        let rules = format!(
+            "  rules:\n    - if: $CI_COMMIT_BRANCH == \"{}\"\n",
+            self.branch
+        );
+        for target in self.deploy {
+            match target {
+                DeployTarget::Docker => {
+                    let image = self.image.unwrap_or("$CI_REGISTRY_IMAGE");
+                    let login = match self.image {
+                        None => "    - docker login -u \"$CI_REGISTRY_USER\" -p \"$CI_REGISTRY_PASSWORD\" \"$CI_REGISTRY\"\n".to_string(),
+                        Some(image) => format!(
+                            "    - docker login -u \"$REGISTRY_USERNAME\" -p \"$REGISTRY_PASSWORD\"{}\n",
+                            registry(image).map(|host| format!(" {host}")).unwrap_or_default()
+                        ),
+                    };
+                    out.push_str(&format!(
+                        "deploy-docker:\n  stage: deploy\n  image: docker:27\n  services:\n    - docker:27-dind\n  before_script: []\n{rules}  script:\n{login}    - docker build -t \"{image}:$CI_COMMIT_SHA\" -t \"{image}:latest\" .\n    - docker push --all-tags \"{image}\"\n\n"
+                    ));
+                }
+                DeployTarget::Vercel => out.push_str(&format!(
+                    "deploy-vercel:\n  stage: deploy\n{rules}  script:\n    - npx vercel pull --yes --environment=production --token=\"$VERCEL_TOKEN\"\n    - npx vercel build --prod --token=\"$VERCEL_TOKEN\"\n    - npx vercel deploy --prebuilt --prod --token=\"$VERCEL_TOKEN\"\n\n"
+                )),
+                // Refused by `pipeline`.
+                DeployTarget::PayloadCloud => {}
+            }
+        }
+        out.truncate(out.trim_end().len());
+        out.push('\n');
+        out
+    }
+}
+
+fn eslint_config(typescript: bool) -> String {
+    if typescript {
+        "{\n  \"root\": true,\n  \"parser\": \"@typescript-eslint/parser\",\n  \"plugins\": [\"@typescript-eslint\"],\n  \"extends\": [\"eslint:recommended\", \"plugin:@typescript-eslint/recommended\"],\n  \"env\": { \"node\": true, \"es2020\": true },\n  \"ignorePatterns\": [\"dist\", \"build\", \"src/payload-types.ts\"]\n}\n"
+            .to_string()
+    } else {
+        "{\n  \"root\": true,\n  \"extends\": [\"eslint:recommended\"],\n  \"parserOptions\": { \"ecmaVersion\": 2020, \"sourceType\": \"module\" },\n  \"env\": { \"node\": true, \"es2020\": true },\n  \"ignorePatterns\": [\"dist\", \"build\"]\n}\n"
+            .to_string()
+    }
+}
+
+fn dockerfile(node: &str) -> String {
+    format!(
+        "FROM node:{node}-alpine AS build\nWORKDIR /app\nCOPY package.json yarn.lock ./\nRUN yarn install --frozen-lockfile\nCOPY . .\nRUN yarn build\n\nFROM node:{node}-alpine\nWORKDIR /app\nENV NODE_ENV=production\nENV PAYLOAD_CONFIG_PATH=dist/payload.config.js\nCOPY package.json yarn.lock ./\nRUN yarn install --frozen-lockfile --production\nCOPY --from=build /app/dist ./dist\nCOPY --from=build /app/build ./build\nEXPOSE 3000\nCMD [\"node\", \"dist/server.js\"]\n"
+    )
+}
+
+/// The pipeline and the files it needs (ESLint config, Dockerfile), by path
+/// from the project root.
+pub fn ci_files(
+    options: &CiOptions,
+    database: &str,
+    typescript: bool,
+    include_tests: bool,
+) -> Result<BTreeMap<String, String>, String> {
+    let pipeline = pipeline(options, database, typescript, include_tests)?;
+    let mut files = BTreeMap::from([(".eslintrc.json".to_string(), eslint_config(typescript))]);
+    match pipeline.provider {
+        CiProvider::Github => {
+            files.insert(".github/workflows/ci.yml".to_string(), pipeline.github())
+        }
+        CiProvider::Gitlab => files.insert(".gitlab-ci.yml".to_string(), pipeline.gitlab()),
+    };
+    if pipeline.deploy.contains(&DeployTarget::Docker) {
+        files.insert("Dockerfile".to_string(), dockerfile(pipeline.node));
+        files.insert(
+            ".dockerignore".to_string(),
+            "node_modules\ndist\nbuild\n.env\n.git\n".to_string(),
+        );
+    }
+    Ok(files)
+}
+
+/// Add the pipeline to a scaffold: its files, the `typecheck` and `lint`
+/// scripts with ESLint in `package.json`, and `payload-types.ts` taken out
+/// of `.gitignore` so the pipeline can compare it.
+pub fn apply_ci(
+    root: &mut ScaffoldFileStructure,
+    options: &CiOptions,
+    database: &str,
+    typescript: bool,
+    include_tests: bool,
+) -> Result<(), String> {
+    let pipeline = pipeline(options, database, typescript, include_tests)?;
+    let Some(ScaffoldFile::File(package_json)) = root.get_mut("package.json") else {
+        return Err("The project has no package.json".to_string());
+    };
+    let anchor = "\"generate:types\":";
+    let dev_anchor = "\"cross-env\":";
+    if !package_json.contains(anchor) || !package_json.contains(dev_anchor) {
+        return Err(
+            "package.json doesn't have the scaffolded scripts and devDependencies".to_string(),
+        );
+    }
+    let mut scripts = String::new();
+    if typescript {
+        scripts.push_str("\"typecheck\": \"tsc --noEmit\",\n    ");
+    }
+    scripts.push_str(&format!(
+        "\"lint\": \"eslint src --ext {}\",\n    ",
+        pipeline.lint_extensions()
+    ));
+    let mut dev_dependencies = "\"eslint\": \"^8.57.0\",\n    ".to_string();
+    if typescript {
+        dev_dependencies.push_str(
+            "\"@typescript-eslint/eslint-plugin\": \"^7.0.0\",\n    \"@typescript-eslint/parser\": \"^7.0.0\",\n    ",
+        );
+    }
+    *package_json = package_json
+        .replacen(anchor, &format!("{scripts}{anchor}"), 1)
+        .replacen(dev_anchor, &format!("{dev_dependencies}{dev_anchor}"), 1);
+
+    if let Some(ScaffoldFile::File(gitignore)) = root.get_mut(".gitignore") {
+        *gitignore = gitignore.replace("\n\n# payload\n/src/payload-types.ts", "");
+    }
+
+    for (path, code) in ci_files(options, database, typescript, include_tests)? {
+        let mut parts: Vec<&str> = path.split('/').collect();
+        let file = parts.pop().unwrap_or_default().to_string();
+        let dir = parts
+            .into_iter()
+            .fold(&mut *root, |dir, name| directory(dir, name));
+        dir.insert(file, ScaffoldFile::File(code));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::payload_tools::scaffolder::{ScaffoldOptions, scaffold_project};
+
+    #[test]
+    fn test_apply_ci_adds_pipeline_and_scripts() {
+        let options = ScaffoldOptions {
+            project_name: "site".to_string(),
+            database: Some("postgres".to_string()),
+            include_tests: Some(true),
+            ci: Some(CiOptions {
+                deploy: Some(vec![DeployTarget::Docker, DeployTarget::PayloadCloud]),
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        let root = scaffold_project(&options);
+        let Some(ScaffoldFile::File(package_json)) = root.get("package.json") else {
+            panic!("package.json missing");
+        };
+        let package_json: serde_json::Value = serde_json::from_str(package_json).unwrap();
+        assert_eq!(package_json["scripts"]["typecheck"], "tsc --noEmit");
+        assert!(package_json["devDependencies"]["eslint"].is_string());
+        let Some(ScaffoldFile::File(gitignore)) = root.get(".gitignore") else {
+            panic!(".gitignore missing");
+        };
+        assert!(!gitignore.contains("payload-types"));
+        assert!(root.contains_key("Dockerfile"));
+
+        let files = ci_files(options.ci.as_ref().unwrap(), "postgres", true, true).unwrap();
+        let workflow = &files[".github/workflows/ci.yml"];
+        assert!(workflow.contains("image: postgres:16"));
+        assert!(workflow.contains("needs: [typecheck, types, lint, integration]"));
+        assert!(workflow.contains("ghcr.io/${{ github.repository }}:${{ github.sha }}"));
+        assert!(workflow.contains("git push --force origin HEAD:refs/heads/production"));
+
+        let gitlab = CiOptions {
+            provider: Some(CiProvider::Gitlab),
+            deploy: Some(vec![DeployTarget::PayloadCloud]),
+            ..Default::default()
+        };
+        assert!(ci_files(&gitlab, "mongodb", true, false).is_err());
+    }
+}
@@ -7,6 +7,8 @@
 //! - Runtime configuration checks
 
 use crate::error::{ServiceError, ServiceResult};
+use crate::payload_tools::schema_cache::{self, Snapshot};
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
@@ -22,7 +24,7 @@ pub struct PayloadConfig {
     pub api_key: Option<String>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct CollectionInfo {
     pub slug: String,
     pub labels: Option<HashMap<String, String>>,
@@ -31,7 +33,7 @@ pub struct CollectionInfo {
     pub auth: Option<AuthConfig>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct FieldInfo {
     pub name: String,
     pub field_type: String,
@@ -41,7 +43,7 @@ pub struct FieldInfo {
     pub admin: Option<HashMap<String, serde_json::Value>>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct AuthConfig {
     pub use_api_key: Option<bool>,
     pub cookies: Option<HashMap<String, serde_json::Value>>,
@@ -54,7 +56,7 @@ pub struct GlobalInfo {
     pub fields: Vec<FieldInfo>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct ServerInfo {
     pub payload_version: String,
     pub server_url: String,
@@ -100,8 +102,85 @@ impl PayloadClient {
         })
     }
 
-    /// Get collection schema from live Payload instance
+    /// Get collection schema from live Payload instance, cached per
+    /// [`schema_cache`]
     pub fn get_collection(&self, slug: &str) -> ServiceResult<CollectionInfo> {
+        let text = self.get_schema(slug, &format!("fetch collection {slug}"))?;
+        tracing::info!("Fetched collection {slug} from {}", self.base_url);
+        // Parse response - this would be actual JSON parsing in real implementation
+        self.parse_collection_response(&text, slug)
+    }
+
+    /// List all collections from live Payload instance, cached per
+    /// [`schema_cache`]
+    pub fn list_collections(&self) -> ServiceResult<Vec<String>> {
+        let _text = self.get_schema("collections", "list collections")?;
+        tracing::info!("Listed collections from {}", self.base_url);
+        // Parse collection list - mock implementation
+        Ok(vec!["users".to_string(), "posts".to_string(), "pages".to_string()])
+    }
+
+    /// Body of `/api/{path}`, from the schema cache while fresh and
+    /// revalidated with the cached ETag / Last-Modified once stale.
+    fn get_schema(&self, path: &str, action: &str) -> ServiceResult<String> {
+        let url = format!("{}/api/{}", self.base_url, path);
+        let key = schema_cache::key(&url, self.api_key.as_deref());
+        schema_cache::get_or_fetch(&key, |previous| {
+            let mut request = ureq::get(&url);
+
+            if let Some(api_key) = &self.api_key {
+                request = request.set("Authorization", &format!("Bearer {}", api_key));
+            }
+            if let Some(previous) = previous {
+                if let Some(etag) = &previous.etag {
+                    request = request.set("If-None-Match", etag);
+                }
+                if let Some(last_modified) = &previous.last_modified {
+                    request = request.set("If-Modified-Since", last_modified);
+                }
+            }
+
+            let response = request
+                .call()
+                .map_err(|e| ServiceError::NetworkError(format!("Failed to {}: {}", action, e)))?;
+
+            if response.status() == 304 && previous.is_some() {
+                tracing::debug!("{url} not modified; keeping cached schema");
+                return Ok(None);
+            }
+            if response.status() < 200 || response.status() >= 300 {
+                return Err(ServiceError::ApiError(format!(
+                    "Failed to {}: HTTP {}",
+                    action,
+                    response.status()
+                )));
+            }
+
+            let etag = response.header("ETag").map(str::to_string);
+            let last_modified = response.header("Last-Modified").map(str::to_string);
+            let text = response.into_string().map_err(|e| {
+                ServiceError::NetworkError(format!("Failed to read response: {}", e))
+            })?;
+            Ok(Some(Snapshot::new(text, etag, last_modified)))
+        })
+    }
+
+    /// Find documents in a collection, passing REST query parameters through
+    /// (e.g. `where[status][equals]=draft`, `sort=-createdAt`, `limit=10`)
+    pub fn find_documents(&self, slug: &str, params: &[(String, String)]) -> ServiceResult<Vec<serde_json::Value>> {
+        let body = self.find_page(slug, params)?;
+        let docs = body
+            .get("docs")
+            .and_then(|docs| docs.as_array())
+            .cloned()
+            .unwrap_or_default();
+        tracing::info!("Fetched {} documents from collection {slug}", docs.len());
+        Ok(docs)
+    }
+
+    /// One page of a find, with Payload's pagination fields (`docs`,
+    /// `hasNextPage`, `totalDocs`, ...)
+    pub fn find_page(&self, slug: &str, params: &[(String, String)]) -> ServiceResult<serde_json::Value> {
         let url = format!("{}/api/{}", self.base_url, slug);
 
         let mut request = ureq::get(&url);
@@ -110,29 +189,47 @@ impl PayloadClient {
             request = request.set("Authorization", &format!("Bearer {}", api_key));
         }
 
+        for (key, value) in params {
+            request = request.query(key, value);
+        }
+
         let response = request
             .call()
-            .map_err(|e| ServiceError::NetworkError(format!("Failed to fetch collection {}: {}", slug, e)))?;
+            .map_err(|e| ServiceError::NetworkError(format!("Failed to query collection {}: {}", slug, e)))?;
 
         if response.status() < 200 || response.status() >= 300 {
             return Err(ServiceError::ApiError(format!(
-                "Failed to get collection {}: HTTP {}",
+                "Failed to query collection {}: HTTP {}",
                 slug,
                 response.status()
             )));
         }
 
-        let text = response.into_string().map_err(|e| {
+        response.into_json().map_err(|e| {
             ServiceError::NetworkError(format!("Failed to read response: {}", e))
-        })?;
+        })
+    }
 
-        // Parse response - this would be actual JSON parsing in real implementation
-        self.parse_collection_response(&text, slug)
+    /// One page of a find with localized fields in `locale` only. Fallback
+    /// is disabled, so a missing translation reads as `null` instead of the
+    /// default locale's value
+    pub fn find_page_in_locale(
+        &self,
+        slug: &str,
+        locale: &str,
+        params: &[(String, String)],
+    ) -> ServiceResult<serde_json::Value> {
+        let mut params = params.to_vec();
+        params.push(("locale".to_string(), locale.to_string()));
+        params.push(("fallback-locale".to_string(), "none".to_string()));
+        self.find_page(slug, &params)
     }
 
-    /// List all collections from live Payload instance
-    pub fn list_collections(&self) -> ServiceResult<Vec<String>> {
-        let url = format!("{}/api/collections", self.base_url);
+    /// Permissions of the client's user from `/api/access`: per collection
+    /// and global, each operation's `permission` and, when query-constrained,
+    /// its `where`
+    pub fn get_access(&self) -> ServiceResult<serde_json::Value> {
+        let url = format!("{}/api/access", self.base_url);
 
         let mut request = ureq::get(&url);
 
@@ -142,21 +239,103 @@ impl PayloadClient {
 
         let response = request
             .call()
-            .map_err(|e| ServiceError::NetworkError(format!("Failed to list collections: {}", e)))?;
+            .map_err(|e| ServiceError::NetworkError(format!("Failed to fetch access: {}", e)))?;
 
         if response.status() < 200 || response.status() >= 300 {
             return Err(ServiceError::ApiError(format!(
-                "Failed to list collections: HTTP {}",
+                "Failed to fetch access: HTTP {}",
                 response.status()
             )));
         }
 
-        let _text = response.into_string().map_err(|e| {
+        response.into_json().map_err(|e| {
             ServiceError::NetworkError(format!("Failed to read response: {}", e))
-        })?;
+        })
+    }
 
-        // Parse collection list - mock implementation
-        Ok(vec!["users".to_string(), "posts".to_string(), "pages".to_string()])
+    /// Create a document, returning it as saved
+    pub fn create_document(&self, slug: &str, data: &serde_json::Value) -> ServiceResult<serde_json::Value> {
+        let url = format!("{}/api/{}", self.base_url, slug);
+        self.send_document(ureq::post(&url), data, &format!("create document in {slug}"))
+    }
+
+    /// Update a document by ID, returning it as saved
+    pub fn update_document(&self, slug: &str, id: &str, data: &serde_json::Value) -> ServiceResult<serde_json::Value> {
+        let url = format!("{}/api/{}/{}", self.base_url, slug, id);
+        self.send_document(ureq::patch(&url), data, &format!("update {slug} {id}"))
+    }
+
+    /// Update every document matching the `where[...]` query parameters in
+    /// one request, returning Payload's `{ docs, errors }`
+    pub fn update_where(&self, slug: &str, params: &[(String, String)], data: &serde_json::Value) -> ServiceResult<serde_json::Value> {
+        let url = format!("{}/api/{}", self.base_url, slug);
+        let mut request = ureq::patch(&url);
+
+        for (key, value) in params {
+            request = request.query(key, value);
+        }
+
+        self.send_document(request, data, &format!("bulk update {slug}"))
+    }
+
+    /// A global's data, passing REST query parameters through (e.g.
+    /// `depth=0`, `locale=de`)
+    pub fn find_global(&self, slug: &str, params: &[(String, String)]) -> ServiceResult<serde_json::Value> {
+        let url = format!("{}/api/globals/{}", self.base_url, slug);
+
+        let mut request = ureq::get(&url);
+
+        if let Some(api_key) = &self.api_key {
+            request = request.set("Authorization", &format!("Bearer {}", api_key));
+        }
+
+        for (key, value) in params {
+            request = request.query(key, value);
+        }
+
+        let response = request
+            .call()
+            .map_err(|e| ServiceError::NetworkError(format!("Failed to fetch global {}: {}", slug, e)))?;
+
+        if response.status() < 200 || response.status() >= 300 {
+            return Err(ServiceError::ApiError(format!(
+                "Failed to fetch global {}: HTTP {}",
+                slug,
+                response.status()
+            )));
+        }
+
+        response.into_json().map_err(|e| {
+            ServiceError::NetworkError(format!("Failed to read response: {}", e))
+        })
+    }
+
+    /// Update a global, returning the response
+    pub fn update_global(&self, slug: &str, data: &serde_json::Value) -> ServiceResult<serde_json::Value> {
+        let url = format!("{}/api/globals/{}", self.base_url, slug);
+        self.send_document(ureq::post(&url), data, &format!("update global {slug}"))
+    }
+
+    /// Send `data` as JSON and return the `doc` of the response. Payload's
+    /// validation messages are passed through on failure.
+    fn send_document(&self, mut request: ureq::Request, data: &serde_json::Value, action: &str) -> ServiceResult<serde_json::Value> {
+        if let Some(api_key) = &self.api_key {
+            request = request.set("Authorization", &format!("Bearer {}", api_key));
+        }
+
+        let response = match request.send_json(data) {
+            Ok(response) => response,
+            Err(ureq::Error::Status(status, response)) => {
+                let body = response.into_string().unwrap_or_default();
+                return Err(ServiceError::ApiError(format!("Failed to {}: HTTP {} {}", action, status, body)));
+            }
+            Err(e) => return Err(ServiceError::NetworkError(format!("Failed to {}: {}", action, e))),
+        };
+
+        let body: serde_json::Value = response.into_json().map_err(|e| {
+            ServiceError::NetworkError(format!("Failed to read response: {}", e))
+        })?;
+        Ok(body.get("doc").cloned().unwrap_or(body))
     }
 
     /// Validate a collection configuration against live schema
@@ -165,11 +344,9 @@ impl PayloadClient {
         let _live_collection = self.get_collection(slug)?;
 
         // Compare with provided config
-        let mut issues = Vec::new();
-
         // This would implement actual validation logic comparing
         // the provided config against the live schema
-        issues.push("Live validation not yet implemented".to_string());
+        let issues = vec!["Live validation not yet implemented".to_string()];
 
         Ok(issues)
     }
@@ -232,6 +409,18 @@ impl PayloadClient {
 
 /// Helper function to create a Payload client from connection string
 pub fn create_payload_client(connection_string: &str, api_key: Option<String>) -> ServiceResult<PayloadClient> {
+    // Resolve `${PAYLOAD_*}` and `${secret:NAME}` references so credentials
+    // need not appear in tool arguments.
+    let connection_string = crate::secrets::expand_argument(connection_string).map_err(ServiceError::FromString)?;
+    let api_key = api_key
+        .map(|key| crate::secrets::expand_argument(&key))
+        .transpose()
+        .map_err(ServiceError::FromString)?;
+    Ok(payload_client(&connection_string, api_key))
+}
+
+/// Create a client for an already resolved connection string.
+pub fn payload_client(connection_string: &str, api_key: Option<String>) -> PayloadClient {
     // Parse connection string like "http://localhost:3000" or "https://my-payload.com"
     let base_url = if connection_string.starts_with("http") {
         connection_string.to_string()
@@ -239,7 +428,7 @@ pub fn create_payload_client(connection_string: &str, api_key: Option<String>) -
         format!("http://{}", connection_string)
     };
 
-    Ok(PayloadClient::new(PayloadConfig { base_url, api_key }))
+    PayloadClient::new(PayloadConfig { base_url, api_key })
 }
 
 #[cfg(test)]
@@ -0,0 +1,549 @@
+//! A typed TypeScript client for the REST API Payload serves for a schema:
+//! document and input types per collection and global, and a `createClient`
+//! whose per-collection functions wrap `fetch`. Only `fetch` is needed at
+//! runtime; `where` queries are encoded in the bracket syntax Payload reads.
+
+use std::collections::BTreeMap;
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::payload_tools::{
+    json_schema::schema_name,
+    naming::{Case, singularize},
+    schema_model::{SchemaField, SchemaModel},
+};
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ClientSdkOptions {
+    /// Directory the files are generated in, defaults to `src/lib/payload`
+    pub out_dir: Option<String>,
+    /// Payload's `routes.api`, defaults to "/api"
+    pub api_route: Option<String>,
+    /// Documents have integer IDs, as on Postgres and SQLite; string IDs
+    /// (MongoDB) by default
+    pub numeric_ids: Option<bool>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+pub struct ClientSdkOutput {
+    /// Generated files by path
+    pub files: BTreeMap<String, String>,
+    /// Client functions generated, across collections and globals
+    pub functions: usize,
+    pub warnings: Vec<String>,
+}
+
+/// Interface name of a collection's documents, e.g. `Post` for `posts`.
+pub(crate) fn document_type(slug: &str) -> String {
+    schema_name(&singularize(slug))
+}
+
+/// Property of the client for a collection or global, e.g. `blogPosts`.
+pub(crate) fn client_key(slug: &str) -> String {
+    Case::Camel.convert(slug)
+}
+
+fn literal(value: &str) -> String {
+    format!("'{}'", value.replace('\\', "\\\\").replace('\'', "\\'"))
+}
+
+struct TypeWriter<'a> {
+    schema: &'a SchemaModel,
+    warnings: Vec<String>,
+}
+
+impl TypeWriter<'_> {
+    /// Type of a related document; `input` types take IDs only.
+    fn related(&mut self, field: &SchemaField, path: &str, input: bool) -> String {
+        let slugs = field.relation_to.as_deref().unwrap_or_default();
+        let documents: Vec<(String, String)> = slugs
+            .iter()
+            .map(|slug| match self.schema.collection(slug) {
+                Some(_) => (slug.clone(), document_type(slug)),
+                None => {
+                    self.warnings.push(format!(
+                        "{path}: collection '{slug}' is not in the schema; typed as unknown"
+                    ));
+                    (slug.clone(), "unknown".to_string())
+                }
+            })
+            .collect();
+        let value = |document: &str| {
+            if input || document == "unknown" {
+                "ID".to_string()
+            } else {
+                format!("ID | {document}")
+            }
+        };
+        match documents.as_slice() {
+            [] => "ID".to_string(),
+            [(_, document)] => value(document),
+            _ => documents
+                .iter()
+                .map(|(slug, document)| {
+                    format!(
+                        "{{ relationTo: {}; value: {} }}",
+                        literal(slug),
+                        value(document)
+                    )
+                })
+                .collect::<Vec<_>>()
+                .join(" | "),
+        }
+    }
+
+    /// TypeScript type of a field's value, indented for `depth`.
+    fn field_type(
+        &mut self,
+        field: &SchemaField,
+        path: &str,
+        depth: usize,
+        input: bool,
+    ) -> Option<String> {
+        let one = match field.field_type.as_str() {
+            "text" | "textarea" | "code" | "email" | "date" => "string".to_string(),
+            "number" => "number".to_string(),
+            "checkbox" => "boolean".to_string(),
+            "point" => "[number, number]".to_string(),
+            "richText" => "Record<string, unknown>".to_string(),
+            "json" => "unknown".to_string(),
+            "select" | "radio" => match &field.options {
+                Some(options) if !options.is_empty() => {
+                    let options: Vec<String> =
+                        options.iter().map(|option| literal(option)).collect();
+                    options.join(" | ")
+                }
+                _ => "string".to_string(),
+            },
+            "relationship" | "upload" => self.related(field, path, input),
+            "group" => self.object(
+                field.fields.as_deref().unwrap_or_default(),
+                path,
+                depth,
+                input,
+                &[],
+            ),
+            "array" => {
+                let row = self.object(
+                    field.fields.as_deref().unwrap_or_default(),
+                    path,
+                    depth,
+                    input,
+                    &["id?: string | null"],
+                );
+                return Some(format!("{row}[]"));
+            }
+            "blocks" => {
+                let mut rows = Vec::new();
+                for block in field.blocks.iter().flatten() {
+                    let block_type = format!("blockType: {}", literal(&block.slug));
+                    let extra = [
+                        block_type.as_str(),
+                        "id?: string | null",
+                        "blockName?: string | null",
+                    ];
+                    rows.push(self.object(
+                        &block.fields,
+                        &format!("{path}.{}", block.slug),
+                        depth,
+                        input,
+                        &extra,
+                    ));
+                }
+                if rows.is_empty() {
+                    return Some("unknown[]".to_string());
+                }
+                return Some(format!("({})[]", rows.join(" | ")));
+            }
+            _ => return None,
+        };
+        Some(if field.has_many {
+            if one.contains(' ') {
+                format!("({one})[]")
+            } else {
+                format!("{one}[]")
+            }
+        } else {
+            one
+        })
+    }
+
+    /// Properties of `fields`, with `extra` properties first.
+    fn properties(
+        &mut self,
+        fields: &[SchemaField],
+        path: &str,
+        depth: usize,
+        input: bool,
+        extra: &[&str],
+    ) -> String {
+        let indent = "  ".repeat(depth + 1);
+        let mut out: String = extra
+            .iter()
+            .map(|property| format!("{indent}{property}\n"))
+            .collect();
+        for field in fields {
+            let field_path = format!("{path}.{}", field.name);
+            let Some(value) = self.field_type(field, &field_path, depth + 1, input) else {
+                continue;
+            };
+            let name = if field
+                .name
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '$')
+            {
+                field.name.clone()
+            } else {
+                literal(&field.name)
+            };
+            if field.required {
+                out.push_str(&format!("{indent}{name}: {value}\n"));
+            } else {
+                out.push_str(&format!("{indent}{name}?: {value} | null\n"));
+            }
+        }
+        out
+    }
+
+    fn object(
+        &mut self,
+        fields: &[SchemaField],
+        path: &str,
+        depth: usize,
+        input: bool,
+        extra: &[&str],
+    ) -> String {
+        let properties = self.properties(fields, path, depth, input, extra);
+        format!("{{\n{properties}{}}}", "  ".repeat(depth))
+    }
+}
+
+/// `types.ts`: document and input interfaces for every collection and global.
+pub(crate) fn typescript_types(schema: &SchemaModel, numeric_ids: bool) -> (String, Vec<String>) {
+    let mut writer = TypeWriter {
+        schema,
+        warnings: Vec::new(),
+    };
+    let mut out = format!(
+        "// Generated from the Payload schema; regenerate rather than edit.\n\nexport type ID = {}\n",
+        if numeric_ids { "number" } else { "string" }
+    );
+    for collection in &schema.collections {
+        let name = document_type(&collection.slug);
+        let mut extra = vec!["id: ID"];
+        if collection.auth {
+            extra.push("email: string");
+        }
+        if collection.upload {
+            extra.extend([
+                "url?: string | null",
+                "filename?: string | null",
+                "mimeType?: string | null",
+                "filesize?: number | null",
+                "width?: number | null",
+                "height?: number | null",
+            ]);
+        }
+        let mut properties =
+            writer.properties(&collection.fields, &collection.slug, 0, false, &extra);
+        if collection.timestamps {
+            properties.push_str("  updatedAt: string\n  createdAt: string\n");
+        }
+        out.push_str(&format!("\nexport interface {name} {{\n{properties}}}\n"));
+        let input_extra: &[&str] = if collection.auth {
+            &["email: string", "password?: string"]
+        } else {
+            &[]
+        };
+        let input = writer.properties(&collection.fields, &collection.slug, 0, true, input_extra);
+        out.push_str(&format!("\nexport interface {name}Input {{\n{input}}}\n"));
+    }
+    for global in &schema.globals {
+        let name = schema_name(&global.slug);
+        let properties = writer.properties(&global.fields, &global.slug, 0, false, &[]);
+        out.push_str(&format!(
+            "\nexport interface {name} {{\n{properties}  updatedAt?: string | null\n  createdAt?: string | null\n}}\n"
+        ));
+        let input = writer.properties(&global.fields, &global.slug, 0, true, &[]);
+        out.push_str(&format!("\nexport interface {name}Input {{\n{input}}}\n"));
+    }
+    (out, writer.warnings)
+}
+
+const RUNTIME: &str = r#"export interface ClientConfig {
+  /** Server URL, e.g. http://localhost:3000 */
+  baseUrl: string
+  /** JWT from a login, sent as `Authorization: JWT <token>` */
+  token?: string
+  /** API key of an auth collection with `useAPIKey` */
+  apiKey?: { collection: string; key: string }
+  /** `include` sends the payload-token cookie, for browsers on the same site */
+  credentials?: RequestCredentials
+  fetch?: typeof fetch
+}
+
+export type WhereField = {
+  equals?: unknown
+  not_equals?: unknown
+  in?: unknown[]
+  not_in?: unknown[]
+  all?: unknown[]
+  exists?: boolean
+  greater_than?: unknown
+  greater_than_equal?: unknown
+  less_than?: unknown
+  less_than_equal?: unknown
+  like?: string
+  contains?: string
+  near?: string
+}
+
+export type Where = {
+  and?: Where[]
+  or?: Where[]
+  [field: string]: WhereField | Where[] | undefined
+}
+
+export interface ReadParams {
+  depth?: number
+  locale?: string
+  fallbackLocale?: string
+  draft?: boolean
+}
+
+export interface FindParams extends ReadParams {
+  where?: Where
+  limit?: number
+  page?: number
+  sort?: string
+}
+
+export interface PaginatedDocs<T> {
+  docs: T[]
+  totalDocs: number
+  limit: number
+  totalPages: number
+  page?: number
+  pagingCounter: number
+  hasPrevPage: boolean
+  hasNextPage: boolean
+  prevPage?: number | null
+  nextPage?: number | null
+}
+
+export class PayloadError extends Error {
+  constructor(
+    public status: number,
+    public errors: { message: string; field?: string }[],
+  ) {
+    super(errors.map((error) => error.message).join('; ') || `Request failed with status ${status}`)
+  }
+}
+
+/** Query string in the bracket syntax Payload reads, e.g. `where[title][equals]=Hello`. */
+export function toQueryString(params: Record<string, unknown>): string {
+  const parts: string[] = []
+  const add = (key: string, value: unknown) => {
+    if (value === undefined) return
+    if (value !== null && typeof value === 'object') {
+      for (const [child, item] of Object.entries(value)) add(`${key}[${child}]`, item)
+    } else {
+      parts.push(`${encodeURIComponent(key)}=${encodeURIComponent(String(value))}`)
+    }
+  }
+  for (const [key, value] of Object.entries(params)) add(key, value)
+  return parts.length ? `?${parts.join('&')}` : ''
+}
+"#;
+
+const REQUEST: &str = r#"  const request = async <T>(
+    method: string,
+    path: string,
+    options: { query?: Record<string, unknown>; body?: unknown } = {},
+  ): Promise<T> => {
+    const headers: Record<string, string> = {}
+    if (options.body !== undefined) headers['Content-Type'] = 'application/json'
+    if (config.token) {
+      headers.Authorization = `JWT ${config.token}`
+    } else if (config.apiKey) {
+      headers.Authorization = `${config.apiKey.collection} API-Key ${config.apiKey.key}`
+    }
+    const url = `${config.baseUrl}${API_ROUTE}${path}${toQueryString(options.query ?? {})}`
+    const response = await (config.fetch ?? fetch)(url, {
+      method,
+      headers,
+      credentials: config.credentials,
+      body: options.body === undefined ? undefined : JSON.stringify(options.body),
+    })
+    const data = await response.json().catch(() => ({}))
+    if (!response.ok) {
+      throw new PayloadError(response.status, data.errors ?? [{ message: response.statusText }])
+    }
+    return data as T
+  }
+"#;
+
+/// Functions of one collection, as a property of the client object.
+fn collection_functions(slug: &str, name: &str, auth: bool) -> (String, usize) {
+    let key = client_key(slug);
+    let path = format!("/{slug}");
+    let mut out = format!(
+        "    {key}: {{
+      find: (params: FindParams = {{}}) =>
+        request<PaginatedDocs<{name}>>('GET', '{path}', {{ query: {{ ...params }} }}),
+      findByID: (id: ID, params: ReadParams = {{}}) =>
+        request<{name}>('GET', `{path}/${{id}}`, {{ query: {{ ...params }} }}),
+      create: (data: {name}Input, params: ReadParams = {{}}) =>
+        request<{{ doc: {name} }}>('POST', '{path}', {{ body: data, query: {{ ...params }} }}).then((result) => result.doc),
+      update: (id: ID, data: Partial<{name}Input>, params: ReadParams = {{}}) =>
+        request<{{ doc: {name} }}>('PATCH', `{path}/${{id}}`, {{ body: data, query: {{ ...params }} }}).then((result) => result.doc),
+      delete: (id: ID) => request<{{ doc: {name} }}>('DELETE', `{path}/${{id}}`).then((result) => result.doc),
+"
+    );
+    let mut functions = 5;
+    if auth {
+        out.push_str(&format!(
+            "      login: async (credentials: {{ email: string; password: string }}) => {{
+        const result = await request<{{ token?: string; exp?: number; user: {name} }}>('POST', '{path}/login', {{ body: credentials }})
+        config.token = result.token
+        return result
+      }},
+      logout: async () => {{
+        const result = await request<{{ message: string }}>('POST', '{path}/logout')
+        config.token = undefined
+        return result
+      }},
+      me: () => request<{{ user: {name} | null; exp?: number; token?: string }}>('GET', '{path}/me'),
+      refreshToken: async () => {{
+        const result = await request<{{ refreshedToken: string; exp: number; user: {name} }}>('POST', '{path}/refresh-token')
+        config.token = result.refreshedToken
+        return result
+      }},
+      forgotPassword: (email: string) => request<{{ message: string }}>('POST', '{path}/forgot-password', {{ body: {{ email }} }}),
+      resetPassword: (token: string, password: string) =>
+        request<{{ token?: string; user: {name} }}>('POST', '{path}/reset-password', {{ body: {{ token, password }} }}),
+"
+        ));
+        functions += 6;
+    }
+    out.push_str("    },\n");
+    (out, functions)
+}
+
+/// A typed fetch client for `schema`: `types.ts`, `client.ts` and an
+/// `index.ts` re-exporting both.
+pub fn client_sdk(schema: &SchemaModel, options: &ClientSdkOptions) -> ClientSdkOutput {
+    let out_dir = options
+        .out_dir
+        .as_deref()
+        .unwrap_or("src/lib/payload")
+        .trim_end_matches('/');
+    let api_route = options.api_route.as_deref().unwrap_or("/api");
+    let (types, warnings) = typescript_types(schema, options.numeric_ids.unwrap_or(false));
+
+    let mut imported = vec!["ID".to_string()];
+    let mut collections = String::new();
+    let mut functions = 0;
+    for collection in &schema.collections {
+        let name = document_type(&collection.slug);
+        let (code, count) = collection_functions(&collection.slug, &name, collection.auth);
+        collections.push_str(&code);
+        functions += count;
+        imported.push(name.clone());
+        imported.push(format!("{name}Input"));
+    }
+    let mut globals = String::new();
+    for global in &schema.globals {
+        let name = schema_name(&global.slug);
+        let key = client_key(&global.slug);
+        let slug = &global.slug;
+        globals.push_str(&format!(
+            "    {key}: {{
+      get: (params: ReadParams = {{}}) => request<{name}>('GET', '/globals/{slug}', {{ query: {{ ...params }} }}),
+      update: (data: Partial<{name}Input>, params: ReadParams = {{}}) =>
+        request<{{ result: {name} }}>('POST', '/globals/{slug}', {{ body: data, query: {{ ...params }} }}).then((result) => result.result),
+    }},
+"
+        ));
+        functions += 2;
+        imported.push(name.clone());
+        imported.push(format!("{name}Input"));
+    }
+
+    let client = format!(
+        "// Generated from the Payload schema; regenerate rather than edit.\nimport type {{ {} }} from './types'\n\nconst API_ROUTE = {}\n\n{RUNTIME}\nexport function createClient(config: ClientConfig) {{\n{REQUEST}\n  return {{\n    /** Use a JWT from elsewhere, or clear it with `undefined` */\n    setToken: (token?: string) => {{\n      config.token = token\n    }},\n{collections}    globals: {{\n{globals}    }},\n  }}\n}}\n\nexport type PayloadClient = ReturnType<typeof createClient>\n",
+        imported.join(", "),
+        literal(api_route),
+    );
+    let mut files = BTreeMap::new();
+    files.insert(format!("{out_dir}/types.ts"), types);
+    files.insert(format!("{out_dir}/client.ts"), client);
+    files.insert(
+        format!("{out_dir}/index.ts"),
+        "export * from './client'\nexport type * from './types'\n".to_string(),
+    );
+    ClientSdkOutput {
+        files,
+        functions,
+        warnings,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::payload_tools::schema_model::{SchemaCollection, SchemaGlobal};
+
+    #[test]
+    fn generates_types_and_client_functions() {
+        let schema = SchemaModel {
+            collections: vec![
+                SchemaCollection {
+                    slug: "blog-posts".to_string(),
+                    timestamps: true,
+                    fields: vec![
+                        SchemaField {
+                            required: true,
+                            ..SchemaField::new("title", "text")
+                        },
+                        SchemaField {
+                            options: Some(vec!["draft".to_string(), "published".to_string()]),
+                            ..SchemaField::new("status", "select")
+                        },
+                        SchemaField {
+                            relation_to: Some(vec!["users".to_string()]),
+                            ..SchemaField::new("author", "relationship")
+                        },
+                    ],
+                    ..Default::default()
+                },
+                SchemaCollection {
+                    slug: "users".to_string(),
+                    auth: true,
+                    ..Default::default()
+                },
+            ],
+            globals: vec![SchemaGlobal {
+                slug: "site-settings".to_string(),
+                fields: vec![SchemaField::new("name", "text")],
+            }],
+        };
+        let output = client_sdk(&schema, &ClientSdkOptions::default());
+        assert_eq!(output.functions, 5 + 11 + 2);
+        assert!(output.warnings.is_empty());
+        let types = &output.files["src/lib/payload/types.ts"];
+        assert!(types.contains(
+            "export interface BlogPost {\n  id: ID\n  title: string\n  status?: 'draft' | 'published' | null\n  author?: ID | User | null\n  updatedAt: string\n  createdAt: string\n}"
+        ));
+        assert!(types.contains("export interface BlogPostInput {\n  title: string\n  status?: 'draft' | 'published' | null\n  author?: ID | null\n}"));
+        let client = &output.files["src/lib/payload/client.ts"];
+        assert!(client.contains("import type { ID, BlogPost, BlogPostInput, User, UserInput, SiteSettings, SiteSettingsInput } from './types'"));
+        assert!(client.contains("    blogPosts: {\n      find: (params: FindParams = {}) =>\n        request<PaginatedDocs<BlogPost>>('GET', '/blog-posts'"));
+        assert!(client.contains(
+            "request<{ token?: string; exp?: number; user: User }>('POST', '/users/login'"
+        ));
+        assert!(client.contains("    siteSettings: {\n      get: "));
+    }
+}
@@ -0,0 +1,412 @@
+//! A first collection definition from a plain-English description, by
+//! keyword rules rather than a language model: the same text always drafts
+//! the same collection. The subject before `with`, `has` or a colon names the
+//! collection; every comma-, `and`- or line-separated phrase after it
+//! becomes a field, its type guessed from words like "email", "date",
+//! "image" or "relates to". Every guess the rules make is listed so the
+//! draft can be reviewed before it is generated.
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value, json};
+
+use crate::payload_tools::naming::{Case, is_plural, pluralize};
+
+/// Words that introduce the field list after the collection's subject.
+const FIELD_LIST_MARKERS: [&str; 10] = [
+    "with", "has", "have", "having", "containing", "contains", "includes", "including", "stores", "needs",
+];
+/// Words dropped from the subject before it becomes the slug.
+const SUBJECT_FILLER: [&str; 22] = [
+    "i", "we", "need", "want", "create", "make", "add", "build", "a", "an", "the", "new", "collection", "collections",
+    "of", "for", "model", "type", "each", "every", "some", "content",
+];
+/// Words dropped from a phrase before it becomes the field name.
+const NAME_FILLER: [&str; 20] = [
+    "a", "an", "the", "its", "their", "his", "her", "optional", "required", "mandatory", "unique", "translated",
+    "localized", "multilingual", "field", "many", "multiple", "several", "list", "some",
+];
+/// Words that end the name part of a phrase.
+const NAME_END: [&str; 14] = [
+    "that", "which", "who", "to", "in", "for", "from", "as", "one", "either", "must", "should", "is", "are",
+];
+const RELATION_VERBS: [&str; 14] = [
+    "relate", "relates", "related", "relation", "relationship", "belong", "belongs", "link", "linked", "links",
+    "reference", "references", "refer", "refers",
+];
+const USER_ROLES: [&str; 6] = ["author", "owner", "creator", "editor", "reviewer", "assignee"];
+
+/// Field types by the words that suggest them, checked in order.
+const TYPE_WORDS: [(&str, &[&str]); 9] = [
+    ("upload", &["image", "photo", "picture", "avatar", "logo", "thumbnail", "cover", "file", "attachment", "video", "pdf"]),
+    ("email", &["email", "e-mail"]),
+    ("date", &["date", "birthday", "deadline", "time", "timestamp", "day", "when"]),
+    ("checkbox", &["whether", "flag", "toggle", "boolean", "yes/no", "featured", "active", "enabled"]),
+    ("number", &[
+        "price", "amount", "cost", "count", "number", "quantity", "age", "rating", "score", "total", "year", "weight",
+        "height", "width", "duration", "order", "position",
+    ]),
+    ("richText", &["body", "content", "article", "rich"]),
+    ("textarea", &["description", "summary", "bio", "biography", "excerpt", "notes", "paragraph", "long", "abstract"]),
+    ("point", &["location", "coordinates", "geolocation", "latitude", "longitude"]),
+    ("json", &["json", "metadata", "settings", "payload"]),
+];
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct CollectionDraft {
+    /// Options for `generate_collection`, to review and adjust
+    pub collection: Value,
+    /// Each guess the rules made, e.g. a field type inferred from a word
+    pub assumptions: Vec<String>,
+}
+
+/// Draft a collection from `description`; `slug` overrides the one taken
+/// from its subject.
+pub fn draft_collection(description: &str, slug: Option<&str>) -> Result<CollectionDraft, String> {
+    let text = description.trim().to_lowercase();
+    if text.is_empty() {
+        return Err("description is empty".to_string());
+    }
+    let mut assumptions = Vec::new();
+    let (subject, list) = split_subject(&text);
+
+    let slug = match slug {
+        Some(slug) => slug.to_string(),
+        None => {
+            let words: Vec<&str> = subject
+                .split_whitespace()
+                .map(|word| word.trim_matches(|c: char| !c.is_alphanumeric()))
+                .filter(|word| !word.is_empty() && !SUBJECT_FILLER.contains(word))
+                .collect();
+            // "called posts" or "named posts" names it outright.
+            let named = words
+                .iter()
+                .position(|word| matches!(*word, "called" | "named"))
+                .map_or(&words[..], |at| &words[at + 1..]);
+            if named.is_empty() {
+                return Err("No collection name found; start with what is stored, e.g. \"Blog posts with ...\"".to_string());
+            }
+            let name = Case::Kebab.convert(&named.join(" "));
+            let slug = pluralize(&name);
+            if slug != name {
+                assumptions.push(format!("Slug '{slug}' is the plural of '{name}'"));
+            }
+            slug
+        }
+    };
+
+    let mut fields: Vec<Map<String, Value>> = Vec::new();
+    for phrase in split_phrases(list) {
+        let phrase = phrase.trim();
+        if phrase.is_empty() {
+            continue;
+        }
+        // "one of draft, review and published" continues over the separators.
+        if let Some(previous) = fields.last_mut().filter(|field| field.contains_key("options")) {
+            if phrase.split_whitespace().count() == 1 && !phrase.contains(':') {
+                if let Some(Value::Array(options)) = previous.get_mut("options") {
+                    options.push(json!(phrase));
+                }
+                continue;
+            }
+        }
+        match draft_field(phrase, &mut assumptions) {
+            Some(field) => {
+                let name = field["name"].as_str().unwrap_or_default();
+                if fields.iter().any(|other| other["name"] == name) {
+                    assumptions.push(format!("Skipped '{phrase}': field '{name}' is already drafted"));
+                } else {
+                    fields.push(field);
+                }
+            }
+            None => assumptions.push(format!("Skipped '{phrase}': no field name found")),
+        }
+    }
+    if fields.is_empty() {
+        return Err("No fields found; list them after \"with\", e.g. \"... with a title, a cover image and tags\"".to_string());
+    }
+
+    let mut collection = Map::new();
+    collection.insert("slug".into(), json!(slug));
+    let auth = ["log in", "login", "sign in", "password", "account"]
+        .iter()
+        .any(|word| text.contains(word));
+    if auth {
+        collection.insert("auth".into(), json!(true));
+        assumptions.push("Auth enabled: the description mentions logging in or accounts".to_string());
+        fields.retain(|field| !matches!(field["name"].as_str(), Some("email" | "password")));
+    }
+    if text.contains("draft") {
+        collection.insert("versions".into(), json!(true));
+        assumptions.push("Versions with drafts enabled: the description mentions drafts".to_string());
+    }
+    collection.insert("timestamps".into(), json!(true));
+    let title = fields
+        .iter()
+        .find(|field| matches!(field["name"].as_str(), Some("title" | "name")))
+        .or_else(|| fields.iter().find(|field| field["type"] == "text"))
+        .and_then(|field| field["name"].as_str());
+    if let Some(title) = title {
+        collection.insert("admin".into(), json!({ "useAsTitle": title }));
+    }
+    collection.insert(
+        "fields".into(),
+        Value::Array(fields.into_iter().map(Value::Object).collect()),
+    );
+
+    Ok(CollectionDraft {
+        collection: Value::Object(collection),
+        assumptions,
+    })
+}
+
+/// The subject and the field list: split at a colon or the first list
+/// marker, else at the first sentence end.
+fn split_subject(text: &str) -> (&str, &str) {
+    let first_line = text.find('\n').unwrap_or(text.len());
+    if let Some(colon) = text[..first_line].find(':') {
+        return (&text[..colon], &text[colon + 1..]);
+    }
+    let mut offset = 0;
+    for word in text.split_inclusive(char::is_whitespace) {
+        let bare = word.trim().trim_matches(|c: char| !c.is_alphanumeric());
+        if offset > 0 && FIELD_LIST_MARKERS.contains(&bare) {
+            return (&text[..offset], &text[offset + word.len()..]);
+        }
+        offset += word.len();
+    }
+    match text.find(['.', '\n']) {
+        Some(end) => (&text[..end], &text[end + 1..]),
+        None => (text, ""),
+    }
+}
+
+/// Phrases separated by commas, semicolons, sentence ends, bullets, new
+/// lines and a standalone "and", keeping parentheses whole.
+fn split_phrases(list: &str) -> Vec<String> {
+    let mut phrases = Vec::new();
+    let mut phrase = String::new();
+    let mut depth = 0usize;
+    for c in list.chars() {
+        match c {
+            '(' => depth += 1,
+            ')' => depth = depth.saturating_sub(1),
+            ',' | ';' | '.' | '\n' | '*' | '•' if depth == 0 => {
+                phrases.push(std::mem::take(&mut phrase));
+                continue;
+            }
+            _ => {}
+        }
+        phrase.push(c);
+    }
+    phrases.push(phrase);
+    phrases
+        .iter()
+        .flat_map(|phrase| phrase.split(" and "))
+        .map(|phrase| phrase.trim().trim_start_matches(['-', ' ']).trim_start_matches("and ").to_string())
+        .collect()
+}
+
+/// The field a phrase describes, or `None` when no name is left after the
+/// filler and type words are dropped.
+fn draft_field(phrase: &str, assumptions: &mut Vec<String>) -> Option<Map<String, Value>> {
+    let (phrase, parenthetical) = match (phrase.find('('), phrase.rfind(')')) {
+        (Some(open), Some(close)) if open < close => (
+            format!("{} {}", &phrase[..open], &phrase[close + 1..]),
+            phrase[open + 1..close].to_string(),
+        ),
+        _ => (phrase.to_string(), String::new()),
+    };
+    let split_words = |text: &str| -> Vec<String> {
+        text.split_whitespace()
+            .map(|word| word.trim_matches(|c: char| !c.is_alphanumeric() && c != '?' && c != '/'))
+            .filter(|word| !word.is_empty())
+            .map(str::to_string)
+            .collect()
+    };
+    let words = split_words(&phrase);
+    let words: Vec<&str> = words.iter().map(String::as_str).collect();
+
+    // Choices: "(draft/published)", "one of a, b or c", "either a or b". A
+    // parenthetical that lists no choices is a hint, e.g. "(many images)".
+    let mut options: Option<Vec<String>> = {
+        let items: Vec<String> = parenthetical
+            .split([',', '/', '|'])
+            .flat_map(|item| item.split(" or "))
+            .map(|item| item.trim().to_string())
+            .filter(|item| !item.is_empty())
+            .collect();
+        (items.len() > 1).then_some(items)
+    };
+    if options.is_none() {
+        if let Some(at) = words.iter().position(|word| matches!(*word, "one" | "either")) {
+            let rest = words[at + 1..].iter().skip_while(|word| **word == "of");
+            let items: Vec<String> = rest
+                .filter(|word| !matches!(**word, "or" | "and"))
+                .map(|word| word.to_string())
+                .collect();
+            if !items.is_empty() {
+                options = Some(items);
+            }
+        }
+    }
+    let hints = if options.is_none() { split_words(&parenthetical) } else { Vec::new() };
+    let cues: Vec<&str> = words.iter().copied().chain(hints.iter().map(String::as_str)).collect();
+    let has = |candidates: &[&str]| cues.iter().any(|word| candidates.contains(word));
+
+    // "is featured" or "has stock" names a flag.
+    let flag = matches!(words.first(), Some(&("is" | "has")));
+    let mut name_words: Vec<&str> = Vec::new();
+    for word in &words[usize::from(flag)..] {
+        if NAME_END.contains(word) || RELATION_VERBS.contains(word) {
+            break;
+        }
+        if !NAME_FILLER.contains(word) {
+            name_words.push(word.trim_end_matches('?'));
+        }
+    }
+    let many = has(&["many", "multiple", "several", "list"]);
+    let mut field = Map::new();
+
+    // Relationships: "relates to users", "belongs to a category", "author".
+    let target = words
+        .iter()
+        .position(|word| RELATION_VERBS.contains(word))
+        .and_then(|at| {
+            words[at + 1..]
+                .iter()
+                .find(|word| !matches!(**word, "to" | "with" | "a" | "an" | "the" | "one" | "many" | "multiple"))
+        })
+        .map(|target| Case::Kebab.convert(&pluralize(target)));
+
+    let field_type = if let Some(target) = target {
+        if name_words.is_empty() {
+            name_words.push(words.iter().rev().find(|word| !RELATION_VERBS.contains(word))?);
+        }
+        field.insert("relationTo".into(), json!(target));
+        let has_many = many || name_words.last().is_some_and(|word| is_plural(word));
+        if has_many {
+            field.insert("hasMany".into(), json!(true));
+        }
+        "relationship".to_string()
+    } else if name_words.iter().any(|word| USER_ROLES.contains(word)) {
+        field.insert("relationTo".into(), json!("users"));
+        assumptions.push(format!("'{phrase}' relates to users"));
+        "relationship".to_string()
+    } else if let Some(options) = options.take() {
+        if many {
+            field.insert("hasMany".into(), json!(true));
+        }
+        field.insert("options".into(), json!(options));
+        "select".to_string()
+    } else if flag || phrase.contains('?') {
+        "checkbox".to_string()
+    } else {
+        let inferred = TYPE_WORDS
+            .iter()
+            .find_map(|(field_type, words)| {
+                // "photos" suggests what "photo" does.
+                let cue = cues.iter().find(|cue| {
+                    words.contains(cue) || cue.strip_suffix('s').is_some_and(|singular| words.contains(&singular))
+                })?;
+                Some((*field_type, *cue))
+            });
+        match inferred {
+            Some((field_type, cue)) => {
+                assumptions.push(format!("'{}' is {field_type} because of '{cue}'", phrase.trim()));
+                if field_type == "upload" {
+                    field.insert("relationTo".into(), json!("media"));
+                    if many || name_words.last().is_some_and(|word| is_plural(word)) {
+                        field.insert("hasMany".into(), json!(true));
+                    }
+                }
+                field_type.to_string()
+            }
+            None => "text".to_string(),
+        }
+    };
+
+    if name_words.is_empty() {
+        return None;
+    }
+    let name = Case::Camel.convert(&name_words.join(" "));
+    field.insert("name".into(), json!(name));
+    field.insert("type".into(), json!(field_type));
+    if has(&["required", "must", "mandatory"]) {
+        field.insert("required".into(), json!(true));
+    }
+    if has(&["unique"]) || (name == "slug" && field_type == "text") {
+        field.insert("unique".into(), json!(true));
+    }
+    if has(&["translated", "localized", "multilingual"]) {
+        field.insert("localized".into(), json!(true));
+    }
+    Some(field)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn drafts_fields_from_a_description() {
+        let draft = draft_collection(
+            "A blog post with a required title, a unique slug, a cover image, a publish date, \
+             an author, a status (draft, published), tags that relate to many tags and a translated body",
+            None,
+        )
+        .unwrap();
+        let collection = &draft.collection;
+        assert_eq!(collection["slug"], "blog-posts");
+        assert_eq!(collection["admin"]["useAsTitle"], "title");
+        assert_eq!(collection["versions"], true);
+        let fields: Vec<(&str, &str)> = collection["fields"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|field| (field["name"].as_str().unwrap(), field["type"].as_str().unwrap()))
+            .collect();
+        assert_eq!(
+            fields,
+            vec![
+                ("title", "text"),
+                ("slug", "text"),
+                ("coverImage", "upload"),
+                ("publishDate", "date"),
+                ("author", "relationship"),
+                ("status", "select"),
+                ("tags", "relationship"),
+                ("body", "richText"),
+            ]
+        );
+        let field = |index: usize| &collection["fields"][index];
+        assert_eq!(field(0)["required"], true);
+        assert_eq!(field(1)["unique"], true);
+        assert_eq!(field(4)["relationTo"], "users");
+        assert_eq!(field(5)["options"], json!(["draft", "published"]));
+        assert_eq!(field(6)["relationTo"], "tags");
+        assert_eq!(field(6)["hasMany"], true);
+        assert_eq!(field(7)["localized"], true);
+        assert!(draft.assumptions.contains(&"'a cover image' is upload because of 'cover'".to_string()));
+    }
+
+    #[test]
+    fn reads_named_collections_and_auth() {
+        let draft = draft_collection(
+            "Create a collection called team members: name, email, role (one of admin, editor or viewer), \
+             and a password to log in",
+            None,
+        )
+        .unwrap();
+        assert_eq!(draft.collection["slug"], "team-members");
+        assert_eq!(draft.collection["auth"], true);
+        let names: Vec<&str> = draft.collection["fields"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|field| field["name"].as_str().unwrap())
+            .collect();
+        assert_eq!(names, vec!["name", "role"]);
+        assert!(draft_collection("with", None).is_err());
+    }
+}
@@ -0,0 +1,140 @@
+//! Argument completion for `completion/complete`.
+//!
+//! Completions are keyed on the argument name, so the same suggestions apply
+//! to tool-style argument names used by prompts and resource templates.
+
+use std::collections::HashMap;
+
+use crate::connections::{client_for, names as connection_names};
+use crate::payload_tools::examples::EXAMPLES;
+use crate::payload_tools::generator::TemplateType;
+use crate::payload_tools::guides::GUIDES;
+use crate::payload_tools::hook_analyzer::HOOK_NAMES;
+use crate::payload_tools::naming::profile_names;
+use crate::payload_tools::plugins::plugin_names;
+use crate::payload_tools::query::get_categories;
+use crate::payload_tools::schemas::{COMMON_OPTIONS, field_type, field_type_names};
+use crate::payload_tools::types::FileType;
+use crate::payload_tools::validator::validation_rules;
+
+/// The protocol caps a completion response at 100 values.
+pub const MAX_COMPLETIONS: usize = 100;
+
+/// Candidate values for argument `name`, before filtering by the typed prefix.
+/// `context` holds arguments the client has already resolved (e.g. the
+/// `connection` of a `payload://` resource template).
+fn candidates(name: &str, context: &HashMap<String, String>) -> Vec<String> {
+    match name {
+        "template_type" | "templateType" => TemplateType::ALL
+            .iter()
+            .map(|t| t.as_str().to_string())
+            .collect(),
+        "field_type" | "fieldType" | "type" => field_type_names().map(str::to_string).collect(),
+        "option" => field_options(context),
+        "plugin" | "plugins" => plugin_names().map(str::to_string).collect(),
+        "file_type" | "fileType" => FileType::ALL.iter().map(|t| t.as_str().to_string()).collect(),
+        "rule" | "rule_id" | "ruleId" | "id" => {
+            validation_rules().into_iter().map(|rule| rule.id).collect()
+        }
+        "category" => get_categories(),
+        "database" | "db" => vec!["mongodb".to_string(), "postgres".to_string()],
+        "slug" | "collection" | "collections" => live_collections(context),
+        "connection" => connection_names(),
+        "topic" => GUIDES.iter().map(|guide| guide.topic.to_string()).collect(),
+        "tag" | "tags" => {
+            let mut tags: Vec<String> = EXAMPLES
+                .iter()
+                .flat_map(|example| example.tags.iter().map(|tag| tag.to_string()))
+                .collect();
+            tags.sort();
+            tags.dedup();
+            tags
+        }
+        "naming_profile" | "namingProfile" => profile_names(),
+        "hook" => HOOK_NAMES.iter().map(|hook| hook.to_string()).collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// Options of the field type already chosen as `field_type` (or `type`),
+/// otherwise the options every data field accepts.
+fn field_options(context: &HashMap<String, String>) -> Vec<String> {
+    let info = ["field_type", "fieldType", "type"]
+        .iter()
+        .find_map(|key| context.get(*key))
+        .and_then(|name| field_type(name));
+    match info {
+        Some(info) => info.options.iter().map(|o| o.to_string()).collect(),
+        None => COMMON_OPTIONS.iter().map(|o| o.to_string()).collect(),
+    }
+}
+
+/// Collection slugs from the live instance named by the `connection` (a
+/// stored connection or host) or `connection_string` context argument,
+/// falling back to `PAYLOAD_URL`.
+fn live_collections(context: &HashMap<String, String>) -> Vec<String> {
+    let connection = context
+        .get("connection")
+        .or_else(|| context.get("connection_string"))
+        .cloned()
+        .or_else(|| std::env::var("PAYLOAD_URL").ok());
+    let Some(connection) = connection else {
+        return Vec::new();
+    };
+    let api_key = context
+        .get("api_key")
+        .cloned()
+        .or_else(|| std::env::var("PAYLOAD_API_KEY").ok());
+    client_for(&connection, api_key)
+        .and_then(|client| client.list_collections().map_err(|err| err.to_string()))
+        .unwrap_or_default()
+}
+
+/// Complete `value` for argument `name`. Prefix matches come first, then
+/// other substring matches, both case-insensitive. Returns the values (capped
+/// at [`MAX_COMPLETIONS`]) and the total number of matches.
+pub fn complete_argument(
+    name: &str,
+    value: &str,
+    context: &HashMap<String, String>,
+) -> (Vec<String>, usize) {
+    // Comma-separated list arguments complete their last item.
+    let (head, partial) = match value.rsplit_once(',') {
+        Some((head, partial)) if name == "collections" => (format!("{head},"), partial.trim_start()),
+        _ => (String::new(), value),
+    };
+    let needle = partial.to_lowercase();
+
+    let (mut prefixed, contained): (Vec<String>, Vec<String>) = candidates(name, context)
+        .into_iter()
+        .filter(|candidate| candidate.to_lowercase().contains(&needle))
+        .partition(|candidate| candidate.to_lowercase().starts_with(&needle));
+    prefixed.extend(contained);
+
+    let total = prefixed.len();
+    let values = prefixed
+        .into_iter()
+        .take(MAX_COMPLETIONS)
+        .map(|candidate| format!("{head}{candidate}"))
+        .collect();
+    (values, total)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_complete_argument() {
+        let context = HashMap::new();
+        let (values, total) = complete_argument("template_type", "co", &context);
+        assert_eq!(values, vec!["collection", "config", "access-control"]);
+        assert_eq!(total, 3);
+
+        let (values, _) = complete_argument("field_type", "RICH", &context);
+        assert_eq!(values, vec!["richText"]);
+
+        let (values, _) = complete_argument("unknown", "", &context);
+        assert!(values.is_empty());
+    }
+}
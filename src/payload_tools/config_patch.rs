@@ -0,0 +1,684 @@
+//! Structured edits to an existing `payload.config.ts`: registering
+//! collections and globals, adding plugins and locales, and switching the
+//! database adapter. Each edit splices text into the source at the spans the
+//! TypeScript parser reports, so everything it doesn't touch, comments and
+//! formatting included, stays as written. Inserted lines follow the
+//! indentation, quote style and semicolons already in the file.
+
+use std::{collections::BTreeSet, ops::Range, sync::OnceLock};
+
+use regex::Regex;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::payload_tools::ts_parser::{
+    LiteralItem, build_config_start, literal_items, parse_object_at,
+};
+
+/// Database adapters: name, factory, package, and the factory's options
+/// with `{env}` for the connection string variable.
+const ADAPTERS: &[(&str, &str, &str, &str)] = &[
+    (
+        "mongodb",
+        "mongooseAdapter",
+        "@payloadcms/db-mongodb",
+        "{\n  url: process.env.{env} || '',\n}",
+    ),
+    (
+        "postgres",
+        "postgresAdapter",
+        "@payloadcms/db-postgres",
+        "{\n  pool: {\n    connectionString: process.env.{env} || '',\n  },\n}",
+    ),
+    (
+        "sqlite",
+        "sqliteAdapter",
+        "@payloadcms/db-sqlite",
+        "{\n  client: {\n    url: process.env.{env} || '',\n  },\n}",
+    ),
+    (
+        "vercel-postgres",
+        "vercelPostgresAdapter",
+        "@payloadcms/db-vercel-postgres",
+        "{\n  pool: {\n    connectionString: process.env.{env} || '',\n  },\n}",
+    ),
+];
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(tag = "op", rename_all = "snake_case")]
+pub enum ConfigEdit {
+    /// Import a collection config and list it in `collections`
+    AddCollection {
+        /// Exported identifier, e.g. `Posts`
+        name: String,
+        /// Module to import it from, e.g. `./collections/Posts`
+        path: String,
+        /// Import it as the module's default export
+        #[serde(default)]
+        default_export: bool,
+    },
+    /// Import a global config and list it in `globals`
+    AddGlobal {
+        name: String,
+        path: String,
+        #[serde(default)]
+        default_export: bool,
+    },
+    /// Import a plugin and call it in `plugins`; an existing call of the same
+    /// plugin gets the new options
+    AddPlugin {
+        /// Plugin factory, e.g. `seoPlugin`
+        name: String,
+        /// Package exporting it, e.g. `@payloadcms/plugin-seo`
+        package: String,
+        /// Options object passed to the plugin, as JSON
+        options: Option<Value>,
+    },
+    /// Replace the `db` adapter and its import
+    SetDatabase {
+        /// mongodb, postgres, sqlite or vercel-postgres
+        adapter: String,
+        /// Environment variable holding the connection string; defaults to
+        /// the one the current adapter reads, else `DATABASE_URI`
+        env: Option<String>,
+    },
+    /// Add a locale to `localization`, creating it when missing
+    AddLocale {
+        code: String,
+        label: Option<String>,
+        /// Make it the `defaultLocale`
+        #[serde(default)]
+        default: bool,
+    },
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+pub struct PatchConfigOutput {
+    /// The patched source
+    pub code: String,
+    /// What each applied edit changed
+    pub applied: Vec<String>,
+    /// Edits that were not applied, with the reason
+    pub skipped: Vec<String>,
+    /// Packages the new imports come from
+    pub dependencies: Vec<String>,
+}
+
+fn import_pattern() -> &'static Regex {
+    static PATTERN: OnceLock<Regex> = OnceLock::new();
+    PATTERN.get_or_init(|| {
+        Regex::new(r#"(?m)^import\s+(?:type\s+)?([^;'"]*?)\s*from\s*(['"])([^'"]+)['"](;?)"#)
+            .expect("valid import pattern")
+    })
+}
+
+/// Leading whitespace of the line containing byte `pos`.
+fn line_indent(code: &str, pos: usize) -> &str {
+    let line_start = code[..pos].rfind('\n').map_or(0, |newline| newline + 1);
+    let line = &code[line_start..];
+    &line[..line.len() - line.trim_start().len()]
+}
+
+/// `text` with every line after the first indented by `indent`.
+fn indented(text: &str, indent: &str) -> String {
+    text.replace('\n', &format!("\n{indent}"))
+}
+
+fn quoted(text: &str) -> String {
+    format!("'{}'", text.replace('\\', "\\\\").replace('\'', "\\'"))
+}
+
+fn ts_key(key: &str) -> String {
+    let mut chars = key.chars();
+    let identifier = chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_' || c == '$')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '$');
+    if identifier {
+        key.to_string()
+    } else {
+        quoted(key)
+    }
+}
+
+/// `value` as a TypeScript literal; objects and arrays of objects span
+/// several lines, indented relative to the first.
+fn ts_value(value: &Value) -> String {
+    match value {
+        Value::String(text) => quoted(text),
+        Value::Array(items) if items.iter().all(|item| !item.is_object()) => {
+            let items: Vec<String> = items.iter().map(ts_value).collect();
+            format!("[{}]", items.join(", "))
+        }
+        Value::Array(items) => {
+            let items: String = items
+                .iter()
+                .map(|item| format!("  {},\n", indented(&ts_value(item), "  ")))
+                .collect();
+            format!("[\n{items}]")
+        }
+        Value::Object(map) if map.is_empty() => "{}".to_string(),
+        Value::Object(map) => {
+            let entries: String = map
+                .iter()
+                .map(|(key, item)| {
+                    format!("  {}: {},\n", ts_key(key), indented(&ts_value(item), "  "))
+                })
+                .collect();
+            format!("{{\n{entries}}}")
+        }
+        other => other.to_string(),
+    }
+}
+
+/// The string a locale entry stands for: `'en'` or `{ code: 'en', ... }`.
+fn locale_code(code: &str, item: &LiteralItem) -> Option<String> {
+    let source = &code[item.start..item.end];
+    if source.starts_with('{') {
+        let locale = parse_object_at(code, item.start).ok()?;
+        return locale.get("code")?.as_str().map(str::to_string);
+    }
+    source
+        .strip_prefix(['\'', '"'])
+        .and_then(|rest| rest.strip_suffix(['\'', '"']))
+        .map(str::to_string)
+}
+
+struct Patcher {
+    code: String,
+    dependencies: BTreeSet<String>,
+}
+
+impl Patcher {
+    fn config(&self) -> Result<(usize, Vec<LiteralItem>), String> {
+        let start = build_config_start(&self.code).ok_or("No buildConfig({ ... }) call found")?;
+        let (items, _) = literal_items(&self.code, start)?;
+        Ok((start, items))
+    }
+
+    /// The config property `key`, if present.
+    fn property(&self, key: &str) -> Result<Option<LiteralItem>, String> {
+        let (_, items) = self.config()?;
+        Ok(items
+            .into_iter()
+            .find(|item| item.key.as_deref() == Some(key)))
+    }
+
+    /// Item `key` of the object literal at `open`.
+    fn item(&self, open: usize, key: &str) -> Result<Option<LiteralItem>, String> {
+        let (items, _) = literal_items(&self.code, open)?;
+        Ok(items
+            .into_iter()
+            .find(|item| item.key.as_deref() == Some(key)))
+    }
+
+    /// Add `text` as the last item of the object or array literal at `open`,
+    /// on its own line when the literal spans lines.
+    fn append(&mut self, open: usize, text: &str) -> Result<(), String> {
+        let (items, close) = literal_items(&self.code, open)?;
+        let code = &self.code;
+        let (at, insert): (Range<usize>, String) = match items.last() {
+            None if code.as_bytes()[open] == b'[' => (open + 1..close, text.to_string()),
+            None => {
+                let indent = line_indent(code, open);
+                let text = indented(text, &format!("{indent}  "));
+                (open + 1..close, format!("\n{indent}  {text},\n{indent}"))
+            }
+            Some(last) if !code[open + 1..items[0].start].contains('\n') => {
+                (last.end..last.end, format!(", {text}"))
+            }
+            Some(last) => {
+                let indent = line_indent(code, last.start);
+                let text = indented(text, indent);
+                let rest = &code[last.end..close];
+                match rest.trim_start().starts_with(',') {
+                    true => {
+                        let comma = last.end + rest.find(',').unwrap_or_default() + 1;
+                        // A comment ending the line stays with its item.
+                        let line_end = code[comma..]
+                            .find('\n')
+                            .map_or(close, |newline| comma + newline)
+                            .min(close);
+                        let tail = code[comma..line_end].trim();
+                        let at = if tail.is_empty() || tail.starts_with("//") {
+                            line_end
+                        } else {
+                            comma
+                        };
+                        (at..at, format!("\n{indent}{text},"))
+                    }
+                    false => (last.end..last.end, format!(",\n{indent}{text}")),
+                }
+            }
+        };
+        self.code.replace_range(at, &insert);
+        Ok(())
+    }
+
+    /// Replace the value of `item` with `text`, indented like the item.
+    fn replace_value(&mut self, item: &LiteralItem, text: &str) {
+        let text = indented(text, line_indent(&self.code, item.start));
+        self.code.replace_range(item.value_start..item.end, &text);
+    }
+
+    /// Set config property `key` to `value`, adding it when missing.
+    fn set_property(&mut self, key: &str, value: &str) -> Result<(), String> {
+        match self.property(key)? {
+            Some(item) => self.replace_value(&item, value),
+            None => {
+                let (start, _) = self.config()?;
+                self.append(start, &format!("{key}: {value}"))?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Import `name` from `module` unless something already binds it.
+    fn add_import(&mut self, name: &str, module: &str, default_export: bool) {
+        let word =
+            Regex::new(&format!(r"\b{}\b", regex::escape(name))).expect("valid identifier pattern");
+        let mut last = None;
+        let mut quote = "'".to_string();
+        let mut semicolon = String::new();
+        let mut same_module = None;
+        for import in import_pattern().captures_iter(&self.code) {
+            let clause = import.get(1).expect("clause group");
+            if word.is_match(clause.as_str()) {
+                return;
+            }
+            quote = import[2].to_string();
+            semicolon = import[4].to_string();
+            last = Some(import.get(0).expect("whole match").end());
+            if &import[3] == module && !default_export && clause.as_str().contains('{') {
+                same_module = Some(clause.range());
+            }
+        }
+        if let Some(clause) = same_module {
+            let close = clause.start + self.code[clause.clone()].rfind('}').unwrap_or_default();
+            let inner = self.code[clause.start..close].trim_end();
+            let at = clause.start + inner.len();
+            let insert = match (inner.ends_with(','), inner.ends_with('{')) {
+                (_, true) => format!(" {name}"),
+                (true, _) => format!(" {name},"),
+                _ => format!(", {name}"),
+            };
+            self.code.insert_str(at, &insert);
+            return;
+        }
+        let binding = if default_export {
+            name.to_string()
+        } else {
+            format!("{{ {name} }}")
+        };
+        let statement = format!("import {binding} from {quote}{module}{quote}{semicolon}");
+        match last {
+            Some(end) => self.code.insert_str(end, &format!("\n{statement}")),
+            None => self.code.insert_str(0, &format!("{statement}\n")),
+        }
+    }
+
+    /// Drop `name` from its import of `module` once nothing else uses it.
+    fn remove_unused_import(&mut self, name: &str, module: &str) {
+        let word =
+            Regex::new(&format!(r"\b{}\b", regex::escape(name))).expect("valid identifier pattern");
+        if word.find_iter(&self.code).count() > 1 {
+            return;
+        }
+        let Some(import) = import_pattern()
+            .captures_iter(&self.code)
+            .find(|import| &import[3] == module && word.is_match(&import[1]))
+        else {
+            return;
+        };
+        let statement = import.get(0).expect("whole match").range();
+        let clause = import[1].to_string();
+        let others: Vec<&str> = clause
+            .trim_matches(|c: char| c == '{' || c == '}' || c.is_whitespace())
+            .split(',')
+            .map(str::trim)
+            .filter(|binding| !binding.is_empty() && *binding != name)
+            .collect();
+        if others.is_empty() || !clause.contains('{') {
+            let end = statement.end + usize::from(self.code[statement.end..].starts_with('\n'));
+            self.code.replace_range(statement.start..end, "");
+        } else {
+            let clause_range = import.get(1).expect("clause group").range();
+            self.code
+                .replace_range(clause_range, &format!("{{ {} }}", others.join(", ")));
+        }
+    }
+
+    /// Import `name` and list it in the `key` array of the config.
+    fn register(
+        &mut self,
+        key: &str,
+        name: &str,
+        path: &str,
+        default_export: bool,
+    ) -> Result<String, String> {
+        match self.property(key)? {
+            Some(item) if !self.code[item.value_start..].starts_with('[') => {
+                return Err(format!("`{key}` is not an array literal"));
+            }
+            Some(item) => {
+                let (elements, _) = literal_items(&self.code, item.value_start)?;
+                if elements
+                    .iter()
+                    .any(|element| &self.code[element.start..element.end] == name)
+                {
+                    return Err(format!("{name} is already in `{key}`"));
+                }
+                self.append(item.value_start, name)?;
+            }
+            None => {
+                let (start, _) = self.config()?;
+                self.append(start, &format!("{key}: [{name}]"))?;
+            }
+        }
+        self.add_import(name, path, default_export);
+        Ok(format!(
+            "Imported {name} from {path} and added it to `{key}`"
+        ))
+    }
+
+    fn add_plugin(
+        &mut self,
+        name: &str,
+        package: &str,
+        options: Option<&Value>,
+    ) -> Result<String, String> {
+        let call = match options {
+            Some(options) => format!("{name}({})", ts_value(options)),
+            None => format!("{name}()"),
+        };
+        let message = match self.property("plugins")? {
+            Some(item) if !self.code[item.value_start..].starts_with('[') => {
+                return Err("`plugins` is not an array literal".to_string());
+            }
+            Some(item) => {
+                let (elements, _) = literal_items(&self.code, item.value_start)?;
+                let existing = elements
+                    .iter()
+                    .find(|element| self.code[element.start..].starts_with(&format!("{name}(")));
+                match existing {
+                    Some(element) => {
+                        let element = element.clone();
+                        self.replace_value(&element, &call);
+                        format!("Replaced the options of {name}")
+                    }
+                    None => {
+                        self.append(item.value_start, &call)?;
+                        format!("Added {name} to `plugins`")
+                    }
+                }
+            }
+            None => {
+                let (start, _) = self.config()?;
+                self.append(start, &format!("plugins: [{call}]"))?;
+                format!("Added `plugins` with {name}")
+            }
+        };
+        self.add_import(name, package, false);
+        self.dependencies.insert(package.to_string());
+        Ok(message)
+    }
+
+    fn set_database(&mut self, adapter: &str, env: Option<&str>) -> Result<String, String> {
+        let &(_, factory, package, options) =
+            ADAPTERS
+                .iter()
+                .find(|(name, ..)| *name == adapter)
+                .ok_or_else(|| format!("Unknown database adapter '{adapter}'"))?;
+        let current = self.property("db")?;
+        let current_source = current
+            .as_ref()
+            .map(|item| self.code[item.value_start..item.end].to_string())
+            .unwrap_or_default();
+        if current_source.starts_with(&format!("{factory}(")) {
+            return Err(format!("`db` already uses {factory}"));
+        }
+        let env_pattern =
+            Regex::new(r"process\.env\.([A-Za-z_][A-Za-z0-9_]*)").expect("valid env pattern");
+        let env = env
+            .map(str::to_string)
+            .or_else(|| {
+                env_pattern
+                    .captures(&current_source)
+                    .map(|found| found[1].to_string())
+            })
+            .unwrap_or_else(|| "DATABASE_URI".to_string());
+        let value = format!("{factory}({})", options.replace("{env}", &env));
+        self.set_property("db", &value)?;
+        self.add_import(factory, package, false);
+        self.dependencies.insert(package.to_string());
+        if let Some(&(_, old_factory, old_package, _)) = ADAPTERS
+            .iter()
+            .find(|(_, factory, ..)| current_source.starts_with(&format!("{factory}(")))
+        {
+            self.remove_unused_import(old_factory, old_package);
+            return Ok(format!(
+                "Replaced {old_factory} with {factory}, reading {env}"
+            ));
+        }
+        Ok(format!("Set `db` to {factory}, reading {env}"))
+    }
+
+    fn add_locale(
+        &mut self,
+        code: &str,
+        label: Option<&str>,
+        default: bool,
+    ) -> Result<String, String> {
+        let Some(localization) = self.property("localization")? else {
+            let locale = match label {
+                Some(label) => format!("{{ label: {}, code: {} }}", quoted(label), quoted(code)),
+                None => quoted(code),
+            };
+            let value = format!(
+                "{{\n  locales: [{locale}],\n  defaultLocale: {},\n}}",
+                quoted(code)
+            );
+            self.set_property("localization", &value)?;
+            return Ok(format!(
+                "Added `localization` with {code} as the default locale"
+            ));
+        };
+        if !self.code[localization.value_start..].starts_with('{') {
+            return Err("`localization` is not an object literal".to_string());
+        }
+        let open = localization.value_start;
+        let mut changes = Vec::new();
+        match self.item(open, "locales")? {
+            Some(locales) if !self.code[locales.value_start..].starts_with('[') => {
+                return Err("`locales` is not an array literal".to_string());
+            }
+            Some(locales) => {
+                let (entries, _) = literal_items(&self.code, locales.value_start)?;
+                let known = entries
+                    .iter()
+                    .any(|entry| locale_code(&self.code, entry).as_deref() == Some(code));
+                let objects = entries
+                    .iter()
+                    .any(|entry| self.code[entry.start..].starts_with('{'));
+                if !known {
+                    let entry = if objects || label.is_some() {
+                        format!(
+                            "{{ label: {}, code: {} }}",
+                            quoted(label.unwrap_or(code)),
+                            quoted(code)
+                        )
+                    } else {
+                        quoted(code)
+                    };
+                    self.append(locales.value_start, &entry)?;
+                    changes.push(format!("Added locale {code}"));
+                }
+            }
+            None => {
+                self.append(open, &format!("locales: [{}]", quoted(code)))?;
+                changes.push(format!("Added `locales` with {code}"));
+            }
+        }
+        if default {
+            match self.item(open, "defaultLocale")? {
+                Some(item) if self.code[item.value_start..item.end] == quoted(code) => {}
+                Some(item) => {
+                    self.replace_value(&item, &quoted(code));
+                    changes.push(format!("Made {code} the default locale"));
+                }
+                None => {
+                    self.append(open, &format!("defaultLocale: {}", quoted(code)))?;
+                    changes.push(format!("Made {code} the default locale"));
+                }
+            }
+        }
+        if changes.is_empty() {
+            return Err(format!("Locale {code} is already configured"));
+        }
+        Ok(changes.join("; "))
+    }
+
+    fn apply(&mut self, edit: &ConfigEdit) -> Result<String, String> {
+        match edit {
+            ConfigEdit::AddCollection {
+                name,
+                path,
+                default_export,
+            } => self.register("collections", name, path, *default_export),
+            ConfigEdit::AddGlobal {
+                name,
+                path,
+                default_export,
+            } => self.register("globals", name, path, *default_export),
+            ConfigEdit::AddPlugin {
+                name,
+                package,
+                options,
+            } => self.add_plugin(name, package, options.as_ref()),
+            ConfigEdit::SetDatabase { adapter, env } => self.set_database(adapter, env.as_deref()),
+            ConfigEdit::AddLocale {
+                code,
+                label,
+                default,
+            } => self.add_locale(code, label.as_deref(), *default),
+        }
+    }
+}
+
+/// Apply `edits` to the config source `code` in order. An edit that cannot
+/// be applied is reported in `skipped` and leaves the source unchanged.
+pub fn patch_config(code: &str, edits: &[ConfigEdit]) -> Result<PatchConfigOutput, String> {
+    let mut patcher = Patcher {
+        code: code.to_string(),
+        dependencies: BTreeSet::new(),
+    };
+    patcher.config()?;
+    let mut applied = Vec::new();
+    let mut skipped = Vec::new();
+    for edit in edits {
+        let before = patcher.code.clone();
+        let dependencies = patcher.dependencies.clone();
+        match patcher.apply(edit) {
+            Ok(message) => applied.push(message),
+            Err(reason) => {
+                patcher.code = before;
+                patcher.dependencies = dependencies;
+                skipped.push(reason);
+            }
+        }
+    }
+    Ok(PatchConfigOutput {
+        code: patcher.code,
+        applied,
+        skipped,
+        dependencies: patcher.dependencies.into_iter().collect(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn patches_config_preserving_comments() {
+        let code = r#"import { buildConfig } from 'payload'
+import { mongooseAdapter } from '@payloadcms/db-mongodb'
+import { Users } from './collections/Users'
+
+export default buildConfig({
+  // Registered collections
+  collections: [Users], // keep Users first
+  db: mongooseAdapter({
+    url: process.env.MONGODB_URI || '',
+  }),
+  localization: {
+    locales: ['en'],
+    defaultLocale: 'en',
+  },
+})
+"#;
+        let edits = vec![
+            ConfigEdit::AddCollection {
+                name: "Posts".to_string(),
+                path: "./collections/Posts".to_string(),
+                default_export: false,
+            },
+            ConfigEdit::AddCollection {
+                name: "Users".to_string(),
+                path: "./collections/Users".to_string(),
+                default_export: false,
+            },
+            ConfigEdit::AddPlugin {
+                name: "seoPlugin".to_string(),
+                package: "@payloadcms/plugin-seo".to_string(),
+                options: Some(json!({ "collections": ["posts"], "uploadsCollection": "media" })),
+            },
+            ConfigEdit::SetDatabase {
+                adapter: "postgres".to_string(),
+                env: None,
+            },
+            ConfigEdit::AddLocale {
+                code: "de".to_string(),
+                label: None,
+                default: true,
+            },
+        ];
+        let output = patch_config(code, &edits).unwrap();
+        assert_eq!(output.applied.len(), 4);
+        assert_eq!(output.skipped, vec!["Users is already in `collections`"]);
+        assert_eq!(
+            output.dependencies,
+            vec!["@payloadcms/db-postgres", "@payloadcms/plugin-seo"]
+        );
+        assert_eq!(
+            output.code,
+            r#"import { buildConfig } from 'payload'
+import { Users } from './collections/Users'
+import { Posts } from './collections/Posts'
+import { seoPlugin } from '@payloadcms/plugin-seo'
+import { postgresAdapter } from '@payloadcms/db-postgres'
+
+export default buildConfig({
+  // Registered collections
+  collections: [Users, Posts], // keep Users first
+  db: postgresAdapter({
+    pool: {
+      connectionString: process.env.MONGODB_URI || '',
+    },
+  }),
+  localization: {
+    locales: ['en', 'de'],
+    defaultLocale: 'de',
+  },
+  plugins: [seoPlugin({
+    collections: ['posts'],
+    uploadsCollection: 'media',
+  })],
+})
+"#
+        );
+    }
+}
@@ -0,0 +1,437 @@
+//! Content transfer between live Payload instances as NDJSON: one
+//! `{"collection": slug, "doc": {...}}` object per line.
+//!
+//! Documents are exported at depth 0, so relationships hold IDs. Imports send
+//! each document's original `id`; when the target assigns a different one,
+//! later references to the document are rewritten before they are sent.
+
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    io::{BufRead, Write},
+};
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value, json};
+
+use crate::payload_tools::{
+    client::PayloadClient,
+    schema_model::{SchemaField, SchemaModel},
+};
+
+const DEFAULT_BATCH_SIZE: usize = 100;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum ImportMode {
+    /// Create every document
+    #[default]
+    Create,
+    /// Update documents whose ID already exists, create the rest
+    Upsert,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ContentExportOptions {
+    /// Collections to export, in order. List referenced collections first so
+    /// an import creates them before the documents pointing at them.
+    pub collections: Vec<String>,
+    /// Documents fetched per request, default 100
+    pub batch_size: Option<usize>,
+    pub locale: Option<String>,
+    /// Export the latest drafts instead of published versions
+    pub draft: Option<bool>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ContentImportOptions {
+    pub mode: Option<ImportMode>,
+    /// Documents looked up per request in upsert mode, default 100
+    pub batch_size: Option<usize>,
+    /// Only import these collections
+    pub collections: Option<Vec<String>>,
+    /// Locates relationship fields to rewrite when IDs change; polymorphic
+    /// relationships (`{ relationTo, value }`) are found without it
+    pub schema: Option<SchemaModel>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ImportFailure {
+    /// 1-based NDJSON line
+    pub line: usize,
+    pub collection: Option<String>,
+    pub id: Option<Value>,
+    pub error: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+pub struct ContentImport {
+    pub created: usize,
+    pub updated: usize,
+    /// Documents saved under a different ID than exported
+    pub reassigned: usize,
+    /// References rewritten to a reassigned ID
+    pub remapped: usize,
+    /// Documents imported per collection
+    pub counts: BTreeMap<String, usize>,
+    pub failures: Vec<ImportFailure>,
+}
+
+pub(crate) fn id_key(id: &Value) -> Option<String> {
+    match id {
+        Value::String(id) => Some(id.clone()),
+        Value::Number(id) => Some(id.to_string()),
+        _ => None,
+    }
+}
+
+/// Write every document of `options.collections` to `out`, returning the
+/// count per collection.
+pub fn export_content(
+    client: &PayloadClient,
+    options: &ContentExportOptions,
+    out: &mut impl Write,
+) -> Result<BTreeMap<String, usize>, String> {
+    if options.collections.is_empty() {
+        return Err("collections must list at least one collection".to_string());
+    }
+    let batch_size = options.batch_size.unwrap_or(DEFAULT_BATCH_SIZE).max(1);
+    let mut counts = BTreeMap::new();
+    for slug in &options.collections {
+        let mut count = 0;
+        let mut page = 1;
+        loop {
+            let mut params = vec![
+                ("limit".to_string(), batch_size.to_string()),
+                ("page".to_string(), page.to_string()),
+                ("depth".to_string(), "0".to_string()),
+                ("sort".to_string(), "id".to_string()),
+            ];
+            if let Some(locale) = &options.locale {
+                params.push(("locale".to_string(), locale.clone()));
+            }
+            if options.draft.unwrap_or(false) {
+                params.push(("draft".to_string(), "true".to_string()));
+            }
+            let body = client
+                .find_page(slug, &params)
+                .map_err(|err| err.to_string())?;
+            let docs = body["docs"].as_array().cloned().unwrap_or_default();
+            for doc in &docs {
+                let line = json!({ "collection": slug, "doc": doc });
+                writeln!(out, "{line}").map_err(|err| err.to_string())?;
+            }
+            count += docs.len();
+            if docs.is_empty() || !body["hasNextPage"].as_bool().unwrap_or(false) {
+                break;
+            }
+            page += 1;
+        }
+        tracing::info!("Exported {count} documents from {slug}");
+        counts.insert(slug.clone(), count);
+    }
+    Ok(counts)
+}
+
+/// Old ID -> new ID per collection, for documents saved under a new ID.
+#[derive(Default)]
+struct IdMap {
+    ids: HashMap<(String, String), Value>,
+    remapped: usize,
+}
+
+impl IdMap {
+    fn remap(&mut self, slug: &str, id: &mut Value) {
+        let Some(key) = id_key(id) else {
+            return;
+        };
+        if let Some(new) = self.ids.get(&(slug.to_string(), key)) {
+            *id = new.clone();
+            self.remapped += 1;
+        }
+    }
+
+    /// Rewrite every `{ relationTo, value }` in `value`.
+    fn remap_polymorphic(&mut self, value: &mut Value) {
+        match value {
+            Value::Object(map) => {
+                if let (Some(Value::String(slug)), Some(_)) =
+                    (map.get("relationTo"), map.get("value"))
+                {
+                    let slug = slug.clone();
+                    if let Some(id) = map.get_mut("value") {
+                        self.remap(&slug, id);
+                    }
+                }
+                for child in map.values_mut() {
+                    self.remap_polymorphic(child);
+                }
+            }
+            Value::Array(items) => {
+                for item in items {
+                    self.remap_polymorphic(item);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Rewrite single-collection relationship IDs in the fields `schema` knows.
+    fn remap_fields(&mut self, doc: &mut Map<String, Value>, fields: &[SchemaField]) {
+        for field in fields {
+            let Some(value) = doc.get_mut(&field.name) else {
+                continue;
+            };
+            match field.field_type.as_str() {
+                "relationship" | "upload" => {
+                    let [slug] = field.relation_to.as_deref().unwrap_or_default() else {
+                        continue;
+                    };
+                    match value {
+                        Value::Array(items) => items.iter_mut().for_each(|id| self.remap(slug, id)),
+                        id => self.remap(slug, id),
+                    }
+                }
+                "group" => {
+                    if let Value::Object(group) = value {
+                        self.remap_fields(group, field.fields.as_deref().unwrap_or_default());
+                    }
+                }
+                "array" => {
+                    for row in value.as_array_mut().into_iter().flatten() {
+                        if let Value::Object(row) = row {
+                            self.remap_fields(row, field.fields.as_deref().unwrap_or_default());
+                        }
+                    }
+                }
+                "blocks" => {
+                    for row in value.as_array_mut().into_iter().flatten() {
+                        let Value::Object(row) = row else {
+                            continue;
+                        };
+                        let block_type = row
+                            .get("blockType")
+                            .and_then(Value::as_str)
+                            .map(str::to_string);
+                        let block = field
+                            .blocks
+                            .iter()
+                            .flatten()
+                            .find(|block| Some(&block.slug) == block_type.as_ref());
+                        if let Some(block) = block {
+                            self.remap_fields(row, &block.fields);
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+}
+
+struct Pending {
+    line: usize,
+    doc: Map<String, Value>,
+}
+
+/// Read NDJSON from `input` and save each document to `client`. Failing
+/// documents are recorded and skipped; malformed lines stop the import.
+pub fn import_content(
+    client: &PayloadClient,
+    input: impl BufRead,
+    options: &ContentImportOptions,
+) -> Result<ContentImport, String> {
+    let batch_size = options.batch_size.unwrap_or(DEFAULT_BATCH_SIZE).max(1);
+    let mut import = ContentImport::default();
+    let mut ids = IdMap::default();
+    let mut batch: Vec<Pending> = Vec::new();
+    let mut batch_slug = String::new();
+
+    for (index, line) in input.lines().enumerate() {
+        let line = line.map_err(|err| err.to_string())?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let entry: Value = serde_json::from_str(&line)
+            .map_err(|err| format!("line {}: invalid JSON: {err}", index + 1))?;
+        let (Some(slug), Some(Value::Object(doc))) =
+            (entry["collection"].as_str(), entry.get("doc"))
+        else {
+            return Err(format!(
+                "line {}: expected {{\"collection\": ..., \"doc\": {{...}}}}",
+                index + 1
+            ));
+        };
+        if options
+            .collections
+            .as_ref()
+            .is_some_and(|only| !only.iter().any(|only| only == slug))
+        {
+            continue;
+        }
+        if slug != batch_slug || batch.len() >= batch_size {
+            save_batch(
+                client,
+                &batch_slug,
+                std::mem::take(&mut batch),
+                options,
+                &mut ids,
+                &mut import,
+            );
+            batch_slug = slug.to_string();
+        }
+        batch.push(Pending {
+            line: index + 1,
+            doc: doc.clone(),
+        });
+    }
+    save_batch(client, &batch_slug, batch, options, &mut ids, &mut import);
+    import.remapped = ids.remapped;
+    Ok(import)
+}
+
+fn save_batch(
+    client: &PayloadClient,
+    slug: &str,
+    batch: Vec<Pending>,
+    options: &ContentImportOptions,
+    ids: &mut IdMap,
+    import: &mut ContentImport,
+) {
+    if batch.is_empty() {
+        return;
+    }
+    let existing = match options.mode.unwrap_or_default() {
+        ImportMode::Create => Ok(HashSet::new()),
+        ImportMode::Upsert => existing_ids(client, slug, &batch),
+    };
+    let existing = match existing {
+        Ok(existing) => existing,
+        Err(error) => {
+            import
+                .failures
+                .extend(batch.into_iter().map(|pending| ImportFailure {
+                    line: pending.line,
+                    collection: Some(slug.to_string()),
+                    id: pending.doc.get("id").cloned(),
+                    error: error.clone(),
+                }));
+            return;
+        }
+    };
+    let fields = options
+        .schema
+        .as_ref()
+        .and_then(|schema| schema.collection(slug))
+        .map(|collection| collection.fields.as_slice())
+        .unwrap_or_default();
+
+    for Pending { line, mut doc } in batch {
+        ids.remap_fields(&mut doc, fields);
+        let mut value = Value::Object(doc);
+        ids.remap_polymorphic(&mut value);
+        let old_id = value.get("id").cloned();
+        let key = old_id.as_ref().and_then(id_key);
+
+        let saved = match key.clone().filter(|key| existing.contains(key)) {
+            Some(key) => {
+                if let Some(doc) = value.as_object_mut() {
+                    doc.remove("id");
+                }
+                client.update_document(slug, &key, &value).map(|_| {
+                    import.updated += 1;
+                })
+            }
+            None => client.create_document(slug, &value).map(|saved| {
+                import.created += 1;
+                if let (Some(old), Some(new)) = (key, saved.get("id")) {
+                    if id_key(new).as_ref() != Some(&old) {
+                        ids.ids.insert((slug.to_string(), old), new.clone());
+                        import.reassigned += 1;
+                    }
+                }
+            }),
+        };
+        match saved {
+            Ok(()) => *import.counts.entry(slug.to_string()).or_default() += 1,
+            Err(err) => import.failures.push(ImportFailure {
+                line,
+                collection: Some(slug.to_string()),
+                id: old_id,
+                error: err.to_string(),
+            }),
+        }
+    }
+}
+
+/// IDs of `batch` that already exist in `slug`, looked up in one request.
+fn existing_ids(
+    client: &PayloadClient,
+    slug: &str,
+    batch: &[Pending],
+) -> Result<HashSet<String>, String> {
+    let keys: Vec<String> = batch
+        .iter()
+        .filter_map(|pending| pending.doc.get("id").and_then(id_key))
+        .collect();
+    if keys.is_empty() {
+        return Ok(HashSet::new());
+    }
+    let params = [
+        ("where[id][in]".to_string(), keys.join(",")),
+        ("limit".to_string(), keys.len().to_string()),
+        ("depth".to_string(), "0".to_string()),
+    ];
+    let docs = client
+        .find_documents(slug, &params)
+        .map_err(|err| err.to_string())?;
+    Ok(docs
+        .iter()
+        .filter_map(|doc| doc.get("id").and_then(id_key))
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::payload_tools::schema_model::SchemaCollection;
+
+    #[test]
+    fn remaps_reassigned_ids() {
+        let mut ids = IdMap::default();
+        ids.ids
+            .insert(("users".to_string(), "u1".to_string()), json!("u9"));
+        ids.ids
+            .insert(("tags".to_string(), "1".to_string()), json!(7));
+
+        let mut author = SchemaField::new("author", "relationship");
+        author.relation_to = Some(vec!["users".to_string()]);
+        let mut tags = SchemaField::new("tags", "relationship");
+        tags.relation_to = Some(vec!["tags".to_string()]);
+        tags.has_many = true;
+        let schema = SchemaModel {
+            collections: vec![SchemaCollection {
+                slug: "posts".to_string(),
+                fields: vec![author, tags],
+                ..Default::default()
+            }],
+            globals: Vec::new(),
+        };
+
+        let mut doc = json!({
+            "title": "Hello",
+            "author": "u1",
+            "tags": [1, 2],
+            "related": { "relationTo": "users", "value": "u1" },
+        });
+        let fields = &schema.collection("posts").unwrap().fields;
+        ids.remap_fields(doc.as_object_mut().unwrap(), fields);
+        ids.remap_polymorphic(&mut doc);
+        assert_eq!(doc["author"], "u9");
+        assert_eq!(doc["tags"], json!([7, 2]));
+        assert_eq!(doc["related"]["value"], "u9");
+        assert_eq!(ids.remapped, 3);
+    }
+}
@@ -0,0 +1,516 @@
+//! Content health of a live instance: documents missing required fields,
+//! relationships pointing at deleted documents, uploads nothing references,
+//! drafts left unpublished and empty collections.
+//!
+//! Documents are read at depth 0 with `draft=true`, so relationships hold IDs
+//! and unpublished changes are seen. Required fields are checked on published
+//! documents only, since Payload doesn't enforce them on drafts. Relationship
+//! and upload fields are followed at the top level and inside groups, arrays
+//! and blocks; rich text is searched for Lexical `{ relationTo, value }` nodes.
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use chrono::{DateTime, Duration, Utc};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+use crate::payload_tools::{
+    client::PayloadClient,
+    content::id_key,
+    i18n_audit::{is_missing, lookup},
+    schema_model::{SchemaCollection, SchemaField, SchemaModel},
+};
+
+const DEFAULT_BATCH_SIZE: usize = 100;
+const DEFAULT_MAX_DOCUMENTS: usize = 1000;
+const DEFAULT_SAMPLES: usize = 5;
+const DEFAULT_DRAFT_AGE_DAYS: u32 = 30;
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ContentAuditOptions {
+    /// Only audit these collections; defaults to every collection of the
+    /// schema. Orphaned uploads are only reported when every collection is
+    /// read in full.
+    pub collections: Option<Vec<String>>,
+    /// Drafts not updated for this many days are stale, default 30
+    pub draft_age_days: Option<u32>,
+    /// Documents fetched per request, default 100
+    pub batch_size: Option<usize>,
+    /// Documents read per collection, default 1000
+    pub max_documents: Option<usize>,
+    /// Document IDs listed per finding, default 5
+    pub samples: Option<usize>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct MissingRequired {
+    /// Dotted path of the field
+    pub field: String,
+    pub missing: usize,
+    pub sample_ids: Vec<Value>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct StaleDrafts {
+    pub count: usize,
+    pub sample_ids: Vec<Value>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct CollectionHealth {
+    pub slug: String,
+    /// Documents read
+    pub documents: usize,
+    pub missing_required: Vec<MissingRequired>,
+    pub stale_drafts: StaleDrafts,
+    /// The collection holds more documents than `maxDocuments`
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub truncated: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct BrokenReferences {
+    pub collection: String,
+    /// Dotted path of the relationship, upload or rich text field
+    pub field: String,
+    pub relation_to: String,
+    pub missing: usize,
+    /// Documents holding a broken reference
+    pub sample_ids: Vec<Value>,
+    /// IDs referenced but not found
+    pub missing_ids: Vec<Value>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct OrphanedUploads {
+    pub collection: String,
+    pub count: usize,
+    pub sample_ids: Vec<Value>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ContentAudit {
+    pub collections: Vec<CollectionHealth>,
+    pub broken_references: Vec<BrokenReferences>,
+    pub orphaned_uploads: Vec<OrphanedUploads>,
+    pub empty_collections: Vec<String>,
+    pub warnings: Vec<String>,
+}
+
+/// A relationship found in a document.
+#[derive(Debug, Clone, PartialEq)]
+struct Reference {
+    field: String,
+    relation_to: String,
+    id: Value,
+}
+
+/// Dotted paths of the required fields under `fields`, descending into groups.
+fn required_fields(prefix: &str, fields: &[SchemaField], out: &mut Vec<String>) {
+    for field in fields {
+        let path = if prefix.is_empty() {
+            field.name.clone()
+        } else {
+            format!("{prefix}.{}", field.name)
+        };
+        if field.field_type == "group" {
+            required_fields(&path, field.fields.as_deref().unwrap_or_default(), out);
+        } else if field.required {
+            out.push(path);
+        }
+    }
+}
+
+/// Every `{ relationTo, value }` under `value`, as found in rich text.
+fn rich_text_references(path: &str, value: &Value, out: &mut Vec<Reference>) {
+    match value {
+        Value::Object(map) => {
+            if let (Some(Value::String(slug)), Some(id)) = (map.get("relationTo"), map.get("value")) {
+                if id_key(id).is_some() {
+                    out.push(Reference {
+                        field: path.to_string(),
+                        relation_to: slug.clone(),
+                        id: id.clone(),
+                    });
+                }
+            }
+            for child in map.values() {
+                rich_text_references(path, child, out);
+            }
+        }
+        Value::Array(items) => items.iter().for_each(|item| rich_text_references(path, item, out)),
+        _ => {}
+    }
+}
+
+/// The relationships of `doc` in the fields `fields` describes.
+fn references(prefix: &str, doc: &Map<String, Value>, fields: &[SchemaField], out: &mut Vec<Reference>) {
+    for field in fields {
+        let Some(value) = doc.get(&field.name) else {
+            continue;
+        };
+        let path = if prefix.is_empty() {
+            field.name.clone()
+        } else {
+            format!("{prefix}.{}", field.name)
+        };
+        let rows = || value.as_array().into_iter().flatten().filter_map(Value::as_object);
+        match field.field_type.as_str() {
+            "relationship" | "upload" => {
+                let relation_to = field.relation_to.as_deref().unwrap_or_default();
+                let values: Vec<&Value> = match value {
+                    Value::Array(items) => items.iter().collect(),
+                    value => vec![value],
+                };
+                for value in values {
+                    let reference = match (relation_to, value) {
+                        ([slug], id) if id_key(id).is_some() => Some((slug.clone(), id.clone())),
+                        (_, Value::Object(polymorphic)) => match (polymorphic.get("relationTo"), polymorphic.get("value")) {
+                            (Some(Value::String(slug)), Some(id)) if id_key(id).is_some() => Some((slug.clone(), id.clone())),
+                            _ => None,
+                        },
+                        _ => None,
+                    };
+                    if let Some((relation_to, id)) = reference {
+                        out.push(Reference {
+                            field: path.clone(),
+                            relation_to,
+                            id,
+                        });
+                    }
+                }
+            }
+            "richText" => rich_text_references(&path, value, out),
+            "group" => {
+                if let Value::Object(group) = value {
+                    references(&path, group, field.fields.as_deref().unwrap_or_default(), out);
+                }
+            }
+            "array" => {
+                for row in rows() {
+                    references(&path, row, field.fields.as_deref().unwrap_or_default(), out);
+                }
+            }
+            "blocks" => {
+                for row in rows() {
+                    let block = field
+                        .blocks
+                        .iter()
+                        .flatten()
+                        .find(|block| row.get("blockType").and_then(Value::as_str) == Some(block.slug.as_str()));
+                    if let Some(block) = block {
+                        references(&path, row, &block.fields, out);
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// A draft whose `updatedAt` is before `cutoff`.
+fn is_stale_draft(doc: &Value, cutoff: DateTime<Utc>) -> bool {
+    doc["_status"] == "draft"
+        && doc["updatedAt"]
+            .as_str()
+            .and_then(|updated| DateTime::parse_from_rfc3339(updated).ok())
+            .is_some_and(|updated| updated < cutoff)
+}
+
+/// What one collection's documents hold, gathered while paging through it.
+#[derive(Default)]
+struct Scan {
+    health: CollectionHealth,
+    missing: BTreeMap<usize, MissingRequired>,
+    /// ID key -> ID of each document read
+    ids: HashMap<String, Value>,
+    /// (referencing document, reference)
+    references: Vec<(Value, Reference)>,
+}
+
+impl Scan {
+    fn record(&mut self, collection: &SchemaCollection, required: &[String], docs: &[Value], cutoff: DateTime<Utc>, samples: usize) {
+        for doc in docs {
+            let id = doc["id"].clone();
+            if let Some(key) = id_key(&id) {
+                self.ids.insert(key, id.clone());
+            }
+            if is_stale_draft(doc, cutoff) {
+                let stale = &mut self.health.stale_drafts;
+                stale.count += 1;
+                if stale.sample_ids.len() < samples {
+                    stale.sample_ids.push(id.clone());
+                }
+            }
+            if doc["_status"] != "draft" {
+                for (index, field) in required.iter().enumerate() {
+                    if !is_missing(lookup(doc, field)) {
+                        continue;
+                    }
+                    let entry = self.missing.entry(index).or_insert_with(|| MissingRequired {
+                        field: field.clone(),
+                        missing: 0,
+                        sample_ids: Vec::new(),
+                    });
+                    entry.missing += 1;
+                    if entry.sample_ids.len() < samples {
+                        entry.sample_ids.push(id.clone());
+                    }
+                }
+            }
+            if let Value::Object(map) = doc {
+                let mut found = Vec::new();
+                references("", map, &collection.fields, &mut found);
+                self.references.extend(found.into_iter().map(|reference| (id.clone(), reference)));
+            }
+        }
+        self.health.documents += docs.len();
+    }
+}
+
+/// IDs of `ids` that exist in `slug`, looked up `batch_size` at a time.
+fn existing_ids(client: &PayloadClient, slug: &str, ids: &[String], batch_size: usize) -> Result<HashSet<String>, String> {
+    let mut existing = HashSet::new();
+    for chunk in ids.chunks(batch_size) {
+        let params = [
+            ("where[id][in]".to_string(), chunk.join(",")),
+            ("limit".to_string(), chunk.len().to_string()),
+            ("depth".to_string(), "0".to_string()),
+        ];
+        let docs = client.find_documents(slug, &params).map_err(|err| err.to_string())?;
+        existing.extend(docs.iter().filter_map(|doc| doc.get("id").and_then(id_key)));
+    }
+    Ok(existing)
+}
+
+/// Group the references whose target is not in `found` per collection, field
+/// and target collection.
+fn broken_references(
+    scans: &[Scan],
+    found: &HashMap<String, HashSet<String>>,
+    samples: usize,
+) -> Vec<BrokenReferences> {
+    let mut broken: BTreeMap<(String, String, String), BrokenReferences> = BTreeMap::new();
+    for scan in scans {
+        for (doc_id, reference) in &scan.references {
+            let Some(existing) = found.get(&reference.relation_to) else {
+                continue;
+            };
+            if id_key(&reference.id).is_some_and(|key| existing.contains(&key)) {
+                continue;
+            }
+            let key = (scan.health.slug.clone(), reference.field.clone(), reference.relation_to.clone());
+            let entry = broken.entry(key).or_insert_with(|| BrokenReferences {
+                collection: scan.health.slug.clone(),
+                field: reference.field.clone(),
+                relation_to: reference.relation_to.clone(),
+                missing: 0,
+                sample_ids: Vec::new(),
+                missing_ids: Vec::new(),
+            });
+            entry.missing += 1;
+            if entry.sample_ids.len() < samples && !entry.sample_ids.contains(doc_id) {
+                entry.sample_ids.push(doc_id.clone());
+            }
+            if entry.missing_ids.len() < samples && !entry.missing_ids.contains(&reference.id) {
+                entry.missing_ids.push(reference.id.clone());
+            }
+        }
+    }
+    broken.into_values().collect()
+}
+
+/// Read every audited collection of `schema` and report its content health.
+pub fn content_audit(client: &PayloadClient, schema: &SchemaModel, options: &ContentAuditOptions) -> Result<ContentAudit, String> {
+    let batch_size = options.batch_size.unwrap_or(DEFAULT_BATCH_SIZE).max(1);
+    let max_documents = options.max_documents.unwrap_or(DEFAULT_MAX_DOCUMENTS);
+    let samples = options.samples.unwrap_or(DEFAULT_SAMPLES);
+    let cutoff = Utc::now() - Duration::days(options.draft_age_days.unwrap_or(DEFAULT_DRAFT_AGE_DAYS).into());
+
+    let mut audit = ContentAudit::default();
+    let mut collections = Vec::new();
+    match &options.collections {
+        Some(slugs) => {
+            for slug in slugs {
+                match schema.collection(slug) {
+                    Some(collection) => collections.push(collection),
+                    None => audit.warnings.push(format!("Collection {slug} is not in the schema")),
+                }
+            }
+        }
+        None => collections.extend(&schema.collections),
+    }
+
+    let mut scans = Vec::new();
+    for collection in collections {
+        let mut required = Vec::new();
+        required_fields("", &collection.fields, &mut required);
+        let mut scan = Scan::default();
+        scan.health.slug = collection.slug.clone();
+        let mut page = 1;
+        while scan.health.documents < max_documents {
+            let params = vec![
+                ("limit".to_string(), batch_size.to_string()),
+                ("page".to_string(), page.to_string()),
+                ("depth".to_string(), "0".to_string()),
+                ("sort".to_string(), "id".to_string()),
+                ("draft".to_string(), "true".to_string()),
+            ];
+            let body = client
+                .find_page(&collection.slug, &params)
+                .map_err(|err| err.to_string())?;
+            let docs = body["docs"].as_array().cloned().unwrap_or_default();
+            let kept = &docs[..docs.len().min(max_documents - scan.health.documents)];
+            scan.record(collection, &required, kept, cutoff, samples);
+            let more = kept.len() < docs.len() || body["hasNextPage"].as_bool().unwrap_or(false);
+            if docs.is_empty() || !more {
+                break;
+            }
+            if scan.health.documents >= max_documents {
+                scan.health.truncated = true;
+            }
+            page += 1;
+        }
+        if scan.health.documents == 0 {
+            audit.empty_collections.push(collection.slug.clone());
+        }
+        scan.health.missing_required = std::mem::take(&mut scan.missing).into_values().collect();
+        scans.push(scan);
+    }
+
+    // Targets read in full are checked against the IDs seen; the rest are
+    // looked up.
+    let mut found: HashMap<String, HashSet<String>> = scans
+        .iter()
+        .filter(|scan| !scan.health.truncated)
+        .map(|scan| (scan.health.slug.clone(), scan.ids.keys().cloned().collect()))
+        .collect();
+    let mut lookups: BTreeMap<String, HashSet<String>> = BTreeMap::new();
+    for (_, reference) in scans.iter().flat_map(|scan| &scan.references) {
+        if !found.contains_key(&reference.relation_to) {
+            if let Some(key) = id_key(&reference.id) {
+                lookups.entry(reference.relation_to.clone()).or_default().insert(key);
+            }
+        }
+    }
+    for (slug, ids) in lookups {
+        let ids: Vec<String> = ids.into_iter().collect();
+        match existing_ids(client, &slug, &ids, batch_size) {
+            Ok(existing) => {
+                found.insert(slug, existing);
+            }
+            Err(err) => audit.warnings.push(format!("Could not check references to {slug}: {err}")),
+        }
+    }
+    audit.broken_references = broken_references(&scans, &found, samples);
+
+    let complete = options.collections.is_none() && scans.iter().all(|scan| !scan.health.truncated);
+    if complete {
+        let mut referenced: HashMap<&str, HashSet<String>> = HashMap::new();
+        for (_, reference) in scans.iter().flat_map(|scan| &scan.references) {
+            if let Some(key) = id_key(&reference.id) {
+                referenced.entry(reference.relation_to.as_str()).or_default().insert(key);
+            }
+        }
+        for scan in &scans {
+            if !schema.collection(&scan.health.slug).is_some_and(|collection| collection.upload) {
+                continue;
+            }
+            let used = referenced.get(scan.health.slug.as_str());
+            let mut orphans: Vec<(&String, &Value)> = scan
+                .ids
+                .iter()
+                .filter(|(key, _)| !used.is_some_and(|used| used.contains(*key)))
+                .collect();
+            if orphans.is_empty() {
+                continue;
+            }
+            orphans.sort_by(|a, b| a.0.cmp(b.0));
+            audit.orphaned_uploads.push(OrphanedUploads {
+                collection: scan.health.slug.clone(),
+                count: orphans.len(),
+                sample_ids: orphans.into_iter().take(samples).map(|(_, id)| id.clone()).collect(),
+            });
+        }
+    } else {
+        audit
+            .warnings
+            .push("Orphaned uploads are only reported when every collection is read in full".to_string());
+    }
+
+    audit.collections = scans.into_iter().map(|scan| scan.health).collect();
+    Ok(audit)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_scan_finds_required_drafts_and_references() {
+        let mut hero = SchemaField::new("hero", "upload");
+        hero.relation_to = Some(vec!["media".to_string()]);
+        let mut related = SchemaField::new("related", "relationship");
+        related.relation_to = Some(vec!["posts".to_string(), "pages".to_string()]);
+        related.has_many = true;
+        let mut meta = SchemaField::new("meta", "group");
+        meta.fields = Some(vec![SchemaField {
+            required: true,
+            ..SchemaField::new("description", "text")
+        }]);
+        let collection = SchemaCollection {
+            slug: "posts".to_string(),
+            fields: vec![
+                SchemaField {
+                    required: true,
+                    ..SchemaField::new("title", "text")
+                },
+                hero,
+                related,
+                SchemaField::new("content", "richText"),
+                meta,
+            ],
+            ..Default::default()
+        };
+        let mut required = Vec::new();
+        required_fields("", &collection.fields, &mut required);
+        assert_eq!(required, vec!["title", "meta.description"]);
+
+        let docs = vec![
+            json!({ "id": 1, "title": "Hi", "hero": 7, "meta": { "description": "Ok" }, "_status": "published",
+                "related": [{ "relationTo": "pages", "value": "p1" }],
+                "content": { "root": { "children": [{ "type": "upload", "relationTo": "media", "value": 8 }] } } }),
+            json!({ "id": 2, "title": "", "_status": "published" }),
+            json!({ "id": 3, "_status": "draft", "updatedAt": "2020-01-01T00:00:00.000Z" }),
+        ];
+        let mut scan = Scan::default();
+        scan.health.slug = "posts".to_string();
+        scan.record(&collection, &required, &docs, Utc::now(), 5);
+        let missing: Vec<(String, usize)> = scan.missing.values().map(|entry| (entry.field.clone(), entry.missing)).collect();
+        assert_eq!(missing, vec![("title".to_string(), 1), ("meta.description".to_string(), 1)]);
+        assert_eq!(scan.health.stale_drafts.sample_ids, vec![json!(3)]);
+        let targets: Vec<(&str, &str)> = scan
+            .references
+            .iter()
+            .map(|(_, reference)| (reference.field.as_str(), reference.relation_to.as_str()))
+            .collect();
+        assert_eq!(targets, vec![("hero", "media"), ("related", "pages"), ("content", "media")]);
+
+        let found = HashMap::from([
+            ("media".to_string(), HashSet::from(["7".to_string()])),
+            ("pages".to_string(), HashSet::from(["p1".to_string()])),
+        ]);
+        let broken = broken_references(&[scan], &found, 5);
+        assert_eq!(broken.len(), 1);
+        assert_eq!((broken[0].field.as_str(), broken[0].missing_ids.clone()), ("content", vec![json!(8)]));
+    }
+}
@@ -0,0 +1,1219 @@
+//! Proposed Payload collections for an existing MongoDB or Postgres
+//! database, as a starting point for migrating legacy data.
+//!
+//! Postgres tables are read from `information_schema` and `pg_indexes`;
+//! MongoDB collections are sampled with `$sample`. Introspection runs
+//! through the `psql` and `mongosh` clients, or works on a snapshot of
+//! their output captured elsewhere. Plain JSON documents, such as an export
+//! from another CMS, are inferred the same way as MongoDB samples.
+
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    io::ErrorKind,
+    process::Command,
+    sync::OnceLock,
+};
+
+use regex::Regex;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+use crate::payload_tools::{naming::Case, plugins::to_literal};
+
+pub const DEFAULT_SAMPLE_SIZE: usize = 100;
+/// Levels of nested objects turned into groups and arrays before `json`.
+const MAX_DEPTH: usize = 3;
+/// Text columns and keys likely to hold long text.
+const LONG_TEXT: &[&str] = &[
+    "body",
+    "content",
+    "description",
+    "bio",
+    "notes",
+    "summary",
+    "excerpt",
+];
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum SourceDatabase {
+    Mongodb,
+    Postgres,
+}
+
+#[derive(Debug, Clone, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct DatabaseImportOptions {
+    pub database: SourceDatabase,
+    /// Connection string passed to `psql` or `mongosh`
+    pub connection_string: Option<String>,
+    /// Introspection output captured beforehand, used instead of connecting
+    pub snapshot: Option<Value>,
+    /// Postgres schema (default `public`)
+    pub schema: Option<String>,
+    /// Documents sampled per MongoDB collection (default 100)
+    pub sample_size: Option<usize>,
+    /// Only propose these tables or collections
+    pub include: Option<Vec<String>>,
+}
+
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct ProposedField {
+    pub name: String,
+    /// Column or document key the field was inferred from
+    pub source: String,
+    #[serde(rename = "type")]
+    pub field_type: String,
+    pub required: bool,
+    pub unique: bool,
+    pub index: bool,
+    pub has_many: bool,
+    pub relation_to: Option<String>,
+    pub options: Vec<String>,
+    /// Sub-fields of `group` and `array` fields
+    pub fields: Vec<ProposedField>,
+    /// Why the inference may need a second look
+    pub note: Option<String>,
+}
+
+impl ProposedField {
+    fn new(source: &str, field_type: &str) -> Self {
+        Self {
+            name: field_name(source),
+            source: source.to_string(),
+            field_type: field_type.to_string(),
+            required: false,
+            unique: false,
+            index: false,
+            has_many: false,
+            relation_to: None,
+            options: Vec::new(),
+            fields: Vec::new(),
+            note: None,
+        }
+    }
+
+    fn noted(mut self, note: impl Into<String>) -> Self {
+        self.note = Some(note.into());
+        self
+    }
+}
+
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct ProposedCollection {
+    pub slug: String,
+    /// Table or collection the definition was inferred from
+    pub source: String,
+    /// Whether `createdAt`/`updatedAt` (or `created_at`/`updated_at`) exist
+    pub timestamps: bool,
+    pub fields: Vec<ProposedField>,
+    /// The collection as a TypeScript file
+    pub code: String,
+}
+
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct DatabaseImport {
+    pub database: SourceDatabase,
+    pub collections: Vec<ProposedCollection>,
+    pub warnings: Vec<String>,
+}
+
+/// `blog_posts` and `blogPosts` become `blog-posts`.
+fn slug_of(name: &str) -> String {
+    let mut slug = String::new();
+    for (idx, ch) in name.chars().enumerate() {
+        if ch.is_ascii_uppercase() {
+            if idx > 0 && !slug.ends_with('-') {
+                slug.push('-');
+            }
+            slug.push(ch.to_ascii_lowercase());
+        } else if ch == '_' || ch == ' ' || ch == '.' {
+            if !slug.is_empty() && !slug.ends_with('-') {
+                slug.push('-');
+            }
+        } else {
+            slug.push(ch);
+        }
+    }
+    slug.trim_end_matches('-').to_string()
+}
+
+/// `author_id` becomes `authorId`.
+fn field_name(source: &str) -> String {
+    let mut name = String::new();
+    let mut upper = false;
+    for ch in source.trim_start_matches('_').chars() {
+        if ch == '_' || ch == '-' || ch == ' ' {
+            upper = !name.is_empty();
+        } else if upper {
+            name.push(ch.to_ascii_uppercase());
+            upper = false;
+        } else {
+            name.push(ch);
+        }
+    }
+    if name.starts_with(|c: char| c.is_ascii_digit()) {
+        name.insert(0, '_');
+    }
+    name
+}
+
+/// Name of a relationship field without its id suffix: `author_id` and
+/// `authorIds` become `author`.
+fn relation_base(source: &str) -> &str {
+    ["_ids", "Ids", "_id", "Id"]
+        .iter()
+        .find_map(|suffix| source.strip_suffix(suffix).filter(|base| !base.is_empty()))
+        .unwrap_or(source)
+}
+
+/// The collection among `slugs` a field named `source` most likely points at.
+fn find_target(source: &str, slugs: &BTreeSet<String>) -> Option<String> {
+    let base = slug_of(relation_base(source));
+    let mut candidates = vec![base.clone(), format!("{base}s"), format!("{base}es")];
+    if let Some(stem) = base.strip_suffix('y') {
+        candidates.push(format!("{stem}ies"));
+    }
+    candidates.into_iter().find(|slug| slugs.contains(slug))
+}
+
+fn is_timestamp(source: &str) -> bool {
+    matches!(
+        source,
+        "createdAt" | "updatedAt" | "created_at" | "updated_at"
+    )
+}
+
+fn is_long_text(source: &str) -> bool {
+    LONG_TEXT.contains(&field_name(source).to_ascii_lowercase().as_str())
+}
+
+fn is_email_name(source: &str) -> bool {
+    source.to_ascii_lowercase().ends_with("email")
+}
+
+/// `author_id` or `tagIds`, named like a reference to another document.
+fn is_id_key(source: &str) -> bool {
+    relation_base(source) != source
+}
+
+/// ObjectId hex strings and UUIDs.
+fn looks_like_id(value: &str) -> bool {
+    static ID: OnceLock<Regex> = OnceLock::new();
+    ID.get_or_init(|| {
+        Regex::new(r"^(?i:[0-9a-f]{24}|[0-9a-f]{8}-[0-9a-f]{4}-[0-9a-f]{4}-[0-9a-f]{4}-[0-9a-f]{12})$")
+            .expect("valid id pattern")
+    })
+    .is_match(value)
+}
+
+/// ISO 8601 dates, with or without a time.
+fn looks_like_date(value: &str) -> bool {
+    static DATE: OnceLock<Regex> = OnceLock::new();
+    DATE.get_or_init(|| {
+        Regex::new(r"^\d{4}-\d{2}-\d{2}(?:[T ]\d{2}:\d{2}(?::\d{2}(?:\.\d+)?)?(?:Z|[+-]\d{2}:?\d{2})?)?$")
+            .expect("valid date pattern")
+    })
+    .is_match(value)
+}
+
+// Postgres
+
+#[derive(Debug, Deserialize)]
+struct PgSnapshot {
+    columns: Vec<PgColumn>,
+    #[serde(default)]
+    constraints: Vec<PgConstraint>,
+    #[serde(default)]
+    enums: Vec<PgEnum>,
+    #[serde(default)]
+    indexes: Vec<PgIndex>,
+}
+
+#[derive(Debug, Deserialize)]
+struct PgColumn {
+    table: String,
+    column: String,
+    #[serde(rename = "type")]
+    data_type: String,
+    #[serde(default)]
+    udt: String,
+    nullable: bool,
+    default: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct PgConstraint {
+    table: String,
+    column: String,
+    /// `PRIMARY KEY`, `UNIQUE` or `FOREIGN KEY`
+    kind: String,
+    references: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct PgEnum {
+    name: String,
+    values: Option<Vec<String>>,
+}
+
+#[derive(Debug, Deserialize)]
+struct PgIndex {
+    table: String,
+    definition: String,
+}
+
+/// One JSON object with the columns, key constraints, enums and indexes of
+/// `schema`.
+fn postgres_query(schema: &str) -> String {
+    format!(
+        "SELECT json_build_object(\
+         'columns', (SELECT coalesce(json_agg(json_build_object('table', c.table_name, 'column', c.column_name, 'type', c.data_type, 'udt', c.udt_name, 'nullable', c.is_nullable = 'YES', 'default', c.column_default) ORDER BY c.table_name, c.ordinal_position), '[]') \
+         FROM information_schema.columns c JOIN information_schema.tables t ON t.table_schema = c.table_schema AND t.table_name = c.table_name AND t.table_type = 'BASE TABLE' WHERE c.table_schema = '{schema}'), \
+         'constraints', (SELECT coalesce(json_agg(json_build_object('table', tc.table_name, 'column', kcu.column_name, 'kind', tc.constraint_type, 'references', ccu.table_name)), '[]') \
+         FROM information_schema.table_constraints tc JOIN information_schema.key_column_usage kcu ON kcu.constraint_name = tc.constraint_name AND kcu.table_schema = tc.table_schema \
+         LEFT JOIN information_schema.constraint_column_usage ccu ON tc.constraint_type = 'FOREIGN KEY' AND ccu.constraint_name = tc.constraint_name AND ccu.table_schema = tc.table_schema \
+         WHERE tc.table_schema = '{schema}' AND tc.constraint_type IN ('PRIMARY KEY', 'UNIQUE', 'FOREIGN KEY')), \
+         'enums', (SELECT coalesce(json_agg(json_build_object('name', t.typname, 'values', (SELECT json_agg(e.enumlabel ORDER BY e.enumsortorder) FROM pg_enum e WHERE e.enumtypid = t.oid))), '[]') \
+         FROM pg_type t JOIN pg_namespace n ON n.oid = t.typnamespace WHERE t.typtype = 'e' AND n.nspname = '{schema}'), \
+         'indexes', (SELECT coalesce(json_agg(json_build_object('table', tablename, 'definition', indexdef)), '[]') FROM pg_indexes WHERE schemaname = '{schema}'))"
+    )
+}
+
+/// The column of a single-column index definition and whether it is unique.
+fn index_column(definition: &str) -> Option<(String, bool)> {
+    let open = definition.rfind('(')?;
+    let close = definition[open..].find(')')? + open;
+    let column = definition[open + 1..close].trim().trim_matches('"');
+    if column.is_empty() || column.contains([',', ' ', '(']) {
+        return None;
+    }
+    Some((column.to_string(), definition.starts_with("CREATE UNIQUE")))
+}
+
+fn pg_scalar(data_type: &str) -> Option<&'static str> {
+    Some(match data_type {
+        "character varying" | "character" | "text" | "uuid" | "citext" | "varchar" | "bpchar" => {
+            "text"
+        }
+        "smallint" | "integer" | "bigint" | "numeric" | "real" | "double precision" | "int2"
+        | "int4" | "int8" | "float4" | "float8" | "money" => "number",
+        "boolean" | "bool" => "checkbox",
+        "date"
+        | "timestamp without time zone"
+        | "timestamp with time zone"
+        | "timestamp"
+        | "timestamptz" => "date",
+        "json" | "jsonb" => "json",
+        _ => return None,
+    })
+}
+
+fn key(table: &str, column: &str) -> (String, String) {
+    (table.to_string(), column.to_string())
+}
+
+fn wanted(include: Option<&[String]>, table: &str) -> bool {
+    include.is_none_or(|include| include.iter().any(|t| t == table))
+}
+
+fn infer_postgres(snapshot: PgSnapshot, include: Option<&[String]>) -> DatabaseImport {
+    let mut warnings = Vec::new();
+    let mut tables: BTreeMap<&str, Vec<&PgColumn>> = BTreeMap::new();
+    for column in &snapshot.columns {
+        tables
+            .entry(column.table.as_str())
+            .or_default()
+            .push(column);
+    }
+    let mut primary = BTreeSet::new();
+    let mut unique = BTreeSet::new();
+    let mut foreign = BTreeMap::new();
+    for constraint in &snapshot.constraints {
+        let at = key(&constraint.table, &constraint.column);
+        match constraint.kind.as_str() {
+            "PRIMARY KEY" => {
+                primary.insert(at);
+            }
+            "UNIQUE" => {
+                unique.insert(at);
+            }
+            "FOREIGN KEY" => {
+                if let Some(references) = &constraint.references {
+                    foreign.insert(at, references.clone());
+                }
+            }
+            _ => {}
+        }
+    }
+    let mut indexed = BTreeSet::new();
+    for index in &snapshot.indexes {
+        if let Some((column, is_unique)) = index_column(&index.definition) {
+            if is_unique {
+                unique.insert(key(&index.table, &column));
+            } else {
+                indexed.insert(key(&index.table, &column));
+            }
+        }
+    }
+    let enums: BTreeMap<&str, &[String]> = snapshot
+        .enums
+        .iter()
+        .map(|e| (e.name.as_str(), e.values.as_deref().unwrap_or_default()))
+        .collect();
+
+    // Tables holding only two foreign keys become hasMany relationships on
+    // the first referenced table.
+    let mut join_tables = BTreeMap::new();
+    for (table, columns) in &tables {
+        let data: Vec<&&PgColumn> = columns
+            .iter()
+            .filter(|c| !is_timestamp(&c.column))
+            .filter(|c| {
+                !primary.contains(&key(table, &c.column))
+                    || foreign.contains_key(&key(table, &c.column))
+            })
+            .collect();
+        let targets: Vec<&String> = data
+            .iter()
+            .filter_map(|c| foreign.get(&key(table, &c.column)))
+            .collect();
+        if data.len() == 2 && targets.len() == 2 {
+            join_tables.insert(*table, (targets[0].clone(), targets[1].clone()));
+        }
+    }
+    let mut many: BTreeMap<String, Vec<(String, String)>> = BTreeMap::new();
+    for (table, (owner, target)) in &join_tables {
+        many.entry(owner.clone())
+            .or_default()
+            .push((table.to_string(), target.clone()));
+    }
+
+    let mut collections = Vec::new();
+    for (table, columns) in &tables {
+        if join_tables.contains_key(table) || !wanted(include, table) {
+            continue;
+        }
+        let mut timestamps = false;
+        let mut fields = Vec::new();
+        for column in columns {
+            let at = key(table, &column.column);
+            if is_timestamp(&column.column) {
+                timestamps = true;
+                continue;
+            }
+            if primary.contains(&at) && !foreign.contains_key(&at) {
+                if column.column != "id" {
+                    warnings.push(format!(
+                        "{table}.{}: primary key replaced by Payload's id; keep it as a unique field if other systems refer to it",
+                        column.column
+                    ));
+                }
+                continue;
+            }
+
+            let mut field = if let Some(target) = foreign.get(&at) {
+                let mut field = ProposedField::new(relation_base(&column.column), "relationship");
+                field.source = column.column.clone();
+                field.relation_to = Some(slug_of(target));
+                if !wanted(include, target) {
+                    field.note = Some(format!("{target} is not part of this import"));
+                }
+                field
+            } else if let Some(values) = enums.get(column.udt.as_str()) {
+                let mut field = ProposedField::new(&column.column, "select");
+                field.options = values.to_vec();
+                field
+            } else if let Some(element) = column.udt.strip_prefix('_') {
+                match pg_scalar(element) {
+                    Some(kind @ ("text" | "number")) => {
+                        let mut field = ProposedField::new(&column.column, kind);
+                        field.has_many = true;
+                        field
+                    }
+                    _ => ProposedField::new(&column.column, "json")
+                        .noted(format!("Array of {element} stored as json")),
+                }
+            } else {
+                match pg_scalar(&column.data_type).or_else(|| pg_scalar(&column.udt)) {
+                    Some("text") if is_email_name(&column.column) => {
+                        ProposedField::new(&column.column, "email")
+                    }
+                    Some("text") if column.data_type == "text" && is_long_text(&column.column) => {
+                        ProposedField::new(&column.column, "textarea")
+                    }
+                    Some(kind) => ProposedField::new(&column.column, kind),
+                    None => ProposedField::new(&column.column, "text").noted(format!(
+                        "No Payload type for {}; stored as text",
+                        column.data_type
+                    )),
+                }
+            };
+            field.required = !column.nullable && column.default.is_none();
+            field.unique = unique.contains(&at);
+            field.index = indexed.contains(&at) && !field.unique;
+            fields.push(field);
+        }
+        for (join_table, target) in many.get(*table).into_iter().flatten() {
+            let mut field = ProposedField::new(&slug_of(target), "relationship");
+            field.source = join_table.clone();
+            field.relation_to = Some(slug_of(target));
+            field.has_many = true;
+            fields.push(field);
+        }
+        collections.push(collection(table, timestamps, fields));
+    }
+    DatabaseImport {
+        database: SourceDatabase::Postgres,
+        collections,
+        warnings,
+    }
+}
+
+// MongoDB
+
+#[derive(Debug, Deserialize)]
+struct MongoCollection {
+    documents: Vec<Value>,
+    #[serde(default)]
+    indexes: Vec<MongoIndex>,
+}
+
+#[derive(Debug, Deserialize)]
+struct MongoIndex {
+    key: Map<String, Value>,
+    #[serde(default)]
+    unique: bool,
+}
+
+/// Samples `size` documents and the indexes of each collection, printed as
+/// relaxed Extended JSON.
+fn mongo_script(size: usize, include: Option<&[String]>) -> String {
+    let include = serde_json::to_string(include.unwrap_or_default()).unwrap_or_default();
+    format!(
+        "const include = {include}; const out = {{}}; for (const name of db.getCollectionNames()) {{ if (name.startsWith('system.') || (include.length && !include.includes(name))) continue; const coll = db.getCollection(name); out[name] = {{ documents: coll.aggregate([{{ $sample: {{ size: {size} }} }}]).toArray(), indexes: coll.getIndexes() }}; }} print(EJSON.stringify(out, {{ relaxed: true }}));"
+    )
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+enum Kind {
+    Bool,
+    Number,
+    String,
+    Date,
+    ObjectId,
+    Array,
+    Object,
+}
+
+impl Kind {
+    /// In `plain` JSON, dates and IDs are strings rather than `$date` and
+    /// `$oid` objects.
+    fn of(value: &Value, plain: bool) -> Option<Kind> {
+        Some(match value {
+            Value::Null => return None,
+            Value::Bool(_) => Kind::Bool,
+            Value::Number(_) => Kind::Number,
+            Value::String(value) if plain && looks_like_id(value) => Kind::ObjectId,
+            Value::String(value) if plain && looks_like_date(value) => Kind::Date,
+            Value::String(_) => Kind::String,
+            Value::Array(_) => Kind::Array,
+            Value::Object(map) if map.contains_key("$oid") => Kind::ObjectId,
+            Value::Object(map) if map.contains_key("$date") => Kind::Date,
+            Value::Object(map)
+                if [
+                    "$numberInt",
+                    "$numberLong",
+                    "$numberDouble",
+                    "$numberDecimal",
+                ]
+                .iter()
+                .any(|key| map.contains_key(*key)) =>
+            {
+                Kind::Number
+            }
+            Value::Object(_) => Kind::Object,
+        })
+    }
+
+    fn name(&self) -> &'static str {
+        match self {
+            Kind::Bool => "boolean",
+            Kind::Number => "number",
+            Kind::String => "string",
+            Kind::Date => "date",
+            Kind::ObjectId => "ObjectId",
+            Kind::Array => "array",
+            Kind::Object => "object",
+        }
+    }
+}
+
+fn string_field(source: &str, values: &[&str]) -> ProposedField {
+    if !values.is_empty()
+        && values
+            .iter()
+            .all(|value| value.contains('@') && !value.contains(char::is_whitespace))
+    {
+        return ProposedField::new(source, "email");
+    }
+    let total: usize = values.iter().map(|value| value.len()).sum();
+    if is_long_text(source)
+        || values.iter().any(|value| value.contains('\n'))
+        || (!values.is_empty() && total / values.len() > 200)
+    {
+        return ProposedField::new(source, "textarea");
+    }
+    let distinct: BTreeSet<&str> = values.iter().copied().collect();
+    if values.len() >= 10 && distinct.len() <= 10 && distinct.len() * 2 <= values.len() {
+        let mut field = ProposedField::new(source, "select").noted(format!(
+            "Options inferred from {} sampled values",
+            values.len()
+        ));
+        field.options = distinct.into_iter().map(str::to_string).collect();
+        return field;
+    }
+    ProposedField::new(source, "text")
+}
+
+/// How sampled documents are read.
+struct Sampling<'a> {
+    /// Collections relationship fields may point at
+    slugs: &'a BTreeSet<String>,
+    /// Plain JSON rather than Extended JSON: ISO date strings are dates, and
+    /// ID-looking values and `...Id` keys are relationship candidates
+    plain: bool,
+    /// Share of documents a field must appear in to be required
+    required_ratio: f64,
+}
+
+/// Fields seen across `documents`, in first-seen order.
+fn infer_documents(
+    documents: &[&Map<String, Value>],
+    sampling: &Sampling,
+    depth: usize,
+) -> Vec<ProposedField> {
+    let slugs = sampling.slugs;
+    let mut keys: Vec<&str> = Vec::new();
+    for document in documents {
+        for key in document.keys() {
+            if !keys.contains(&key.as_str()) {
+                keys.push(key);
+            }
+        }
+    }
+
+    let mut fields = Vec::new();
+    for key in keys {
+        if key == "_id" || key == "__v" || (depth == 0 && (is_timestamp(key) || (sampling.plain && key == "id"))) {
+            continue;
+        }
+        let values: Vec<&Value> = documents
+            .iter()
+            .filter_map(|document| document.get(key))
+            .filter(|value| !value.is_null())
+            .collect();
+        let kinds: BTreeSet<Kind> = values
+            .iter()
+            .filter_map(|value| Kind::of(value, sampling.plain))
+            .collect();
+        let mut field = match kinds.iter().collect::<Vec<_>>().as_slice() {
+            [] => ProposedField::new(key, "json").noted("Only null values in the sample"),
+            [Kind::ObjectId | Kind::Number | Kind::String] if sampling.plain && is_id_key(key) => {
+                candidate(relationship(key, slugs, false))
+            }
+            [Kind::ObjectId] if sampling.plain => candidate(relationship(key, slugs, false)),
+            [Kind::Bool] => ProposedField::new(key, "checkbox"),
+            [Kind::Number] => ProposedField::new(key, "number"),
+            [Kind::Date] => ProposedField::new(key, "date"),
+            [Kind::ObjectId] => relationship(key, slugs, false),
+            [Kind::String] => {
+                let strings: Vec<&str> = values.iter().filter_map(|value| value.as_str()).collect();
+                string_field(key, &strings)
+            }
+            [Kind::Object] if depth < MAX_DEPTH => {
+                let objects: Vec<&Map<String, Value>> = values
+                    .iter()
+                    .filter_map(|value| value.as_object())
+                    .collect();
+                let mut field = ProposedField::new(key, "group");
+                field.fields = infer_documents(&objects, sampling, depth + 1);
+                field
+            }
+            [Kind::Array] => array_field(key, &values, sampling, depth),
+            [Kind::Object] => ProposedField::new(key, "json").noted("Nested too deeply for groups"),
+            kinds => ProposedField::new(key, "json").noted(format!(
+                "Mixed types in the sample: {}",
+                kinds
+                    .iter()
+                    .map(|kind| kind.name())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            )),
+        };
+        let seen = values.len();
+        field.required = !documents.is_empty() && seen as f64 >= sampling.required_ratio * documents.len() as f64;
+        if field.required && seen < documents.len() && field.note.is_none() {
+            field.note = Some(format!(
+                "Required, though missing from {} of {} sampled documents",
+                documents.len() - seen,
+                documents.len()
+            ));
+        }
+        fields.push(field);
+    }
+    fields
+}
+
+fn relationship(source: &str, slugs: &BTreeSet<String>, has_many: bool) -> ProposedField {
+    let mut field = ProposedField::new(relation_base(source), "relationship");
+    field.source = source.to_string();
+    field.has_many = has_many;
+    field.relation_to = find_target(source, slugs);
+    if field.relation_to.is_none() {
+        field.note = Some("Could not tell which collection these ids belong to".to_string());
+    }
+    field
+}
+
+/// A relationship inferred from plain JSON, which holds no typed IDs.
+fn candidate(field: ProposedField) -> ProposedField {
+    if field.note.is_some() {
+        return field;
+    }
+    field.noted("Relationship candidate: the values look like document IDs")
+}
+
+fn has_many(source: &str, field_type: &str) -> ProposedField {
+    let mut field = ProposedField::new(source, field_type);
+    field.has_many = true;
+    field
+}
+
+fn array_field(
+    source: &str,
+    values: &[&Value],
+    sampling: &Sampling,
+    depth: usize,
+) -> ProposedField {
+    let slugs = sampling.slugs;
+    let items: Vec<&Value> = values
+        .iter()
+        .filter_map(|value| value.as_array())
+        .flatten()
+        .filter(|item| !item.is_null())
+        .collect();
+    let kinds: BTreeSet<Kind> = items
+        .iter()
+        .filter_map(|item| Kind::of(item, sampling.plain))
+        .collect();
+    match kinds.iter().collect::<Vec<_>>().as_slice() {
+        [] => ProposedField::new(source, "json").noted("Only empty arrays in the sample"),
+        [Kind::ObjectId | Kind::Number | Kind::String] if sampling.plain && is_id_key(source) => {
+            candidate(relationship(source, slugs, true))
+        }
+        [Kind::ObjectId] if sampling.plain => candidate(relationship(source, slugs, true)),
+        [Kind::ObjectId] => relationship(source, slugs, true),
+        [Kind::String] => has_many(source, "text"),
+        [Kind::Number] => has_many(source, "number"),
+        [Kind::Object] if depth < MAX_DEPTH => {
+            let objects: Vec<&Map<String, Value>> =
+                items.iter().filter_map(|item| item.as_object()).collect();
+            let mut field = ProposedField::new(source, "array");
+            field.fields = infer_documents(&objects, sampling, depth + 1);
+            field
+        }
+        _ => ProposedField::new(source, "json").noted("Array items of mixed or nested types"),
+    }
+}
+
+fn infer_mongo(
+    snapshot: BTreeMap<String, MongoCollection>,
+    include: Option<&[String]>,
+) -> DatabaseImport {
+    let mut warnings = Vec::new();
+    let slugs: BTreeSet<String> = snapshot.keys().map(|name| slug_of(name)).collect();
+    let mut collections = Vec::new();
+    for (name, sampled) in &snapshot {
+        if include.is_some_and(|include| !include.contains(name)) {
+            continue;
+        }
+        let documents: Vec<&Map<String, Value>> = sampled
+            .documents
+            .iter()
+            .filter_map(|document| document.as_object())
+            .collect();
+        if documents.is_empty() {
+            warnings.push(format!(
+                "{name}: no documents sampled; add its fields by hand"
+            ));
+        }
+        let timestamps = documents
+            .iter()
+            .any(|document| document.keys().any(|key| is_timestamp(key)));
+        let sampling = Sampling {
+            slugs: &slugs,
+            plain: false,
+            required_ratio: 1.0,
+        };
+        let mut fields = infer_documents(&documents, &sampling, 0);
+        for index in &sampled.indexes {
+            let mut keys = index.key.keys();
+            let (Some(key), None) = (keys.next(), keys.next()) else {
+                continue;
+            };
+            if let Some(field) = fields.iter_mut().find(|field| &field.source == key) {
+                if index.unique {
+                    field.unique = true;
+                } else {
+                    field.index = true;
+                }
+            }
+        }
+        collections.push(collection(name, timestamps, fields));
+    }
+    DatabaseImport {
+        database: SourceDatabase::Mongodb,
+        collections,
+        warnings,
+    }
+}
+
+// Sample documents
+
+#[derive(Debug, Clone, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct SampleInferenceOptions {
+    /// Slug of the proposed collection
+    pub slug: String,
+    /// Sample documents as plain JSON, e.g. exported from another CMS
+    pub documents: Vec<Value>,
+    /// Share of documents a field must appear in to be required (default 1,
+    /// every document)
+    pub required_ratio: Option<f64>,
+    /// Existing collections that ID-looking fields may point at
+    pub collections: Option<Vec<String>>,
+}
+
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct SampleInference {
+    pub collection: ProposedCollection,
+    /// Documents the definition was inferred from
+    pub documents: usize,
+    pub warnings: Vec<String>,
+}
+
+/// A collection proposed for documents like `options.documents`. Strings
+/// holding ISO dates become dates, and keys like `authorId` or values like
+/// ObjectIds and UUIDs become relationship candidates.
+pub fn infer_from_samples(options: &SampleInferenceOptions) -> Result<SampleInference, String> {
+    let required_ratio = options.required_ratio.unwrap_or(1.0);
+    if !(required_ratio > 0.0 && required_ratio <= 1.0) {
+        return Err("requiredRatio must be above 0 and at most 1".to_string());
+    }
+    let documents: Vec<&Map<String, Value>> = options
+        .documents
+        .iter()
+        .filter_map(|document| document.as_object())
+        .collect();
+    if documents.is_empty() {
+        return Err("documents must hold at least one object".to_string());
+    }
+    let mut warnings = Vec::new();
+    let skipped = options.documents.len() - documents.len();
+    if skipped > 0 {
+        warnings.push(format!("{skipped} samples are not objects and were skipped"));
+    }
+    let slug = slug_of(&options.slug);
+    let mut slugs: BTreeSet<String> = options
+        .collections
+        .iter()
+        .flatten()
+        .map(|name| slug_of(name))
+        .collect();
+    slugs.insert(slug.clone());
+    let sampling = Sampling {
+        slugs: &slugs,
+        plain: true,
+        required_ratio,
+    };
+    let fields = infer_documents(&documents, &sampling, 0);
+    let timestamps = documents
+        .iter()
+        .any(|document| document.keys().any(|key| is_timestamp(key)));
+    if documents.len() < 10 {
+        warnings.push(format!(
+            "Only {} samples; required flags and select options are guesses",
+            documents.len()
+        ));
+    }
+    Ok(SampleInference {
+        collection: collection(&slug, timestamps, fields),
+        documents: documents.len(),
+        warnings,
+    })
+}
+
+// Rendering
+
+fn collection(source: &str, timestamps: bool, fields: Vec<ProposedField>) -> ProposedCollection {
+    let slug = slug_of(source);
+    let mut proposed = ProposedCollection {
+        slug,
+        source: source.to_string(),
+        timestamps,
+        fields,
+        code: String::new(),
+    };
+    proposed.code = render_collection(&proposed);
+    proposed
+}
+
+fn render_field(field: &ProposedField, indent: &str) -> String {
+    let inner = format!("{indent}  ");
+    let mut lines = vec![
+        format!("{inner}name: '{}',", field.name),
+        format!("{inner}type: '{}',", field.field_type),
+    ];
+    if let Some(note) = &field.note {
+        lines.insert(0, format!("{inner}// {note}"));
+    }
+    if field.field_type == "relationship" {
+        match &field.relation_to {
+            Some(target) => lines.push(format!("{inner}relationTo: '{target}',")),
+            None => lines.push(format!(
+                "{inner}relationTo: '', // TODO: set the related collection"
+            )),
+        }
+    }
+    if field.has_many {
+        lines.push(format!("{inner}hasMany: true,"));
+    }
+    if !field.options.is_empty() {
+        let options: Vec<Value> = field.options.iter().cloned().map(Value::String).collect();
+        lines.push(format!(
+            "{inner}options: {},",
+            to_literal(&Value::Array(options))
+        ));
+    }
+    for (set, option) in [
+        (field.required, "required"),
+        (field.unique, "unique"),
+        (field.index, "index"),
+    ] {
+        if set {
+            lines.push(format!("{inner}{option}: true,"));
+        }
+    }
+    if !field.fields.is_empty() {
+        lines.push(format!("{inner}fields: ["));
+        lines.extend(
+            field
+                .fields
+                .iter()
+                .map(|sub| render_field(sub, &format!("{inner}  "))),
+        );
+        lines.push(format!("{inner}],"));
+    }
+    format!("{indent}{{\n{}\n{indent}}},", lines.join("\n"))
+}
+
+fn render_collection(collection: &ProposedCollection) -> String {
+    let name = Case::Pascal.convert(&collection.slug);
+    let fields = collection
+        .fields
+        .iter()
+        .map(|field| render_field(field, "    "))
+        .collect::<Vec<_>>()
+        .join("\n");
+    format!(
+        "import {{ CollectionConfig }} from 'payload/types';\n\nconst {name}: CollectionConfig = {{\n  slug: '{}',\n  timestamps: {},\n  fields: [\n{fields}\n  ],\n}};\n\nexport default {name};\n",
+        collection.slug, collection.timestamps
+    )
+}
+
+// Introspection
+
+fn run(program: &str, args: &[&str]) -> Result<String, String> {
+    let output = Command::new(program)
+        .args(args)
+        .output()
+        .map_err(|err| match err.kind() {
+            ErrorKind::NotFound => {
+                format!("{program} is not installed; install it or pass `snapshot`")
+            }
+            _ => format!("Could not run {program}: {err}"),
+        })?;
+    if !output.status.success() {
+        return Err(format!(
+            "{program} failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+/// URL schemes a `connectionString` may have. Anything else is refused, so a
+/// value starting with `-` is never read as a client option.
+fn schemes(database: SourceDatabase) -> &'static [&'static str] {
+    match database {
+        SourceDatabase::Postgres => &["postgres://", "postgresql://"],
+        SourceDatabase::Mongodb => &["mongodb://", "mongodb+srv://"],
+    }
+}
+
+fn check_connection_string(
+    database: SourceDatabase,
+    connection_string: &str,
+) -> Result<(), String> {
+    let schemes = schemes(database);
+    let lower = connection_string.to_ascii_lowercase();
+    if schemes.iter().any(|scheme| lower.starts_with(scheme)) {
+        Ok(())
+    } else {
+        Err(format!(
+            "connectionString must be a {} URL",
+            schemes
+                .iter()
+                .map(|scheme| format!("`{scheme}`"))
+                .collect::<Vec<_>>()
+                .join(" or ")
+        ))
+    }
+}
+
+fn introspect(options: &DatabaseImportOptions) -> Result<Value, String> {
+    let connection_string = options
+        .connection_string
+        .as_deref()
+        .ok_or("connectionString or snapshot is required")?;
+    check_connection_string(options.database, connection_string)?;
+    let output = match options.database {
+        SourceDatabase::Postgres => {
+            let schema = options.schema.as_deref().unwrap_or("public");
+            if schema.is_empty()
+                || !schema
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || c == '_')
+            {
+                return Err(format!("Invalid schema name '{schema}'"));
+            }
+            run(
+                "psql",
+                &[
+                    format!("--dbname={connection_string}").as_str(),
+                    "-X",
+                    "-A",
+                    "-t",
+                    "-q",
+                    "-v",
+                    "ON_ERROR_STOP=1",
+                    "-c",
+                    postgres_query(schema).as_str(),
+                ],
+            )?
+        }
+        SourceDatabase::Mongodb => {
+            let size = options.sample_size.unwrap_or(DEFAULT_SAMPLE_SIZE).max(1);
+            run(
+                "mongosh",
+                &[
+                    "--quiet",
+                    "--norc",
+                    "--eval",
+                    mongo_script(size, options.include.as_deref()).as_str(),
+                    "--",
+                    connection_string,
+                ],
+            )?
+        }
+    };
+    serde_json::from_str(&output).map_err(|err| format!("Unexpected introspection output: {err}"))
+}
+
+/// Collections proposed for the database described by `options`.
+pub fn import_from_database(options: &DatabaseImportOptions) -> Result<DatabaseImport, String> {
+    let snapshot = match &options.snapshot {
+        Some(snapshot) => snapshot.clone(),
+        None => introspect(options)?,
+    };
+    let include = options.include.as_deref();
+    match options.database {
+        SourceDatabase::Postgres => serde_json::from_value(snapshot)
+            .map(|snapshot| infer_postgres(snapshot, include))
+            .map_err(|err| format!("Invalid Postgres snapshot: {err}")),
+        SourceDatabase::Mongodb => serde_json::from_value(snapshot)
+            .map(|snapshot| infer_mongo(snapshot, include))
+            .map_err(|err| format!("Invalid MongoDB snapshot: {err}")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn options(database: SourceDatabase, snapshot: Value) -> DatabaseImportOptions {
+        DatabaseImportOptions {
+            database,
+            connection_string: None,
+            snapshot: Some(snapshot),
+            schema: None,
+            sample_size: None,
+            include: None,
+        }
+    }
+
+    #[test]
+    fn test_import_postgres_relationships_and_indexes() {
+        let column = |table: &str, column: &str, data_type: &str, nullable: bool| json!({ "table": table, "column": column, "type": data_type, "udt": "", "nullable": nullable, "default": null });
+        let snapshot = json!({
+            "columns": [
+                column("authors", "id", "integer", false),
+                column("authors", "email", "character varying", false),
+                column("blog_posts", "id", "integer", false),
+                column("blog_posts", "author_id", "integer", true),
+                column("blog_posts", "body", "text", true),
+                column("blog_posts", "created_at", "timestamp with time zone", false),
+                column("post_tags", "post_id", "integer", false),
+                column("post_tags", "tag_id", "integer", false),
+                column("tags", "id", "integer", false),
+                column("tags", "name", "text", false),
+            ],
+            "constraints": [
+                { "table": "authors", "column": "id", "kind": "PRIMARY KEY", "references": null },
+                { "table": "blog_posts", "column": "id", "kind": "PRIMARY KEY", "references": null },
+                { "table": "tags", "column": "id", "kind": "PRIMARY KEY", "references": null },
+                { "table": "blog_posts", "column": "author_id", "kind": "FOREIGN KEY", "references": "authors" },
+                { "table": "post_tags", "column": "post_id", "kind": "FOREIGN KEY", "references": "blog_posts" },
+                { "table": "post_tags", "column": "tag_id", "kind": "FOREIGN KEY", "references": "tags" },
+            ],
+            "indexes": [
+                { "table": "authors", "definition": "CREATE UNIQUE INDEX authors_email_key ON public.authors USING btree (email)" },
+            ],
+        });
+        let import = import_from_database(&options(SourceDatabase::Postgres, snapshot)).unwrap();
+        let slugs: Vec<&str> = import.collections.iter().map(|c| c.slug.as_str()).collect();
+        assert_eq!(slugs, vec!["authors", "blog-posts", "tags"]);
+
+        let email = &import.collections[0].fields[0];
+        assert_eq!((email.field_type.as_str(), email.unique), ("email", true));
+        let posts = &import.collections[1];
+        assert!(posts.timestamps);
+        let author = &posts.fields[0];
+        assert_eq!(author.name, "author");
+        assert_eq!(author.relation_to.as_deref(), Some("authors"));
+        assert_eq!(posts.fields[1].field_type, "textarea");
+        let tags = &posts.fields[2];
+        assert!(tags.has_many && tags.relation_to.as_deref() == Some("tags"));
+        assert!(posts.code.contains("relationTo: 'authors',"));
+    }
+
+    #[test]
+    fn test_infer_from_plain_samples() {
+        let documents: Vec<Value> = (0..10)
+            .map(|n| {
+                let category = ["news", "guides"][n % 2];
+                let mut doc = json!({
+                    "id": n,
+                    "title": format!("Post {n}"),
+                    "authorId": n % 3,
+                    "category": category,
+                    "publishedAt": "2024-05-01T10:00:00Z",
+                    "related": ["64b7f0c2a1b2c3d4e5f60718"],
+                });
+                if n > 0 {
+                    doc["subtitle"] = json!("More");
+                }
+                doc
+            })
+            .collect();
+        let inference = infer_from_samples(&SampleInferenceOptions {
+            slug: "posts".to_string(),
+            documents,
+            required_ratio: Some(0.9),
+            collections: Some(vec!["authors".to_string()]),
+        })
+        .unwrap();
+        let fields: Vec<(&str, &str, bool)> = inference
+            .collection
+            .fields
+            .iter()
+            .map(|f| (f.name.as_str(), f.field_type.as_str(), f.required))
+            .collect();
+        assert_eq!(
+            fields,
+            vec![
+                ("author", "relationship", true),
+                ("category", "select", true),
+                ("publishedAt", "date", true),
+                ("related", "relationship", true),
+                ("title", "text", true),
+                ("subtitle", "text", true),
+            ]
+        );
+        let field = |name: &str| inference.collection.fields.iter().find(|f| f.name == name).unwrap();
+        assert_eq!(field("author").relation_to.as_deref(), Some("authors"));
+        assert!(field("related").has_many && field("related").relation_to.is_none());
+        assert_eq!(field("category").options, vec!["guides", "news"]);
+        assert!(field("subtitle").note.as_deref().unwrap().contains("missing from 1 of 10"));
+        assert!(inference.warnings.is_empty());
+    }
+
+    #[test]
+    fn test_import_mongo_samples() {
+        let snapshot = json!({
+            "authors": { "documents": [{ "_id": { "$oid": "1" }, "name": "Ada" }], "indexes": [] },
+            "posts": {
+                "documents": [
+                    { "_id": { "$oid": "a" }, "title": "One", "authorId": { "$oid": "1" }, "tags": ["x"], "meta": { "views": 1 }, "createdAt": { "$date": "2024-01-01T00:00:00Z" } },
+                    { "_id": { "$oid": "b" }, "title": "Two", "authorId": { "$oid": "1" }, "tags": [], "meta": { "views": 2.5 }, "rating": "high" },
+                ],
+                "indexes": [{ "key": { "_id": 1 } }, { "key": { "title": 1 }, "unique": true }],
+            },
+        });
+        let import = import_from_database(&options(SourceDatabase::Mongodb, snapshot)).unwrap();
+        let posts = &import.collections[1];
+        assert_eq!(posts.slug, "posts");
+        assert!(posts.timestamps);
+        let types: Vec<(&str, &str, bool)> = posts
+            .fields
+            .iter()
+            .map(|f| (f.name.as_str(), f.field_type.as_str(), f.required))
+            .collect();
+        // Fields follow key order, which serde_json keeps sorted.
+        assert_eq!(
+            types,
+            vec![
+                ("author", "relationship", true),
+                ("meta", "group", true),
+                ("tags", "text", true),
+                ("title", "text", true),
+                ("rating", "text", false),
+            ]
+        );
+        assert!(posts.fields[3].unique);
+        assert_eq!(posts.fields[0].relation_to.as_deref(), Some("authors"));
+    }
+
+    #[test]
+    fn test_connection_string_needs_a_database_url() {
+        let connect = |database: SourceDatabase, connection_string: &str| DatabaseImportOptions {
+            connection_string: Some(connection_string.to_string()),
+            snapshot: None,
+            ..options(database, Value::Null)
+        };
+        let err = import_from_database(&connect(SourceDatabase::Postgres, "-c\\! id")).unwrap_err();
+        assert_eq!(err, "connectionString must be a `postgres://` or `postgresql://` URL");
+        let err =
+            import_from_database(&connect(SourceDatabase::Mongodb, "postgres://db")).unwrap_err();
+        assert!(err.starts_with("connectionString must be a `mongodb://`"));
+
+        assert!(check_connection_string(SourceDatabase::Postgres, "PostgreSQL://u@db/app").is_ok());
+        assert!(
+            check_connection_string(SourceDatabase::Mongodb, "mongodb+srv://cluster/app").is_ok()
+        );
+    }
+}
@@ -0,0 +1,405 @@
+//! Version checks of a Payload project's `package.json` against a bundled
+//! compatibility matrix.
+//!
+//! Payload 3 releases every `@payloadcms/*` package in lockstep with
+//! `payload`, builds with Next.js and has no bundler packages. Payload 2
+//! packages carry their own majors, need a bundler and predate the Payload 3
+//! only adapters. Versions are read from the ranges as written; an install
+//! may resolve them higher.
+
+use std::collections::BTreeMap;
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// An official package: its major for Payload 2, whether Payload 3 ships it,
+/// and what replaces it there.
+struct Compat {
+    package: &'static str,
+    v2_major: Option<u64>,
+    v3: bool,
+    v3_replacement: Option<&'static str>,
+}
+
+const fn compat(package: &'static str, v2_major: Option<u64>, v3: bool) -> Compat {
+    Compat {
+        package,
+        v2_major,
+        v3,
+        v3_replacement: None,
+    }
+}
+
+const MATRIX: &[Compat] = &[
+    compat("@payloadcms/db-mongodb", Some(1), true),
+    compat("@payloadcms/db-postgres", Some(0), true),
+    compat("@payloadcms/db-sqlite", None, true),
+    compat("@payloadcms/db-vercel-postgres", None, true),
+    compat("@payloadcms/bundler-webpack", Some(1), false),
+    compat("@payloadcms/bundler-vite", Some(0), false),
+    compat("@payloadcms/richtext-slate", Some(1), true),
+    compat("@payloadcms/richtext-lexical", Some(0), true),
+    compat("@payloadcms/next", None, true),
+    compat("@payloadcms/ui", None, true),
+    compat("@payloadcms/translations", None, true),
+    compat("@payloadcms/email-nodemailer", None, true),
+    compat("@payloadcms/email-resend", None, true),
+    compat("@payloadcms/plugin-seo", Some(2), true),
+    compat("@payloadcms/plugin-form-builder", Some(1), true),
+    compat("@payloadcms/plugin-nested-docs", Some(1), true),
+    compat("@payloadcms/plugin-redirects", Some(1), true),
+    compat("@payloadcms/plugin-search", Some(1), true),
+    compat("@payloadcms/plugin-stripe", Some(0), true),
+    compat("@payloadcms/plugin-sentry", Some(0), true),
+    compat("@payloadcms/plugin-multi-tenant", None, true),
+    Compat {
+        v3_replacement: Some(
+            "@payloadcms/storage-s3 (or storage-gcs, storage-azure, storage-vercel-blob)",
+        ),
+        ..compat("@payloadcms/plugin-cloud-storage", Some(1), true)
+    },
+    Compat {
+        v3_replacement: Some("@payloadcms/payload-cloud"),
+        ..compat("@payloadcms/plugin-cloud", Some(3), false)
+    },
+    compat("@payloadcms/payload-cloud", None, true),
+    compat("@payloadcms/storage-s3", None, true),
+    compat("@payloadcms/storage-gcs", None, true),
+    compat("@payloadcms/storage-azure", None, true),
+    compat("@payloadcms/storage-vercel-blob", None, true),
+    compat("@payloadcms/storage-uploadthing", None, true),
+];
+
+/// Framework packages Payload 3 needs at least this major of.
+const V3_FRAMEWORK: [(&str, u64); 3] = [("next", 15), ("react", 19), ("react-dom", 19)];
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum IssueLevel {
+    /// The combination doesn't work
+    Error,
+    /// Works, but should change
+    Warning,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct DependencyIssue {
+    pub package: String,
+    /// Range in `package.json`; absent for a missing package
+    pub installed: Option<String>,
+    pub level: IssueLevel,
+    pub message: String,
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct DependencyReport {
+    /// `payload` range in `package.json`
+    pub payload: Option<String>,
+    pub payload_major: Option<u64>,
+    /// Official packages and their ranges
+    pub packages: BTreeMap<String, String>,
+    pub issues: Vec<DependencyIssue>,
+    /// Commands that fix the issues, for the detected package manager
+    pub commands: Vec<String>,
+}
+
+/// Major, minor and patch of a range such as `^3.2.1`, `~2.0`, `>=15` or
+/// `3.0.0-beta.1`. `None` for tags, URLs and workspace links.
+fn parse_version(range: &str) -> Option<(u64, u64, u64)> {
+    let version = range
+        .trim()
+        .trim_start_matches(['^', '~', '>', '=', 'v', ' '])
+        .split([' ', '-', '+', '|'])
+        .next()?;
+    let mut parts = version.split('.').map(|part| part.parse::<u64>());
+    let major = parts.next()?.ok()?;
+    let minor = parts.next().and_then(Result::ok).unwrap_or_default();
+    let patch = parts.next().and_then(Result::ok).unwrap_or_default();
+    Some((major, minor, patch))
+}
+
+/// `dependencies` and `devDependencies`, the former winning.
+fn all_dependencies(package_json: &Value) -> BTreeMap<String, String> {
+    let mut out = BTreeMap::new();
+    for section in ["devDependencies", "dependencies"] {
+        if let Some(deps) = package_json[section].as_object() {
+            out.extend(
+                deps.iter()
+                    .filter_map(|(name, range)| Some((name.clone(), range.as_str()?.to_string()))),
+            );
+        }
+    }
+    out
+}
+
+/// `pnpm`, `yarn`, `npm` or `bun`, from the `packageManager` field; pnpm
+/// otherwise.
+fn package_manager(package_json: &Value) -> &'static str {
+    let field = package_json["packageManager"].as_str().unwrap_or_default();
+    ["yarn", "npm", "bun"]
+        .into_iter()
+        .find(|manager| field.starts_with(&format!("{manager}@")))
+        .unwrap_or("pnpm")
+}
+
+fn add_command(manager: &str, specs: &[String]) -> String {
+    let verb = if manager == "npm" { "install" } else { "add" };
+    format!("{manager} {verb} {}", specs.join(" "))
+}
+
+fn remove_command(manager: &str, packages: &[String]) -> String {
+    let verb = if manager == "npm" {
+        "uninstall"
+    } else {
+        "remove"
+    };
+    format!("{manager} {verb} {}", packages.join(" "))
+}
+
+/// Check the dependencies of `package_json` (the file's contents).
+pub fn check_dependencies(package_json: &str) -> Result<DependencyReport, String> {
+    let package_json: Value =
+        serde_json::from_str(package_json).map_err(|err| format!("Invalid package.json: {err}"))?;
+    let deps = all_dependencies(&package_json);
+    let manager = package_manager(&package_json);
+    let mut report = DependencyReport {
+        payload: deps.get("payload").cloned(),
+        packages: deps
+            .iter()
+            .filter(|(name, _)| name.as_str() == "payload" || name.starts_with("@payloadcms/"))
+            .map(|(name, range)| (name.clone(), range.clone()))
+            .collect(),
+        ..Default::default()
+    };
+    let Some(payload) = report.payload.clone() else {
+        return Err("package.json does not depend on payload".to_string());
+    };
+    let Some((major, minor, patch)) = parse_version(&payload) else {
+        report.issues.push(DependencyIssue {
+            package: "payload".to_string(),
+            installed: Some(payload),
+            level: IssueLevel::Warning,
+            message: "Could not read a version from this range; pin a semver range to check compatibility"
+                .to_string(),
+        });
+        return Ok(report);
+    };
+    report.payload_major = Some(major);
+
+    let mut issue = |package: &str, level: IssueLevel, message: String| {
+        report.issues.push(DependencyIssue {
+            package: package.to_string(),
+            installed: deps.get(package).cloned(),
+            level,
+            message,
+        });
+    };
+    let (mut upgrades, mut removals) = (Vec::new(), Vec::new());
+    match major {
+        3 => {
+            let target = format!("{major}.{minor}.{patch}");
+            for (package, range) in deps
+                .iter()
+                .filter(|(name, _)| name.starts_with("@payloadcms/"))
+            {
+                let known = MATRIX.iter().find(|compat| compat.package == package);
+                if let Some(compat) = known.filter(|compat| !compat.v3) {
+                    let message = match compat.v3_replacement {
+                        Some(replacement) => {
+                            format!("Not used with Payload 3; replace it with {replacement}")
+                        }
+                        None => "Not used with Payload 3, which builds with Next.js; remove it"
+                            .to_string(),
+                    };
+                    issue(package, IssueLevel::Error, message);
+                    removals.push(package.clone());
+                    continue;
+                }
+                if let Some(replacement) = known.and_then(|compat| compat.v3_replacement) {
+                    issue(
+                        package,
+                        IssueLevel::Warning,
+                        format!("Deprecated in Payload 3; use {replacement}"),
+                    );
+                }
+                match parse_version(range) {
+                    Some(version) if version == (major, minor, patch) => {}
+                    Some((package_major, ..)) => {
+                        let level = if package_major == major {
+                            IssueLevel::Warning
+                        } else {
+                            IssueLevel::Error
+                        };
+                        issue(
+                            package,
+                            level,
+                            format!(
+                                "Payload 3 packages are released together; use {target} to match payload"
+                            ),
+                        );
+                        upgrades.push(format!("{package}@{target}"));
+                    }
+                    None => {}
+                }
+            }
+            if !deps.contains_key("@payloadcms/next") {
+                issue(
+                    "@payloadcms/next",
+                    IssueLevel::Error,
+                    "Payload 3 needs @payloadcms/next for the admin panel and API routes"
+                        .to_string(),
+                );
+                upgrades.push(format!("@payloadcms/next@{target}"));
+            }
+            for (package, minimum) in V3_FRAMEWORK {
+                match deps.get(package).and_then(|range| parse_version(range)) {
+                    Some((found, ..)) if found >= minimum => {}
+                    _ => {
+                        issue(
+                            package,
+                            IssueLevel::Error,
+                            format!("Payload 3 needs {package} {minimum} or later"),
+                        );
+                        upgrades.push(format!("{package}@^{minimum}"));
+                    }
+                }
+            }
+        }
+        2 => {
+            for (package, range) in deps
+                .iter()
+                .filter(|(name, _)| name.starts_with("@payloadcms/"))
+            {
+                let Some(compat) = MATRIX.iter().find(|compat| compat.package == package) else {
+                    continue;
+                };
+                let Some(v2_major) = compat.v2_major else {
+                    issue(
+                        package,
+                        IssueLevel::Error,
+                        "Only released for Payload 3; remove it or upgrade payload".to_string(),
+                    );
+                    removals.push(package.clone());
+                    continue;
+                };
+                if let Some((found, ..)) = parse_version(range) {
+                    if found != v2_major {
+                        issue(
+                            package,
+                            IssueLevel::Error,
+                            format!(
+                                "Payload 2 needs version {v2_major}.x; {found}.x is for another Payload major"
+                            ),
+                        );
+                        upgrades.push(format!("{package}@^{v2_major}"));
+                    }
+                }
+            }
+            if !deps.contains_key("@payloadcms/bundler-webpack")
+                && !deps.contains_key("@payloadcms/bundler-vite")
+            {
+                issue(
+                    "@payloadcms/bundler-webpack",
+                    IssueLevel::Error,
+                    "Payload 2 needs a bundler for the admin panel".to_string(),
+                );
+                upgrades.push("@payloadcms/bundler-webpack@^1".to_string());
+            }
+            if !deps
+                .keys()
+                .any(|name| name.starts_with("@payloadcms/db-") && !removals.contains(name))
+            {
+                issue(
+                    "@payloadcms/db-mongodb",
+                    IssueLevel::Error,
+                    "Payload 2 needs a database adapter".to_string(),
+                );
+                upgrades.push("@payloadcms/db-mongodb@^1".to_string());
+            }
+            issue(
+                "payload",
+                IssueLevel::Warning,
+                "Payload 2 is in maintenance; see the Payload 3 migration guide when planning an upgrade".to_string(),
+            );
+        }
+        _ => issue(
+            "payload",
+            IssueLevel::Warning,
+            format!(
+                "Payload {major} is not in the compatibility matrix; only Payload 2 and 3 are checked"
+            ),
+        ),
+    }
+
+    if !removals.is_empty() {
+        report.commands.push(remove_command(manager, &removals));
+    }
+    if !upgrades.is_empty() {
+        report.commands.push(add_command(manager, &upgrades));
+    }
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check_dependencies_v3_lockstep() {
+        let package_json = r#"{
+            "packageManager": "pnpm@9.0.0",
+            "dependencies": {
+                "payload": "^3.2.0",
+                "@payloadcms/next": "^3.2.0",
+                "@payloadcms/db-mongodb": "^3.1.0",
+                "@payloadcms/richtext-lexical": "^0.11.0",
+                "@payloadcms/bundler-webpack": "^1.0.0",
+                "next": "15.0.3",
+                "react": "^18.2.0",
+                "react-dom": "^19.0.0"
+            }
+        }"#;
+        let report = check_dependencies(package_json).unwrap();
+        assert_eq!(report.payload_major, Some(3));
+        let issues: Vec<(&str, IssueLevel)> = report
+            .issues
+            .iter()
+            .map(|issue| (issue.package.as_str(), issue.level))
+            .collect();
+        assert_eq!(
+            issues,
+            vec![
+                ("@payloadcms/bundler-webpack", IssueLevel::Error),
+                ("@payloadcms/db-mongodb", IssueLevel::Warning),
+                ("@payloadcms/richtext-lexical", IssueLevel::Error),
+                ("react", IssueLevel::Error),
+            ]
+        );
+        assert_eq!(
+            report.commands,
+            vec![
+                "pnpm remove @payloadcms/bundler-webpack",
+                "pnpm add @payloadcms/db-mongodb@3.2.0 @payloadcms/richtext-lexical@3.2.0 react@^19",
+            ]
+        );
+
+        let v2 =
+            r#"{ "dependencies": { "payload": "^2.0.0", "@payloadcms/db-sqlite": "^3.0.0" } }"#;
+        let report = check_dependencies(v2).unwrap();
+        assert!(
+            report
+                .issues
+                .iter()
+                .any(|issue| issue.package == "@payloadcms/db-sqlite"
+                    && issue.level == IssueLevel::Error)
+        );
+        assert!(
+            report
+                .commands
+                .contains(&"pnpm remove @payloadcms/db-sqlite".to_string())
+        );
+    }
+}
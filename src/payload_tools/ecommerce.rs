@@ -0,0 +1,643 @@
+//! A Stripe store for a Payload 3 app: products, customers and orders
+//! collections, a hook syncing products and their prices to Stripe, a
+//! checkout endpoint creating Stripe Checkout sessions, and webhook handlers
+//! updating orders, routed by the Stripe plugin or a generated endpoint.
+
+use std::collections::BTreeMap;
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+use crate::payload_tools::{
+    email::server_url_env,
+    plugins::{PayloadVersion, plugin_config},
+};
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct EcommerceOptions {
+    /// Three-letter ISO currency code prices are in (default `usd`)
+    pub currency: Option<String>,
+    /// Route webhooks through the Stripe plugin (default true) rather than a
+    /// generated endpoint
+    pub plugin: Option<bool>,
+    /// Frontend path Stripe returns to after payment (default
+    /// `/checkout/success`)
+    pub success_path: Option<String>,
+    /// Frontend path Stripe returns to when checkout is abandoned (default
+    /// `/cart`)
+    pub cancel_path: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct EcommerceConfig {
+    pub imports: Vec<String>,
+    /// Entry for the config's `plugins` array; set unless `plugin` is false
+    pub plugin: Option<String>,
+    /// Entries for the config's `collections` array
+    pub collections: Vec<String>,
+    /// Entries for the config's `endpoints` array
+    pub endpoints: Vec<String>,
+    /// Collections, hooks, endpoints and the Stripe client by path from the
+    /// project root
+    pub files: BTreeMap<String, String>,
+    /// npm packages and version ranges to add to `package.json`
+    pub dependencies: BTreeMap<String, String>,
+    /// Environment variables read by the generated code
+    pub env: Vec<String>,
+}
+
+const STRIPE_PACKAGE: (&str, &str) = ("stripe", "^17.0.0");
+
+const STRIPE_ENV: [&str; 2] = ["STRIPE_SECRET_KEY", "STRIPE_WEBHOOKS_ENDPOINT_SECRET"];
+
+fn check_path(name: &str, path: &str) -> Result<(), String> {
+    if path.starts_with('/') && !path.contains(['?', '#', '\'', '`', ' ']) {
+        Ok(())
+    } else {
+        Err(format!("{name} must be a path starting with /, got '{path}'"))
+    }
+}
+
+fn stripe_client(currency: &str) -> String {
+    format!(
+        "import Stripe from 'stripe';
+
+/** Server-side Stripe client; never import it into client components. */
+export const stripe = new Stripe(process.env.STRIPE_SECRET_KEY ?? '');
+
+export const CURRENCY = '{currency}';
+"
+    )
+}
+
+const ACCESS: &str = "import type { Access } from 'payload';
+
+/** Admins are users with the admin role; customers never are. */
+export const isAdmin = (user: unknown): boolean => {
+  const { collection, role } = (user ?? {}) as { collection?: string; role?: string };
+  return collection !== 'customers' && role === 'admin';
+};
+
+export const admins: Access = ({ req: { user } }) => isAdmin(user);
+
+/** Admins see everything, customers only what `field` ties to them. */
+export const adminsOrCustomer =
+  (field: string): Access =>
+  ({ req: { user } }) => {
+    if (isAdmin(user)) return true;
+    if (user?.collection !== 'customers') return false;
+    return { [field]: { equals: user.id } };
+  };
+";
+
+const PRODUCTS: &str = "import type { CollectionConfig } from 'payload';
+
+import { admins, isAdmin } from '../access/store';
+import { syncProductToStripe } from '../stripe/syncProduct';
+
+/** Products for sale; saving one syncs it and its price to Stripe. */
+const Products: CollectionConfig = {
+  slug: 'products',
+  admin: {
+    useAsTitle: 'name',
+    defaultColumns: ['name', 'priceInCents', 'active'],
+  },
+  access: {
+    read: ({ req: { user } }) => isAdmin(user) || { active: { equals: true } },
+    create: admins,
+    update: admins,
+    delete: admins,
+  },
+  hooks: {
+    afterChange: [syncProductToStripe],
+  },
+  fields: [
+    { name: 'name', type: 'text', required: true },
+    { name: 'description', type: 'textarea' },
+    {
+      name: 'priceInCents',
+      type: 'number',
+      required: true,
+      min: 0,
+      admin: { description: 'Price in the smallest currency unit, e.g. 1999 for 19.99' },
+    },
+    {
+      name: 'active',
+      type: 'checkbox',
+      defaultValue: true,
+      admin: { position: 'sidebar', description: 'Inactive products are hidden and cannot be bought' },
+    },
+    {
+      name: 'stripeProductID',
+      type: 'text',
+      index: true,
+      access: { create: () => false, update: () => false },
+      admin: { position: 'sidebar', readOnly: true },
+    },
+    {
+      name: 'stripePriceID',
+      type: 'text',
+      access: { create: () => false, update: () => false },
+      admin: { position: 'sidebar', readOnly: true },
+    },
+  ],
+};
+
+export default Products;
+";
+
+const CUSTOMERS: &str = "import type { CollectionConfig } from 'payload';
+
+import { admins, adminsOrCustomer } from '../access/store';
+
+/** Shoppers who log in to check out; their Stripe customer is created on first checkout. */
+const Customers: CollectionConfig = {
+  slug: 'customers',
+  auth: true,
+  admin: {
+    useAsTitle: 'email',
+    defaultColumns: ['email', 'name', 'stripeCustomerID'],
+  },
+  access: {
+    read: adminsOrCustomer('id'),
+    create: () => true,
+    update: adminsOrCustomer('id'),
+    delete: admins,
+  },
+  fields: [
+    { name: 'name', type: 'text' },
+    {
+      name: 'stripeCustomerID',
+      type: 'text',
+      index: true,
+      access: { create: () => false, update: () => false },
+      admin: { position: 'sidebar', readOnly: true },
+    },
+  ],
+};
+
+export default Customers;
+";
+
+const ORDERS: &str = "import type { CollectionConfig } from 'payload';
+
+import { admins, adminsOrCustomer } from '../access/store';
+
+/** Orders created by the checkout endpoint and moved on by Stripe webhooks. */
+const Orders: CollectionConfig = {
+  slug: 'orders',
+  admin: {
+    useAsTitle: 'stripeCheckoutSessionID',
+    defaultColumns: ['customer', 'status', 'totalInCents', 'createdAt'],
+  },
+  access: {
+    read: adminsOrCustomer('customer'),
+    create: admins,
+    update: admins,
+    delete: admins,
+  },
+  fields: [
+    { name: 'customer', type: 'relationship', relationTo: 'customers', required: true, index: true },
+    {
+      name: 'items',
+      type: 'array',
+      required: true,
+      minRows: 1,
+      fields: [
+        { name: 'product', type: 'relationship', relationTo: 'products', required: true },
+        { name: 'name', type: 'text', required: true },
+        { name: 'quantity', type: 'number', required: true, min: 1 },
+        { name: 'unitAmount', type: 'number', required: true, min: 0 },
+      ],
+    },
+    { name: 'totalInCents', type: 'number', required: true, min: 0 },
+    { name: 'currency', type: 'text', required: true },
+    {
+      name: 'status',
+      type: 'select',
+      required: true,
+      defaultValue: 'pending',
+      index: true,
+      options: [
+        { label: 'Pending', value: 'pending' },
+        { label: 'Paid', value: 'paid' },
+        { label: 'Cancelled', value: 'cancelled' },
+        { label: 'Refunded', value: 'refunded' },
+      ],
+      admin: { position: 'sidebar' },
+    },
+    {
+      name: 'stripeCheckoutSessionID',
+      type: 'text',
+      unique: true,
+      admin: { position: 'sidebar', readOnly: true },
+    },
+    {
+      name: 'stripePaymentIntentID',
+      type: 'text',
+      index: true,
+      admin: { position: 'sidebar', readOnly: true },
+    },
+  ],
+};
+
+export default Orders;
+";
+
+const SYNC_PRODUCT: &str = "import type { CollectionAfterChangeHook } from 'payload';
+
+import { CURRENCY, stripe } from './client';
+
+/**
+ * Creates the Stripe product on first save and keeps its name, description and
+ * active flag in sync. Stripe prices can't be edited, so a change to priceInCents
+ * creates a new default price and archives the old one. Failures are logged, and
+ * the next save retries.
+ */
+export const syncProductToStripe: CollectionAfterChangeHook = async ({ context, doc, previousDoc, req }) => {
+  if (context.skipStripeSync) {
+    return doc;
+  }
+  try {
+    const details = {
+      name: doc.name,
+      description: doc.description || undefined,
+      active: doc.active !== false,
+      metadata: { payloadID: String(doc.id) },
+    };
+    let productID: string | undefined = doc.stripeProductID || undefined;
+    if (productID) {
+      await stripe.products.update(productID, details);
+    } else {
+      productID = (await stripe.products.create(details)).id;
+    }
+    let priceID: string | undefined = doc.stripePriceID || undefined;
+    if (!priceID || doc.priceInCents !== previousDoc?.priceInCents) {
+      const price = await stripe.prices.create({
+        product: productID,
+        unit_amount: doc.priceInCents,
+        currency: CURRENCY,
+      });
+      await stripe.products.update(productID, { default_price: price.id });
+      if (priceID) {
+        await stripe.prices.update(priceID, { active: false });
+      }
+      priceID = price.id;
+    }
+    if (productID !== doc.stripeProductID || priceID !== doc.stripePriceID) {
+      await req.payload.update({
+        collection: 'products',
+        id: doc.id,
+        data: { stripeProductID: productID, stripePriceID: priceID },
+        context: { skipStripeSync: true },
+        req,
+      });
+      return { ...doc, stripeProductID: productID, stripePriceID: priceID };
+    }
+  } catch (error) {
+    req.payload.logger.error(`Could not sync product ${doc.id} to Stripe: ${error}`);
+  }
+  return doc;
+};
+";
+
+const WEBHOOKS: &str = "import type { Payload } from 'payload';
+import type Stripe from 'stripe';
+
+export type StripeWebhookHandler = (args: { event: Stripe.Event; payload: Payload }) => Promise<void>;
+
+type OrderUpdate = {
+  status: 'paid' | 'cancelled' | 'refunded';
+  totalInCents?: number;
+  stripePaymentIntentID?: string;
+};
+
+const paymentIntentID = (value: string | { id: string } | null): string | undefined =>
+  typeof value === 'string' ? value : value?.id;
+
+const updateOrder = async (payload: Payload, orderID: string | undefined, data: OrderUpdate) => {
+  if (!orderID) {
+    payload.logger.warn(`Stripe event without an orderID in its metadata; order not set to ${data.status}`);
+    return;
+  }
+  await payload.update({ collection: 'orders', id: orderID, data });
+};
+
+/** Marks the order paid; methods that settle later arrive as async_payment_succeeded. */
+const sessionPaid: StripeWebhookHandler = async ({ event, payload }) => {
+  const session = event.data.object as Stripe.Checkout.Session;
+  if (session.payment_status === 'unpaid') {
+    return;
+  }
+  await updateOrder(payload, session.metadata?.orderID, {
+    status: 'paid',
+    totalInCents: session.amount_total ?? undefined,
+    stripePaymentIntentID: paymentIntentID(session.payment_intent),
+  });
+};
+
+const sessionCancelled: StripeWebhookHandler = async ({ event, payload }) => {
+  const session = event.data.object as Stripe.Checkout.Session;
+  await updateOrder(payload, session.metadata?.orderID, { status: 'cancelled' });
+};
+
+/** Fully refunded charges refund their order; partial refunds leave it paid. */
+const chargeRefunded: StripeWebhookHandler = async ({ event, payload }) => {
+  const charge = event.data.object as Stripe.Charge;
+  const intent = paymentIntentID(charge.payment_intent);
+  if (!charge.refunded || !intent) {
+    return;
+  }
+  await payload.update({
+    collection: 'orders',
+    where: { stripePaymentIntentID: { equals: intent } },
+    data: { status: 'refunded' },
+  });
+};
+
+/** Handlers by Stripe event type. Stripe retries failed deliveries, so each can run more than once. */
+export const stripeWebhookHandlers: Record<string, StripeWebhookHandler> = {
+  'checkout.session.completed': sessionPaid,
+  'checkout.session.async_payment_succeeded': sessionPaid,
+  'checkout.session.async_payment_failed': sessionCancelled,
+  'checkout.session.expired': sessionCancelled,
+  'charge.refunded': chargeRefunded,
+};
+";
+
+fn checkout_endpoint(success_path: &str, cancel_path: &str, server_url: &str) -> String {
+    format!(
+        "import type {{ Endpoint }} from 'payload';
+
+import {{ CURRENCY, stripe }} from '../stripe/client';
+
+const MAX_QUANTITY = 99;
+
+type CheckoutItem = {{ product: string | number; quantity?: number }};
+
+const badRequest = (error: string) => Response.json({{ error }}, {{ status: 400 }});
+
+/**
+ * POST /api/checkout with {{ items: [{{ product, quantity }}] }} as a logged-in customer:
+ * creates a pending order and returns the Stripe Checkout `url` to send the customer to.
+ */
+export const checkoutEndpoint: Endpoint = {{
+  path: '/checkout',
+  method: 'post',
+  handler: async (req) => {{
+    const {{ payload, user }} = req;
+    if (!user || user.collection !== 'customers') {{
+      return Response.json({{ error: 'Log in as a customer to check out' }}, {{ status: 401 }});
+    }}
+    const body = (req.json ? await req.json() : {{}}) as {{ items?: CheckoutItem[] }};
+    const requested = Array.isArray(body.items) ? body.items : [];
+    if (requested.length === 0) {{
+      return badRequest('At least one item is required');
+    }}
+
+    const items: {{ product: string | number; name: string; quantity: number; unitAmount: number; price: string }}[] = [];
+    for (const {{ product: id, quantity = 1 }} of requested) {{
+      const count = Math.trunc(Number(quantity));
+      if (!(count >= 1 && count <= MAX_QUANTITY)) {{
+        return badRequest(`Quantity must be between 1 and ${{MAX_QUANTITY}}`);
+      }}
+      const product = await payload.findByID({{ collection: 'products', id, depth: 0, disableErrors: true }});
+      if (!product?.active || !product.stripePriceID) {{
+        return badRequest(`Product ${{id}} is not for sale`);
+      }}
+      items.push({{
+        product: product.id,
+        name: product.name,
+        quantity: count,
+        unitAmount: product.priceInCents,
+        price: product.stripePriceID,
+      }});
+    }}
+
+    const order = await payload.create({{
+      collection: 'orders',
+      data: {{
+        customer: user.id,
+        items: items.map(({{ price: _price, ...item }}) => item),
+        totalInCents: items.reduce((total, item) => total + item.unitAmount * item.quantity, 0),
+        currency: CURRENCY,
+        status: 'pending',
+      }},
+    }});
+    try {{
+      let customerID = user.stripeCustomerID;
+      if (!customerID) {{
+        const customer = await stripe.customers.create({{
+          email: user.email,
+          name: user.name || undefined,
+          metadata: {{ payloadID: String(user.id) }},
+        }});
+        customerID = customer.id;
+        await payload.update({{ collection: 'customers', id: user.id, data: {{ stripeCustomerID: customerID }} }});
+      }}
+      const origin = process.env.{server_url} ?? '';
+      const session = await stripe.checkout.sessions.create({{
+        mode: 'payment',
+        customer: customerID,
+        line_items: items.map(({{ price, quantity }}) => ({{ price, quantity }})),
+        client_reference_id: String(order.id),
+        metadata: {{ orderID: String(order.id) }},
+        success_url: `${{origin}}{success_path}?session_id={{CHECKOUT_SESSION_ID}}`,
+        cancel_url: `${{origin}}{cancel_path}`,
+      }});
+      await payload.update({{ collection: 'orders', id: order.id, data: {{ stripeCheckoutSessionID: session.id }} }});
+      return Response.json({{ order: order.id, url: session.url }});
+    }} catch (error) {{
+      payload.logger.error(`Checkout for order ${{order.id}} failed: ${{error}}`);
+      await payload.update({{ collection: 'orders', id: order.id, data: {{ status: 'cancelled' }} }});
+      return Response.json({{ error: 'Checkout is unavailable' }}, {{ status: 502 }});
+    }}
+  }},
+}};
+
+export default checkoutEndpoint;
+"
+    )
+}
+
+const WEBHOOKS_ENDPOINT: &str = "import type { Endpoint } from 'payload';
+import type Stripe from 'stripe';
+
+import { stripe } from '../stripe/client';
+import { stripeWebhookHandlers } from '../stripe/webhooks';
+
+/** POST /api/stripe/webhooks: checks the Stripe signature, then runs the handler for the event type. */
+export const stripeWebhooksEndpoint: Endpoint = {
+  path: '/stripe/webhooks',
+  method: 'post',
+  handler: async (req) => {
+    const body = req.text ? await req.text() : '';
+    let event: Stripe.Event;
+    try {
+      event = stripe.webhooks.constructEvent(
+        body,
+        req.headers.get('stripe-signature') ?? '',
+        process.env.STRIPE_WEBHOOKS_ENDPOINT_SECRET ?? '',
+      );
+    } catch (error) {
+      return Response.json({ error: `Invalid signature: ${error}` }, { status: 400 });
+    }
+    const handler = stripeWebhookHandlers[event.type];
+    if (handler) {
+      try {
+        await handler({ event, payload: req.payload });
+      } catch (error) {
+        // A non-2xx answer makes Stripe retry the delivery.
+        req.payload.logger.error(`Stripe webhook ${event.type} failed: ${error}`);
+        return Response.json({ error: 'Webhook handler failed' }, { status: 500 });
+      }
+    }
+    return Response.json({ received: true });
+  },
+};
+
+export default stripeWebhooksEndpoint;
+";
+
+/// Store collections, Stripe sync and checkout, and the webhook handlers
+/// behind the Stripe plugin or a generated endpoint.
+pub fn ecommerce_config(options: &EcommerceOptions) -> Result<EcommerceConfig, String> {
+    let currency = options
+        .currency
+        .as_deref()
+        .unwrap_or("usd")
+        .to_ascii_lowercase();
+    if currency.len() != 3 || !currency.chars().all(|c| c.is_ascii_lowercase()) {
+        return Err(format!(
+            "Invalid currency '{currency}'; expected a three-letter ISO code such as usd"
+        ));
+    }
+    let success_path = options
+        .success_path
+        .as_deref()
+        .unwrap_or("/checkout/success");
+    check_path("successPath", success_path)?;
+    let cancel_path = options.cancel_path.as_deref().unwrap_or("/cart");
+    check_path("cancelPath", cancel_path)?;
+    let server_url = server_url_env(PayloadVersion::V3);
+
+    let mut files = BTreeMap::from([
+        (
+            "src/stripe/client.ts".to_string(),
+            stripe_client(&currency),
+        ),
+        ("src/access/store.ts".to_string(), ACCESS.to_string()),
+        (
+            "src/collections/Products.ts".to_string(),
+            PRODUCTS.to_string(),
+        ),
+        (
+            "src/collections/Customers.ts".to_string(),
+            CUSTOMERS.to_string(),
+        ),
+        ("src/collections/Orders.ts".to_string(), ORDERS.to_string()),
+        (
+            "src/stripe/syncProduct.ts".to_string(),
+            SYNC_PRODUCT.to_string(),
+        ),
+        ("src/stripe/webhooks.ts".to_string(), WEBHOOKS.to_string()),
+        (
+            "src/endpoints/checkout.ts".to_string(),
+            checkout_endpoint(success_path, cancel_path, server_url),
+        ),
+    ]);
+    let collections = vec![
+        "Products".to_string(),
+        "Customers".to_string(),
+        "Orders".to_string(),
+    ];
+    let mut imports: Vec<String> = collections
+        .iter()
+        .map(|name| format!("import {name} from './collections/{name}';"))
+        .collect();
+    imports.push("import { checkoutEndpoint } from './endpoints/checkout';".to_string());
+    let mut endpoints = vec!["checkoutEndpoint".to_string()];
+    let mut dependencies = BTreeMap::from([(
+        STRIPE_PACKAGE.0.to_string(),
+        STRIPE_PACKAGE.1.to_string(),
+    )]);
+
+    let plugin = if options.plugin.unwrap_or(true) {
+        let mut plugin_options = Map::new();
+        plugin_options.insert(
+            "webhooks".to_string(),
+            Value::String("stripeWebhookHandlers".to_string()),
+        );
+        let plugin = plugin_config("stripe", PayloadVersion::V3, &plugin_options, "")?;
+        imports.extend(plugin.imports);
+        imports.push("import { stripeWebhookHandlers } from './stripe/webhooks';".to_string());
+        dependencies.extend(plugin.dependencies);
+        Some(plugin.code)
+    } else {
+        imports.push(
+            "import { stripeWebhooksEndpoint } from './endpoints/stripeWebhooks';".to_string(),
+        );
+        endpoints.push("stripeWebhooksEndpoint".to_string());
+        files.insert(
+            "src/endpoints/stripeWebhooks.ts".to_string(),
+            WEBHOOKS_ENDPOINT.to_string(),
+        );
+        None
+    };
+
+    let mut env: Vec<String> = STRIPE_ENV.iter().map(|var| var.to_string()).collect();
+    env.push(server_url.to_string());
+    Ok(EcommerceConfig {
+        imports,
+        plugin,
+        collections,
+        endpoints,
+        files,
+        dependencies,
+        env,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ecommerce_config_plugin_or_endpoint() {
+        let options = EcommerceOptions {
+            currency: Some("EUR".to_string()),
+            ..Default::default()
+        };
+        let config = ecommerce_config(&options).unwrap();
+        let plugin = config.plugin.as_deref().unwrap();
+        assert!(plugin.starts_with("stripePlugin({"));
+        assert!(plugin.contains("  webhooks: stripeWebhookHandlers,\n"));
+        assert_eq!(config.collections, vec!["Products", "Customers", "Orders"]);
+        assert_eq!(config.endpoints, vec!["checkoutEndpoint"]);
+        assert!(config.files["src/stripe/client.ts"].contains("export const CURRENCY = 'eur';"));
+        assert!(
+            config.files["src/endpoints/checkout.ts"]
+                .contains("success_url: `${origin}/checkout/success?session_id={CHECKOUT_SESSION_ID}`,")
+        );
+        assert_eq!(config.dependencies["stripe"], "^17.0.0");
+        assert!(config.dependencies.contains_key("@payloadcms/plugin-stripe"));
+
+        let endpoint = EcommerceOptions {
+            plugin: Some(false),
+            ..options.clone()
+        };
+        let config = ecommerce_config(&endpoint).unwrap();
+        assert!(config.plugin.is_none());
+        assert_eq!(config.endpoints, vec!["checkoutEndpoint", "stripeWebhooksEndpoint"]);
+        assert!(config.files["src/endpoints/stripeWebhooks.ts"].contains("constructEvent("));
+        assert!(!config.dependencies.contains_key("@payloadcms/plugin-stripe"));
+
+        let invalid = EcommerceOptions {
+            currency: Some("euro".to_string()),
+            ..options
+        };
+        assert!(ecommerce_config(&invalid).is_err());
+    }
+}
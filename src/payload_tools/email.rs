@@ -0,0 +1,399 @@
+//! Email transport and auth email templates: the `email` option for a
+//! nodemailer (SMTP) or Resend transport, the `auth` options of an auth
+//! collection with custom verification and forgot-password emails, and the
+//! files those options import.
+//!
+//! Payload 2 takes the `email` option in `payload.init` and has no Resend
+//! adapter, so Resend goes through its SMTP relay there; Payload 3 takes an
+//! adapter from `@payloadcms/email-*` in `buildConfig`.
+
+use std::collections::BTreeMap;
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::payload_tools::plugins::{PayloadVersion, env_used, render_call, to_literal};
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum EmailAdapter {
+    #[default]
+    Nodemailer,
+    Resend,
+}
+
+impl EmailAdapter {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            EmailAdapter::Nodemailer => "nodemailer",
+            EmailAdapter::Resend => "resend",
+        }
+    }
+
+    /// Environment variables the generated config reads, with example values
+    /// for `.env`.
+    pub fn env(&self) -> &'static [(&'static str, &'static str)] {
+        match self {
+            EmailAdapter::Nodemailer => &[
+                ("SMTP_HOST", "smtp.example.com"),
+                ("SMTP_PORT", "587"),
+                ("SMTP_USER", ""),
+                ("SMTP_PASS", ""),
+            ],
+            EmailAdapter::Resend => &[("RESEND_API_KEY", "")],
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct EmailConfigOptions {
+    /// `nodemailer` (SMTP, default) or `resend`
+    pub adapter: Option<EmailAdapter>,
+    /// Sender address, e.g. `noreply@example.com`
+    pub from_address: String,
+    /// Sender name (default `Payload`)
+    pub from_name: Option<String>,
+    /// Auth collection the emails are for (default `users`)
+    pub collection: Option<String>,
+    /// Require email verification before login (default true)
+    pub verify: Option<bool>,
+    /// Send a custom forgot-password email (default true)
+    pub forgot_password: Option<bool>,
+    /// Seconds a login token is valid (default 7200)
+    pub token_expiration: Option<u64>,
+    /// Failed logins before the account is locked; 0 disables locking
+    /// (default 5)
+    pub max_login_attempts: Option<u64>,
+    /// Milliseconds an account stays locked (default 600000)
+    pub lock_time: Option<u64>,
+    /// Payload major version, "2" (default) or "3"
+    pub payload_version: Option<PayloadVersion>,
+}
+
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct EmailConfig {
+    /// Imports for the file holding the `email` option
+    pub imports: Vec<String>,
+    /// Value of the `email` option: `buildConfig` on Payload 3,
+    /// `payload.init` on Payload 2
+    pub code: String,
+    /// Value of the auth collection's `auth` option
+    pub auth: String,
+    /// Value of the auth collection's `hooks` option, when it needs any
+    pub hooks: Option<String>,
+    /// Imports for the auth collection
+    pub auth_imports: Vec<String>,
+    /// Files under `src/` the options import, by path
+    pub files: BTreeMap<String, String>,
+    /// npm packages and version ranges to add to `package.json`
+    pub dependencies: BTreeMap<String, String>,
+    /// Environment variables read by the generated config and templates
+    pub env: Vec<String>,
+}
+
+const LAYOUT: &str = r#"const escape = (value: string): string =>
+  value.replace(/[&<>"']/g, (char) => `&#${char.charCodeAt(0)};`);
+
+export const renderEmail = ({
+  heading,
+  body,
+  action,
+}: {
+  heading: string;
+  body: string;
+  action?: { label: string; url: string };
+}): string => `<!doctype html>
+<html>
+  <body style="margin:0;padding:24px;background:#f4f4f5;font-family:sans-serif;color:#18181b;">
+    <table role="presentation" width="100%" style="max-width:560px;margin:0 auto;background:#ffffff;border-radius:8px;padding:32px;">
+      <tr><td>
+        <h1 style="font-size:20px;margin:0 0 16px;">${escape(heading)}</h1>
+        <p style="font-size:15px;line-height:1.6;margin:0 0 24px;">${escape(body)}</p>
+        ${
+          action
+            ? `<a href="${action.url}" style="display:inline-block;padding:12px 20px;background:#18181b;color:#ffffff;border-radius:6px;text-decoration:none;">${escape(action.label)}</a>`
+            : ''
+        }
+      </td></tr>
+    </table>
+  </body>
+</html>`;
+"#;
+
+pub(crate) fn server_url_env(version: PayloadVersion) -> &'static str {
+    match version {
+        PayloadVersion::V2 => "PAYLOAD_PUBLIC_SERVER_URL",
+        PayloadVersion::V3 => "NEXT_PUBLIC_SERVER_URL",
+    }
+}
+
+/// Template module exporting `generate<name>HTML` and `generate<name>Subject`.
+fn email_template(
+    name: &str,
+    subject: &str,
+    heading: &str,
+    body: &str,
+    label: &str,
+    url: &str,
+    server_url: &str,
+) -> String {
+    format!(
+        "import {{ renderEmail }} from './layout';\n\nconst serverURL = process.env.{server_url} || 'http://localhost:3000';\n\nexport const generate{name}Subject = (): string => '{subject}';\n\nexport const generate{name}HTML = ({{\n  token,\n  user,\n}}: {{\n  token?: string;\n  user?: {{ email?: string }};\n}}): string =>\n  renderEmail({{\n    heading: '{heading}',\n    body: `Hi ${{user?.email ?? 'there'}}, {body}`,\n    action: {{ label: '{label}', url: `${{serverURL}}{url}` }},\n  }});\n"
+    )
+}
+
+fn after_forgot_password_hook(version: PayloadVersion) -> String {
+    let types = match version {
+        PayloadVersion::V2 => "payload/types",
+        PayloadVersion::V3 => "payload",
+    };
+    format!(
+        "import type {{ CollectionAfterForgotPasswordHook }} from '{types}';\n\nexport const afterForgotPasswordHook: CollectionAfterForgotPasswordHook = async ({{ args, context }}) => {{\n  const email = args?.data?.email;\n  console.log(`Password reset requested for ${{email ?? 'unknown user'}}`, context);\n}};\n"
+    )
+}
+
+/// Email transport, auth options and email templates for `options`.
+pub fn email_config(options: &EmailConfigOptions) -> Result<EmailConfig, String> {
+    let adapter = options.adapter.unwrap_or_default();
+    let version = options.payload_version.unwrap_or_default();
+    let collection = options.collection.as_deref().unwrap_or("users");
+    if options.from_address.trim().is_empty() || !options.from_address.contains('@') {
+        return Err(format!(
+            "fromAddress must be an email address, got '{}'",
+            options.from_address
+        ));
+    }
+    if collection.is_empty()
+        || !collection
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+    {
+        return Err(format!("Invalid collection slug '{collection}'"));
+    }
+    let from_address = to_literal(&Value::String(options.from_address.clone()));
+    let from_name = to_literal(&Value::String(
+        options
+            .from_name
+            .clone()
+            .unwrap_or_else(|| "Payload".to_string()),
+    ));
+
+    let smtp = match adapter {
+        EmailAdapter::Nodemailer => {
+            "{ host: process.env.SMTP_HOST, port: Number(process.env.SMTP_PORT || 587), auth: { user: process.env.SMTP_USER, pass: process.env.SMTP_PASS } }"
+        }
+        EmailAdapter::Resend => {
+            "{ host: 'smtp.resend.com', port: 465, secure: true, auth: { user: 'resend', pass: process.env.RESEND_API_KEY } }"
+        }
+    };
+    let (imports, code, dependencies): (Vec<&str>, String, &[(&str, &str)]) =
+        match (version, adapter) {
+            (PayloadVersion::V2, _) => {
+                let entries = vec![
+                    ("fromName".to_string(), from_name),
+                    ("fromAddress".to_string(), from_address),
+                    ("transportOptions".to_string(), smtp.to_string()),
+                ];
+                (vec![], render_object(&entries), &[])
+            }
+            (PayloadVersion::V3, EmailAdapter::Nodemailer) => {
+                let entries = vec![
+                    ("defaultFromAddress".to_string(), from_address),
+                    ("defaultFromName".to_string(), from_name),
+                    ("transportOptions".to_string(), smtp.to_string()),
+                ];
+                (
+                    vec!["import { nodemailerAdapter } from '@payloadcms/email-nodemailer';"],
+                    trim_call(render_call("nodemailerAdapter", &entries, "")),
+                    &[("@payloadcms/email-nodemailer", "^3.0.0")],
+                )
+            }
+            (PayloadVersion::V3, EmailAdapter::Resend) => {
+                let entries = vec![
+                    ("defaultFromAddress".to_string(), from_address),
+                    ("defaultFromName".to_string(), from_name),
+                    (
+                        "apiKey".to_string(),
+                        "process.env.RESEND_API_KEY || ''".to_string(),
+                    ),
+                ];
+                (
+                    vec!["import { resendAdapter } from '@payloadcms/email-resend';"],
+                    trim_call(render_call("resendAdapter", &entries, "")),
+                    &[("@payloadcms/email-resend", "^3.0.0")],
+                )
+            }
+        };
+
+    let server_url = server_url_env(version);
+    let verify = options.verify.unwrap_or(true);
+    let forgot_password = options.forgot_password.unwrap_or(true);
+    let mut auth = vec![(
+        "tokenExpiration".to_string(),
+        options.token_expiration.unwrap_or(7200).to_string(),
+    )];
+    match options.max_login_attempts.unwrap_or(5) {
+        0 => auth.push(("maxLoginAttempts".to_string(), "0".to_string())),
+        attempts => {
+            auth.push(("maxLoginAttempts".to_string(), attempts.to_string()));
+            auth.push((
+                "lockTime".to_string(),
+                options.lock_time.unwrap_or(600_000).to_string(),
+            ));
+        }
+    }
+
+    let mut files = BTreeMap::new();
+    let mut auth_imports = Vec::new();
+    if verify || forgot_password {
+        files.insert("email/layout.ts".to_string(), LAYOUT.to_string());
+    }
+    if verify {
+        auth.push((
+            "verify".to_string(),
+            "{ generateEmailSubject: generateVerifyEmailSubject, generateEmailHTML: generateVerifyEmailHTML }".to_string(),
+        ));
+        auth_imports.push(
+            "import { generateVerifyEmailHTML, generateVerifyEmailSubject } from '../email/verifyEmail';".to_string(),
+        );
+        files.insert(
+            "email/verifyEmail.ts".to_string(),
+            email_template(
+                "VerifyEmail",
+                "Verify your email",
+                "Confirm your email address",
+                "confirm your email address to finish setting up your account.",
+                "Verify email",
+                &format!("/admin/{collection}/verify/${{token}}"),
+                server_url,
+            ),
+        );
+    }
+    if forgot_password {
+        auth.push((
+            "forgotPassword".to_string(),
+            "{ generateEmailSubject: generateForgotPasswordSubject, generateEmailHTML: generateForgotPasswordHTML }".to_string(),
+        ));
+        auth_imports.push(
+            "import { generateForgotPasswordHTML, generateForgotPasswordSubject } from '../email/forgotPassword';".to_string(),
+        );
+        auth_imports.push(
+            "import { afterForgotPasswordHook } from '../hooks/afterForgotPassword';".to_string(),
+        );
+        files.insert(
+            "email/forgotPassword.ts".to_string(),
+            email_template(
+                "ForgotPassword",
+                "Reset your password",
+                "Reset your password",
+                "we received a request to reset your password. The link expires in one hour; ignore this email if you did not ask for it.",
+                "Reset password",
+                "/admin/reset/${token}",
+                server_url,
+            ),
+        );
+        files.insert(
+            "hooks/afterForgotPassword.ts".to_string(),
+            after_forgot_password_hook(version),
+        );
+    }
+
+    let mut env_vars: Vec<&str> = adapter.env().iter().map(|(var, _)| *var).collect();
+    if verify || forgot_password {
+        env_vars.push(server_url);
+    }
+    let mut read = vec![("email".to_string(), code.clone())];
+    read.extend(
+        files
+            .values()
+            .map(|file| ("file".to_string(), file.clone())),
+    );
+
+    Ok(EmailConfig {
+        imports: imports.into_iter().map(str::to_string).collect(),
+        code,
+        auth: render_object(&auth),
+        hooks: forgot_password
+            .then(|| "{ afterForgotPassword: [afterForgotPasswordHook] }".to_string()),
+        auth_imports,
+        files,
+        dependencies: dependencies
+            .iter()
+            .map(|(package, range)| (package.to_string(), range.to_string()))
+            .collect(),
+        env: env_used(&env_vars, &read),
+    })
+}
+
+/// `{ key: value, ... }` over several lines, for a property value.
+fn render_object(entries: &[(String, String)]) -> String {
+    let body = entries
+        .iter()
+        .map(|(key, code)| format!("  {key}: {code},"))
+        .collect::<Vec<_>>()
+        .join("\n");
+    format!("{{\n{body}\n}}")
+}
+
+/// A [`render_call`] used as a property value rather than an array entry.
+fn trim_call(call: String) -> String {
+    call.strip_suffix(',').map(str::to_string).unwrap_or(call)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn options(adapter: EmailAdapter, version: PayloadVersion) -> EmailConfigOptions {
+        EmailConfigOptions {
+            adapter: Some(adapter),
+            from_address: "noreply@example.com".to_string(),
+            from_name: None,
+            collection: Some("members".to_string()),
+            verify: None,
+            forgot_password: None,
+            token_expiration: None,
+            max_login_attempts: None,
+            lock_time: None,
+            payload_version: Some(version),
+        }
+    }
+
+    #[test]
+    fn test_email_config_per_adapter() {
+        let config = email_config(&options(EmailAdapter::Resend, PayloadVersion::V3)).unwrap();
+        assert_eq!(
+            config.imports,
+            vec!["import { resendAdapter } from '@payloadcms/email-resend';"]
+        );
+        assert!(config.code.starts_with("resendAdapter({"));
+        assert!(config.auth.contains("lockTime: 600000"));
+        assert!(config.files["email/verifyEmail.ts"].contains("/admin/members/verify/${token}"));
+        assert_eq!(config.env, vec!["RESEND_API_KEY", "NEXT_PUBLIC_SERVER_URL"]);
+
+        let smtp = EmailConfigOptions {
+            verify: Some(false),
+            forgot_password: Some(false),
+            max_login_attempts: Some(0),
+            ..options(EmailAdapter::Nodemailer, PayloadVersion::V2)
+        };
+        let config = email_config(&smtp).unwrap();
+        assert!(config.imports.is_empty());
+        assert!(
+            config
+                .code
+                .contains("transportOptions: { host: process.env.SMTP_HOST")
+        );
+        assert!(!config.auth.contains("lockTime"));
+        assert!(config.files.is_empty() && config.auth_imports.is_empty());
+        assert_eq!(config.hooks, None);
+
+        let invalid = EmailConfigOptions {
+            from_address: "noreply".to_string(),
+            ..smtp
+        };
+        assert!(email_config(&invalid).is_err());
+    }
+}
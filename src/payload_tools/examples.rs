@@ -0,0 +1,475 @@
+//! Reference configurations served by the `get_example` tool. Each example is
+//! a config definition in the validator's JSON format, rendered to ready-to-adapt
+//! TypeScript: one file per collection and global, the access helpers they use
+//! and a `payload.config.ts`.
+
+use std::collections::BTreeSet;
+
+use schemars::JsonSchema;
+use serde::Serialize;
+use serde_json::{Value, json};
+
+use crate::payload_tools::{
+    generator::{TemplateType, capitalize, generate_template},
+    types::FileType,
+};
+
+pub struct Example {
+    pub id: &'static str,
+    pub title: &'static str,
+    pub description: &'static str,
+    pub tags: &'static [&'static str],
+    /// Database adapter written to `payload.config.ts`: mongodb or postgres
+    pub db: &'static str,
+    /// Config definition (JSON) with `collections` and `globals`; access
+    /// operations name helpers from [`ACCESS_HELPERS`]
+    pub definition: &'static str,
+}
+
+pub const EXAMPLES: &[Example] = &[
+    Example {
+        id: "blog",
+        title: "Blog",
+        description: "Posts with drafts and autosave, categories, authors with roles, media with image sizes and site settings",
+        tags: &[
+            "blog",
+            "content",
+            "drafts",
+            "versions",
+            "media",
+            "uploads",
+            "rich-text",
+            "seo",
+            "roles",
+        ],
+        db: "mongodb",
+        definition: include_str!("../examples/blog.json"),
+    },
+    Example {
+        id: "saas",
+        title: "SaaS with roles",
+        description: "Organizations as tenants, users with roles and API keys, projects with members and invitations",
+        tags: &[
+            "saas",
+            "auth",
+            "roles",
+            "multi-tenant",
+            "access-control",
+            "api-keys",
+            "organizations",
+        ],
+        db: "postgres",
+        definition: include_str!("../examples/saas.json"),
+    },
+    Example {
+        id: "marketplace",
+        title: "Marketplace",
+        description: "Sellers and buyers, draft products with images and stock, orders with line items, reviews and nested categories",
+        tags: &[
+            "marketplace",
+            "ecommerce",
+            "auth",
+            "roles",
+            "relationships",
+            "uploads",
+            "orders",
+            "reviews",
+            "drafts",
+        ],
+        db: "postgres",
+        definition: include_str!("../examples/marketplace.json"),
+    },
+    Example {
+        id: "docs",
+        title: "Headless docs site",
+        description: "Localized, versioned docs pages in ordered sections with code samples, related pages and a navigation global",
+        tags: &[
+            "docs",
+            "headless",
+            "localization",
+            "drafts",
+            "versions",
+            "navigation",
+            "nested",
+        ],
+        db: "mongodb",
+        definition: include_str!("../examples/docs.json"),
+    },
+];
+
+/// Access functions the examples refer to by name, as TypeScript.
+pub const ACCESS_HELPERS: &[(&str, &str)] = &[
+    ("anyone", "export const anyone: Access = () => true"),
+    (
+        "authenticated",
+        "export const authenticated: Access = ({ req: { user } }) => Boolean(user)",
+    ),
+    (
+        "admins",
+        "export const admins: Access = ({ req: { user } }) => Boolean(user?.roles?.includes('admin'))",
+    ),
+    (
+        "editors",
+        "export const editors: Access = ({ req: { user } }) =>\n  Boolean(user?.roles?.some((role) => role === 'admin' || role === 'editor'))",
+    ),
+    (
+        "sellers",
+        "export const sellers: Access = ({ req: { user } }) =>\n  Boolean(user?.roles?.some((role) => role === 'admin' || role === 'seller'))",
+    ),
+    (
+        "adminsOrSelf",
+        "export const adminsOrSelf: Access = ({ req: { user } }) => {\n  if (!user) return false\n  if (user.roles?.includes('admin')) return true\n  return { id: { equals: user.id } }\n}",
+    ),
+    (
+        "adminsOrSeller",
+        "export const adminsOrSeller: Access = ({ req: { user } }) => {\n  if (!user) return false\n  if (user.roles?.includes('admin')) return true\n  return { seller: { equals: user.id } }\n}",
+    ),
+    (
+        "adminsOrBuyer",
+        "export const adminsOrBuyer: Access = ({ req: { user } }) => {\n  if (!user) return false\n  if (user.roles?.includes('admin')) return true\n  return { buyer: { equals: user.id } }\n}",
+    ),
+    (
+        "publishedOrAuthenticated",
+        "export const publishedOrAuthenticated: Access = ({ req: { user } }) => {\n  if (user) return true\n  return { _status: { equals: 'published' } }\n}",
+    ),
+    (
+        "sameOrganization",
+        "export const sameOrganization: Access = ({ req: { user } }) => {\n  if (!user) return false\n  if (user.roles?.includes('admin')) return true\n  const organization = typeof user.organization === 'object' ? user.organization.id : user.organization\n  return { organization: { equals: organization } }\n}",
+    ),
+];
+
+#[derive(Clone, Debug, Serialize, JsonSchema)]
+pub struct ExampleSummary {
+    pub id: &'static str,
+    pub title: &'static str,
+    pub description: &'static str,
+    pub tags: &'static [&'static str],
+    pub collections: Vec<String>,
+    pub globals: Vec<String>,
+}
+
+#[derive(Clone, Debug, Serialize, JsonSchema)]
+pub struct ExampleFile {
+    pub path: String,
+    /// Kind of file the definition was validated as, if any
+    pub file_type: Option<FileType>,
+    pub code: String,
+}
+
+#[derive(Clone, Debug, Serialize, JsonSchema)]
+pub struct ExampleCode {
+    #[serde(flatten)]
+    pub summary: ExampleSummary,
+    /// Config definition the files are rendered from, in the validator's
+    /// JSON format
+    pub definition: Value,
+    pub files: Vec<ExampleFile>,
+}
+
+pub fn example(id: &str) -> Option<&'static Example> {
+    EXAMPLES.iter().find(|example| example.id == id)
+}
+
+/// The example `id`, or an error listing the examples.
+pub fn require_example(id: &str) -> Result<&'static Example, String> {
+    example(id).ok_or_else(|| {
+        format!(
+            "Unknown example '{id}'. Examples: {}",
+            EXAMPLES
+                .iter()
+                .map(|example| example.id)
+                .collect::<Vec<_>>()
+                .join(", ")
+        )
+    })
+}
+
+impl Example {
+    pub fn definition(&self) -> Value {
+        serde_json::from_str(self.definition).expect("bundled example definitions are valid JSON")
+    }
+
+    pub fn summary(&self) -> ExampleSummary {
+        let definition = self.definition();
+        ExampleSummary {
+            id: self.id,
+            title: self.title,
+            description: self.description,
+            tags: self.tags,
+            collections: slugs(&definition, "collections"),
+            globals: slugs(&definition, "globals"),
+        }
+    }
+
+    /// Whether the example has every tag in `tags` and, when `query` is set,
+    /// mentions it in its id, title, description or tags.
+    pub fn matches(&self, tags: &[String], query: Option<&str>) -> bool {
+        let has_tags = tags.iter().all(|tag| {
+            self.tags
+                .iter()
+                .any(|own| own.eq_ignore_ascii_case(tag.trim()))
+        });
+        let query = query.map(str::trim).filter(|query| !query.is_empty());
+        has_tags
+            && query.is_none_or(|query| {
+                let query = query.to_lowercase();
+                [self.id, self.title, self.description]
+                    .into_iter()
+                    .chain(self.tags.iter().copied())
+                    .any(|text| text.to_lowercase().contains(&query))
+            })
+    }
+
+    /// The example rendered to TypeScript files.
+    pub fn code(&self) -> Result<ExampleCode, String> {
+        let definition = self.definition();
+        let mut helpers = BTreeSet::new();
+        let mut files = Vec::new();
+        for (key, file_type, config_type) in [
+            ("collections", FileType::Collection, "CollectionConfig"),
+            ("globals", FileType::Global, "GlobalConfig"),
+        ] {
+            for entity in definition
+                .get(key)
+                .and_then(Value::as_array)
+                .into_iter()
+                .flatten()
+            {
+                let slug = entity
+                    .get("slug")
+                    .and_then(Value::as_str)
+                    .ok_or_else(|| format!("{} has an entry without a slug", self.id))?;
+                let mut used = BTreeSet::new();
+                let literal = ts_literal(entity, 0, false, &mut used)?;
+                let imports = if used.is_empty() {
+                    String::new()
+                } else {
+                    let names = used.iter().cloned().collect::<Vec<_>>().join(", ");
+                    format!("import {{ {names} }} from '../access';\n")
+                };
+                let name = capitalize(slug);
+                files.push(ExampleFile {
+                    path: format!("src/{key}/{slug}.ts"),
+                    file_type: Some(file_type),
+                    code: format!(
+                        "import {{ {config_type} }} from 'payload/types';\n{imports}\nconst {name}: {config_type} = {literal};\n\nexport default {name};\n"
+                    ),
+                });
+                helpers.extend(used);
+            }
+        }
+
+        let access = ACCESS_HELPERS
+            .iter()
+            .filter(|(name, _)| helpers.contains(*name))
+            .map(|(_, code)| *code)
+            .collect::<Vec<_>>()
+            .join("\n\n");
+        files.push(ExampleFile {
+            path: "src/access.ts".to_string(),
+            file_type: None,
+            code: format!("import type {{ Access }} from 'payload/types';\n\n{access}\n"),
+        });
+
+        let summary = self.summary();
+        let config = generate_template(
+            TemplateType::Config,
+            &json!({
+                "serverURL": definition.get("serverURL"),
+                "collections": summary.collections,
+                "globals": summary.globals,
+                "db": self.db,
+                "admin": definition.get("admin"),
+            }),
+        )?;
+        files.push(ExampleFile {
+            path: "src/payload.config.ts".to_string(),
+            file_type: Some(FileType::Config),
+            code: config,
+        });
+
+        Ok(ExampleCode {
+            summary,
+            definition,
+            files,
+        })
+    }
+}
+
+/// Examples with every tag in `tags` that mention `query`.
+pub fn find_examples(tags: &[String], query: Option<&str>) -> Vec<ExampleSummary> {
+    EXAMPLES
+        .iter()
+        .filter(|example| example.matches(tags, query))
+        .map(Example::summary)
+        .collect()
+}
+
+fn slugs(definition: &Value, key: &str) -> Vec<String> {
+    definition
+        .get(key)
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+        .filter_map(|entity| entity.get("slug").and_then(Value::as_str))
+        .map(str::to_string)
+        .collect()
+}
+
+fn ts_key(key: &str) -> String {
+    let mut chars = key.chars();
+    let identifier = chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_' || c == '$')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '$');
+    if identifier {
+        key.to_string()
+    } else {
+        format!("'{key}'")
+    }
+}
+
+/// Sort rank of an object key: identifying keys first and `fields` last, the
+/// rest in the map's (alphabetical) order.
+fn key_rank(key: &str) -> usize {
+    match key {
+        "slug" | "name" => 0,
+        "type" => 1,
+        "fields" => 3,
+        _ => 2,
+    }
+}
+
+/// `value` as a TypeScript literal indented for `depth`. Inside `access`
+/// objects, strings name access helpers and are written as identifiers.
+fn ts_literal(
+    value: &Value,
+    depth: usize,
+    access: bool,
+    helpers: &mut BTreeSet<String>,
+) -> Result<String, String> {
+    let indent = "  ".repeat(depth + 1);
+    let closing = "  ".repeat(depth);
+    Ok(match value {
+        Value::String(name) if access => {
+            if !ACCESS_HELPERS.iter().any(|(helper, _)| helper == name) {
+                return Err(format!("Unknown access helper '{name}'"));
+            }
+            helpers.insert(name.clone());
+            name.clone()
+        }
+        Value::String(text) => format!("'{}'", text.replace('\\', "\\\\").replace('\'', "\\'")),
+        Value::Array(items) if items.iter().all(|item| !item.is_object()) => {
+            let items = items
+                .iter()
+                .map(|item| ts_literal(item, depth, false, helpers))
+                .collect::<Result<Vec<_>, _>>()?;
+            format!("[{}]", items.join(", "))
+        }
+        Value::Array(items) => {
+            let mut out = String::from("[\n");
+            for item in items {
+                out.push_str(&format!(
+                    "{indent}{},\n",
+                    ts_literal(item, depth + 1, false, helpers)?
+                ));
+            }
+            out.push_str(&closing);
+            out.push(']');
+            out
+        }
+        Value::Object(map) => {
+            let mut entries: Vec<_> = map.iter().collect();
+            entries.sort_by_key(|(key, _)| key_rank(key));
+            let mut out = String::from("{\n");
+            for (key, item) in entries {
+                let item = ts_literal(item, depth + 1, access || key == "access", helpers)?;
+                out.push_str(&format!("{indent}{}: {item},\n", ts_key(key)));
+            }
+            out.push_str(&closing);
+            out.push('}');
+            out
+        }
+        other => other.to_string(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::payload_tools::validator::validate_payload_code;
+
+    #[test]
+    fn examples_validate_and_render() {
+        for example in EXAMPLES {
+            let definition = example.definition();
+            let config = validate_payload_code(example.definition, FileType::Config);
+            assert!(
+                config.errors.is_empty(),
+                "{}: {:?}",
+                example.id,
+                config.errors
+            );
+            for (key, file_type) in [
+                ("collections", FileType::Collection),
+                ("globals", FileType::Global),
+            ] {
+                for entity in definition
+                    .get(key)
+                    .and_then(Value::as_array)
+                    .into_iter()
+                    .flatten()
+                {
+                    let result = validate_payload_code(&entity.to_string(), file_type);
+                    assert!(
+                        result.is_valid && result.warnings.is_empty(),
+                        "{} {}: {:?} {:?}",
+                        example.id,
+                        entity["slug"],
+                        result.errors,
+                        result.warnings
+                    );
+                }
+            }
+
+            let code = example.code().unwrap();
+            let access = code
+                .files
+                .iter()
+                .find(|file| file.path == "src/access.ts")
+                .unwrap();
+            assert!(
+                access.code.contains("export const admins"),
+                "{}",
+                example.id
+            );
+            assert!(
+                code.files
+                    .iter()
+                    .any(|file| file.path == "src/payload.config.ts")
+            );
+        }
+
+        let posts = example("blog").unwrap().code().unwrap();
+        let posts = posts
+            .files
+            .iter()
+            .find(|file| file.path == "src/collections/posts.ts")
+            .unwrap();
+        assert!(posts.code.contains("read: publishedOrAuthenticated,"));
+        assert!(posts.code.contains(
+            "import { authenticated, editors, publishedOrAuthenticated } from '../access';"
+        ));
+
+        assert_eq!(
+            find_examples(&["roles".into(), "multi-tenant".into()], None).len(),
+            1
+        );
+        assert!(
+            find_examples(&[], Some("marketplace"))
+                .iter()
+                .any(|found| found.id == "marketplace")
+        );
+        assert!(require_example("wiki").is_err());
+    }
+}
@@ -0,0 +1,998 @@
+//! Payload collections, globals and blocks converted from the content model
+//! of another CMS: a Contentful space export, Strapi `schema.json` files or
+//! Sanity schema definitions. Each format is read into a [`SchemaModel`],
+//! which is validated and rendered as config files.
+//!
+//! Images, files and assets become uploads to a `media` collection, added
+//! when the source doesn't define one. Validation rules beyond required,
+//! unique and fixed choices don't carry over.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::payload_tools::{
+    naming::{Case, pluralize},
+    plugins::to_literal,
+    schema_model::{SchemaBlock, SchemaCollection, SchemaField, SchemaGlobal, SchemaModel},
+    ts_parser::{parse_config_object, parse_object_at},
+};
+
+/// Upload collection that image, file and asset fields point at.
+const MEDIA: &str = "media";
+/// Nesting of named object types followed before giving up on a cycle.
+const MAX_DEPTH: usize = 8;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum ExternalFormat {
+    Contentful,
+    Strapi,
+    Sanity,
+}
+
+#[derive(Debug, Clone, Deserialize, JsonSchema)]
+pub struct ExternalSchemaOptions {
+    pub format: ExternalFormat,
+    /// Contentful: a space export or its `contentTypes` array. Strapi: a
+    /// `schema.json` file, an array of them, or an object of them keyed by
+    /// UID (`api::post.post`, `shared.seo`); component files need their
+    /// UID under `uid`. Sanity: a type definition, an array of them, or
+    /// schema files as JavaScript or TypeScript strings
+    pub source: Value,
+}
+
+#[derive(Debug, Clone, Default, Serialize, JsonSchema)]
+pub struct ExternalSchemaImport {
+    /// The converted content model
+    pub schema: SchemaModel,
+    /// Collection, global and block files by path from the project root
+    pub files: BTreeMap<String, String>,
+    /// Definitions that didn't carry over or need review
+    pub warnings: Vec<String>,
+}
+
+/// Convert the content model in `options.source` into Payload config.
+pub fn import_external_schema(options: &ExternalSchemaOptions) -> Result<ExternalSchemaImport, String> {
+    let mut warnings = Vec::new();
+    let schema = match options.format {
+        ExternalFormat::Contentful => contentful(&options.source, &mut warnings)?,
+        ExternalFormat::Strapi => strapi(&options.source, &mut warnings)?,
+        ExternalFormat::Sanity => sanity(&options.source, &mut warnings)?,
+    };
+    finish(schema, warnings)
+}
+
+/// `schema` with a `media` collection added when uploads need one,
+/// validated and rendered.
+pub(crate) fn finish(mut schema: SchemaModel, mut warnings: Vec<String>) -> Result<ExternalSchemaImport, String> {
+    if schema.collections.is_empty() && schema.globals.is_empty() {
+        return Err("No content types found in source".to_string());
+    }
+    if uses_media(&schema) && schema.collection(MEDIA).is_none() {
+        schema.collections.push(SchemaCollection {
+            slug: MEDIA.to_string(),
+            upload: true,
+            ..Default::default()
+        });
+    }
+    warnings.extend(schema.validate());
+    let files = render_files(&schema, &mut warnings);
+    Ok(ExternalSchemaImport {
+        schema,
+        files,
+        warnings,
+    })
+}
+
+pub(crate) fn collection_slug(name: &str) -> String {
+    Case::Kebab.convert(&pluralize(name))
+}
+
+pub(crate) fn field(name: &str, field_type: &str) -> SchemaField {
+    SchemaField::new(&Case::Camel.convert(name), field_type)
+}
+
+pub(crate) fn relation(name: &str, field_type: &str, targets: Vec<String>, has_many: bool) -> SchemaField {
+    SchemaField {
+        relation_to: Some(targets),
+        has_many,
+        ..field(name, field_type)
+    }
+}
+
+pub(crate) fn choice(name: &str, options: &[Value]) -> SchemaField {
+    SchemaField {
+        options: Some(
+            options
+                .iter()
+                .filter_map(|option| option.as_str().or_else(|| option.get("value")?.as_str()))
+                .map(str::to_string)
+                .collect(),
+        ),
+        ..field(name, "select")
+    }
+}
+
+fn items(value: &Value) -> &[Value] {
+    value.as_array().map_or(&[], Vec::as_slice)
+}
+
+fn text<'a>(value: &'a Value, key: &str) -> Option<&'a str> {
+    value.get(key).and_then(Value::as_str)
+}
+
+fn flag(value: &Value, key: &str) -> bool {
+    value.get(key).and_then(Value::as_bool).unwrap_or(false)
+}
+
+fn fields_use_media(fields: &[SchemaField]) -> bool {
+    fields.iter().any(|field| {
+        field.field_type == "upload"
+            || fields_use_media(field.fields.as_deref().unwrap_or_default())
+            || field
+                .blocks
+                .iter()
+                .flatten()
+                .any(|block| fields_use_media(&block.fields))
+    })
+}
+
+fn uses_media(schema: &SchemaModel) -> bool {
+    schema
+        .collections
+        .iter()
+        .any(|collection| fields_use_media(&collection.fields))
+        || schema.globals.iter().any(|global| fields_use_media(&global.fields))
+}
+
+// Contentful
+
+fn contentful(source: &Value, warnings: &mut Vec<String>) -> Result<SchemaModel, String> {
+    let types = source
+        .get("contentTypes")
+        .unwrap_or(source)
+        .as_array()
+        .ok_or("Expected a Contentful space export or its contentTypes array")?;
+    let slugs: BTreeMap<&str, String> = types
+        .iter()
+        .filter_map(|content_type| content_type["sys"]["id"].as_str())
+        .map(|id| (id, collection_slug(id)))
+        .collect();
+    let mut schema = SchemaModel::default();
+    for content_type in types {
+        let Some(id) = content_type["sys"]["id"].as_str() else {
+            warnings.push("Skipped a content type without sys.id".to_string());
+            continue;
+        };
+        let slug = slugs[id].clone();
+        let mut fields = Vec::new();
+        for spec in items(&content_type["fields"]) {
+            let Some(name) = text(spec, "id") else {
+                continue;
+            };
+            if flag(spec, "omitted") || flag(spec, "disabled") {
+                warnings.push(format!("{slug}.{name}: omitted or disabled in Contentful, not converted"));
+                continue;
+            }
+            let kind = text(spec, "type").unwrap_or_default();
+            let mut converted = if kind == "Array" {
+                let item = &spec["items"];
+                contentful_field(name, text(item, "type").unwrap_or_default(), item, &slugs, true, &slug, warnings)
+            } else {
+                contentful_field(name, kind, spec, &slugs, false, &slug, warnings)
+            };
+            converted.required = flag(spec, "required");
+            converted.localized = flag(spec, "localized");
+            converted.unique = items(&spec["validations"]).iter().any(|rule| flag(rule, "unique"));
+            fields.push(converted);
+        }
+        schema.collections.push(SchemaCollection {
+            slug,
+            fields,
+            timestamps: true,
+            ..Default::default()
+        });
+    }
+    Ok(schema)
+}
+
+fn contentful_field(
+    name: &str,
+    kind: &str,
+    spec: &Value,
+    slugs: &BTreeMap<&str, String>,
+    has_many: bool,
+    slug: &str,
+    warnings: &mut Vec<String>,
+) -> SchemaField {
+    let rule = |key: &str| items(&spec["validations"]).iter().find_map(|rule| rule.get(key));
+    let mut converted = match kind {
+        "Symbol" => match rule("in") {
+            Some(options) => choice(name, items(options)),
+            None => field(name, "text"),
+        },
+        "Text" => field(name, "textarea"),
+        "RichText" => field(name, "richText"),
+        "Integer" | "Number" => field(name, "number"),
+        "Date" => field(name, "date"),
+        "Boolean" => field(name, "checkbox"),
+        "Location" => field(name, "point"),
+        "Object" => field(name, "json"),
+        "Link" if text(spec, "linkType") == Some("Asset") => relation(name, "upload", vec![MEDIA.to_string()], false),
+        "Link" => {
+            let mut targets: Vec<String> = items(rule("linkContentType").unwrap_or(&Value::Null))
+                .iter()
+                .filter_map(Value::as_str)
+                .map(|id| slugs.get(id).cloned().unwrap_or_else(|| collection_slug(id)))
+                .collect();
+            if targets.is_empty() {
+                warnings.push(format!("{slug}.{name}: links any entry; relationTo lists every collection"));
+                targets = slugs.values().cloned().collect();
+            }
+            relation(name, "relationship", targets, false)
+        }
+        other => {
+            warnings.push(format!("{slug}.{name}: Contentful type '{other}' became json"));
+            field(name, "json")
+        }
+    };
+    converted.has_many = has_many;
+    converted
+}
+
+// Strapi
+
+/// Content types and components by UID.
+struct Strapi<'a> {
+    schemas: Vec<(String, &'a Value)>,
+    slugs: BTreeMap<String, String>,
+}
+
+impl<'a> Strapi<'a> {
+    fn new(source: &'a Value) -> Self {
+        let entries: Vec<(Option<&str>, &Value)> = match source {
+            Value::Array(list) => list.iter().map(|schema| (None, schema)).collect(),
+            Value::Object(map) if map.contains_key("attributes") => vec![(None, source)],
+            Value::Object(map) => match map.get("data").and_then(Value::as_array) {
+                Some(list) => list.iter().map(|schema| (None, schema)).collect(),
+                None => map.iter().map(|(uid, schema)| (Some(uid.as_str()), schema)).collect(),
+            },
+            _ => Vec::new(),
+        };
+        let mut schemas = Vec::new();
+        for (key, entry) in entries {
+            // Content-type builder responses wrap the schema with its UID.
+            let schema = entry.get("schema").unwrap_or(entry);
+            let uid = key
+                .or_else(|| text(entry, "uid"))
+                .or_else(|| text(&schema["info"], "singularName"))
+                .or_else(|| text(&schema["info"], "displayName"))
+                .unwrap_or_default();
+            schemas.push((uid.to_string(), schema));
+        }
+        let slugs = schemas
+            .iter()
+            .filter(|(_, schema)| schema.get("kind").is_some())
+            .map(|(uid, schema)| (uid.clone(), Self::slug(uid, schema)))
+            .collect();
+        Self { schemas, slugs }
+    }
+
+    fn slug(uid: &str, schema: &Value) -> String {
+        let info = &schema["info"];
+        if schema["kind"] == "singleType" {
+            let name = text(info, "singularName").unwrap_or_else(|| Self::name(uid));
+            return Case::Kebab.convert(name);
+        }
+        match text(info, "pluralName") {
+            Some(plural) => Case::Kebab.convert(plural),
+            None => collection_slug(text(info, "singularName").unwrap_or_else(|| Self::name(uid))),
+        }
+    }
+
+    /// The last segment of a UID: `post` of `api::post.post`.
+    fn name(uid: &str) -> &str {
+        uid.rsplit(['.', ':']).next().unwrap_or(uid)
+    }
+
+    fn target(&self, uid: &str) -> String {
+        self.slugs
+            .get(uid)
+            .cloned()
+            .unwrap_or_else(|| collection_slug(Self::name(uid)))
+    }
+
+    fn component(&self, uid: &str) -> Option<&'a Value> {
+        let name = Self::name(uid);
+        self.schemas
+            .iter()
+            .filter(|(_, schema)| schema.get("kind").is_none())
+            .find(|(key, _)| key == uid)
+            .or_else(|| {
+                self.schemas
+                    .iter()
+                    .filter(|(_, schema)| schema.get("kind").is_none())
+                    .find(|(key, _)| Self::name(key) == name)
+            })
+            .map(|(_, schema)| *schema)
+    }
+
+    fn fields(&self, schema: &Value, path: &str, depth: usize, warnings: &mut Vec<String>) -> Vec<SchemaField> {
+        let Some(attributes) = schema["attributes"].as_object() else {
+            return Vec::new();
+        };
+        attributes
+            .iter()
+            .filter_map(|(name, spec)| self.field(name, spec, path, depth, warnings))
+            .collect()
+    }
+
+    fn field(
+        &self,
+        name: &str,
+        spec: &Value,
+        path: &str,
+        depth: usize,
+        warnings: &mut Vec<String>,
+    ) -> Option<SchemaField> {
+        let at = format!("{path}.{name}");
+        let kind = text(spec, "type").unwrap_or_default();
+        let mut converted = match kind {
+            "string" => field(name, "text"),
+            "text" => field(name, "textarea"),
+            "richtext" => {
+                warnings.push(format!("{at}: Markdown in Strapi; existing content needs converting to rich text"));
+                field(name, "richText")
+            }
+            "blocks" => field(name, "richText"),
+            "email" => field(name, "email"),
+            "uid" => SchemaField {
+                unique: true,
+                ..field(name, "text")
+            },
+            "integer" | "biginteger" | "float" | "decimal" => field(name, "number"),
+            "date" | "datetime" | "time" => field(name, "date"),
+            "boolean" => field(name, "checkbox"),
+            "json" => field(name, "json"),
+            "enumeration" => choice(name, items(&spec["enum"])),
+            "media" => relation(name, "upload", vec![MEDIA.to_string()], flag(spec, "multiple")),
+            "password" => {
+                warnings.push(format!("{at}: password fields aren't converted; use an auth collection"));
+                return None;
+            }
+            "relation" => {
+                if spec.get("mappedBy").is_some() {
+                    warnings.push(format!("{at}: inverse side of a relation, kept on the owning side only"));
+                    return None;
+                }
+                let target = text(spec, "target").unwrap_or_default();
+                let has_many = matches!(
+                    text(spec, "relation"),
+                    Some("oneToMany" | "manyToMany" | "morphToMany")
+                );
+                relation(name, "relationship", vec![self.target(target)], has_many)
+            }
+            "component" | "dynamiczone" if depth >= MAX_DEPTH => {
+                warnings.push(format!("{at}: components nested too deeply, became json"));
+                field(name, "json")
+            }
+            "component" => {
+                let uid = text(spec, "component").unwrap_or_default();
+                match self.component(uid) {
+                    Some(component) => SchemaField {
+                        fields: Some(self.fields(component, &at, depth + 1, warnings)),
+                        ..field(name, if flag(spec, "repeatable") { "array" } else { "group" })
+                    },
+                    None => {
+                        warnings.push(format!("{at}: component {uid} is not in the source, became json"));
+                        field(name, "json")
+                    }
+                }
+            }
+            "dynamiczone" => {
+                let mut blocks = Vec::new();
+                for uid in items(&spec["components"]).iter().filter_map(Value::as_str) {
+                    match self.component(uid) {
+                        Some(component) => blocks.push(SchemaBlock {
+                            slug: Case::Kebab.convert(Self::name(uid)),
+                            fields: self.fields(component, &at, depth + 1, warnings),
+                        }),
+                        None => warnings.push(format!("{at}: component {uid} is not in the source, left out")),
+                    }
+                }
+                SchemaField {
+                    blocks: Some(blocks),
+                    ..field(name, "blocks")
+                }
+            }
+            other => {
+                warnings.push(format!("{at}: Strapi type '{other}' became json"));
+                field(name, "json")
+            }
+        };
+        converted.required = flag(spec, "required");
+        converted.unique |= flag(spec, "unique");
+        converted.localized = spec["pluginOptions"]["i18n"]["localized"] == true;
+        Some(converted)
+    }
+}
+
+fn strapi(source: &Value, warnings: &mut Vec<String>) -> Result<SchemaModel, String> {
+    let strapi = Strapi::new(source);
+    if strapi.schemas.is_empty() {
+        return Err("Expected a Strapi schema.json, an array of them or an object of them by UID".to_string());
+    }
+    let mut schema = SchemaModel::default();
+    for (uid, content_type) in &strapi.schemas {
+        let Some(slug) = strapi.slugs.get(uid) else {
+            continue;
+        };
+        let fields = strapi.fields(content_type, slug, 0, warnings);
+        if content_type["kind"] == "singleType" {
+            schema.globals.push(SchemaGlobal {
+                slug: slug.clone(),
+                fields,
+            });
+        } else {
+            schema.collections.push(SchemaCollection {
+                slug: slug.clone(),
+                fields,
+                timestamps: true,
+                ..Default::default()
+            });
+        }
+    }
+    Ok(schema)
+}
+
+// Sanity
+
+const SANITY_HELPERS: [&str; 3] = ["defineType", "defineField", "defineArrayMember"];
+
+/// `code` with the `defineType`, `defineField` and `defineArrayMember`
+/// wrappers removed, and where each `defineType` object now starts.
+fn unwrap_helpers(code: &str) -> (String, Vec<usize>) {
+    let bytes = code.as_bytes();
+    let mut out = String::with_capacity(code.len());
+    let mut starts = Vec::new();
+    // Whether each open parenthesis belongs to a removed helper call.
+    let mut parens: Vec<bool> = Vec::new();
+    let mut quote: Option<u8> = None;
+    let mut pos = 0;
+    while pos < code.len() {
+        let byte = bytes[pos];
+        if let Some(open) = quote {
+            if byte == b'\\' && pos + 1 < code.len() {
+                let end = pos + 1 + code[pos + 1..].chars().next().map_or(1, char::len_utf8);
+                out.push_str(&code[pos..end]);
+                pos = end;
+                continue;
+            }
+            if byte == open {
+                quote = None;
+            }
+        } else if code[pos..].starts_with("//") || code[pos..].starts_with("/*") {
+            let end = if bytes[pos + 1] == b'/' {
+                code[pos..].find('\n').map_or(code.len(), |end| pos + end)
+            } else {
+                code[pos..].find("*/").map_or(code.len(), |end| pos + end + 2)
+            };
+            out.push_str(&code[pos..end]);
+            pos = end;
+            continue;
+        } else if matches!(byte, b'\'' | b'"' | b'`') {
+            quote = Some(byte);
+        } else if byte == b'(' {
+            parens.push(false);
+        } else if byte == b')' && parens.pop() == Some(true) {
+            pos += 1;
+            continue;
+        } else if pos == 0 || !(bytes[pos - 1].is_ascii_alphanumeric() || bytes[pos - 1] == b'_') {
+            let rest = &code[pos..];
+            if let Some(helper) = SANITY_HELPERS.iter().find(|helper| {
+                rest.strip_prefix(**helper)
+                    .is_some_and(|after| after.trim_start().starts_with('('))
+            }) {
+                let after = &rest[helper.len()..];
+                pos += helper.len() + (after.len() - after.trim_start().len()) + 1;
+                parens.push(true);
+                if *helper == "defineType" {
+                    starts.push(out.len());
+                }
+                continue;
+            }
+        }
+        let end = pos + code[pos..].chars().next().map_or(1, char::len_utf8);
+        out.push_str(&code[pos..end]);
+        pos = end;
+    }
+    (out, starts)
+}
+
+/// Type definitions of a schema file: each `defineType` object, or the
+/// default export.
+fn sanity_source(code: &str) -> Result<Vec<Value>, String> {
+    let (code, starts) = unwrap_helpers(code);
+    if starts.is_empty() {
+        return parse_config_object(&code).map(|value| vec![value]);
+    }
+    starts
+        .into_iter()
+        .map(|start| {
+            let offset = code[start..].len() - code[start..].trim_start().len();
+            parse_object_at(&code, start + offset)
+        })
+        .collect()
+}
+
+struct Sanity<'a> {
+    /// Object types, usable as a field type by name
+    objects: BTreeMap<&'a str, &'a Value>,
+    /// Collection slugs of document types
+    documents: BTreeMap<&'a str, String>,
+}
+
+impl Sanity<'_> {
+    fn fields(&self, definition: &Value, path: &str, depth: usize, warnings: &mut Vec<String>) -> Vec<SchemaField> {
+        items(&definition["fields"])
+            .iter()
+            .filter_map(|spec| {
+                let Some(name) = text(spec, "name") else {
+                    warnings.push(format!("{path}: skipped a field without a name"));
+                    return None;
+                };
+                let mut converted = self.field(name, spec, &format!("{path}.{name}"), depth, warnings);
+                // Rules are functions like `(rule) => rule.required()`, read as their source.
+                converted.required = text(spec, "validation").is_some_and(|rule| rule.contains(".required()"));
+                Some(converted)
+            })
+            .collect()
+    }
+
+    fn targets(&self, to: &Value) -> Vec<String> {
+        let to = match to {
+            Value::Array(list) => list.iter().collect(),
+            other => vec![other],
+        };
+        to.into_iter()
+            .filter_map(|target| text(target, "type"))
+            .map(|name| self.documents.get(name).cloned().unwrap_or_else(|| collection_slug(name)))
+            .collect()
+    }
+
+    fn field(&self, name: &str, spec: &Value, at: &str, depth: usize, warnings: &mut Vec<String>) -> SchemaField {
+        let kind = text(spec, "type").unwrap_or_default();
+        match kind {
+            "string" => match spec["options"]["list"].as_array() {
+                Some(list) => choice(name, list),
+                None => field(name, "text"),
+            },
+            "text" => field(name, "textarea"),
+            "number" => field(name, "number"),
+            "boolean" => field(name, "checkbox"),
+            "date" | "datetime" => field(name, "date"),
+            "url" => field(name, "text"),
+            "email" => field(name, "email"),
+            "slug" => SchemaField {
+                unique: true,
+                ..field(name, "text")
+            },
+            "geopoint" => field(name, "point"),
+            "image" | "file" => {
+                if spec.get("fields").is_some() {
+                    warnings.push(format!("{at}: extra {kind} fields belong on the media collection"));
+                }
+                relation(name, "upload", vec![MEDIA.to_string()], false)
+            }
+            "reference" => relation(name, "relationship", self.targets(&spec["to"]), false),
+            "array" => self.array(name, spec, at, depth, warnings),
+            _ if depth >= MAX_DEPTH => {
+                warnings.push(format!("{at}: objects nested too deeply, became json"));
+                field(name, "json")
+            }
+            "object" => SchemaField {
+                fields: Some(self.fields(spec, at, depth + 1, warnings)),
+                ..field(name, "group")
+            },
+            other => match self.objects.get(other) {
+                Some(object) => SchemaField {
+                    fields: Some(self.fields(object, at, depth + 1, warnings)),
+                    ..field(name, "group")
+                },
+                None => {
+                    warnings.push(format!("{at}: Sanity type '{other}' became json"));
+                    field(name, "json")
+                }
+            },
+        }
+    }
+
+    /// The fields of an array member that is an object, inline or named.
+    fn member_object<'b>(&'b self, member: &'b Value) -> Option<&'b Value> {
+        match text(member, "type")? {
+            "object" => Some(member),
+            name => self.objects.get(name).copied(),
+        }
+    }
+
+    fn array(&self, name: &str, spec: &Value, at: &str, depth: usize, warnings: &mut Vec<String>) -> SchemaField {
+        let members = items(&spec["of"]);
+        let kinds: BTreeSet<&str> = members.iter().filter_map(|member| text(member, "type")).collect();
+        let all = |wanted: &[&str]| !kinds.is_empty() && kinds.iter().all(|kind| wanted.contains(kind));
+        if kinds.contains("block") {
+            if kinds.len() > 1 {
+                warnings.push(format!("{at}: embedded non-text members of the portable text need blocks in the editor"));
+            }
+            return field(name, "richText");
+        }
+        if all(&["reference"]) {
+            let targets = members.iter().flat_map(|member| self.targets(&member["to"])).collect();
+            return relation(name, "relationship", targets, true);
+        }
+        if all(&["image", "file"]) {
+            return relation(name, "upload", vec![MEDIA.to_string()], true);
+        }
+        if all(&["string"]) {
+            let mut converted = match spec["options"]["list"].as_array() {
+                Some(list) => choice(name, list),
+                None => field(name, "text"),
+            };
+            converted.has_many = true;
+            return converted;
+        }
+        if all(&["number"]) {
+            return SchemaField {
+                has_many: true,
+                ..field(name, "number")
+            };
+        }
+        let objects: Vec<(&Value, &Value)> = members
+            .iter()
+            .filter_map(|member| Some((member, self.member_object(member)?)))
+            .collect();
+        if depth >= MAX_DEPTH || objects.is_empty() || objects.len() < members.len() {
+            warnings.push(format!("{at}: array of mixed Sanity types became json"));
+            return field(name, "json");
+        }
+        if let [(_, object)] = objects.as_slice() {
+            return SchemaField {
+                fields: Some(self.fields(object, at, depth + 1, warnings)),
+                ..field(name, "array")
+            };
+        }
+        let blocks = objects
+            .iter()
+            .map(|(member, object)| {
+                let block = text(member, "name")
+                    .or_else(|| text(member, "type").filter(|kind| *kind != "object"))
+                    .unwrap_or("block");
+                SchemaBlock {
+                    slug: Case::Kebab.convert(block),
+                    fields: self.fields(object, &format!("{at}.{block}"), depth + 1, warnings),
+                }
+            })
+            .collect();
+        SchemaField {
+            blocks: Some(blocks),
+            ..field(name, "blocks")
+        }
+    }
+}
+
+fn sanity(source: &Value, warnings: &mut Vec<String>) -> Result<SchemaModel, String> {
+    let mut definitions = Vec::new();
+    let entries = match source {
+        Value::Array(list) => list.iter().collect(),
+        other => vec![other],
+    };
+    for entry in entries {
+        match entry {
+            Value::String(code) => definitions.extend(sanity_source(code)?),
+            Value::Object(_) => definitions.push(entry.clone()),
+            _ => return Err("Sanity types must be objects or schema source strings".to_string()),
+        }
+    }
+    let mut sanity = Sanity {
+        objects: BTreeMap::new(),
+        documents: BTreeMap::new(),
+    };
+    for definition in &definitions {
+        let Some(name) = text(definition, "name") else {
+            continue;
+        };
+        if definition["type"] == "document" {
+            sanity.documents.insert(name, collection_slug(name));
+        } else {
+            sanity.objects.insert(name, definition);
+        }
+    }
+    let mut schema = SchemaModel::default();
+    for definition in &definitions {
+        let Some(slug) = text(definition, "name").and_then(|name| sanity.documents.get(name)) else {
+            continue;
+        };
+        schema.collections.push(SchemaCollection {
+            slug: slug.clone(),
+            fields: sanity.fields(definition, slug, 0, warnings),
+            timestamps: true,
+            ..Default::default()
+        });
+    }
+    Ok(schema)
+}
+
+// Rendering
+
+fn block_name(slug: &str) -> String {
+    format!("{}Block", Case::Pascal.convert(slug))
+}
+
+/// Every block of `fields`, outermost first, keeping the first definition
+/// of each slug.
+fn collect_blocks<'a>(fields: &'a [SchemaField], blocks: &mut BTreeMap<String, &'a SchemaBlock>, warnings: &mut Vec<String>) {
+    for field in fields {
+        for block in field.blocks.iter().flatten() {
+            match blocks.get(&block.slug) {
+                Some(existing) if **existing != *block => warnings.push(format!(
+                    "Block {} is defined twice with different fields; the first definition is used",
+                    block.slug
+                )),
+                Some(_) => {}
+                None => {
+                    blocks.insert(block.slug.clone(), block);
+                }
+            }
+            collect_blocks(&block.fields, blocks, warnings);
+        }
+        collect_blocks(field.fields.as_deref().unwrap_or_default(), blocks, warnings);
+    }
+}
+
+fn render_field(field: &SchemaField, indent: &str, imports: &mut BTreeSet<String>) -> String {
+    let inner = format!("{indent}  ");
+    let mut lines = vec![
+        format!("{inner}name: '{}',", field.name),
+        format!("{inner}type: '{}',", field.field_type),
+    ];
+    if let Some(targets) = &field.relation_to {
+        let target = match targets.as_slice() {
+            [single] => to_literal(&Value::String(single.clone())),
+            _ => to_literal(&Value::Array(targets.iter().cloned().map(Value::String).collect())),
+        };
+        lines.push(format!("{inner}relationTo: {target},"));
+    }
+    if let Some(options) = &field.options {
+        lines.push(format!(
+            "{inner}options: {},",
+            to_literal(&Value::Array(options.iter().cloned().map(Value::String).collect()))
+        ));
+    }
+    for (set, option) in [
+        (field.has_many, "hasMany"),
+        (field.required, "required"),
+        (field.unique, "unique"),
+        (field.localized, "localized"),
+    ] {
+        if set {
+            lines.push(format!("{inner}{option}: true,"));
+        }
+    }
+    if let Some(fields) = &field.fields {
+        lines.push(format!("{inner}fields: ["));
+        lines.extend(fields.iter().map(|sub| render_field(sub, &format!("{inner}  "), imports)));
+        lines.push(format!("{inner}],"));
+    }
+    if let Some(blocks) = &field.blocks {
+        let names: Vec<String> = blocks.iter().map(|block| block_name(&block.slug)).collect();
+        imports.extend(blocks.iter().map(|block| block.slug.clone()));
+        lines.push(format!("{inner}blocks: [{}],", names.join(", ")));
+    }
+    format!("{indent}{{\n{}\n{indent}}},", lines.join("\n"))
+}
+
+/// The `fields` property of a config, and the block imports it needs from
+/// `blocks_dir`.
+fn render_fields(fields: &[SchemaField], blocks_dir: &str) -> (String, String) {
+    let mut imports = BTreeSet::new();
+    let fields = if fields.is_empty() {
+        "  fields: [],".to_string()
+    } else {
+        let lines: Vec<String> = fields
+            .iter()
+            .map(|field| render_field(field, "    ", &mut imports))
+            .collect();
+        format!("  fields: [\n{}\n  ],", lines.join("\n"))
+    };
+    let imports = imports
+        .iter()
+        .map(|slug| format!("import {{ {} }} from '{blocks_dir}/{slug}';\n", block_name(slug)))
+        .collect();
+    (fields, imports)
+}
+
+fn render_files(schema: &SchemaModel, warnings: &mut Vec<String>) -> BTreeMap<String, String> {
+    let mut files = BTreeMap::new();
+    let mut blocks = BTreeMap::new();
+    for collection in &schema.collections {
+        let name = Case::Pascal.convert(&collection.slug);
+        let (fields, imports) = render_fields(&collection.fields, "../blocks");
+        collect_blocks(&collection.fields, &mut blocks, warnings);
+        let mut options = String::new();
+        if collection.timestamps {
+            options.push_str("  timestamps: true,\n");
+        }
+        if collection.upload {
+            options.push_str("  upload: true,\n");
+        }
+        files.insert(
+            format!("src/collections/{}.ts", collection.slug),
+            format!(
+                "import {{ CollectionConfig }} from 'payload/types';\n{imports}\nconst {name}: CollectionConfig = {{\n  slug: '{}',\n{options}{fields}\n}};\n\nexport default {name};\n",
+                collection.slug
+            ),
+        );
+    }
+    for global in &schema.globals {
+        let name = Case::Pascal.convert(&global.slug);
+        let (fields, imports) = render_fields(&global.fields, "../blocks");
+        collect_blocks(&global.fields, &mut blocks, warnings);
+        files.insert(
+            format!("src/globals/{}.ts", global.slug),
+            format!(
+                "import {{ GlobalConfig }} from 'payload/types';\n{imports}\nconst {name}: GlobalConfig = {{\n  slug: '{}',\n{fields}\n}};\n\nexport default {name};\n",
+                global.slug
+            ),
+        );
+    }
+    for (slug, block) in blocks {
+        let name = block_name(&slug);
+        let (fields, imports) = render_fields(&block.fields, ".");
+        files.insert(
+            format!("src/blocks/{slug}.ts"),
+            format!(
+                "import {{ Block }} from 'payload/types';\n{imports}\nexport const {name}: Block = {{\n  slug: '{slug}',\n{fields}\n}};\n\nexport default {name};\n"
+            ),
+        );
+    }
+    files
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn convert(format: ExternalFormat, source: Value) -> ExternalSchemaImport {
+        import_external_schema(&ExternalSchemaOptions { format, source }).unwrap()
+    }
+
+    fn summary(fields: &[SchemaField]) -> Vec<(&str, &str)> {
+        fields
+            .iter()
+            .map(|field| (field.name.as_str(), field.field_type.as_str()))
+            .collect()
+    }
+
+    #[test]
+    fn converts_contentful_content_types() {
+        let import = convert(
+            ExternalFormat::Contentful,
+            json!({ "contentTypes": [
+                { "sys": { "id": "blogPost" }, "fields": [
+                    { "id": "title", "type": "Symbol", "required": true, "localized": true },
+                    { "id": "category", "type": "Symbol", "validations": [{ "in": ["news", "guides"] }] },
+                    { "id": "hero", "type": "Link", "linkType": "Asset" },
+                    { "id": "author", "type": "Link", "linkType": "Entry", "validations": [{ "linkContentType": ["person"] }] },
+                    { "id": "tags", "type": "Array", "items": { "type": "Symbol" } },
+                    { "id": "legacy", "type": "Symbol", "omitted": true },
+                ]},
+                { "sys": { "id": "person" }, "fields": [{ "id": "name", "type": "Symbol" }] },
+            ]}),
+        );
+        let posts = import.schema.collection("blog-posts").unwrap();
+        assert_eq!(
+            summary(&posts.fields),
+            vec![("title", "text"), ("category", "select"), ("hero", "upload"), ("author", "relationship"), ("tags", "text")]
+        );
+        assert!(posts.fields[0].required && posts.fields[0].localized);
+        assert_eq!(posts.fields[3].relation_to, Some(vec!["people".to_string()]));
+        assert!(posts.fields[4].has_many);
+        assert!(import.schema.collection("media").is_some_and(|media| media.upload));
+        assert!(import.files["src/collections/blog-posts.ts"].contains("relationTo: 'people',"));
+        assert!(import.warnings.iter().any(|warning| warning.contains("legacy")));
+    }
+
+    #[test]
+    fn converts_strapi_components_and_single_types() {
+        let import = convert(
+            ExternalFormat::Strapi,
+            json!({
+                "api::article.article": {
+                    "kind": "collectionType",
+                    "info": { "singularName": "article", "pluralName": "articles" },
+                    "attributes": {
+                        "title": { "type": "string", "required": true },
+                        "slug": { "type": "uid", "targetField": "title" },
+                        "author": { "type": "relation", "relation": "manyToOne", "target": "api::writer.writer", "inversedBy": "articles" },
+                        "seo": { "type": "component", "component": "shared.seo" },
+                        "body": { "type": "dynamiczone", "components": ["sections.hero", "sections.quote"] },
+                    }
+                },
+                "api::homepage.homepage": {
+                    "kind": "singleType",
+                    "info": { "singularName": "homepage", "pluralName": "homepages" },
+                    "attributes": { "headline": { "type": "string" } }
+                },
+                "shared.seo": { "attributes": { "metaTitle": { "type": "string" } } },
+                "sections.hero": { "attributes": { "image": { "type": "media" } } },
+                "sections.quote": { "attributes": { "text": { "type": "text" } } },
+            }),
+        );
+        // Attributes come out in key order, as serde_json keeps object keys sorted.
+        let articles = import.schema.collection("articles").unwrap();
+        assert_eq!(
+            summary(&articles.fields),
+            vec![("author", "relationship"), ("body", "blocks"), ("seo", "group"), ("slug", "text"), ("title", "text")]
+        );
+        assert!(articles.fields[3].unique && articles.fields[4].required);
+        assert_eq!(articles.fields[0].relation_to, Some(vec!["writers".to_string()]));
+        assert_eq!(import.schema.globals[0].slug, "homepage");
+        assert!(import.files.contains_key("src/blocks/hero.ts"));
+        assert!(import.files["src/collections/articles.ts"].contains("blocks: [HeroBlock, QuoteBlock],"));
+        assert!(import.files["src/collections/articles.ts"].contains("import { HeroBlock } from '../blocks/hero';"));
+    }
+
+    #[test]
+    fn converts_sanity_schema_files() {
+        let post = r#"
+            import { defineArrayMember, defineField, defineType } from 'sanity'
+
+            export default defineType({
+              name: 'post',
+              type: 'document',
+              fields: [
+                defineField({ name: 'title', type: 'string', validation: (rule) => rule.required() }),
+                defineField({ name: 'slug', type: 'slug', options: { source: 'title' } }),
+                defineField({ name: 'author', type: 'reference', to: { type: 'author' } }),
+                defineField({ name: 'body', type: 'array', of: [defineArrayMember({ type: 'block' })] }),
+                defineField({ name: 'seo', type: 'seo' }),
+                defineField({
+                  name: 'sections',
+                  type: 'array',
+                  of: [defineArrayMember({ type: 'hero' }), defineArrayMember({ name: 'gallery', type: 'object', fields: [{ name: 'images', type: 'array', of: [{ type: 'image' }] }] })],
+                }),
+              ],
+            })
+        "#;
+        let import = convert(
+            ExternalFormat::Sanity,
+            json!([
+                post,
+                { "name": "author", "type": "document", "fields": [{ "name": "name", "type": "string" }] },
+                { "name": "seo", "type": "object", "fields": [{ "name": "description", "type": "text" }] },
+                { "name": "hero", "type": "object", "fields": [{ "name": "heading", "type": "string" }] },
+            ]),
+        );
+        let posts = import.schema.collection("posts").unwrap();
+        assert_eq!(
+            summary(&posts.fields),
+            vec![
+                ("title", "text"),
+                ("slug", "text"),
+                ("author", "relationship"),
+                ("body", "richText"),
+                ("seo", "group"),
+                ("sections", "blocks"),
+            ]
+        );
+        assert!(posts.fields[0].required && posts.fields[1].unique);
+        assert_eq!(posts.fields[2].relation_to, Some(vec!["authors".to_string()]));
+        let blocks: Vec<&str> = posts.fields[5].blocks.iter().flatten().map(|block| block.slug.as_str()).collect();
+        assert_eq!(blocks, vec!["hero", "gallery"]);
+        assert!(import.files.contains_key("src/blocks/gallery.ts"));
+        assert!(import.schema.collection("media").is_some());
+    }
+}
@@ -0,0 +1,318 @@
+//! Everything a site needs for the form-builder plugin: the plugin call with
+//! collection overrides, a React renderer with one component per field
+//! block, and a `/submit-form` endpoint that checks required fields before
+//! creating the submission.
+
+use std::collections::BTreeMap;
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value, json};
+
+use crate::payload_tools::{
+    email::server_url_env,
+    generator::capitalize,
+    plugins::{PayloadVersion, PluginConfig, plugin_config},
+};
+
+/// Field blocks with a renderer; `payment` needs a payment provider and is
+/// left disabled.
+pub const FORM_BLOCKS: &[&str] = &[
+    "text", "textarea", "select", "email", "state", "country", "checkbox", "number", "message",
+];
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct FormBuilderOptions {
+    /// Field blocks to enable (default all of text, textarea, select, email,
+    /// state, country, checkbox, number and message)
+    pub fields: Option<Vec<String>>,
+    /// Collections a form may redirect to after submission (default `pages`)
+    pub redirect_relationships: Option<Vec<String>>,
+    /// Admin group of the forms and submissions collections (default `Forms`)
+    pub admin_group: Option<String>,
+    /// Payload major version, "2" (default) or "3"
+    pub payload_version: Option<PayloadVersion>,
+}
+
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct FormBuilderConfig {
+    #[serde(flatten)]
+    pub plugin: PluginConfig,
+    /// Endpoints for the config's `endpoints` array, imported by `imports`
+    pub endpoints: Vec<String>,
+    /// Renderer components and the endpoint, by path from the project root
+    pub files: BTreeMap<String, String>,
+}
+
+const RICH_TEXT: &str = r#"import React from 'react';
+
+type Node = { text?: string; children?: Node[] };
+
+const textOf = (node: Node): string =>
+  node.text ?? (node.children ?? []).map(textOf).join('');
+
+/** Plain paragraphs from Slate (Payload 2) or Lexical (Payload 3) rich text. */
+export const RichText: React.FC<{ content?: unknown }> = ({ content }) => {
+  const root = content as { root?: Node } | Node[] | undefined;
+  const nodes = Array.isArray(root) ? root : root?.root?.children ?? [];
+  return (
+    <>
+      {nodes.map((node, index) => (
+        <p key={index}>{textOf(node)}</p>
+      ))}
+    </>
+  );
+};
+"#;
+
+const WRAPPER: &str = r#"import React from 'react';
+import type { FormField } from '../types';
+
+/** Label, width and error message around a field's input. */
+export const Wrapper: React.FC<{
+  field: FormField;
+  error?: string;
+  children: React.ReactNode;
+}> = ({ field, error, children }) => (
+  <div style={{ width: field.width ? `${field.width}%` : '100%' }}>
+    {field.label && (
+      <label htmlFor={field.name}>
+        {field.label}
+        {field.required && ' *'}
+      </label>
+    )}
+    {children}
+    {error && <p role="alert">{error}</p>}
+  </div>
+);
+"#;
+
+const TYPES: &str = r#"export type FormField = {
+  blockType: string;
+  name?: string;
+  label?: string;
+  required?: boolean;
+  defaultValue?: string | number | boolean;
+  width?: number;
+  options?: { label: string; value: string }[];
+  message?: unknown;
+};
+
+export type FormDoc = {
+  id: string;
+  fields: FormField[];
+  submitButtonLabel?: string;
+  confirmationType?: 'message' | 'redirect';
+  confirmationMessage?: unknown;
+  redirect?: { url?: string };
+};
+
+export type FieldProps = { field: FormField; error?: string };
+"#;
+
+/// Renderer for one field block.
+fn field_component(block: &str) -> String {
+    let name = capitalize(block);
+    let input = match block {
+        "textarea" => "<textarea id={field.name} name={field.name} required={field.required} defaultValue={field.defaultValue as string} rows={4} />".to_string(),
+        "select" => "<select id={field.name} name={field.name} required={field.required} defaultValue={field.defaultValue as string}>\n        {(field.options ?? []).map((option) => (\n          <option key={option.value} value={option.value}>\n            {option.label}\n          </option>\n        ))}\n      </select>".to_string(),
+        "checkbox" => "<input id={field.name} name={field.name} type=\"checkbox\" required={field.required} defaultChecked={Boolean(field.defaultValue)} />".to_string(),
+        "message" => {
+            return format!(
+                "import React from 'react';\nimport {{ RichText }} from '../RichText';\nimport type {{ FieldProps }} from '../types';\n\nexport const {name}: React.FC<FieldProps> = ({{ field }}) => <RichText content={{field.message}} />;\n"
+            );
+        }
+        _ => {
+            let kind = match block {
+                "email" => "email",
+                "number" => "number",
+                _ => "text",
+            };
+            let autocomplete = match block {
+                "email" => " autoComplete=\"email\"",
+                "country" => " autoComplete=\"country-name\"",
+                "state" => " autoComplete=\"address-level1\"",
+                _ => "",
+            };
+            format!(
+                "<input id={{field.name}} name={{field.name}} type=\"{kind}\"{autocomplete} required={{field.required}} defaultValue={{field.defaultValue as string}} />"
+            )
+        }
+    };
+    format!(
+        "import React from 'react';\nimport {{ Wrapper }} from './Wrapper';\nimport type {{ FieldProps }} from '../types';\n\nexport const {name}: React.FC<FieldProps> = ({{ field, error }}) => (\n  <Wrapper field={{field}} error={{error}}>\n    {input}\n  </Wrapper>\n);\n"
+    )
+}
+
+fn fields_index(blocks: &[String]) -> String {
+    let imports = blocks
+        .iter()
+        .map(|block| {
+            let name = capitalize(block);
+            format!("import {{ {name} }} from './{name}';")
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+    let entries = blocks
+        .iter()
+        .map(|block| format!("  {block}: {},", capitalize(block)))
+        .collect::<Vec<_>>()
+        .join("\n");
+    format!(
+        "import React from 'react';\n{imports}\nimport type {{ FieldProps }} from '../types';\n\n/** Renderer per `blockType`; blocks without one are skipped. */\nexport const fields: Record<string, React.FC<FieldProps>> = {{\n{entries}\n}};\n"
+    )
+}
+
+fn form_component(version: PayloadVersion) -> String {
+    let directive = match version {
+        PayloadVersion::V2 => "",
+        PayloadVersion::V3 => "'use client';\n\n",
+    };
+    format!(
+        "{directive}import React, {{ useState }} from 'react';\nimport {{ fields }} from './fields';\nimport {{ RichText }} from './RichText';\nimport type {{ FormDoc }} from './types';\n\nconst serverURL = process.env.{server_url} ?? '';\n\ntype SubmitError = {{ field?: string; message: string }};\n\nexport const Form: React.FC<{{ form: FormDoc }}> = ({{ form }}) => {{\n  const [status, setStatus] = useState<'idle' | 'submitting' | 'done'>('idle');\n  const [errors, setErrors] = useState<Record<string, string>>({{}});\n  const [error, setError] = useState<string | null>(null);\n\n  const onSubmit = async (event: React.FormEvent<HTMLFormElement>) => {{\n    event.preventDefault();\n    setStatus('submitting');\n    setErrors({{}});\n    setError(null);\n\n    const data = new FormData(event.currentTarget);\n    const submissionData = form.fields\n      .filter((field) => field.name)\n      .map((field) => {{\n        const name = field.name as string;\n        const value = field.blockType === 'checkbox' ? data.get(name) === 'on' : data.get(name) ?? '';\n        return {{ field: name, value }};\n      }});\n\n    try {{\n      const response = await fetch(`${{serverURL}}/api/submit-form`, {{\n        method: 'POST',\n        headers: {{ 'Content-Type': 'application/json' }},\n        body: JSON.stringify({{ form: form.id, submissionData }}),\n      }});\n      const result = await response.json();\n      if (!response.ok) {{\n        const failures: SubmitError[] = result.errors ?? [];\n        setErrors(\n          Object.fromEntries(\n            failures.filter((failure) => failure.field).map((failure) => [failure.field, failure.message]),\n          ),\n        );\n        setError(failures[0]?.message ?? 'Submission failed');\n        setStatus('idle');\n        return;\n      }}\n    }} catch {{\n      setError('Submission failed; check your connection and try again');\n      setStatus('idle');\n      return;\n    }}\n\n    if (form.confirmationType === 'redirect' && form.redirect?.url) {{\n      window.location.href = form.redirect.url;\n      return;\n    }}\n    setStatus('done');\n  }};\n\n  if (status === 'done') {{\n    return <RichText content={{form.confirmationMessage}} />;\n  }}\n\n  return (\n    <form onSubmit={{onSubmit}}>\n      {{form.fields.map((field, index) => {{\n        const Field = fields[field.blockType];\n        return Field ? (\n          <Field key={{field.name ?? index}} field={{field}} error={{field.name ? errors[field.name] : undefined}} />\n        ) : null;\n      }})}}\n      {{error && <p role=\"alert\">{{error}}</p>}}\n      <button type=\"submit\" disabled={{status === 'submitting'}}>\n        {{form.submitButtonLabel ?? 'Submit'}}\n      </button>\n    </form>\n  );\n}};\n\nexport default Form;\n",
+        server_url = server_url_env(version),
+    )
+}
+
+/// Root endpoint checking required fields and storing the submission.
+fn submit_endpoint(version: PayloadVersion) -> String {
+    let (import, handler, body, reply) = match version {
+        PayloadVersion::V2 => (
+            "import { Endpoint } from 'payload/config';",
+            "async (req, res) =>",
+            "req.body ?? {}",
+            "res.status(status).json(body)",
+        ),
+        PayloadVersion::V3 => (
+            "import type { Endpoint } from 'payload';",
+            "async (req) =>",
+            "(req.json ? await req.json() : {})",
+            "Response.json(body, { status })",
+        ),
+    };
+    format!(
+        "{import}\n\ntype SubmissionField = {{ field: string; value: unknown }};\n\ntype FormField = {{ name?: string; required?: boolean }};\n\nexport const submitForm: Endpoint = {{\n  path: '/submit-form',\n  method: 'post',\n  handler: {handler} {{\n    const reply = (status: number, body: unknown) => {reply};\n    const {{ form: formID, submissionData }} = {body};\n    if (!formID || !Array.isArray(submissionData)) {{\n      return reply(400, {{ errors: [{{ message: 'form and submissionData are required' }}] }});\n    }}\n\n    try {{\n      const form = await req.payload.findByID({{ collection: 'forms', id: formID, req }});\n      const values = new Map<string, unknown>(\n        (submissionData as SubmissionField[]).map(({{ field, value }}) => [field, value]),\n      );\n      const missing = ((form.fields ?? []) as FormField[]).filter(\n        (field) => field.name && field.required && [undefined, null, '', false].includes(values.get(field.name) as never),\n      );\n      if (missing.length > 0) {{\n        return reply(400, {{\n          errors: missing.map((field) => ({{ field: field.name, message: 'This field is required' }})),\n        }});\n      }}\n\n      const submission = await req.payload.create({{\n        collection: 'form-submissions',\n        data: {{\n          form: formID,\n          submissionData: (submissionData as SubmissionField[]).map(({{ field, value }}) => ({{\n            field,\n            value: String(value ?? ''),\n          }})),\n        }},\n        req,\n      }});\n\n      return reply(201, {{\n        id: submission.id,\n        confirmationType: form.confirmationType,\n        confirmationMessage: form.confirmationMessage,\n        redirect: form.redirect,\n      }});\n    }} catch (error) {{\n      req.payload.logger.error(`Form submission failed: ${{error}}`);\n      return reply(500, {{ errors: [{{ message: 'Could not submit the form' }}] }});\n    }}\n  }},\n}};\n\nexport default submitForm;\n"
+    )
+}
+
+/// Plugin call, renderer and endpoint for the form-builder plugin.
+pub fn form_builder_config(options: &FormBuilderOptions) -> Result<FormBuilderConfig, String> {
+    let version = options.payload_version.unwrap_or_default();
+    let blocks: Vec<String> = match &options.fields {
+        Some(fields) if !fields.is_empty() => fields.clone(),
+        _ => FORM_BLOCKS.iter().map(|block| block.to_string()).collect(),
+    };
+    if let Some(unknown) = blocks
+        .iter()
+        .find(|block| !FORM_BLOCKS.contains(&block.as_str()))
+    {
+        return Err(format!(
+            "Unknown form field block '{unknown}'; expected one of {}",
+            FORM_BLOCKS.join(", ")
+        ));
+    }
+    let group = options.admin_group.as_deref().unwrap_or("Forms");
+
+    let enabled: Map<String, Value> = FORM_BLOCKS
+        .iter()
+        .chain(["payment"].iter())
+        .map(|block| (block.to_string(), json!(blocks.iter().any(|b| b == block))))
+        .collect();
+    let mut plugin_options = Map::new();
+    plugin_options.insert("fields".to_string(), Value::Object(enabled));
+    plugin_options.insert(
+        "formOverrides".to_string(),
+        json!({ "admin": { "group": group } }),
+    );
+    plugin_options.insert(
+        "formSubmissionOverrides".to_string(),
+        json!({ "admin": { "group": group, "defaultColumns": ["form", "createdAt"] } }),
+    );
+    if let Some(relationships) = &options.redirect_relationships {
+        plugin_options.insert("redirectRelationships".to_string(), json!(relationships));
+    }
+    let mut plugin = plugin_config("form-builder", version, &plugin_options, "")?;
+    plugin
+        .imports
+        .push("import { submitForm } from './endpoints/submitForm';".to_string());
+    plugin.env.push(server_url_env(version).to_string());
+
+    let mut files = BTreeMap::from([
+        (
+            "src/endpoints/submitForm.ts".to_string(),
+            submit_endpoint(version),
+        ),
+        (
+            "src/components/Form/index.tsx".to_string(),
+            form_component(version),
+        ),
+        (
+            "src/components/Form/types.ts".to_string(),
+            TYPES.to_string(),
+        ),
+        (
+            "src/components/Form/RichText.tsx".to_string(),
+            RICH_TEXT.to_string(),
+        ),
+        (
+            "src/components/Form/fields/Wrapper.tsx".to_string(),
+            WRAPPER.to_string(),
+        ),
+        (
+            "src/components/Form/fields/index.tsx".to_string(),
+            fields_index(&blocks),
+        ),
+    ]);
+    for block in &blocks {
+        files.insert(
+            format!("src/components/Form/fields/{}.tsx", capitalize(block)),
+            field_component(block),
+        );
+    }
+
+    Ok(FormBuilderConfig {
+        plugin,
+        endpoints: vec!["submitForm".to_string()],
+        files,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_form_builder_config_covers_enabled_blocks() {
+        let options = FormBuilderOptions {
+            fields: Some(vec!["text".to_string(), "select".to_string()]),
+            redirect_relationships: None,
+            admin_group: None,
+            payload_version: Some(PayloadVersion::V3),
+        };
+        let config = form_builder_config(&options).unwrap();
+        assert!(config.plugin.code.starts_with("formBuilderPlugin({"));
+        assert!(config.plugin.code.contains("text: true"));
+        assert!(config.plugin.code.contains("payment: false"));
+        assert!(
+            config
+                .files
+                .contains_key("src/components/Form/fields/Select.tsx")
+        );
+        assert!(
+            !config
+                .files
+                .contains_key("src/components/Form/fields/Email.tsx")
+        );
+        assert!(config.files["src/components/Form/index.tsx"].starts_with("'use client';"));
+        assert!(
+            config.files["src/endpoints/submitForm.ts"].contains("Response.json(body, { status })")
+        );
+
+        let payment = FormBuilderOptions {
+            fields: Some(vec!["payment".to_string()]),
+            ..options
+        };
+        assert!(form_builder_config(&payment).is_err());
+    }
+}
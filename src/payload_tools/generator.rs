@@ -1,8 +1,21 @@
+use std::sync::OnceLock;
+
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use serde_json::{json, Map, Value};
 
-#[derive(Clone, Copy, Debug, Serialize, Deserialize, JsonSchema)]
+use crate::payload_tools::cache::{CacheStats, MemoCache};
+use crate::payload_tools::client_sdk::document_type;
+use crate::payload_tools::email::server_url_env;
+use crate::payload_tools::json_schema::schema_name;
+use crate::payload_tools::naming::{self, NamingProfile};
+use crate::payload_tools::plugins::{plugin as plugin_registry, plugin_config, PayloadVersion};
+use crate::payload_tools::schemas::require_field_type;
+use crate::payload_tools::storage_adapters::{storage_adapter_config, StorageAdapterOptions};
+use crate::payload_tools::template_overrides;
+use crate::payload_tools::types::FileType;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "kebab-case")]
 pub enum TemplateType {
     Collection,
@@ -15,9 +28,236 @@ pub enum TemplateType {
     Plugin,
     Block,
     Migration,
+    DataAccess,
+    FieldEncryption,
+    ScheduledPublish,
+    Security,
 }
 
+impl TemplateType {
+    pub const ALL: [TemplateType; 14] = [
+        TemplateType::Collection,
+        TemplateType::Field,
+        TemplateType::Global,
+        TemplateType::Config,
+        TemplateType::AccessControl,
+        TemplateType::Hook,
+        TemplateType::Endpoint,
+        TemplateType::Plugin,
+        TemplateType::Block,
+        TemplateType::Migration,
+        TemplateType::DataAccess,
+        TemplateType::FieldEncryption,
+        TemplateType::ScheduledPublish,
+        TemplateType::Security,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TemplateType::Collection => "collection",
+            TemplateType::Field => "field",
+            TemplateType::Global => "global",
+            TemplateType::Config => "config",
+            TemplateType::AccessControl => "access-control",
+            TemplateType::Hook => "hook",
+            TemplateType::Endpoint => "endpoint",
+            TemplateType::Plugin => "plugin",
+            TemplateType::Block => "block",
+            TemplateType::Migration => "migration",
+            TemplateType::DataAccess => "data-access",
+            TemplateType::FieldEncryption => "field-encryption",
+            TemplateType::ScheduledPublish => "scheduled-publish",
+            TemplateType::Security => "security",
+        }
+    }
+
+    pub fn description(&self) -> &'static str {
+        match self {
+            TemplateType::Collection => "Collection config with fields, admin, access, hooks and versions",
+            TemplateType::Field => "Single field definition",
+            TemplateType::Global => "Global config with fields, access and versions",
+            TemplateType::Config => "Root payload.config.ts with db adapter, collections, globals and plugins",
+            TemplateType::AccessControl => "Role-based access control function",
+            TemplateType::Hook => "Before/after operation hook",
+            TemplateType::Endpoint => "Custom REST endpoint handler",
+            TemplateType::Plugin => "Plugin that extends the incoming config",
+            TemplateType::Block => "Block definition for blocks fields",
+            TemplateType::Migration => "Data migration using the Local API",
+            TemplateType::DataAccess => "Next.js data access for a collection or global: cached server actions or React Query hooks",
+            TemplateType::FieldEncryption => "beforeChange/afterRead hooks encrypting sensitive fields at rest with Node crypto",
+            TemplateType::ScheduledPublish => "Payload 3 publishAt/unpublishAt fields with a jobs queue task and cron that publish and unpublish on schedule",
+            TemplateType::Security => "csrf, cors and rate limiting settings with security header middleware for Next.js (Payload 3) or Express (Payload 2)",
+        }
+    }
+
+    /// Option keys accepted in `options` for this template, with a short
+    /// description of each.
+    pub fn options(&self) -> &'static [(&'static str, &'static str)] {
+        match self {
+            TemplateType::Collection => &[
+                ("slug", "Collection slug (required)"),
+                ("fields", "Array of field definitions"),
+                ("auth", "Enable authentication on the collection (default false)"),
+                ("timestamps", "Add createdAt/updatedAt timestamps (default true)"),
+                ("hooks", "Add beforeOperation/afterOperation hook placeholders (default false)"),
+                ("access", "Add role-based access functions (default false)"),
+                ("versions", "Enable versions and drafts (default false)"),
+                ("softDelete", "Soft-delete documents: a deletedAt field, reads hiding deleted documents from non-admins, and soft-delete and restore endpoints (default false)"),
+                ("admin", "Admin options: useAsTitle, defaultColumns, group"),
+            ],
+            TemplateType::Field => &[
+                ("name", "Field name (required except for row, collapsible and tabs)"),
+                ("type", "Payload field type (required)"),
+                ("required", "Mark the field as required (default false)"),
+                ("unique", "Enforce a unique index (default false)"),
+                ("localized", "Store a value per locale (default false)"),
+                ("access", "Add field-level access functions (default false)"),
+                ("validation", "Add a validate function placeholder (default false)"),
+                ("defaultValue", "Default value for new documents"),
+                ("admin", "Admin options: description, readOnly"),
+            ],
+            TemplateType::Global => &[
+                ("slug", "Global slug (required)"),
+                ("fields", "Array of field definitions"),
+                ("access", "Add role-based access functions (default false)"),
+                ("versions", "Enable versions and drafts (default false)"),
+                ("admin", "Admin options: group"),
+            ],
+            TemplateType::Config => &[
+                ("serverURL", "Public server URL (default http://localhost:3000)"),
+                ("collections", "Collection names to import and register"),
+                ("globals", "Global names to import and register"),
+                ("plugins", "Plugin names, or { name, options } objects, to import and register"),
+                ("storage", "Storage adapter for uploads: provider (s3, r2, gcs, azure), collections, bucket, prefix, acl, signedUrls"),
+                ("db", "Database adapter: mongodb or postgres (default mongodb)"),
+                ("typescript", "Emit payload-types.ts output settings (default true)"),
+                ("admin", "Admin options: user, bundler (webpack or vite)"),
+            ],
+            TemplateType::AccessControl => &[
+                ("name", "Access function name (default \"default\")"),
+                ("roles", "Roles allowed through the access check"),
+            ],
+            TemplateType::Hook => &[
+                ("type", "Entity the hook belongs to: collection, global or field"),
+                ("name", "Hook name"),
+                ("operation", "Operation the hook handles (default create)"),
+                ("timing", "before or after (default before)"),
+            ],
+            TemplateType::Endpoint => &[
+                ("path", "Endpoint path (default /api/custom)"),
+                ("method", "HTTP method (default get)"),
+            ],
+            TemplateType::Plugin => &[("name", "Plugin name (default custom-plugin)")],
+            TemplateType::Block => &[
+                ("name", "Block slug (default custom-block)"),
+                ("imageField", "Include an image upload field (default true)"),
+                ("contentField", "Include a rich text content field (default true)"),
+            ],
+            TemplateType::Migration => &[
+                ("name", "Migration name (default custom-migration)"),
+                ("collection", "Collection the migration operates on"),
+                ("operation", "Operation applied to each document: update, delete, or purge to delete soft-deleted documents (default update)"),
+                ("retentionDays", "Days soft-deleted documents are kept before purge deletes them (default 30)"),
+            ],
+            TemplateType::DataAccess => &[
+                ("slug", "Collection or global slug (required)"),
+                ("global", "The slug is a global (default false)"),
+                ("mode", "server-actions, cached with tags, or react-query (default server-actions)"),
+                ("apiRoute", "Payload's routes.api, for react-query (default /api)"),
+            ],
+            TemplateType::FieldEncryption => &[
+                ("fields", "Names of the sensitive fields to encrypt"),
+                ("envVar", "Environment variable holding the encryption key (default PAYLOAD_ENCRYPTION_KEY)"),
+            ],
+            TemplateType::ScheduledPublish => &[
+                ("collections", "Slugs of the collections to schedule (required)"),
+                ("cron", "How often due documents are checked (default */5 * * * *)"),
+                ("queue", "Jobs queue the task runs on (default scheduled-publish)"),
+                ("statusField", "Select field holding draft or published (default _status, Payload's drafts status)"),
+            ],
+            TemplateType::Security => &[
+                ("target", "Deployment target: nextjs (Payload 3) or express (Payload 2) (default nextjs)"),
+                ("origins", "Origins allowed by csrf and cors (default the server URL from the environment)"),
+                ("max", "Requests per client IP in each window (default 500)"),
+                ("windowMinutes", "Length of the rate limit window in minutes (default 15)"),
+            ],
+        }
+    }
+
+    /// The kind of file the validator checks this template's output as, if any.
+    pub fn file_type(&self) -> Option<FileType> {
+        match self {
+            TemplateType::Collection => Some(FileType::Collection),
+            TemplateType::Field | TemplateType::Block => Some(FileType::Field),
+            TemplateType::Global => Some(FileType::Global),
+            TemplateType::Config => Some(FileType::Config),
+            TemplateType::AccessControl
+            | TemplateType::Plugin
+            | TemplateType::Hook
+            | TemplateType::Endpoint
+            | TemplateType::Migration
+            | TemplateType::DataAccess
+            | TemplateType::FieldEncryption
+            | TemplateType::ScheduledPublish
+            | TemplateType::Security => None,
+        }
+    }
+}
+
+impl std::str::FromStr for TemplateType {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        TemplateType::ALL
+            .into_iter()
+            .find(|t| t.as_str().eq_ignore_ascii_case(s))
+            .ok_or_else(|| format!("Unknown template type: {s}"))
+    }
+}
+
+/// Generated templates kept for repeated calls with the same options.
+const TEMPLATE_CACHE_SIZE: usize = 256;
+
+fn template_cache() -> &'static MemoCache<String> {
+    static CACHE: OnceLock<MemoCache<String>> = OnceLock::new();
+    CACHE.get_or_init(|| MemoCache::new(TEMPLATE_CACHE_SIZE))
+}
+
+pub fn template_cache_stats() -> CacheStats {
+    template_cache().stats()
+}
+
+/// Generate a template with the configured naming profile; see
+/// [`generate_template_with_naming`].
 pub fn generate_template(template_type: TemplateType, options: &Value) -> Result<String, String> {
+    generate_template_with_naming(template_type, options, &naming::active())
+}
+
+/// Generate a template with names rewritten to fit `naming`, rendering the
+/// user's override for the type when the templates directory has one (see
+/// [`template_overrides`]) and the built-in template otherwise.
+pub fn generate_template_with_naming(
+    template_type: TemplateType,
+    options: &Value,
+    naming: &NamingProfile,
+) -> Result<String, String> {
+    let options = naming.apply(template_type, options);
+    match template_overrides::render(template_type, &options)? {
+        Some(code) => Ok(code),
+        None => builtin_template(template_type, &options),
+    }
+}
+
+/// Generate the built-in template, reusing the code from an earlier call with
+/// the same type and options. Object keys are serialized in sorted order, so
+/// options that differ only in key order share an entry.
+pub(crate) fn builtin_template(template_type: TemplateType, options: &Value) -> Result<String, String> {
+    template_cache().get_or_try_insert(&(template_type.as_str(), options.to_string()), || {
+        render_template(template_type, options)
+    })
+}
+
+fn render_template(template_type: TemplateType, options: &Value) -> Result<String, String> {
     let map = options
         .as_object()
         .ok_or_else(|| "Template options must be an object".to_string())?;
@@ -33,6 +273,10 @@ pub fn generate_template(template_type: TemplateType, options: &Value) -> Result
         TemplateType::Plugin => generate_plugin_template(map),
         TemplateType::Block => generate_block_template(map),
         TemplateType::Migration => generate_migration_template(map),
+        TemplateType::DataAccess => generate_data_access_template(map),
+        TemplateType::FieldEncryption => generate_field_encryption_template(map),
+        TemplateType::ScheduledPublish => generate_scheduled_publish_template(map),
+        TemplateType::Security => generate_security_template(map),
     }
 }
 
@@ -76,6 +320,7 @@ fn generate_collection_template(options: &Map<String, Value>) -> Result<String,
     let hooks = get_bool(options, "hooks", false);
     let access = get_bool(options, "access", false);
     let versions = get_bool(options, "versions", false);
+    let soft_delete = get_bool(options, "softDelete", false);
 
     let admin = options
         .get("admin")
@@ -83,15 +328,13 @@ fn generate_collection_template(options: &Map<String, Value>) -> Result<String,
         .cloned()
         .unwrap_or_default();
 
-    let fields_code = if fields.is_empty() {
-        String::new()
-    } else {
-        let mut lines = Vec::new();
-        for field in fields {
-            lines.push(generate_field_template_from_value(&field)?);
+    let mut fields_code = fields_code(&fields)?;
+    if soft_delete {
+        if !fields_code.is_empty() {
+            fields_code.push_str(",\n    ");
         }
-        lines.join(",\n    ")
-    };
+        fields_code.push_str(SOFT_DELETE_FIELD);
+    }
 
     let admin_code = if admin.is_empty() {
         String::new()
@@ -123,7 +366,9 @@ fn generate_collection_template(options: &Map<String, Value>) -> Result<String,
         String::new()
     };
 
-    let access_code = if access {
+    let access_code = if soft_delete {
+        SOFT_DELETE_ACCESS.to_string()
+    } else if access {
         "\n  access: {\n    read: () => true,\n    update: () => true,\n    create: () => true,\n    delete: () => true,\n  },"
             .to_string()
     } else {
@@ -143,8 +388,14 @@ fn generate_collection_template(options: &Map<String, Value>) -> Result<String,
         String::new()
     };
 
+    let endpoints_code = if soft_delete {
+        soft_delete_endpoints(&slug)
+    } else {
+        String::new()
+    };
+
     Ok(format!(
-        "import {{ CollectionConfig }} from 'payload/types';\n\nconst {}: CollectionConfig = {{\n  slug: '{}',{}{}{}{}{}\n  {}fields: [\n    {}\n  ],\n}};\n\nexport default {};",
+        "import {{ CollectionConfig }} from 'payload/types';\n\nconst {}: CollectionConfig = {{\n  slug: '{}',{}{}{}{}{}{}\n  {}fields: [\n    {}\n  ],\n}};\n\nexport default {};",
         capitalize(&slug),
         slug,
         admin_code,
@@ -152,12 +403,46 @@ fn generate_collection_template(options: &Map<String, Value>) -> Result<String,
         access_code,
         hooks_code,
         versions_code,
+        endpoints_code,
         if timestamps { "timestamps: true,\n  " } else { "" },
         fields_code,
         capitalize(&slug)
     ))
 }
 
+/// Set by the soft-delete endpoint and cleared by restore; documents with a
+/// value are hidden from everyone but admins.
+const SOFT_DELETE_FIELD: &str = "{\n      name: 'deletedAt',\n      type: 'date',\n      index: true,\n      admin: {\n        position: 'sidebar',\n        readOnly: true,\n        description: 'Set when the document is soft-deleted; restore clears it',\n      },\n    }";
+
+/// Only admins read deleted documents or delete for good; everyone else goes
+/// through the soft-delete endpoint.
+const SOFT_DELETE_ACCESS: &str = "\n  access: {\n    read: ({ req: { user } }) => (user?.role === 'admin' ? true : { deletedAt: { equals: null } }),\n    update: () => true,\n    create: () => true,\n    delete: ({ req: { user } }) => user?.role === 'admin',\n  },";
+
+/// `POST /:id/soft-delete` and `POST /:id/restore`, setting and clearing
+/// `deletedAt` with the user's update access.
+fn soft_delete_endpoints(slug: &str) -> String {
+    let endpoint = |path: &str, value: &str| {
+        format!(
+            "\n    {{\n      path: '/:id/{path}',\n      method: 'post',\n      handler: async (req, res) => {{\n        if (!req.user) {{\n          return res.status(401).json({{ message: 'Unauthorized' }});\n        }}\n        try {{\n          const doc = await req.payload.update({{\n            collection: '{slug}',\n            id: req.params.id,\n            data: {{ deletedAt: {value} }},\n            overrideAccess: false,\n            user: req.user,\n            req,\n          }});\n          return res.status(200).json({{ doc }});\n        }} catch (error) {{\n          return res.status(error.status ?? 500).json({{ message: error.message }});\n        }}\n      }},\n    }},"
+        )
+    };
+    format!(
+        "\n  endpoints: [{}{}\n  ],",
+        endpoint("soft-delete", "new Date().toISOString()"),
+        endpoint("restore", "null")
+    )
+}
+
+/// Field definitions rendered by the field template, joined for the body of
+/// a `fields` array.
+pub(crate) fn fields_code(fields: &[Value]) -> Result<String, String> {
+    let lines = fields
+        .iter()
+        .map(generate_field_template_from_value)
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(lines.join(",\n    "))
+}
+
 fn generate_field_template(options: &Map<String, Value>) -> Result<String, String> {
     generate_field_template_from_value(&Value::Object(options.clone()))
 }
@@ -166,8 +451,15 @@ fn generate_field_template_from_value(value: &Value) -> Result<String, String> {
     let map = value
         .as_object()
         .ok_or_else(|| "Field options must be an object".to_string())?;
-    let name = get_string(map, "name").ok_or("Field name is required")?;
     let field_type = get_string(map, "type").ok_or("Field type is required")?;
+    let info = require_field_type(&field_type)?;
+    // Presentational fields (row, collapsible, tabs) store nothing and take no name.
+    let name_code = if info.stores_data {
+        let name = get_string(map, "name").ok_or("Field name is required")?;
+        format!("\n    name: '{name}',")
+    } else {
+        String::new()
+    };
 
     let required = get_bool(map, "required", false);
     let unique = get_bool(map, "unique", false);
@@ -213,32 +505,15 @@ fn generate_field_template_from_value(value: &Value) -> Result<String, String> {
         String::new()
     };
 
-    let default_value_code = default_value.map(|v| {
-        format!(
-            "\n    defaultValue: {},",
-            if v.is_string() {
-                value_to_literal(v)
-            } else {
-                value_to_literal(v)
-            }
-        )
-    });
+    let default_value_code = default_value.map(|v| format!("\n    defaultValue: {},", value_to_literal(v)));
 
-    let field_specific = match field_type.as_str() {
-        "text" | "textarea" | "email" | "code" => "\n    minLength: 1,\n    maxLength: 255,".to_string(),
-        "number" => "\n    min: 0,\n    max: 1000,".to_string(),
-        "select" => "\n    options: [\n      { label: 'Option 1', value: 'option1' },\n      { label: 'Option 2', value: 'option2' },\n    ],\n    hasMany: false,".to_string(),
-        "relationship" => "\n    relationTo: 'collection-name',\n    hasMany: false,".to_string(),
-        "array" => "\n    minRows: 0,\n    maxRows: 10,\n    fields: [\n      {\n        name: 'subField',\n        type: 'text',\n        required: true,\n      },\n    ],".to_string(),
-        "blocks" => "\n    blocks: [\n      {\n        slug: 'block-name',\n        fields: [\n          {\n            name: 'blockField',\n            type: 'text',\n            required: true,\n          },\n        ],\n      },\n    ],".to_string(),
-        _ => String::new(),
-    };
+    let field_specific = field_specific_code(map, &field_type).unwrap_or_else(|| info.example.to_string());
 
     let default_and_specific = default_value_code.unwrap_or_default() + &field_specific;
 
     Ok(format!(
-        "{{\n    name: '{name}',\n    type: '{field_type}',{required}{unique}{localized}{admin}{access}{validation}{default_and_specific}\n  }}",
-        name = name,
+        "{{{name_code}\n    type: '{field_type}',{required}{unique}{localized}{admin}{access}{validation}{default_and_specific}\n  }}",
+        name_code = name_code,
         field_type = field_type,
         required = if required { "\n    required: true," } else { "" },
         unique = if unique { "\n    unique: true," } else { "" },
@@ -250,6 +525,45 @@ fn generate_field_template_from_value(value: &Value) -> Result<String, String> {
     ))
 }
 
+/// Relationship targets and choice options given in the field options, in
+/// place of the placeholders of the field type's example.
+fn field_specific_code(map: &Map<String, Value>, field_type: &str) -> Option<String> {
+    let has_many = get_bool(map, "hasMany", false);
+    match field_type {
+        "relationship" | "upload" => {
+            let relation_to = map.get("relationTo")?;
+            let mut code = format!("\n    relationTo: {},", value_to_literal(relation_to));
+            if field_type == "relationship" || has_many {
+                code.push_str(&format!("\n    hasMany: {has_many},"));
+            }
+            Some(code)
+        }
+        "select" | "radio" => {
+            let options: String = get_array(map, "options")?
+                .iter()
+                .filter_map(|option| {
+                    let value = option.as_str().or_else(|| option.get("value")?.as_str())?;
+                    let label = option
+                        .get("label")
+                        .and_then(|v| v.as_str())
+                        .map_or_else(|| capitalize_words(&value.replace(['-', '_'], " ")), str::to_string);
+                    Some(format!(
+                        "\n      {{ label: {}, value: {} }},",
+                        value_to_literal(&json!(label)),
+                        value_to_literal(&json!(value))
+                    ))
+                })
+                .collect();
+            let mut code = format!("\n    options: [{options}\n    ],");
+            if field_type == "select" {
+                code.push_str(&format!("\n    hasMany: {has_many},"));
+            }
+            Some(code)
+        }
+        _ => None,
+    }
+}
+
 fn generate_global_template(options: &Map<String, Value>) -> Result<String, String> {
     let slug = get_string(options, "slug").ok_or("Global slug is required")?;
     let fields = get_array(options, "fields").cloned().unwrap_or_default();
@@ -261,15 +575,7 @@ fn generate_global_template(options: &Map<String, Value>) -> Result<String, Stri
         .cloned()
         .unwrap_or_default();
 
-    let fields_code = if fields.is_empty() {
-        String::new()
-    } else {
-        let mut lines = Vec::new();
-        for field in fields {
-            lines.push(generate_field_template_from_value(&field)?);
-        }
-        lines.join(",\n    ")
-    };
+    let fields_code = fields_code(&fields)?;
 
     let admin_code = if admin.is_empty() {
         String::new()
@@ -336,34 +642,45 @@ fn generate_config_template(options: &Map<String, Value>) -> Result<String, Stri
             .join("\n")
     };
 
-    let plugins_imports = plugins
-        .iter()
-        .filter_map(|p| p.as_str())
-        .map(|plugin| match plugin {
-            "form-builder" => "import formBuilder from '@payloadcms/plugin-form-builder';".to_string(),
-            "seo" => "import seoPlugin from '@payloadcms/plugin-seo';".to_string(),
-            "nested-docs" => "import nestedDocs from '@payloadcms/plugin-nested-docs';".to_string(),
-            other => format!("import {} from '@payloadcms/plugin-{}';", other, other),
-        })
-        .collect::<Vec<_>>()
-        .join("\n");
+    // Registry plugins get their release's imports and default options;
+    // other names fall back to `@payloadcms/plugin-<name>`.
+    let mut plugin_imports: Vec<String> = Vec::new();
+    let mut plugin_calls = Vec::new();
+    for plugin in &plugins {
+        let (name, plugin_options) = match plugin {
+            Value::String(name) => (name.as_str(), Map::new()),
+            Value::Object(map) => (
+                map.get("name").and_then(|v| v.as_str()).ok_or("Plugin entries need a name")?,
+                map.get("options").and_then(|v| v.as_object()).cloned().unwrap_or_default(),
+            ),
+            _ => continue,
+        };
+        if plugin_registry(name).is_some() {
+            let config = plugin_config(name, PayloadVersion::V2, &plugin_options, "    ")?;
+            for import in config.imports {
+                if !plugin_imports.contains(&import) {
+                    plugin_imports.push(import);
+                }
+            }
+            plugin_calls.push(config.code);
+        } else {
+            plugin_imports.push(format!("import {} from '@payloadcms/plugin-{}';", name, name));
+            plugin_calls.push(format!("{}(),", name));
+        }
+    }
+    if let Some(storage) = options.get("storage") {
+        let storage: StorageAdapterOptions = serde_json::from_value(storage.clone())
+            .map_err(|err| format!("Invalid storage options: {err}"))?;
+        let config = storage_adapter_config(&storage, "    ")?;
+        plugin_imports.extend(config.imports);
+        plugin_calls.push(config.code);
+    }
+    let plugins_imports = plugin_imports.join("\n");
 
-    let plugins_init = if plugins.is_empty() {
+    let plugins_init = if plugin_calls.is_empty() {
         String::new()
     } else {
-        let mut parts = Vec::new();
-        for plugin in plugins.iter().filter_map(|p| p.as_str()) {
-            let code = match plugin {
-                "form-builder" => "formBuilder({\n      formOverrides: {\n        admin: {\n          group: 'Content',\n        },\n      },\n      formSubmissionOverrides: {\n        admin: {\n          group: 'Content',\n        },\n      },\n      redirectRelationships: ['pages'],\n    }),"
-                    .to_string(),
-                "seo" => "seoPlugin(),".to_string(),
-                "nested-docs" => "nestedDocs({\n      collections: ['pages'],\n    }),"
-                    .to_string(),
-                other => format!("{}(),", other),
-            };
-            parts.push(code);
-        }
-        format!("\n  plugins: [\n    {}\n  ],", parts.join("\n    "))
+        format!("\n  plugins: [\n    {}\n  ],", plugin_calls.join("\n    "))
     };
 
     let collections_init = if collections.is_empty() {
@@ -616,11 +933,7 @@ fn generate_block_template(options: &Map<String, Value>) -> Result<String, Strin
     let fields_code = if fields.is_empty() {
         String::new()
     } else {
-        let mut parts = Vec::new();
-        for field in fields {
-            parts.push(generate_field_template_from_value(&field)?);
-        }
-        parts.join(",\n    ")
+        format!("{},", fields_code(&fields)?)
     };
 
     let image_code = if image_field {
@@ -672,6 +985,14 @@ fn generate_migration_template(options: &Map<String, Value>) -> Result<String, S
 
     let body = if collection.is_empty() {
         "// Add your migration logic here\n    // This could be schema changes, data transformations, etc.\n    ".to_string()
+    } else if operation == "purge" {
+        let days = options
+            .get("retentionDays")
+            .and_then(|v| v.as_u64())
+            .unwrap_or(30);
+        format!(
+            "// Delete documents soft-deleted more than {days} days ago\n    const collection = '{collection}';\n    const cutoff = new Date(Date.now() - {days} * 24 * 60 * 60 * 1000).toISOString();\n    \n    const result = await payload.delete({{\n      collection,\n      where: {{\n        deletedAt: {{\n          less_than: cutoff,\n        }},\n      }},\n    }});\n    \n    console.log(`Purged ${{result.docs.length}} soft-deleted documents`);\n    ",
+        )
     } else if operation == "delete" {
         format!(
             "// Get the collection\n    const collection = '{collection}';\n    \n    const docs = await payload.find({{\n      collection,\n      limit: 100,\n    }});\n    \n    console.log(`Found ${{docs.docs.length}} documents to migrate`);\n    \n    for (const doc of docs.docs) {{\n      await payload.delete({{\n        collection,\n        id: doc.id,\n      }});\n    }}\n    ",
@@ -692,7 +1013,613 @@ fn generate_migration_template(options: &Map<String, Value>) -> Result<String, S
     ))
 }
 
-fn capitalize(value: &str) -> String {
+fn generate_data_access_template(options: &Map<String, Value>) -> Result<String, String> {
+    let slug = get_string(options, "slug").ok_or("Collection or global slug is required")?;
+    let global = get_bool(options, "global", false);
+    let api_route = get_string(options, "apiRoute").unwrap_or_else(|| "/api".to_string());
+    match get_string(options, "mode").as_deref().unwrap_or("server-actions") {
+        "server-actions" if global => Ok(global_server_actions(&slug)),
+        "server-actions" => Ok(collection_server_actions(&slug)),
+        "react-query" if global => Ok(global_query_hooks(&slug, &api_route)),
+        "react-query" => Ok(collection_query_hooks(&slug, &api_route)),
+        mode => Err(format!("Unknown data access mode: {mode} (expected server-actions or react-query)")),
+    }
+}
+
+/// Plural and singular names of a collection's functions; the plural gets an
+/// "All" prefix when both are the same, e.g. `useAllMedia` and `useMedia`.
+fn collection_function_names(slug: &str) -> (String, String) {
+    let plural = schema_name(slug);
+    let singular = document_type(slug);
+    if plural == singular {
+        (format!("All{plural}"), singular)
+    } else {
+        (plural, singular)
+    }
+}
+
+fn collection_server_actions(slug: &str) -> String {
+    let (plural, singular) = collection_function_names(slug);
+    format!(
+        "'use server'
+
+import config from '@payload-config'
+import {{ revalidateTag, unstable_cache }} from 'next/cache'
+import {{ getPayload, type RequiredDataFromCollectionSlug, type Where }} from 'payload'
+
+type ID = number | string
+
+// Cache tags: '{slug}' covers every query on the collection, '{slug}:<id>' one document.
+const listTag = '{slug}'
+const documentTag = (id: ID) => `{slug}:${{id}}`
+
+export async function find{plural}(
+  options: {{ where?: Where; limit?: number; page?: number; sort?: string; depth?: number; locale?: string }} = {{}},
+) {{
+  return unstable_cache(
+    async () => (await getPayload({{ config }})).find({{ collection: '{slug}', ...options }}),
+    ['{slug}', 'find', JSON.stringify(options)],
+    {{ tags: [listTag] }},
+  )()
+}}
+
+export async function find{singular}ByID(id: ID, options: {{ depth?: number; locale?: string }} = {{}}) {{
+  return unstable_cache(
+    async () => (await getPayload({{ config }})).findByID({{ collection: '{slug}', id, ...options }}),
+    ['{slug}', 'findByID', String(id), JSON.stringify(options)],
+    {{ tags: [listTag, documentTag(id)] }},
+  )()
+}}
+
+export async function create{singular}(data: RequiredDataFromCollectionSlug<'{slug}'>) {{
+  const doc = await (await getPayload({{ config }})).create({{ collection: '{slug}', data }})
+  await revalidate{plural}()
+  return doc
+}}
+
+export async function update{singular}(id: ID, data: Partial<RequiredDataFromCollectionSlug<'{slug}'>>) {{
+  const doc = await (await getPayload({{ config }})).update({{ collection: '{slug}', id, data }})
+  await revalidate{plural}(id)
+  return doc
+}}
+
+export async function delete{singular}(id: ID) {{
+  const doc = await (await getPayload({{ config }})).delete({{ collection: '{slug}', id }})
+  await revalidate{plural}(id)
+  return doc
+}}
+
+/** Drop cached queries on the collection, and on one document when `id` is given; call it from an afterChange hook to pick up edits made in the admin. */
+export async function revalidate{plural}(id?: ID) {{
+  revalidateTag(listTag)
+  if (id !== undefined) revalidateTag(documentTag(id))
+}}
+"
+    )
+}
+
+fn global_server_actions(slug: &str) -> String {
+    let name = schema_name(slug);
+    format!(
+        "'use server'
+
+import config from '@payload-config'
+import {{ revalidateTag, unstable_cache }} from 'next/cache'
+import {{ getPayload, type DataFromGlobalSlug }} from 'payload'
+
+const tag = 'global:{slug}'
+
+export async function get{name}(options: {{ depth?: number; locale?: string }} = {{}}) {{
+  return unstable_cache(
+    async () => (await getPayload({{ config }})).findGlobal({{ slug: '{slug}', ...options }}),
+    ['{slug}', JSON.stringify(options)],
+    {{ tags: [tag] }},
+  )()
+}}
+
+export async function update{name}(data: Partial<DataFromGlobalSlug<'{slug}'>>) {{
+  const result = await (await getPayload({{ config }})).updateGlobal({{ slug: '{slug}', data }})
+  await revalidate{name}()
+  return result
+}}
+
+/** Drop the cached global; call it from an afterChange hook to pick up edits made in the admin. */
+export async function revalidate{name}() {{
+  revalidateTag(tag)
+}}
+"
+    )
+}
+
+/// `fetch` wrapper shared by the React Query templates, sending the
+/// payload-token cookie and throwing Payload's error messages.
+fn query_request(base: &str) -> String {
+    format!(
+        "async function request<T>(path: string, init: RequestInit = {{}}): Promise<T> {{
+  const response = await fetch(`{base}${{path}}`, {{
+    credentials: 'include',
+    ...init,
+    headers: {{ 'Content-Type': 'application/json', ...init.headers }},
+  }})
+  const data = await response.json().catch(() => ({{}}))
+  if (!response.ok) {{
+    throw new Error(data.errors?.map((error: {{ message: string }}) => error.message).join('; ') || response.statusText)
+  }}
+  return data as T
+}}"
+    )
+}
+
+fn collection_query_hooks(slug: &str, api_route: &str) -> String {
+    let (plural, singular) = collection_function_names(slug);
+    let keys = format!("{}Keys", naming::Case::Camel.convert(&document_type(slug)));
+    let document = document_type(slug);
+    let request = query_request(&format!("{}/{slug}", api_route.trim_end_matches('/')));
+    format!(
+        "'use client'
+
+import {{ useMutation, useQuery, useQueryClient }} from '@tanstack/react-query'
+import type {{ PaginatedDocs, Where }} from 'payload'
+import {{ stringify }} from 'qs-esm'
+
+import type {{ {document} }} from '@/payload-types'
+
+type ID = {document}['id']
+
+export interface Find{plural}Params {{
+  where?: Where
+  limit?: number
+  page?: number
+  sort?: string
+  depth?: number
+  locale?: string
+}}
+
+/** Query keys; invalidate `{keys}.all` to refetch everything on the collection. */
+export const {keys} = {{
+  all: ['{slug}'] as const,
+  list: (params: Find{plural}Params) => ['{slug}', 'list', params] as const,
+  detail: (id: ID) => ['{slug}', 'detail', id] as const,
+}}
+
+{request}
+
+export function use{plural}(params: Find{plural}Params = {{}}) {{
+  return useQuery({{
+    queryKey: {keys}.list(params),
+    queryFn: () => request<PaginatedDocs<{document}>>(stringify(params, {{ addQueryPrefix: true }})),
+  }})
+}}
+
+export function use{singular}(id: ID | null | undefined, params: {{ depth?: number; locale?: string }} = {{}}) {{
+  return useQuery({{
+    queryKey: [...{keys}.detail(id as ID), params],
+    queryFn: () => request<{document}>(`/${{id}}${{stringify(params, {{ addQueryPrefix: true }})}}`),
+    enabled: id != null,
+  }})
+}}
+
+export function useCreate{singular}() {{
+  const queryClient = useQueryClient()
+  return useMutation({{
+    mutationFn: (data: Partial<{document}>) =>
+      request<{{ doc: {document} }}>('', {{ method: 'POST', body: JSON.stringify(data) }}).then((result) => result.doc),
+    onSuccess: () => queryClient.invalidateQueries({{ queryKey: {keys}.all }}),
+  }})
+}}
+
+export function useUpdate{singular}() {{
+  const queryClient = useQueryClient()
+  return useMutation({{
+    mutationFn: ({{ id, data }}: {{ id: ID; data: Partial<{document}> }}) =>
+      request<{{ doc: {document} }}>(`/${{id}}`, {{ method: 'PATCH', body: JSON.stringify(data) }}).then((result) => result.doc),
+    onSuccess: () => queryClient.invalidateQueries({{ queryKey: {keys}.all }}),
+  }})
+}}
+
+export function useDelete{singular}() {{
+  const queryClient = useQueryClient()
+  return useMutation({{
+    mutationFn: (id: ID) => request<{{ doc: {document} }}>(`/${{id}}`, {{ method: 'DELETE' }}).then((result) => result.doc),
+    onSuccess: () => queryClient.invalidateQueries({{ queryKey: {keys}.all }}),
+  }})
+}}
+"
+    )
+}
+
+fn global_query_hooks(slug: &str, api_route: &str) -> String {
+    let name = schema_name(slug);
+    let key = format!("{}Key", naming::Case::Camel.convert(slug));
+    let request = query_request(&format!("{}/globals/{slug}", api_route.trim_end_matches('/')));
+    format!(
+        "'use client'
+
+import {{ useMutation, useQuery, useQueryClient }} from '@tanstack/react-query'
+import {{ stringify }} from 'qs-esm'
+
+import type {{ {name} }} from '@/payload-types'
+
+export const {key} = ['global', '{slug}'] as const
+
+{request}
+
+export function use{name}(params: {{ depth?: number; locale?: string }} = {{}}) {{
+  return useQuery({{
+    queryKey: [...{key}, params],
+    queryFn: () => request<{name}>(stringify(params, {{ addQueryPrefix: true }})),
+  }})
+}}
+
+export function useUpdate{name}() {{
+  const queryClient = useQueryClient()
+  return useMutation({{
+    mutationFn: (data: Partial<{name}>) =>
+      request<{{ result: {name} }}>('', {{ method: 'POST', body: JSON.stringify(data) }}).then((result) => result.result),
+    onSuccess: () => queryClient.invalidateQueries({{ queryKey: {key} }}),
+  }})
+}}
+"
+    )
+}
+
+fn generate_field_encryption_template(options: &Map<String, Value>) -> Result<String, String> {
+    let env_var = get_string(options, "envVar").unwrap_or_else(|| "PAYLOAD_ENCRYPTION_KEY".to_string());
+    if env_var.is_empty() || !env_var.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+        return Err(format!("Invalid environment variable name: {env_var}"));
+    }
+    let fields: Vec<String> = get_array(options, "fields")
+        .into_iter()
+        .flatten()
+        .filter_map(|field| field.as_str())
+        .map(|field| format!("'{}'", field.replace('\'', "\\'")))
+        .collect();
+
+    Ok(format!(
+        "import crypto from 'crypto';
+import type {{ Field, FieldHook }} from 'payload/types';
+
+// AES-256-GCM with a random IV per value. Stored values look like
+// enc:v1:<iv>:<tag>:<ciphertext>, so plain values saved before encryption was
+// added still read back and are encrypted on their next save.
+const ALGORITHM = 'aes-256-gcm';
+const PREFIX = 'enc:v1:';
+
+const key = (): Buffer => {{
+  const secret = process.env.{env_var};
+  if (!secret) {{
+    throw new Error('{env_var} is not set');
+  }}
+  return crypto.createHash('sha256').update(secret).digest();
+}};
+
+export const encrypt = (value: string): string => {{
+  const iv = crypto.randomBytes(12);
+  const cipher = crypto.createCipheriv(ALGORITHM, key(), iv);
+  const encrypted = Buffer.concat([cipher.update(value, 'utf8'), cipher.final()]);
+  return PREFIX + [iv, cipher.getAuthTag(), encrypted].map((part) => part.toString('base64')).join(':');
+}};
+
+export const decrypt = (value: string): string => {{
+  if (!value.startsWith(PREFIX)) {{
+    return value;
+  }}
+  const [iv, tag, encrypted] = value
+    .slice(PREFIX.length)
+    .split(':')
+    .map((part) => Buffer.from(part, 'base64'));
+  const decipher = crypto.createDecipheriv(ALGORITHM, key(), iv);
+  decipher.setAuthTag(tag);
+  return Buffer.concat([decipher.update(encrypted), decipher.final()]).toString('utf8');
+}};
+
+export const encryptBeforeChange: FieldHook = ({{ value }}) =>
+  typeof value === 'string' && value !== '' && !value.startsWith(PREFIX) ? encrypt(value) : value;
+
+export const decryptAfterRead: FieldHook = ({{ value }}) =>
+  typeof value === 'string' ? decrypt(value) : value;
+
+/**
+ * The field with its value encrypted at rest, e.g. `encrypted({{ name: 'ssn', type: 'text' }})`.
+ * Encrypted values can't be queried, sorted or kept unique; keep read access
+ * restricted, since afterRead returns the plain value.
+ */
+export const encrypted = <T extends Field>(field: T): T => {{
+  const hooks = 'hooks' in field ? field.hooks : undefined;
+  return {{
+    ...field,
+    hooks: {{
+      ...hooks,
+      beforeChange: [...(hooks?.beforeChange ?? []), encryptBeforeChange],
+      afterRead: [decryptAfterRead, ...(hooks?.afterRead ?? [])],
+    }},
+  }} as T;
+}};
+
+/** Fields to wrap in encrypted() in their collection configs. */
+export const encryptedFields = [{}] as const;
+",
+        fields.join(", ")
+    ))
+}
+
+fn generate_scheduled_publish_template(options: &Map<String, Value>) -> Result<String, String> {
+    let collections: Vec<&str> = get_array(options, "collections")
+        .into_iter()
+        .flatten()
+        .filter_map(|slug| slug.as_str())
+        .collect();
+    if collections.is_empty() {
+        return Err("At least one collection to schedule is required".to_string());
+    }
+    if let Some(slug) = collections.iter().find(|slug| {
+        slug.is_empty()
+            || !slug
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+    }) {
+        return Err(format!("Invalid collection slug: {slug}"));
+    }
+    let cron = get_string(options, "cron").unwrap_or_else(|| "*/5 * * * *".to_string());
+    if !matches!(cron.split_whitespace().count(), 5 | 6) || cron.contains('\'') {
+        return Err(format!("Invalid cron expression: {cron}"));
+    }
+    let queue = get_string(options, "queue").unwrap_or_else(|| "scheduled-publish".to_string());
+    let status = get_string(options, "statusField").unwrap_or_else(|| "_status".to_string());
+    for value in [&queue, &status] {
+        if value.is_empty() || !value.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_') {
+            return Err(format!("Invalid name: {value}"));
+        }
+    }
+    let collections = collections
+        .iter()
+        .map(|slug| format!("'{slug}'"))
+        .collect::<Vec<_>>()
+        .join(", ");
+
+    Ok(format!(
+        "import type {{ CollectionConfig, Field, JobsConfig, Payload, TaskConfig }} from 'payload';
+
+/** Collections whose documents publish and unpublish on schedule. */
+export const scheduledCollections = [{collections}] as const;
+
+const QUEUE = '{queue}';
+const CRON = '{cron}';
+
+const dateField = (name: string, description: string): Field => ({{
+  name,
+  type: 'date',
+  index: true,
+  admin: {{
+    position: 'sidebar',
+    description,
+    date: {{ pickerAppearance: 'dayAndTime' }},
+  }},
+}});
+
+export const scheduleFields: Field[] = [
+  dateField('publishAt', 'Publish automatically at this time'),
+  dateField('unpublishAt', 'Return to draft automatically at this time'),
+];
+
+/** The collection with publishAt and unpublishAt; apply it to each of scheduledCollections. */
+export const withSchedule = (collection: CollectionConfig): CollectionConfig => ({{
+  ...collection,
+  fields: [...collection.fields, ...scheduleFields],
+}});
+
+/** Moves documents in `from` whose `field` time has passed to `to`, clearing the time. */
+const flip = async (
+  payload: Payload,
+  collection: (typeof scheduledCollections)[number],
+  field: 'publishAt' | 'unpublishAt',
+  from: string,
+  to: string,
+): Promise<number> => {{
+  const due = await payload.find({{
+    collection,
+    draft: true,
+    depth: 0,
+    pagination: false,
+    where: {{
+      and: [{{ [field]: {{ less_than_equal: new Date().toISOString() }} }}, {{ {status}: {{ equals: from }} }}],
+    }},
+  }});
+  for (const doc of due.docs) {{
+    const {{ id, createdAt: _createdAt, updatedAt: _updatedAt, ...data }} = doc as Record<string, unknown>;
+    await payload.update({{
+      collection,
+      id: id as string | number,
+      data: {{ ...data, {status}: to, [field]: null }},
+    }});
+  }}
+  return due.docs.length;
+}};
+
+export const schedulePublishTask: TaskConfig<{{
+  input: Record<string, never>;
+  output: {{ published: number; unpublished: number }};
+}}> = {{
+  slug: 'schedulePublish',
+  schedule: [{{ cron: CRON, queue: QUEUE }}],
+  outputSchema: [
+    {{ name: 'published', type: 'number' }},
+    {{ name: 'unpublished', type: 'number' }},
+  ],
+  handler: async ({{ req }}) => {{
+    let published = 0;
+    let unpublished = 0;
+    for (const collection of scheduledCollections) {{
+      published += await flip(req.payload, collection, 'publishAt', 'draft', 'published');
+      unpublished += await flip(req.payload, collection, 'unpublishAt', 'published', 'draft');
+    }}
+    return {{ output: {{ published, unpublished }} }};
+  }},
+}};
+
+/**
+ * Spread into the config's `jobs`. autoRun runs the queue on the same cron in
+ * long-running servers; on serverless hosts call /api/payload-jobs/run from a
+ * platform cron instead.
+ */
+export const scheduledPublishJobs: Partial<JobsConfig> = {{
+  tasks: [schedulePublishTask],
+  autoRun: [{{ cron: CRON, queue: QUEUE, limit: 100 }}],
+}};
+"
+    ))
+}
+
+/// Headers every response gets, as the body of a TypeScript object literal.
+const SECURITY_HEADERS: &str = "  'X-Content-Type-Options': 'nosniff',
+  'X-Frame-Options': 'SAMEORIGIN',
+  'Referrer-Policy': 'strict-origin-when-cross-origin',
+  'Permissions-Policy': 'camera=(), microphone=(), geolocation=()',
+  'Cross-Origin-Opener-Policy': 'same-origin',
+  ...(process.env.NODE_ENV === 'production'
+    ? { 'Strict-Transport-Security': 'max-age=63072000; includeSubDomains; preload' }
+    : {}),
+";
+
+fn generate_security_template(options: &Map<String, Value>) -> Result<String, String> {
+    let target = get_string(options, "target").unwrap_or_else(|| "nextjs".to_string());
+    let version = match target.as_str() {
+        "nextjs" => PayloadVersion::V3,
+        "express" => PayloadVersion::V2,
+        other => return Err(format!("Unknown target '{other}'; expected nextjs or express")),
+    };
+    let mut origins = Vec::new();
+    for origin in get_array(options, "origins").into_iter().flatten() {
+        let origin = origin
+            .as_str()
+            .filter(|origin| {
+                (origin.starts_with("https://") || origin.starts_with("http://"))
+                    && !origin.contains(['\'', '\\', ' '])
+            })
+            .ok_or_else(|| format!("Invalid origin: {origin}"))?;
+        origins.push(format!("'{}'", origin.trim_end_matches('/')));
+    }
+    if origins.is_empty() {
+        origins.push(format!("process.env.{}", server_url_env(version)));
+    }
+    let origins = origins.join(", ");
+    let max = options.get("max").map_or(Some(500), Value::as_u64).filter(|max| *max > 0);
+    let window = options
+        .get("windowMinutes")
+        .map_or(Some(15), Value::as_u64)
+        .filter(|window| *window > 0);
+    let (Some(max), Some(window)) = (max, window) else {
+        return Err("max and windowMinutes must be positive integers".to_string());
+    };
+
+    if version == PayloadVersion::V2 {
+        return Ok(format!(
+            "import type {{ NextFunction, Request, Response }} from 'express';
+import type {{ Config }} from 'payload/config';
+
+/** Origins allowed to send cookie-authenticated requests and to call the API from browsers. */
+const ORIGINS = [{origins}].filter(Boolean) as string[];
+
+/**
+ * Spread into buildConfig. rateLimit counts requests per client IP (trustProxy
+ * reads it from X-Forwarded-For behind a load balancer), csrf only accepts
+ * cookie-authenticated requests from ORIGINS and cors only answers them.
+ */
+export const securityConfig: Pick<Config, 'rateLimit' | 'csrf' | 'cors'> = {{
+  rateLimit: {{
+    window: {window} * 60 * 1000,
+    max: {max},
+    trustProxy: true,
+  }},
+  csrf: ORIGINS,
+  cors: ORIGINS,
+}};
+
+/** Added to every response; HSTS only in production, which must be served over https. */
+export const securityHeaders: Record<string, string> = {{
+{SECURITY_HEADERS}}};
+
+/** Register with app.use() before payload.init, after app.disable('x-powered-by'). */
+export const securityHeadersMiddleware = (_req: Request, res: Response, next: NextFunction): void => {{
+  res.set(securityHeaders);
+  next();
+}};
+"
+        ));
+    }
+
+    Ok(format!(
+        "import {{ NextResponse, type NextRequest }} from 'next/server';
+import type {{ Config }} from 'payload';
+
+/** Origins allowed to send cookie-authenticated requests and to call the API from browsers. */
+const ORIGINS = [{origins}].filter(Boolean) as string[];
+
+/** Spread into buildConfig: csrf only accepts cookie-authenticated requests from ORIGINS and cors only answers them. */
+export const securityConfig: Pick<Config, 'csrf' | 'cors'> = {{
+  csrf: ORIGINS,
+  cors: ORIGINS,
+}};
+
+/** Added to every response; HSTS only in production, which must be served over https. */
+export const securityHeaders: Record<string, string> = {{
+{SECURITY_HEADERS}}};
+
+const MAX_REQUESTS = {max};
+const WINDOW_MS = {window} * 60 * 1000;
+
+// Payload 3 has no rateLimit option, so API requests are counted here per
+// client IP in fixed windows. The counts live in this instance's memory;
+// serverless or multi-instance deployments need a shared store such as Redis.
+const hits = new Map<string, {{ count: number; resetAt: number }}>();
+
+const clientIp = (request: NextRequest): string =>
+  request.headers.get('x-forwarded-for')?.split(',')[0]?.trim() || request.headers.get('x-real-ip') || 'unknown';
+
+/** Milliseconds until the window resets when `ip` is over the limit, otherwise null. */
+const rateLimited = (ip: string): number | null => {{
+  const now = Date.now();
+  const entry = hits.get(ip);
+  if (!entry || entry.resetAt <= now) {{
+    if (hits.size > 10_000) {{
+      for (const [key, value] of hits) {{
+        if (value.resetAt <= now) hits.delete(key);
+      }}
+    }}
+    hits.set(ip, {{ count: 1, resetAt: now + WINDOW_MS }});
+    return null;
+  }}
+  entry.count += 1;
+  return entry.count > MAX_REQUESTS ? entry.resetAt - now : null;
+}};
+
+/**
+ * Rate limits /api and adds securityHeaders. Use it from src/middleware.ts:
+ *
+ *   export {{ securityMiddleware as middleware }} from './security';
+ *   export const config = {{ matcher: ['/((?!_next/static|_next/image|favicon.ico).*)'] }};
+ */
+export function securityMiddleware(request: NextRequest): NextResponse {{
+  if (request.nextUrl.pathname.startsWith('/api')) {{
+    const retryAfter = rateLimited(clientIp(request));
+    if (retryAfter !== null) {{
+      return NextResponse.json(
+        {{ errors: [{{ message: 'Too many requests' }}] }},
+        {{ status: 429, headers: {{ ...securityHeaders, 'Retry-After': String(Math.ceil(retryAfter / 1000)) }} }},
+      );
+    }}
+  }}
+  const response = NextResponse.next();
+  for (const [name, value] of Object.entries(securityHeaders)) {{
+    response.headers.set(name, value);
+  }}
+  return response;
+}}
+"
+    ))
+}
+
+pub(crate) fn capitalize(value: &str) -> String {
     let mut chars = value.chars();
     match chars.next() {
         Some(first) => first.to_uppercase().collect::<String>() + chars.as_str(),
@@ -703,7 +1630,7 @@ fn capitalize(value: &str) -> String {
 fn capitalize_words(value: &str) -> String {
     value
         .split_whitespace()
-        .map(|word| capitalize(word))
+        .map(capitalize)
         .collect::<Vec<_>>()
         .join(" ")
 }
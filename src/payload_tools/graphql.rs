@@ -0,0 +1,698 @@
+//! GraphQL SDL for the API Payload generates from a schema: a document type,
+//! paginated list, where-input and create/update inputs per collection, the
+//! queries and mutations that use them, and the auth operations of auth
+//! collections. Top-level types and operations are named as Payload names
+//! them (`Post`, `Posts`, `createPost`, `Post_where`), so clients generated
+//! from the SDL work against the running CMS; nested type names may differ.
+
+use std::collections::BTreeSet;
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::payload_tools::{
+    json_schema::schema_name,
+    naming::singularize,
+    schema_model::{SchemaCollection, SchemaField, SchemaModel},
+};
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct GraphqlSdlOptions {
+    /// Locale codes of the config's `localization`; adds `locale` and
+    /// `fallbackLocale` arguments
+    pub locales: Option<Vec<String>>,
+    /// Documents have integer IDs, as on Postgres and SQLite; string IDs
+    /// (MongoDB) by default
+    pub numeric_ids: Option<bool>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+pub struct GraphqlSdlOutput {
+    /// The schema in GraphQL SDL
+    pub sdl: String,
+    pub types: usize,
+    pub queries: usize,
+    pub mutations: usize,
+    pub warnings: Vec<String>,
+}
+
+/// A name GraphQL accepts: other characters become underscores, and a
+/// leading digit gets one in front.
+fn format_name(name: &str) -> String {
+    let name: String = name
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '_' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    if name.starts_with(|c: char| c.is_ascii_digit()) {
+        format!("_{name}")
+    } else {
+        name
+    }
+}
+
+/// Singular and plural type names of a collection, as Payload derives them
+/// from the slug; a slug like `media` that reads the same in both gets an
+/// `all` prefix on the plural.
+fn collection_names(slug: &str) -> (String, String) {
+    let singular = schema_name(&singularize(slug));
+    let plural = schema_name(slug);
+    if singular == plural {
+        let all = format!("all{plural}");
+        (singular, all)
+    } else {
+        (singular, plural)
+    }
+}
+
+/// Types in the order they were first needed, defined once each.
+struct Sdl<'a> {
+    schema: &'a SchemaModel,
+    definitions: Vec<String>,
+    names: BTreeSet<String>,
+    id: &'static str,
+    warnings: Vec<String>,
+}
+
+impl Sdl<'_> {
+    fn define(&mut self, name: &str, definition: String) {
+        if self.names.insert(name.to_string()) {
+            self.definitions.push(definition);
+        }
+    }
+
+    fn object(&mut self, keyword: &str, name: &str, fields: &[String]) {
+        let body: String = fields.iter().map(|field| format!("  {field}\n")).collect();
+        self.define(name, format!("{keyword} {name} {{\n{body}}}"));
+    }
+
+    fn enumeration(&mut self, name: &str, values: &[String]) {
+        let values: Vec<String> = values.iter().map(|value| format_name(value)).collect();
+        self.object("enum", name, &values);
+    }
+
+    fn target(&self, slug: &str) -> Option<String> {
+        self.schema
+            .collection(slug)
+            .map(|collection| collection_names(&collection.slug).0)
+    }
+
+    /// Output type of `field`, a field of type `parent`.
+    fn output(&mut self, parent: &str, field: &SchemaField) -> Option<String> {
+        let nested = format!("{parent}_{}", schema_name(&field.name));
+        let one = match field.field_type.as_str() {
+            "text" | "textarea" | "code" => "String".to_string(),
+            "email" => "EmailAddress".to_string(),
+            "number" => "Float".to_string(),
+            "checkbox" => "Boolean".to_string(),
+            "date" => "DateTime".to_string(),
+            "point" => "[Float]".to_string(),
+            "json" | "richText" => "JSON".to_string(),
+            "select" | "radio" => match &field.options {
+                Some(options) if !options.is_empty() => {
+                    self.enumeration(&nested, options);
+                    nested
+                }
+                _ => "String".to_string(),
+            },
+            "relationship" | "upload" => {
+                let slugs = field.relation_to.as_deref().unwrap_or_default();
+                let targets: Option<Vec<String>> =
+                    slugs.iter().map(|slug| self.target(slug)).collect();
+                match (slugs, targets) {
+                    ([_], Some(targets)) => targets[0].clone(),
+                    ([_, _, ..], Some(targets)) => {
+                        let relation_to = format!("{nested}_RelationTo");
+                        self.enumeration(&relation_to, slugs);
+                        self.define(&nested, format!("union {nested} = {}", targets.join(" | ")));
+                        let relationship = format!("{nested}_Relationship");
+                        self.object(
+                            "type",
+                            &relationship,
+                            &[
+                                format!("relationTo: {relation_to}"),
+                                format!("value: {nested}"),
+                            ],
+                        );
+                        relationship
+                    }
+                    _ => {
+                        self.warnings.push(format!(
+                            "{parent}.{}: related collection not in the schema; typed as JSON",
+                            field.name
+                        ));
+                        "JSON".to_string()
+                    }
+                }
+            }
+            "group" => {
+                let fields = self.fields(&nested, field.fields.as_deref().unwrap_or_default());
+                self.object("type", &nested, &fields);
+                nested
+            }
+            "array" => {
+                let mut fields = vec!["id: String".to_string()];
+                fields.extend(self.fields(&nested, field.fields.as_deref().unwrap_or_default()));
+                self.object("type", &nested, &fields);
+                return Some(list(&nested, field.required));
+            }
+            "blocks" => {
+                let mut members = Vec::new();
+                for block in field.blocks.iter().flatten() {
+                    let name = format!("{}Block", schema_name(&block.slug));
+                    let mut fields = vec![
+                        "id: String".to_string(),
+                        "blockName: String".to_string(),
+                        "blockType: String".to_string(),
+                    ];
+                    fields.extend(self.fields(&name, &block.fields));
+                    self.object("type", &name, &fields);
+                    members.push(name);
+                }
+                if members.is_empty() {
+                    return Some("JSON".to_string());
+                }
+                self.define(&nested, format!("union {nested} = {}", members.join(" | ")));
+                return Some(list(&nested, field.required));
+            }
+            _ => return None,
+        };
+        Some(if field.has_many {
+            list(&one, field.required)
+        } else if field.required {
+            format!("{one}!")
+        } else {
+            one
+        })
+    }
+
+    fn fields(&mut self, parent: &str, fields: &[SchemaField]) -> Vec<String> {
+        fields
+            .iter()
+            .filter_map(|field| {
+                let output = self.output(parent, field)?;
+                Some(format!("{}: {output}", format_name(&field.name)))
+            })
+            .collect()
+    }
+
+    /// Input type of `field` in a mutation; nested inputs are named from
+    /// `base`, e.g. `mutationPost_Meta` or `mutationPostUpdate_Meta`.
+    fn input(
+        &mut self,
+        parent: &str,
+        base: &str,
+        field: &SchemaField,
+        update: bool,
+    ) -> Option<String> {
+        let path = format!("{parent}_{}", schema_name(&field.name));
+        let one = match field.field_type.as_str() {
+            "relationship" | "upload" => match field.relation_to.as_deref().unwrap_or_default() {
+                [_] => self.id.to_string(),
+                _ => "JSON".to_string(),
+            },
+            "group" | "array" => {
+                let name = format!("{base}{}Input", &path[parent.len()..]);
+                let fields = self.inputs(
+                    &path,
+                    base,
+                    field.fields.as_deref().unwrap_or_default(),
+                    update,
+                );
+                self.object("input", &name, &fields);
+                if field.field_type == "array" {
+                    return Some(if field.required && !update {
+                        format!("[{name}]!")
+                    } else {
+                        format!("[{name}]")
+                    });
+                }
+                name
+            }
+            "blocks" => "JSON".to_string(),
+            _ => self.output(
+                parent,
+                &SchemaField {
+                    required: false,
+                    has_many: false,
+                    ..field.clone()
+                },
+            )?,
+        };
+        let required = field.required && !update;
+        Some(match (field.has_many, required) {
+            (true, true) => format!("[{one}]!"),
+            (true, false) => format!("[{one}]"),
+            (false, true) => format!("{one}!"),
+            (false, false) => one,
+        })
+    }
+
+    fn inputs(
+        &mut self,
+        parent: &str,
+        base: &str,
+        fields: &[SchemaField],
+        update: bool,
+    ) -> Vec<String> {
+        fields
+            .iter()
+            .filter_map(|field| {
+                let input = self.input(parent, base, field, update)?;
+                Some(format!("{}: {input}", format_name(&field.name)))
+            })
+            .collect()
+    }
+
+    /// The `_where` input of a collection and its operator inputs.
+    fn where_input(&mut self, name: &str, collection: &SchemaCollection) {
+        let mut leaves = Vec::new();
+        leaf_fields("", &collection.fields, &mut leaves);
+        let mut fields = Vec::new();
+        let mut operator = |sdl: &mut Self, path: &str, value: &str, operators: &[&str]| {
+            let input = format!("{name}_{path}_operator");
+            let list_operators = ["in", "not_in", "all"];
+            let entries: Vec<String> = operators
+                .iter()
+                .map(|operator| match *operator {
+                    op if list_operators.contains(&op) => format!("{op}: [{value}]"),
+                    "exists" => "exists: Boolean".to_string(),
+                    "near" => "near: [Float]".to_string(),
+                    op => format!("{op}: {value}"),
+                })
+                .collect();
+            sdl.object("input", &input, &entries);
+            fields.push(format!("{path}: {input}"));
+        };
+        const TEXT: &[&str] = &[
+            "equals",
+            "not_equals",
+            "like",
+            "contains",
+            "in",
+            "not_in",
+            "all",
+            "exists",
+        ];
+        const NUMBER: &[&str] = &[
+            "equals",
+            "not_equals",
+            "greater_than_equal",
+            "greater_than",
+            "less_than_equal",
+            "less_than",
+            "in",
+            "not_in",
+            "exists",
+        ];
+        const DATE: &[&str] = &[
+            "equals",
+            "not_equals",
+            "greater_than_equal",
+            "greater_than",
+            "less_than_equal",
+            "less_than",
+            "like",
+            "exists",
+        ];
+        const CHOICE: &[&str] = &["equals", "not_equals", "in", "not_in", "all", "exists"];
+        operator(self, "id", self.id, CHOICE);
+        for (path, field) in leaves {
+            let enum_name = format!("{name}_{}", schema_name(&path.replace("__", "_")));
+            match field.field_type.as_str() {
+                "text" | "textarea" | "code" | "email" => operator(self, &path, "String", TEXT),
+                "number" => operator(self, &path, "Float", NUMBER),
+                "date" => operator(self, &path, "DateTime", DATE),
+                "checkbox" => operator(self, &path, "Boolean", &["equals", "not_equals", "exists"]),
+                "select" | "radio" if self.names.contains(&enum_name) => {
+                    operator(self, &path, &enum_name, CHOICE)
+                }
+                "select" | "radio" => operator(self, &path, "String", CHOICE),
+                "relationship" | "upload" => match field.relation_to.as_deref().unwrap_or_default()
+                {
+                    [_] => operator(self, &path, self.id, CHOICE),
+                    _ => operator(
+                        self,
+                        &path,
+                        "JSON",
+                        &["equals", "not_equals", "in", "not_in", "exists"],
+                    ),
+                },
+                "point" => operator(
+                    self,
+                    &path,
+                    "[Float]",
+                    &["equals", "not_equals", "near", "exists"],
+                ),
+                "json" | "richText" => operator(
+                    self,
+                    &path,
+                    "JSON",
+                    &["equals", "like", "contains", "exists"],
+                ),
+                _ => {}
+            }
+        }
+        if collection.timestamps {
+            operator(self, "updatedAt", "DateTime", DATE);
+            operator(self, "createdAt", "DateTime", DATE);
+        }
+        let own = format!("{name}_where");
+        fields.push(format!("AND: [{own}]"));
+        fields.push(format!("OR: [{own}]"));
+        self.object("input", &own, &fields);
+    }
+}
+
+fn list(item: &str, required: bool) -> String {
+    if required {
+        format!("[{item}!]!")
+    } else {
+        format!("[{item}!]")
+    }
+}
+
+/// Queryable fields under `fields`, with group and array sub-fields joined
+/// to their parent by `__` as in Payload's where-inputs.
+fn leaf_fields<'a>(
+    prefix: &str,
+    fields: &'a [SchemaField],
+    out: &mut Vec<(String, &'a SchemaField)>,
+) {
+    for field in fields {
+        let path = format!("{prefix}{}", format_name(&field.name));
+        match field.field_type.as_str() {
+            "group" | "array" => leaf_fields(
+                &format!("{path}__"),
+                field.fields.as_deref().unwrap_or_default(),
+                out,
+            ),
+            "blocks" => {}
+            _ => out.push((path, field)),
+        }
+    }
+}
+
+/// The SDL of the GraphQL API Payload serves for `schema`.
+pub fn graphql_sdl(schema: &SchemaModel, options: &GraphqlSdlOptions) -> GraphqlSdlOutput {
+    let mut sdl = Sdl {
+        schema,
+        definitions: Vec::new(),
+        names: BTreeSet::new(),
+        id: if options.numeric_ids.unwrap_or(false) {
+            "Int"
+        } else {
+            "String"
+        },
+        warnings: Vec::new(),
+    };
+    let locales = options
+        .locales
+        .as_deref()
+        .filter(|locales| !locales.is_empty());
+    let locale_args = if locales.is_some() {
+        ", locale: LocaleInputType, fallbackLocale: FallbackLocaleInputType"
+    } else {
+        ""
+    };
+    let mut queries = Vec::new();
+    let mut mutations = Vec::new();
+    let id = sdl.id;
+
+    for collection in &schema.collections {
+        let (singular, plural) = collection_names(&collection.slug);
+        let mut fields = vec![format!("id: {id}!")];
+        fields.extend(sdl.fields(&singular, &collection.fields));
+        if collection.auth {
+            fields.push("email: EmailAddress!".to_string());
+        }
+        if collection.upload {
+            for upload in [
+                "url: String",
+                "thumbnailURL: String",
+                "filename: String",
+                "mimeType: String",
+                "filesize: Float",
+                "width: Float",
+                "height: Float",
+                "focalX: Float",
+                "focalY: Float",
+            ] {
+                fields.push(upload.to_string());
+            }
+        }
+        if collection.timestamps {
+            fields.push("updatedAt: DateTime".to_string());
+            fields.push("createdAt: DateTime".to_string());
+        }
+        sdl.object("type", &singular, &fields);
+        sdl.object(
+            "type",
+            &plural,
+            &[
+                format!("docs: [{singular}]"),
+                "hasNextPage: Boolean".to_string(),
+                "hasPrevPage: Boolean".to_string(),
+                "limit: Int".to_string(),
+                "nextPage: Int".to_string(),
+                "offset: Int".to_string(),
+                "page: Int".to_string(),
+                "pagingCounter: Int".to_string(),
+                "prevPage: Int".to_string(),
+                "totalDocs: Int".to_string(),
+                "totalPages: Int".to_string(),
+            ],
+        );
+        sdl.object(
+            "type",
+            &format!("count{plural}"),
+            &["totalDocs: Int".to_string()],
+        );
+        sdl.where_input(&singular, collection);
+
+        let create = format!("mutation{singular}");
+        let mut inputs = sdl.inputs(&singular, &create, &collection.fields, false);
+        if collection.auth {
+            inputs.push("email: String!".to_string());
+            inputs.push("password: String!".to_string());
+        }
+        sdl.object("input", &format!("{create}Input"), &inputs);
+        let update = format!("mutation{singular}Update");
+        let mut inputs = sdl.inputs(&singular, &update, &collection.fields, true);
+        if collection.auth {
+            inputs.push("email: String".to_string());
+            inputs.push("password: String".to_string());
+        }
+        sdl.object("input", &format!("{update}Input"), &inputs);
+
+        queries.push(format!(
+            "{singular}(id: {id}!, draft: Boolean{locale_args}): {singular}"
+        ));
+        queries.push(format!(
+            "{plural}(draft: Boolean, where: {singular}_where, limit: Int, page: Int, sort: String{locale_args}): {plural}"
+        ));
+        queries.push(format!(
+            "count{plural}(draft: Boolean, where: {singular}_where{locale_args}): count{plural}"
+        ));
+        mutations.push(format!(
+            "create{singular}(data: {create}Input!, draft: Boolean{locale_args}): {singular}"
+        ));
+        mutations.push(format!(
+            "update{singular}(id: {id}!, autosave: Boolean, data: {update}Input!, draft: Boolean{locale_args}): {singular}"
+        ));
+        mutations.push(format!("delete{singular}(id: {id}!): {singular}"));
+        mutations.push(format!("duplicate{singular}(id: {id}!): {singular}"));
+
+        if collection.auth {
+            let slug = format_name(&collection.slug);
+            sdl.object(
+                "type",
+                &format!("{slug}Me"),
+                &[
+                    "collection: String".to_string(),
+                    "exp: Int".to_string(),
+                    "token: String".to_string(),
+                    format!("user: {singular}"),
+                ],
+            );
+            sdl.object(
+                "type",
+                &format!("{slug}LoginResult"),
+                &[
+                    "exp: Int".to_string(),
+                    "token: String".to_string(),
+                    format!("user: {singular}"),
+                ],
+            );
+            sdl.object(
+                "type",
+                &format!("{slug}RefreshedUser"),
+                &[
+                    "exp: Int".to_string(),
+                    "refreshedToken: String".to_string(),
+                    format!("user: {singular}"),
+                ],
+            );
+            sdl.object(
+                "type",
+                &format!("{slug}ResetPassword"),
+                &["token: String".to_string(), format!("user: {singular}")],
+            );
+            queries.push(format!("me{singular}: {slug}Me"));
+            queries.push(format!("initialized{singular}: Boolean"));
+            mutations.push(format!(
+                "login{singular}(email: String!, password: String!): {slug}LoginResult"
+            ));
+            mutations.push(format!("logout{singular}: String"));
+            mutations.push(format!("refreshToken{singular}: {slug}RefreshedUser"));
+            mutations.push(format!(
+                "forgotPassword{singular}(email: String!, disableEmail: Boolean, expiration: Int): Boolean!"
+            ));
+            mutations.push(format!(
+                "resetPassword{singular}(password: String, token: String): {slug}ResetPassword"
+            ));
+            mutations.push(format!("unlock{singular}(email: String!): Boolean!"));
+            mutations.push(format!("verifyEmail{singular}(token: String): Boolean"));
+        }
+    }
+
+    for global in &schema.globals {
+        let name = schema_name(&global.slug);
+        let mut fields = sdl.fields(&name, &global.fields);
+        fields.push("updatedAt: DateTime".to_string());
+        fields.push("createdAt: DateTime".to_string());
+        sdl.object("type", &name, &fields);
+        let input = format!("mutation{name}");
+        let inputs = sdl.inputs(&name, &input, &global.fields, true);
+        sdl.object("input", &format!("{input}Input"), &inputs);
+        queries.push(format!("{name}(draft: Boolean{locale_args}): {name}"));
+        mutations.push(format!(
+            "update{name}(data: {input}Input!, draft: Boolean{locale_args}): {name}"
+        ));
+    }
+
+    if let Some(locales) = locales {
+        sdl.enumeration("LocaleInputType", locales);
+        let mut fallbacks = locales.to_vec();
+        fallbacks.push("none".to_string());
+        sdl.enumeration("FallbackLocaleInputType", &fallbacks);
+    }
+
+    let types = sdl.definitions.len();
+    let mut sections = vec![
+        "scalar DateTime\n\nscalar EmailAddress\n\nscalar JSON".to_string(),
+        format!(
+            "type Query {{\n{}}}",
+            queries
+                .iter()
+                .map(|query| format!("  {query}\n"))
+                .collect::<String>()
+        ),
+    ];
+    if !mutations.is_empty() {
+        sections.push(format!(
+            "type Mutation {{\n{}}}",
+            mutations
+                .iter()
+                .map(|mutation| format!("  {mutation}\n"))
+                .collect::<String>()
+        ));
+    }
+    sections.extend(sdl.definitions);
+    GraphqlSdlOutput {
+        sdl: sections.join("\n\n") + "\n",
+        types,
+        queries: queries.len(),
+        mutations: mutations.len(),
+        warnings: sdl.warnings,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::payload_tools::schema_model::{SchemaBlock, SchemaGlobal};
+
+    #[test]
+    fn renders_types_operations_and_inputs() {
+        let mut meta = SchemaField::new("meta", "group");
+        meta.fields = Some(vec![SchemaField::new("description", "textarea")]);
+        let mut layout = SchemaField::new("layout", "blocks");
+        layout.blocks = Some(vec![SchemaBlock {
+            slug: "call-to-action".to_string(),
+            fields: vec![SchemaField::new("label", "text")],
+        }]);
+        let schema = SchemaModel {
+            collections: vec![
+                SchemaCollection {
+                    slug: "blog-posts".to_string(),
+                    timestamps: true,
+                    fields: vec![
+                        SchemaField {
+                            required: true,
+                            ..SchemaField::new("title", "text")
+                        },
+                        SchemaField {
+                            options: Some(vec!["news".to_string(), "how-to".to_string()]),
+                            ..SchemaField::new("category", "select")
+                        },
+                        SchemaField {
+                            relation_to: Some(vec!["users".to_string()]),
+                            ..SchemaField::new("author", "relationship")
+                        },
+                        SchemaField {
+                            relation_to: Some(vec!["blog-posts".to_string(), "media".to_string()]),
+                            has_many: true,
+                            ..SchemaField::new("related", "relationship")
+                        },
+                        meta,
+                        layout,
+                    ],
+                    ..Default::default()
+                },
+                SchemaCollection {
+                    slug: "users".to_string(),
+                    auth: true,
+                    ..Default::default()
+                },
+                SchemaCollection {
+                    slug: "media".to_string(),
+                    upload: true,
+                    ..Default::default()
+                },
+            ],
+            globals: vec![SchemaGlobal {
+                slug: "site-settings".to_string(),
+                fields: vec![SchemaField::new("name", "text")],
+            }],
+        };
+        let output = graphql_sdl(&schema, &GraphqlSdlOptions::default());
+        let sdl = &output.sdl;
+        for expected in [
+            "type BlogPost {\n  id: String!\n  title: String!\n  category: BlogPost_Category\n  author: User\n  related: [BlogPost_Related_Relationship!]\n  meta: BlogPost_Meta\n  layout: [BlogPost_Layout!]\n  updatedAt: DateTime\n  createdAt: DateTime\n}",
+            "enum BlogPost_Category {\n  news\n  how_to\n}",
+            "union BlogPost_Related = BlogPost | Media",
+            "union BlogPost_Layout = CallToActionBlock",
+            "  BlogPosts(draft: Boolean, where: BlogPost_where, limit: Int, page: Int, sort: String): BlogPosts\n",
+            "  createBlogPost(data: mutationBlogPostInput!, draft: Boolean): BlogPost\n",
+            "input mutationBlogPostInput {\n  title: String!\n  category: BlogPost_Category\n  author: String\n  related: [JSON]\n  meta: mutationBlogPost_MetaInput\n  layout: JSON\n}",
+            "input mutationBlogPostUpdateInput {\n  title: String\n",
+            "  meta__description: BlogPost_meta__description_operator\n",
+            "  category: BlogPost_category_operator\n",
+            "input BlogPost_category_operator {\n  equals: BlogPost_Category\n",
+            "  AND: [BlogPost_where]\n",
+            "  loginUser(email: String!, password: String!): usersLoginResult\n",
+            "  allMedia(draft: Boolean, where: Media_where, limit: Int, page: Int, sort: String): allMedia\n",
+            "  updateSiteSettings(data: mutationSiteSettingsInput!, draft: Boolean): SiteSettings\n",
+        ] {
+            assert!(sdl.contains(expected), "missing {expected:?} in\n{sdl}");
+        }
+        assert!(output.warnings.is_empty());
+    }
+}
@@ -0,0 +1,58 @@
+//! Payload best-practice guides bundled with the server, so generated code can
+//! be grounded in guidance without internet access. Served as
+//! `docs://payload/{topic}` resources and searched by `search_docs`.
+
+pub const DOCS_URI_PREFIX: &str = "docs://payload/";
+
+pub struct Guide {
+    pub topic: &'static str,
+    pub title: &'static str,
+    pub summary: &'static str,
+    /// Markdown
+    pub content: &'static str,
+}
+
+pub const GUIDES: &[Guide] = &[
+    Guide {
+        topic: "access-control",
+        title: "Access control patterns",
+        summary: "Access functions, query constraints, roles, field-level and multi-tenant access",
+        content: include_str!("../docs/payload/access-control.md"),
+    },
+    Guide {
+        topic: "hooks",
+        title: "Hooks patterns",
+        summary: "Choosing a hook, transactions, recursion guards and idempotent side effects",
+        content: include_str!("../docs/payload/hooks.md"),
+    },
+    Guide {
+        topic: "versioning",
+        title: "Versioning strategy",
+        summary: "When to enable versions and drafts, autosave, scheduled publishing and limits",
+        content: include_str!("../docs/payload/versioning.md"),
+    },
+    Guide {
+        topic: "uploads",
+        title: "Upload handling",
+        summary: "Upload collections, image sizes, storage adapters, limits and file access",
+        content: include_str!("../docs/payload/uploads.md"),
+    },
+];
+
+pub fn guide(topic: &str) -> Option<&'static Guide> {
+    GUIDES.iter().find(|guide| guide.topic == topic)
+}
+
+/// The guide for `topic`, or an error listing the topics.
+pub fn require_guide(topic: &str) -> Result<&'static Guide, String> {
+    guide(topic).ok_or_else(|| {
+        format!(
+            "Unknown docs topic '{topic}'. Topics: {}",
+            GUIDES.iter().map(|guide| guide.topic).collect::<Vec<_>>().join(", ")
+        )
+    })
+}
+
+pub fn guide_uri(topic: &str) -> String {
+    format!("{DOCS_URI_PREFIX}{topic}")
+}
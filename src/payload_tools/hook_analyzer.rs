@@ -0,0 +1,440 @@
+//! Checks a TypeScript hook function for common Payload mistakes: not
+//! returning the data or doc the hook receives, write operations that
+//! re-trigger the hook, Local API calls that leave the request's transaction,
+//! and database access that skips access control.
+//!
+//! The analysis is lexical: comments and string contents are blanked out, the
+//! first function in the source is taken as the hook, and calls are found by
+//! pattern, so nested callbacks count as part of the hook body.
+
+use std::sync::OnceLock;
+
+use regex::Regex;
+use schemars::JsonSchema;
+use serde::Serialize;
+
+use crate::payload_tools::types::{Reference, Suggestion, ValidationResult};
+
+/// Hooks whose return value replaces what they received, with its name.
+const RETURNED: [(&str, &str); 7] = [
+    ("beforeOperation", "args"),
+    ("beforeValidate", "data"),
+    ("beforeChange", "data"),
+    ("afterChange", "doc"),
+    ("beforeRead", "doc"),
+    ("afterRead", "doc"),
+    ("afterOperation", "result"),
+];
+
+/// Hooks that run again when the hook writes through the Local API.
+const WRITE_TRIGGERED: [&str; 3] = ["beforeValidate", "beforeChange", "afterChange"];
+
+/// Collection, global and field hook names.
+pub const HOOK_NAMES: [&str; 16] = [
+    "beforeOperation",
+    "beforeValidate",
+    "beforeChange",
+    "afterChange",
+    "beforeRead",
+    "afterRead",
+    "beforeDelete",
+    "afterDelete",
+    "afterOperation",
+    "beforeLogin",
+    "afterLogin",
+    "afterLogout",
+    "afterMe",
+    "afterRefresh",
+    "afterForgotPassword",
+    "afterError",
+];
+
+const LOCAL_API: &str = "find|findByID|findGlobal|findVersions|findVersionByID|count|create|update|updateGlobal|delete|duplicate|restoreVersion";
+const WRITES: [&str; 5] = ["create", "update", "updateGlobal", "delete", "duplicate"];
+
+#[derive(Clone, Debug, Serialize, JsonSchema)]
+pub struct HookAnalysis {
+    /// Hook the code was checked as, e.g. beforeChange; absent when neither
+    /// given nor found in a type annotation or name
+    pub hook: Option<String>,
+    /// `collection`, `global` or `field` when the hook's type annotation tells
+    pub scope: Option<String>,
+    #[serde(flatten)]
+    pub result: ValidationResult,
+}
+
+/// Analyze the hook in `code`; `hook` names the hook when the code does not
+/// show it, e.g. `beforeChange`.
+pub fn analyze_hook(code: &str, hook: Option<&str>) -> Result<HookAnalysis, String> {
+    let masked = mask(code);
+    let (detected, scope) = detect_hook(&masked, code);
+    let hook = match hook {
+        Some(name) => Some(
+            HOOK_NAMES
+                .iter()
+                .find(|known| known.eq_ignore_ascii_case(name))
+                .ok_or_else(|| {
+                    format!(
+                        "Unknown hook: {name}. Expected one of {}",
+                        HOOK_NAMES.join(", ")
+                    )
+                })?
+                .to_string(),
+        ),
+        None => detected,
+    };
+    let body = function_body(&masked).ok_or("No function found in hook code")?;
+
+    let mut result = ValidationResult::ok();
+    if let Some(hook) = &hook {
+        check_return(&masked, body, hook, scope.as_deref(), &mut result);
+    }
+    check_local_api(&masked, body, hook.as_deref(), &mut result);
+    result.references.push(Reference {
+        title: "Payload CMS Hooks Documentation".to_string(),
+        url: "https://payloadcms.com/docs/hooks/overview".to_string(),
+    });
+    result.is_valid = result.errors.is_empty();
+    Ok(HookAnalysis {
+        hook,
+        scope,
+        result,
+    })
+}
+
+/// `code` with comments and the contents of string and template literals
+/// replaced by spaces, keeping offsets and line breaks.
+fn mask(code: &str) -> String {
+    let bytes = code.as_bytes();
+    let mut out = bytes.to_vec();
+    let blank_from = |out: &mut Vec<u8>, from: usize, to: usize| {
+        for byte in &mut out[from..to] {
+            if *byte != b'\n' {
+                *byte = b' ';
+            }
+        }
+    };
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'/' if bytes.get(i + 1) == Some(&b'/') => {
+                let end = code[i..].find('\n').map_or(bytes.len(), |n| i + n);
+                blank_from(&mut out, i, end);
+                i = end;
+            }
+            b'/' if bytes.get(i + 1) == Some(&b'*') => {
+                let end = code[i + 2..].find("*/").map_or(bytes.len(), |n| i + n + 4);
+                blank_from(&mut out, i, end);
+                i = end;
+            }
+            quote @ (b'\'' | b'"' | b'`') => {
+                let mut end = i + 1;
+                while end < bytes.len() && bytes[end] != quote {
+                    end += if bytes[end] == b'\\' { 2 } else { 1 };
+                }
+                let end = end.min(bytes.len());
+                blank_from(&mut out, i + 1, end);
+                i = end + 1;
+            }
+            _ => i += 1,
+        }
+    }
+    // Only ASCII bytes were replaced, so the result is still UTF-8
+    String::from_utf8(out).unwrap_or_default()
+}
+
+/// Hook name and scope from a `CollectionBeforeChangeHook`-style annotation,
+/// or the first hook name used in an identifier or key.
+fn detect_hook(masked: &str, code: &str) -> (Option<String>, Option<String>) {
+    static TYPED: OnceLock<Regex> = OnceLock::new();
+    let typed = TYPED.get_or_init(|| {
+        Regex::new(r"\b(Collection|Global)([A-Z]\w*?)Hook\b|\bFieldHook\b")
+            .expect("valid hook type pattern")
+    });
+    if let Some(caps) = typed.captures(masked) {
+        let Some(scope) = caps.get(1) else {
+            return (name_in(masked), Some("field".to_string()));
+        };
+        let name = caps.get(2).map(|name| {
+            let mut chars = name.as_str().chars();
+            chars
+                .next()
+                .map(|first| first.to_ascii_lowercase().to_string() + chars.as_str())
+                .unwrap_or_default()
+        });
+        let name = name.filter(|name| HOOK_NAMES.contains(&name.as_str()));
+        return (
+            name.or_else(|| name_in(masked)),
+            Some(scope.as_str().to_lowercase()),
+        );
+    }
+    (name_in(masked).or_else(|| name_in(code)), None)
+}
+
+/// The hook name appearing first in `text`, case-insensitively.
+fn name_in(text: &str) -> Option<String> {
+    let lower = text.to_lowercase();
+    HOOK_NAMES
+        .iter()
+        .filter_map(|name| lower.find(&name.to_lowercase()).map(|at| (at, name)))
+        .min()
+        .map(|(_, name)| name.to_string())
+}
+
+/// Byte range of the first function's body: the braces after the first `=>`
+/// or `function`, or the expression of a concise arrow body.
+fn function_body(masked: &str) -> Option<(usize, usize)> {
+    static START: OnceLock<Regex> = OnceLock::new();
+    let start =
+        START.get_or_init(|| Regex::new(r"=>|\bfunction\b").expect("valid function pattern"));
+    let found = start.find(masked)?;
+    let rest = &masked[found.end()..];
+    if found.as_str() == "=>" {
+        let offset = rest.len() - rest.trim_start().len();
+        if !rest.trim_start().starts_with('{') {
+            return Some((found.end() + offset, masked.len()));
+        }
+        return matching_brace(masked, found.end() + offset).map(|end| (found.end() + offset, end));
+    }
+    let open = found.end() + rest.find('{')?;
+    matching_brace(masked, open).map(|end| (open, end))
+}
+
+/// Offset just past the brace (or parenthesis) closing the one at `open`.
+fn matching_brace(masked: &str, open: usize) -> Option<usize> {
+    let (opening, closing) = match masked.as_bytes()[open] {
+        b'(' => (b'(', b')'),
+        _ => (b'{', b'}'),
+    };
+    let mut depth = 0usize;
+    for (offset, &byte) in masked.as_bytes()[open..].iter().enumerate() {
+        if byte == opening {
+            depth += 1;
+        } else if byte == closing {
+            depth -= 1;
+            if depth == 0 {
+                return Some(open + offset + 1);
+            }
+        }
+    }
+    None
+}
+
+fn line_of(code: &str, offset: usize) -> usize {
+    code[..offset].matches('\n').count() + 1
+}
+
+fn check_return(
+    masked: &str,
+    (start, end): (usize, usize),
+    hook: &str,
+    scope: Option<&str>,
+    result: &mut ValidationResult,
+) {
+    let Some(&(_, returned)) = RETURNED.iter().find(|(name, _)| *name == hook) else {
+        return;
+    };
+    let returned = if scope == Some("field") {
+        "value"
+    } else {
+        returned
+    };
+    let body = &masked[start..end];
+    // A concise arrow body is its own return value
+    if !body.starts_with('{') {
+        return;
+    }
+    static RETURN: OnceLock<Regex> = OnceLock::new();
+    let pattern =
+        RETURN.get_or_init(|| Regex::new(r"\breturn\b\s*([;}]|$)?").expect("valid return pattern"));
+    let mut returns_value = false;
+    for caps in pattern.captures_iter(body) {
+        let Some(whole) = caps.get(0) else { continue };
+        if caps.get(1).is_some() {
+            result.warnings.push(format!(
+                "Line {}: {hook} hook returns nothing here; Payload keeps the {returned} it passed in, so changes made to a copy are lost",
+                line_of(masked, start + whole.start())
+            ));
+        } else {
+            returns_value = true;
+        }
+    }
+    if !returns_value {
+        result.warnings.push(format!(
+            "{hook} hook never returns {returned}; Payload uses the returned value, so changes made to a copy are lost"
+        ));
+        result.suggestions.push(Suggestion {
+            message: format!("End the hook by returning {returned}"),
+            code: Some(format!("return {returned};")),
+        });
+    }
+}
+
+fn check_local_api(
+    masked: &str,
+    (start, end): (usize, usize),
+    hook: Option<&str>,
+    result: &mut ValidationResult,
+) {
+    static CALL: OnceLock<Regex> = OnceLock::new();
+    static DB: OnceLock<Regex> = OnceLock::new();
+    let call = CALL.get_or_init(|| {
+        Regex::new(&format!(r"\bpayload\s*\.\s*({LOCAL_API})\s*\("))
+            .expect("valid Local API pattern")
+    });
+    let db = DB
+        .get_or_init(|| Regex::new(r"\bpayload\s*\.\s*db\s*\.\s*(\w+)").expect("valid db pattern"));
+    let body = &masked[start..end];
+    let guarded = body.contains("context");
+    let mut bypasses_access = false;
+
+    for caps in call.captures_iter(body) {
+        let (Some(whole), Some(method)) = (caps.get(0), caps.get(1)) else {
+            continue;
+        };
+        let at = start + whole.start();
+        let line = line_of(masked, at);
+        let open = start + whole.end() - 1;
+        let args =
+            matching_brace(masked, open).map_or(&masked[open..], |close| &masked[open..close]);
+        let method = method.as_str();
+
+        if WRITES.contains(&method)
+            && !guarded
+            && hook.is_some_and(|hook| WRITE_TRIGGERED.contains(&hook))
+        {
+            result.warnings.push(format!(
+                "Line {line}: payload.{method} inside {} runs the collection's hooks again and can loop forever when it writes the same collection; change data directly or guard the call with a context flag",
+                hook.unwrap_or_default()
+            ));
+        }
+        if !has_word(args, "req") {
+            result.warnings.push(format!(
+                "Line {line}: payload.{method} does not pass req, so it runs outside the request's transaction and without its user"
+            ));
+        }
+        if !args.contains("overrideAccess") {
+            bypasses_access = true;
+        }
+    }
+
+    for caps in db.captures_iter(body) {
+        let Some(whole) = caps.get(0) else { continue };
+        result.warnings.push(format!(
+            "Line {}: payload.db.{} goes to the database adapter directly, skipping access control, hooks and validation",
+            line_of(masked, start + whole.start()),
+            caps.get(1).map_or("", |method| method.as_str())
+        ));
+    }
+
+    if guarded
+        && hook.is_some_and(|hook| WRITE_TRIGGERED.contains(&hook))
+        && body.contains("payload")
+    {
+        result.suggestions.push(Suggestion {
+            message:
+                "Check the context flag at the top of the hook so the nested write returns early"
+                    .to_string(),
+            code: Some("if (context.skipHook) return data;".to_string()),
+        });
+    } else if !guarded
+        && result
+            .warnings
+            .iter()
+            .any(|warning| warning.contains("loop forever"))
+    {
+        result.suggestions.push(Suggestion {
+            message: "Mark nested writes with a context flag and skip the hook when it is set".to_string(),
+            code: Some("await req.payload.update({ collection, id, data, req, context: { skipHook: true } });".to_string()),
+        });
+    }
+    if bypasses_access {
+        result.suggestions.push(Suggestion {
+            message: "Local API calls skip access control unless overrideAccess is false; pass it with req when the hook acts for the user".to_string(),
+            code: Some("overrideAccess: false,".to_string()),
+        });
+        result.references.push(Reference {
+            title: "Payload CMS Local API Access Control".to_string(),
+            url: "https://payloadcms.com/docs/local-api/access-control".to_string(),
+        });
+    }
+    if result
+        .warnings
+        .iter()
+        .any(|warning| warning.contains("transaction"))
+    {
+        result.references.push(Reference {
+            title: "Payload CMS Transactions".to_string(),
+            url: "https://payloadcms.com/docs/database/transactions".to_string(),
+        });
+    }
+}
+
+/// Whether `word` appears in `text` as a whole identifier.
+fn has_word(text: &str, word: &str) -> bool {
+    text.match_indices(word).any(|(at, _)| {
+        let is_ident = |byte: u8| byte.is_ascii_alphanumeric() || byte == b'_' || byte == b'$';
+        let before = at.checked_sub(1).map(|i| text.as_bytes()[i]);
+        let after = text.as_bytes().get(at + word.len()).copied();
+        !before.is_some_and(is_ident) && !after.is_some_and(is_ident)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_analyze_hook_flags_common_mistakes() {
+        let code = r#"
+import { CollectionBeforeChangeHook } from 'payload/types';
+
+// return data is never reached
+export const stampHook: CollectionBeforeChangeHook = async ({ data, req }) => {
+  const { docs } = await req.payload.find({ collection: 'tags', where: { title: { equals: 'return;' } } });
+  await req.payload.update({ collection: 'posts', id: data.id, data: { tags: docs }, req });
+  await req.payload.db.updateOne({ collection: 'posts', where: {}, data: {} });
+  data.tagCount = docs.length;
+};
+"#;
+        let analysis = analyze_hook(code, None).unwrap();
+        assert_eq!(analysis.hook.as_deref(), Some("beforeChange"));
+        assert_eq!(analysis.scope.as_deref(), Some("collection"));
+        let warnings = &analysis.result.warnings;
+        assert!(
+            warnings.iter().any(|w| w.contains("never returns data")),
+            "{warnings:#?}"
+        );
+        assert!(
+            warnings
+                .iter()
+                .any(|w| w.starts_with("Line 6: payload.find does not pass req"))
+        );
+        assert!(
+            warnings
+                .iter()
+                .any(|w| w.starts_with("Line 7: payload.update inside beforeChange"))
+        );
+        assert!(
+            warnings
+                .iter()
+                .any(|w| w.starts_with("Line 8: payload.db.updateOne"))
+        );
+        assert!(!warnings.iter().any(|w| w.contains("returns nothing here")));
+
+        let fixed = r#"
+const hook: FieldHook = ({ value, req, context }) => {
+  if (context.skipHook) return value;
+  return value?.trim();
+};
+"#;
+        let analysis = analyze_hook(fixed, Some("beforeChange")).unwrap();
+        assert_eq!(analysis.scope.as_deref(), Some("field"));
+        assert!(
+            analysis.result.warnings.is_empty(),
+            "{:#?}",
+            analysis.result.warnings
+        );
+        assert!(analyze_hook(fixed, Some("beforeSave")).is_err());
+    }
+}
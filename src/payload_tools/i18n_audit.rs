@@ -0,0 +1,283 @@
+//! Translation coverage of a localized live instance. Each collection is read
+//! once per locale with fallback disabled, so an untranslated field comes
+//! back empty instead of holding the default locale's value.
+//!
+//! Localized fields are found at the top level and inside groups. Fields
+//! inside arrays and blocks are per row and are not inspected; a localized
+//! array or blocks field counts as missing when it has no rows.
+
+use std::collections::BTreeMap;
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::payload_tools::{
+    client::PayloadClient,
+    schema_model::{SchemaField, SchemaModel},
+};
+
+const DEFAULT_BATCH_SIZE: usize = 100;
+const DEFAULT_MAX_DOCUMENTS: usize = 1000;
+const DEFAULT_SAMPLES: usize = 5;
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct I18nAuditOptions {
+    /// Locale codes to check, as in the config's `localization.locales`
+    pub locales: Vec<String>,
+    /// Only audit these collections; defaults to every collection with
+    /// localized fields
+    pub collections: Option<Vec<String>>,
+    /// Documents fetched per request, default 100
+    pub batch_size: Option<usize>,
+    /// Documents read per collection and locale, default 1000
+    pub max_documents: Option<usize>,
+    /// Document IDs listed per missing field and locale, default 5
+    pub samples: Option<usize>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct MissingTranslations {
+    /// Dotted path of the field
+    pub field: String,
+    pub locale: String,
+    pub missing: usize,
+    pub sample_ids: Vec<Value>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct CollectionTranslations {
+    pub slug: String,
+    pub localized_fields: Vec<String>,
+    /// Documents read per locale
+    pub documents: BTreeMap<String, usize>,
+    /// Field and locale pairs with at least one missing value
+    pub missing: Vec<MissingTranslations>,
+    /// The collection holds more documents than `maxDocuments`
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub truncated: bool,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+pub struct I18nAudit {
+    pub collections: Vec<CollectionTranslations>,
+    pub warnings: Vec<String>,
+}
+
+/// Dotted paths of the localized fields under `fields`, descending into
+/// groups that aren't localized themselves.
+pub fn localized_fields(prefix: &str, fields: &[SchemaField], out: &mut Vec<String>) {
+    for field in fields {
+        let path = if prefix.is_empty() {
+            field.name.clone()
+        } else {
+            format!("{prefix}.{}", field.name)
+        };
+        if field.localized {
+            out.push(path);
+        } else if field.field_type == "group" {
+            localized_fields(&path, field.fields.as_deref().unwrap_or_default(), out);
+        }
+    }
+}
+
+/// An absent, null, blank or empty value. Rich text counts as empty when
+/// its Lexical root holds nothing but empty paragraphs.
+pub(crate) fn is_missing(value: Option<&Value>) -> bool {
+    match value {
+        None | Some(Value::Null) => true,
+        Some(Value::String(text)) => text.trim().is_empty(),
+        Some(Value::Array(items)) => items.is_empty(),
+        Some(Value::Object(object)) => match object.get("root") {
+            Some(root) => root["children"]
+                .as_array()
+                .is_none_or(|nodes| {
+                    nodes.iter().all(|node| {
+                        node["children"].as_array().is_some_and(Vec::is_empty)
+                    })
+                }),
+            None => object.is_empty(),
+        },
+        Some(_) => false,
+    }
+}
+
+pub(crate) fn lookup<'a>(doc: &'a Value, path: &str) -> Option<&'a Value> {
+    path.split('.').try_fold(doc, |value, key| value.get(key))
+}
+
+/// Missing translations per field and locale, in field order.
+#[derive(Default)]
+struct Tally {
+    entries: BTreeMap<(usize, String), MissingTranslations>,
+}
+
+impl Tally {
+    /// Record the documents of one page read in `locale`.
+    fn record(&mut self, fields: &[String], locale: &str, docs: &[Value], samples: usize) {
+        for doc in docs {
+            for (index, field) in fields.iter().enumerate() {
+                if !is_missing(lookup(doc, field)) {
+                    continue;
+                }
+                let entry = self
+                    .entries
+                    .entry((index, locale.to_string()))
+                    .or_insert_with(|| MissingTranslations {
+                        field: field.clone(),
+                        locale: locale.to_string(),
+                        missing: 0,
+                        sample_ids: Vec::new(),
+                    });
+                entry.missing += 1;
+                if entry.sample_ids.len() < samples {
+                    entry.sample_ids.push(doc["id"].clone());
+                }
+            }
+        }
+    }
+
+    fn into_missing(self) -> Vec<MissingTranslations> {
+        self.entries.into_values().collect()
+    }
+}
+
+/// Read every audited collection of `schema` once per locale and report
+/// the localized fields left empty.
+pub fn i18n_audit(client: &PayloadClient, schema: &SchemaModel, options: &I18nAuditOptions) -> Result<I18nAudit, String> {
+    if options.locales.is_empty() {
+        return Err("locales must list at least one locale".to_string());
+    }
+    let batch_size = options.batch_size.unwrap_or(DEFAULT_BATCH_SIZE).max(1);
+    let max_documents = options.max_documents.unwrap_or(DEFAULT_MAX_DOCUMENTS);
+    let samples = options.samples.unwrap_or(DEFAULT_SAMPLES);
+
+    let mut audit = I18nAudit::default();
+    let mut collections = Vec::new();
+    match &options.collections {
+        Some(slugs) => {
+            for slug in slugs {
+                match schema.collection(slug) {
+                    Some(collection) => collections.push(collection),
+                    None => audit.warnings.push(format!("Collection {slug} is not in the schema")),
+                }
+            }
+        }
+        None => collections.extend(&schema.collections),
+    }
+
+    for collection in collections {
+        let mut fields = Vec::new();
+        localized_fields("", &collection.fields, &mut fields);
+        if fields.is_empty() {
+            if options.collections.is_some() {
+                audit
+                    .warnings
+                    .push(format!("Collection {} has no localized fields", collection.slug));
+            }
+            continue;
+        }
+        let mut report = CollectionTranslations {
+            slug: collection.slug.clone(),
+            ..Default::default()
+        };
+        let mut tally = Tally::default();
+        for locale in &options.locales {
+            let mut read = 0;
+            let mut page = 1;
+            while read < max_documents {
+                let params = vec![
+                    ("limit".to_string(), batch_size.to_string()),
+                    ("page".to_string(), page.to_string()),
+                    ("depth".to_string(), "0".to_string()),
+                    ("sort".to_string(), "id".to_string()),
+                ];
+                let body = client
+                    .find_page_in_locale(&collection.slug, locale, &params)
+                    .map_err(|err| err.to_string())?;
+                let docs = body["docs"].as_array().cloned().unwrap_or_default();
+                let kept = &docs[..docs.len().min(max_documents - read)];
+                tally.record(&fields, locale, kept, samples);
+                read += kept.len();
+                let more = kept.len() < docs.len() || body["hasNextPage"].as_bool().unwrap_or(false);
+                if docs.is_empty() || !more {
+                    break;
+                }
+                if read >= max_documents {
+                    report.truncated = true;
+                }
+                page += 1;
+            }
+            report.documents.insert(locale.clone(), read);
+        }
+        report.localized_fields = fields;
+        report.missing = tally.into_missing();
+        audit.collections.push(report);
+    }
+
+    if audit.collections.is_empty() && audit.warnings.is_empty() {
+        audit.warnings.push(
+            "No localized fields found; pass the config as schema or files when the live schema doesn't mark them"
+                .to_string(),
+        );
+    }
+    Ok(audit)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn finds_localized_fields_in_groups() {
+        let mut meta = SchemaField::new("meta", "group");
+        meta.fields = Some(vec![
+            SchemaField {
+                localized: true,
+                ..SchemaField::new("description", "textarea")
+            },
+            SchemaField::new("image", "upload"),
+        ]);
+        let fields = vec![
+            SchemaField {
+                localized: true,
+                ..SchemaField::new("title", "text")
+            },
+            SchemaField::new("slug", "text"),
+            meta,
+        ];
+        let mut out = Vec::new();
+        localized_fields("", &fields, &mut out);
+        assert_eq!(out, vec!["title", "meta.description"]);
+    }
+
+    #[test]
+    fn counts_missing_values_per_locale() {
+        let fields = vec!["title".to_string(), "content".to_string(), "meta.description".to_string()];
+        let empty_rich_text = json!({ "root": { "children": [{ "type": "paragraph", "children": [] }] } });
+        let docs = vec![
+            json!({ "id": 1, "title": "Hola", "content": empty_rich_text, "meta": { "description": "" } }),
+            json!({ "id": 2, "title": null, "content": { "root": { "children": [{ "children": [{ "text": "Hi" }] }] } } }),
+            json!({ "id": 3, "title": "  ", "content": null, "meta": { "description": "Ok" } }),
+        ];
+        let mut tally = Tally::default();
+        tally.record(&fields, "es", &docs, 1);
+        let missing: Vec<(String, usize, Vec<Value>)> = tally
+            .into_missing()
+            .into_iter()
+            .map(|entry| (entry.field, entry.missing, entry.sample_ids))
+            .collect();
+        assert_eq!(
+            missing,
+            vec![
+                ("title".to_string(), 2, vec![json!(2)]),
+                ("content".to_string(), 2, vec![json!(1)]),
+                ("meta.description".to_string(), 2, vec![json!(1)]),
+            ]
+        );
+    }
+}
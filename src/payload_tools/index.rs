@@ -1,13 +1,21 @@
 pub use crate::payload_tools::client::*;
+pub use crate::payload_tools::completions::*;
 pub use crate::payload_tools::generator::*;
 pub use crate::payload_tools::mcp::*;
+pub use crate::payload_tools::prompts::*;
 pub use crate::payload_tools::query::{
     get_categories, get_validation_rule_by_id, get_validation_rules_by_category,
     get_validation_rules_by_file_type, get_validation_rules_with_examples, query_validation_rules,
 };
+pub use crate::payload_tools::resources::*;
 pub use crate::payload_tools::scaffolder::*;
 pub use crate::payload_tools::schemas::*;
-pub use crate::payload_tools::sql::execute_sql_query;
+pub use crate::payload_tools::search::*;
+pub use crate::payload_tools::sql::{
+    execute_prepared_query, execute_sql_query, execute_sql_query_with_client,
+};
+pub use crate::payload_tools::storage::*;
+pub use crate::payload_tools::testgen::*;
 pub use crate::payload_tools::types::*;
 pub use crate::payload_tools::validator::*;
 
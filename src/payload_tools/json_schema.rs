@@ -0,0 +1,346 @@
+//! JSON Schema (draft 2020-12) for Payload documents, one per collection and
+//! global. The field mapping is shared with the OpenAPI generator, which
+//! differs only in where IDs, related documents and blocks are defined.
+
+use std::collections::BTreeMap;
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value, json};
+
+use crate::payload_tools::schema_model::{SchemaCollection, SchemaField, SchemaModel};
+
+/// Where field schemas point for definitions shared between documents.
+pub(crate) trait SchemaRefs {
+    /// Schema of a document ID
+    fn id(&self) -> Value;
+    /// Schema of a populated document of collection `slug`
+    fn document(&self, slug: &str) -> Value;
+    /// Schema to use for a block's rows, given the row schema
+    fn block(&mut self, _slug: &str, row: Value) -> Value {
+        row
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct JsonSchemaOptions {
+    /// Describe the data written to Payload rather than documents it returns:
+    /// relationships are IDs and generated fields are left out
+    pub input: Option<bool>,
+    /// Base of each schema's `$id`, e.g. "https://example.com/schemas/";
+    /// relationships refer to the related collection's schema by relative URI
+    pub base_uri: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct JsonSchemaOutput {
+    /// Schema per collection slug
+    pub collections: BTreeMap<String, Value>,
+    /// Schema per global slug
+    pub globals: BTreeMap<String, Value>,
+}
+
+/// `blog-posts` -> `BlogPosts`
+pub(crate) fn schema_name(slug: &str) -> String {
+    slug.split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|part| !part.is_empty())
+        .map(|part| {
+            let mut chars = part.chars();
+            match chars.next() {
+                Some(first) => first.to_ascii_uppercase().to_string() + chars.as_str(),
+                None => String::new(),
+            }
+        })
+        .collect()
+}
+
+/// JSON Schema for a field's value. `input` describes request bodies, where
+/// relationships are IDs rather than populated documents.
+pub(crate) fn field_schema(field: &SchemaField, input: bool, refs: &mut impl SchemaRefs) -> Value {
+    let one = match field.field_type.as_str() {
+        "text" | "textarea" | "code" => json!({ "type": "string" }),
+        "email" => json!({ "type": "string", "format": "email" }),
+        "date" => json!({ "type": "string", "format": "date-time" }),
+        "number" => json!({ "type": "number" }),
+        "checkbox" => json!({ "type": "boolean" }),
+        "select" | "radio" => match &field.options {
+            Some(options) => json!({ "type": "string", "enum": options }),
+            None => json!({ "type": "string" }),
+        },
+        "point" => json!({
+            "type": "array",
+            "prefixItems": [{ "type": "number" }, { "type": "number" }],
+            "minItems": 2,
+            "maxItems": 2,
+        }),
+        "relationship" | "upload" => relationship_schema(field, input, &*refs),
+        "group" => object_schema(
+            field.fields.as_deref().unwrap_or_default(),
+            input,
+            false,
+            refs,
+        ),
+        "array" => {
+            let row = object_schema(
+                field.fields.as_deref().unwrap_or_default(),
+                input,
+                true,
+                refs,
+            );
+            return json!({ "type": "array", "items": row });
+        }
+        "blocks" => {
+            let mut blocks = Vec::new();
+            for block in field.blocks.iter().flatten() {
+                let mut row = object_schema(&block.fields, input, true, refs);
+                row["properties"]["blockName"] = json!({ "type": ["string", "null"] });
+                row["properties"]["blockType"] = json!({ "const": block.slug });
+                if let Some(required) = row["required"].as_array_mut() {
+                    required.push(json!("blockType"));
+                } else {
+                    row["required"] = json!(["blockType"]);
+                }
+                blocks.push(refs.block(&block.slug, row));
+            }
+            return json!({ "type": "array", "items": { "oneOf": blocks } });
+        }
+        "richText" => json!({ "type": ["object", "array"] }),
+        _ => json!({}),
+    };
+    if field.has_many {
+        json!({ "type": "array", "items": one })
+    } else {
+        one
+    }
+}
+
+fn relationship_schema(field: &SchemaField, input: bool, refs: &impl SchemaRefs) -> Value {
+    let slugs = field.relation_to.as_deref().unwrap_or_default();
+    let value = |slug: &str| {
+        if input {
+            refs.id()
+        } else {
+            json!({ "oneOf": [refs.id(), refs.document(slug)] })
+        }
+    };
+    match slugs {
+        [slug] => value(slug.as_str()),
+        [] => refs.id(),
+        _ => json!({
+            "oneOf": slugs
+                .iter()
+                .map(|slug| json!({
+                    "type": "object",
+                    "properties": {
+                        "relationTo": { "const": slug },
+                        "value": value(slug.as_str()),
+                    },
+                    "required": ["relationTo", "value"],
+                }))
+                .collect::<Vec<_>>()
+        }),
+    }
+}
+
+/// An object of `fields`; array and block rows also carry an `id`.
+pub(crate) fn object_schema(
+    fields: &[SchemaField],
+    input: bool,
+    row: bool,
+    refs: &mut impl SchemaRefs,
+) -> Value {
+    let mut properties = Map::new();
+    let mut required = Vec::new();
+    if row {
+        properties.insert("id".to_string(), json!({ "type": ["string", "null"] }));
+    }
+    for field in fields {
+        properties.insert(field.name.clone(), field_schema(field, input, refs));
+        if field.required {
+            required.push(json!(field.name));
+        }
+    }
+    let mut schema = json!({ "type": "object", "properties": properties });
+    if !required.is_empty() {
+        schema["required"] = Value::Array(required);
+    }
+    schema
+}
+
+/// A collection's documents as Payload returns them, with the ID, built-in
+/// auth and upload fields, and timestamps.
+pub(crate) fn document_schema(collection: &SchemaCollection, refs: &mut impl SchemaRefs) -> Value {
+    let mut schema = object_schema(&collection.fields, false, false, refs);
+    schema["properties"]["id"] = refs.id();
+    let mut required = vec![json!("id")];
+    if collection.auth {
+        schema["properties"]["email"] = json!({ "type": "string", "format": "email" });
+        required.push(json!("email"));
+    }
+    if collection.upload {
+        for name in ["url", "filename", "mimeType"] {
+            schema["properties"][name] = json!({ "type": ["string", "null"] });
+        }
+        for name in ["filesize", "width", "height"] {
+            schema["properties"][name] = json!({ "type": ["number", "null"] });
+        }
+    }
+    if collection.timestamps {
+        for name in ["createdAt", "updatedAt"] {
+            schema["properties"][name] = json!({ "type": "string", "format": "date-time" });
+            required.push(json!(name));
+        }
+    }
+    if let Some(existing) = schema["required"].as_array() {
+        required.extend(existing.iter().cloned());
+    }
+    schema["required"] = Value::Array(required);
+    schema
+}
+
+/// The body written to create or update a collection's documents.
+pub(crate) fn input_schema(collection: &SchemaCollection, refs: &mut impl SchemaRefs) -> Value {
+    let mut schema = object_schema(&collection.fields, true, false, refs);
+    if collection.auth {
+        schema["properties"]["email"] = json!({ "type": "string", "format": "email" });
+        schema["properties"]["password"] = json!({ "type": "string", "format": "password" });
+    }
+    schema
+}
+
+/// Standalone documents: blocks go to `$defs`, related collections are
+/// referenced by their schema's URI.
+struct Definitions {
+    defs: Map<String, Value>,
+}
+
+fn schema_uri(slug: &str) -> String {
+    format!("{slug}.schema.json")
+}
+
+impl SchemaRefs for Definitions {
+    fn id(&self) -> Value {
+        json!({ "type": ["string", "integer"] })
+    }
+
+    fn document(&self, slug: &str) -> Value {
+        json!({ "$ref": schema_uri(slug) })
+    }
+
+    fn block(&mut self, slug: &str, row: Value) -> Value {
+        let name = format!("{}Block", schema_name(slug));
+        self.defs.insert(name.clone(), row);
+        json!({ "$ref": format!("#/$defs/{name}") })
+    }
+}
+
+fn standalone(
+    slug: &str,
+    options: &JsonSchemaOptions,
+    build: impl FnOnce(&mut Definitions) -> Value,
+) -> Value {
+    let mut definitions = Definitions { defs: Map::new() };
+    let schema = build(&mut definitions);
+    let mut document = json!({
+        "$schema": "https://json-schema.org/draft/2020-12/schema",
+        "$id": format!("{}{}", options.base_uri.as_deref().unwrap_or_default(), schema_uri(slug)),
+        "title": schema_name(slug),
+    });
+    if let (Some(document), Value::Object(schema)) = (document.as_object_mut(), schema) {
+        document.extend(schema);
+    }
+    if !definitions.defs.is_empty() {
+        document["$defs"] = Value::Object(definitions.defs);
+    }
+    document
+}
+
+pub fn json_schemas(schema: &SchemaModel, options: &JsonSchemaOptions) -> JsonSchemaOutput {
+    let input = options.input.unwrap_or(false);
+    let collections = schema
+        .collections
+        .iter()
+        .map(|collection| {
+            let document = standalone(&collection.slug, options, |refs| {
+                if input {
+                    input_schema(collection, refs)
+                } else {
+                    document_schema(collection, refs)
+                }
+            });
+            (collection.slug.clone(), document)
+        })
+        .collect();
+    let globals = schema
+        .globals
+        .iter()
+        .map(|global| {
+            let document = standalone(&global.slug, options, |refs| {
+                let mut schema = object_schema(&global.fields, input, false, refs);
+                if !input {
+                    schema["properties"]["globalType"] = json!({ "const": global.slug });
+                }
+                schema
+            });
+            (global.slug.clone(), document)
+        })
+        .collect();
+    JsonSchemaOutput {
+        collections,
+        globals,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::payload_tools::schema_model::SchemaBlock;
+
+    #[test]
+    fn collects_blocks_into_defs() {
+        let mut author = SchemaField::new("author", "relationship");
+        author.relation_to = Some(vec!["users".to_string()]);
+        let mut layout = SchemaField::new("layout", "blocks");
+        layout.blocks = Some(vec![SchemaBlock {
+            slug: "call-to-action".to_string(),
+            fields: vec![SchemaField {
+                required: true,
+                ..SchemaField::new("label", "text")
+            }],
+        }]);
+        let schema = SchemaModel {
+            collections: vec![SchemaCollection {
+                slug: "pages".to_string(),
+                fields: vec![author, layout],
+                timestamps: true,
+                ..Default::default()
+            }],
+            globals: Vec::new(),
+        };
+        let options = JsonSchemaOptions {
+            base_uri: Some("https://example.com/schemas/".to_string()),
+            ..Default::default()
+        };
+        let output = json_schemas(&schema, &options);
+        let pages = &output.collections["pages"];
+        assert_eq!(
+            pages["$id"],
+            "https://example.com/schemas/pages.schema.json"
+        );
+        assert_eq!(pages["type"], "object");
+        assert_eq!(
+            pages["properties"]["layout"]["items"]["oneOf"][0]["$ref"],
+            "#/$defs/CallToActionBlock"
+        );
+        assert_eq!(
+            pages["$defs"]["CallToActionBlock"]["required"],
+            json!(["label", "blockType"])
+        );
+        assert_eq!(
+            pages["properties"]["author"]["oneOf"][1]["$ref"],
+            "users.schema.json"
+        );
+        assert_eq!(pages["required"], json!(["id", "createdAt", "updatedAt"]));
+    }
+}
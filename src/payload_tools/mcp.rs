@@ -1,20 +1,81 @@
+use std::collections::BTreeMap;
+use std::path::Path;
+
 use schemars::JsonSchema;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
 
 use crate::payload_tools::{
-    generator::{generate_template, TemplateType},
+    access_matrix::{live_matrix, static_matrix, AccessMatrix, TableFormat, LIVE_USER},
+    collection_draft::{draft_collection, CollectionDraft},
+    content::{export_content, import_content, ContentExportOptions, ContentImport, ContentImportOptions},
+    backup::{backup_instance, restore_instance, Backup, BackupOptions, Restore, RestoreOptions},
+    db_import::{
+        import_from_database, infer_from_samples, DatabaseImport, DatabaseImportOptions, SampleInference,
+        SampleInferenceOptions,
+    },
+    external_schema::{import_external_schema, ExternalSchemaImport, ExternalSchemaOptions},
+    wordpress::{wp_export_to_seed, wp_to_payload_schema, WpSchemaOptions, WpSeed, WpSeedOptions},
+    email::{email_config, EmailConfig, EmailConfigOptions},
+    examples::{find_examples, require_example, ExampleCode, ExampleSummary},
+    forms::{form_builder_config, FormBuilderConfig, FormBuilderOptions},
+    preview::{preview_config, PreviewConfig, PreviewOptions},
+    audit_log::{audit_log_config, AuditLogConfig, AuditLogOptions},
+    workflow::{workflow_config, WorkflowConfig, WorkflowOptions},
+    seo::{seo_config, SeoConfig, SeoOptions},
+    redirects::{redirects_config, RedirectsConfig, RedirectsOptions},
+    site_search::{site_search_config, SiteSearchConfig, SiteSearchOptions},
+    ecommerce::{ecommerce_config, EcommerceConfig, EcommerceOptions},
+    webhooks::{webhooks_config, WebhooksConfig, WebhooksOptions},
+    generator::{generate_template_with_naming, TemplateType},
+    hook_analyzer::{analyze_hook, HookAnalysis},
+    i18n_audit::{i18n_audit, I18nAudit, I18nAuditOptions},
+    content_audit::{content_audit, ContentAudit, ContentAuditOptions},
+    bulk_update::{bulk_update, BulkUpdate, BulkUpdateOptions},
+    anonymize::{anonymize_export, AnonymizeOptions},
+    multitenancy::{multitenancy_files, MultitenancyOptions},
+    naming,
+    json_schema::{json_schemas, JsonSchemaOptions, JsonSchemaOutput},
+    openapi::{openapi_document, OpenApiOptions, OpenApiOutput},
+    graphql::{graphql_sdl, GraphqlSdlOptions, GraphqlSdlOutput},
+    client_sdk::{client_sdk, ClientSdkOptions, ClientSdkOutput},
+    payload_types::{parse_payload_types, PayloadTypesSchema},
+    config_patch::{patch_config, ConfigEdit, PatchConfigOutput},
+    dependencies::{check_dependencies, DependencyReport},
+    env_audit::{config_env, env_audit, project_env, EnvAudit, EnvAuditOptions},
+    relationship_depth::{analyze_depth, DepthAnalysis, DepthOptions},
+    schema_graph::{schema_graph, SchemaGraph, SchemaGraphOptions},
+    schema_model::{live_schema, SchemaModel},
     query::{get_validation_rules_with_examples, query_validation_rules},
     scaffolder::{
-        scaffold_project, validate_scaffold_options, ScaffoldFile, ScaffoldFileStructure,
-        ScaffoldOptions,
+        add_to_project, scaffold_project, validate_scaffold, validate_scaffold_options, write_scaffold_with_progress,
+        CollectionOption, ManifestEntry, ProjectAdditions, ScaffoldFile, ScaffoldFileStructure, ScaffoldOptions,
     },
-    sql::execute_sql_query,
-    types::FileType,
-    validator::validate_payload_code,
-    client::create_payload_client,
+    plugins::{plugin_config, PayloadVersion, PluginConfig},
+    schemas::{require_field_type, FieldTypeInfo, COMMON_ADMIN_OPTIONS, COMMON_OPTIONS, LAYOUT_OPTIONS},
+    search::{search, search_docs, DocMatch, SearchKind, SearchResult, DEFAULT_SEARCH_LIMIT},
+    self_check::{self_check, self_check_passed},
+    sql::execute_prepared_query,
+    breaking_changes::{check_breaking_changes, BreakingChangeOptions, BreakingChangeReport},
+    schema_registry::{diff_schema_versions, register_schema, SchemaRegistration, SchemaVersion, SchemaVersionDiff},
+    storage::{artifacts_from_result, NewTodo, Storage},
+    storage_adapters::{storage_adapter_config, StorageAdapterOptions},
+    template_overrides::{self, override_path, template_variables, templates_dir, TemplateVariable},
+    testgen::generate_collection_tests,
+    where_query::{convert_query, ConvertedQuery, QueryFormat},
+    types::{
+        Artifact, FileType, Goal, GoalTodo, ImplementationPlan, PlanRevision, SourceFile, SqlBindings, SqlQueryResult, Todo, ValidationReportFormat,
+        ValidationResult, ValidationRule,
+    },
+    validator::{check_production, validate_payload_code_with_naming},
+    client::{CollectionInfo, PayloadClient, ServerInfo},
+};
+use crate::{
+    connections::{self, resolve as resolve_connection, Connection, Connections},
+    paths,
+    project_runner::{stop_project, ProjectStatus, StoppedProject},
 };
-use rmcp::model::{CallToolResult, Content, Tool};
+use rmcp::model::{CallToolResult, Content, Tool, ToolAnnotations};
 use rmcp::ErrorData;
 
 #[derive(Debug, Deserialize, JsonSchema)]
@@ -26,6 +87,15 @@ pub struct EchoParams {
 pub struct ValidateParams {
     pub code: String,
     pub file_type: FileType,
+    /// File the code came from, used to locate results in `sarif` and `junit` reports
+    pub path: Option<String>,
+    /// Also render the result as a `sarif` or `junit` report
+    pub format: Option<ValidationReportFormat>,
+    /// Naming profile to enforce; defaults to the one in settings
+    pub naming_profile: Option<String>,
+    /// Also warn about configs missing csrf, cors or, on Payload 2,
+    /// rateLimit (default false)
+    pub production: Option<bool>,
 }
 
 #[derive(Debug, Deserialize, JsonSchema)]
@@ -34,15 +104,111 @@ pub struct QueryParams {
     pub file_type: Option<FileType>,
 }
 
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct DescribeFieldTypeParams {
+    /// Field type, e.g. `relationship`
+    #[serde(rename = "type")]
+    pub field_type: String,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct SearchParams {
+    pub query: String,
+    /// Restrict results to these kinds: rule, instruction, template, best_practice, doc
+    pub kinds: Option<Vec<SearchKind>>,
+    pub limit: Option<usize>,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct SearchDocsParams {
+    pub query: String,
+    /// Search one guide: access-control, hooks, versioning or uploads
+    pub topic: Option<String>,
+    pub limit: Option<usize>,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct GetExampleParams {
+    /// Example to return code for: blog, saas, marketplace or docs
+    pub id: Option<String>,
+    /// Only list examples with all of these feature tags, e.g. roles, drafts, uploads
+    pub tags: Option<Vec<String>>,
+    /// Only list examples mentioning this text
+    pub query: Option<String>,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct AnalyzeHookParams {
+    /// TypeScript source of the hook function
+    pub code: String,
+    /// Hook to check the code as, e.g. beforeChange or afterRead; detected from
+    /// the type annotation or names when omitted
+    pub hook: Option<String>,
+    /// File the code came from, used to locate results in `sarif` and `junit` reports
+    pub path: Option<String>,
+    /// Also render the result as a `sarif` or `junit` report
+    pub format: Option<ValidationReportFormat>,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct AccessMatrixParams {
+    /// Config, collection, global and access helper sources
+    pub files: Option<Vec<SourceFile>>,
+    /// Roles to evaluate; defaults to the role options of auth collections
+    /// and the roles the access code checks for
+    pub roles: Option<Vec<String>>,
+    /// Read the permissions of the connection's user from a live instance, by
+    /// default the one from `connect_payload`
+    pub live: Option<bool>,
+    pub connection_string: Option<String>,
+    pub api_key: Option<String>,
+    /// Stored connection to use instead of `connection_string`
+    pub connection: Option<String>,
+    /// Column name for the live user (default `connection`)
+    pub role: Option<String>,
+    /// Table rendering: markdown (default) or ascii
+    pub format: Option<TableFormat>,
+}
+
+impl AccessMatrixParams {
+    pub(crate) fn is_live(&self) -> bool {
+        self.live.unwrap_or(false) || self.connection.is_some() || self.connection_string.is_some()
+    }
+}
+
 #[derive(Debug, Deserialize, JsonSchema)]
 pub struct SqlParams {
     pub sql: String,
+    /// Values for `?` placeholders (array) or `:name` placeholders (object)
+    pub params: Option<SqlBindings>,
+    /// Optional Payload instance whose collections are exposed as tables
+    pub connection_string: Option<String>,
+    pub api_key: Option<String>,
+    /// Stored connection to use instead of `connection_string`
+    pub connection: Option<String>,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct ConvertQueryParams {
+    /// A `where` object (or its JSON text), a REST query string such as
+    /// `where[status][equals]=draft`, or an SQL condition such as `status = 'draft'`
+    pub query: Value,
+    /// Format of `query`; inferred when omitted
+    pub from: Option<QueryFormat>,
 }
 
 #[derive(Debug, Deserialize, JsonSchema)]
 pub struct GenerateTemplateParams {
     pub template_type: TemplateType,
     pub options: Value,
+    /// Naming profile applied to slugs and names; defaults to the one in settings
+    pub naming_profile: Option<String>,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct ListTemplateVariablesParams {
+    /// Only describe this template type
+    pub template_type: Option<TemplateType>,
 }
 
 #[derive(Debug, Deserialize, JsonSchema)]
@@ -55,6 +221,22 @@ pub struct GenerateCollectionParams {
     pub hooks: Option<bool>,
     pub access: Option<bool>,
     pub versions: Option<bool>,
+    /// Soft-delete documents: a deletedAt field, reads hiding deleted
+    /// documents from non-admins, and soft-delete and restore endpoints
+    pub soft_delete: Option<bool>,
+    /// Naming profile applied to slugs and names; defaults to the one in settings
+    pub naming_profile: Option<String>,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct DraftCollectionParams {
+    /// Plain-English content model, e.g. "Blog posts with a title, a cover
+    /// image, an author and tags that relate to many tags"
+    pub description: String,
+    /// Slug to use instead of the one taken from the description
+    pub slug: Option<String>,
+    /// Naming profile applied to the generated code; defaults to the one in settings
+    pub naming_profile: Option<String>,
 }
 
 #[derive(Debug, Deserialize, JsonSchema)]
@@ -69,96 +251,1729 @@ pub struct GenerateFieldParams {
     pub admin: Option<Value>,
     pub validation: Option<bool>,
     pub default_value: Option<Value>,
+    /// Naming profile applied to the name; defaults to the one in settings
+    pub naming_profile: Option<String>,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct GeneratePluginConfigParams {
+    /// Official plugin: seo, form-builder, nested-docs, redirects, search,
+    /// stripe, cloud-storage, sentry or multi-tenant
+    pub plugin: String,
+    /// Payload major version, "2" (default) or "3"
+    pub payload_version: Option<PayloadVersion>,
+    /// Plugin options laid over the defaults; function options take
+    /// JavaScript source as a string
+    pub options: Option<Value>,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct GenerateStorageAdapterParams {
+    #[serde(flatten)]
+    pub options: StorageAdapterOptions,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct GenerateEmailConfigParams {
+    #[serde(flatten)]
+    pub options: EmailConfigOptions,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct GenerateFormBuilderParams {
+    #[serde(flatten)]
+    pub options: FormBuilderOptions,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct GeneratePreviewConfigParams {
+    #[serde(flatten)]
+    pub options: PreviewOptions,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct GenerateAuditLogParams {
+    #[serde(flatten)]
+    pub options: AuditLogOptions,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct GenerateWorkflowParams {
+    #[serde(flatten)]
+    pub options: WorkflowOptions,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct GenerateSeoParams {
+    #[serde(flatten)]
+    pub options: SeoOptions,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct GenerateRedirectsParams {
+    #[serde(flatten)]
+    pub options: RedirectsOptions,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct GenerateSearchParams {
+    #[serde(flatten)]
+    pub options: SiteSearchOptions,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct GenerateEcommerceParams {
+    #[serde(flatten)]
+    pub options: EcommerceOptions,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct GenerateWebhooksParams {
+    #[serde(flatten)]
+    pub options: WebhooksOptions,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct GenerateTestsParams {
+    pub collections: Vec<CollectionOption>,
+    pub roles: Option<Vec<String>>,
+    pub database: Option<String>,
+    pub hooks: Option<bool>,
+    pub access: Option<bool>,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct ImportFromDatabaseParams {
+    #[serde(flatten)]
+    pub options: DatabaseImportOptions,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct InferSchemaParams {
+    #[serde(flatten)]
+    pub options: SampleInferenceOptions,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct ImportExternalSchemaParams {
+    #[serde(flatten)]
+    pub options: ExternalSchemaOptions,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct WpToPayloadSchemaParams {
+    #[serde(flatten)]
+    pub options: WpSchemaOptions,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct WpExportToSeedParams {
+    /// Contents of a WXR export; required unless `path` is given
+    pub content: Option<String>,
+    /// Path to the WXR export on the server's disk
+    pub path: Option<String>,
+    #[serde(flatten)]
+    pub options: WpSeedOptions,
+}
+
+impl WpExportToSeedParams {
+    pub(crate) fn source(&self) -> Result<String, String> {
+        content_or_path(&self.content, &self.path)
+    }
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct ParsePayloadTypesParams {
+    /// Contents of payload-types.ts; required unless `path` is given
+    pub content: Option<String>,
+    /// Path to payload-types.ts on the server's disk
+    pub path: Option<String>,
+}
+
+impl ParsePayloadTypesParams {
+    pub(crate) fn source(&self) -> Result<String, String> {
+        content_or_path(&self.content, &self.path)
+    }
+}
+
+/// Source given inline as `content` or as a `path` on the server's disk.
+fn content_or_path(content: &Option<String>, path: &Option<String>) -> Result<String, String> {
+    match (content, path) {
+        (Some(_), Some(_)) => Err("Pass either content or path, not both".to_string()),
+        (Some(content), None) => Ok(content.clone()),
+        (None, Some(path)) => {
+            std::fs::read_to_string(path).map_err(|err| format!("Failed to read {path}: {err}"))
+        }
+        (None, None) => Err("content or path is required".to_string()),
+    }
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct PatchConfigParams {
+    /// Contents of payload.config.ts; required unless `path` is given
+    pub content: Option<String>,
+    /// Path to payload.config.ts on the server's disk; the file itself is not changed
+    pub path: Option<String>,
+    /// Edits applied in order
+    pub edits: Vec<ConfigEdit>,
+}
+
+impl PatchConfigParams {
+    pub(crate) fn source(&self) -> Result<String, String> {
+        content_or_path(&self.content, &self.path)
+    }
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct CheckDependenciesParams {
+    /// Contents of package.json; required unless `path` is given
+    pub content: Option<String>,
+    /// Path to a project directory or its package.json on the server's disk
+    pub path: Option<String>,
+}
+
+impl CheckDependenciesParams {
+    pub(crate) fn source(&self) -> Result<String, String> {
+        let path = self.path.as_ref().map(|path| {
+            let path = std::path::Path::new(path);
+            if path.is_dir() {
+                path.join("package.json").to_string_lossy().into_owned()
+            } else {
+                path.to_string_lossy().into_owned()
+            }
+        });
+        content_or_path(&self.content, &path)
+    }
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct EnvAuditParams {
+    /// Project options; defaults to the project scaffolded in this session
+    /// unless `files` are given
+    pub project: Option<ScaffoldOptions>,
+    /// Config and other sources whose `process.env` reads are required
+    pub files: Option<Vec<SourceFile>>,
+    /// Contents of the `.env` to check
+    pub env: Option<String>,
+    /// Path to the `.env` on the server's disk
+    pub env_path: Option<String>,
+    /// Contents of the `.env.example` to extend
+    pub example: Option<String>,
+    /// Path to the `.env.example` on the server's disk; the file itself is not changed
+    pub example_path: Option<String>,
+    #[serde(flatten)]
+    pub options: EnvAuditOptions,
+}
+
+impl EnvAuditParams {
+    pub(crate) fn reads_disk(&self) -> bool {
+        self.env_path.is_some() || self.example_path.is_some()
+    }
+}
+
+/// Audit the variables of `project` and `params.files` against the given
+/// `.env` and `.env.example`.
+pub(crate) fn env_audit_for(params: &EnvAuditParams, project: Option<&ScaffoldOptions>) -> Result<EnvAudit, String> {
+    if project.is_none() && params.files.is_none() {
+        return Err("project or files is required".to_string());
+    }
+    let mut required = project
+        .map(|project| project_env(project, params.options.email))
+        .unwrap_or_default();
+    required.extend(config_env(params.files.as_deref().unwrap_or_default()));
+    let optional = |content: &Option<String>, path: &Option<String>| match (content, path) {
+        (None, None) => Ok(None),
+        _ => content_or_path(content, path).map(Some),
+    };
+    let env = optional(&params.env, &params.env_path)?;
+    let example = optional(&params.example, &params.example_path)?;
+    Ok(env_audit(required, env.as_deref(), example.as_deref(), &params.options))
+}
+
+/// Where a tool reads its schema from: `schema`, then `files`, then a live
+/// instance when a connection is given or `live` is set, then `project`.
+#[derive(Debug, Default, Deserialize, JsonSchema)]
+pub struct SchemaSourceParams {
+    /// Schema as returned by `parse_payload_types`
+    pub schema: Option<SchemaModel>,
+    /// Config, collection and global sources, as TypeScript or JSON
+    pub files: Option<Vec<SourceFile>>,
+    /// Project options; defaults to the project scaffolded in this session
+    pub project: Option<ScaffoldOptions>,
+    /// Read the collections of a live instance, by default the one from `connect_payload`
+    pub live: Option<bool>,
+    pub connection_string: Option<String>,
+    pub api_key: Option<String>,
+    /// Stored connection to use instead of `connection_string`
+    pub connection: Option<String>,
+}
+
+impl SchemaSourceParams {
+    pub(crate) fn is_live(&self) -> bool {
+        self.live.unwrap_or(false) || self.connection.is_some() || self.connection_string.is_some()
+    }
+
+    /// Whether any source was given, before falling back to the session project.
+    pub(crate) fn is_given(&self) -> bool {
+        self.schema.is_some() || self.files.is_some() || self.project.is_some() || self.is_live()
+    }
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct GenerateOpenApiParams {
+    #[serde(flatten)]
+    pub source: SchemaSourceParams,
+    #[serde(flatten)]
+    pub options: OpenApiOptions,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct GenerateGraphqlSdlParams {
+    #[serde(flatten)]
+    pub source: SchemaSourceParams,
+    #[serde(flatten)]
+    pub options: GraphqlSdlOptions,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct GenerateClientSdkParams {
+    #[serde(flatten)]
+    pub source: SchemaSourceParams,
+    #[serde(flatten)]
+    pub options: ClientSdkOptions,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct GenerateJsonSchemaParams {
+    #[serde(flatten)]
+    pub source: SchemaSourceParams,
+    #[serde(flatten)]
+    pub options: JsonSchemaOptions,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct SchemaGraphParams {
+    #[serde(flatten)]
+    pub source: SchemaSourceParams,
+    #[serde(flatten)]
+    pub options: SchemaGraphOptions,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct AnalyzeRelationshipDepthParams {
+    #[serde(flatten)]
+    pub source: SchemaSourceParams,
+    #[serde(flatten)]
+    pub options: DepthOptions,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct ExportContentParams {
+    /// Defaults to the connection made by `connect_payload` in this session
+    pub connection_string: Option<String>,
+    pub api_key: Option<String>,
+    /// Stored connection to use instead of `connection_string`
+    pub connection: Option<String>,
+    /// Write the NDJSON to this file on the server's disk instead of returning it
+    pub path: Option<String>,
+    #[serde(flatten)]
+    pub options: ContentExportOptions,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct AnonymizeExportParams {
+    /// Defaults to the connection made by `connect_payload` in this session
+    pub connection_string: Option<String>,
+    pub api_key: Option<String>,
+    /// Stored connection to use instead of `connection_string`
+    pub connection: Option<String>,
+    /// Write the NDJSON to this file on the server's disk instead of returning it
+    pub path: Option<String>,
+    #[serde(flatten)]
+    pub options: AnonymizeOptions,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct I18nAuditParams {
+    /// Defaults to the connection made by `connect_payload` in this session
+    pub connection_string: Option<String>,
+    pub api_key: Option<String>,
+    /// Stored connection to use instead of `connection_string`
+    pub connection: Option<String>,
+    /// Schema marking the localized fields; defaults to the live schema
+    pub schema: Option<SchemaModel>,
+    /// Config, collection and global sources marking the localized fields
+    pub files: Option<Vec<SourceFile>>,
+    #[serde(flatten)]
+    pub options: I18nAuditOptions,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct ContentAuditParams {
+    /// Defaults to the connection made by `connect_payload` in this session
+    pub connection_string: Option<String>,
+    pub api_key: Option<String>,
+    /// Stored connection to use instead of `connection_string`
+    pub connection: Option<String>,
+    /// Schema marking required, relationship and upload fields; defaults to
+    /// the live schema
+    pub schema: Option<SchemaModel>,
+    /// Config, collection and global sources to read the schema from
+    pub files: Option<Vec<SourceFile>>,
+    #[serde(flatten)]
+    pub options: ContentAuditOptions,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct BulkUpdateParams {
+    /// Defaults to the connection made by `connect_payload` in this session
+    pub connection_string: Option<String>,
+    pub api_key: Option<String>,
+    /// Stored connection to use instead of `connection_string`
+    pub connection: Option<String>,
+    #[serde(flatten)]
+    pub options: BulkUpdateOptions,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct ImportContentParams {
+    /// Defaults to the connection made by `connect_payload` in this session
+    pub connection_string: Option<String>,
+    pub api_key: Option<String>,
+    /// Stored connection to use instead of `connection_string`
+    pub connection: Option<String>,
+    /// NDJSON as written by `export_content`; required unless `path` is given
+    pub content: Option<String>,
+    /// NDJSON file on the server's disk
+    pub path: Option<String>,
+    #[serde(flatten)]
+    pub options: ContentImportOptions,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct BackupInstanceParams {
+    /// Defaults to the connection made by `connect_payload` in this session
+    pub connection_string: Option<String>,
+    pub api_key: Option<String>,
+    /// Stored connection to use instead of `connection_string`
+    pub connection: Option<String>,
+    /// Directory on the server's disk to create the timestamped backup in
+    pub dir: String,
+    #[serde(flatten)]
+    pub options: BackupOptions,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct RestoreInstanceParams {
+    /// Defaults to the connection made by `connect_payload` in this session
+    pub connection_string: Option<String>,
+    pub api_key: Option<String>,
+    /// Stored connection to use instead of `connection_string`
+    pub connection: Option<String>,
+    /// Backup directory written by `backup_instance`
+    pub path: String,
+    #[serde(flatten)]
+    pub options: RestoreOptions,
 }
 
 #[derive(Debug, Deserialize, JsonSchema)]
 pub struct ConnectPayloadParams {
-    pub connection_string: String,
+    /// Required unless `connection` is given
+    pub connection_string: Option<String>,
     pub api_key: Option<String>,
+    /// Stored connection to use instead of `connection_string`
+    pub connection: Option<String>,
 }
 
 #[derive(Debug, Deserialize, JsonSchema)]
 pub struct GetCollectionParams {
-    pub connection_string: String,
+    /// Defaults to the connection made by `connect_payload` in this session
+    pub connection_string: Option<String>,
     pub api_key: Option<String>,
+    /// Stored connection to use instead of `connection_string`
+    pub connection: Option<String>,
     pub slug: String,
 }
 
 #[derive(Debug, Deserialize, JsonSchema)]
 pub struct ListCollectionsParams {
-    pub connection_string: String,
+    /// Defaults to the connection made by `connect_payload` in this session
+    pub connection_string: Option<String>,
     pub api_key: Option<String>,
+    /// Stored connection to use instead of `connection_string`
+    pub connection: Option<String>,
 }
 
 #[derive(Debug, Deserialize, JsonSchema)]
 pub struct ValidateAgainstLiveParams {
-    pub connection_string: String,
+    /// Defaults to the connection made by `connect_payload` in this session
+    pub connection_string: Option<String>,
     pub api_key: Option<String>,
+    /// Stored connection to use instead of `connection_string`
+    pub connection: Option<String>,
     pub slug: String,
     pub config: Value,
 }
 
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct AddConnectionParams {
+    /// Name used as `connection` in other tools (letters, digits, `-`, `_`)
+    pub name: String,
+    /// Base URL of the Payload instance
+    pub url: String,
+    /// Credential reference, e.g. `${secret:payload-staging}`; never a plaintext key
+    pub api_key: Option<String>,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct ListConnectionsParams {}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct ScaffoldProjectParams {
+    #[serde(flatten)]
+    pub options: ScaffoldOptions,
+    /// Write the files under this directory and return a manifest instead of
+    /// the file contents; only over local transports (stdio, unix, pipe)
+    #[serde(rename = "outDir")]
+    pub out_dir: Option<String>,
+    /// Replace existing files under `outDir`
+    pub overwrite: Option<bool>,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct RunProjectParams {
+    /// Directory of a scaffolded project on the server's disk
+    pub path: String,
+    /// Run `pnpm install` first, default true
+    pub install: Option<bool>,
+    /// Passed to the dev server as `PORT`
+    pub port: Option<u16>,
+    /// Seconds to wait for the dev server to report ready, default 120
+    pub timeout_seconds: Option<u64>,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct StopProjectParams {
+    /// Directory passed to `run_project`
+    pub path: String,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct AddToProjectParams {
+    /// Project to extend; defaults to the last project scaffolded in this session
+    pub project: Option<ScaffoldOptions>,
+    #[serde(flatten)]
+    pub additions: ProjectAdditions,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct GenerateMultitenancyParams {
+    #[serde(flatten)]
+    pub options: MultitenancyOptions,
+    /// Project to patch; without it (and without `patch`) the files are
+    /// returned on their own
+    pub project: Option<ScaffoldOptions>,
+    /// Patch the last project scaffolded in this session
+    pub patch: Option<bool>,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct ValidateProjectParams {
+    /// Project to validate; defaults to the last project scaffolded in this session
+    pub project: Option<ScaffoldOptions>,
+    /// Also render the results as a `sarif` or `junit` report
+    pub format: Option<ValidationReportFormat>,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct SelfCheckParams {
+    /// Also render the results as a `sarif` or `junit` report
+    pub format: Option<ValidationReportFormat>,
+    /// Fail on warnings as well as errors (default false)
+    pub deny_warnings: Option<bool>,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct StartPlanningParams {
+    /// What to build
+    pub goal: String,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct SavePlanParams {
+    /// Defaults to the goal opened by `start_planning` in this session
+    pub goal_id: Option<String>,
+    /// Plan text; each top-level numbered item (`1. Title: description`) becomes a todo
+    pub plan: String,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct AddTodoParams {
+    /// Defaults to the goal opened by `start_planning` in this session
+    pub goal_id: Option<String>,
+    pub title: String,
+    pub description: String,
+    /// Estimated complexity from 0 (trivial) to 10
+    pub complexity: u8,
+    pub code_example: Option<String>,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct UpdateTodoStatusParams {
+    /// Defaults to the goal opened by `start_planning` in this session
+    pub goal_id: Option<String>,
+    pub todo_id: String,
+    pub is_complete: bool,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct GetPlanParams {
+    /// Defaults to the goal opened by `start_planning` in this session
+    pub goal_id: Option<String>,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct ListTodosParams {
+    /// Only todos that are not complete (default true)
+    pub incomplete_only: Option<bool>,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct PlanHistoryParams {
+    /// Defaults to the goal opened by `start_planning` in this session
+    pub goal_id: Option<String>,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct RegisterSchemaParams {
+    /// Registry name the version is numbered under, usually the project
+    pub name: String,
+    /// e.g. a release or git tag
+    pub label: Option<String>,
+    #[serde(flatten)]
+    pub source: SchemaSourceParams,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct ListSchemaVersionsParams {
+    /// Only versions registered under this name
+    pub name: Option<String>,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct DiffSchemaVersionsParams {
+    pub name: String,
+    /// Defaults to the version before `to`
+    pub from: Option<u32>,
+    /// Defaults to the latest version
+    pub to: Option<u32>,
+    /// Adapter the migration imports its types from: mongodb (default), postgres or sqlite
+    pub database: Option<String>,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct CheckBreakingChangesParams {
+    #[serde(flatten)]
+    pub options: BreakingChangeOptions,
+    /// Candidate schema checked against the registry, e.g. the config of a
+    /// pull request; without one, two registered versions are compared
+    #[serde(flatten)]
+    pub source: SchemaSourceParams,
+    /// Report breaking changes as a tool error, so the CLI `tool` runner
+    /// exits with status 1 (default true)
+    pub fail_on_breaking: Option<bool>,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct AttachResultParams {
+    /// Defaults to the goal opened by `start_planning` in this session
+    pub goal_id: Option<String>,
+    /// Todo to attach to; the plan itself when omitted
+    pub todo_id: Option<String>,
+    /// Tool that produced the result, e.g. `scaffold_project` or `validate`
+    pub tool: String,
+    /// Structured result to attach; defaults to the tool's last result in this session
+    pub result: Option<Value>,
+}
+
+impl AddTodoParams {
+    pub fn new_todo(&self) -> NewTodo {
+        NewTodo {
+            title: self.title.clone(),
+            description: self.description.clone(),
+            complexity: self.complexity,
+            code_example: self.code_example.clone(),
+        }
+    }
+}
+
+// Structured results returned by the tools, published as `outputSchema`.
+
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct QueryOutput {
+    pub rules: Vec<ValidationRule>,
+}
+
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct FieldTypeOutput {
+    #[serde(flatten)]
+    pub field_type: FieldTypeInfo,
+    /// Options accepted by every field of this kind, besides `options`
+    pub common_options: Vec<String>,
+    /// `admin` options accepted by every field, besides `admin_options`
+    pub common_admin_options: Vec<String>,
+}
+
+/// Plugin setup for `generate_plugin_config`, indented for the top level of
+/// a `plugins` array.
+pub fn generate_plugin_config(params: &GeneratePluginConfigParams) -> Result<PluginConfig, String> {
+    let options = match &params.options {
+        Some(Value::Object(options)) => options.clone(),
+        Some(_) => return Err("Plugin options must be an object".to_string()),
+        None => serde_json::Map::new(),
+    };
+    plugin_config(&params.plugin, params.payload_version.unwrap_or_default(), &options, "")
+}
+
+/// Registry entry for a field type with the options shared by its kind.
+pub fn describe_field_type(name: &str) -> Result<FieldTypeOutput, String> {
+    let info = require_field_type(name)?;
+    let common = if info.stores_data { COMMON_OPTIONS } else { LAYOUT_OPTIONS };
+    Ok(FieldTypeOutput {
+        field_type: info.clone(),
+        common_options: common.iter().map(|o| o.to_string()).collect(),
+        common_admin_options: COMMON_ADMIN_OPTIONS.iter().map(|o| o.to_string()).collect(),
+    })
+}
+
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct SearchOutput {
+    pub results: Vec<SearchResult>,
+}
+
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct SearchDocsOutput {
+    pub results: Vec<DocMatch>,
+}
+
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct GetExampleOutput {
+    /// Examples matching `tags` and `query`
+    pub examples: Vec<ExampleSummary>,
+    /// Files of the example named by `id`, or of the only match
+    pub example: Option<ExampleCode>,
+}
+
+/// Examples matching the params, with the code of the requested example.
+pub fn get_example(params: &GetExampleParams) -> Result<GetExampleOutput, String> {
+    if let Some(id) = &params.id {
+        let example = require_example(id)?;
+        return Ok(GetExampleOutput {
+            examples: vec![example.summary()],
+            example: Some(example.code()?),
+        });
+    }
+    let examples = find_examples(params.tags.as_deref().unwrap_or_default(), params.query.as_deref());
+    let example = match examples.as_slice() {
+        [only] => Some(require_example(only.id)?.code()?),
+        _ => None,
+    };
+    Ok(GetExampleOutput { examples, example })
+}
+
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct SqlOutput {
+    pub results: SqlQueryResult,
+}
+
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct CodeOutput {
+    pub code: String,
+}
+
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct DraftCollectionOutput {
+    #[serde(flatten)]
+    pub draft: CollectionDraft,
+    /// The draft rendered by the collection template
+    pub code: String,
+}
+
+/// Draft a collection from its description and render it the way
+/// `generate_collection` would.
+pub(crate) fn draft_collection_code(params: &DraftCollectionParams) -> Result<DraftCollectionOutput, String> {
+    let draft = draft_collection(&params.description, params.slug.as_deref())?;
+    let naming = naming::profile(params.naming_profile.as_deref())?;
+    let code = generate_template_with_naming(TemplateType::Collection, &draft.collection, &naming)?;
+    Ok(DraftCollectionOutput { draft, code })
+}
+
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct ScaffoldOutput {
+    pub message: String,
+    /// Nested map of directory names to sub-trees and file names to contents;
+    /// omitted when the files were written to `outDir`
+    #[serde(rename = "fileStructure")]
+    pub file_structure: Option<Value>,
+    /// Set when file contents were replaced by `result://` URIs
+    pub linked: Option<bool>,
+    /// Files written to `outDir`, in path order
+    pub manifest: Option<Vec<ManifestEntry>>,
+}
+
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct FileStructureOutput {
+    #[serde(rename = "fileStructure")]
+    pub file_structure: Value,
+    /// Set when file contents were replaced by `result://` URIs
+    pub linked: Option<bool>,
+}
+
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct MultitenancyOutput {
+    /// Standalone files by path from the project root; omitted when a
+    /// project was patched
+    pub files: Option<BTreeMap<String, String>>,
+    pub message: Option<String>,
+    /// The patched project, as in `scaffold_project`
+    #[serde(rename = "fileStructure")]
+    pub file_structure: Option<Value>,
+    /// Set when file contents were replaced by `result://` URIs
+    pub linked: Option<bool>,
+}
+
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct ContentExportOutput {
+    /// Documents exported per collection
+    pub counts: BTreeMap<String, usize>,
+    pub total: usize,
+    /// The NDJSON, or its `result://` URI when `linked`; omitted when written to `path`
+    pub ndjson: Option<String>,
+    pub path: Option<String>,
+    /// Set when the NDJSON was replaced by a `result://` URI
+    pub linked: Option<bool>,
+}
+
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct AnonymizeExportOutput {
+    #[serde(flatten)]
+    pub export: ContentExportOutput,
+    /// Values replaced per field name
+    pub scrubbed: BTreeMap<String, usize>,
+}
+
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct ValidateOutput {
+    #[serde(flatten)]
+    pub result: ValidationResult,
+    /// The result rendered in the requested `format`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub report: Option<String>,
+}
+
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct ProjectValidationOutput {
+    pub project_name: String,
+    pub is_valid: bool,
+    /// Validation result per source file path
+    pub files: BTreeMap<String, ValidationResult>,
+    /// The results rendered in the requested `format`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub report: Option<String>,
+}
+
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct SelfCheckOutput {
+    /// Whether every generated file passed
+    pub is_valid: bool,
+    /// Validation result per generated file, under `templates/`,
+    /// `presets/{name}/` and `examples/{id}/`
+    pub files: BTreeMap<String, ValidationResult>,
+    /// The results rendered in the requested `format`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub report: Option<String>,
+}
+
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct TemplateVariables {
+    pub template_type: TemplateType,
+    /// Override file names looked up in the templates directory, in order
+    pub files: Vec<String>,
+    /// Override file currently used instead of the built-in template
+    pub override_path: Option<String>,
+    pub variables: Vec<TemplateVariable>,
+}
+
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct ListTemplateVariablesOutput {
+    /// Directory searched for overrides; absent when overrides are disabled
+    pub templates_dir: Option<String>,
+    pub templates: Vec<TemplateVariables>,
+}
+
+/// Variables available to template overrides, per template type.
+pub fn list_template_variables(params: &ListTemplateVariablesParams) -> ListTemplateVariablesOutput {
+    let dir = templates_dir();
+    let templates = TemplateType::ALL
+        .into_iter()
+        .filter(|template_type| params.template_type.is_none_or(|wanted| wanted == *template_type))
+        .map(|template_type| TemplateVariables {
+            template_type,
+            files: template_overrides::EXTENSIONS
+                .iter()
+                .map(|ext| format!("{}.{ext}", template_type.as_str()))
+                .collect(),
+            override_path: dir
+                .and_then(|dir| override_path(dir, template_type))
+                .map(|path| path.display().to_string()),
+            variables: template_variables(template_type),
+        })
+        .collect();
+    ListTemplateVariablesOutput {
+        templates_dir: dir.map(|dir| dir.display().to_string()),
+        templates,
+    }
+}
+
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct AccessMatrixOutput {
+    #[serde(flatten)]
+    pub matrix: AccessMatrix,
+    /// The matrix as a table with `yes`, `no`, `query` or `?` per cell
+    pub table: String,
+}
+
+/// The matrix of the given sources, or of the live instance `client` reads.
+pub(crate) fn access_matrix(
+    params: &AccessMatrixParams,
+    client: Option<&PayloadClient>,
+) -> Result<AccessMatrixOutput, String> {
+    let matrix = match client {
+        Some(client) => {
+            let access = client.get_access().map_err(|err| err.to_string())?;
+            live_matrix(&access, params.role.as_deref().unwrap_or(LIVE_USER))
+        }
+        None => {
+            let files = params.files.as_deref().ok_or("files or a connection is required")?;
+            static_matrix(files, params.roles.as_deref())?
+        }
+    };
+    let table = matrix.render(params.format.unwrap_or_default());
+    Ok(AccessMatrixOutput { matrix, table })
+}
+
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct AnalyzeHookOutput {
+    #[serde(flatten)]
+    pub analysis: HookAnalysis,
+    /// The result rendered in the requested `format`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub report: Option<String>,
+}
+
+/// Analyze a hook and render the requested report.
+pub fn analyze_hook_code(params: &AnalyzeHookParams) -> Result<AnalyzeHookOutput, String> {
+    let analysis = analyze_hook(&params.code, params.hook.as_deref())?;
+    let path = params.path.clone().unwrap_or_else(|| "hook.ts".to_string());
+    let report = params
+        .format
+        .unwrap_or_default()
+        .render([(path.as_str(), &analysis.result)]);
+    Ok(AnalyzeHookOutput { analysis, report })
+}
+
+pub(crate) fn validate_code(params: &ValidateParams) -> Result<ValidateOutput, String> {
+    let naming = naming::profile(params.naming_profile.as_deref())?;
+    let mut result = validate_payload_code_with_naming(&params.code, params.file_type, &naming);
+    if params.production.unwrap_or(false) {
+        check_production(&params.code, params.file_type, &mut result);
+    }
+    let path = params
+        .path
+        .clone()
+        .unwrap_or_else(|| format!("{}.ts", params.file_type));
+    let report = params
+        .format
+        .unwrap_or_default()
+        .render([(path.as_str(), &result)]);
+    Ok(ValidateOutput { result, report })
+}
+
+pub(crate) fn validate_project_files(
+    project: &ScaffoldOptions,
+    jobs: usize,
+    format: Option<ValidationReportFormat>,
+) -> ProjectValidationOutput {
+    let files = validate_scaffold(&scaffold_project(project), jobs);
+    let report = format
+        .unwrap_or_default()
+        .render(files.iter().map(|(path, result)| (path.as_str(), result)));
+    ProjectValidationOutput {
+        project_name: project.project_name.clone(),
+        is_valid: files.values().all(|result| result.is_valid),
+        files,
+        report,
+    }
+}
+
+/// Validate the server's own generated code for `self_check`.
+pub(crate) fn self_check_files(params: &SelfCheckParams, jobs: usize) -> SelfCheckOutput {
+    let files = self_check(jobs);
+    let report = params
+        .format
+        .unwrap_or_default()
+        .render(files.iter().map(|(path, result)| (path.as_str(), result)));
+    SelfCheckOutput {
+        is_valid: self_check_passed(&files, params.deny_warnings.unwrap_or(false)),
+        files,
+        report,
+    }
+}
+
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct PlanOutput {
+    pub goal: Goal,
+    pub plan: ImplementationPlan,
+}
+
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct TodoOutput {
+    pub todo: Todo,
+}
+
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct TodoListOutput {
+    pub todos: Vec<GoalTodo>,
+}
+
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct PlanHistoryOutput {
+    pub revisions: Vec<PlanRevision>,
+}
+
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct SchemaVersionsOutput {
+    pub versions: Vec<SchemaVersion>,
+}
+
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct ArtifactsOutput {
+    pub artifacts: Vec<Artifact>,
+}
+
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct ConnectOutput {
+    pub success: bool,
+    pub server_info: Option<ServerInfo>,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct ConnectionOutput {
+    pub name: String,
+    pub connection: Connection,
+}
+
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct ConnectionListOutput {
+    /// Stored connections by name, with credential references unresolved
+    pub connections: Connections,
+}
+
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct CollectionSchemaOutput {
+    pub success: bool,
+    pub collection: Option<CollectionInfo>,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct CollectionListOutput {
+    pub success: bool,
+    pub collections: Option<Vec<String>>,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct LiveValidationOutput {
+    pub success: bool,
+    pub issues: Option<Vec<String>>,
+    pub error: Option<String>,
+}
+
+/// Planning tools report storage errors in the result rather than as protocol errors.
+pub fn planning_result<T: Serialize>(result: Result<T, String>) -> CallToolResult {
+    match result {
+        Ok(output) => CallToolResult::structured(json!(output)),
+        Err(err) => CallToolResult::structured_error(json!({ "error": err })),
+    }
+}
+
+/// A breaking-change report, as a tool error when it failed and `fail_on_breaking` is set.
+pub fn breaking_change_result(result: Result<BreakingChangeReport, String>, fail_on_breaking: Option<bool>) -> CallToolResult {
+    match result {
+        Ok(report) if !report.passed && fail_on_breaking.unwrap_or(true) => {
+            CallToolResult::structured_error(json!(report))
+        }
+        result => planning_result(result),
+    }
+}
+
+/// Tool annotations: `(read_only, destructive, idempotent, open_world)` hints.
+fn hints(read_only: bool, destructive: bool, idempotent: bool, open_world: bool) -> ToolAnnotations {
+    ToolAnnotations {
+        title: None,
+        read_only_hint: Some(read_only),
+        destructive_hint: Some(destructive),
+        idempotent_hint: Some(idempotent),
+        open_world_hint: Some(open_world),
+    }
+}
+
+fn annotated(mut tool: Tool, annotations: ToolAnnotations) -> Tool {
+    tool.annotations = Some(annotations);
+    tool
+}
+
+fn with_output<O: JsonSchema + 'static>(tool: Tool, annotations: ToolAnnotations) -> Tool {
+    let mut tool = annotated(tool, annotations);
+    tool.output_schema = Some(rmcp::handler::server::tool::cached_schema_for_type::<O>());
+    tool
+}
+
 pub fn tool_definitions() -> Vec<Tool> {
     vec![
-        Tool::new(
-            "echo",
-            "Echo a message back to the caller",
-            rmcp::handler::server::tool::cached_schema_for_type::<EchoParams>(),
-        ),
-        Tool::new(
-            "validate",
-            "Validate Payload CMS code (collection, field, global, config)",
-            rmcp::handler::server::tool::cached_schema_for_type::<ValidateParams>(),
-        ),
-        Tool::new(
-            "query",
-            "Query validation rules and best practices",
-            rmcp::handler::server::tool::cached_schema_for_type::<QueryParams>(),
-        ),
-        Tool::new(
-            "mcp_query",
-            "Execute SQL-like queries against validation rules",
-            rmcp::handler::server::tool::cached_schema_for_type::<SqlParams>(),
-        ),
-        Tool::new(
-            "generate_template",
-            "Generate Payload CMS code templates",
-            rmcp::handler::server::tool::cached_schema_for_type::<GenerateTemplateParams>(),
-        ),
-        Tool::new(
-            "generate_collection",
-            "Generate a Payload CMS collection template",
-            rmcp::handler::server::tool::cached_schema_for_type::<GenerateCollectionParams>(),
-        ),
-        Tool::new(
-            "generate_field",
-            "Generate a Payload CMS field template",
-            rmcp::handler::server::tool::cached_schema_for_type::<GenerateFieldParams>(),
-        ),
-        Tool::new(
-            "scaffold_project",
-            "Scaffold a complete Payload CMS 3 project structure",
-            rmcp::handler::server::tool::cached_schema_for_type::<ScaffoldOptions>(),
-        ),
-        Tool::new(
-            "connect_payload",
-            "Connect to a live Payload CMS instance and test the connection",
-            rmcp::handler::server::tool::cached_schema_for_type::<ConnectPayloadParams>(),
-        ),
-        Tool::new(
-            "get_collection_schema",
-            "Get collection schema from a live Payload CMS instance",
-            rmcp::handler::server::tool::cached_schema_for_type::<GetCollectionParams>(),
-        ),
-        Tool::new(
-            "list_collections",
-            "List all collections from a live Payload CMS instance",
-            rmcp::handler::server::tool::cached_schema_for_type::<ListCollectionsParams>(),
-        ),
-        Tool::new(
-            "validate_against_live",
-            "Validate a collection configuration against a live Payload instance",
-            rmcp::handler::server::tool::cached_schema_for_type::<ValidateAgainstLiveParams>(),
+        annotated(
+            Tool::new(
+                "echo",
+                "Echo a message back to the caller",
+                rmcp::handler::server::tool::cached_schema_for_type::<EchoParams>(),
+            ),
+            hints(true, false, true, false),
+        ),
+        annotated(
+            Tool::new(
+                "health",
+                "Report server status, active transports and open sessions",
+                rmcp::handler::server::tool::cached_schema_for_type::<crate::tools::HealthParams>(),
+            ),
+            hints(true, false, true, false),
+        ),
+        with_output::<crate::tools::explain_config::ConfigExplanation>(
+            Tool::new(
+                "explain_config",
+                "Show each server setting's effective value and the layer that supplied it: default, settings file, environment variable or command-line flag",
+                rmcp::handler::server::tool::cached_schema_for_type::<crate::tools::ExplainConfigParams>(),
+            ),
+            hints(true, false, true, false),
+        ),
+        with_output::<ValidateOutput>(
+            Tool::new(
+                "validate",
+                "Validate Payload CMS code (collection, field, global, config)",
+                rmcp::handler::server::tool::cached_schema_for_type::<ValidateParams>(),
+            ),
+            hints(true, false, true, false),
+        ),
+        with_output::<AnalyzeHookOutput>(
+            Tool::new(
+                "analyze_hook",
+                "Check a TypeScript hook for missing returns, writes that re-trigger the hook, Local API calls without req, and access control bypasses",
+                rmcp::handler::server::tool::cached_schema_for_type::<AnalyzeHookParams>(),
+            ),
+            hints(true, false, true, false),
+        ),
+        with_output::<AccessMatrixOutput>(
+            Tool::new(
+                "access_matrix",
+                "Compute the operation x role x collection matrix of effective permissions (allow, deny or query-constrained) from config sources or a live instance, with a markdown or ASCII table",
+                rmcp::handler::server::tool::cached_schema_for_type::<AccessMatrixParams>(),
+            ),
+            hints(true, false, true, true),
+        ),
+        with_output::<QueryOutput>(
+            Tool::new(
+                "query",
+                "Query validation rules and best practices",
+                rmcp::handler::server::tool::cached_schema_for_type::<QueryParams>(),
+            ),
+            hints(true, false, true, false),
+        ),
+        with_output::<SearchOutput>(
+            Tool::new(
+                "search",
+                "Ranked full-text search over validation rules, instructions, templates and best practices",
+                rmcp::handler::server::tool::cached_schema_for_type::<SearchParams>(),
+            ),
+            hints(true, false, true, false),
+        ),
+        with_output::<SearchDocsOutput>(
+            Tool::new(
+                "search_docs",
+                "Search the bundled Payload best-practice guides served as docs://payload/{topic}",
+                rmcp::handler::server::tool::cached_schema_for_type::<SearchDocsParams>(),
+            ),
+            hints(true, false, true, false),
+        ),
+        with_output::<GetExampleOutput>(
+            Tool::new(
+                "get_example",
+                "Complete, validated reference configurations (blog, saas, marketplace, docs) searchable by feature tags, returned as ready-to-adapt collection and config code",
+                rmcp::handler::server::tool::cached_schema_for_type::<GetExampleParams>(),
+            ),
+            hints(true, false, true, false),
+        ),
+        with_output::<SqlOutput>(
+            Tool::new(
+                "mcp_query",
+                "Execute SQL-like queries against validation rules or live Payload collections",
+                rmcp::handler::server::tool::cached_schema_for_type::<SqlParams>(),
+            ),
+            hints(true, false, true, true),
+        ),
+        with_output::<ConvertedQuery>(
+            Tool::new(
+                "convert_query",
+                "Translate a Payload where query between JSON, the REST query string and Postgres SQL",
+                rmcp::handler::server::tool::cached_schema_for_type::<ConvertQueryParams>(),
+            ),
+            hints(true, false, true, false),
+        ),
+        with_output::<CodeOutput>(
+            Tool::new(
+                "generate_template",
+                "Generate Payload CMS code templates",
+                rmcp::handler::server::tool::cached_schema_for_type::<GenerateTemplateParams>(),
+            ),
+            hints(true, false, true, false),
+        ),
+        with_output::<ListTemplateVariablesOutput>(
+            Tool::new(
+                "list_template_variables",
+                "Describe the variables available to user template overrides per template type, and which overrides are active",
+                rmcp::handler::server::tool::cached_schema_for_type::<ListTemplateVariablesParams>(),
+            ),
+            hints(true, false, true, false),
+        ),
+        with_output::<CodeOutput>(
+            Tool::new(
+                "generate_collection",
+                "Generate a Payload CMS collection template",
+                rmcp::handler::server::tool::cached_schema_for_type::<GenerateCollectionParams>(),
+            ),
+            hints(true, false, true, false),
+        ),
+        with_output::<DraftCollectionOutput>(
+            Tool::new(
+                "draft_collection",
+                "Draft a collection from a plain-English description of its content, listing each guess for review",
+                rmcp::handler::server::tool::cached_schema_for_type::<DraftCollectionParams>(),
+            ),
+            hints(true, false, true, false),
+        ),
+        with_output::<CodeOutput>(
+            Tool::new(
+                "generate_field",
+                "Generate a Payload CMS field template",
+                rmcp::handler::server::tool::cached_schema_for_type::<GenerateFieldParams>(),
+            ),
+            hints(true, false, true, false),
+        ),
+        with_output::<PluginConfig>(
+            Tool::new(
+                "generate_plugin_config",
+                "Generate imports, plugin config, dependencies and env vars for an official Payload plugin",
+                rmcp::handler::server::tool::cached_schema_for_type::<GeneratePluginConfigParams>(),
+            ),
+            hints(true, false, true, false),
+        ),
+        with_output::<PluginConfig>(
+            Tool::new(
+                "generate_storage_adapter",
+                "Generate the storage adapter config (S3, R2, GCS or Azure) for upload collections, with its dependencies and env vars",
+                rmcp::handler::server::tool::cached_schema_for_type::<GenerateStorageAdapterParams>(),
+            ),
+            hints(true, false, true, false),
+        ),
+        with_output::<EmailConfig>(
+            Tool::new(
+                "generate_email_config",
+                "Generate the email adapter config (nodemailer or Resend) and auth options with custom verification and forgot-password emails",
+                rmcp::handler::server::tool::cached_schema_for_type::<GenerateEmailConfigParams>(),
+            ),
+            hints(true, false, true, false),
+        ),
+        with_output::<FormBuilderConfig>(
+            Tool::new(
+                "generate_form_builder",
+                "Generate the form-builder plugin config, a React form renderer per field block and a submission endpoint",
+                rmcp::handler::server::tool::cached_schema_for_type::<GenerateFormBuilderParams>(),
+            ),
+            hints(true, false, true, false),
+        ),
+        with_output::<PreviewConfig>(
+            Tool::new(
+                "generate_preview_config",
+                "Generate admin live preview config, draft preview route handlers and the frontend refresh component for selected collections",
+                rmcp::handler::server::tool::cached_schema_for_type::<GeneratePreviewConfigParams>(),
+            ),
+            hints(true, false, true, false),
+        ),
+        with_output::<AuditLogConfig>(
+            Tool::new(
+                "generate_audit_log",
+                "Generate an audit log collection and afterChange/afterDelete hooks recording who changed what in selected collections, with retention",
+                rmcp::handler::server::tool::cached_schema_for_type::<GenerateAuditLogParams>(),
+            ),
+            hints(true, false, true, false),
+        ),
+        with_output::<WorkflowConfig>(
+            Tool::new(
+                "generate_workflow",
+                "Generate an approval workflow for a collection: status field, transition validation with role checks and email notifications",
+                rmcp::handler::server::tool::cached_schema_for_type::<GenerateWorkflowParams>(),
+            ),
+            hints(true, false, true, false),
+        ),
+        with_output::<SeoConfig>(
+            Tool::new(
+                "generate_seo",
+                "Generate SEO meta fields (seo plugin or a field group), sitemap and robots route handlers and a Next.js metadata helper for selected collections",
+                rmcp::handler::server::tool::cached_schema_for_type::<GenerateSeoParams>(),
+            ),
+            hints(true, false, true, false),
+        ),
+        with_output::<RedirectsConfig>(
+            Tool::new(
+                "generate_redirects",
+                "Generate redirects (plugin or collection), Next.js middleware applying them and optional 404 hit tracking",
+                rmcp::handler::server::tool::cached_schema_for_type::<GenerateRedirectsParams>(),
+            ),
+            hints(true, false, true, false),
+        ),
+        with_output::<SiteSearchConfig>(
+            Tool::new(
+                "generate_search",
+                "Generate site search on the search plugin, Algolia or Meilisearch, with index sync hooks and a typed search endpoint",
+                rmcp::handler::server::tool::cached_schema_for_type::<GenerateSearchParams>(),
+            ),
+            hints(true, false, true, false),
+        ),
+        with_output::<EcommerceConfig>(
+            Tool::new(
+                "generate_ecommerce",
+                "Generate a Stripe store: products, customers and orders collections, price sync, a checkout endpoint and webhook handlers",
+                rmcp::handler::server::tool::cached_schema_for_type::<GenerateEcommerceParams>(),
+            ),
+            hints(true, false, true, false),
+        ),
+        with_output::<WebhooksConfig>(
+            Tool::new(
+                "generate_webhooks",
+                "Generate outgoing webhooks: a webhooks collection, hooks queueing signed deliveries with retries, and a delivery log",
+                rmcp::handler::server::tool::cached_schema_for_type::<GenerateWebhooksParams>(),
+            ),
+            hints(true, false, true, false),
+        ),
+        with_output::<ScaffoldOutput>(
+            Tool::new(
+                "scaffold_project",
+                "Scaffold a complete Payload CMS 3 project structure",
+                rmcp::handler::server::tool::cached_schema_for_type::<ScaffoldProjectParams>(),
+            ),
+            hints(false, true, true, false),
+        ),
+        with_output::<ScaffoldOutput>(
+            Tool::new(
+                "add_to_project",
+                "Add collections, globals, blocks or plugins to a project and re-scaffold it",
+                rmcp::handler::server::tool::cached_schema_for_type::<AddToProjectParams>(),
+            ),
+            hints(true, false, true, false),
+        ),
+        with_output::<ProjectStatus>(
+            Tool::new(
+                "run_project",
+                "Install a scaffolded project and run its dev server, returning the admin URL once ready",
+                rmcp::handler::server::tool::cached_schema_for_type::<RunProjectParams>(),
+            ),
+            hints(false, false, true, true),
+        ),
+        with_output::<StoppedProject>(
+            Tool::new(
+                "stop_project",
+                "Stop the dev server started by run_project",
+                rmcp::handler::server::tool::cached_schema_for_type::<StopProjectParams>(),
+            ),
+            hints(false, true, true, false),
+        ),
+        with_output::<MultitenancyOutput>(
+            Tool::new(
+                "generate_multitenancy",
+                "Generate a tenants collection, tenant fields, tenant-scoped access and an afterLogin hook, standalone or patched into a project",
+                rmcp::handler::server::tool::cached_schema_for_type::<GenerateMultitenancyParams>(),
+            ),
+            hints(true, false, true, false),
+        ),
+        with_output::<ProjectValidationOutput>(
+            Tool::new(
+                "validate_project",
+                "Validate every config, collection, global and block file of a scaffolded project",
+                rmcp::handler::server::tool::cached_schema_for_type::<ValidateProjectParams>(),
+            ),
+            hints(true, false, true, false),
+        ),
+        with_output::<SelfCheckOutput>(
+            Tool::new(
+                "self_check",
+                "Validate the code this server generates: every template, field type, scaffold preset and reference example",
+                rmcp::handler::server::tool::cached_schema_for_type::<SelfCheckParams>(),
+            ),
+            hints(true, false, true, false),
+        ),
+        with_output::<FileStructureOutput>(
+            Tool::new(
+                "generate_tests",
+                "Generate Vitest integration-test skeletons for collections",
+                rmcp::handler::server::tool::cached_schema_for_type::<GenerateTestsParams>(),
+            ),
+            hints(true, false, true, false),
+        ),
+        with_output::<DatabaseImport>(
+            Tool::new(
+                "import_from_database",
+                "Propose Payload collections from an existing MongoDB database or Postgres schema",
+                rmcp::handler::server::tool::cached_schema_for_type::<ImportFromDatabaseParams>(),
+            ),
+            hints(true, false, true, true),
+        ),
+        with_output::<SampleInference>(
+            Tool::new(
+                "infer_schema",
+                "Infer a Payload collection from sample JSON documents, such as an export from another CMS",
+                rmcp::handler::server::tool::cached_schema_for_type::<InferSchemaParams>(),
+            ),
+            hints(true, false, true, false),
+        ),
+        with_output::<ExternalSchemaImport>(
+            Tool::new(
+                "import_external_schema",
+                "Convert a Contentful, Strapi or Sanity content model into Payload collections, globals and blocks",
+                rmcp::handler::server::tool::cached_schema_for_type::<ImportExternalSchemaParams>(),
+            ),
+            hints(true, false, true, false),
+        ),
+        with_output::<ExternalSchemaImport>(
+            Tool::new(
+                "wp_to_payload_schema",
+                "Map WordPress post types, taxonomies and ACF field groups to Payload collections and globals",
+                rmcp::handler::server::tool::cached_schema_for_type::<WpToPayloadSchemaParams>(),
+            ),
+            hints(true, false, true, false),
+        ),
+        with_output::<WpSeed>(
+            Tool::new(
+                "wp_export_to_seed",
+                "Turn a WordPress WXR export into a Local API seed script for the collections of wp_to_payload_schema",
+                rmcp::handler::server::tool::cached_schema_for_type::<WpExportToSeedParams>(),
+            ),
+            hints(true, false, true, false),
+        ),
+        with_output::<EnvAudit>(
+            Tool::new(
+                "env_audit",
+                "Derive the environment variables a project needs, report the ones missing from or unused in its .env, and extend .env.example",
+                rmcp::handler::server::tool::cached_schema_for_type::<EnvAuditParams>(),
+            ),
+            hints(true, false, true, false),
+        ),
+        with_output::<PatchConfigOutput>(
+            Tool::new(
+                "patch_config",
+                "Apply structured edits to a payload.config.ts (collections, globals, plugins, db adapter, locales), preserving formatting and comments",
+                rmcp::handler::server::tool::cached_schema_for_type::<PatchConfigParams>(),
+            ),
+            hints(true, false, true, false),
+        ),
+        with_output::<DependencyReport>(
+            Tool::new(
+                "check_dependencies",
+                "Check a project's payload and @payloadcms/* versions against the compatibility matrix and suggest upgrade commands",
+                rmcp::handler::server::tool::cached_schema_for_type::<CheckDependenciesParams>(),
+            ),
+            hints(true, false, true, false),
+        ),
+        with_output::<PayloadTypesSchema>(
+            Tool::new(
+                "parse_payload_types",
+                "Reconstruct collections, globals and fields from a generated payload-types.ts and validate them",
+                rmcp::handler::server::tool::cached_schema_for_type::<ParsePayloadTypesParams>(),
+            ),
+            hints(true, false, true, false),
+        ),
+        with_output::<OpenApiOutput>(
+            Tool::new(
+                "generate_openapi",
+                "Generate an OpenAPI 3.1 document for the REST API Payload serves for a schema",
+                rmcp::handler::server::tool::cached_schema_for_type::<GenerateOpenApiParams>(),
+            ),
+            hints(true, false, true, true),
+        ),
+        with_output::<GraphqlSdlOutput>(
+            Tool::new(
+                "generate_graphql_sdl",
+                "Generate the GraphQL SDL Payload serves for a schema: types, queries, mutations and where inputs",
+                rmcp::handler::server::tool::cached_schema_for_type::<GenerateGraphqlSdlParams>(),
+            ),
+            hints(true, false, true, true),
+        ),
+        with_output::<ClientSdkOutput>(
+            Tool::new(
+                "generate_client_sdk",
+                "Generate a typed TypeScript fetch client for a schema: document types and find, findByID, create, update and delete per collection, with auth",
+                rmcp::handler::server::tool::cached_schema_for_type::<GenerateClientSdkParams>(),
+            ),
+            hints(true, false, true, true),
+        ),
+        with_output::<JsonSchemaOutput>(
+            Tool::new(
+                "generate_json_schema",
+                "Convert collections and globals into JSON Schema documents, with $defs for blocks",
+                rmcp::handler::server::tool::cached_schema_for_type::<GenerateJsonSchemaParams>(),
+            ),
+            hints(true, false, true, true),
+        ),
+        with_output::<SchemaGraph>(
+            Tool::new(
+                "schema_graph",
+                "Graph the relationship and upload fields between collections and globals, with optional Mermaid or DOT output",
+                rmcp::handler::server::tool::cached_schema_for_type::<SchemaGraphParams>(),
+            ),
+            hints(true, false, true, true),
+        ),
+        with_output::<DepthAnalysis>(
+            Tool::new(
+                "analyze_relationship_depth",
+                "Find relationship cycles and estimate how many documents a read populates at a given depth",
+                rmcp::handler::server::tool::cached_schema_for_type::<AnalyzeRelationshipDepthParams>(),
+            ),
+            hints(true, false, true, true),
+        ),
+        with_output::<ContentExportOutput>(
+            Tool::new(
+                "export_content",
+                "Export documents from a live Payload instance as NDJSON, keeping relationships as IDs",
+                rmcp::handler::server::tool::cached_schema_for_type::<ExportContentParams>(),
+            ),
+            hints(false, false, false, true),
+        ),
+        with_output::<AnonymizeExportOutput>(
+            Tool::new(
+                "anonymize_export",
+                "Export documents from a live Payload instance as NDJSON with PII fields replaced by fake values",
+                rmcp::handler::server::tool::cached_schema_for_type::<AnonymizeExportParams>(),
+            ),
+            hints(false, false, false, true),
+        ),
+        with_output::<I18nAudit>(
+            Tool::new(
+                "i18n_audit",
+                "Report localized fields with missing translations per locale in a live Payload instance",
+                rmcp::handler::server::tool::cached_schema_for_type::<I18nAuditParams>(),
+            ),
+            hints(true, false, true, true),
+        ),
+        with_output::<ContentAudit>(
+            Tool::new(
+                "content_audit",
+                "Report missing required fields, broken references, orphaned uploads, stale drafts and empty collections in a live Payload instance",
+                rmcp::handler::server::tool::cached_schema_for_type::<ContentAuditParams>(),
+            ),
+            hints(true, false, true, true),
+        ),
+        with_output::<BulkUpdate>(
+            Tool::new(
+                "bulk_update",
+                "Patch every document matching a where query in a live Payload instance, after a dry run and its confirmation token",
+                rmcp::handler::server::tool::cached_schema_for_type::<BulkUpdateParams>(),
+            ),
+            hints(false, true, true, true),
+        ),
+        with_output::<ContentImport>(
+            Tool::new(
+                "import_content",
+                "Import NDJSON documents into a live Payload instance, creating or upserting them",
+                rmcp::handler::server::tool::cached_schema_for_type::<ImportContentParams>(),
+            ),
+            hints(false, false, false, true),
+        ),
+        with_output::<Backup>(
+            Tool::new(
+                "backup_instance",
+                "Back up collections and globals of a live Payload instance to a timestamped directory",
+                rmcp::handler::server::tool::cached_schema_for_type::<BackupInstanceParams>(),
+            ),
+            hints(false, false, false, true),
+        ),
+        with_output::<Restore>(
+            Tool::new(
+                "restore_instance",
+                "Restore a backup from backup_instance into a live Payload instance, or plan it with dryRun",
+                rmcp::handler::server::tool::cached_schema_for_type::<RestoreInstanceParams>(),
+            ),
+            hints(false, true, false, true),
+        ),
+        with_output::<ConnectOutput>(
+            Tool::new(
+                "connect_payload",
+                "Connect to a live Payload CMS instance and test the connection",
+                rmcp::handler::server::tool::cached_schema_for_type::<ConnectPayloadParams>(),
+            ),
+            hints(true, false, true, true),
+        ),
+        with_output::<FieldTypeOutput>(
+            Tool::new(
+                "describe_field_type",
+                "Describe a field type: its options, required options, admin options, storage and constraints",
+                rmcp::handler::server::tool::cached_schema_for_type::<DescribeFieldTypeParams>(),
+            ),
+            hints(true, false, true, false),
+        ),
+        with_output::<ConnectionOutput>(
+            Tool::new(
+                "add_connection",
+                "Store a named Payload connection (URL and credential reference) in settings",
+                rmcp::handler::server::tool::cached_schema_for_type::<AddConnectionParams>(),
+            ),
+            hints(false, false, false, true),
+        ),
+        with_output::<ConnectionListOutput>(
+            Tool::new(
+                "list_connections",
+                "List stored Payload connections",
+                rmcp::handler::server::tool::cached_schema_for_type::<ListConnectionsParams>(),
+            ),
+            hints(true, false, true, false),
+        ),
+        with_output::<CollectionSchemaOutput>(
+            Tool::new(
+                "get_collection_schema",
+                "Get collection schema from a live Payload CMS instance",
+                rmcp::handler::server::tool::cached_schema_for_type::<GetCollectionParams>(),
+            ),
+            hints(true, false, true, true),
+        ),
+        with_output::<CollectionListOutput>(
+            Tool::new(
+                "list_collections",
+                "List all collections from a live Payload CMS instance",
+                rmcp::handler::server::tool::cached_schema_for_type::<ListCollectionsParams>(),
+            ),
+            hints(true, false, true, true),
+        ),
+        with_output::<LiveValidationOutput>(
+            Tool::new(
+                "validate_against_live",
+                "Validate a collection configuration against a live Payload instance",
+                rmcp::handler::server::tool::cached_schema_for_type::<ValidateAgainstLiveParams>(),
+            ),
+            hints(true, false, true, true),
+        ),
+        with_output::<PlanOutput>(
+            Tool::new(
+                "start_planning",
+                "Start planning a Payload build: create a goal with an empty implementation plan",
+                rmcp::handler::server::tool::cached_schema_for_type::<StartPlanningParams>(),
+            ),
+            hints(false, false, false, false),
+        ),
+        with_output::<PlanOutput>(
+            Tool::new(
+                "save_plan",
+                "Save plan text for a goal, adding a todo for each numbered item",
+                rmcp::handler::server::tool::cached_schema_for_type::<SavePlanParams>(),
+            ),
+            hints(false, false, false, false),
+        ),
+        with_output::<TodoOutput>(
+            Tool::new(
+                "add_todo",
+                "Add a todo to a goal's implementation plan",
+                rmcp::handler::server::tool::cached_schema_for_type::<AddTodoParams>(),
+            ),
+            hints(false, false, false, false),
+        ),
+        with_output::<TodoOutput>(
+            Tool::new(
+                "update_todo_status",
+                "Mark a todo complete or incomplete",
+                rmcp::handler::server::tool::cached_schema_for_type::<UpdateTodoStatusParams>(),
+            ),
+            hints(false, false, true, false),
+        ),
+        with_output::<PlanOutput>(
+            Tool::new(
+                "get_plan",
+                "Get a goal and its implementation plan with todos",
+                rmcp::handler::server::tool::cached_schema_for_type::<GetPlanParams>(),
+            ),
+            hints(true, false, true, false),
+        ),
+        with_output::<TodoListOutput>(
+            Tool::new(
+                "list_todos",
+                "List todos across all goals, by default only incomplete ones",
+                rmcp::handler::server::tool::cached_schema_for_type::<ListTodosParams>(),
+            ),
+            hints(true, false, true, false),
+        ),
+        with_output::<PlanHistoryOutput>(
+            Tool::new(
+                "plan_history",
+                "List snapshots of a goal's plan after each change",
+                rmcp::handler::server::tool::cached_schema_for_type::<PlanHistoryParams>(),
+            ),
+            hints(true, false, true, false),
+        ),
+        with_output::<SchemaRegistration>(
+            Tool::new(
+                "register_schema",
+                "Store a snapshot of a schema as the next version under a name, reporting changes since the last one",
+                rmcp::handler::server::tool::cached_schema_for_type::<RegisterSchemaParams>(),
+            ),
+            hints(false, false, true, true),
+        ),
+        with_output::<SchemaVersionsOutput>(
+            Tool::new(
+                "list_schema_versions",
+                "List registered schema versions with their labels and dates",
+                rmcp::handler::server::tool::cached_schema_for_type::<ListSchemaVersionsParams>(),
+            ),
+            hints(true, false, true, false),
+        ),
+        with_output::<SchemaVersionDiff>(
+            Tool::new(
+                "diff_schema_versions",
+                "Compare two registered schema versions and generate the data migration between them",
+                rmcp::handler::server::tool::cached_schema_for_type::<DiffSchemaVersionsParams>(),
+            ),
+            hints(true, false, true, false),
+        ),
+        with_output::<BreakingChangeReport>(
+            Tool::new(
+                "check_breaking_changes",
+                "Classify schema changes since a registered version as breaking or non-breaking, with remediation hints; fails on breaking changes",
+                rmcp::handler::server::tool::cached_schema_for_type::<CheckBreakingChangesParams>(),
+            ),
+            hints(true, false, true, true),
+        ),
+        with_output::<ArtifactsOutput>(
+            Tool::new(
+                "attach_result",
+                "Attach a tool result (scaffolded files, validation report, generated code) to a todo or plan",
+                rmcp::handler::server::tool::cached_schema_for_type::<AttachResultParams>(),
+            ),
+            hints(false, false, false, false),
         ),
     ]
 }
@@ -173,11 +1988,57 @@ pub async fn run_tool(name: &str, args: Value) -> Result<CallToolResult, ErrorDa
                 params.message
             ))]))
         }
+        "health" => Err(ErrorData::invalid_request(
+            "health reports on a running server; call it over an MCP connection",
+            None,
+        )),
+        "explain_config" => Err(ErrorData::invalid_request(
+            "explain_config reports on a running server; use `config explain` without one",
+            None,
+        )),
+        "run_project" => Err(ErrorData::invalid_request(
+            "run_project keeps the dev server as a child of a running server; call it over an MCP connection",
+            None,
+        )),
+        "stop_project" => {
+            let params: StopProjectParams = serde_json::from_value(args)
+                .map_err(|err| ErrorData::invalid_params(err.to_string(), None))?;
+            match stop_project(&params.path).await {
+                Ok(stopped) => Ok(CallToolResult::structured(json!(stopped))),
+                Err(err) => Ok(CallToolResult::structured_error(json!({ "error": err }))),
+            }
+        }
         "validate" => {
             let params: ValidateParams = serde_json::from_value(args)
                 .map_err(|err| ErrorData::invalid_params(err.to_string(), None))?;
-            let result = validate_payload_code(&params.code, params.file_type);
-            Ok(CallToolResult::structured(json!(result)))
+            match validate_code(&params) {
+                Ok(output) => Ok(CallToolResult::structured(json!(output))),
+                Err(err) => Ok(CallToolResult::structured_error(json!({ "error": err }))),
+            }
+        }
+        "access_matrix" => {
+            let params: AccessMatrixParams = serde_json::from_value(args)
+                .map_err(|err| ErrorData::invalid_params(err.to_string(), None))?;
+            let client = if params.is_live() {
+                match required_client(params.connection.clone(), params.connection_string.clone(), params.api_key.clone()) {
+                    Ok(client) => Some(client),
+                    Err(err) => return Ok(CallToolResult::structured_error(json!({ "error": err }))),
+                }
+            } else {
+                None
+            };
+            match access_matrix(&params, client.as_ref()) {
+                Ok(output) => Ok(CallToolResult::structured(json!(output))),
+                Err(err) => Ok(CallToolResult::structured_error(json!({ "error": err }))),
+            }
+        }
+        "analyze_hook" => {
+            let params: AnalyzeHookParams = serde_json::from_value(args)
+                .map_err(|err| ErrorData::invalid_params(err.to_string(), None))?;
+            match analyze_hook_code(&params) {
+                Ok(output) => Ok(CallToolResult::structured(json!(output))),
+                Err(err) => Ok(CallToolResult::structured_error(json!({ "error": err }))),
+            }
         }
         "query" => {
             let params: QueryParams = serde_json::from_value(args)
@@ -187,24 +2048,88 @@ pub async fn run_tool(name: &str, args: Value) -> Result<CallToolResult, ErrorDa
             } else {
                 query_validation_rules(&params.query, params.file_type)
             };
-            Ok(CallToolResult::structured(json!({ "rules": rules })))
+            match rules {
+                Ok(rules) => Ok(CallToolResult::structured(json!({ "rules": rules }))),
+                Err(err) => Ok(CallToolResult::structured_error(json!({ "error": err }))),
+            }
+        }
+        "describe_field_type" => {
+            let params: DescribeFieldTypeParams = serde_json::from_value(args)
+                .map_err(|err| ErrorData::invalid_params(err.to_string(), None))?;
+            match describe_field_type(&params.field_type) {
+                Ok(output) => Ok(CallToolResult::structured(json!(output))),
+                Err(err) => Err(ErrorData::invalid_params(err, None)),
+            }
+        }
+        "search" => {
+            let params: SearchParams = serde_json::from_value(args)
+                .map_err(|err| ErrorData::invalid_params(err.to_string(), None))?;
+            let results = search(
+                &params.query,
+                params.kinds.as_deref(),
+                params.limit.unwrap_or(DEFAULT_SEARCH_LIMIT),
+            );
+            Ok(CallToolResult::structured(json!({ "results": results })))
+        }
+        "search_docs" => {
+            let params: SearchDocsParams = serde_json::from_value(args)
+                .map_err(|err| ErrorData::invalid_params(err.to_string(), None))?;
+            match search_docs(
+                &params.query,
+                params.topic.as_deref(),
+                params.limit.unwrap_or(DEFAULT_SEARCH_LIMIT),
+            ) {
+                Ok(results) => Ok(CallToolResult::structured(json!({ "results": results }))),
+                Err(err) => Ok(CallToolResult::structured_error(json!({ "error": err }))),
+            }
+        }
+        "get_example" => {
+            let params: GetExampleParams = serde_json::from_value(args)
+                .map_err(|err| ErrorData::invalid_params(err.to_string(), None))?;
+            match get_example(&params) {
+                Ok(output) => Ok(CallToolResult::structured(json!(output))),
+                Err(err) => Ok(CallToolResult::structured_error(json!({ "error": err }))),
+            }
         }
         "mcp_query" => {
             let params: SqlParams = serde_json::from_value(args)
                 .map_err(|err| ErrorData::invalid_params(err.to_string(), None))?;
-            match execute_sql_query(&params.sql) {
+            let client = match resolve_connection(
+                params.connection.as_deref(),
+                params.connection_string.as_deref(),
+                params.api_key.clone(),
+            ) {
+                Ok(client) => client,
+                Err(err) => return Ok(CallToolResult::structured_error(json!({ "error": err }))),
+            };
+            match execute_prepared_query(&params.sql, params.params.as_ref(), client.as_ref()) {
                 Ok(results) => Ok(CallToolResult::structured(json!({ "results": results }))),
                 Err(err) => Ok(CallToolResult::structured_error(json!({ "error": err }))),
             }
         }
+        "convert_query" => {
+            let params: ConvertQueryParams = serde_json::from_value(args)
+                .map_err(|err| ErrorData::invalid_params(err.to_string(), None))?;
+            match convert_query(&params.query, params.from) {
+                Ok(converted) => Ok(CallToolResult::structured(json!(converted))),
+                Err(err) => Ok(CallToolResult::structured_error(json!({ "error": err }))),
+            }
+        }
         "generate_template" => {
             let params: GenerateTemplateParams = serde_json::from_value(args)
                 .map_err(|err| ErrorData::invalid_params(err.to_string(), None))?;
-            match generate_template(params.template_type, &params.options) {
+            let generated = naming::profile(params.naming_profile.as_deref())
+                .and_then(|naming| generate_template_with_naming(params.template_type, &params.options, &naming));
+            match generated {
                 Ok(code) => Ok(CallToolResult::structured(json!({ "code": code }))),
                 Err(err) => Ok(CallToolResult::structured_error(json!({ "error": err }))),
             }
         }
+        "list_template_variables" => {
+            let params: ListTemplateVariablesParams = serde_json::from_value(args)
+                .map_err(|err| ErrorData::invalid_params(err.to_string(), None))?;
+            Ok(CallToolResult::structured(json!(list_template_variables(&params))))
+        }
         "generate_collection" => {
             let params: GenerateCollectionParams = serde_json::from_value(args)
                 .map_err(|err| ErrorData::invalid_params(err.to_string(), None))?;
@@ -231,12 +2156,25 @@ pub async fn run_tool(name: &str, args: Value) -> Result<CallToolResult, ErrorDa
             if let Some(versions) = params.versions {
                 options.insert("versions".into(), json!(versions));
             }
+            if let Some(soft_delete) = params.soft_delete {
+                options.insert("softDelete".into(), json!(soft_delete));
+            }
 
-            match generate_template(TemplateType::Collection, &Value::Object(options)) {
+            let generated = naming::profile(params.naming_profile.as_deref())
+                .and_then(|naming| generate_template_with_naming(TemplateType::Collection, &Value::Object(options), &naming));
+            match generated {
                 Ok(code) => Ok(CallToolResult::structured(json!({ "code": code }))),
                 Err(err) => Ok(CallToolResult::structured_error(json!({ "error": err }))),
             }
         }
+        "draft_collection" => {
+            let params: DraftCollectionParams = serde_json::from_value(args)
+                .map_err(|err| ErrorData::invalid_params(err.to_string(), None))?;
+            match draft_collection_code(&params) {
+                Ok(output) => Ok(CallToolResult::structured(json!(output))),
+                Err(err) => Ok(CallToolResult::structured_error(json!({ "error": err }))),
+            }
+        }
         "generate_field" => {
             let params: GenerateFieldParams = serde_json::from_value(args)
                 .map_err(|err| ErrorData::invalid_params(err.to_string(), None))?;
@@ -265,13 +2203,111 @@ pub async fn run_tool(name: &str, args: Value) -> Result<CallToolResult, ErrorDa
                 options.insert("defaultValue".into(), default_value);
             }
 
-            match generate_template(TemplateType::Field, &Value::Object(options)) {
+            let generated = naming::profile(params.naming_profile.as_deref())
+                .and_then(|naming| generate_template_with_naming(TemplateType::Field, &Value::Object(options), &naming));
+            match generated {
                 Ok(code) => Ok(CallToolResult::structured(json!({ "code": code }))),
                 Err(err) => Ok(CallToolResult::structured_error(json!({ "error": err }))),
             }
         }
+        "generate_storage_adapter" => {
+            let params: GenerateStorageAdapterParams = serde_json::from_value(args)
+                .map_err(|err| ErrorData::invalid_params(err.to_string(), None))?;
+            match storage_adapter_config(&params.options, "") {
+                Ok(config) => Ok(CallToolResult::structured(json!(config))),
+                Err(err) => Ok(CallToolResult::structured_error(json!({ "error": err }))),
+            }
+        }
+        "generate_email_config" => {
+            let params: GenerateEmailConfigParams = serde_json::from_value(args)
+                .map_err(|err| ErrorData::invalid_params(err.to_string(), None))?;
+            match email_config(&params.options) {
+                Ok(config) => Ok(CallToolResult::structured(json!(config))),
+                Err(err) => Ok(CallToolResult::structured_error(json!({ "error": err }))),
+            }
+        }
+        "generate_form_builder" => {
+            let params: GenerateFormBuilderParams = serde_json::from_value(args)
+                .map_err(|err| ErrorData::invalid_params(err.to_string(), None))?;
+            match form_builder_config(&params.options) {
+                Ok(config) => Ok(CallToolResult::structured(json!(config))),
+                Err(err) => Ok(CallToolResult::structured_error(json!({ "error": err }))),
+            }
+        }
+        "generate_preview_config" => {
+            let params: GeneratePreviewConfigParams = serde_json::from_value(args)
+                .map_err(|err| ErrorData::invalid_params(err.to_string(), None))?;
+            match preview_config(&params.options) {
+                Ok(config) => Ok(CallToolResult::structured(json!(config))),
+                Err(err) => Ok(CallToolResult::structured_error(json!({ "error": err }))),
+            }
+        }
+        "generate_audit_log" => {
+            let params: GenerateAuditLogParams = serde_json::from_value(args)
+                .map_err(|err| ErrorData::invalid_params(err.to_string(), None))?;
+            match audit_log_config(&params.options) {
+                Ok(config) => Ok(CallToolResult::structured(json!(config))),
+                Err(err) => Ok(CallToolResult::structured_error(json!({ "error": err }))),
+            }
+        }
+        "generate_workflow" => {
+            let params: GenerateWorkflowParams = serde_json::from_value(args)
+                .map_err(|err| ErrorData::invalid_params(err.to_string(), None))?;
+            match workflow_config(&params.options) {
+                Ok(config) => Ok(CallToolResult::structured(json!(config))),
+                Err(err) => Ok(CallToolResult::structured_error(json!({ "error": err }))),
+            }
+        }
+        "generate_seo" => {
+            let params: GenerateSeoParams = serde_json::from_value(args)
+                .map_err(|err| ErrorData::invalid_params(err.to_string(), None))?;
+            match seo_config(&params.options) {
+                Ok(config) => Ok(CallToolResult::structured(json!(config))),
+                Err(err) => Ok(CallToolResult::structured_error(json!({ "error": err }))),
+            }
+        }
+        "generate_redirects" => {
+            let params: GenerateRedirectsParams = serde_json::from_value(args)
+                .map_err(|err| ErrorData::invalid_params(err.to_string(), None))?;
+            match redirects_config(&params.options) {
+                Ok(config) => Ok(CallToolResult::structured(json!(config))),
+                Err(err) => Ok(CallToolResult::structured_error(json!({ "error": err }))),
+            }
+        }
+        "generate_search" => {
+            let params: GenerateSearchParams = serde_json::from_value(args)
+                .map_err(|err| ErrorData::invalid_params(err.to_string(), None))?;
+            match site_search_config(&params.options) {
+                Ok(config) => Ok(CallToolResult::structured(json!(config))),
+                Err(err) => Ok(CallToolResult::structured_error(json!({ "error": err }))),
+            }
+        }
+        "generate_ecommerce" => {
+            let params: GenerateEcommerceParams = serde_json::from_value(args)
+                .map_err(|err| ErrorData::invalid_params(err.to_string(), None))?;
+            match ecommerce_config(&params.options) {
+                Ok(config) => Ok(CallToolResult::structured(json!(config))),
+                Err(err) => Ok(CallToolResult::structured_error(json!({ "error": err }))),
+            }
+        }
+        "generate_webhooks" => {
+            let params: GenerateWebhooksParams = serde_json::from_value(args)
+                .map_err(|err| ErrorData::invalid_params(err.to_string(), None))?;
+            match webhooks_config(&params.options) {
+                Ok(config) => Ok(CallToolResult::structured(json!(config))),
+                Err(err) => Ok(CallToolResult::structured_error(json!({ "error": err }))),
+            }
+        }
+        "generate_plugin_config" => {
+            let params: GeneratePluginConfigParams = serde_json::from_value(args)
+                .map_err(|err| ErrorData::invalid_params(err.to_string(), None))?;
+            match generate_plugin_config(&params) {
+                Ok(config) => Ok(CallToolResult::structured(json!(config))),
+                Err(err) => Ok(CallToolResult::structured_error(json!({ "error": err }))),
+            }
+        }
         "scaffold_project" => {
-            let params: ScaffoldOptions = serde_json::from_value(args)
+            let ScaffoldProjectParams { options: params, out_dir, overwrite } = serde_json::from_value(args)
                 .map_err(|err| ErrorData::invalid_params(err.to_string(), None))?;
 
             if let Err(errors) = validate_scaffold_options(&params) {
@@ -279,17 +2315,311 @@ pub async fn run_tool(name: &str, args: Value) -> Result<CallToolResult, ErrorDa
             }
 
             let scaffold = scaffold_project(&params);
+            if let Some(out_dir) = out_dir {
+                let written = write_scaffold_with_progress(
+                    Path::new(&out_dir),
+                    &scaffold,
+                    overwrite.unwrap_or(false),
+                    |_, _, _| {},
+                );
+                return match written {
+                    Ok(manifest) => Ok(CallToolResult::structured(json!({
+                        "message": format!("Wrote Payload CMS project {} to {out_dir}", params.project_name),
+                        "manifest": manifest
+                    }))),
+                    Err(err) => Ok(CallToolResult::structured_error(json!({ "error": err }))),
+                };
+            }
             let file_structure = scaffold_to_json(scaffold);
             Ok(CallToolResult::structured(json!({
                 "message": format!("Successfully scaffolded Payload CMS project: {}", params.project_name),
                 "fileStructure": file_structure
             })))
         }
+        "add_to_project" => {
+            let params: AddToProjectParams = serde_json::from_value(args)
+                .map_err(|err| ErrorData::invalid_params(err.to_string(), None))?;
+            // Without a session there is no stored project to fall back to.
+            let Some(mut project) = params.project else {
+                return Ok(CallToolResult::structured_error(json!({ "error": "project is required" })));
+            };
+            add_to_project(&mut project, params.additions);
+            if let Err(errors) = validate_scaffold_options(&project) {
+                return Ok(CallToolResult::structured_error(json!({ "errors": errors })));
+            }
+
+            let file_structure = scaffold_to_json(scaffold_project(&project));
+            Ok(CallToolResult::structured(json!({
+                "message": format!("Updated Payload CMS project: {}", project.project_name),
+                "fileStructure": file_structure
+            })))
+        }
+        "generate_multitenancy" => {
+            let GenerateMultitenancyParams { options, project, patch } = serde_json::from_value(args)
+                .map_err(|err| ErrorData::invalid_params(err.to_string(), None))?;
+            let Some(mut project) = project else {
+                if patch.unwrap_or(false) {
+                    return Ok(CallToolResult::structured_error(json!({ "error": "project is required" })));
+                }
+                return match multitenancy_files(&options) {
+                    Ok(files) => Ok(CallToolResult::structured(json!({ "files": files }))),
+                    Err(err) => Ok(CallToolResult::structured_error(json!({ "error": err }))),
+                };
+            };
+            project.multitenancy = Some(options);
+            if let Err(errors) = validate_scaffold_options(&project) {
+                return Ok(CallToolResult::structured_error(json!({ "errors": errors })));
+            }
+
+            let file_structure = scaffold_to_json(scaffold_project(&project));
+            Ok(CallToolResult::structured(json!({
+                "message": format!("Added multi-tenancy to Payload CMS project: {}", project.project_name),
+                "fileStructure": file_structure
+            })))
+        }
+        "import_from_database" => {
+            let params: ImportFromDatabaseParams = serde_json::from_value(args)
+                .map_err(|err| ErrorData::invalid_params(err.to_string(), None))?;
+            match import_from_database(&params.options) {
+                Ok(import) => Ok(CallToolResult::structured(json!(import))),
+                Err(err) => Ok(CallToolResult::structured_error(json!({ "error": err }))),
+            }
+        }
+        "infer_schema" => {
+            let params: InferSchemaParams = serde_json::from_value(args)
+                .map_err(|err| ErrorData::invalid_params(err.to_string(), None))?;
+            match infer_from_samples(&params.options) {
+                Ok(inference) => Ok(CallToolResult::structured(json!(inference))),
+                Err(err) => Ok(CallToolResult::structured_error(json!({ "error": err }))),
+            }
+        }
+        "import_external_schema" => {
+            let params: ImportExternalSchemaParams = serde_json::from_value(args)
+                .map_err(|err| ErrorData::invalid_params(err.to_string(), None))?;
+            match import_external_schema(&params.options) {
+                Ok(import) => Ok(CallToolResult::structured(json!(import))),
+                Err(err) => Ok(CallToolResult::structured_error(json!({ "error": err }))),
+            }
+        }
+        "wp_to_payload_schema" => {
+            let params: WpToPayloadSchemaParams = serde_json::from_value(args)
+                .map_err(|err| ErrorData::invalid_params(err.to_string(), None))?;
+            match wp_to_payload_schema(&params.options) {
+                Ok(import) => Ok(CallToolResult::structured(json!(import))),
+                Err(err) => Ok(CallToolResult::structured_error(json!({ "error": err }))),
+            }
+        }
+        "wp_export_to_seed" => {
+            let params: WpExportToSeedParams = serde_json::from_value(args)
+                .map_err(|err| ErrorData::invalid_params(err.to_string(), None))?;
+            match params
+                .source()
+                .and_then(|export| wp_export_to_seed(&export, &params.options))
+            {
+                Ok(seed) => Ok(CallToolResult::structured(json!(seed))),
+                Err(err) => Ok(CallToolResult::structured_error(json!({ "error": err }))),
+            }
+        }
+        "patch_config" => {
+            let params: PatchConfigParams = serde_json::from_value(args)
+                .map_err(|err| ErrorData::invalid_params(err.to_string(), None))?;
+            match params.source().and_then(|source| patch_config(&source, &params.edits)) {
+                Ok(patched) => Ok(CallToolResult::structured(json!(patched))),
+                Err(err) => Ok(CallToolResult::structured_error(json!({ "error": err }))),
+            }
+        }
+        "env_audit" => {
+            let params: EnvAuditParams = serde_json::from_value(args)
+                .map_err(|err| ErrorData::invalid_params(err.to_string(), None))?;
+            match env_audit_for(&params, params.project.as_ref()) {
+                Ok(audit) => Ok(CallToolResult::structured(json!(audit))),
+                Err(err) => Ok(CallToolResult::structured_error(json!({ "error": err }))),
+            }
+        }
+        "check_dependencies" => {
+            let params: CheckDependenciesParams = serde_json::from_value(args)
+                .map_err(|err| ErrorData::invalid_params(err.to_string(), None))?;
+            match params.source().and_then(|source| check_dependencies(&source)) {
+                Ok(report) => Ok(CallToolResult::structured(json!(report))),
+                Err(err) => Ok(CallToolResult::structured_error(json!({ "error": err }))),
+            }
+        }
+        "parse_payload_types" => {
+            let params: ParsePayloadTypesParams = serde_json::from_value(args)
+                .map_err(|err| ErrorData::invalid_params(err.to_string(), None))?;
+            match params.source().and_then(|source| parse_payload_types(&source)) {
+                Ok(parsed) => Ok(CallToolResult::structured(json!(parsed))),
+                Err(err) => Ok(CallToolResult::structured_error(json!({ "error": err }))),
+            }
+        }
+        "generate_openapi" => {
+            let params: GenerateOpenApiParams = serde_json::from_value(args)
+                .map_err(|err| ErrorData::invalid_params(err.to_string(), None))?;
+            match source_schema(params.source) {
+                Ok(schema) => Ok(CallToolResult::structured(json!(openapi_document(&schema, &params.options)))),
+                Err(err) => Ok(CallToolResult::structured_error(json!({ "error": err }))),
+            }
+        }
+        "generate_graphql_sdl" => {
+            let params: GenerateGraphqlSdlParams = serde_json::from_value(args)
+                .map_err(|err| ErrorData::invalid_params(err.to_string(), None))?;
+            match source_schema(params.source) {
+                Ok(schema) => Ok(CallToolResult::structured(json!(graphql_sdl(&schema, &params.options)))),
+                Err(err) => Ok(CallToolResult::structured_error(json!({ "error": err }))),
+            }
+        }
+        "generate_client_sdk" => {
+            let params: GenerateClientSdkParams = serde_json::from_value(args)
+                .map_err(|err| ErrorData::invalid_params(err.to_string(), None))?;
+            match source_schema(params.source) {
+                Ok(schema) => Ok(CallToolResult::structured(json!(client_sdk(&schema, &params.options)))),
+                Err(err) => Ok(CallToolResult::structured_error(json!({ "error": err }))),
+            }
+        }
+        "generate_json_schema" => {
+            let params: GenerateJsonSchemaParams = serde_json::from_value(args)
+                .map_err(|err| ErrorData::invalid_params(err.to_string(), None))?;
+            match source_schema(params.source) {
+                Ok(schema) => Ok(CallToolResult::structured(json!(json_schemas(&schema, &params.options)))),
+                Err(err) => Ok(CallToolResult::structured_error(json!({ "error": err }))),
+            }
+        }
+        "schema_graph" => {
+            let params: SchemaGraphParams = serde_json::from_value(args)
+                .map_err(|err| ErrorData::invalid_params(err.to_string(), None))?;
+            match source_schema(params.source) {
+                Ok(schema) => Ok(CallToolResult::structured(json!(schema_graph(&schema, &params.options)))),
+                Err(err) => Ok(CallToolResult::structured_error(json!({ "error": err }))),
+            }
+        }
+        "analyze_relationship_depth" => {
+            let params: AnalyzeRelationshipDepthParams = serde_json::from_value(args)
+                .map_err(|err| ErrorData::invalid_params(err.to_string(), None))?;
+            match source_schema(params.source) {
+                Ok(schema) => Ok(CallToolResult::structured(json!(relationship_depth(&schema, &params.options)))),
+                Err(err) => Ok(CallToolResult::structured_error(json!({ "error": err }))),
+            }
+        }
+        "export_content" => {
+            let params: ExportContentParams = serde_json::from_value(args)
+                .map_err(|err| ErrorData::invalid_params(err.to_string(), None))?;
+            let exported = required_client(params.connection, params.connection_string, params.api_key)
+                .and_then(|client| export_to(&client, &params.options, params.path));
+            match exported {
+                Ok(output) => Ok(CallToolResult::structured(json!(output))),
+                Err(err) => Ok(CallToolResult::structured_error(json!({ "error": err }))),
+            }
+        }
+        "anonymize_export" => {
+            let params: AnonymizeExportParams = serde_json::from_value(args)
+                .map_err(|err| ErrorData::invalid_params(err.to_string(), None))?;
+            let exported = required_client(params.connection, params.connection_string, params.api_key)
+                .and_then(|client| anonymize_to(&client, &params.options, params.path));
+            match exported {
+                Ok(output) => Ok(CallToolResult::structured(json!(output))),
+                Err(err) => Ok(CallToolResult::structured_error(json!({ "error": err }))),
+            }
+        }
+        "i18n_audit" => {
+            let params: I18nAuditParams = serde_json::from_value(args)
+                .map_err(|err| ErrorData::invalid_params(err.to_string(), None))?;
+            let audit = required_client(params.connection, params.connection_string, params.api_key)
+                .and_then(|client| {
+                    let schema = audit_schema(&client, params.schema, params.files)?;
+                    i18n_audit(&client, &schema, &params.options)
+                });
+            match audit {
+                Ok(audit) => Ok(CallToolResult::structured(json!(audit))),
+                Err(err) => Ok(CallToolResult::structured_error(json!({ "error": err }))),
+            }
+        }
+        "content_audit" => {
+            let params: ContentAuditParams = serde_json::from_value(args)
+                .map_err(|err| ErrorData::invalid_params(err.to_string(), None))?;
+            let audit = required_client(params.connection, params.connection_string, params.api_key)
+                .and_then(|client| {
+                    let schema = audit_schema(&client, params.schema, params.files)?;
+                    content_audit(&client, &schema, &params.options)
+                });
+            match audit {
+                Ok(audit) => Ok(CallToolResult::structured(json!(audit))),
+                Err(err) => Ok(CallToolResult::structured_error(json!({ "error": err }))),
+            }
+        }
+        "bulk_update" => {
+            let params: BulkUpdateParams = serde_json::from_value(args)
+                .map_err(|err| ErrorData::invalid_params(err.to_string(), None))?;
+            let update = required_client(params.connection, params.connection_string, params.api_key)
+                .and_then(|client| bulk_update(&client, &params.options));
+            match update {
+                Ok(update) => Ok(CallToolResult::structured(json!(update))),
+                Err(err) => Ok(CallToolResult::structured_error(json!({ "error": err }))),
+            }
+        }
+        "import_content" => {
+            let params: ImportContentParams = serde_json::from_value(args)
+                .map_err(|err| ErrorData::invalid_params(err.to_string(), None))?;
+            let imported = required_client(params.connection, params.connection_string, params.api_key)
+                .and_then(|client| import_from(&client, params.content, params.path, &params.options));
+            match imported {
+                Ok(import) => Ok(CallToolResult::structured(json!(import))),
+                Err(err) => Ok(CallToolResult::structured_error(json!({ "error": err }))),
+            }
+        }
+        "backup_instance" => {
+            let params: BackupInstanceParams = serde_json::from_value(args)
+                .map_err(|err| ErrorData::invalid_params(err.to_string(), None))?;
+            let backup = required_client(params.connection, params.connection_string, params.api_key)
+                .and_then(|client| backup_instance(&client, Path::new(&params.dir), &params.options));
+            match backup {
+                Ok(backup) => Ok(CallToolResult::structured(json!(backup))),
+                Err(err) => Ok(CallToolResult::structured_error(json!({ "error": err }))),
+            }
+        }
+        "restore_instance" => {
+            let params: RestoreInstanceParams = serde_json::from_value(args)
+                .map_err(|err| ErrorData::invalid_params(err.to_string(), None))?;
+            let restore = required_client(params.connection, params.connection_string, params.api_key)
+                .and_then(|client| restore_instance(&client, Path::new(&params.path), &params.options));
+            match restore {
+                Ok(restore) => Ok(CallToolResult::structured(json!(restore))),
+                Err(err) => Ok(CallToolResult::structured_error(json!({ "error": err }))),
+            }
+        }
+        "validate_project" => {
+            let params: ValidateProjectParams = serde_json::from_value(args)
+                .map_err(|err| ErrorData::invalid_params(err.to_string(), None))?;
+            let Some(project) = params.project else {
+                return Ok(CallToolResult::structured_error(json!({ "error": "project is required" })));
+            };
+            Ok(CallToolResult::structured(json!(validate_project_files(&project, 0, params.format))))
+        }
+        "self_check" => {
+            let params: SelfCheckParams = serde_json::from_value(args)
+                .map_err(|err| ErrorData::invalid_params(err.to_string(), None))?;
+            Ok(CallToolResult::structured(json!(self_check_files(&params, 0))))
+        }
+        "generate_tests" => {
+            let params: GenerateTestsParams = serde_json::from_value(args)
+                .map_err(|err| ErrorData::invalid_params(err.to_string(), None))?;
+            match generate_collection_tests(
+                &params.collections,
+                &params.roles.unwrap_or_default(),
+                params.database.as_deref().unwrap_or("mongodb"),
+                params.hooks.unwrap_or(true),
+                params.access.unwrap_or(true),
+            ) {
+                Ok(files) => Ok(CallToolResult::structured(json!({
+                    "fileStructure": scaffold_to_json(files)
+                }))),
+                Err(err) => Ok(CallToolResult::structured_error(json!({ "error": err }))),
+            }
+        }
         "connect_payload" => {
             let params: ConnectPayloadParams = serde_json::from_value(args)
                 .map_err(|err| ErrorData::invalid_params(err.to_string(), None))?;
 
-            match create_payload_client(&params.connection_string, params.api_key) {
+            match required_client(params.connection, params.connection_string, params.api_key) {
                 Ok(client) => {
                     match client.test_connection() {
                         Ok(info) => Ok(CallToolResult::structured(json!({
@@ -312,7 +2642,8 @@ pub async fn run_tool(name: &str, args: Value) -> Result<CallToolResult, ErrorDa
             let params: GetCollectionParams = serde_json::from_value(args)
                 .map_err(|err| ErrorData::invalid_params(err.to_string(), None))?;
 
-            match create_payload_client(&params.connection_string, params.api_key) {
+            let client = required_client(params.connection, params.connection_string, params.api_key);
+            match client {
                 Ok(client) => {
                     match client.get_collection(&params.slug) {
                         Ok(collection) => Ok(CallToolResult::structured(json!({
@@ -335,7 +2666,8 @@ pub async fn run_tool(name: &str, args: Value) -> Result<CallToolResult, ErrorDa
             let params: ListCollectionsParams = serde_json::from_value(args)
                 .map_err(|err| ErrorData::invalid_params(err.to_string(), None))?;
 
-            match create_payload_client(&params.connection_string, params.api_key) {
+            let client = required_client(params.connection, params.connection_string, params.api_key);
+            match client {
                 Ok(client) => {
                     match client.list_collections() {
                         Ok(collections) => Ok(CallToolResult::structured(json!({
@@ -358,7 +2690,8 @@ pub async fn run_tool(name: &str, args: Value) -> Result<CallToolResult, ErrorDa
             let params: ValidateAgainstLiveParams = serde_json::from_value(args)
                 .map_err(|err| ErrorData::invalid_params(err.to_string(), None))?;
 
-            match create_payload_client(&params.connection_string, params.api_key) {
+            let client = required_client(params.connection, params.connection_string, params.api_key);
+            match client {
                 Ok(client) => {
                     match client.validate_collection_config(&params.slug, &params.config) {
                         Ok(issues) => Ok(CallToolResult::structured(json!({
@@ -377,6 +2710,133 @@ pub async fn run_tool(name: &str, args: Value) -> Result<CallToolResult, ErrorDa
                 })))
             }
         }
+        "add_connection" => {
+            let params: AddConnectionParams = serde_json::from_value(args)
+                .map_err(|err| ErrorData::invalid_params(err.to_string(), None))?;
+            let connection = Connection {
+                url: params.url,
+                api_key: params.api_key,
+            };
+            match connections::store(paths::active_settings_path(), &params.name, &connection) {
+                Ok(()) => Ok(CallToolResult::structured(json!(ConnectionOutput {
+                    name: params.name,
+                    connection,
+                }))),
+                Err(err) => Ok(CallToolResult::structured_error(json!({ "error": err }))),
+            }
+        }
+        "list_connections" => match connections::load(paths::active_settings_path()) {
+            Ok(connections) => Ok(CallToolResult::structured(json!(ConnectionListOutput { connections }))),
+            Err(err) => Ok(CallToolResult::structured_error(json!({ "error": err }))),
+        },
+        "start_planning" => {
+            let params: StartPlanningParams = serde_json::from_value(args)
+                .map_err(|err| ErrorData::invalid_params(err.to_string(), None))?;
+            let result = Storage::open_default()
+                .and_then(|storage| storage.create_goal(&params.goal))
+                .map(|(goal, plan)| PlanOutput { goal, plan });
+            Ok(planning_result(result))
+        }
+        "save_plan" => {
+            let params: SavePlanParams = serde_json::from_value(args)
+                .map_err(|err| ErrorData::invalid_params(err.to_string(), None))?;
+            let result = required_goal(params.goal_id).and_then(|goal_id| {
+                let storage = Storage::open_default()?;
+                storage.save_plan(&goal_id, &params.plan)?;
+                storage.get_plan(&goal_id)
+            });
+            Ok(planning_result(result.map(|(goal, plan)| PlanOutput { goal, plan })))
+        }
+        "add_todo" => {
+            let params: AddTodoParams = serde_json::from_value(args)
+                .map_err(|err| ErrorData::invalid_params(err.to_string(), None))?;
+            let result = required_goal(params.goal_id.clone()).and_then(|goal_id| {
+                Storage::open_default()?.add_todo(&goal_id, params.new_todo())
+            });
+            Ok(planning_result(result.map(|todo| TodoOutput { todo })))
+        }
+        "update_todo_status" => {
+            let params: UpdateTodoStatusParams = serde_json::from_value(args)
+                .map_err(|err| ErrorData::invalid_params(err.to_string(), None))?;
+            let result = required_goal(params.goal_id).and_then(|goal_id| {
+                Storage::open_default()?.update_todo_status(&goal_id, &params.todo_id, params.is_complete)
+            });
+            Ok(planning_result(result.map(|todo| TodoOutput { todo })))
+        }
+        "get_plan" => {
+            let params: GetPlanParams = serde_json::from_value(args)
+                .map_err(|err| ErrorData::invalid_params(err.to_string(), None))?;
+            let result = required_goal(params.goal_id)
+                .and_then(|goal_id| Storage::open_default()?.get_plan(&goal_id));
+            Ok(planning_result(result.map(|(goal, plan)| PlanOutput { goal, plan })))
+        }
+        "list_todos" => {
+            let params: ListTodosParams = serde_json::from_value(args)
+                .map_err(|err| ErrorData::invalid_params(err.to_string(), None))?;
+            let result = Storage::open_default()
+                .and_then(|storage| storage.list_todos(params.incomplete_only.unwrap_or(true)));
+            Ok(planning_result(result.map(|todos| TodoListOutput { todos })))
+        }
+        "plan_history" => {
+            let params: PlanHistoryParams = serde_json::from_value(args)
+                .map_err(|err| ErrorData::invalid_params(err.to_string(), None))?;
+            let result = required_goal(params.goal_id)
+                .and_then(|goal_id| Storage::open_default()?.plan_history(&goal_id));
+            Ok(planning_result(result.map(|revisions| PlanHistoryOutput { revisions })))
+        }
+        "register_schema" => {
+            let params: RegisterSchemaParams = serde_json::from_value(args)
+                .map_err(|err| ErrorData::invalid_params(err.to_string(), None))?;
+            let result = source_schema(params.source).and_then(|schema| {
+                register_schema(&Storage::open_default()?, &params.name, params.label.as_deref(), &schema)
+            });
+            Ok(planning_result(result))
+        }
+        "list_schema_versions" => {
+            let params: ListSchemaVersionsParams = serde_json::from_value(args)
+                .map_err(|err| ErrorData::invalid_params(err.to_string(), None))?;
+            let result = Storage::open_default().and_then(|storage| storage.schema_versions(params.name.as_deref()));
+            Ok(planning_result(result.map(|versions| SchemaVersionsOutput { versions })))
+        }
+        "diff_schema_versions" => {
+            let params: DiffSchemaVersionsParams = serde_json::from_value(args)
+                .map_err(|err| ErrorData::invalid_params(err.to_string(), None))?;
+            let result = Storage::open_default().and_then(|storage| {
+                diff_schema_versions(&storage, &params.name, params.from, params.to, params.database.as_deref())
+            });
+            Ok(planning_result(result))
+        }
+        "check_breaking_changes" => {
+            let params: CheckBreakingChangesParams = serde_json::from_value(args)
+                .map_err(|err| ErrorData::invalid_params(err.to_string(), None))?;
+            let candidate = if params.source.is_given() {
+                match source_schema(params.source) {
+                    Ok(schema) => Some(schema),
+                    Err(err) => return Ok(CallToolResult::structured_error(json!({ "error": err }))),
+                }
+            } else {
+                None
+            };
+            let result = Storage::open_default()
+                .and_then(|storage| check_breaking_changes(&storage, &params.options, candidate.as_ref()));
+            Ok(breaking_change_result(result, params.fail_on_breaking))
+        }
+        "attach_result" => {
+            let params: AttachResultParams = serde_json::from_value(args)
+                .map_err(|err| ErrorData::invalid_params(err.to_string(), None))?;
+            let result = required_goal(params.goal_id).and_then(|goal_id| {
+                let value = params
+                    .result
+                    .ok_or_else(|| "result is required".to_string())?;
+                Storage::open_default()?.attach_artifacts(
+                    &goal_id,
+                    params.todo_id.as_deref(),
+                    &params.tool,
+                    artifacts_from_result(&params.tool, &value),
+                )
+            });
+            Ok(planning_result(result.map(|artifacts| ArtifactsOutput { artifacts })))
+        }
         _ => Err(ErrorData::invalid_params(
             format!("Unknown tool: {name}"),
             None,
@@ -384,7 +2844,139 @@ pub async fn run_tool(name: &str, args: Value) -> Result<CallToolResult, ErrorDa
     }
 }
 
-fn scaffold_to_json(map: ScaffoldFileStructure) -> Value {
+/// Without a session there is no connection to fall back to.
+fn required_client(
+    connection: Option<String>,
+    connection_string: Option<String>,
+    api_key: Option<String>,
+) -> Result<PayloadClient, String> {
+    resolve_connection(connection.as_deref(), connection_string.as_deref(), api_key)?
+        .ok_or_else(|| "connection or connection_string is required".to_string())
+}
+
+/// Export to the file at `path`, or into memory when there is none.
+pub(crate) fn export_to(
+    client: &PayloadClient,
+    options: &ContentExportOptions,
+    path: Option<String>,
+) -> Result<ContentExportOutput, String> {
+    let (counts, ndjson) = match &path {
+        Some(path) => {
+            let file = std::fs::File::create(path).map_err(|err| format!("Failed to create {path}: {err}"))?;
+            let mut out = std::io::BufWriter::new(file);
+            let counts = export_content(client, options, &mut out)?;
+            std::io::Write::flush(&mut out).map_err(|err| format!("Failed to write {path}: {err}"))?;
+            (counts, None)
+        }
+        None => {
+            let mut out = Vec::new();
+            let counts = export_content(client, options, &mut out)?;
+            (counts, Some(String::from_utf8_lossy(&out).into_owned()))
+        }
+    };
+    Ok(ContentExportOutput {
+        total: counts.values().sum(),
+        counts,
+        ndjson,
+        path,
+        linked: None,
+    })
+}
+
+/// Export anonymized to the file at `path`, or into memory when there is none.
+pub(crate) fn anonymize_to(
+    client: &PayloadClient,
+    options: &AnonymizeOptions,
+    path: Option<String>,
+) -> Result<AnonymizeExportOutput, String> {
+    let (summary, ndjson) = match &path {
+        Some(path) => {
+            let file = std::fs::File::create(path).map_err(|err| format!("Failed to create {path}: {err}"))?;
+            let mut out = std::io::BufWriter::new(file);
+            let summary = anonymize_export(client, options, &mut out)?;
+            std::io::Write::flush(&mut out).map_err(|err| format!("Failed to write {path}: {err}"))?;
+            (summary, None)
+        }
+        None => {
+            let mut out = Vec::new();
+            let summary = anonymize_export(client, options, &mut out)?;
+            (summary, Some(String::from_utf8_lossy(&out).into_owned()))
+        }
+    };
+    Ok(AnonymizeExportOutput {
+        export: ContentExportOutput {
+            total: summary.documents.values().sum(),
+            counts: summary.documents,
+            ndjson,
+            path,
+            linked: None,
+        },
+        scrubbed: summary.scrubbed,
+    })
+}
+
+/// Import from `content`, or stream the file at `path`.
+pub(crate) fn import_from(
+    client: &PayloadClient,
+    content: Option<String>,
+    path: Option<String>,
+    options: &ContentImportOptions,
+) -> Result<ContentImport, String> {
+    match (content, path) {
+        (Some(_), Some(_)) => Err("Pass either content or path, not both".to_string()),
+        (Some(content), None) => import_content(client, content.as_bytes(), options),
+        (None, Some(path)) => {
+            let file = std::fs::File::open(&path).map_err(|err| format!("Failed to open {path}: {err}"))?;
+            import_content(client, std::io::BufReader::new(file), options)
+        }
+        (None, None) => Err("content or path is required".to_string()),
+    }
+}
+
+/// The schema `i18n_audit` and `content_audit` check: the one given, or the
+/// live instance's.
+pub(crate) fn audit_schema(
+    client: &PayloadClient,
+    schema: Option<SchemaModel>,
+    files: Option<Vec<SourceFile>>,
+) -> Result<SchemaModel, String> {
+    match (schema, files) {
+        (Some(schema), _) => Ok(schema),
+        (None, Some(files)) => SchemaModel::from_sources(&files),
+        (None, None) => live_schema(client),
+    }
+}
+
+/// The depth analysis of a schema's whole graph, globals included.
+pub(crate) fn relationship_depth(schema: &SchemaModel, options: &DepthOptions) -> DepthAnalysis {
+    analyze_depth(&schema_graph(schema, &SchemaGraphOptions::default()), options)
+}
+
+/// Without a session, a live schema needs a connection and there is no
+/// project to fall back to.
+fn source_schema(source: SchemaSourceParams) -> Result<SchemaModel, String> {
+    if let Some(schema) = source.schema {
+        return Ok(schema);
+    }
+    if let Some(files) = source.files {
+        return SchemaModel::from_sources(&files);
+    }
+    if source.is_live() {
+        let client = required_client(source.connection, source.connection_string, source.api_key)?;
+        return live_schema(&client);
+    }
+    source
+        .project
+        .map(|project| SchemaModel::from(&project))
+        .ok_or_else(|| "schema, files, project or a connection is required".to_string())
+}
+
+/// Without a session there is no goal to fall back to.
+fn required_goal(goal_id: Option<String>) -> Result<String, String> {
+    goal_id.ok_or_else(|| "goal_id is required".to_string())
+}
+
+pub fn scaffold_to_json(map: ScaffoldFileStructure) -> Value {
     let mut out = serde_json::Map::new();
     for (k, v) in map {
         match v {
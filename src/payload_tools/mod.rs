@@ -1,13 +1,69 @@
+pub mod access_matrix;
+pub mod anonymize;
+pub mod audit_log;
+pub mod backup;
+pub mod breaking_changes;
+pub mod bulk_update;
+pub mod cache;
+pub mod ci;
 pub mod client;
+pub mod client_sdk;
+pub mod collection_draft;
+pub mod completions;
+pub mod config_patch;
+pub mod content;
+pub mod content_audit;
+pub mod dependencies;
+pub mod db_import;
+pub mod ecommerce;
+pub mod email;
+pub mod env_audit;
+pub mod examples;
+pub mod external_schema;
+pub mod forms;
 pub mod generator;
+pub mod graphql;
+pub mod guides;
+pub mod hook_analyzer;
+pub mod i18n_audit;
 pub mod index;
+pub mod json_schema;
 pub mod mcp;
+pub mod multitenancy;
+pub mod naming;
+pub mod openapi;
+pub mod parallel;
+pub mod payload_types;
+pub mod plugins;
+pub mod preview;
+pub mod prompts;
 pub mod query;
+pub mod redirects;
+pub mod relationship_depth;
+pub mod resources;
 pub mod scaffolder;
+pub mod schema_cache;
+pub mod schema_graph;
+pub mod schema_model;
+pub mod schema_registry;
 pub mod schemas;
+pub mod search;
+pub mod seo;
+pub mod self_check;
+pub mod site_search;
 pub mod sql;
+pub mod sql_parser;
+pub mod storage;
+pub mod storage_adapters;
+pub mod template_overrides;
+pub mod testgen;
+pub mod ts_parser;
 pub mod types;
 pub mod validator;
+pub mod webhooks;
+pub mod where_query;
+pub mod wordpress;
+pub mod workflow;
 
 
 
@@ -0,0 +1,336 @@
+//! The multi-tenant pattern: a tenants collection, a `tenant` relationship on
+//! each scoped collection, access functions limiting users to their tenants,
+//! and an `afterLogin` hook keeping the user's active tenant valid.
+//!
+//! Users belong to tenants through a `tenants` relationship and write to
+//! their `activeTenant`; users with the `super-admin` role see everything.
+//! The files are returned on their own or patched into a scaffold.
+
+use std::collections::BTreeMap;
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+use crate::payload_tools::{
+    generator::{TemplateType, capitalize, generate_template},
+    scaffolder::{ScaffoldFile, ScaffoldFileStructure},
+};
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct MultitenancyOptions {
+    /// Collections whose documents belong to a tenant
+    pub collections: Vec<String>,
+    /// Slug of the tenants collection (default `tenants`)
+    pub tenants_slug: Option<String>,
+    /// Auth collection of users assigned to tenants (default `users`)
+    pub users_slug: Option<String>,
+    /// Name of the relationship field on scoped collections (default `tenant`)
+    pub field_name: Option<String>,
+}
+
+/// The access functions a scoped collection used before being patched; the
+/// collection template writes this block when `access` is set.
+const OPEN_ACCESS: &str = "\n  access: {\n    read: () => true,\n    update: () => true,\n    create: () => true,\n    delete: () => true,\n  },";
+
+struct Names<'a> {
+    tenants: &'a str,
+    users: &'a str,
+    field: &'a str,
+}
+
+fn is_slug(value: &str) -> bool {
+    !value.is_empty()
+        && value
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
+
+fn names(options: &MultitenancyOptions) -> Result<Names<'_>, String> {
+    let names = Names {
+        tenants: options.tenants_slug.as_deref().unwrap_or("tenants"),
+        users: options.users_slug.as_deref().unwrap_or("users"),
+        field: options.field_name.as_deref().unwrap_or("tenant"),
+    };
+    if options.collections.is_empty() {
+        return Err("At least one collection to scope by tenant is required".to_string());
+    }
+    for slug in options
+        .collections
+        .iter()
+        .map(String::as_str)
+        .chain([names.tenants, names.users])
+    {
+        if !is_slug(slug) {
+            return Err(format!("Invalid collection slug '{slug}'"));
+        }
+    }
+    if let Some(slug) = options
+        .collections
+        .iter()
+        .find(|slug| *slug == names.tenants || *slug == names.users)
+    {
+        return Err(format!(
+            "'{slug}' cannot be scoped by tenant; users are assigned tenants through their `tenants` field"
+        ));
+    }
+    if names.field.starts_with(|c: char| c.is_ascii_digit())
+        || !names
+            .field
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_')
+    {
+        return Err(format!("Invalid field name '{}'", names.field));
+    }
+    Ok(names)
+}
+
+fn tenants_collection(names: &Names) -> String {
+    let name = capitalize(names.tenants);
+    format!(
+        "import {{ CollectionConfig }} from 'payload/types';\nimport {{ isSuperAdmin, superAdminOnly, tenantIds }} from '../access/tenants';\n\nconst {name}: CollectionConfig = {{\n  slug: '{tenants}',\n  admin: {{\n    useAsTitle: 'name',\n  }},\n  access: {{\n    read: ({{ req: {{ user }} }}) => {{\n      if (!user) return false;\n      if (isSuperAdmin(user)) return true;\n      return {{ id: {{ in: tenantIds(user) }} }};\n    }},\n    create: superAdminOnly,\n    update: superAdminOnly,\n    delete: superAdminOnly,\n  }},\n  fields: [\n    {{\n      name: 'name',\n      type: 'text',\n      required: true,\n    }},\n    {{\n      name: 'slug',\n      type: 'text',\n      required: true,\n      unique: true,\n      index: true,\n    }},\n    {{\n      name: 'domains',\n      type: 'array',\n      fields: [\n        {{\n          name: 'domain',\n          type: 'text',\n          required: true,\n        }},\n      ],\n    }},\n  ],\n}};\n\nexport default {name};\n",
+        tenants = names.tenants,
+    )
+}
+
+fn access_functions(names: &Names) -> String {
+    format!(
+        "import {{ Access }} from 'payload/types';\n\ntype Ref = string | {{ id: string }};\n\ntype TenantUser = {{\n  id: string;\n  roles?: string[];\n  tenants?: Ref[];\n  activeTenant?: Ref | null;\n}};\n\nconst idOf = (ref: Ref): string => (typeof ref === 'string' ? ref : ref.id);\n\nexport const isSuperAdmin = (user?: TenantUser | null): boolean =>\n  Boolean(user?.roles?.includes('super-admin'));\n\nexport const tenantIds = (user?: TenantUser | null): string[] => (user?.tenants ?? []).map(idOf);\n\n/** The tenant new documents are created in: the active tenant, else the first. */\nexport const activeTenantId = (user?: TenantUser | null): string | undefined =>\n  user?.activeTenant ? idOf(user.activeTenant) : tenantIds(user)[0];\n\nexport const superAdminOnly: Access = ({{ req: {{ user }} }}) => isSuperAdmin(user);\n\n/** Documents of any of the user's tenants. */\nexport const tenantRead: Access = ({{ req: {{ user }} }}) => {{\n  if (!user) return false;\n  if (isSuperAdmin(user)) return true;\n  return {{ {field}: {{ in: tenantIds(user) }} }};\n}};\n\nexport const tenantCreate: Access = ({{ req: {{ user }} }}) =>\n  Boolean(user && (isSuperAdmin(user) || activeTenantId(user)));\n\n/** Documents of the active tenant only. */\nexport const tenantWrite: Access = ({{ req: {{ user }} }}) => {{\n  if (!user) return false;\n  if (isSuperAdmin(user)) return true;\n  const tenant = activeTenantId(user);\n  return tenant ? {{ {field}: {{ equals: tenant }} }} : false;\n}};\n",
+        field = names.field,
+    )
+}
+
+fn tenant_field(names: &Names) -> String {
+    format!(
+        "import {{ Field }} from 'payload/types';\nimport {{ activeTenantId, isSuperAdmin }} from '../access/tenants';\n\n/** Set to the user's active tenant; only super admins pick another one. */\nexport const tenantField: Field = {{\n  name: '{field}',\n  type: 'relationship',\n  relationTo: '{tenants}',\n  required: true,\n  index: true,\n  admin: {{\n    position: 'sidebar',\n    condition: (_, __, {{ user }}) => isSuperAdmin(user),\n  }},\n  access: {{\n    update: ({{ req: {{ user }} }}) => isSuperAdmin(user),\n  }},\n  hooks: {{\n    beforeValidate: [\n      ({{ req: {{ user }}, value }}) => (isSuperAdmin(user) && value ? value : activeTenantId(user) ?? value),\n    ],\n  }},\n}};\n",
+        field = names.field,
+        tenants = names.tenants,
+    )
+}
+
+fn user_fields(names: &Names) -> String {
+    format!(
+        "import {{ Field }} from 'payload/types';\nimport {{ isSuperAdmin }} from '../access/tenants';\n\nexport const userTenantFields: Field[] = [\n  {{\n    name: 'roles',\n    type: 'select',\n    hasMany: true,\n    defaultValue: ['user'],\n    options: ['super-admin', 'user'],\n    access: {{\n      update: ({{ req: {{ user }} }}) => isSuperAdmin(user),\n    }},\n  }},\n  {{\n    name: 'tenants',\n    type: 'relationship',\n    relationTo: '{tenants}',\n    hasMany: true,\n    access: {{\n      update: ({{ req: {{ user }} }}) => isSuperAdmin(user),\n    }},\n  }},\n  {{\n    name: 'activeTenant',\n    type: 'relationship',\n    relationTo: '{tenants}',\n    admin: {{\n      position: 'sidebar',\n    }},\n  }},\n];\n",
+        tenants = names.tenants,
+    )
+}
+
+fn after_login_hook(names: &Names) -> String {
+    format!(
+        "import {{ CollectionAfterLoginHook }} from 'payload/types';\nimport {{ tenantIds }} from '../access/tenants';\n\n/** Keep the active tenant one of the user's tenants, defaulting to the first. */\nexport const setActiveTenant: CollectionAfterLoginHook = async ({{ req, user }}) => {{\n  const tenants = tenantIds(user);\n  const active =\n    user.activeTenant && (typeof user.activeTenant === 'string' ? user.activeTenant : user.activeTenant.id);\n  if (active && tenants.includes(active)) {{\n    return user;\n  }}\n\n  const activeTenant = tenants[0] ?? null;\n  await req.payload.update({{\n    collection: '{users}',\n    id: user.id,\n    data: {{ activeTenant }},\n    req,\n  }});\n  return {{ ...user, activeTenant }};\n}};\n",
+        users = names.users,
+    )
+}
+
+/// The tenants collection, fields, access functions and hook, by path from
+/// the project root.
+pub fn multitenancy_files(
+    options: &MultitenancyOptions,
+) -> Result<BTreeMap<String, String>, String> {
+    let names = names(options)?;
+    Ok(BTreeMap::from([
+        (
+            format!("src/collections/{}.ts", names.tenants),
+            tenants_collection(&names),
+        ),
+        (
+            "src/access/tenants.ts".to_string(),
+            access_functions(&names),
+        ),
+        ("src/fields/tenant.ts".to_string(), tenant_field(&names)),
+        ("src/fields/userTenants.ts".to_string(), user_fields(&names)),
+        (
+            "src/hooks/setActiveTenant.ts".to_string(),
+            after_login_hook(&names),
+        ),
+    ]))
+}
+
+/// Insert `import` after the first line of `code`.
+fn add_import(code: &mut String, import: &str) {
+    let at = code.find('\n').map_or(code.len(), |line_end| line_end + 1);
+    code.insert_str(at, &format!("{import}\n"));
+}
+
+/// Insert `property` after the collection's `slug` line.
+fn add_property(code: &mut String, slug: &str, property: &str) -> Result<(), String> {
+    let slug_line = format!("  slug: '{slug}',");
+    let at = code
+        .find(&slug_line)
+        .ok_or_else(|| format!("No `slug: '{slug}'` line in collection {slug}"))?
+        + slug_line.len();
+    code.insert_str(at, property);
+    Ok(())
+}
+
+fn add_field(code: &mut String, slug: &str, field: &str) -> Result<(), String> {
+    const FIELDS: &str = "  fields: [\n";
+    let at = code
+        .find(FIELDS)
+        .ok_or_else(|| format!("No fields array in collection {slug}"))?
+        + FIELDS.len();
+    code.insert_str(at, &format!("    {field},\n"));
+    Ok(())
+}
+
+/// Scope collection `slug` by tenant: add the tenant field and replace its
+/// access functions.
+fn patch_scoped(code: &mut String, slug: &str) -> Result<(), String> {
+    const SCOPED_ACCESS: &str = "\n  access: {\n    read: tenantRead,\n    create: tenantCreate,\n    update: tenantWrite,\n    delete: tenantWrite,\n  },";
+    if code.contains("tenantField") {
+        return Ok(());
+    }
+    if code.contains(OPEN_ACCESS) {
+        *code = code.replacen(OPEN_ACCESS, SCOPED_ACCESS, 1);
+    } else if code.contains("\n  access: {") {
+        return Err(format!(
+            "Collection {slug} has custom access functions; combine them with tenantRead and tenantWrite by hand"
+        ));
+    } else {
+        add_property(code, slug, SCOPED_ACCESS)?;
+    }
+    add_field(code, slug, "tenantField")?;
+    add_import(
+        code,
+        "import { tenantCreate, tenantRead, tenantWrite } from '../access/tenants';",
+    );
+    add_import(code, "import { tenantField } from '../fields/tenant';");
+    Ok(())
+}
+
+/// Give the users collection its tenant fields and the `afterLogin` hook.
+fn patch_users(code: &mut String, slug: &str) -> Result<(), String> {
+    const HOOKS: &str = "\n  hooks: {\n";
+    if code.contains("userTenantFields") {
+        return Ok(());
+    }
+    match code.find(HOOKS) {
+        Some(at) => code.insert_str(at + HOOKS.len(), "    afterLogin: [setActiveTenant],\n"),
+        None => add_property(
+            code,
+            slug,
+            "\n  hooks: {\n    afterLogin: [setActiveTenant],\n  },",
+        )?,
+    }
+    add_field(code, slug, "...userTenantFields")?;
+    add_import(
+        code,
+        "import { setActiveTenant } from '../hooks/setActiveTenant';",
+    );
+    add_import(
+        code,
+        "import { userTenantFields } from '../fields/userTenants';",
+    );
+    Ok(())
+}
+
+pub(crate) fn directory<'a>(root: &'a mut ScaffoldFileStructure, name: &str) -> &'a mut ScaffoldFileStructure {
+    let entry = root
+        .entry(name.to_string())
+        .or_insert_with(|| ScaffoldFile::Directory(ScaffoldFileStructure::new()));
+    if let ScaffoldFile::File(_) = entry {
+        *entry = ScaffoldFile::Directory(ScaffoldFileStructure::new());
+    }
+    match entry {
+        ScaffoldFile::Directory(dir) => dir,
+        ScaffoldFile::File(_) => unreachable!("replaced by a directory above"),
+    }
+}
+
+/// Add the multi-tenant files to a scaffold and patch its collections. A
+/// users collection is created when the scaffold has none.
+pub fn apply_multitenancy(
+    root: &mut ScaffoldFileStructure,
+    options: &MultitenancyOptions,
+) -> Result<(), String> {
+    let names = names(options)?;
+    let files = multitenancy_files(options)?;
+    let src = directory(root, "src");
+    for (path, code) in files {
+        let mut parts: Vec<&str> = path.split('/').skip(1).collect();
+        let file = parts.pop().unwrap_or_default().to_string();
+        let dir = parts
+            .into_iter()
+            .fold(&mut *src, |dir, name| directory(dir, name));
+        dir.insert(file, ScaffoldFile::File(code));
+    }
+
+    let collections = directory(src, "collections");
+    for slug in &options.collections {
+        match collections.get_mut(&format!("{slug}.ts")) {
+            Some(ScaffoldFile::File(code)) => patch_scoped(code, slug)?,
+            _ => return Err(format!("Collection {slug} is not in the project")),
+        }
+    }
+    let users_file = format!("{}.ts", names.users);
+    if !collections.contains_key(&users_file) {
+        let code = generate_template(
+            TemplateType::Collection,
+            &json!({ "slug": names.users, "auth": true, "fields": [] }),
+        )?;
+        collections.insert(users_file.clone(), ScaffoldFile::File(code));
+    }
+    if let Some(ScaffoldFile::File(code)) = collections.get_mut(&users_file) {
+        patch_users(code, names.users)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_apply_multitenancy_patches_collections() {
+        let posts = generate_template(
+            TemplateType::Collection,
+            &json!({ "slug": "posts", "access": true, "hooks": true, "fields": [] }),
+        )
+        .unwrap();
+        let mut collections = ScaffoldFileStructure::new();
+        collections.insert("posts.ts".to_string(), ScaffoldFile::File(posts));
+        let mut src = ScaffoldFileStructure::new();
+        src.insert(
+            "collections".to_string(),
+            ScaffoldFile::Directory(collections),
+        );
+        let mut root = ScaffoldFileStructure::new();
+        root.insert("src".to_string(), ScaffoldFile::Directory(src));
+
+        let options = MultitenancyOptions {
+            collections: vec!["posts".to_string()],
+            tenants_slug: None,
+            users_slug: None,
+            field_name: None,
+        };
+        apply_multitenancy(&mut root, &options).unwrap();
+
+        let Some(ScaffoldFile::Directory(src)) = root.get("src") else {
+            panic!("src is missing");
+        };
+        let Some(ScaffoldFile::Directory(collections)) = src.get("collections") else {
+            panic!("collections are missing");
+        };
+        let Some(ScaffoldFile::File(posts)) = collections.get("posts.ts") else {
+            panic!("posts is missing");
+        };
+        assert!(posts.contains("read: tenantRead,"));
+        assert!(posts.contains("fields: [\n    tenantField,\n"));
+        assert!(!posts.contains("read: () => true"));
+        let Some(ScaffoldFile::File(users)) = collections.get("users.ts") else {
+            panic!("users collection was not created");
+        };
+        assert!(users.contains("afterLogin: [setActiveTenant],"));
+        assert!(collections.contains_key("tenants.ts"));
+        assert!(
+            matches!(src.get("fields"), Some(ScaffoldFile::Directory(fields)) if fields.contains_key("tenant.ts"))
+        );
+
+        let scoped_users = MultitenancyOptions {
+            collections: vec!["users".to_string()],
+            ..options
+        };
+        assert!(multitenancy_files(&scoped_users).is_err());
+    }
+}
@@ -0,0 +1,472 @@
+//! Naming profiles: the case of slugs, field names and block slugs, and
+//! whether collection slugs are plural. The validator reports names that
+//! break the active profile and the generator rewrites names to fit it.
+//!
+//! Built-in profiles are `none` (the default, no rules), `payload`
+//! (kebab-case slugs, camelCase fields, PascalCase blocks, plural
+//! collections) and `snake`. Settings can define more under
+//! `naming_profiles` and pick the default with `naming_profile`; tools
+//! accept `naming_profile` per call.
+
+use std::{collections::BTreeMap, str::FromStr, sync::OnceLock};
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+use crate::payload_tools::{generator::TemplateType, types::FileType};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum Case {
+    /// `blogPost`
+    Camel,
+    /// `BlogPost`
+    Pascal,
+    /// `blog-post`
+    Kebab,
+    /// `blog_post`
+    Snake,
+}
+
+impl Case {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Case::Camel => "camelCase",
+            Case::Pascal => "PascalCase",
+            Case::Kebab => "kebab-case",
+            Case::Snake => "snake_case",
+        }
+    }
+
+    pub fn convert(&self, name: &str) -> String {
+        let words = words(name);
+        match self {
+            Case::Camel => words
+                .iter()
+                .enumerate()
+                .map(|(i, word)| if i == 0 { word.to_lowercase() } else { capitalize(word) })
+                .collect(),
+            Case::Pascal => words.iter().map(|word| capitalize(word)).collect(),
+            Case::Kebab => lower_words(&words).join("-"),
+            Case::Snake => lower_words(&words).join("_"),
+        }
+    }
+
+    pub fn matches(&self, name: &str) -> bool {
+        self.convert(name) == name
+    }
+}
+
+/// Words of a name in any case: split on separators, lower-to-upper
+/// changes, and the end of an acronym (`HTTPServer` is `HTTP`, `Server`).
+fn words(name: &str) -> Vec<String> {
+    let chars: Vec<char> = name.chars().collect();
+    let mut words = Vec::new();
+    let mut word = String::new();
+    for (i, &c) in chars.iter().enumerate() {
+        if !c.is_ascii_alphanumeric() {
+            if !word.is_empty() {
+                words.push(std::mem::take(&mut word));
+            }
+            continue;
+        }
+        if c.is_ascii_uppercase() && !word.is_empty() {
+            let prev = chars[i - 1];
+            let next_lower = chars.get(i + 1).is_some_and(char::is_ascii_lowercase);
+            if !prev.is_ascii_uppercase() || next_lower {
+                words.push(std::mem::take(&mut word));
+            }
+        }
+        word.push(c);
+    }
+    if !word.is_empty() {
+        words.push(word);
+    }
+    words
+}
+
+fn lower_words(words: &[String]) -> Vec<String> {
+    words.iter().map(|word| word.to_lowercase()).collect()
+}
+
+fn capitalize(word: &str) -> String {
+    let mut chars = word.chars();
+    match chars.next() {
+        Some(first) => first.to_ascii_uppercase().to_string() + &chars.as_str().to_lowercase(),
+        None => String::new(),
+    }
+}
+
+/// Words that read as plural as they are.
+const UNCOUNTABLE: [&str; 10] = [
+    "media", "data", "metadata", "news", "information", "content", "feedback", "equipment", "series", "staff",
+];
+const IRREGULAR: [(&str, &str); 4] = [
+    ("person", "people"),
+    ("child", "children"),
+    ("man", "men"),
+    ("woman", "women"),
+];
+
+/// Whether the last word of `name` is plural.
+pub fn is_plural(name: &str) -> bool {
+    let Some(last) = words(name).pop().map(|word| word.to_lowercase()) else {
+        return true;
+    };
+    if UNCOUNTABLE.contains(&last.as_str()) || IRREGULAR.iter().any(|(_, plural)| *plural == last) {
+        return true;
+    }
+    last.ends_with('s') && !["ss", "us", "is"].iter().any(|end| last.ends_with(end))
+}
+
+/// `name` with its last word made plural, keeping its separators and case.
+pub fn pluralize(name: &str) -> String {
+    if is_plural(name) {
+        return name.to_string();
+    }
+    let lower = name.to_lowercase();
+    let last = words(name).pop().unwrap_or_default().to_lowercase();
+    if let Some((singular, plural)) = IRREGULAR.iter().find(|(singular, _)| *singular == last) {
+        let stem = &name[..name.len() - singular.len()];
+        let upper = name[stem.len()..].starts_with(|c: char| c.is_ascii_uppercase());
+        return stem.to_string() + &if upper { capitalize(plural) } else { plural.to_string() };
+    }
+    let consonant_y = lower.ends_with('y')
+        && !lower[..lower.len() - 1].ends_with(['a', 'e', 'i', 'o', 'u']);
+    if consonant_y {
+        format!("{}ies", &name[..name.len() - 1])
+    } else if ["s", "x", "z", "ch", "sh"].iter().any(|end| lower.ends_with(end)) {
+        format!("{name}es")
+    } else {
+        format!("{name}s")
+    }
+}
+
+/// `name` with its last word made singular, keeping its separators and case.
+pub fn singularize(name: &str) -> String {
+    if !is_plural(name) {
+        return name.to_string();
+    }
+    let lower = name.to_lowercase();
+    let last = words(name).pop().unwrap_or_default().to_lowercase();
+    if UNCOUNTABLE.contains(&last.as_str()) {
+        return name.to_string();
+    }
+    if let Some((singular, plural)) = IRREGULAR.iter().find(|(_, plural)| *plural == last) {
+        let stem = &name[..name.len() - plural.len()];
+        let upper = name[stem.len()..].starts_with(|c: char| c.is_ascii_uppercase());
+        return stem.to_string() + &if upper { capitalize(singular) } else { singular.to_string() };
+    }
+    if lower.ends_with("ies") && lower.len() > 4 {
+        format!("{}y", &name[..name.len() - 3])
+    } else if ["sses", "xes", "zes", "ches", "shes"].iter().any(|end| lower.ends_with(end)) {
+        name[..name.len() - 2].to_string()
+    } else {
+        name[..name.len() - 1].to_string()
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct NamingProfile {
+    /// Case of collection and global slugs
+    pub slugs: Option<Case>,
+    /// Case of field and named tab names
+    pub fields: Option<Case>,
+    /// Case of block slugs
+    pub blocks: Option<Case>,
+    /// Collection slugs name their documents in the plural, e.g. `posts`
+    #[serde(default)]
+    pub plural_collections: bool,
+}
+
+/// Custom profiles by name, as kept in settings and `MCP_NAMING_PROFILES`.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(transparent)]
+pub struct NamingProfiles(BTreeMap<String, NamingProfile>);
+
+impl FromStr for NamingProfiles {
+    type Err = String;
+
+    /// Parse the JSON form used by `MCP_NAMING_PROFILES`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        serde_json::from_str(s).map_err(|err| format!("invalid naming profiles: {err}"))
+    }
+}
+
+impl NamingProfiles {
+    /// Whether `name` is a built-in or custom profile.
+    pub fn knows(&self, name: &str) -> bool {
+        self.0.contains_key(name) || builtin(name).is_some()
+    }
+}
+
+pub const BUILTIN_PROFILES: [&str; 3] = ["none", "payload", "snake"];
+
+fn builtin(name: &str) -> Option<NamingProfile> {
+    match name {
+        "none" => Some(NamingProfile::default()),
+        "payload" => Some(NamingProfile {
+            slugs: Some(Case::Kebab),
+            fields: Some(Case::Camel),
+            blocks: Some(Case::Pascal),
+            plural_collections: true,
+        }),
+        "snake" => Some(NamingProfile {
+            slugs: Some(Case::Snake),
+            fields: Some(Case::Snake),
+            blocks: Some(Case::Pascal),
+            plural_collections: true,
+        }),
+        _ => None,
+    }
+}
+
+static SETTINGS: OnceLock<(String, NamingProfiles)> = OnceLock::new();
+
+/// Set the default profile and the custom ones; the first call wins.
+pub fn configure(default: &str, custom: &NamingProfiles) {
+    let _ = SETTINGS.set((default.to_string(), custom.clone()));
+}
+
+fn settings() -> &'static (String, NamingProfiles) {
+    SETTINGS.get_or_init(|| ("none".to_string(), NamingProfiles::default()))
+}
+
+/// The profile called `name`, custom profiles first, or the configured
+/// default without a name.
+pub fn profile(name: Option<&str>) -> Result<NamingProfile, String> {
+    let (default, custom) = settings();
+    let name = name.unwrap_or(default.as_str());
+    custom
+        .0
+        .get(name)
+        .cloned()
+        .or_else(|| builtin(name))
+        .ok_or_else(|| {
+            format!(
+                "Unknown naming profile \"{name}\"; expected one of {}",
+                profile_names().join(", ")
+            )
+        })
+}
+
+/// Built-in profiles followed by the custom ones.
+pub fn profile_names() -> Vec<String> {
+    let (_, custom) = settings();
+    BUILTIN_PROFILES
+        .iter()
+        .map(|name| name.to_string())
+        .chain(custom.0.keys().filter(|name| builtin(name).is_none()).cloned())
+        .collect()
+}
+
+/// The configured default profile; an unknown name applies no rules.
+pub fn active() -> NamingProfile {
+    profile(None).unwrap_or_default()
+}
+
+/// Names the validator and the generator visit in a fields array.
+#[derive(Clone, Copy)]
+enum Name {
+    Field,
+    Block,
+}
+
+impl NamingProfile {
+    fn case(&self, name: Name) -> Option<Case> {
+        match name {
+            Name::Field => self.fields,
+            Name::Block => self.blocks,
+        }
+    }
+
+    /// Names in `object` that break the profile, as validation errors.
+    pub fn check(&self, object: &Value, file_type: FileType) -> Vec<String> {
+        let mut errors = Vec::new();
+        let slug = object.get("slug").and_then(Value::as_str);
+        match (file_type, slug) {
+            (FileType::Collection, Some(slug)) => {
+                self.check_name(slug, self.slugs, "Collection slug", &mut errors);
+                if self.plural_collections && !is_plural(slug) {
+                    errors.push(format!(
+                        "Collection slug \"{slug}\" should be plural: \"{}\".",
+                        pluralize(slug)
+                    ));
+                }
+            }
+            (FileType::Global, Some(slug)) => self.check_name(slug, self.slugs, "Global slug", &mut errors),
+            // A field file without a type is a block.
+            (FileType::Field, Some(slug)) if object.get("type").is_none() => {
+                self.check_name(slug, self.blocks, "Block slug", &mut errors)
+            }
+            (FileType::Field, _) => {
+                self.check_fields(std::slice::from_ref(object), &mut errors);
+                return errors;
+            }
+            _ => {}
+        }
+        if let Some(fields) = object.get("fields").and_then(Value::as_array) {
+            self.check_fields(fields, &mut errors);
+        }
+        errors
+    }
+
+    fn check_name(&self, name: &str, case: Option<Case>, what: &str, errors: &mut Vec<String>) {
+        let Some(case) = case else {
+            return;
+        };
+        if !case.matches(name) {
+            errors.push(format!(
+                "{what} \"{name}\" should be {}: \"{}\".",
+                case.as_str(),
+                case.convert(name)
+            ));
+        }
+    }
+
+    fn check_fields(&self, fields: &[Value], errors: &mut Vec<String>) {
+        for field in fields {
+            if let Some(name) = field.get("name").and_then(Value::as_str) {
+                self.check_name(name, self.case(Name::Field), "Field name", errors);
+            }
+            for tab in field.get("tabs").and_then(Value::as_array).into_iter().flatten() {
+                if let Some(name) = tab.get("name").and_then(Value::as_str) {
+                    self.check_name(name, self.case(Name::Field), "Tab name", errors);
+                }
+                self.check_fields(tab.get("fields").and_then(Value::as_array).map_or(&[], Vec::as_slice), errors);
+            }
+            for block in field.get("blocks").and_then(Value::as_array).into_iter().flatten() {
+                if let Some(slug) = block.get("slug").and_then(Value::as_str) {
+                    self.check_name(slug, self.case(Name::Block), "Block slug", errors);
+                }
+                self.check_fields(block.get("fields").and_then(Value::as_array).map_or(&[], Vec::as_slice), errors);
+            }
+            if let Some(fields) = field.get("fields").and_then(Value::as_array) {
+                self.check_fields(fields, errors);
+            }
+        }
+    }
+
+    /// Template `options` with their names rewritten to fit the profile.
+    pub fn apply(&self, template_type: TemplateType, options: &Value) -> Value {
+        let mut options = options.clone();
+        let Some(map) = options.as_object_mut() else {
+            return options;
+        };
+        let rename = |map: &mut Map<String, Value>, key: &str, case: Option<Case>, plural: bool| {
+            if let Some(Value::String(name)) = map.get_mut(key) {
+                if let Some(case) = case {
+                    *name = case.convert(name);
+                }
+                if plural {
+                    *name = pluralize(name);
+                }
+            }
+        };
+        match template_type {
+            TemplateType::Collection => rename(map, "slug", self.slugs, self.plural_collections),
+            TemplateType::Global => rename(map, "slug", self.slugs, false),
+            TemplateType::Block => rename(map, "name", self.blocks, false),
+            TemplateType::Field => {
+                self.apply_field(map);
+                return options;
+            }
+            _ => return options,
+        }
+        if let Some(Value::Array(fields)) = map.get_mut("fields") {
+            self.apply_fields(fields);
+        }
+        options
+    }
+
+    fn apply_fields(&self, fields: &mut [Value]) {
+        for field in fields {
+            if let Some(field) = field.as_object_mut() {
+                self.apply_field(field);
+            }
+        }
+    }
+
+    fn apply_field(&self, field: &mut Map<String, Value>) {
+        let convert = |value: Option<&mut Value>, case: Option<Case>| {
+            if let (Some(Value::String(name)), Some(case)) = (value, case) {
+                *name = case.convert(name);
+            }
+        };
+        convert(field.get_mut("name"), self.fields);
+        for (key, name_key, case) in [("tabs", "name", self.fields), ("blocks", "slug", self.blocks)] {
+            for item in field.get_mut(key).and_then(Value::as_array_mut).into_iter().flatten() {
+                let Some(item) = item.as_object_mut() else {
+                    continue;
+                };
+                convert(item.get_mut(name_key), case);
+                if let Some(Value::Array(fields)) = item.get_mut("fields") {
+                    self.apply_fields(fields);
+                }
+            }
+        }
+        if let Some(Value::Array(fields)) = field.get_mut("fields") {
+            self.apply_fields(fields);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn converts_between_cases() {
+        assert_eq!(Case::Kebab.convert("blogPosts"), "blog-posts");
+        assert_eq!(Case::Camel.convert("hero_image"), "heroImage");
+        assert_eq!(Case::Pascal.convert("call-to-action"), "CallToAction");
+        assert_eq!(Case::Snake.convert("HTTPServer"), "http_server");
+        assert!(Case::Camel.matches("seoTitle"));
+        assert!(!Case::Kebab.matches("blog_posts"));
+
+        assert_eq!(pluralize("category"), "categories");
+        assert_eq!(pluralize("blog-post"), "blog-posts");
+        assert_eq!(pluralize("address"), "addresses");
+        assert_eq!(pluralize("sales-person"), "sales-people");
+        assert_eq!(pluralize("media"), "media");
+        assert!(is_plural("posts") && !is_plural("status"));
+        assert_eq!(singularize("categories"), "category");
+        assert_eq!(singularize("blog-posts"), "blog-post");
+        assert_eq!(singularize("addresses"), "address");
+        assert_eq!(singularize("salesPeople"), "salesPerson");
+        assert_eq!(singularize("media"), "media");
+    }
+
+    #[test]
+    fn checks_and_applies_a_profile() {
+        let payload = profile(Some("payload")).unwrap();
+        let collection = json!({
+            "slug": "blog_post",
+            "fields": [
+                { "name": "hero_image", "type": "upload" },
+                { "name": "layout", "type": "blocks", "blocks": [{ "slug": "call-to-action", "fields": [] }] }
+            ]
+        });
+        let errors = payload.check(&collection, FileType::Collection);
+        assert_eq!(
+            errors,
+            vec![
+                "Collection slug \"blog_post\" should be kebab-case: \"blog-post\".",
+                "Collection slug \"blog_post\" should be plural: \"blog_posts\".",
+                "Field name \"hero_image\" should be camelCase: \"heroImage\".",
+                "Block slug \"call-to-action\" should be PascalCase: \"CallToAction\".",
+            ]
+        );
+        assert!(profile(None).unwrap().check(&collection, FileType::Collection).is_empty());
+        assert!(profile(Some("unknown")).is_err());
+
+        let applied = payload.apply(TemplateType::Collection, &collection);
+        assert_eq!(applied["slug"], "blog-posts");
+        assert_eq!(applied["fields"][0]["name"], "heroImage");
+        assert_eq!(applied["fields"][1]["blocks"][0]["slug"], "CallToAction");
+        assert!(payload.check(&applied, FileType::Collection).is_empty());
+    }
+}
@@ -0,0 +1,483 @@
+//! OpenAPI 3.1 description of the REST API Payload serves for a schema:
+//! CRUD routes per collection, the auth routes of auth collections, and
+//! read/update routes per global.
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value, json};
+
+use crate::payload_tools::{
+    json_schema::{SchemaRefs, document_schema, input_schema, object_schema, schema_name},
+    schema_model::{SchemaCollection, SchemaModel},
+};
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct OpenApiOptions {
+    /// Defaults to "Payload CMS API"
+    pub title: Option<String>,
+    /// Version of the API described, defaults to "1.0.0"
+    pub version: Option<String>,
+    /// Defaults to http://localhost:3000
+    pub server_url: Option<String>,
+    /// Payload's `routes.api`, defaults to "/api"
+    pub api_route: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct OpenApiOutput {
+    /// The OpenAPI 3.1 document
+    pub document: Value,
+    pub paths: usize,
+}
+
+fn component(name: &str) -> Value {
+    json!({ "$ref": format!("#/components/schemas/{name}") })
+}
+
+/// Definitions live under `#/components/schemas`; blocks stay inline.
+struct Components;
+
+impl SchemaRefs for Components {
+    fn id(&self) -> Value {
+        component("ID")
+    }
+
+    fn document(&self, slug: &str) -> Value {
+        component(&schema_name(slug))
+    }
+}
+
+fn paginated(name: &str) -> Value {
+    json!({
+        "type": "object",
+        "properties": {
+            "docs": { "type": "array", "items": component(name) },
+            "totalDocs": { "type": "integer" },
+            "limit": { "type": "integer" },
+            "totalPages": { "type": "integer" },
+            "page": { "type": "integer" },
+            "pagingCounter": { "type": "integer" },
+            "hasPrevPage": { "type": "boolean" },
+            "hasNextPage": { "type": "boolean" },
+            "prevPage": { "type": ["integer", "null"] },
+            "nextPage": { "type": ["integer", "null"] },
+        },
+        "required": ["docs", "totalDocs", "limit", "totalPages", "page", "hasPrevPage", "hasNextPage"],
+    })
+}
+
+fn parameters(names: &[&str]) -> Value {
+    Value::Array(
+        names
+            .iter()
+            .map(|name| json!({ "$ref": format!("#/components/parameters/{name}") }))
+            .collect(),
+    )
+}
+
+fn body(schema: Value) -> Value {
+    json!({ "required": true, "content": { "application/json": { "schema": schema } } })
+}
+
+fn response(description: &str, schema: Value) -> Value {
+    json!({ "description": description, "content": { "application/json": { "schema": schema } } })
+}
+
+fn message_doc(name: &str) -> Value {
+    json!({
+        "type": "object",
+        "properties": { "message": { "type": "string" }, "doc": component(name) },
+    })
+}
+
+fn operation(tag: &str, id: String, summary: String, mut responses: Value) -> Value {
+    for (status, name) in [
+        ("400", "BadRequest"),
+        ("401", "Unauthorized"),
+        ("403", "Forbidden"),
+    ] {
+        responses[status] = json!({ "$ref": format!("#/components/responses/{name}") });
+    }
+    json!({ "tags": [tag], "operationId": id, "summary": summary, "responses": responses })
+}
+
+fn collection_paths(collection: &SchemaCollection, paths: &mut Map<String, Value>) {
+    let slug = &collection.slug;
+    let name = schema_name(slug);
+    let input = format!("{name}Input");
+    let list = format!("{name}List");
+    let by_id =
+        json!([{ "name": "id", "in": "path", "required": true, "schema": component("ID") }]);
+
+    let mut find = operation(
+        slug,
+        format!("find{name}"),
+        format!("Find {slug} documents"),
+        json!({ "200": response("Paginated documents", component(&list)) }),
+    );
+    find["parameters"] =
+        parameters(&["where", "limit", "page", "sort", "depth", "locale", "draft"]);
+    let mut create = operation(
+        slug,
+        format!("create{name}"),
+        format!("Create a {slug} document"),
+        json!({ "201": response("Created document", message_doc(&name)) }),
+    );
+    create["requestBody"] = body(component(&input));
+    create["parameters"] = parameters(&["depth", "locale", "draft"]);
+    let mut update_many = operation(
+        slug,
+        format!("update{name}"),
+        format!("Update the {slug} documents matching `where`"),
+        json!({ "200": response("Updated documents", json!({ "type": "object", "properties": { "docs": { "type": "array", "items": component(&name) } } })) }),
+    );
+    update_many["requestBody"] = body(component(&input));
+    update_many["parameters"] = parameters(&["where", "depth", "locale", "draft"]);
+    let mut delete_many = operation(
+        slug,
+        format!("delete{name}"),
+        format!("Delete the {slug} documents matching `where`"),
+        json!({ "200": response("Deleted documents", json!({ "type": "object", "properties": { "docs": { "type": "array", "items": component(&name) } } })) }),
+    );
+    delete_many["parameters"] = parameters(&["where"]);
+    paths.insert(
+        format!("/{slug}"),
+        json!({ "get": find, "post": create, "patch": update_many, "delete": delete_many }),
+    );
+
+    let mut find_by_id = operation(
+        slug,
+        format!("find{name}ById"),
+        format!("Get a {slug} document"),
+        json!({ "200": response("The document", component(&name)), "404": { "$ref": "#/components/responses/NotFound" } }),
+    );
+    find_by_id["parameters"] = by_id.clone();
+    extend(
+        &mut find_by_id["parameters"],
+        parameters(&["depth", "locale", "draft"]),
+    );
+    let mut update = operation(
+        slug,
+        format!("update{name}ById"),
+        format!("Update a {slug} document"),
+        json!({ "200": response("Updated document", message_doc(&name)), "404": { "$ref": "#/components/responses/NotFound" } }),
+    );
+    update["parameters"] = by_id.clone();
+    extend(
+        &mut update["parameters"],
+        parameters(&["depth", "locale", "draft"]),
+    );
+    update["requestBody"] = body(component(&input));
+    let mut delete = operation(
+        slug,
+        format!("delete{name}ById"),
+        format!("Delete a {slug} document"),
+        json!({ "200": response("Deleted document", message_doc(&name)), "404": { "$ref": "#/components/responses/NotFound" } }),
+    );
+    delete["parameters"] = by_id;
+    paths.insert(
+        format!("/{slug}/{{id}}"),
+        json!({ "get": find_by_id, "patch": update, "delete": delete }),
+    );
+
+    if collection.auth {
+        auth_paths(collection, &name, paths);
+    }
+}
+
+fn auth_paths(collection: &SchemaCollection, name: &str, paths: &mut Map<String, Value>) {
+    let slug = &collection.slug;
+    let credentials = json!({
+        "type": "object",
+        "properties": { "email": { "type": "string", "format": "email" }, "password": { "type": "string" } },
+        "required": ["email", "password"],
+    });
+    let token = json!({
+        "type": "object",
+        "properties": {
+            "message": { "type": "string" },
+            "user": component(name),
+            "token": { "type": "string" },
+            "exp": { "type": "integer" },
+        },
+    });
+    let message = json!({ "type": "object", "properties": { "message": { "type": "string" } } });
+    let email = json!({
+        "type": "object",
+        "properties": { "email": { "type": "string", "format": "email" } },
+        "required": ["email"],
+    });
+    let routes = [
+        ("login", "login", "Log in", Some(credentials), token.clone()),
+        ("logout", "logout", "Log out", None, message.clone()),
+        (
+            "refresh-token",
+            "refreshToken",
+            "Refresh the session token",
+            None,
+            token.clone(),
+        ),
+        (
+            "forgot-password",
+            "forgotPassword",
+            "Email a password reset link",
+            Some(email.clone()),
+            message.clone(),
+        ),
+        (
+            "reset-password",
+            "resetPassword",
+            "Reset a password with a reset token",
+            Some(json!({
+                "type": "object",
+                "properties": { "token": { "type": "string" }, "password": { "type": "string" } },
+                "required": ["token", "password"],
+            })),
+            token,
+        ),
+        (
+            "unlock",
+            "unlock",
+            "Unlock a locked account",
+            Some(email),
+            message.clone(),
+        ),
+    ];
+    for (route, verb, summary, request, result) in routes {
+        let mut post = operation(
+            slug,
+            format!("{verb}{name}"),
+            format!("{summary} ({slug})"),
+            json!({ "200": response(summary, result) }),
+        );
+        if let Some(request) = request {
+            post["requestBody"] = body(request);
+        }
+        if route == "login" || route == "forgot-password" || route == "reset-password" {
+            post["security"] = json!([]);
+        }
+        paths.insert(format!("/{slug}/{route}"), json!({ "post": post }));
+    }
+    let me = operation(
+        slug,
+        format!("me{name}"),
+        format!("The logged-in {slug} user"),
+        json!({ "200": response("Current user", json!({
+            "type": "object",
+            "properties": { "user": { "oneOf": [component(name), { "type": "null" }] }, "exp": { "type": "integer" } },
+        })) }),
+    );
+    paths.insert(format!("/{slug}/me"), json!({ "get": me }));
+    let mut verify = operation(
+        slug,
+        format!("verify{name}"),
+        format!("Verify a {slug} email address"),
+        json!({ "200": response("Verified", message) }),
+    );
+    verify["parameters"] = json!([{ "name": "token", "in": "path", "required": true, "schema": { "type": "string" } }]);
+    verify["security"] = json!([]);
+    paths.insert(
+        format!("/{slug}/verify/{{token}}"),
+        json!({ "post": verify }),
+    );
+}
+
+fn extend(target: &mut Value, items: Value) {
+    if let (Some(target), Value::Array(items)) = (target.as_array_mut(), items) {
+        target.extend(items);
+    }
+}
+
+pub fn openapi_document(schema: &SchemaModel, options: &OpenApiOptions) -> OpenApiOutput {
+    let server_url = options
+        .server_url
+        .as_deref()
+        .unwrap_or("http://localhost:3000")
+        .trim_end_matches('/');
+    let api_route = options
+        .api_route
+        .as_deref()
+        .unwrap_or("/api")
+        .trim_end_matches('/');
+
+    let mut schemas = Map::new();
+    schemas.insert(
+        "ID".to_string(),
+        json!({ "type": ["string", "integer"], "description": "String IDs on MongoDB, integers on SQL databases" }),
+    );
+    schemas.insert(
+        "Errors".to_string(),
+        json!({
+            "type": "object",
+            "properties": {
+                "errors": {
+                    "type": "array",
+                    "items": { "type": "object", "properties": { "message": { "type": "string" } } },
+                },
+            },
+        }),
+    );
+    let mut paths = Map::new();
+    let mut tags = Vec::new();
+    for collection in &schema.collections {
+        let name = schema_name(&collection.slug);
+        schemas.insert(name.clone(), document_schema(collection, &mut Components));
+        schemas.insert(
+            format!("{name}Input"),
+            input_schema(collection, &mut Components),
+        );
+        schemas.insert(format!("{name}List"), paginated(&name));
+        tags.push(json!({ "name": collection.slug, "description": format!("{} collection", collection.slug) }));
+        collection_paths(collection, &mut paths);
+    }
+    for global in &schema.globals {
+        let name = schema_name(&global.slug);
+        let mut document = object_schema(&global.fields, false, false, &mut Components);
+        document["properties"]["globalType"] = json!({ "const": global.slug });
+        schemas.insert(name.clone(), document);
+        schemas.insert(
+            format!("{name}Input"),
+            object_schema(&global.fields, true, false, &mut Components),
+        );
+        tags.push(json!({ "name": global.slug, "description": format!("{} global", global.slug) }));
+
+        let mut get = operation(
+            &global.slug,
+            format!("get{name}"),
+            format!("Get the {} global", global.slug),
+            json!({ "200": response("The global", component(&name)) }),
+        );
+        get["parameters"] = parameters(&["depth", "locale", "draft"]);
+        let mut update = operation(
+            &global.slug,
+            format!("update{name}"),
+            format!("Update the {} global", global.slug),
+            json!({ "200": response("Updated global", message_doc(&name)) }),
+        );
+        update["parameters"] = parameters(&["depth", "locale", "draft"]);
+        update["requestBody"] = body(component(&format!("{name}Input")));
+        paths.insert(
+            format!("/globals/{}", global.slug),
+            json!({ "get": get, "post": update }),
+        );
+    }
+
+    let query_parameter = |name: &str, schema: Value, description: &str| json!({ "name": name, "in": "query", "required": false, "schema": schema, "description": description });
+    let error = |description: &str| response(description, component("Errors"));
+    let count = paths.len();
+    let document = json!({
+        "openapi": "3.1.0",
+        "info": {
+            "title": options.title.as_deref().unwrap_or("Payload CMS API"),
+            "version": options.version.as_deref().unwrap_or("1.0.0"),
+        },
+        "servers": [{ "url": format!("{server_url}{api_route}") }],
+        "tags": tags,
+        "security": [{ "jwt": [] }, { "cookie": [] }, { "apiKey": [] }],
+        "paths": paths,
+        "components": {
+            "schemas": schemas,
+            "parameters": {
+                "where": {
+                    "name": "where",
+                    "in": "query",
+                    "required": false,
+                    "style": "deepObject",
+                    "explode": true,
+                    "schema": { "type": "object" },
+                    "description": "Query constraints, e.g. where[status][equals]=published",
+                },
+                "limit": query_parameter("limit", json!({ "type": "integer", "default": 10 }), "Documents per page"),
+                "page": query_parameter("page", json!({ "type": "integer", "default": 1 }), "Page to return"),
+                "sort": query_parameter("sort", json!({ "type": "string" }), "Field to sort by; prefix with - for descending"),
+                "depth": query_parameter("depth", json!({ "type": "integer", "default": 2 }), "How deep to populate relationships"),
+                "locale": query_parameter("locale", json!({ "type": "string" }), "Locale of localized fields"),
+                "draft": query_parameter("draft", json!({ "type": "boolean" }), "Read or write the draft version"),
+            },
+            "responses": {
+                "BadRequest": error("The request failed validation"),
+                "Unauthorized": error("No valid credentials were given"),
+                "Forbidden": error("Access control denied the request"),
+                "NotFound": error("No document with this ID"),
+            },
+            "securitySchemes": {
+                "jwt": {
+                    "type": "http",
+                    "scheme": "bearer",
+                    "bearerFormat": "JWT",
+                    "description": "Token from a login route; Payload also accepts `Authorization: JWT <token>`",
+                },
+                "cookie": { "type": "apiKey", "in": "cookie", "name": "payload-token" },
+                "apiKey": {
+                    "type": "apiKey",
+                    "in": "header",
+                    "name": "Authorization",
+                    "description": "`<collection> API-Key <key>` for auth collections with useAPIKey",
+                },
+            },
+        },
+    });
+    OpenApiOutput {
+        document,
+        paths: count,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::payload_tools::schema_model::{SchemaField, SchemaGlobal};
+
+    #[test]
+    fn describes_collections_auth_and_globals() {
+        let mut author = SchemaField::new("author", "relationship");
+        author.relation_to = Some(vec!["users".to_string()]);
+        let schema = SchemaModel {
+            collections: vec![
+                SchemaCollection {
+                    slug: "blog-posts".to_string(),
+                    fields: vec![
+                        SchemaField {
+                            required: true,
+                            ..SchemaField::new("title", "text")
+                        },
+                        author,
+                    ],
+                    timestamps: true,
+                    ..Default::default()
+                },
+                SchemaCollection {
+                    slug: "users".to_string(),
+                    auth: true,
+                    ..Default::default()
+                },
+            ],
+            globals: vec![SchemaGlobal {
+                slug: "header".to_string(),
+                fields: vec![SchemaField::new("nav", "json")],
+            }],
+        };
+        let output = openapi_document(&schema, &OpenApiOptions::default());
+        let document = &output.document;
+        assert_eq!(document["openapi"], "3.1.0");
+        assert_eq!(document["servers"][0]["url"], "http://localhost:3000/api");
+        assert!(document["paths"]["/blog-posts/{id}"]["patch"].is_object());
+        assert!(document["paths"]["/users/login"]["post"]["requestBody"].is_object());
+        assert!(document["paths"]["/globals/header"]["get"].is_object());
+        assert!(document["paths"].get("/blog-posts/login").is_none());
+
+        let posts = &document["components"]["schemas"]["BlogPosts"];
+        assert_eq!(posts["properties"]["title"]["type"], "string");
+        assert_eq!(
+            posts["properties"]["author"]["oneOf"][1]["$ref"],
+            "#/components/schemas/Users"
+        );
+        let input = &document["components"]["schemas"]["BlogPostsInput"];
+        assert_eq!(
+            input["properties"]["author"]["$ref"],
+            "#/components/schemas/ID"
+        );
+        assert_eq!(input["required"], json!(["title"]));
+    }
+}
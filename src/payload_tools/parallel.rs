@@ -0,0 +1,56 @@
+//! Bounded parallel map for CPU-bound work over many files, such as
+//! validating every collection of a large project.
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+
+/// Worker count used when a limit of 0 is configured: one per CPU.
+pub fn default_jobs() -> usize {
+    thread::available_parallelism().map_or(1, |n| n.get())
+}
+
+/// Apply `f` to every item on up to `jobs` threads (0 means
+/// [`default_jobs`]). Results come back in input order, so output does not
+/// depend on scheduling.
+pub fn map<T: Sync, R: Send>(items: &[T], jobs: usize, f: impl Fn(&T) -> R + Sync) -> Vec<R> {
+    let jobs = if jobs == 0 { default_jobs() } else { jobs }.min(items.len());
+    if jobs <= 1 {
+        return items.iter().map(f).collect();
+    }
+    let next = AtomicUsize::new(0);
+    let mut indexed: Vec<(usize, R)> = thread::scope(|scope| {
+        let workers: Vec<_> = (0..jobs)
+            .map(|_| {
+                scope.spawn(|| {
+                    let mut done = Vec::new();
+                    loop {
+                        let index = next.fetch_add(1, Ordering::Relaxed);
+                        let Some(item) = items.get(index) else {
+                            return done;
+                        };
+                        done.push((index, f(item)));
+                    }
+                })
+            })
+            .collect();
+        workers
+            .into_iter()
+            .flat_map(|worker| worker.join().unwrap_or_else(|panic| std::panic::resume_unwind(panic)))
+            .collect()
+    });
+    indexed.sort_unstable_by_key(|(index, _)| *index);
+    indexed.into_iter().map(|(_, result)| result).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_map_keeps_input_order() {
+        let items: Vec<u64> = (0..500).collect();
+        let squares = map(&items, 4, |n| n * n);
+        assert_eq!(squares, items.iter().map(|n| n * n).collect::<Vec<_>>());
+        assert_eq!(map(&items[..0], 0, |n| *n), Vec::<u64>::new());
+    }
+}
@@ -0,0 +1,888 @@
+//! Reconstructs a [`SchemaModel`] from a generated `payload-types.ts`.
+//!
+//! The generated file only carries TypeScript shapes, so some field types
+//! collapse together: `text`, `textarea`, `code` and `date` are all
+//! `string`, and `radio` looks like `select`. Those are reported as the
+//! most common type, with a warning when a name suggests otherwise.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::payload_tools::schema_model::{
+    SchemaBlock, SchemaCollection, SchemaField, SchemaGlobal, SchemaModel,
+};
+
+/// Collections Payload adds on its own
+const INTERNAL_COLLECTIONS: [&str; 4] = [
+    "payload-preferences",
+    "payload-migrations",
+    "payload-locked-documents",
+    "payload-jobs",
+];
+
+const AUTH_FIELDS: [&str; 14] = [
+    "email",
+    "resetPasswordToken",
+    "resetPasswordExpiration",
+    "salt",
+    "hash",
+    "loginAttempts",
+    "lockUntil",
+    "password",
+    "_verified",
+    "_verificationToken",
+    "enableAPIKey",
+    "apiKey",
+    "apiKeyIndex",
+    "sessions",
+];
+
+const UPLOAD_FIELDS: [&str; 10] = [
+    "url",
+    "thumbnailURL",
+    "filename",
+    "mimeType",
+    "filesize",
+    "width",
+    "height",
+    "focalX",
+    "focalY",
+    "sizes",
+];
+
+/// Nesting limit when following interface references
+const MAX_DEPTH: usize = 16;
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct PayloadTypesSchema {
+    pub schema: SchemaModel,
+    /// Schema validation errors for the reconstructed model
+    pub errors: Vec<String>,
+    pub warnings: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Ident(String),
+    Str(String),
+    Num(String),
+    Punct(char),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum TsType {
+    Keyword(String),
+    Literal(String),
+    Ref(String),
+    Array(Box<TsType>),
+    Tuple(Vec<TsType>),
+    Object { props: Vec<TsProp>, index: bool },
+    Union(Vec<TsType>),
+    Intersection(Vec<TsType>),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct TsProp {
+    name: String,
+    optional: bool,
+    ty: TsType,
+}
+
+fn tokenize(source: &str) -> Vec<Token> {
+    let chars: Vec<char> = source.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        if c.is_whitespace() {
+            i += 1;
+        } else if c == '/' && chars.get(i + 1) == Some(&'/') {
+            while i < chars.len() && chars[i] != '\n' {
+                i += 1;
+            }
+        } else if c == '/' && chars.get(i + 1) == Some(&'*') {
+            i += 2;
+            while i < chars.len() && !(chars[i] == '*' && chars.get(i + 1) == Some(&'/')) {
+                i += 1;
+            }
+            i += 2;
+        } else if c == '\'' || c == '"' || c == '`' {
+            let mut value = String::new();
+            i += 1;
+            while i < chars.len() && chars[i] != c {
+                if chars[i] == '\\' && i + 1 < chars.len() {
+                    i += 1;
+                }
+                value.push(chars[i]);
+                i += 1;
+            }
+            i += 1;
+            tokens.push(Token::Str(value));
+        } else if c.is_ascii_alphabetic() || c == '_' || c == '$' {
+            let start = i;
+            while i < chars.len()
+                && (chars[i].is_ascii_alphanumeric() || chars[i] == '_' || chars[i] == '$')
+            {
+                i += 1;
+            }
+            tokens.push(Token::Ident(chars[start..i].iter().collect()));
+        } else if c.is_ascii_digit() {
+            let start = i;
+            while i < chars.len() && (chars[i].is_ascii_digit() || chars[i] == '.') {
+                i += 1;
+            }
+            tokens.push(Token::Num(chars[start..i].iter().collect()));
+        } else {
+            tokens.push(Token::Punct(c));
+            i += 1;
+        }
+    }
+    tokens
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn peek_at(&self, offset: usize) -> Option<&Token> {
+        self.tokens.get(self.pos + offset)
+    }
+
+    fn eat(&mut self, c: char) -> bool {
+        if self.peek() == Some(&Token::Punct(c)) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Skip a balanced `<...>` type argument list.
+    fn skip_generics(&mut self) {
+        let mut depth = 0;
+        while let Some(token) = self.peek() {
+            match token {
+                Token::Punct('<') => depth += 1,
+                Token::Punct('>') => depth -= 1,
+                _ => {}
+            }
+            self.pos += 1;
+            if depth == 0 {
+                break;
+            }
+        }
+    }
+
+    /// Skip to the end of the current member, past a top-level `;` or `,`.
+    fn skip_member(&mut self, close: char) {
+        let mut depth = 0i32;
+        while let Some(token) = self.peek() {
+            match token {
+                Token::Punct('{' | '(' | '[' | '<') => depth += 1,
+                Token::Punct(c) if depth == 0 && *c == close => return,
+                Token::Punct('}' | ')' | ']' | '>') => depth -= 1,
+                Token::Punct(';' | ',') if depth == 0 => {
+                    self.pos += 1;
+                    return;
+                }
+                _ => {}
+            }
+            self.pos += 1;
+        }
+    }
+
+    fn parse_type(&mut self) -> TsType {
+        self.eat('|');
+        let mut members = vec![self.parse_intersection()];
+        while self.eat('|') {
+            members.push(self.parse_intersection());
+        }
+        if members.len() == 1 {
+            members.remove(0)
+        } else {
+            TsType::Union(members)
+        }
+    }
+
+    fn parse_intersection(&mut self) -> TsType {
+        self.eat('&');
+        let mut members = vec![self.parse_postfix()];
+        while self.eat('&') {
+            members.push(self.parse_postfix());
+        }
+        if members.len() == 1 {
+            members.remove(0)
+        } else {
+            TsType::Intersection(members)
+        }
+    }
+
+    fn parse_postfix(&mut self) -> TsType {
+        let mut ty = self.parse_primary();
+        while self.peek() == Some(&Token::Punct('[')) && self.peek_at(1) == Some(&Token::Punct(']'))
+        {
+            self.pos += 2;
+            ty = TsType::Array(Box::new(ty));
+        }
+        ty
+    }
+
+    fn parse_primary(&mut self) -> TsType {
+        let Some(token) = self.peek().cloned() else {
+            return TsType::Keyword("unknown".to_string());
+        };
+        self.pos += 1;
+        match token {
+            Token::Punct('(') => {
+                let ty = self.parse_type();
+                self.eat(')');
+                ty
+            }
+            Token::Punct('{') => self.parse_object('}'),
+            Token::Punct('[') => {
+                let mut items = Vec::new();
+                while !self.eat(']') && self.peek().is_some() {
+                    items.push(self.parse_type());
+                    self.eat(',');
+                }
+                TsType::Tuple(items)
+            }
+            Token::Punct('-') => match self.peek().cloned() {
+                Some(Token::Num(value)) => {
+                    self.pos += 1;
+                    TsType::Literal(format!("-{value}"))
+                }
+                _ => TsType::Keyword("unknown".to_string()),
+            },
+            Token::Str(value) | Token::Num(value) => TsType::Literal(value),
+            Token::Ident(name) => {
+                if self.peek() == Some(&Token::Punct('<')) {
+                    self.skip_generics();
+                }
+                match name.as_str() {
+                    "string" | "number" | "boolean" | "null" | "undefined" | "unknown" | "any"
+                    | "never" | "object" => TsType::Keyword(name),
+                    "true" | "false" => TsType::Keyword("boolean".to_string()),
+                    _ => TsType::Ref(name),
+                }
+            }
+            Token::Punct(_) => TsType::Keyword("unknown".to_string()),
+        }
+    }
+
+    /// Parse object members up to `close`; the opening brace is consumed.
+    fn parse_object(&mut self, close: char) -> TsType {
+        let mut props = Vec::new();
+        let mut index = false;
+        loop {
+            if self.eat(close) || self.peek().is_none() {
+                break;
+            }
+            if self.eat(';') || self.eat(',') {
+                continue;
+            }
+            if self.peek() == Some(&Token::Punct('[')) {
+                index = true;
+                self.skip_member(close);
+                continue;
+            }
+            let name = match self.peek().cloned() {
+                Some(Token::Ident(name) | Token::Str(name) | Token::Num(name)) => name,
+                _ => {
+                    self.skip_member(close);
+                    continue;
+                }
+            };
+            self.pos += 1;
+            let optional = self.eat('?');
+            if !self.eat(':') {
+                self.skip_member(close);
+                continue;
+            }
+            let ty = self.parse_type();
+            props.push(TsProp { name, optional, ty });
+        }
+        TsType::Object { props, index }
+    }
+
+    /// Collect top-level `interface` and `type` declarations.
+    fn declarations(&mut self) -> BTreeMap<String, TsType> {
+        let mut declarations = BTreeMap::new();
+        while let Some(token) = self.peek().cloned() {
+            self.pos += 1;
+            let Token::Ident(keyword) = token else {
+                continue;
+            };
+            let Some(Token::Ident(name)) = self.peek().cloned() else {
+                continue;
+            };
+            match keyword.as_str() {
+                "interface" => {
+                    self.pos += 1;
+                    while self.peek().is_some_and(|token| *token != Token::Punct('{')) {
+                        self.pos += 1;
+                    }
+                    if self.eat('{') {
+                        declarations.insert(name, self.parse_object('}'));
+                    }
+                }
+                "type" => {
+                    self.pos += 1;
+                    if self.peek() == Some(&Token::Punct('<')) {
+                        self.skip_generics();
+                    }
+                    if self.eat('=') {
+                        let ty = self.parse_type();
+                        self.eat(';');
+                        declarations.insert(name, ty);
+                    }
+                }
+                _ => {}
+            }
+        }
+        declarations
+    }
+}
+
+/// Union members, flattened, without `null` and `undefined`.
+fn members(ty: &TsType) -> Vec<&TsType> {
+    match ty {
+        TsType::Union(items) => items.iter().flat_map(members).collect(),
+        TsType::Keyword(keyword) if keyword == "null" || keyword == "undefined" => Vec::new(),
+        _ => vec![ty],
+    }
+}
+
+fn nullable(ty: &TsType) -> bool {
+    match ty {
+        TsType::Union(items) => items.iter().any(nullable),
+        TsType::Keyword(keyword) => keyword == "null" || keyword == "undefined",
+        _ => false,
+    }
+}
+
+fn props(ty: &TsType) -> Option<&[TsProp]> {
+    match ty {
+        TsType::Object { props, .. } => Some(props),
+        _ => None,
+    }
+}
+
+fn prop<'a>(props: &'a [TsProp], name: &str) -> Option<&'a TsProp> {
+    props.iter().find(|prop| prop.name == name)
+}
+
+fn is_keyword(ty: &TsType, keyword: &str) -> bool {
+    matches!(ty, TsType::Keyword(value) if value == keyword)
+}
+
+struct Context<'a> {
+    declarations: &'a BTreeMap<String, TsType>,
+    /// Interface name -> collection slug
+    collections: BTreeMap<String, String>,
+    uploads: BTreeSet<String>,
+    warnings: Vec<String>,
+}
+
+impl<'a> Context<'a> {
+    /// Follow a reference to a non-collection declaration.
+    fn resolve(&self, ty: &'a TsType) -> &'a TsType {
+        let mut ty = ty;
+        for _ in 0..MAX_DEPTH {
+            match ty {
+                TsType::Ref(name) if !self.collections.contains_key(name) => {
+                    match self.declarations.get(name) {
+                        Some(target) => ty = target,
+                        None => break,
+                    }
+                }
+                _ => break,
+            }
+        }
+        ty
+    }
+
+    /// Slug when `ty` is a document reference: a collection interface or its ID type.
+    fn collection_ref(&self, ty: &TsType) -> Option<&str> {
+        match ty {
+            TsType::Ref(name) => self.collections.get(name).map(String::as_str),
+            _ => None,
+        }
+    }
+
+    /// `relationTo` slugs when every member is an ID or document reference.
+    fn relationship(&self, items: &[&'a TsType]) -> Option<Vec<String>> {
+        let mut slugs = Vec::new();
+        for item in items {
+            if let Some(slug) = self.collection_ref(item) {
+                slugs.push(slug.to_string());
+                continue;
+            }
+            let resolved = self.resolve(item);
+            if is_keyword(resolved, "string") || is_keyword(resolved, "number") {
+                continue;
+            }
+            // Polymorphic: { relationTo: 'posts'; value: string | Post }
+            let props = props(resolved)?;
+            match prop(props, "relationTo").map(|prop| &prop.ty) {
+                Some(TsType::Literal(slug)) if prop(props, "value").is_some() => {
+                    slugs.push(slug.clone())
+                }
+                _ => return None,
+            }
+        }
+        if slugs.is_empty() {
+            return None;
+        }
+        let mut seen = BTreeSet::new();
+        slugs.retain(|slug| seen.insert(slug.clone()));
+        Some(slugs)
+    }
+
+    fn literals(&self, items: &[&'a TsType]) -> Option<Vec<String>> {
+        items
+            .iter()
+            .map(|item| match self.resolve(item) {
+                TsType::Literal(value) => Some(value.clone()),
+                _ => None,
+            })
+            .collect::<Option<Vec<_>>>()
+            .filter(|options| !options.is_empty())
+    }
+
+    fn fields(
+        &mut self,
+        props: &'a [TsProp],
+        skip: &[&str],
+        path: &str,
+        depth: usize,
+    ) -> Vec<SchemaField> {
+        props
+            .iter()
+            .filter(|prop| !skip.contains(&prop.name.as_str()))
+            .map(|prop| self.field(prop, path, depth))
+            .collect()
+    }
+
+    fn field(&mut self, prop: &'a TsProp, path: &str, depth: usize) -> SchemaField {
+        let path = format!("{path}.{}", prop.name);
+        let mut field = self.field_type(&prop.name, &prop.ty, &path, depth);
+        field.required = !prop.optional && !nullable(&prop.ty);
+        field
+    }
+
+    fn field_type(&mut self, name: &str, ty: &'a TsType, path: &str, depth: usize) -> SchemaField {
+        let mut field = SchemaField::new(name, "json");
+        if depth > MAX_DEPTH {
+            self.warnings
+                .push(format!("{path}: nested too deeply, reported as json"));
+            return field;
+        }
+        let items = members(ty);
+        if let Some(options) = self.literals(&items) {
+            field.field_type = "select".to_string();
+            field.options = Some(options);
+            return field;
+        }
+        if let Some(relation_to) = self.relationship(&items) {
+            let upload = relation_to.len() == 1
+                && items
+                    .iter()
+                    .any(|item| matches!(item, TsType::Ref(name) if self.uploads.contains(name)));
+            field.field_type = if upload { "upload" } else { "relationship" }.to_string();
+            field.relation_to = Some(relation_to);
+            return field;
+        }
+        let [item] = items.as_slice() else {
+            // Mixed unions are what json fields generate.
+            return field;
+        };
+        match self.resolve(item) {
+            TsType::Keyword(keyword) => match keyword.as_str() {
+                "string" => field.field_type = string_type(name, path, &mut self.warnings),
+                "number" => field.field_type = "number".to_string(),
+                "boolean" => field.field_type = "checkbox".to_string(),
+                _ => {}
+            },
+            TsType::Tuple(values)
+                if values.len() == 2 && values.iter().all(|value| is_keyword(value, "number")) =>
+            {
+                field.field_type = "point".to_string();
+            }
+            TsType::Object { props, index } => {
+                if prop(props, "root").is_some() {
+                    field.field_type = "richText".to_string();
+                } else if !props.is_empty() {
+                    field.field_type = "group".to_string();
+                    field.fields = Some(self.fields(props, &[], path, depth + 1));
+                } else if !index {
+                    field.field_type = "group".to_string();
+                    field.fields = Some(Vec::new());
+                }
+            }
+            TsType::Array(inner) => self.array_type(&mut field, inner, path, depth),
+            _ => {}
+        }
+        field
+    }
+
+    fn array_type(&mut self, field: &mut SchemaField, inner: &'a TsType, path: &str, depth: usize) {
+        let items = members(inner);
+        if let Some(options) = self.literals(&items) {
+            field.field_type = "select".to_string();
+            field.has_many = true;
+            field.options = Some(options);
+            return;
+        }
+        if let Some(relation_to) = self.relationship(&items) {
+            let upload = relation_to.len() == 1
+                && items
+                    .iter()
+                    .any(|item| matches!(item, TsType::Ref(name) if self.uploads.contains(name)));
+            field.field_type = if upload { "upload" } else { "relationship" }.to_string();
+            field.has_many = true;
+            field.relation_to = Some(relation_to);
+            return;
+        }
+        let rows: Vec<&'a TsType> = items.iter().map(|item| self.resolve(item)).collect();
+        let is_block =
+            |row: &TsType| props(row).is_some_and(|props| prop(props, "blockType").is_some());
+        if !rows.is_empty() && rows.iter().all(|row| is_block(row)) {
+            field.field_type = "blocks".to_string();
+            let mut blocks = Vec::new();
+            for row in rows {
+                let props = props(row).unwrap_or_default();
+                let slug = match prop(props, "blockType").map(|prop| &prop.ty) {
+                    Some(TsType::Literal(slug)) => slug.clone(),
+                    _ => continue,
+                };
+                let fields = self.fields(
+                    props,
+                    &["id", "blockName", "blockType"],
+                    &format!("{path}.{slug}"),
+                    depth + 1,
+                );
+                blocks.push(SchemaBlock { slug, fields });
+            }
+            field.blocks = Some(blocks);
+            return;
+        }
+        let [row] = rows.as_slice() else {
+            return;
+        };
+        match *row {
+            TsType::Keyword(keyword) if keyword == "string" => {
+                field.field_type = string_type(&field.name, path, &mut self.warnings);
+                field.has_many = true;
+            }
+            TsType::Keyword(keyword) if keyword == "number" => {
+                field.field_type = "number".to_string();
+                field.has_many = true;
+            }
+            // Payload 2 Slate rich text: { [k: string]: unknown }[]
+            TsType::Object { props, index: true } if props.is_empty() => {
+                field.field_type = "richText".to_string();
+            }
+            TsType::Object { props, .. } => {
+                field.field_type = "array".to_string();
+                field.fields = Some(self.fields(props, &["id"], path, depth + 1));
+            }
+            _ => {}
+        }
+    }
+}
+
+/// `text` unless the name suggests one of the other string-backed types.
+fn string_type(name: &str, path: &str, warnings: &mut Vec<String>) -> String {
+    let lower = name.to_lowercase();
+    let guess = if lower == "email" || lower.ends_with("email") {
+        "email"
+    } else if lower == "date" || lower.ends_with("date") || name.ends_with("At") {
+        "date"
+    } else {
+        return "text".to_string();
+    };
+    warnings.push(format!(
+        "{path}: string field assumed to be {guess} from its name"
+    ));
+    guess.to_string()
+}
+
+/// `slug -> interface name` entries of a `Config` member map.
+fn config_map(config: &[TsProp], key: &str) -> Vec<(String, String)> {
+    let Some(TsType::Object { props, .. }) = prop(config, key).map(|prop| &prop.ty) else {
+        return Vec::new();
+    };
+    props
+        .iter()
+        .filter_map(|prop| match &prop.ty {
+            TsType::Ref(name) => Some((prop.name.clone(), name.clone())),
+            _ => None,
+        })
+        .collect()
+}
+
+pub fn parse_payload_types(source: &str) -> Result<PayloadTypesSchema, String> {
+    let mut parser = Parser {
+        tokens: tokenize(source),
+        pos: 0,
+    };
+    let declarations = parser.declarations();
+    let Some(config) = declarations.get("Config").and_then(props) else {
+        return Err(
+            "No `Config` interface found; is this a generated payload-types.ts?".to_string(),
+        );
+    };
+    let collections: Vec<(String, String)> = config_map(config, "collections")
+        .into_iter()
+        .filter(|(slug, _)| !INTERNAL_COLLECTIONS.contains(&slug.as_str()))
+        .collect();
+    let globals = config_map(config, "globals");
+    if collections.is_empty() && globals.is_empty() {
+        return Err("The `Config` interface lists no collections or globals".to_string());
+    }
+
+    let uploads = collections
+        .iter()
+        .filter(|(_, interface)| {
+            declarations
+                .get(interface)
+                .and_then(props)
+                .is_some_and(|props| {
+                    prop(props, "filename").is_some() && prop(props, "mimeType").is_some()
+                })
+        })
+        .map(|(_, interface)| interface.clone())
+        .collect();
+    let mut context = Context {
+        declarations: &declarations,
+        collections: config_map(config, "collections")
+            .into_iter()
+            .map(|(slug, interface)| (interface, slug))
+            .collect(),
+        uploads,
+        warnings: Vec::new(),
+    };
+
+    let mut schema = SchemaModel::default();
+    for (slug, interface) in &collections {
+        let Some(props) = declarations.get(interface).and_then(props) else {
+            context.warnings.push(format!(
+                "collections.{slug}: interface {interface} not found"
+            ));
+            continue;
+        };
+        let auth = ["hash", "salt", "resetPasswordToken", "sessions"]
+            .iter()
+            .any(|name| prop(props, name).is_some());
+        let upload = context.uploads.contains(interface);
+        let mut skip = vec!["id", "createdAt", "updatedAt"];
+        if auth {
+            skip.extend(AUTH_FIELDS);
+        }
+        if upload {
+            skip.extend(UPLOAD_FIELDS);
+        }
+        let fields = context.fields(props, &skip, &format!("collections.{slug}"), 0);
+        schema.collections.push(SchemaCollection {
+            slug: slug.clone(),
+            fields,
+            timestamps: prop(props, "createdAt").is_some() || prop(props, "updatedAt").is_some(),
+            auth,
+            upload,
+        });
+    }
+    for (slug, interface) in &globals {
+        let Some(props) = declarations.get(interface).and_then(props) else {
+            context
+                .warnings
+                .push(format!("globals.{slug}: interface {interface} not found"));
+            continue;
+        };
+        let fields = context.fields(
+            props,
+            &["id", "createdAt", "updatedAt", "globalType"],
+            &format!("globals.{slug}"),
+            0,
+        );
+        schema.globals.push(SchemaGlobal {
+            slug: slug.clone(),
+            fields,
+        });
+    }
+
+    let errors = schema.validate();
+    Ok(PayloadTypesSchema {
+        schema,
+        errors,
+        warnings: context.warnings,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TYPES: &str = r#"
+/* tslint:disable */
+/**
+ * This file was automatically generated by Payload.
+ */
+export interface Config {
+  collections: {
+    posts: Post;
+    users: User;
+    media: Media;
+    'payload-preferences': PayloadPreference;
+  };
+  globals: {
+    header: Header;
+  };
+}
+export interface Post {
+  id: string;
+  title: string;
+  status?: ('draft' | 'published') | null;
+  author?: (string | null) | User;
+  tags?: (string | Media)[] | null;
+  related?:
+    | ({
+        relationTo: 'posts';
+        value: string | Post;
+      } | null)
+    | ({
+        relationTo: 'users';
+        value: string | User;
+      } | null);
+  content?: {
+    root: {
+      type: string;
+      children: {
+        type: string;
+        version: number;
+        [k: string]: unknown;
+      }[];
+    };
+    [k: string]: unknown;
+  } | null;
+  meta?: {
+    description?: string | null;
+  };
+  layout?:
+    | (
+        | {
+            heading: string;
+            id?: string | null;
+            blockName?: string | null;
+            blockType: 'hero';
+          }
+        | CallToAction
+      )[]
+    | null;
+  links?: {
+      label?: string | null;
+      id?: string | null;
+    }[] | null;
+  location?: [number, number] | null;
+  publishedAt?: string | null;
+  updatedAt: string;
+  createdAt: string;
+}
+export interface CallToAction {
+  label?: string | null;
+  id?: string | null;
+  blockName?: string | null;
+  blockType: 'cta';
+}
+export interface User {
+  id: string;
+  name?: string | null;
+  updatedAt: string;
+  createdAt: string;
+  email: string;
+  salt?: string | null;
+  hash?: string | null;
+  password?: string | null;
+}
+export interface Media {
+  id: string;
+  alt: string;
+  url?: string | null;
+  filename?: string | null;
+  mimeType?: string | null;
+  updatedAt: string;
+  createdAt: string;
+}
+export interface PayloadPreference {
+  id: string;
+}
+export interface Header {
+  id: string;
+  nav?: { [k: string]: unknown } | unknown[] | string | number | boolean | null;
+}
+declare module 'payload' {
+  export interface GeneratedTypes extends Config {}
+}
+"#;
+
+    #[test]
+    fn reconstructs_collections_and_globals() {
+        let parsed = parse_payload_types(TYPES).unwrap();
+        assert!(parsed.errors.is_empty(), "{:?}", parsed.errors);
+        let slugs: Vec<&str> = parsed
+            .schema
+            .collections
+            .iter()
+            .map(|c| c.slug.as_str())
+            .collect();
+        assert_eq!(slugs, ["posts", "users", "media"]);
+
+        let posts = parsed.schema.collection("posts").unwrap();
+        assert!(posts.timestamps);
+        let field = |name: &str| {
+            posts
+                .fields
+                .iter()
+                .find(|field| field.name == name)
+                .unwrap()
+        };
+        assert_eq!(field("title").field_type, "text");
+        assert!(field("title").required);
+        assert_eq!(
+            field("status").options.as_deref(),
+            Some(&["draft".to_string(), "published".to_string()][..])
+        );
+        assert_eq!(
+            field("author").relation_to.as_deref(),
+            Some(&["users".to_string()][..])
+        );
+        assert_eq!(field("tags").field_type, "upload");
+        assert!(field("tags").has_many);
+        assert_eq!(field("related").relation_to.as_ref().unwrap().len(), 2);
+        assert_eq!(field("content").field_type, "richText");
+        assert_eq!(field("meta").field_type, "group");
+        let blocks = field("layout").blocks.as_ref().unwrap();
+        assert_eq!(
+            blocks.iter().map(|b| b.slug.as_str()).collect::<Vec<_>>(),
+            ["hero", "cta"]
+        );
+        assert_eq!(blocks[0].fields.len(), 1);
+        assert_eq!(field("links").field_type, "array");
+        assert_eq!(field("location").field_type, "point");
+        assert_eq!(field("publishedAt").field_type, "date");
+
+        let users = parsed.schema.collection("users").unwrap();
+        assert!(users.auth);
+        assert_eq!(users.fields.len(), 1);
+        assert!(parsed.schema.collection("media").unwrap().upload);
+        assert_eq!(parsed.schema.globals[0].fields[0].field_type, "json");
+    }
+}
@@ -0,0 +1,538 @@
+//! Official Payload plugins: packages, imports and option schemas per
+//! Payload major version, used by the config generator, the scaffolder and
+//! the `generate_plugin_config` tool.
+
+use std::collections::BTreeMap;
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+/// Payload major version a plugin config is generated for.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub enum PayloadVersion {
+    #[default]
+    #[serde(rename = "2")]
+    V2,
+    #[serde(rename = "3")]
+    V3,
+}
+
+impl PayloadVersion {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PayloadVersion::V2 => "2",
+            PayloadVersion::V3 => "3",
+        }
+    }
+}
+
+/// One option a plugin accepts.
+#[derive(Clone, Debug, Serialize, JsonSchema)]
+pub struct PluginOption {
+    pub name: &'static str,
+    /// `string`, `boolean`, `number`, `string[]`, `object` or `function`
+    pub kind: &'static str,
+    pub required: bool,
+    pub description: &'static str,
+}
+
+/// How a plugin is installed and called in one Payload major version.
+#[derive(Clone, Debug, Serialize, JsonSchema)]
+pub struct PluginRelease {
+    /// npm packages and version ranges, the plugin's own package first
+    pub dependencies: &'static [(&'static str, &'static str)],
+    pub imports: &'static [&'static str],
+    /// Function called in the `plugins` array
+    pub function: &'static str,
+    /// Options written by default, as JavaScript source
+    pub defaults: &'static [(&'static str, &'static str)],
+}
+
+#[derive(Clone, Debug, Serialize, JsonSchema)]
+pub struct PluginInfo {
+    pub name: &'static str,
+    pub description: &'static str,
+    pub options: &'static [PluginOption],
+    /// Environment variables the default options read
+    pub env: &'static [&'static str],
+    pub v2: Option<PluginRelease>,
+    pub v3: Option<PluginRelease>,
+}
+
+impl PluginInfo {
+    pub fn release(&self, version: PayloadVersion) -> Option<&PluginRelease> {
+        match version {
+            PayloadVersion::V2 => self.v2.as_ref(),
+            PayloadVersion::V3 => self.v3.as_ref(),
+        }
+    }
+
+    fn option(&self, name: &str) -> Option<&PluginOption> {
+        self.options.iter().find(|option| option.name == name)
+    }
+}
+
+const fn option(
+    name: &'static str,
+    kind: &'static str,
+    required: bool,
+    description: &'static str,
+) -> PluginOption {
+    PluginOption {
+        name,
+        kind,
+        required,
+        description,
+    }
+}
+
+pub const PLUGIN_REGISTRY: &[PluginInfo] = &[
+    PluginInfo {
+        name: "seo",
+        description: "Meta title, description and image fields with generation hooks",
+        options: &[
+            option("collections", "string[]", false, "Collections that get SEO fields"),
+            option("globals", "string[]", false, "Globals that get SEO fields"),
+            option("uploadsCollection", "string", false, "Upload collection for the meta image"),
+            option("tabbedUI", "boolean", false, "Put SEO fields in their own tab"),
+            option("fields", "function", false, "Extend or replace the default SEO fields"),
+            option("generateTitle", "function", false, "Build the meta title from the document"),
+            option("generateDescription", "function", false, "Build the meta description"),
+            option("generateURL", "function", false, "Build the preview URL"),
+            option("generateImage", "function", false, "Pick the meta image"),
+        ],
+        env: &[],
+        v2: Some(PluginRelease {
+            dependencies: &[("@payloadcms/plugin-seo", "^2.0.0")],
+            imports: &["import seoPlugin from '@payloadcms/plugin-seo';"],
+            function: "seoPlugin",
+            defaults: &[("collections", "['pages']"), ("uploadsCollection", "'media'")],
+        }),
+        v3: Some(PluginRelease {
+            dependencies: &[("@payloadcms/plugin-seo", "^3.0.0")],
+            imports: &["import { seoPlugin } from '@payloadcms/plugin-seo';"],
+            function: "seoPlugin",
+            defaults: &[("collections", "['pages']"), ("uploadsCollection", "'media'")],
+        }),
+    },
+    PluginInfo {
+        name: "form-builder",
+        description: "Forms and form submissions collections with optional email and payments",
+        options: &[
+            option("fields", "object", false, "Form field blocks to enable or override"),
+            option("redirectRelationships", "string[]", false, "Collections a form may redirect to"),
+            option("formOverrides", "object", false, "Overrides for the forms collection"),
+            option("formSubmissionOverrides", "object", false, "Overrides for the submissions collection"),
+            option("beforeEmail", "function", false, "Transform emails before they are sent"),
+            option("handlePayment", "function", false, "Process payment fields on submission"),
+        ],
+        env: &[],
+        v2: Some(PluginRelease {
+            dependencies: &[("@payloadcms/plugin-form-builder", "^1.0.0")],
+            imports: &["import formBuilder from '@payloadcms/plugin-form-builder';"],
+            function: "formBuilder",
+            defaults: &[
+                ("formOverrides", "{ admin: { group: 'Content' } }"),
+                ("formSubmissionOverrides", "{ admin: { group: 'Content' } }"),
+                ("redirectRelationships", "['pages']"),
+            ],
+        }),
+        v3: Some(PluginRelease {
+            dependencies: &[("@payloadcms/plugin-form-builder", "^3.0.0")],
+            imports: &["import { formBuilderPlugin } from '@payloadcms/plugin-form-builder';"],
+            function: "formBuilderPlugin",
+            defaults: &[
+                ("formOverrides", "{ admin: { group: 'Content' } }"),
+                ("formSubmissionOverrides", "{ admin: { group: 'Content' } }"),
+                ("redirectRelationships", "['pages']"),
+            ],
+        }),
+    },
+    PluginInfo {
+        name: "nested-docs",
+        description: "Parent relationships and breadcrumbs for hierarchical documents",
+        options: &[
+            option("collections", "string[]", true, "Collections that can be nested"),
+            option("generateLabel", "function", false, "Label of each breadcrumb"),
+            option("generateURL", "function", false, "URL of each breadcrumb"),
+            option("parentFieldSlug", "string", false, "Name of the parent field (default parent)"),
+            option("breadcrumbsFieldSlug", "string", false, "Name of the breadcrumbs field (default breadcrumbs)"),
+        ],
+        env: &[],
+        v2: Some(PluginRelease {
+            dependencies: &[("@payloadcms/plugin-nested-docs", "^1.0.0")],
+            imports: &["import nestedDocs from '@payloadcms/plugin-nested-docs';"],
+            function: "nestedDocs",
+            defaults: &[("collections", "['pages']")],
+        }),
+        v3: Some(PluginRelease {
+            dependencies: &[("@payloadcms/plugin-nested-docs", "^3.0.0")],
+            imports: &["import { nestedDocsPlugin } from '@payloadcms/plugin-nested-docs';"],
+            function: "nestedDocsPlugin",
+            defaults: &[("collections", "['pages']")],
+        }),
+    },
+    PluginInfo {
+        name: "redirects",
+        description: "Redirects collection for managing URL redirects",
+        options: &[
+            option("collections", "string[]", true, "Collections a redirect may point to"),
+            option("overrides", "object", false, "Overrides for the redirects collection"),
+            option("redirectTypes", "string[]", false, "HTTP status codes editors can choose"),
+        ],
+        env: &[],
+        v2: Some(PluginRelease {
+            dependencies: &[("@payloadcms/plugin-redirects", "^1.0.0")],
+            imports: &["import redirects from '@payloadcms/plugin-redirects';"],
+            function: "redirects",
+            defaults: &[("collections", "['pages']")],
+        }),
+        v3: Some(PluginRelease {
+            dependencies: &[("@payloadcms/plugin-redirects", "^3.0.0")],
+            imports: &["import { redirectsPlugin } from '@payloadcms/plugin-redirects';"],
+            function: "redirectsPlugin",
+            defaults: &[("collections", "['pages']")],
+        }),
+    },
+    PluginInfo {
+        name: "search",
+        description: "Search collection kept in sync with the indexed collections",
+        options: &[
+            option("collections", "string[]", true, "Collections indexed for search"),
+            option("defaultPriorities", "object", false, "Priority per collection"),
+            option("searchOverrides", "object", false, "Overrides for the search collection"),
+            option("beforeSync", "function", false, "Transform documents before indexing"),
+            option("syncDrafts", "boolean", false, "Index drafts too"),
+            option("deleteDrafts", "boolean", false, "Remove unpublished documents from the index"),
+        ],
+        env: &[],
+        v2: Some(PluginRelease {
+            dependencies: &[("@payloadcms/plugin-search", "^1.0.0")],
+            imports: &["import search from '@payloadcms/plugin-search';"],
+            function: "search",
+            defaults: &[("collections", "['pages']")],
+        }),
+        v3: Some(PluginRelease {
+            dependencies: &[("@payloadcms/plugin-search", "^3.0.0")],
+            imports: &["import { searchPlugin } from '@payloadcms/plugin-search';"],
+            function: "searchPlugin",
+            defaults: &[("collections", "['pages']")],
+        }),
+    },
+    PluginInfo {
+        name: "stripe",
+        description: "Two-way sync with Stripe, webhooks and a Stripe REST proxy",
+        options: &[
+            option("stripeSecretKey", "string", true, "Stripe secret key"),
+            option("stripeWebhooksEndpointSecret", "string", false, "Secret that verifies webhooks"),
+            option("rest", "boolean", false, "Expose the Stripe REST proxy (default false)"),
+            option("logs", "boolean", false, "Log sync and webhook activity"),
+            option("sync", "object", false, "Collections synced with Stripe objects"),
+            option("webhooks", "function", false, "Handlers per Stripe event, or one handler for every event"),
+        ],
+        env: &["STRIPE_SECRET_KEY", "STRIPE_WEBHOOKS_ENDPOINT_SECRET"],
+        v2: Some(PluginRelease {
+            dependencies: &[("@payloadcms/plugin-stripe", "^0.0.1")],
+            imports: &["import stripePlugin from '@payloadcms/plugin-stripe';"],
+            function: "stripePlugin",
+            defaults: &[
+                ("stripeSecretKey", "process.env.STRIPE_SECRET_KEY"),
+                ("stripeWebhooksEndpointSecret", "process.env.STRIPE_WEBHOOKS_ENDPOINT_SECRET"),
+                ("rest", "false"),
+            ],
+        }),
+        v3: Some(PluginRelease {
+            dependencies: &[("@payloadcms/plugin-stripe", "^3.0.0")],
+            imports: &["import { stripePlugin } from '@payloadcms/plugin-stripe';"],
+            function: "stripePlugin",
+            defaults: &[
+                ("stripeSecretKey", "process.env.STRIPE_SECRET_KEY"),
+                ("stripeWebhooksEndpointSecret", "process.env.STRIPE_WEBHOOKS_ENDPOINT_SECRET"),
+                ("rest", "false"),
+            ],
+        }),
+    },
+    PluginInfo {
+        name: "cloud-storage",
+        description: "Store uploads in S3 instead of on local disk",
+        options: &[
+            option("collections", "object", true, "Upload collections stored in the bucket"),
+            option("bucket", "string", false, "Bucket name (Payload 3)"),
+            option("config", "object", false, "S3 client config: credentials, region, endpoint (Payload 3)"),
+            option("enabled", "boolean", false, "Turn storage off, e.g. in development"),
+        ],
+        env: &["S3_BUCKET", "S3_ACCESS_KEY_ID", "S3_SECRET_ACCESS_KEY", "S3_REGION"],
+        v2: Some(PluginRelease {
+            dependencies: &[
+                ("@payloadcms/plugin-cloud-storage", "^1.0.0"),
+                ("@aws-sdk/client-s3", "^3.0.0"),
+            ],
+            imports: &[
+                "import { cloudStorage } from '@payloadcms/plugin-cloud-storage';",
+                "import { s3Adapter } from '@payloadcms/plugin-cloud-storage/s3';",
+            ],
+            function: "cloudStorage",
+            defaults: &[(
+                "collections",
+                "{ media: { adapter: s3Adapter({ config: { credentials: { accessKeyId: process.env.S3_ACCESS_KEY_ID, secretAccessKey: process.env.S3_SECRET_ACCESS_KEY }, region: process.env.S3_REGION }, bucket: process.env.S3_BUCKET }) } }",
+            )],
+        }),
+        v3: Some(PluginRelease {
+            dependencies: &[("@payloadcms/storage-s3", "^3.0.0")],
+            imports: &["import { s3Storage } from '@payloadcms/storage-s3';"],
+            function: "s3Storage",
+            defaults: &[
+                ("collections", "{ media: true }"),
+                ("bucket", "process.env.S3_BUCKET"),
+                (
+                    "config",
+                    "{ credentials: { accessKeyId: process.env.S3_ACCESS_KEY_ID, secretAccessKey: process.env.S3_SECRET_ACCESS_KEY }, region: process.env.S3_REGION }",
+                ),
+            ],
+        }),
+    },
+    PluginInfo {
+        name: "sentry",
+        description: "Report server errors to Sentry",
+        options: &[
+            option("dsn", "string", false, "Sentry DSN (Payload 2)"),
+            option("Sentry", "object", false, "Initialized Sentry SDK (Payload 3)"),
+            option("options", "object", false, "Sentry client and capture options"),
+            option("enabled", "boolean", false, "Turn reporting off, e.g. in development"),
+        ],
+        env: &["SENTRY_DSN"],
+        v2: Some(PluginRelease {
+            dependencies: &[("@payloadcms/plugin-sentry", "^0.0.1")],
+            imports: &["import { sentry } from '@payloadcms/plugin-sentry';"],
+            function: "sentry",
+            defaults: &[("dsn", "process.env.SENTRY_DSN")],
+        }),
+        v3: Some(PluginRelease {
+            dependencies: &[("@payloadcms/plugin-sentry", "^3.0.0"), ("@sentry/nextjs", "^8.0.0")],
+            imports: &[
+                "import { sentryPlugin } from '@payloadcms/plugin-sentry';",
+                "import * as Sentry from '@sentry/nextjs';",
+            ],
+            function: "sentryPlugin",
+            defaults: &[("Sentry", "Sentry")],
+        }),
+    },
+    PluginInfo {
+        name: "multi-tenant",
+        description: "Scope documents to tenants with a tenant selector in the admin panel",
+        options: &[
+            option("collections", "object", true, "Collections scoped to a tenant"),
+            option("tenantsSlug", "string", false, "Slug of the tenants collection (default tenants)"),
+            option("tenantField", "object", false, "Overrides for the tenant field"),
+            option("userHasAccessToAllTenants", "function", false, "Users who see every tenant"),
+            option("debug", "boolean", false, "Show the tenant field in the admin panel"),
+        ],
+        env: &[],
+        v2: None,
+        v3: Some(PluginRelease {
+            dependencies: &[("@payloadcms/plugin-multi-tenant", "^3.0.0")],
+            imports: &["import { multiTenantPlugin } from '@payloadcms/plugin-multi-tenant';"],
+            function: "multiTenantPlugin",
+            defaults: &[("collections", "{ pages: {} }"), ("tenantsSlug", "'tenants'")],
+        }),
+    },
+];
+
+/// Registry entry for `name`; `s3` is accepted for `cloud-storage`.
+pub fn plugin(name: &str) -> Option<&'static PluginInfo> {
+    let name = if name == "s3" { "cloud-storage" } else { name };
+    PLUGIN_REGISTRY.iter().find(|info| info.name == name)
+}
+
+pub fn plugin_names() -> impl Iterator<Item = &'static str> {
+    PLUGIN_REGISTRY.iter().map(|info| info.name)
+}
+
+/// Registry entry and release for `name`, or an error naming what is
+/// supported.
+pub fn require_release(
+    name: &str,
+    version: PayloadVersion,
+) -> Result<(&'static PluginInfo, &'static PluginRelease), String> {
+    let info = plugin(name).ok_or_else(|| {
+        format!(
+            "Unknown plugin '{name}'. Supported plugins: {}",
+            plugin_names().collect::<Vec<_>>().join(", ")
+        )
+    })?;
+    let release = info.release(version).ok_or_else(|| {
+        format!("The {name} plugin is not available for Payload {}", version.as_str())
+    })?;
+    Ok((info, release))
+}
+
+/// Generated plugin setup: the call for the `plugins` array and what it
+/// needs around it.
+#[derive(Clone, Debug, Serialize, JsonSchema)]
+pub struct PluginConfig {
+    pub imports: Vec<String>,
+    /// Entry for the `plugins` array
+    pub code: String,
+    /// npm packages and version ranges to add to `package.json`
+    pub dependencies: BTreeMap<String, String>,
+    /// Environment variables read by the generated options
+    pub env: Vec<String>,
+}
+
+/// Check `value` against the option's kind. `function` options take
+/// JavaScript source as a string.
+fn check_option(plugin: &str, option: &PluginOption, value: &Value) -> Result<(), String> {
+    let valid = match option.kind {
+        "string" | "function" => value.is_string(),
+        "boolean" => value.is_boolean(),
+        "number" => value.is_number(),
+        "string[]" => value
+            .as_array()
+            .is_some_and(|items| items.iter().all(Value::is_string)),
+        "object" => value.is_object(),
+        _ => true,
+    };
+    if valid {
+        Ok(())
+    } else {
+        Err(format!(
+            "Option '{}' of the {plugin} plugin must be {}",
+            option.name, option.kind
+        ))
+    }
+}
+
+/// `function({ key: code, ... }),` with one option per line, for an entry
+/// of a `plugins` array at `indent`.
+pub(crate) fn render_call(function: &str, entries: &[(String, String)], indent: &str) -> String {
+    if entries.is_empty() {
+        return format!("{function}(),");
+    }
+    let body = entries
+        .iter()
+        .map(|(key, code)| format!("{indent}  {key}: {code},"))
+        .collect::<Vec<_>>()
+        .join("\n");
+    format!("{function}({{\n{body}\n{indent}}}),")
+}
+
+/// The variables of `env` that the rendered options read.
+pub(crate) fn env_used(env: &[&str], entries: &[(String, String)]) -> Vec<String> {
+    env.iter()
+        .filter(|var| {
+            entries
+                .iter()
+                .any(|(_, code)| code.contains(&format!("process.env.{var}")))
+        })
+        .map(|var| var.to_string())
+        .collect()
+}
+
+pub(crate) fn to_literal(value: &Value) -> String {
+    match value {
+        Value::String(s) => format!("'{}'", s.replace('\'', "\\'")),
+        Value::Array(items) => format!(
+            "[{}]",
+            items.iter().map(to_literal).collect::<Vec<_>>().join(", ")
+        ),
+        Value::Object(map) => format!(
+            "{{ {} }}",
+            map.iter()
+                .map(|(key, value)| format!("{key}: {}", to_literal(value)))
+                .collect::<Vec<_>>()
+                .join(", ")
+        ),
+        other => other.to_string(),
+    }
+}
+
+/// Plugin call for `name` with `options` laid over the release defaults,
+/// indented for an entry of the `plugins` array at `indent`.
+pub fn plugin_config(
+    name: &str,
+    version: PayloadVersion,
+    options: &Map<String, Value>,
+    indent: &str,
+) -> Result<PluginConfig, String> {
+    let (info, release) = require_release(name, version)?;
+    let mut entries: Vec<(String, String)> = release
+        .defaults
+        .iter()
+        .map(|(key, code)| (key.to_string(), code.to_string()))
+        .collect();
+    for (key, value) in options {
+        let option = info.option(key).ok_or_else(|| {
+            format!(
+                "Unknown option '{key}' for the {} plugin. Options: {}",
+                info.name,
+                info.options.iter().map(|o| o.name).collect::<Vec<_>>().join(", ")
+            )
+        })?;
+        check_option(info.name, option, value)?;
+        let code = match (option.kind, value) {
+            ("function", Value::String(source)) => source.clone(),
+            _ => to_literal(value),
+        };
+        match entries.iter_mut().find(|(existing, _)| existing == key) {
+            Some(entry) => entry.1 = code,
+            None => entries.push((key.clone(), code)),
+        }
+    }
+    if let Some(missing) = info
+        .options
+        .iter()
+        .find(|option| option.required && !entries.iter().any(|(key, _)| key == option.name))
+    {
+        return Err(format!(
+            "The {} plugin requires '{}': {}",
+            info.name, missing.name, missing.description
+        ));
+    }
+
+    Ok(PluginConfig {
+        imports: release.imports.iter().map(|line| line.to_string()).collect(),
+        code: render_call(release.function, &entries, indent),
+        env: env_used(info.env, &entries),
+        dependencies: release
+            .dependencies
+            .iter()
+            .map(|(package, range)| (package.to_string(), range.to_string()))
+            .collect(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn test_plugin_config_merges_options_per_version() {
+        let options = json!({ "collections": ["pages", "posts"], "parentFieldSlug": "parent" });
+        let config = plugin_config(
+            "nested-docs",
+            PayloadVersion::V3,
+            options.as_object().unwrap(),
+            "    ",
+        )
+        .unwrap();
+        assert_eq!(
+            config.imports,
+            vec!["import { nestedDocsPlugin } from '@payloadcms/plugin-nested-docs';"]
+        );
+        assert_eq!(
+            config.code,
+            "nestedDocsPlugin({\n      collections: ['pages', 'posts'],\n      parentFieldSlug: 'parent',\n    }),"
+        );
+
+        let stripe = plugin_config("stripe", PayloadVersion::V2, &Map::new(), "").unwrap();
+        assert_eq!(stripe.env, vec!["STRIPE_SECRET_KEY", "STRIPE_WEBHOOKS_ENDPOINT_SECRET"]);
+
+        let bad = json!({ "collections": "pages" });
+        assert!(plugin_config("redirects", PayloadVersion::V2, bad.as_object().unwrap(), "").is_err());
+        assert!(plugin_config("multi-tenant", PayloadVersion::V2, &Map::new(), "").is_err());
+        assert!(plugin_config("seo", PayloadVersion::V2, json!({ "colections": [] }).as_object().unwrap(), "").is_err());
+    }
+}
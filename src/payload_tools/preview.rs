@@ -0,0 +1,342 @@
+//! Draft and live preview for a Payload 3 app on Next.js. The admin opens
+//! `/next/preview`, which checks the preview secret and the logged-in user,
+//! turns on draft mode and redirects to the document's page; pages read
+//! drafts while draft mode is on and refresh when the editor saves.
+
+use std::collections::BTreeMap;
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::payload_tools::{email::server_url_env, plugins::PayloadVersion};
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct PreviewOptions {
+    /// Collections to preview
+    pub collections: Vec<String>,
+    /// Globals to show in live preview
+    pub globals: Option<Vec<String>>,
+    /// Frontend path per collection or global, with `{slug}` for the
+    /// document's slug; `/<collection>/{slug}` and `/` by default
+    pub paths: Option<BTreeMap<String, String>>,
+    /// Field holding the document's slug (default `slug`)
+    pub slug_field: Option<String>,
+    /// Save drafts while editing so the preview follows every change,
+    /// in milliseconds (default 100; 0 turns autosave off)
+    pub autosave_interval: Option<u32>,
+}
+
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct PreviewConfig {
+    pub imports: Vec<String>,
+    /// Entry for the root config's `admin` object
+    pub live_preview: String,
+    /// Properties to merge into each collection's config, by slug
+    pub collections: BTreeMap<String, String>,
+    /// Route handlers, the path helper and frontend components, by path from
+    /// the project root
+    pub files: BTreeMap<String, String>,
+    /// npm packages and version ranges to add to `package.json`
+    pub dependencies: BTreeMap<String, String>,
+    /// Environment variables read by the generated code
+    pub env: Vec<String>,
+}
+
+const SECRET_ENV: &str = "PREVIEW_SECRET";
+
+const BREAKPOINTS: &str = "    breakpoints: [
+      { label: 'Mobile', name: 'mobile', width: 375, height: 667 },
+      { label: 'Tablet', name: 'tablet', width: 768, height: 1024 },
+      { label: 'Desktop', name: 'desktop', width: 1440, height: 900 },
+    ],\n";
+
+const PREVIEW_ROUTE: &str = "import config from '@payload-config'
+import { draftMode } from 'next/headers'
+import { redirect } from 'next/navigation'
+import { getPayload } from 'payload'
+
+/** Turns on draft mode for logged-in users holding the preview secret, then opens `path`. */
+export async function GET(request: Request): Promise<Response> {
+  const { searchParams } = new URL(request.url)
+  const path = searchParams.get('path')
+  const secret = process.env.PREVIEW_SECRET
+  if (!secret || searchParams.get('previewSecret') !== secret) {
+    return new Response('You are not allowed to preview this page', { status: 403 })
+  }
+  if (!path || !path.startsWith('/') || path.startsWith('//')) {
+    return new Response('A path on this site is required', { status: 400 })
+  }
+
+  const payload = await getPayload({ config })
+  const { user } = await payload.auth({ headers: request.headers })
+  const draft = await draftMode()
+  if (!user) {
+    draft.disable()
+    return new Response('You are not allowed to preview this page', { status: 403 })
+  }
+  draft.enable()
+  redirect(path)
+}
+";
+
+const EXIT_PREVIEW_ROUTE: &str = "import { draftMode } from 'next/headers'
+import { redirect } from 'next/navigation'
+
+/** Turns off draft mode and returns to `path`, or the home page. */
+export async function GET(request: Request): Promise<Response> {
+  const path = new URL(request.url).searchParams.get('path')
+  ;(await draftMode()).disable()
+  redirect(path?.startsWith('/') && !path.startsWith('//') ? path : '/')
+}
+";
+
+const LIVE_PREVIEW_LISTENER: &str = "'use client'
+
+import { RefreshRouteOnSave } from '@payloadcms/live-preview-react'
+import { useRouter } from 'next/navigation'
+import React from 'react'
+
+/** Re-renders the page when the document is saved in the admin; render it while draft mode is on. */
+export const LivePreviewListener: React.FC = () => {
+  const router = useRouter()
+  return <RefreshRouteOnSave refresh={() => router.refresh()} serverURL={process.env.NEXT_PUBLIC_SERVER_URL ?? ''} />
+}
+";
+
+fn is_slug(value: &str) -> bool {
+    !value.is_empty()
+        && value
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
+
+fn string_list(values: &[&str]) -> String {
+    values
+        .iter()
+        .map(|value| format!("'{value}'"))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// `generatePreviewPath`, mapping a document to its preview URL.
+fn preview_path_helper(paths: &[(&str, String)]) -> String {
+    let paths: String = paths
+        .iter()
+        .map(|(slug, path)| format!("  '{slug}': '{}',\n", path.replace('\'', "\\'")))
+        .collect();
+    format!(
+        "/** Frontend path of each previewed collection and global; `{{slug}}` is the document's slug. */
+const paths: Record<string, string> = {{
+{paths}}}
+
+/** URL of the preview route for a document, or null when `collection` has no page. */
+export const generatePreviewPath = ({{
+  collection,
+  slug,
+}}: {{
+  collection: string
+  slug?: string | null
+}}): string | null => {{
+  const pattern = paths[collection]
+  if (pattern === undefined) return null
+  const path = pattern.replace('{{slug}}', encodeURIComponent(slug ?? ''))
+  const params = new URLSearchParams({{
+    collection,
+    slug: slug ?? '',
+    path,
+    previewSecret: process.env.{SECRET_ENV} ?? '',
+  }})
+  return `/next/preview?${{params.toString()}}`
+}}
+"
+    )
+}
+
+/// `queryBySlug`, reading the latest draft while draft mode is on.
+fn query_helper(slug_field: &str) -> String {
+    format!(
+        "import config from '@payload-config'
+import {{ draftMode }} from 'next/headers'
+import {{ getPayload, type CollectionSlug }} from 'payload'
+
+/**
+ * The document with `slug`, as its latest draft while draft mode is on.
+ * Render `<LivePreviewListener />` on the page when `draft` is true.
+ */
+export async function queryBySlug<T extends CollectionSlug>(collection: T, slug: string) {{
+  const {{ isEnabled: draft }} = await draftMode()
+  const payload = await getPayload({{ config }})
+  const result = await payload.find({{
+    collection,
+    draft,
+    limit: 1,
+    overrideAccess: draft,
+    pagination: false,
+    where: {{ {slug_field}: {{ equals: slug }} }},
+  }})
+  return {{ doc: result.docs[0] ?? null, draft }}
+}}
+"
+    )
+}
+
+/// Config entries and files for draft and live preview of the selected
+/// collections and globals.
+pub fn preview_config(options: &PreviewOptions) -> Result<PreviewConfig, String> {
+    if options.collections.is_empty() {
+        return Err("At least one collection to preview is required".to_string());
+    }
+    let globals = options.globals.as_deref().unwrap_or_default();
+    for slug in options.collections.iter().chain(globals) {
+        if !is_slug(slug) {
+            return Err(format!("Invalid slug '{slug}'"));
+        }
+    }
+    let slug_field = options.slug_field.as_deref().unwrap_or("slug");
+    let identifier = slug_field
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || c == '_');
+    if slug_field.is_empty() || !identifier || slug_field.starts_with(|c: char| c.is_ascii_digit())
+    {
+        return Err(format!("Invalid slug field '{slug_field}'"));
+    }
+    let configured = options.paths.clone().unwrap_or_default();
+    if let Some(slug) = configured
+        .keys()
+        .find(|slug| !options.collections.contains(slug) && !globals.contains(slug))
+    {
+        return Err(format!("Path given for '{slug}', which is not previewed"));
+    }
+    if let Some((slug, path)) = configured.iter().find(|(_, path)| !path.starts_with('/')) {
+        return Err(format!("Path '{path}' for '{slug}' must start with /"));
+    }
+
+    let paths: Vec<(&str, String)> = options
+        .collections
+        .iter()
+        .map(|slug| (slug, format!("/{slug}/{{slug}}")))
+        .chain(globals.iter().map(|slug| (slug, "/".to_string())))
+        .map(|(slug, default)| {
+            let path = configured.get(slug).cloned().unwrap_or(default);
+            (slug.as_str(), path)
+        })
+        .collect();
+
+    let collection_slugs: Vec<&str> = options.collections.iter().map(String::as_str).collect();
+    let global_slugs: Vec<&str> = globals.iter().map(String::as_str).collect();
+    let live_preview = format!(
+        "livePreview: {{
+    url: ({{ data, collectionConfig, globalConfig }}) =>
+      generatePreviewPath({{
+        collection: collectionConfig?.slug ?? globalConfig?.slug ?? '',
+        slug: data?.{slug_field},
+      }}) ?? '',
+    collections: [{}],
+    globals: [{}],
+{BREAKPOINTS}  }},",
+        string_list(&collection_slugs),
+        string_list(&global_slugs),
+    );
+
+    let drafts = match options.autosave_interval.unwrap_or(100) {
+        0 => "drafts: true".to_string(),
+        interval => format!("drafts: {{\n      autosave: {{ interval: {interval} }},\n    }}"),
+    };
+    let collections = options
+        .collections
+        .iter()
+        .map(|slug| {
+            let code = format!(
+                "admin: {{
+    preview: (data) => generatePreviewPath({{ collection: '{slug}', slug: data?.{slug_field} as string }}),
+  }},
+  versions: {{
+    {drafts},
+  }},"
+            );
+            (slug.clone(), code)
+        })
+        .collect();
+
+    let files = BTreeMap::from([
+        (
+            "src/utilities/generatePreviewPath.ts".to_string(),
+            preview_path_helper(&paths),
+        ),
+        (
+            "src/utilities/queryBySlug.ts".to_string(),
+            query_helper(slug_field),
+        ),
+        (
+            "src/app/(frontend)/next/preview/route.ts".to_string(),
+            PREVIEW_ROUTE.to_string(),
+        ),
+        (
+            "src/app/(frontend)/next/exit-preview/route.ts".to_string(),
+            EXIT_PREVIEW_ROUTE.to_string(),
+        ),
+        (
+            "src/components/LivePreviewListener/index.tsx".to_string(),
+            LIVE_PREVIEW_LISTENER.to_string(),
+        ),
+    ]);
+
+    Ok(PreviewConfig {
+        imports: vec![
+            "import { generatePreviewPath } from './utilities/generatePreviewPath'".to_string(),
+        ],
+        live_preview,
+        collections,
+        files,
+        dependencies: BTreeMap::from([(
+            "@payloadcms/live-preview-react".to_string(),
+            "^3.0.0".to_string(),
+        )]),
+        env: vec![
+            SECRET_ENV.to_string(),
+            server_url_env(PayloadVersion::V3).to_string(),
+        ],
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_preview_config_maps_paths() {
+        let options = PreviewOptions {
+            collections: vec!["posts".to_string(), "pages".to_string()],
+            globals: Some(vec!["header".to_string()]),
+            paths: Some(BTreeMap::from([(
+                "pages".to_string(),
+                "/{slug}".to_string(),
+            )])),
+            ..Default::default()
+        };
+        let config = preview_config(&options).unwrap();
+        assert!(
+            config
+                .live_preview
+                .contains("collections: ['posts', 'pages'],")
+        );
+        assert!(config.live_preview.contains("globals: ['header'],"));
+        let helper = &config.files["src/utilities/generatePreviewPath.ts"];
+        assert!(
+            helper
+                .contains("  'posts': '/posts/{slug}',\n  'pages': '/{slug}',\n  'header': '/',\n")
+        );
+        assert!(
+            config.collections["posts"].contains("collection: 'posts', slug: data?.slug as string")
+        );
+        assert!(config.collections["posts"].contains("autosave: { interval: 100 }"));
+        assert_eq!(config.env, vec!["PREVIEW_SECRET", "NEXT_PUBLIC_SERVER_URL"]);
+
+        let unknown = PreviewOptions {
+            paths: Some(BTreeMap::from([("authors".to_string(), "/a".to_string())])),
+            ..options
+        };
+        assert!(preview_config(&unknown).is_err());
+    }
+}
@@ -0,0 +1,230 @@
+//! Prompt library served through `prompts/list` and `prompts/get`.
+//!
+//! Each prompt declares typed arguments that are interpolated into the
+//! message text. Prompts that benefit from the rule catalog embed the
+//! relevant validation rules so the model reviews against the same checks the
+//! `validate` tool runs.
+
+use rmcp::model::{GetPromptResult, Prompt, PromptArgument, PromptMessage, PromptMessageRole};
+use serde_json::{Map, Value};
+
+use crate::payload_tools::validator::validation_rules;
+
+struct PromptSpec {
+    name: &'static str,
+    description: &'static str,
+    /// `(name, description, required)`
+    arguments: &'static [(&'static str, &'static str, bool)],
+    render: fn(&PromptArgs) -> String,
+}
+
+const PROMPTS: &[PromptSpec] = &[
+    PromptSpec {
+        name: "design_collection_schema",
+        description: "Design a Payload collection schema for a domain",
+        arguments: &[
+            ("domain", "What the collection models, e.g. 'blog posts with authors and tags'", true),
+            ("collections", "Comma-separated collection slugs to design", false),
+            ("database", "Database adapter: mongodb or postgres", false),
+            ("requirements", "Extra requirements such as localization, drafts or roles", false),
+        ],
+        render: render_design_collection,
+    },
+    PromptSpec {
+        name: "review_config_security",
+        description: "Review a Payload config or collection for security issues",
+        arguments: &[
+            ("config", "The payload.config.ts or collection source to review", true),
+            ("roles", "Comma-separated user roles the project uses", false),
+        ],
+        render: render_security_review,
+    },
+    PromptSpec {
+        name: "plan_payload_3_migration",
+        description: "Plan an upgrade of a Payload 2.x project to Payload 3",
+        arguments: &[
+            ("config", "The current payload.config.ts", false),
+            ("database", "Current database adapter: mongodb or postgres", false),
+            ("plugins", "Comma-separated plugins in use", false),
+            ("notes", "Deployment details, custom admin components or other constraints", false),
+        ],
+        render: render_migration_plan,
+    },
+    PromptSpec {
+        name: "sequential_thinking",
+        description: "Plan a Payload build step by step with the planning tools",
+        arguments: &[
+            ("goal", "What to build, e.g. 'a multi-language blog with editorial workflow'", false),
+        ],
+        render: render_sequential_thinking,
+    },
+];
+
+pub fn prompt_definitions() -> Vec<Prompt> {
+    PROMPTS
+        .iter()
+        .map(|spec| {
+            let arguments = spec
+                .arguments
+                .iter()
+                .map(|(name, description, required)| PromptArgument {
+                    name: name.to_string(),
+                    title: None,
+                    description: Some(description.to_string()),
+                    required: Some(*required),
+                })
+                .collect();
+            Prompt::new(spec.name, Some(spec.description), Some(arguments))
+        })
+        .collect()
+}
+
+/// Render a prompt by name, checking that required arguments are present.
+pub fn get_prompt(name: &str, arguments: Option<&Map<String, Value>>) -> Result<GetPromptResult, String> {
+    let spec = PROMPTS
+        .iter()
+        .find(|spec| spec.name == name)
+        .ok_or_else(|| format!("Unknown prompt: {name}"))?;
+
+    let args = PromptArgs(arguments.cloned().unwrap_or_default());
+    for (arg, _, required) in spec.arguments {
+        if *required && args.get(arg).is_none() {
+            return Err(format!("Missing required argument '{arg}' for prompt '{name}'"));
+        }
+    }
+
+    Ok(GetPromptResult {
+        description: Some(spec.description.to_string()),
+        messages: vec![PromptMessage::new_text(
+            PromptMessageRole::User,
+            (spec.render)(&args),
+        )],
+    })
+}
+
+struct PromptArgs(Map<String, Value>);
+
+impl PromptArgs {
+    /// Non-empty argument value. Clients send strings, but other JSON values are
+    /// rendered as-is.
+    fn get(&self, name: &str) -> Option<String> {
+        match self.0.get(name)? {
+            Value::String(s) if s.trim().is_empty() => None,
+            Value::String(s) => Some(s.trim().to_string()),
+            Value::Null => None,
+            other => Some(other.to_string()),
+        }
+    }
+
+    fn get_or(&self, name: &str, default: &str) -> String {
+        self.get(name).unwrap_or_else(|| default.to_string())
+    }
+
+    fn list(&self, name: &str) -> Vec<String> {
+        self.get(name)
+            .map(|value| {
+                value
+                    .split(',')
+                    .map(|item| item.trim().to_string())
+                    .filter(|item| !item.is_empty())
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+}
+
+fn rules_checklist(categories: &[&str]) -> String {
+    validation_rules()
+        .into_iter()
+        .filter(|rule| categories.contains(&rule.category.as_str()))
+        .map(|rule| format!("- {} ({}): {}", rule.name, rule.id, rule.description))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn render_design_collection(args: &PromptArgs) -> String {
+    let domain = args.get_or("domain", "");
+    let collections = args.list("collections");
+    let scope = if collections.is_empty() {
+        "Propose the set of collections and globals this domain needs.".to_string()
+    } else {
+        format!("Design these collections: {}.", collections.join(", "))
+    };
+    let database = args.get_or("database", "mongodb");
+    let requirements = args
+        .get("requirements")
+        .map(|r| format!("\nAdditional requirements: {r}\n"))
+        .unwrap_or_default();
+
+    format!(
+        "You are designing a Payload CMS schema for: {domain}.\n\n{scope}\nTarget database adapter: {database}.\n{requirements}\nFor each collection provide:\n1. The slug and a one-line purpose.\n2. Fields with name, type, required/unique/index flags and relationships (relationTo, hasMany).\n3. admin.useAsTitle and defaultColumns.\n4. Access control per operation and role.\n5. Hooks needed (e.g. slug generation) and whether versions/drafts are enabled.\n\nFollow these rules:\n{rules}\n\nFinish with the collection configs as TypeScript, then call `validate` on each one.",
+        rules = rules_checklist(&["best-practices", "data-integrity", "performance", "usability"]),
+    )
+}
+
+fn render_security_review(args: &PromptArgs) -> String {
+    let config = args.get_or("config", "");
+    let roles = args.list("roles");
+    let roles = if roles.is_empty() {
+        "Infer the roles from the config.".to_string()
+    } else {
+        format!("The project uses these roles: {}.", roles.join(", "))
+    };
+
+    format!(
+        "Review the following Payload configuration for security issues. {roles}\n\n```ts\n{config}\n```\n\nCheck in particular:\n{rules}\n- Access functions that return true unconditionally or ignore the requesting user\n- Auth collections: password policy, lockouts, token expiration and cookie settings\n- Fields exposing secrets or personal data without field-level read access\n- Hooks or endpoints that bypass access with overrideAccess\n- CORS, CSRF and serverURL settings\n\nReport each finding with severity (high/medium/low), the affected collection or field, and a corrected code snippet.",
+        rules = rules_checklist(&["security"]),
+    )
+}
+
+fn render_migration_plan(args: &PromptArgs) -> String {
+    let database = args.get_or("database", "mongodb");
+    let plugins = args.list("plugins");
+    let plugins = if plugins.is_empty() {
+        "No plugins were listed; identify any from the config.".to_string()
+    } else {
+        format!("Plugins in use: {}.", plugins.join(", "))
+    };
+    let config = args
+        .get("config")
+        .map(|c| format!("\nCurrent config:\n```ts\n{c}\n```\n"))
+        .unwrap_or_default();
+    let notes = args
+        .get("notes")
+        .map(|n| format!("\nConstraints: {n}\n"))
+        .unwrap_or_default();
+
+    format!(
+        "Plan the upgrade of a Payload 2.x project to Payload 3. Database adapter: {database}. {plugins}\n{config}{notes}\nCover, in order:\n1. Moving into a Next.js app (the `(payload)` route group, `withPayload` in next.config) and removing the Express server.\n2. Package changes: `payload` core, `@payloadcms/db-{database}`, `@payloadcms/richtext-lexical` (and migrating Slate rich text data), `@payloadcms/next`.\n3. Config changes: imports from 'payload' instead of 'payload/config', removal of `admin.bundler`, `secret` and `db` in buildConfig, `sharp` for image resizing.\n4. Breaking API changes in hooks, access functions, custom endpoints (Request/Response handlers) and custom admin components.\n5. Plugin compatibility and replacements for each listed plugin.\n6. A data migration and rollback strategy, including a staging dry run.\n\nFor each step list the files to change and a verification check."
+    )
+}
+
+fn render_sequential_thinking(args: &PromptArgs) -> String {
+    let goal = args
+        .get("goal")
+        .map(|goal| format!("The goal is: {goal}\n\n"))
+        .unwrap_or_default();
+    format!(
+        "{goal}Work through this goal one step at a time, writing down your reasoning before each decision.\n\n1. Call `start_planning` with the goal to open a plan.\n2. Clarify the requirements: content types, relationships, roles and access, localization, drafts and versions, media, integrations and deployment.\n3. Break the work into small steps (collections, globals, blocks, access control, hooks, plugins, tests) and record them with `save_plan` as a numbered list, or one by one with `add_todo` including a complexity from 0 to 10 and a code example where useful.\n4. Work through the todos in order. Generate code with `scaffold_project`, `add_to_project` and the `generate_*` tools, check it with `validate` or `validate_project`, and mark each todo done with `update_todo_status`.\n5. Revisit the plan with `get_plan` whenever a step reveals new work, and revise earlier steps if your understanding changes.\n\nDo not skip ahead: finish and validate each step before starting the next."
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_get_prompt_interpolates_and_requires_arguments() {
+        let args = json!({ "domain": "recipes", "collections": "recipes, ingredients" });
+        let result = get_prompt("design_collection_schema", args.as_object()).unwrap();
+        assert_eq!(result.messages.len(), 1);
+        let text = render_design_collection(&PromptArgs(args.as_object().cloned().unwrap()));
+        assert!(text.contains("recipes, ingredients"));
+
+        let err = get_prompt("review_config_security", None).unwrap_err();
+        assert!(err.contains("'config'"));
+        assert!(get_prompt("nope", None).is_err());
+        assert_eq!(prompt_definitions().len(), PROMPTS.len());
+    }
+}
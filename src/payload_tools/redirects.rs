@@ -0,0 +1,444 @@
+//! Redirects for a Payload 3 site on Next.js: a redirects collection, from
+//! the redirects plugin or generated, Next.js middleware answering matching
+//! requests from a cached copy of it, and optional 404 tracking counting
+//! hits per missing path.
+
+use std::collections::BTreeMap;
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, json};
+
+use crate::payload_tools::{
+    email::server_url_env,
+    plugins::{PayloadVersion, plugin_config},
+    seo::{check_slug_field, page_path_helper, page_paths},
+};
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct RedirectsOptions {
+    /// Collections a redirect may point to
+    pub collections: Vec<String>,
+    /// Use the redirects plugin (default true) rather than a generated
+    /// collection
+    pub plugin: Option<bool>,
+    /// HTTP status codes editors can choose from 301, 302, 307 and 308
+    /// (default 301 and 302); the first is the default
+    pub redirect_types: Option<Vec<String>>,
+    /// Frontend path per collection, with `{slug}` for the document's slug;
+    /// `/<collection>/{slug}` by default
+    pub paths: Option<BTreeMap<String, String>>,
+    /// Field holding the document's slug (default `slug`)
+    pub slug_field: Option<String>,
+    /// Seconds the middleware keeps the redirects before fetching them
+    /// again (default 60)
+    pub cache_seconds: Option<u32>,
+    /// Count hits on missing pages in a `not-found-hits` collection
+    /// (default false)
+    pub track_not_found: Option<bool>,
+}
+
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct RedirectsConfig {
+    pub imports: Vec<String>,
+    /// Entry for the config's `plugins` array; set unless `plugin` is false
+    pub plugin: Option<String>,
+    /// Entries for the config's `collections` array
+    pub collections: Vec<String>,
+    /// Collections, middleware and helpers by path from the project root
+    pub files: BTreeMap<String, String>,
+    /// npm packages and version ranges to add to `package.json`
+    pub dependencies: BTreeMap<String, String>,
+    /// Environment variables read by the generated code
+    pub env: Vec<String>,
+}
+
+const REDIRECT_TYPES: [&str; 4] = ["301", "302", "307", "308"];
+
+fn is_slug(value: &str) -> bool {
+    !value.is_empty()
+        && value
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
+
+fn string_list(values: &[String]) -> String {
+    values
+        .iter()
+        .map(|value| format!("'{value}'"))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+fn redirect_label(status: &str) -> &'static str {
+    match status {
+        "301" => "301 - Permanent",
+        "302" => "302 - Temporary",
+        "307" => "307 - Temporary, keeps the method",
+        _ => "308 - Permanent, keeps the method",
+    }
+}
+
+/// The collection the plugin would add, with public read access and
+/// admin-only writes.
+fn redirects_collection(collections: &[String], types: &[String]) -> String {
+    let options: String = types
+        .iter()
+        .map(|status| {
+            format!(
+                "        {{ label: '{}', value: '{status}' }},\n",
+                redirect_label(status)
+            )
+        })
+        .collect();
+    format!(
+        "import type {{ CollectionConfig }} from 'payload';
+
+const isAdmin = ({{ req: {{ user }} }}: {{ req: {{ user?: {{ role?: string }} | null }} }}) => user?.role === 'admin';
+
+/** Same shape as the redirects plugin's collection, so the middleware reads either. */
+const Redirects: CollectionConfig = {{
+  slug: 'redirects',
+  admin: {{
+    useAsTitle: 'from',
+    defaultColumns: ['from', 'to.type', 'type'],
+  }},
+  access: {{
+    read: () => true,
+    create: isAdmin,
+    update: isAdmin,
+    delete: isAdmin,
+  }},
+  fields: [
+    {{
+      name: 'from',
+      type: 'text',
+      required: true,
+      unique: true,
+      index: true,
+      admin: {{ description: 'Path on this site, e.g. /old-page' }},
+      validate: (value: unknown) =>
+        (typeof value === 'string' && value.startsWith('/')) || 'Must be a path starting with /',
+    }},
+    {{
+      name: 'to',
+      type: 'group',
+      fields: [
+        {{
+          name: 'type',
+          type: 'radio',
+          defaultValue: 'reference',
+          options: [
+            {{ label: 'Internal link', value: 'reference' }},
+            {{ label: 'Custom URL', value: 'custom' }},
+          ],
+        }},
+        {{
+          name: 'reference',
+          type: 'relationship',
+          relationTo: [{}],
+          admin: {{ condition: (_, siblingData) => siblingData?.type === 'reference' }},
+        }},
+        {{
+          name: 'url',
+          type: 'text',
+          admin: {{ condition: (_, siblingData) => siblingData?.type === 'custom' }},
+        }},
+      ],
+    }},
+    {{
+      name: 'type',
+      type: 'select',
+      required: true,
+      defaultValue: '{}',
+      options: [
+{options}      ],
+    }},
+  ],
+}};
+
+export default Redirects;
+",
+        string_list(collections),
+        types[0]
+    )
+}
+
+fn middleware(slug_field: &str, cache_seconds: u32) -> String {
+    format!(
+        "import {{ NextResponse, type NextRequest }} from 'next/server';
+
+import {{ pagePath }} from '@/utilities/pagePath';
+
+type Redirect = {{
+  from: string;
+  type?: string | null;
+  to?: {{
+    type?: 'reference' | 'custom' | null;
+    url?: string | null;
+    reference?: {{ relationTo: string; value: {{ {slug_field}?: string | null }} | string | number }} | null;
+  }} | null;
+}};
+
+type Target = {{ location: string; status: number }};
+
+const CACHE_MS = {cache_seconds} * 1000;
+let cache: {{ loadedAt: number; redirects: Map<string, Target> }} | null = null;
+
+const normalize = (path: string): string => (path.length > 1 ? path.replace(/\\/+$/, '') : path);
+
+const target = (redirect: Redirect): string | null => {{
+  const reference = redirect.to?.reference;
+  if (redirect.to?.type === 'reference' && reference && typeof reference.value === 'object') {{
+    return pagePath(reference.relationTo, reference.value.{slug_field});
+  }}
+  return redirect.to?.url || null;
+}};
+
+/** Redirects by normalized `from` path, fetched from the REST API at most every CACHE_MS. */
+const loadRedirects = async (origin: string): Promise<Map<string, Target>> => {{
+  if (cache && Date.now() - cache.loadedAt < CACHE_MS) {{
+    return cache.redirects;
+  }}
+  const redirects = new Map<string, Target>();
+  try {{
+    const response = await fetch(`${{origin}}/api/redirects?depth=1&limit=0&pagination=false`);
+    const {{ docs }} = (await response.json()) as {{ docs: Redirect[] }};
+    for (const redirect of docs) {{
+      const location = target(redirect);
+      if (location) {{
+        redirects.set(normalize(redirect.from), {{ location, status: Number(redirect.type) || 301 }});
+      }}
+    }}
+  }} catch {{
+    // Keep serving the previous copy while the API is unreachable.
+    return cache?.redirects ?? redirects;
+  }}
+  cache = {{ loadedAt: Date.now(), redirects }};
+  return redirects;
+}};
+
+export async function middleware(request: NextRequest): Promise<NextResponse> {{
+  const redirects = await loadRedirects(request.nextUrl.origin);
+  const match = redirects.get(normalize(request.nextUrl.pathname));
+  if (!match) {{
+    return NextResponse.next();
+  }}
+  return NextResponse.redirect(new URL(match.location, request.url), match.status);
+}}
+
+export const config = {{
+  // Pages only: not the API, the admin panel, Next.js assets or files.
+  matcher: ['/((?!api|admin|_next|.*\\\\..*).*)'],
+}};
+"
+    )
+}
+
+const NOT_FOUND_COLLECTION: &str = "import type { CollectionConfig } from 'payload';
+
+const MAX_PATH_LENGTH = 2048;
+
+/** Hits per missing path, written by the /track endpoint from the not-found page. */
+const NotFoundHits: CollectionConfig = {
+  slug: 'not-found-hits',
+  admin: {
+    useAsTitle: 'path',
+    defaultColumns: ['path', 'hits', 'lastSeen', 'referrer'],
+    description: 'Missing pages visitors asked for; add a redirect for the common ones.',
+  },
+  access: {
+    read: ({ req: { user } }) => Boolean(user),
+    create: () => false,
+    update: () => false,
+    delete: ({ req: { user } }) => (user as { role?: string } | null)?.role === 'admin',
+  },
+  defaultSort: '-hits',
+  fields: [
+    { name: 'path', type: 'text', required: true, unique: true, index: true },
+    { name: 'hits', type: 'number', required: true, defaultValue: 0, index: true },
+    { name: 'lastSeen', type: 'date', index: true },
+    { name: 'referrer', type: 'text' },
+  ],
+  endpoints: [
+    {
+      path: '/track',
+      method: 'post',
+      handler: async (req) => {
+        const body = (req.json ? await req.json() : {}) as { path?: unknown; referrer?: unknown };
+        const path = typeof body.path === 'string' ? body.path.split('?')[0] : '';
+        if (!path.startsWith('/') || path.length > MAX_PATH_LENGTH) {
+          return Response.json({ error: 'A path on this site is required' }, { status: 400 });
+        }
+        const referrer = typeof body.referrer === 'string' ? body.referrer.slice(0, MAX_PATH_LENGTH) : undefined;
+        const { docs } = await req.payload.find({
+          collection: 'not-found-hits',
+          where: { path: { equals: path } },
+          limit: 1,
+          depth: 0,
+        });
+        const data = { lastSeen: new Date().toISOString(), ...(referrer ? { referrer } : {}) };
+        if (docs[0]) {
+          await req.payload.update({
+            collection: 'not-found-hits',
+            id: docs[0].id,
+            data: { ...data, hits: (docs[0].hits ?? 0) + 1 },
+          });
+        } else {
+          await req.payload.create({ collection: 'not-found-hits', data: { ...data, path, hits: 1 } });
+        }
+        return new Response(null, { status: 204 });
+      },
+    },
+  ],
+};
+
+export default NotFoundHits;
+";
+
+const NOT_FOUND_TRACKER: &str = "'use client';
+
+import { usePathname } from 'next/navigation';
+import { useEffect } from 'react';
+
+/** Reports the missing path once per visit; render it in app/(frontend)/not-found.tsx. */
+export const NotFoundTracker: React.FC = () => {
+  const pathname = usePathname();
+  useEffect(() => {
+    const body = JSON.stringify({ path: pathname, referrer: document.referrer || undefined });
+    const url = '/api/not-found-hits/track';
+    if (!navigator.sendBeacon?.(url, new Blob([body], { type: 'application/json' }))) {
+      void fetch(url, { method: 'POST', headers: { 'Content-Type': 'application/json' }, body, keepalive: true });
+    }
+  }, [pathname]);
+  return null;
+};
+";
+
+/// Redirects collection or plugin call, middleware and optional 404
+/// tracking.
+pub fn redirects_config(options: &RedirectsOptions) -> Result<RedirectsConfig, String> {
+    if options.collections.is_empty() {
+        return Err("At least one collection to redirect to is required".to_string());
+    }
+    if let Some(slug) = options.collections.iter().find(|slug| !is_slug(slug)) {
+        return Err(format!("Invalid collection slug '{slug}'"));
+    }
+    let types = options
+        .redirect_types
+        .clone()
+        .unwrap_or_else(|| vec!["301".to_string(), "302".to_string()]);
+    if types.is_empty() {
+        return Err("At least one redirect type is required".to_string());
+    }
+    if let Some(status) = types
+        .iter()
+        .find(|status| !REDIRECT_TYPES.contains(&status.as_str()))
+    {
+        return Err(format!(
+            "Unknown redirect type '{status}'; expected one of {}",
+            REDIRECT_TYPES.join(", ")
+        ));
+    }
+    let slug_field = options.slug_field.as_deref().unwrap_or("slug");
+    check_slug_field(slug_field)?;
+    let paths = page_paths(&options.collections, options.paths.as_ref())?;
+    let cache_seconds = options.cache_seconds.unwrap_or(60);
+
+    let mut files = BTreeMap::from([
+        (
+            "src/utilities/pagePath.ts".to_string(),
+            page_path_helper(&paths),
+        ),
+        (
+            "src/middleware.ts".to_string(),
+            middleware(slug_field, cache_seconds),
+        ),
+    ]);
+    let mut imports = Vec::new();
+    let mut collections = Vec::new();
+    let mut dependencies = BTreeMap::new();
+    let plugin = if options.plugin.unwrap_or(true) {
+        let mut plugin_options = Map::new();
+        plugin_options.insert("collections".to_string(), json!(options.collections));
+        plugin_options.insert("redirectTypes".to_string(), json!(types));
+        let plugin = plugin_config("redirects", PayloadVersion::V3, &plugin_options, "")?;
+        imports.extend(plugin.imports);
+        dependencies = plugin.dependencies;
+        Some(plugin.code)
+    } else {
+        imports.push("import Redirects from './collections/Redirects';".to_string());
+        collections.push("Redirects".to_string());
+        files.insert(
+            "src/collections/Redirects.ts".to_string(),
+            redirects_collection(&options.collections, &types),
+        );
+        None
+    };
+    if options.track_not_found.unwrap_or(false) {
+        imports.push("import NotFoundHits from './collections/NotFoundHits';".to_string());
+        collections.push("NotFoundHits".to_string());
+        files.insert(
+            "src/collections/NotFoundHits.ts".to_string(),
+            NOT_FOUND_COLLECTION.to_string(),
+        );
+        files.insert(
+            "src/components/NotFoundTracker/index.tsx".to_string(),
+            NOT_FOUND_TRACKER.to_string(),
+        );
+    }
+
+    Ok(RedirectsConfig {
+        imports,
+        plugin,
+        collections,
+        files,
+        dependencies,
+        env: vec![server_url_env(PayloadVersion::V3).to_string()],
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_redirects_config_plugin_or_collection() {
+        let options = RedirectsOptions {
+            collections: vec!["pages".to_string(), "posts".to_string()],
+            track_not_found: Some(true),
+            ..Default::default()
+        };
+        let config = redirects_config(&options).unwrap();
+        let plugin = config.plugin.as_deref().unwrap();
+        assert!(plugin.starts_with("redirectsPlugin({"));
+        assert!(plugin.contains("redirectTypes: ['301', '302'],"));
+        assert_eq!(config.collections, vec!["NotFoundHits"]);
+        assert!(config.files["src/middleware.ts"].contains("const CACHE_MS = 60 * 1000;"));
+        assert!(
+            config
+                .files
+                .contains_key("src/components/NotFoundTracker/index.tsx")
+        );
+
+        let custom = RedirectsOptions {
+            plugin: Some(false),
+            redirect_types: Some(vec!["308".to_string()]),
+            track_not_found: None,
+            ..options.clone()
+        };
+        let config = redirects_config(&custom).unwrap();
+        assert!(config.plugin.is_none());
+        assert_eq!(config.collections, vec!["Redirects"]);
+        let collection = &config.files["src/collections/Redirects.ts"];
+        assert!(collection.contains("relationTo: ['pages', 'posts'],"));
+        assert!(collection.contains("defaultValue: '308',"));
+
+        let unknown = RedirectsOptions {
+            redirect_types: Some(vec!["303".to_string()]),
+            ..options
+        };
+        assert!(redirects_config(&unknown).is_err());
+    }
+}
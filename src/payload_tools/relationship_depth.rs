@@ -0,0 +1,305 @@
+//! Relationship cycles and the cost of populating them. Payload populates
+//! relationship and upload fields down to the request's `depth` (2 by
+//! default), so a `hasMany` field inside a cycle multiplies the documents a
+//! single read loads with every level. A field's `maxDepth` caps the depth
+//! left below it.
+
+use std::collections::HashMap;
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::payload_tools::schema_graph::{GraphEdge, SchemaGraph};
+
+/// Payload's `defaultDepth`.
+pub const DEFAULT_DEPTH: u32 = 2;
+const DEFAULT_HAS_MANY_SIZE: u64 = 10;
+const DEFAULT_MAX_DOCUMENTS: u64 = 100;
+/// Enumerating cycles is exponential in the worst case.
+const MAX_CYCLES: usize = 50;
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct DepthOptions {
+    /// Populate depth to estimate for; defaults to Payload's default of 2
+    pub depth: Option<u32>,
+    /// References assumed per `hasMany` field, default 10
+    pub has_many_size: Option<u64>,
+    /// Warn when reading one document populates more than this, default 100
+    pub max_documents: Option<u64>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct RelationshipCycle {
+    /// Slugs along the cycle, starting and ending with the same one
+    pub collections: Vec<String>,
+    /// The field followed at each step, as `slug.field`
+    pub fields: Vec<String>,
+    /// Some field on the cycle holds many references, so the documents
+    /// loaded grow exponentially with depth
+    pub exponential: bool,
+    /// Some field on the cycle sets `maxDepth`, which stops the cycle
+    pub bounded: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct PopulateCost {
+    pub slug: String,
+    /// Worst-case documents populated when reading one document at `depth`
+    pub documents: u64,
+    /// A deeper request would populate more
+    pub grows_with_depth: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct DepthAnalysis {
+    pub depth: u32,
+    pub cycles: Vec<RelationshipCycle>,
+    /// Per collection and global, most expensive first
+    pub costs: Vec<PopulateCost>,
+    pub warnings: Vec<String>,
+}
+
+/// Cycles and populate costs of `graph`, with warnings for unbounded
+/// cycles and reads that populate more than `max_documents`.
+pub fn analyze_depth(graph: &SchemaGraph, options: &DepthOptions) -> DepthAnalysis {
+    let depth = options.depth.unwrap_or(DEFAULT_DEPTH);
+    let has_many_size = options.has_many_size.unwrap_or(DEFAULT_HAS_MANY_SIZE);
+    let max_documents = options.max_documents.unwrap_or(DEFAULT_MAX_DOCUMENTS);
+    let collections: Vec<&str> = graph
+        .nodes
+        .iter()
+        .filter(|node| node.kind == "collection")
+        .map(|node| node.slug.as_str())
+        .collect();
+    let edges: Vec<&GraphEdge> = graph
+        .edges
+        .iter()
+        .filter(|edge| collections.contains(&edge.to.as_str()))
+        .collect();
+
+    let cycles = find_cycles(&collections, &edges);
+    let mut warnings: Vec<String> = cycles
+        .iter()
+        .filter(|cycle| !cycle.bounded)
+        .map(|cycle| {
+            let growth = if cycle.exponential {
+                "grows exponentially with depth"
+            } else {
+                "is populated again at every level"
+            };
+            format!(
+                "Relationship cycle {} {growth}; set maxDepth on one of {}",
+                cycle.collections.join(" -> "),
+                cycle.fields.join(", ")
+            )
+        })
+        .collect();
+
+    let mut cost = Cost {
+        edges: &edges,
+        has_many_size,
+        memo: HashMap::new(),
+    };
+    let mut costs: Vec<PopulateCost> = graph
+        .nodes
+        .iter()
+        .filter(|node| node.kind != "missing")
+        .map(|node| {
+            let documents = cost.documents(&node.slug, depth);
+            PopulateCost {
+                slug: node.slug.clone(),
+                documents,
+                grows_with_depth: cost.documents(&node.slug, depth.saturating_add(1)) > documents,
+            }
+        })
+        .collect();
+    costs.sort_by_key(|entry| std::cmp::Reverse(entry.documents));
+    warnings.extend(costs.iter().filter(|entry| entry.documents > max_documents).map(|entry| {
+        format!(
+            "Reading one {} document at depth {depth} can populate {} documents (limit {max_documents}); \
+             lower the request depth or set maxDepth on its hasMany relationships",
+            entry.slug, entry.documents
+        )
+    }));
+
+    DepthAnalysis {
+        depth,
+        cycles,
+        costs,
+        warnings,
+    }
+}
+
+/// Worst-case populate counts, memoized by slug and remaining depth.
+struct Cost<'a> {
+    edges: &'a [&'a GraphEdge],
+    has_many_size: u64,
+    memo: HashMap<(String, u32), u64>,
+}
+
+impl Cost<'_> {
+    /// Documents populated below one `slug` document with `depth` levels
+    /// left. A polymorphic field loads one of its targets per reference, so
+    /// it counts the most expensive.
+    fn documents(&mut self, slug: &str, depth: u32) -> u64 {
+        if depth == 0 {
+            return 0;
+        }
+        if let Some(count) = self.memo.get(&(slug.to_string(), depth)) {
+            return *count;
+        }
+        let edges = self.edges;
+        let mut per_field: HashMap<&str, u64> = HashMap::new();
+        for edge in edges.iter().filter(|edge| edge.from == slug) {
+            let remaining = edge.max_depth.map_or(depth, |max| max.min(depth));
+            if remaining == 0 {
+                continue;
+            }
+            let fan_out = if edge.has_many { self.has_many_size } else { 1 };
+            let below = self.documents(&edge.to, remaining - 1);
+            let count = fan_out.saturating_mul(below.saturating_add(1));
+            let entry = per_field.entry(edge.field.as_str()).or_default();
+            *entry = (*entry).max(count);
+        }
+        let total = per_field.values().fold(0u64, |sum, count| sum.saturating_add(*count));
+        self.memo.insert((slug.to_string(), depth), total);
+        total
+    }
+}
+
+/// Simple cycles between collections, each found once from its first slug
+/// in `collections` order.
+fn find_cycles(collections: &[&str], edges: &[&GraphEdge]) -> Vec<RelationshipCycle> {
+    let mut cycles = Vec::new();
+    for (start, slug) in collections.iter().enumerate() {
+        let allowed = &collections[start..];
+        let mut path = vec![*slug];
+        let mut via: Vec<&GraphEdge> = Vec::new();
+        walk(slug, allowed, edges, &mut path, &mut via, &mut cycles);
+        if cycles.len() >= MAX_CYCLES {
+            cycles.truncate(MAX_CYCLES);
+            break;
+        }
+    }
+    cycles
+}
+
+fn walk<'a>(
+    slug: &str,
+    allowed: &[&str],
+    edges: &[&'a GraphEdge],
+    path: &mut Vec<&'a str>,
+    via: &mut Vec<&'a GraphEdge>,
+    cycles: &mut Vec<RelationshipCycle>,
+) {
+    if cycles.len() >= MAX_CYCLES {
+        return;
+    }
+    for edge in edges.iter().copied().filter(|edge| edge.from == slug) {
+        if !allowed.contains(&edge.to.as_str()) {
+            continue;
+        }
+        if edge.to == path[0] {
+            via.push(edge);
+            let fields: Vec<String> = via
+                .iter()
+                .map(|edge| format!("{}.{}", edge.from, edge.field))
+                .collect();
+            let mut collections: Vec<String> = path.iter().map(|slug| slug.to_string()).collect();
+            collections.push(edge.to.clone());
+            cycles.push(RelationshipCycle {
+                collections,
+                fields,
+                exponential: via.iter().any(|edge| edge.has_many),
+                bounded: via.iter().any(|edge| edge.max_depth.is_some()),
+            });
+            via.pop();
+        } else if !path.contains(&edge.to.as_str()) {
+            path.push(&edge.to);
+            via.push(edge);
+            walk(&edge.to, allowed, edges, path, via, cycles);
+            via.pop();
+            path.pop();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::payload_tools::{
+        schema_graph::{SchemaGraphOptions, schema_graph},
+        schema_model::{SchemaCollection, SchemaField, SchemaModel},
+    };
+
+    fn relationship(name: &str, to: &str, has_many: bool) -> SchemaField {
+        SchemaField {
+            relation_to: Some(vec![to.to_string()]),
+            has_many,
+            ..SchemaField::new(name, "relationship")
+        }
+    }
+
+    fn collection(slug: &str, fields: Vec<SchemaField>) -> SchemaCollection {
+        SchemaCollection {
+            slug: slug.to_string(),
+            fields,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn finds_cycles_and_estimates_fan_out() {
+        let schema = SchemaModel {
+            collections: vec![
+                collection(
+                    "posts",
+                    vec![
+                        relationship("author", "users", false),
+                        relationship("related", "posts", true),
+                    ],
+                ),
+                collection("users", vec![relationship("posts", "posts", true)]),
+            ],
+            globals: Vec::new(),
+        };
+        let graph = schema_graph(&schema, &SchemaGraphOptions::default());
+        let analysis = analyze_depth(&graph, &DepthOptions::default());
+
+        let fields: Vec<Vec<String>> = analysis.cycles.iter().map(|cycle| cycle.fields.clone()).collect();
+        assert_eq!(
+            fields,
+            vec![
+                vec!["posts.author".to_string(), "users.posts".to_string()],
+                vec!["posts.related".to_string()],
+            ]
+        );
+        assert!(analysis.cycles.iter().all(|cycle| cycle.exponential && !cycle.bounded));
+
+        // posts at depth 2: author (1 + its 10 posts) + 10 related posts,
+        // each with an author and 10 related posts of their own.
+        let posts = analysis.costs.iter().find(|cost| cost.slug == "posts").unwrap();
+        assert_eq!(posts.documents, 11 + 10 * 12);
+        assert!(posts.grows_with_depth);
+        assert_eq!(analysis.warnings.len(), 4);
+    }
+
+    #[test]
+    fn max_depth_bounds_cycles() {
+        let mut parent = relationship("parent", "categories", false);
+        parent.max_depth = Some(1);
+        let schema = SchemaModel {
+            collections: vec![collection("categories", vec![parent])],
+            globals: Vec::new(),
+        };
+        let graph = schema_graph(&schema, &SchemaGraphOptions::default());
+        let analysis = analyze_depth(&graph, &DepthOptions::default());
+        assert!(analysis.cycles[0].bounded);
+        assert_eq!(analysis.costs[0].documents, 1);
+        assert!(!analysis.costs[0].grows_with_depth);
+        assert!(analysis.warnings.is_empty());
+    }
+}
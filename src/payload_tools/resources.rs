@@ -0,0 +1,436 @@
+//! Static MCP resources: validation rules, template option schemas and the
+//! field-type catalog.
+//!
+//! URIs:
+//! - `rule://{id}` for each validation rule
+//! - `template://{type}` for each template type and its options
+//! - `catalog://field-types` for the field-type catalog
+//! - `docs://payload/{topic}` for each bundled best-practice guide
+//!
+//! Resources render as JSON by default. Append `?format=markdown` to a URI to
+//! get a markdown rendering instead. Guides are markdown by default and
+//! `?format=json` wraps them with their metadata.
+//!
+//! Live schemas are exposed through resource templates:
+//! - `payload://{connection}/collections` lists a live instance's collections
+//! - `payload://{connection}/collections/{slug}` is one collection's schema
+//!
+//! `{connection}` is the name of a stored connection, or the instance's host
+//! (and port) with the API key read from `PAYLOAD_API_KEY`.
+//!
+//! Tool results too large to inline are held in a [`ResultStore`] and served
+//! as `result://{id}/{path}` resources.
+
+use std::collections::{HashMap, VecDeque};
+use std::sync::Mutex;
+
+use rmcp::model::{Annotated, RawResource, RawResourceTemplate, Resource, ResourceTemplate};
+use serde_json::{json, Map, Value};
+
+use crate::payload_tools::generator::TemplateType;
+use crate::payload_tools::guides::{guide_uri, require_guide, DOCS_URI_PREFIX, GUIDES};
+use crate::payload_tools::schemas::field_type_metadata;
+use crate::payload_tools::validator::validation_rules;
+
+pub const RESOURCES_PAGE_SIZE: usize = 25;
+pub const FIELD_TYPES_URI: &str = "catalog://field-types";
+/// Tool results whose JSON exceeds this many bytes are returned as resource links.
+pub const INLINE_RESULT_LIMIT: usize = 256 * 1024;
+/// Number of linked results a session keeps before evicting the oldest.
+const MAX_STORED_RESULTS: usize = 16;
+
+fn resource(uri: String, name: String, description: String, mime_type: &str) -> Resource {
+    Annotated {
+        raw: RawResource {
+            uri,
+            name: name.clone(),
+            title: Some(name),
+            description: Some(description),
+            mime_type: Some(mime_type.to_string()),
+            size: None,
+            icons: None,
+        },
+        annotations: None,
+    }
+}
+
+pub fn resource_definitions() -> Vec<Resource> {
+    let mut resources = Vec::new();
+    for rule in validation_rules() {
+        resources.push(resource(
+            format!("rule://{}", rule.id),
+            rule.name,
+            rule.description,
+            "application/json",
+        ));
+    }
+    for template in TemplateType::ALL {
+        resources.push(resource(
+            format!("template://{}", template.as_str()),
+            format!("{} template", template.as_str()),
+            template.description().to_string(),
+            "application/json",
+        ));
+    }
+    resources.push(resource(
+        FIELD_TYPES_URI.to_string(),
+        "Field types".to_string(),
+        "Catalog of Payload field types".to_string(),
+        "application/json",
+    ));
+    for guide in GUIDES {
+        resources.push(resource(
+            guide_uri(guide.topic),
+            guide.title.to_string(),
+            guide.summary.to_string(),
+            "text/markdown",
+        ));
+    }
+    resources
+}
+
+pub fn resource_templates() -> Vec<ResourceTemplate> {
+    [
+        (
+            "payload://{connection}/collections",
+            "Live collections",
+            "Collection slugs on a live Payload instance",
+        ),
+        (
+            "payload://{connection}/collections/{slug}",
+            "Live collection schema",
+            "Schema of a collection on a live Payload instance; subscribe to be notified when it changes",
+        ),
+    ]
+    .into_iter()
+    .map(|(uri_template, name, description)| Annotated {
+        raw: RawResourceTemplate {
+            uri_template: uri_template.to_string(),
+            name: name.to_string(),
+            title: Some(name.to_string()),
+            description: Some(description.to_string()),
+            mime_type: Some("application/json".to_string()),
+            icons: None,
+        },
+        annotations: None,
+    })
+    .collect()
+}
+
+/// A `payload://` URI resolved against the resource templates.
+#[derive(Debug, PartialEq)]
+pub struct LiveResource {
+    pub connection: String,
+    /// `None` for the collection list
+    pub slug: Option<String>,
+}
+
+pub fn parse_live_uri(uri: &str) -> Option<LiveResource> {
+    let (connection, path) = uri.strip_prefix("payload://")?.split_once('/')?;
+    let mut segments = path.trim_end_matches('/').split('/');
+    if connection.is_empty() || segments.next()? != "collections" {
+        return None;
+    }
+    let slug = segments.next().map(str::to_string);
+    if segments.next().is_some() || slug.as_deref() == Some("") {
+        return None;
+    }
+    Some(LiveResource {
+        connection: connection.to_string(),
+        slug,
+    })
+}
+
+/// Fetch a live schema resource, or `Ok(None)` if `uri` is not a `payload://`
+/// URI. This performs blocking HTTP requests.
+pub fn read_live_resource(uri: &str) -> Result<Option<String>, String> {
+    let Some(live) = parse_live_uri(uri) else {
+        return Ok(None);
+    };
+    let api_key = std::env::var("PAYLOAD_API_KEY").ok();
+    let client = crate::connections::client_for(&live.connection, api_key)?;
+    let value = match &live.slug {
+        Some(slug) => json!(client.get_collection(slug).map_err(|err| err.to_string())?),
+        None => json!({
+            "collections": client.list_collections().map_err(|err| err.to_string())?,
+        }),
+    };
+    Ok(Some(to_json(&value)))
+}
+
+/// Large tool results held for one session.
+#[derive(Default)]
+pub struct ResultStore {
+    inner: Mutex<StoredResults>,
+}
+
+#[derive(Default)]
+struct StoredResults {
+    next_id: u64,
+    /// Result ids, oldest first
+    order: VecDeque<u64>,
+    files: HashMap<String, String>,
+}
+
+impl ResultStore {
+    /// Move every file of a `fileStructure` tree into the store, replacing its
+    /// contents with the `result://` URI it can be read from. Returns a link
+    /// for each stored file.
+    pub fn link_files(&self, tree: &mut Value) -> Result<Vec<RawResource>, String> {
+        let mut stored = self
+            .inner
+            .lock()
+            .map_err(|_| "Result store poisoned".to_string())?;
+        let id = stored.next_id;
+        stored.next_id += 1;
+        stored.order.push_back(id);
+        if stored.order.len() > MAX_STORED_RESULTS {
+            if let Some(evicted) = stored.order.pop_front() {
+                let prefix = format!("result://{evicted}/");
+                stored.files.retain(|uri, _| !uri.starts_with(&prefix));
+            }
+        }
+
+        let mut links = Vec::new();
+        link_tree(tree, &format!("result://{id}/"), &mut stored.files, &mut links);
+        Ok(links)
+    }
+
+    pub fn read(&self, uri: &str) -> Option<String> {
+        self.inner.lock().ok()?.files.get(uri).cloned()
+    }
+}
+
+fn link_tree(
+    tree: &mut Value,
+    prefix: &str,
+    files: &mut HashMap<String, String>,
+    links: &mut Vec<RawResource>,
+) {
+    let Value::Object(entries) = tree else {
+        return;
+    };
+    for (name, entry) in entries.iter_mut() {
+        let path = format!("{prefix}{name}");
+        match entry {
+            Value::String(content) => {
+                let content = std::mem::replace(content, path.clone());
+                links.push(RawResource {
+                    uri: path.clone(),
+                    name: name.clone(),
+                    title: None,
+                    description: None,
+                    mime_type: Some("text/plain".to_string()),
+                    size: Some(content.len() as u32),
+                    icons: None,
+                });
+                files.insert(path, content);
+            }
+            nested => link_tree(nested, &format!("{path}/"), files, links),
+        }
+    }
+}
+
+/// Slice `items` into a page starting at the offset encoded in `cursor`,
+/// returning the cursor for the next page when more items remain.
+pub fn paginate<T>(
+    items: Vec<T>,
+    cursor: Option<&str>,
+    page_size: usize,
+) -> Result<(Vec<T>, Option<String>), String> {
+    let offset = match cursor {
+        Some(cursor) => cursor
+            .parse::<usize>()
+            .map_err(|_| format!("Invalid cursor: {cursor}"))?,
+        None => 0,
+    };
+    let total = items.len();
+    let page = items.into_iter().skip(offset).take(page_size).collect();
+    let next = offset + page_size;
+    Ok((page, (next < total).then(|| next.to_string())))
+}
+
+/// Render the resource at `uri`, or `Ok(None)` if it is not served here.
+pub fn read_resource(uri: &str) -> Result<Option<String>, String> {
+    let (base, query) = uri.split_once('?').unwrap_or((uri, ""));
+    if let Some(topic) = base.strip_prefix(DOCS_URI_PREFIX) {
+        let guide = require_guide(topic)?;
+        return match query {
+            "" | "format=markdown" => Ok(Some(guide.content.to_string())),
+            "format=json" => Ok(Some(to_json(&json!({
+                "topic": guide.topic,
+                "title": guide.title,
+                "summary": guide.summary,
+                "content": guide.content,
+            })))),
+            other => Err(format!("Unsupported resource query: {other}")),
+        };
+    }
+    let markdown = match query {
+        "" | "format=json" => false,
+        "format=markdown" => true,
+        other => return Err(format!("Unsupported resource query: {other}")),
+    };
+
+    if let Some(id) = base.strip_prefix("rule://") {
+        let rule = validation_rules()
+            .into_iter()
+            .find(|rule| rule.id == id)
+            .ok_or_else(|| format!("Unknown validation rule: {id}"))?;
+        return Ok(Some(if markdown {
+            let file_types = rule
+                .file_types
+                .iter()
+                .map(|ft| ft.as_str())
+                .collect::<Vec<_>>()
+                .join(", ");
+            format!(
+                "# {}\n\n{}\n\n- ID: `{}`\n- Category: {}\n- Applies to: {}\n\n## Valid\n\n{}\n\n## Invalid\n\n{}\n",
+                rule.name,
+                rule.description,
+                rule.id,
+                rule.category,
+                file_types,
+                code_blocks(&rule.examples.valid),
+                code_blocks(&rule.examples.invalid),
+            )
+        } else {
+            to_json(&json!(rule))
+        }));
+    }
+
+    if let Some(name) = base.strip_prefix("template://") {
+        let template = TemplateType::ALL
+            .into_iter()
+            .find(|t| t.as_str() == name)
+            .ok_or_else(|| format!("Unknown template type: {name}"))?;
+        return Ok(Some(if markdown {
+            let rows = template
+                .options()
+                .iter()
+                .map(|(key, description)| format!("| `{key}` | {description} |"))
+                .collect::<Vec<_>>()
+                .join("\n");
+            format!(
+                "# {} template\n\n{}\n\n| Option | Description |\n| --- | --- |\n{rows}\n",
+                template.as_str(),
+                template.description(),
+            )
+        } else {
+            let options: Map<String, Value> = template
+                .options()
+                .iter()
+                .map(|(key, description)| (key.to_string(), json!(description)))
+                .collect();
+            to_json(&json!({
+                "name": template.as_str(),
+                "description": template.description(),
+                "file_type": template.file_type(),
+                "options": options,
+            }))
+        }));
+    }
+
+    if base == FIELD_TYPES_URI {
+        let field_types = field_type_metadata();
+        return Ok(Some(if markdown {
+            let rows = field_types
+                .iter()
+                .map(|f| {
+                    format!(
+                        "| `{}` | {} | {} | {} | {} | {} |",
+                        f.name,
+                        f.category,
+                        f.description,
+                        f.required_options.join(", "),
+                        f.db_type,
+                        f.has_subfields
+                    )
+                })
+                .collect::<Vec<_>>()
+                .join("\n");
+            format!(
+                "# Field types\n\n| Type | Category | Description | Required options | Storage | Has sub-fields |\n| --- | --- | --- | --- | --- | --- |\n{rows}\n"
+            )
+        } else {
+            to_json(&json!(field_types))
+        }));
+    }
+
+    Ok(None)
+}
+
+fn code_blocks(examples: &[String]) -> String {
+    examples
+        .iter()
+        .map(|example| format!("```ts\n{example}\n```"))
+        .collect::<Vec<_>>()
+        .join("\n\n")
+}
+
+fn to_json(value: &Value) -> String {
+    serde_json::to_string_pretty(value).unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_every_listed_resource_reads() {
+        for resource in resource_definitions() {
+            let uri = &resource.raw.uri;
+            assert!(read_resource(uri).unwrap().is_some(), "{uri}");
+            assert!(read_resource(&format!("{uri}?format=markdown")).unwrap().is_some());
+        }
+        assert!(read_resource("file://instructions").unwrap().is_none());
+        assert!(read_resource("rule://missing").is_err());
+    }
+
+    #[test]
+    fn test_parse_live_uri() {
+        assert_eq!(
+            parse_live_uri("payload://localhost:3000/collections/posts"),
+            Some(LiveResource {
+                connection: "localhost:3000".to_string(),
+                slug: Some("posts".to_string()),
+            })
+        );
+        assert_eq!(
+            parse_live_uri("payload://cms.example.com/collections")
+                .unwrap()
+                .slug,
+            None
+        );
+        assert!(parse_live_uri("payload://host/collections/a/b").is_none());
+        assert!(parse_live_uri("payload://host/globals/a").is_none());
+        assert!(parse_live_uri("rule://timestamps").is_none());
+    }
+
+    #[test]
+    fn test_paginate() {
+        let (page, next) = paginate((0..30).collect(), None, 25).unwrap();
+        assert_eq!(page.len(), 25);
+        assert_eq!(next.as_deref(), Some("25"));
+        let (page, next) = paginate((0..30).collect::<Vec<_>>(), next.as_deref(), 25).unwrap();
+        assert_eq!(page, vec![25, 26, 27, 28, 29]);
+        assert!(next.is_none());
+        assert!(paginate(vec![1], Some("x"), 25).is_err());
+    }
+
+    #[test]
+    fn test_result_store_links_files() {
+        let store = ResultStore::default();
+        let mut tree = json!({ "package.json": "{}", "src": { "payload.config.ts": "export default {}" } });
+        let links = store.link_files(&mut tree).unwrap();
+        assert_eq!(links.len(), 2);
+        let uri = tree["src"]["payload.config.ts"].as_str().unwrap();
+        assert_eq!(uri, "result://0/src/payload.config.ts");
+        assert_eq!(store.read(uri).as_deref(), Some("export default {}"));
+
+        for _ in 0..MAX_STORED_RESULTS {
+            store.link_files(&mut json!({ "a": "b" })).unwrap();
+        }
+        assert!(store.read(uri).is_none());
+    }
+}
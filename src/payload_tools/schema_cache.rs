@@ -0,0 +1,313 @@
+//! Collection schemas fetched from live Payload instances, kept for a TTL so
+//! agents validating in a loop do not refetch them on every call.
+//!
+//! Entries are keyed by request URL and API key. Once an entry is older than
+//! the TTL it is revalidated with `If-None-Match` / `If-Modified-Since`, and
+//! a `304 Not Modified` keeps the cached body. With the on-disk option,
+//! snapshots are also written to `schema-cache/` in the state directory so
+//! they survive restarts.
+
+use std::{
+    collections::{HashMap, hash_map::DefaultHasher},
+    fs,
+    hash::{Hash, Hasher},
+    path::{Path, PathBuf},
+    sync::{
+        Mutex, OnceLock,
+        atomic::{AtomicU64, Ordering},
+    },
+    time::{Duration, SystemTime},
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::paths;
+
+/// Seconds a fetched schema is used without asking the server again.
+pub const DEFAULT_TTL_SECS: u64 = 300;
+const CAPACITY: usize = 256;
+const DISK_DIR: &str = "schema-cache";
+
+struct Options {
+    /// `None` disables caching
+    ttl: Option<Duration>,
+    disk_dir: Option<PathBuf>,
+}
+
+static OPTIONS: OnceLock<Options> = OnceLock::new();
+static CACHE: OnceLock<SchemaCache> = OnceLock::new();
+
+/// Set the TTL (0 disables the cache) and whether snapshots are persisted;
+/// the first call wins.
+pub fn configure(ttl_secs: u64, on_disk: bool) {
+    let _ = OPTIONS.set(Options {
+        ttl: (ttl_secs > 0).then(|| Duration::from_secs(ttl_secs)),
+        disk_dir: on_disk.then(|| paths::state_dir().join(DISK_DIR)),
+    });
+}
+
+fn options() -> &'static Options {
+    OPTIONS.get_or_init(|| Options {
+        ttl: Some(Duration::from_secs(DEFAULT_TTL_SECS)),
+        disk_dir: None,
+    })
+}
+
+fn cache() -> &'static SchemaCache {
+    CACHE.get_or_init(|| SchemaCache::new(CAPACITY))
+}
+
+/// A response body with the validators needed to revalidate it.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Snapshot {
+    pub body: String,
+    pub etag: Option<String>,
+    pub last_modified: Option<String>,
+    fetched_at: SystemTime,
+}
+
+impl Snapshot {
+    pub fn new(body: String, etag: Option<String>, last_modified: Option<String>) -> Self {
+        Self {
+            body,
+            etag,
+            last_modified,
+            fetched_at: SystemTime::now(),
+        }
+    }
+
+    fn is_fresh(&self, ttl: Duration) -> bool {
+        self.fetched_at.elapsed().is_ok_and(|age| age < ttl)
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize)]
+pub struct SchemaCacheStats {
+    pub entries: usize,
+    pub ttl_secs: u64,
+    /// Served without a request
+    pub hits: u64,
+    /// Served after a `304 Not Modified`
+    pub revalidated: u64,
+    /// Fetched in full
+    pub misses: u64,
+}
+
+/// Snapshot written to disk; `key` guards against file name collisions.
+#[derive(Serialize, Deserialize)]
+struct DiskEntry {
+    key: String,
+    snapshot: Snapshot,
+}
+
+struct SchemaCache {
+    capacity: usize,
+    entries: Mutex<HashMap<String, Snapshot>>,
+    hits: AtomicU64,
+    revalidated: AtomicU64,
+    misses: AtomicU64,
+}
+
+impl SchemaCache {
+    fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            entries: Mutex::new(HashMap::new()),
+            hits: AtomicU64::new(0),
+            revalidated: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+        }
+    }
+
+    fn get(&self, key: &str) -> Option<Snapshot> {
+        self.entries
+            .lock()
+            .ok()
+            .and_then(|entries| entries.get(key).cloned())
+    }
+
+    fn insert(&self, key: &str, snapshot: Snapshot) {
+        if let Ok(mut entries) = self.entries.lock() {
+            if entries.len() >= self.capacity && !entries.contains_key(key) {
+                let oldest = entries
+                    .iter()
+                    .min_by_key(|(_, snapshot)| snapshot.fetched_at)
+                    .map(|(key, _)| key.clone());
+                if let Some(oldest) = oldest {
+                    entries.remove(&oldest);
+                }
+            }
+            entries.insert(key.to_string(), snapshot);
+        }
+    }
+
+    /// The body for `key`: cached while fresh, otherwise from `fetch`, which
+    /// gets the previous snapshot to send its validators and returns `None`
+    /// for `304 Not Modified`.
+    fn get_or_fetch<E>(
+        &self,
+        key: &str,
+        ttl: Duration,
+        disk_dir: Option<&Path>,
+        fetch: impl FnOnce(Option<&Snapshot>) -> Result<Option<Snapshot>, E>,
+    ) -> Result<String, E> {
+        let previous = self
+            .get(key)
+            .or_else(|| disk_dir.and_then(|dir| read_disk(dir, key)));
+        if let Some(previous) = previous.as_ref().filter(|snapshot| snapshot.is_fresh(ttl)) {
+            self.hits.fetch_add(1, Ordering::Relaxed);
+            return Ok(previous.body.clone());
+        }
+        let snapshot = match (fetch(previous.as_ref())?, previous) {
+            (Some(fetched), _) => {
+                self.misses.fetch_add(1, Ordering::Relaxed);
+                fetched
+            }
+            (None, Some(previous)) => {
+                self.revalidated.fetch_add(1, Ordering::Relaxed);
+                Snapshot {
+                    fetched_at: SystemTime::now(),
+                    ..previous
+                }
+            }
+            // `fetch` only reports "not modified" for a previous snapshot.
+            (None, None) => return Ok(String::new()),
+        };
+        if let Some(dir) = disk_dir {
+            write_disk(dir, key, &snapshot);
+        }
+        let body = snapshot.body.clone();
+        self.insert(key, snapshot);
+        Ok(body)
+    }
+
+    fn stats(&self, ttl: Option<Duration>) -> SchemaCacheStats {
+        SchemaCacheStats {
+            entries: self
+                .entries
+                .lock()
+                .map(|entries| entries.len())
+                .unwrap_or(0),
+            ttl_secs: ttl.map_or(0, |ttl| ttl.as_secs()),
+            hits: self.hits.load(Ordering::Relaxed),
+            revalidated: self.revalidated.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+        }
+    }
+}
+
+fn disk_path(dir: &Path, key: &str) -> PathBuf {
+    let mut hasher = DefaultHasher::new();
+    key.hash(&mut hasher);
+    dir.join(format!("{:016x}.json", hasher.finish()))
+}
+
+fn read_disk(dir: &Path, key: &str) -> Option<Snapshot> {
+    let contents = fs::read_to_string(disk_path(dir, key)).ok()?;
+    let entry: DiskEntry = serde_json::from_str(&contents).ok()?;
+    (entry.key == key).then_some(entry.snapshot)
+}
+
+/// Best effort: a failed write only costs a refetch after restart.
+fn write_disk(dir: &Path, key: &str, snapshot: &Snapshot) {
+    let entry = DiskEntry {
+        key: key.to_string(),
+        snapshot: snapshot.clone(),
+    };
+    let written = fs::create_dir_all(dir).and_then(|_| {
+        fs::write(
+            disk_path(dir, key),
+            serde_json::to_string(&entry).unwrap_or_default(),
+        )
+    });
+    if let Err(err) = written {
+        tracing::warn!(
+            "Could not write schema snapshot to {}: {err}",
+            dir.display()
+        );
+    }
+}
+
+/// Cache key for `url` fetched with `api_key`; the key itself is hashed so
+/// it is not kept in memory or written to disk.
+pub fn key(url: &str, api_key: Option<&str>) -> String {
+    match api_key {
+        Some(api_key) => {
+            let mut hasher = DefaultHasher::new();
+            api_key.hash(&mut hasher);
+            format!("{url}#{:016x}", hasher.finish())
+        }
+        None => url.to_string(),
+    }
+}
+
+/// The body for `key`, from the cache while fresh; see
+/// [`SchemaCache::get_or_fetch`]. With the cache disabled `fetch` is always
+/// called without a previous snapshot.
+pub fn get_or_fetch<E>(
+    key: &str,
+    fetch: impl FnOnce(Option<&Snapshot>) -> Result<Option<Snapshot>, E>,
+) -> Result<String, E> {
+    let options = options();
+    match options.ttl {
+        Some(ttl) => cache().get_or_fetch(key, ttl, options.disk_dir.as_deref(), fetch),
+        None => Ok(fetch(None)?
+            .map(|snapshot| snapshot.body)
+            .unwrap_or_default()),
+    }
+}
+
+pub fn stats() -> SchemaCacheStats {
+    cache().stats(options().ttl)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_schema_cache_revalidates_stale_entries() {
+        let cache = SchemaCache::new(4);
+        let fetched = |body: &str| {
+            Ok::<_, String>(Some(Snapshot::new(
+                body.to_string(),
+                Some("\"v1\"".to_string()),
+                None,
+            )))
+        };
+
+        assert_eq!(
+            cache.get_or_fetch("posts", Duration::from_secs(60), None, |_| fetched("a")),
+            Ok("a".to_string())
+        );
+        // Fresh: served without calling fetch.
+        assert_eq!(
+            cache.get_or_fetch("posts", Duration::from_secs(60), None, |_| Err(
+                "fetched".to_string()
+            )),
+            Ok("a".to_string())
+        );
+        // Stale: fetch sees the validators and answers "not modified".
+        let revalidate = |previous: Option<&Snapshot>| {
+            assert_eq!(
+                previous.and_then(|snapshot| snapshot.etag.as_deref()),
+                Some("\"v1\"")
+            );
+            Ok::<_, String>(None)
+        };
+        assert_eq!(
+            cache.get_or_fetch("posts", Duration::ZERO, None, revalidate),
+            Ok("a".to_string())
+        );
+        assert_eq!(
+            cache.get_or_fetch("posts", Duration::ZERO, None, |_| fetched("b")),
+            Ok("b".to_string())
+        );
+
+        let stats = cache.stats(Some(Duration::from_secs(60)));
+        assert_eq!(
+            (stats.entries, stats.hits, stats.revalidated, stats.misses),
+            (1, 1, 1, 2)
+        );
+    }
+}
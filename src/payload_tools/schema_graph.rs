@@ -0,0 +1,328 @@
+//! The content model as a directed graph: a node per collection and global,
+//! an edge per relationship or upload field pointing at the collections it
+//! references. Rendered as Mermaid or Graphviz DOT for people.
+
+use std::fmt::Write;
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::payload_tools::schema_model::{SchemaField, SchemaModel};
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct SchemaGraphOptions {
+    /// Also render the graph as `mermaid` or `dot`
+    pub format: Option<GraphFormat>,
+    /// Leave globals and their edges out of the graph
+    #[serde(default)]
+    pub exclude_globals: bool,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum GraphFormat {
+    /// A Mermaid flowchart, for Markdown and GitHub
+    Mermaid,
+    /// Graphviz DOT
+    Dot,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct GraphNode {
+    pub slug: String,
+    /// `collection`, `global`, or `missing` for a referenced collection the
+    /// schema doesn't define
+    pub kind: String,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub auth: bool,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub upload: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct GraphEdge {
+    pub from: String,
+    pub to: String,
+    /// Dotted path of the field; fields inside blocks go through the block
+    /// slug, e.g. `layout.hero.image`
+    pub field: String,
+    /// `relationship` or `upload`
+    #[serde(rename = "type")]
+    pub field_type: String,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub has_many: bool,
+    /// The field may point at several collections; one edge per target
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub polymorphic: bool,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub required: bool,
+    /// The field's `maxDepth`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_depth: Option<u32>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct SchemaGraph {
+    pub nodes: Vec<GraphNode>,
+    pub edges: Vec<GraphEdge>,
+    /// The graph in the requested `format`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub diagram: Option<String>,
+    /// References to collections the schema doesn't define
+    pub warnings: Vec<String>,
+}
+
+/// Relationship and upload fields under `fields`, with their dotted paths.
+fn reference_fields<'a>(prefix: &str, fields: &'a [SchemaField], out: &mut Vec<(String, &'a SchemaField)>) {
+    for field in fields {
+        let path = if prefix.is_empty() {
+            field.name.clone()
+        } else {
+            format!("{prefix}.{}", field.name)
+        };
+        if matches!(field.field_type.as_str(), "relationship" | "upload") {
+            out.push((path.clone(), field));
+        }
+        if let Some(fields) = &field.fields {
+            reference_fields(&path, fields, out);
+        }
+        for block in field.blocks.iter().flatten() {
+            reference_fields(&format!("{path}.{}", block.slug), &block.fields, out);
+        }
+    }
+}
+
+/// The graph of `schema`, rendered in `options.format` when given.
+pub fn schema_graph(schema: &SchemaModel, options: &SchemaGraphOptions) -> SchemaGraph {
+    let mut nodes: Vec<GraphNode> = schema
+        .collections
+        .iter()
+        .map(|collection| GraphNode {
+            slug: collection.slug.clone(),
+            kind: "collection".to_string(),
+            auth: collection.auth,
+            upload: collection.upload,
+        })
+        .collect();
+    let mut sources: Vec<(&str, &[SchemaField])> = schema
+        .collections
+        .iter()
+        .map(|collection| (collection.slug.as_str(), collection.fields.as_slice()))
+        .collect();
+    if !options.exclude_globals {
+        nodes.extend(schema.globals.iter().map(|global| GraphNode {
+            slug: global.slug.clone(),
+            kind: "global".to_string(),
+            auth: false,
+            upload: false,
+        }));
+        sources.extend(
+            schema
+                .globals
+                .iter()
+                .map(|global| (global.slug.as_str(), global.fields.as_slice())),
+        );
+    }
+
+    let mut edges = Vec::new();
+    let mut warnings = Vec::new();
+    for (from, fields) in sources {
+        let mut references = Vec::new();
+        reference_fields("", fields, &mut references);
+        for (path, field) in references {
+            let targets = field.relation_to.as_deref().unwrap_or_default();
+            if targets.is_empty() {
+                warnings.push(format!("{from}.{path}: {} field has no relationTo", field.field_type));
+            }
+            for to in targets {
+                if schema.collection(to).is_none() {
+                    warnings.push(format!("{from}.{path}: relationTo '{to}' is not a collection in the schema"));
+                    if nodes.iter().all(|node| &node.slug != to) {
+                        nodes.push(GraphNode {
+                            slug: to.clone(),
+                            kind: "missing".to_string(),
+                            auth: false,
+                            upload: false,
+                        });
+                    }
+                }
+                edges.push(GraphEdge {
+                    from: from.to_string(),
+                    to: to.clone(),
+                    field: path.clone(),
+                    field_type: field.field_type.clone(),
+                    has_many: field.has_many,
+                    polymorphic: targets.len() > 1,
+                    required: field.required,
+                    max_depth: field.max_depth,
+                });
+            }
+        }
+    }
+
+    let mut graph = SchemaGraph {
+        nodes,
+        edges,
+        diagram: None,
+        warnings,
+    };
+    graph.diagram = options.format.map(|format| graph.render(format));
+    graph
+}
+
+impl SchemaGraph {
+    pub fn render(&self, format: GraphFormat) -> String {
+        match format {
+            GraphFormat::Mermaid => self.mermaid(),
+            GraphFormat::Dot => self.dot(),
+        }
+    }
+
+    /// Edge label: the field path, `[]` when it holds many references.
+    fn label(edge: &GraphEdge) -> String {
+        if edge.has_many {
+            format!("{}[]", edge.field)
+        } else {
+            edge.field.clone()
+        }
+    }
+
+    /// Mermaid node IDs can't hold hyphens, so slugs are shown as labels.
+    fn mermaid(&self) -> String {
+        let id = |slug: &str| slug.replace(|c: char| !c.is_ascii_alphanumeric(), "_");
+        let mut out = String::from("flowchart LR\n");
+        for node in &self.nodes {
+            let (open, close) = match node.kind.as_str() {
+                "global" => ("([", "])"),
+                "missing" => ("{{", "}}"),
+                _ => ("[", "]"),
+            };
+            let _ = writeln!(out, "  {}{open}\"{}\"{close}", id(&node.slug), node.slug);
+        }
+        for edge in &self.edges {
+            let arrow = if edge.field_type == "upload" { "-.->" } else { "-->" };
+            let _ = writeln!(
+                out,
+                "  {} {arrow}|\"{}\"| {}",
+                id(&edge.from),
+                Self::label(edge),
+                id(&edge.to)
+            );
+        }
+        out
+    }
+
+    fn dot(&self) -> String {
+        let mut out = String::from("digraph schema {\n  rankdir=LR;\n  node [shape=box];\n");
+        for node in &self.nodes {
+            let attributes = match node.kind.as_str() {
+                "global" => " [shape=ellipse]",
+                "missing" => " [style=dashed, color=red]",
+                _ => "",
+            };
+            let _ = writeln!(out, "  \"{}\"{attributes};", node.slug);
+        }
+        for edge in &self.edges {
+            let style = if edge.field_type == "upload" { ", style=dashed" } else { "" };
+            let _ = writeln!(
+                out,
+                "  \"{}\" -> \"{}\" [label=\"{}\"{style}];",
+                edge.from,
+                edge.to,
+                Self::label(edge)
+            );
+        }
+        out.push_str("}\n");
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::payload_tools::{schema_model::SchemaModel, types::SourceFile};
+
+    const CONFIG: &str = r#"{
+        "collections": [
+            {
+                "slug": "posts",
+                "fields": [
+                    { "name": "title", "type": "text" },
+                    { "name": "author", "type": "relationship", "relationTo": "users", "required": true },
+                    { "name": "tags", "type": "relationship", "relationTo": ["tags", "categories"], "hasMany": true },
+                    {
+                        "name": "layout",
+                        "type": "blocks",
+                        "blocks": [
+                            { "slug": "hero", "fields": [{ "name": "image", "type": "upload", "relationTo": "media" }] }
+                        ]
+                    }
+                ]
+            },
+            { "slug": "users", "auth": true, "fields": [] },
+            { "slug": "media", "upload": true, "fields": [] },
+            { "slug": "tags", "fields": [{ "name": "name", "type": "text" }] }
+        ],
+        "globals": [
+            { "slug": "site-settings", "fields": [{ "name": "featured", "type": "relationship", "relationTo": "posts" }] }
+        ]
+    }"#;
+
+    fn schema() -> SchemaModel {
+        SchemaModel::from_sources(&[SourceFile {
+            path: Some("payload.config.json".to_string()),
+            code: CONFIG.to_string(),
+        }])
+        .unwrap()
+    }
+
+    #[test]
+    fn extracts_edges_from_nested_and_polymorphic_fields() {
+        let graph = schema_graph(&schema(), &SchemaGraphOptions::default());
+        let edges: Vec<(&str, &str, &str)> = graph
+            .edges
+            .iter()
+            .map(|edge| (edge.from.as_str(), edge.field.as_str(), edge.to.as_str()))
+            .collect();
+        assert_eq!(
+            edges,
+            vec![
+                ("posts", "author", "users"),
+                ("posts", "tags", "tags"),
+                ("posts", "tags", "categories"),
+                ("posts", "layout.hero.image", "media"),
+                ("site-settings", "featured", "posts"),
+            ]
+        );
+        assert!(graph.edges[1].polymorphic && graph.edges[1].has_many);
+        assert!(graph.edges[0].required);
+        assert_eq!(graph.edges[3].field_type, "upload");
+
+        let missing = graph.nodes.iter().find(|node| node.slug == "categories").unwrap();
+        assert_eq!(missing.kind, "missing");
+        assert_eq!(graph.warnings.len(), 1);
+        assert!(graph.diagram.is_none());
+    }
+
+    #[test]
+    fn renders_mermaid_and_dot() {
+        let options = SchemaGraphOptions {
+            format: Some(GraphFormat::Mermaid),
+            exclude_globals: true,
+        };
+        let graph = schema_graph(&schema(), &options);
+        assert!(graph.nodes.iter().all(|node| node.kind != "global"));
+        let mermaid = graph.diagram.as_deref().unwrap();
+        assert!(mermaid.starts_with("flowchart LR\n"));
+        assert!(mermaid.contains("  posts -->|\"tags[]\"| tags\n"));
+        assert!(mermaid.contains("  posts -.->|\"layout.hero.image\"| media\n"));
+        assert!(!mermaid.contains("site_settings"));
+
+        let dot = graph.render(GraphFormat::Dot);
+        assert!(dot.contains("  \"posts\" -> \"users\" [label=\"author\"];\n"));
+        assert!(dot.contains("  \"categories\" [style=dashed, color=red];\n"));
+        assert!(dot.ends_with("}\n"));
+    }
+}
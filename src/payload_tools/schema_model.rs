@@ -0,0 +1,385 @@
+//! A normalized Payload schema: collections and globals with their fields,
+//! shaped like the config so it serializes into what the schema validator
+//! checks. Built from config sources, scaffold options, a live instance, or
+//! a generated `payload-types.ts` when the config itself isn't at hand.
+
+use std::path::Path;
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+use crate::payload_tools::{
+    client::{CollectionInfo, FieldInfo, PayloadClient},
+    scaffolder::{FieldOption, ScaffoldOptions},
+    schemas::{validate_collection_schema, validate_global_schema},
+    types::{FileType, SourceFile},
+    validator::parse_payload_object,
+};
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct SchemaField {
+    pub name: String,
+    #[serde(rename = "type")]
+    pub field_type: String,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub required: bool,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub unique: bool,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub has_many: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub relation_to: Option<Vec<String>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub options: Option<Vec<String>>,
+    /// Sub-fields of `group` and `array` fields
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fields: Option<Vec<SchemaField>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub blocks: Option<Vec<SchemaBlock>>,
+    /// Population depth limit of a relationship or upload field
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_depth: Option<u32>,
+    /// Stores a value per locale
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub localized: bool,
+}
+
+impl SchemaField {
+    pub fn new(name: &str, field_type: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            field_type: field_type.to_string(),
+            ..Default::default()
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct SchemaBlock {
+    pub slug: String,
+    pub fields: Vec<SchemaField>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct SchemaCollection {
+    pub slug: String,
+    pub fields: Vec<SchemaField>,
+    #[serde(default)]
+    pub timestamps: bool,
+    /// Auth collection; its built-in fields are not listed
+    #[serde(default)]
+    pub auth: bool,
+    /// Upload collection; its built-in fields are not listed
+    #[serde(default)]
+    pub upload: bool,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct SchemaGlobal {
+    pub slug: String,
+    pub fields: Vec<SchemaField>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct SchemaModel {
+    pub collections: Vec<SchemaCollection>,
+    pub globals: Vec<SchemaGlobal>,
+}
+
+impl SchemaModel {
+    pub fn collection(&self, slug: &str) -> Option<&SchemaCollection> {
+        self.collections
+            .iter()
+            .find(|collection| collection.slug == slug)
+    }
+
+    /// Schema validation errors, prefixed with the collection or global.
+    pub fn validate(&self) -> Vec<String> {
+        let mut errors = Vec::new();
+        for collection in &self.collections {
+            // Auth and upload collections may have only built-in fields.
+            if collection.fields.is_empty() && (collection.auth || collection.upload) {
+                continue;
+            }
+            let value = serde_json::to_value(collection).unwrap_or_default();
+            if let Err(err) = validate_collection_schema(&value) {
+                errors.push(format!("collections.{}: {err}", collection.slug));
+            }
+        }
+        for global in &self.globals {
+            let value = serde_json::to_value(global).unwrap_or_default();
+            if let Err(err) = validate_global_schema(&value) {
+                errors.push(format!("globals.{}: {err}", global.slug));
+            }
+        }
+        errors
+    }
+}
+
+impl SchemaModel {
+    /// The collections and globals defined in config, collection and global
+    /// sources. A slug defined twice keeps its first definition.
+    pub fn from_sources(sources: &[SourceFile]) -> Result<Self, String> {
+        let model = Self::from_objects(config_objects(sources));
+        if model.collections.is_empty() && model.globals.is_empty() {
+            return Err("No collections or globals found in the sources".to_string());
+        }
+        Ok(model)
+    }
+
+    /// The collections and globals defined inline in a root config object.
+    pub fn from_config(config: &Value) -> Self {
+        let mut objects = Vec::new();
+        config_entries(config, &mut objects);
+        Self::from_objects(objects)
+    }
+
+    fn from_objects(objects: Vec<(FileType, Map<String, Value>)>) -> Self {
+        let mut model = Self::default();
+        for (file_type, object) in objects {
+            let Some(slug) = object.get("slug").and_then(Value::as_str) else {
+                continue;
+            };
+            let fields = config_fields(
+                object
+                    .get("fields")
+                    .and_then(Value::as_array)
+                    .map_or(&[], Vec::as_slice),
+            );
+            let enabled = |key: &str| {
+                object
+                    .get(key)
+                    .is_some_and(|value| value != &Value::Bool(false))
+            };
+            if file_type == FileType::Global {
+                if model.globals.iter().all(|global| global.slug != slug) {
+                    model.globals.push(SchemaGlobal {
+                        slug: slug.to_string(),
+                        fields,
+                    });
+                }
+            } else if model.collection(slug).is_none() {
+                model.collections.push(SchemaCollection {
+                    slug: slug.to_string(),
+                    fields,
+                    timestamps: object.get("timestamps") != Some(&Value::Bool(false)),
+                    auth: enabled("auth"),
+                    upload: enabled("upload"),
+                });
+            }
+        }
+        model
+    }
+}
+
+/// Collection and global config objects in `sources`, tagged with their
+/// type: the entries of a root config's `collections` and `globals` (those
+/// given as imported identifiers are left to their own files) and whole
+/// collection and global files.
+pub(crate) fn config_objects(sources: &[SourceFile]) -> Vec<(FileType, Map<String, Value>)> {
+    let mut objects = Vec::new();
+    for source in sources {
+        let path = Path::new(source.path.as_deref().unwrap_or_default());
+        let Some(file_type) = FileType::detect(path, &source.code) else {
+            continue;
+        };
+        let Ok(Value::Object(object)) = parse_payload_object(&source.code) else {
+            continue;
+        };
+        match file_type {
+            FileType::Config => config_entries(&Value::Object(object), &mut objects),
+            FileType::Collection | FileType::Global => objects.push((file_type, object)),
+            FileType::Field => {}
+        }
+    }
+    objects
+}
+
+/// Inline entries of a root config's `collections` and `globals`.
+fn config_entries(config: &Value, objects: &mut Vec<(FileType, Map<String, Value>)>) {
+    for (key, kind) in [
+        ("collections", FileType::Collection),
+        ("globals", FileType::Global),
+    ] {
+        let items = config
+            .get(key)
+            .and_then(Value::as_array)
+            .into_iter()
+            .flatten();
+        objects.extend(items.filter_map(|item| Some((kind, item.as_object()?.clone()))));
+    }
+}
+
+/// Fields of a config `fields` array. Unnamed `row`, `collapsible` and tab
+/// fields contribute their sub-fields in place, as they do in the data; a
+/// named tab becomes a group.
+fn config_fields(fields: &[Value]) -> Vec<SchemaField> {
+    let sub_fields = |value: &Value| {
+        config_fields(
+            value
+                .get("fields")
+                .and_then(Value::as_array)
+                .map_or(&[], Vec::as_slice),
+        )
+    };
+    let mut out = Vec::new();
+    for field in fields {
+        let Some(field_type) = field.get("type").and_then(Value::as_str) else {
+            continue;
+        };
+        let name = field.get("name").and_then(Value::as_str);
+        if field_type == "tabs" {
+            for tab in field
+                .get("tabs")
+                .and_then(Value::as_array)
+                .into_iter()
+                .flatten()
+            {
+                match tab.get("name").and_then(Value::as_str) {
+                    Some(name) => out.push(SchemaField {
+                        fields: Some(sub_fields(tab)),
+                        ..SchemaField::new(name, "group")
+                    }),
+                    None => out.extend(sub_fields(tab)),
+                }
+            }
+            continue;
+        }
+        let Some(name) = name else {
+            out.extend(sub_fields(field));
+            continue;
+        };
+        let strings = |value: &Value| -> Option<Vec<String>> {
+            match value {
+                Value::String(value) => Some(vec![value.clone()]),
+                Value::Array(items) => Some(
+                    items
+                        .iter()
+                        .filter_map(|item| {
+                            item.as_str()
+                                .or_else(|| item.get("value")?.as_str())
+                                .map(str::to_string)
+                        })
+                        .collect(),
+                ),
+                _ => None,
+            }
+        };
+        let flag = |key: &str| field.get(key).and_then(Value::as_bool).unwrap_or(false);
+        out.push(SchemaField {
+            required: flag("required"),
+            unique: flag("unique"),
+            has_many: flag("hasMany"),
+            localized: flag("localized"),
+            max_depth: field
+                .get("maxDepth")
+                .and_then(Value::as_u64)
+                .and_then(|depth| u32::try_from(depth).ok()),
+            relation_to: field.get("relationTo").and_then(strings),
+            options: field.get("options").and_then(strings),
+            fields: field.get("fields").map(|_| sub_fields(field)),
+            blocks: field.get("blocks").and_then(Value::as_array).map(|blocks| {
+                blocks
+                    .iter()
+                    .filter_map(|block| {
+                        Some(SchemaBlock {
+                            slug: block.get("slug")?.as_str()?.to_string(),
+                            fields: sub_fields(block),
+                        })
+                    })
+                    .collect()
+            }),
+            ..SchemaField::new(name, field_type)
+        });
+    }
+    out
+}
+
+impl From<&FieldOption> for SchemaField {
+    fn from(field: &FieldOption) -> Self {
+        Self {
+            required: field.required.unwrap_or(false),
+            unique: field.unique.unwrap_or(false),
+            localized: field.localized.unwrap_or(false),
+            ..Self::new(&field.name, &field.field_type)
+        }
+    }
+}
+
+impl From<&FieldInfo> for SchemaField {
+    fn from(field: &FieldInfo) -> Self {
+        Self {
+            required: field.required,
+            unique: field.unique,
+            localized: field.localized,
+            ..Self::new(&field.name, &field.field_type)
+        }
+    }
+}
+
+impl From<&CollectionInfo> for SchemaCollection {
+    fn from(collection: &CollectionInfo) -> Self {
+        Self {
+            slug: collection.slug.clone(),
+            fields: collection.fields.iter().map(SchemaField::from).collect(),
+            timestamps: collection.timestamps,
+            auth: collection.auth.is_some(),
+            upload: false,
+        }
+    }
+}
+
+impl From<&ScaffoldOptions> for SchemaModel {
+    fn from(options: &ScaffoldOptions) -> Self {
+        let fields = |fields: &Option<Vec<FieldOption>>| {
+            fields
+                .iter()
+                .flatten()
+                .map(SchemaField::from)
+                .collect::<Vec<_>>()
+        };
+        Self {
+            collections: options
+                .collections
+                .iter()
+                .flatten()
+                .map(|collection| SchemaCollection {
+                    slug: collection.name.clone(),
+                    fields: fields(&collection.fields),
+                    timestamps: collection.timestamps.unwrap_or(true),
+                    auth: collection.auth.unwrap_or(false),
+                    upload: false,
+                })
+                .collect(),
+            globals: options
+                .globals
+                .iter()
+                .flatten()
+                .map(|global| SchemaGlobal {
+                    slug: global.name.clone(),
+                    fields: fields(&global.fields),
+                })
+                .collect(),
+        }
+    }
+}
+
+/// Every collection of a live instance. The REST API doesn't list globals.
+pub fn live_schema(client: &PayloadClient) -> Result<SchemaModel, String> {
+    let slugs = client.list_collections().map_err(|err| err.to_string())?;
+    let collections = slugs
+        .iter()
+        .map(|slug| {
+            client
+                .get_collection(slug)
+                .map(|collection| SchemaCollection::from(&collection))
+                .map_err(|err| err.to_string())
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(SchemaModel {
+        collections,
+        globals: Vec::new(),
+    })
+}
@@ -0,0 +1,669 @@
+//! Versioned snapshots of a project's schema, stored with the planning data
+//! so a team can follow how its content model evolved. Two versions are
+//! compared field by field, and the differences become a Payload migration
+//! that moves existing documents to the newer shape.
+
+use std::collections::BTreeSet;
+
+use chrono::DateTime;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+
+use crate::payload_tools::{
+    schema_model::{SchemaBlock, SchemaField, SchemaModel},
+    storage::{Storage, StoredSchema},
+};
+
+/// A registered schema snapshot, without the schema itself.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct SchemaVersion {
+    /// Registry name, usually the project
+    pub name: String,
+    /// 1 for the first snapshot of `name`
+    pub version: u32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+    pub recorded_at: String,
+    pub collections: usize,
+    pub globals: usize,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub enum ChangeKind {
+    Added,
+    Removed,
+    TypeChanged,
+    RequiredAdded,
+    RequiredRemoved,
+    UniqueAdded,
+    UniqueRemoved,
+    HasManyChanged,
+    LocalizedChanged,
+    OptionsChanged,
+    RelationChanged,
+    /// `timestamps`, `auth` or `upload` of a collection
+    SettingChanged,
+}
+
+/// One difference between two schemas.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct SchemaChange {
+    pub kind: ChangeKind,
+    /// Collection or global slug
+    pub slug: String,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub global: bool,
+    /// Dotted path of the field, through groups, arrays and blocks; absent
+    /// for the collection or global itself
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub field: Option<String>,
+    pub detail: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub before: Option<Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub after: Option<Value>,
+}
+
+/// Result of registering a schema.
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
+pub struct SchemaRegistration {
+    pub version: SchemaVersion,
+    /// False when the schema matched the latest version and was not stored again
+    pub created: bool,
+    /// Changes since the previous version
+    pub changes: Vec<SchemaChange>,
+}
+
+/// Changes between two registered versions and the migration for them.
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
+pub struct SchemaVersionDiff {
+    pub from: SchemaVersion,
+    pub to: SchemaVersion,
+    pub changes: Vec<SchemaChange>,
+    /// Path of the migration under the project
+    pub migration_path: String,
+    pub migration: String,
+}
+
+struct Differ {
+    slug: String,
+    global: bool,
+    changes: Vec<SchemaChange>,
+}
+
+impl Differ {
+    fn push(
+        &mut self,
+        kind: ChangeKind,
+        field: Option<&str>,
+        detail: String,
+        before: Option<Value>,
+        after: Option<Value>,
+    ) {
+        self.changes.push(SchemaChange {
+            kind,
+            slug: self.slug.clone(),
+            global: self.global,
+            field: field.map(str::to_string),
+            detail,
+            before,
+            after,
+        });
+    }
+
+    fn fields(&mut self, prefix: &str, from: &[SchemaField], to: &[SchemaField]) {
+        for old in from {
+            let path = format!("{prefix}{}", old.name);
+            match to.iter().find(|field| field.name == old.name) {
+                Some(new) => self.field(&path, old, new),
+                None => self.push(
+                    ChangeKind::Removed,
+                    Some(&path),
+                    format!("Field `{path}` ({}) removed", old.field_type),
+                    Some(json!(old)),
+                    None,
+                ),
+            }
+        }
+        for new in to
+            .iter()
+            .filter(|new| from.iter().all(|old| old.name != new.name))
+        {
+            let path = format!("{prefix}{}", new.name);
+            let required = if new.required { "required " } else { "" };
+            self.push(
+                ChangeKind::Added,
+                Some(&path),
+                format!("{required}{} field `{path}` added", new.field_type),
+                None,
+                Some(json!(new)),
+            );
+        }
+    }
+
+    fn field(&mut self, path: &str, old: &SchemaField, new: &SchemaField) {
+        if old.field_type != new.field_type {
+            self.push(
+                ChangeKind::TypeChanged,
+                Some(path),
+                format!(
+                    "`{path}` changed from {} to {}",
+                    old.field_type, new.field_type
+                ),
+                Some(json!(old.field_type)),
+                Some(json!(new.field_type)),
+            );
+            return;
+        }
+        let flags = [
+            (
+                old.required,
+                new.required,
+                ChangeKind::RequiredAdded,
+                ChangeKind::RequiredRemoved,
+                "required",
+            ),
+            (
+                old.unique,
+                new.unique,
+                ChangeKind::UniqueAdded,
+                ChangeKind::UniqueRemoved,
+                "unique",
+            ),
+        ];
+        for (before, after, added, removed, flag) in flags {
+            if before != after {
+                let (kind, verb) = if after {
+                    (added, "is now")
+                } else {
+                    (removed, "is no longer")
+                };
+                self.push(
+                    kind,
+                    Some(path),
+                    format!("`{path}` {verb} {flag}"),
+                    Some(json!(before)),
+                    Some(json!(after)),
+                );
+            }
+        }
+        if old.has_many != new.has_many {
+            let detail = if new.has_many {
+                "now holds many values"
+            } else {
+                "now holds a single value"
+            };
+            self.push(
+                ChangeKind::HasManyChanged,
+                Some(path),
+                format!("`{path}` {detail}"),
+                Some(json!(old.has_many)),
+                Some(json!(new.has_many)),
+            );
+        }
+        if old.localized != new.localized {
+            let detail = if new.localized {
+                "is now localized"
+            } else {
+                "is no longer localized"
+            };
+            self.push(
+                ChangeKind::LocalizedChanged,
+                Some(path),
+                format!("`{path}` {detail}"),
+                Some(json!(old.localized)),
+                Some(json!(new.localized)),
+            );
+        }
+        if old.options != new.options {
+            let before: BTreeSet<&String> = old.options.iter().flatten().collect();
+            let after: BTreeSet<&String> = new.options.iter().flatten().collect();
+            let removed: Vec<&str> = before
+                .difference(&after)
+                .map(|option| option.as_str())
+                .collect();
+            let added: Vec<&str> = after
+                .difference(&before)
+                .map(|option| option.as_str())
+                .collect();
+            let mut detail = Vec::new();
+            if !removed.is_empty() {
+                detail.push(format!("removed {}", removed.join(", ")));
+            }
+            if !added.is_empty() {
+                detail.push(format!("added {}", added.join(", ")));
+            }
+            if detail.is_empty() {
+                detail.push("reordered".to_string());
+            }
+            self.push(
+                ChangeKind::OptionsChanged,
+                Some(path),
+                format!("Options of `{path}`: {}", detail.join("; ")),
+                Some(json!(old.options)),
+                Some(json!(new.options)),
+            );
+        }
+        if old.relation_to != new.relation_to {
+            self.push(
+                ChangeKind::RelationChanged,
+                Some(path),
+                format!(
+                    "`{path}` now relates to {}",
+                    new.relation_to.as_deref().unwrap_or_default().join(", ")
+                ),
+                Some(json!(old.relation_to)),
+                Some(json!(new.relation_to)),
+            );
+        }
+        self.fields(
+            &format!("{path}."),
+            old.fields.as_deref().unwrap_or_default(),
+            new.fields.as_deref().unwrap_or_default(),
+        );
+        self.blocks(
+            path,
+            old.blocks.as_deref().unwrap_or_default(),
+            new.blocks.as_deref().unwrap_or_default(),
+        );
+    }
+
+    fn blocks(&mut self, path: &str, from: &[SchemaBlock], to: &[SchemaBlock]) {
+        for old in from {
+            let block_path = format!("{path}.{}", old.slug);
+            match to.iter().find(|block| block.slug == old.slug) {
+                Some(new) => self.fields(&format!("{block_path}."), &old.fields, &new.fields),
+                None => self.push(
+                    ChangeKind::Removed,
+                    Some(&block_path),
+                    format!("Block `{}` removed from `{path}`", old.slug),
+                    Some(json!(old)),
+                    None,
+                ),
+            }
+        }
+        for new in to
+            .iter()
+            .filter(|new| from.iter().all(|old| old.slug != new.slug))
+        {
+            self.push(
+                ChangeKind::Added,
+                Some(&format!("{path}.{}", new.slug)),
+                format!("Block `{}` added to `{path}`", new.slug),
+                None,
+                Some(json!(new)),
+            );
+        }
+    }
+}
+
+/// Differences that turn `from` into `to`, collections before globals.
+pub fn diff_schemas(from: &SchemaModel, to: &SchemaModel) -> Vec<SchemaChange> {
+    let mut changes = Vec::new();
+    for old in &from.collections {
+        let mut differ = Differ {
+            slug: old.slug.clone(),
+            global: false,
+            changes: Vec::new(),
+        };
+        match to.collection(&old.slug) {
+            Some(new) => {
+                let settings = [
+                    ("timestamps", old.timestamps, new.timestamps),
+                    ("auth", old.auth, new.auth),
+                    ("upload", old.upload, new.upload),
+                ];
+                for (setting, before, after) in settings {
+                    if before != after {
+                        differ.push(
+                            ChangeKind::SettingChanged,
+                            None,
+                            format!("`{setting}` turned {}", if after { "on" } else { "off" }),
+                            Some(json!(before)),
+                            Some(json!(after)),
+                        );
+                    }
+                }
+                differ.fields("", &old.fields, &new.fields);
+            }
+            None => differ.push(
+                ChangeKind::Removed,
+                None,
+                format!("Collection `{}` removed", old.slug),
+                None,
+                None,
+            ),
+        }
+        changes.extend(differ.changes);
+    }
+    for new in to
+        .collections
+        .iter()
+        .filter(|new| from.collection(&new.slug).is_none())
+    {
+        let mut differ = Differ {
+            slug: new.slug.clone(),
+            global: false,
+            changes: Vec::new(),
+        };
+        differ.push(
+            ChangeKind::Added,
+            None,
+            format!("Collection `{}` added", new.slug),
+            None,
+            None,
+        );
+        changes.extend(differ.changes);
+    }
+    for old in &from.globals {
+        let mut differ = Differ {
+            slug: old.slug.clone(),
+            global: true,
+            changes: Vec::new(),
+        };
+        match to.globals.iter().find(|global| global.slug == old.slug) {
+            Some(new) => differ.fields("", &old.fields, &new.fields),
+            None => differ.push(
+                ChangeKind::Removed,
+                None,
+                format!("Global `{}` removed", old.slug),
+                None,
+                None,
+            ),
+        }
+        changes.extend(differ.changes);
+    }
+    for new in &to.globals {
+        if from.globals.iter().all(|old| old.slug != new.slug) {
+            let mut differ = Differ {
+                slug: new.slug.clone(),
+                global: true,
+                changes: Vec::new(),
+            };
+            differ.push(
+                ChangeKind::Added,
+                None,
+                format!("Global `{}` added", new.slug),
+                None,
+                None,
+            );
+            changes.extend(differ.changes);
+        }
+    }
+    changes
+}
+
+/// Store `schema` as the next version of `name`, reporting what changed.
+pub fn register_schema(
+    storage: &Storage,
+    name: &str,
+    label: Option<&str>,
+    schema: &SchemaModel,
+) -> Result<SchemaRegistration, String> {
+    let ((version, _), created, previous) = storage.register_schema(name, label, schema)?;
+    let changes = match previous {
+        Some((_, previous)) if created => diff_schemas(&previous, schema),
+        _ => Vec::new(),
+    };
+    Ok(SchemaRegistration {
+        version,
+        created,
+        changes,
+    })
+}
+
+/// Versions `from` and `to` of `name` with their schemas; `to` defaults to
+/// the latest and `from` to the version before it.
+pub(crate) fn version_pair(
+    storage: &Storage,
+    name: &str,
+    from: Option<u32>,
+    to: Option<u32>,
+) -> Result<(StoredSchema, StoredSchema), String> {
+    let to = storage.schema_version(name, to)?;
+    let from = match from {
+        Some(from) => from,
+        None if to.0.version > 1 => to.0.version - 1,
+        None => {
+            return Err(format!(
+                "Schema {name} has only version 1; register another to compare"
+            ));
+        }
+    };
+    Ok((storage.schema_version(name, Some(from))?, to))
+}
+
+/// Compare two versions of `name`: by default the latest and the one
+/// before it.
+pub fn diff_schema_versions(
+    storage: &Storage,
+    name: &str,
+    from: Option<u32>,
+    to: Option<u32>,
+    database: Option<&str>,
+) -> Result<SchemaVersionDiff, String> {
+    let ((from, from_schema), (to, to_schema)) = version_pair(storage, name, from, to)?;
+    let changes = diff_schemas(&from_schema, &to_schema);
+    let (migration_path, migration) =
+        migration(&from, &to, &changes, database.unwrap_or("mongodb"));
+    Ok(SchemaVersionDiff {
+        from,
+        to,
+        changes,
+        migration_path,
+        migration,
+    })
+}
+
+/// Expression converting `value` to a field of type `to`.
+fn conversion(to: &str, value: &str) -> String {
+    match to {
+        "number" => format!("Number({value})"),
+        "text" | "textarea" | "email" | "code" | "select" | "radio" => {
+            format!("String({value} ?? '')")
+        }
+        "checkbox" => format!("Boolean({value})"),
+        "date" => format!("new Date({value}).toISOString()"),
+        _ => format!("{value} /* TODO: convert to {to} */"),
+    }
+}
+
+/// Migration step for one change, as statements inside `up`.
+fn migration_step(change: &SchemaChange) -> String {
+    let slug = &change.slug;
+    let comment = format!("  // {}\n", change.detail);
+    let Some(field) = change.field.as_deref() else {
+        return match change.kind {
+            ChangeKind::Removed => format!(
+                "{comment}  // Export its documents before deploying; the data is not removed automatically.\n"
+            ),
+            _ => comment,
+        };
+    };
+    let top_level = !field.contains('.');
+    if change.global {
+        return format!(
+            "{comment}  // TODO: update the `{slug}` global with payload.updateGlobal if stored data needs to change\n"
+        );
+    }
+    let loop_over = |body: String| {
+        format!(
+            "{comment}  {{\n    const {{ docs }} = await payload.find({{ collection: '{slug}', depth: 0, limit: 0, pagination: false, req }})\n    for (const doc of docs) {{\n      await payload.update({{ collection: '{slug}', id: doc.id, data: {{ {body} }}, req }})\n    }}\n  }}\n"
+        )
+    };
+    let after_type = change
+        .after
+        .as_ref()
+        .and_then(Value::as_str)
+        .unwrap_or_default();
+    match change.kind {
+        ChangeKind::TypeChanged if top_level => loop_over(format!(
+            "{field}: {}",
+            conversion(after_type, &format!("doc.{field}"))
+        )),
+        ChangeKind::HasManyChanged if top_level => {
+            if change.after == Some(Value::Bool(true)) {
+                loop_over(format!("{field}: doc.{field} == null ? [] : [doc.{field}]"))
+            } else {
+                loop_over(format!(
+                    "{field}: Array.isArray(doc.{field}) ? doc.{field}[0] : doc.{field}"
+                ))
+            }
+        }
+        ChangeKind::RequiredAdded | ChangeKind::Added
+            if change.after.as_ref().is_some_and(|after| {
+                after == &Value::Bool(true) || after.get("required") == Some(&Value::Bool(true))
+            }) =>
+        {
+            format!(
+                "{comment}  await payload.update({{\n    collection: '{slug}',\n    where: {{ '{field}': {{ exists: false }} }},\n    data: {{ /* TODO: default for '{field}' */ }},\n    req,\n  }})\n"
+            )
+        }
+        ChangeKind::OptionsChanged => {
+            let before: Vec<&str> = change
+                .before
+                .as_ref()
+                .and_then(Value::as_array)
+                .into_iter()
+                .flatten()
+                .filter_map(Value::as_str)
+                .collect();
+            let after: Vec<&str> = change
+                .after
+                .as_ref()
+                .and_then(Value::as_array)
+                .into_iter()
+                .flatten()
+                .filter_map(Value::as_str)
+                .collect();
+            let removed: Vec<String> = before
+                .iter()
+                .filter(|option| !after.contains(option))
+                .map(|option| format!("'{option}'"))
+                .collect();
+            if removed.is_empty() {
+                return comment;
+            }
+            format!(
+                "{comment}  await payload.update({{\n    collection: '{slug}',\n    where: {{ '{field}': {{ in: [{}] }} }},\n    data: {{ /* TODO: replacement value for '{field}' */ }},\n    req,\n  }})\n",
+                removed.join(", ")
+            )
+        }
+        ChangeKind::Removed => format!(
+            "{comment}  // Copy `{field}` elsewhere first if its data is still needed; documents keep it on MongoDB, and the Postgres schema migration drops the column.\n"
+        ),
+        ChangeKind::TypeChanged | ChangeKind::HasManyChanged => {
+            format!(
+                "{comment}  // TODO: convert the nested values of `{field}` inside their parent field\n"
+            )
+        }
+        _ => comment,
+    }
+}
+
+/// A Payload 3 migration moving documents from one version to the next.
+/// Column and index changes on Postgres and SQLite still come from
+/// `payload migrate:create`; this covers the data.
+pub fn migration(
+    from: &SchemaVersion,
+    to: &SchemaVersion,
+    changes: &[SchemaChange],
+    database: &str,
+) -> (String, String) {
+    let stamp = DateTime::parse_from_rfc3339(&to.recorded_at)
+        .map(|at| at.format("%Y%m%d_%H%M%S").to_string())
+        .unwrap_or_else(|_| "00000000_000000".to_string());
+    let name = to.name.replace(|c: char| !c.is_ascii_alphanumeric(), "_");
+    let path = format!(
+        "src/migrations/{stamp}_{name}_v{}_to_v{}.ts",
+        from.version, to.version
+    );
+    let steps: String = changes.iter().map(migration_step).collect();
+    let steps = if steps.is_empty() {
+        "  // The schemas are identical\n".to_string()
+    } else {
+        steps
+    };
+    let content = format!(
+        "import {{ MigrateDownArgs, MigrateUpArgs }} from '@payloadcms/db-{database}'\n\n// {} v{} -> v{}\nexport async function up({{ payload, req }}: MigrateUpArgs): Promise<void> {{\n{steps}}}\n\nexport async function down({{ payload, req }}: MigrateDownArgs): Promise<void> {{\n  // TODO: reverse the steps of `up` that changed data\n}}\n",
+        to.name, from.version, to.version
+    );
+    (path, content)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::payload_tools::schema_model::SchemaCollection;
+
+    fn posts(fields: Vec<SchemaField>) -> SchemaModel {
+        SchemaModel {
+            collections: vec![SchemaCollection {
+                slug: "posts".to_string(),
+                fields,
+                ..Default::default()
+            }],
+            globals: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn diffs_fields_and_writes_a_migration() {
+        let from = posts(vec![
+            SchemaField::new("price", "text"),
+            SchemaField::new("legacy", "text"),
+            SchemaField {
+                options: Some(vec!["draft".to_string(), "archived".to_string()]),
+                ..SchemaField::new("status", "select")
+            },
+        ]);
+        let to = posts(vec![
+            SchemaField::new("price", "number"),
+            SchemaField {
+                options: Some(vec!["draft".to_string(), "published".to_string()]),
+                ..SchemaField::new("status", "select")
+            },
+            SchemaField {
+                required: true,
+                ..SchemaField::new("summary", "textarea")
+            },
+        ]);
+        let changes = diff_schemas(&from, &to);
+        let kinds: Vec<(ChangeKind, Option<&str>)> = changes
+            .iter()
+            .map(|change| (change.kind, change.field.as_deref()))
+            .collect();
+        assert_eq!(
+            kinds,
+            vec![
+                (ChangeKind::TypeChanged, Some("price")),
+                (ChangeKind::Removed, Some("legacy")),
+                (ChangeKind::OptionsChanged, Some("status")),
+                (ChangeKind::Added, Some("summary")),
+            ]
+        );
+        assert_eq!(
+            changes[2].detail,
+            "Options of `status`: removed archived; added published"
+        );
+
+        let version = |version| SchemaVersion {
+            name: "blog".to_string(),
+            version,
+            label: None,
+            recorded_at: "2026-03-01T09:30:00+00:00".to_string(),
+            collections: 1,
+            globals: 0,
+        };
+        let (path, content) = migration(&version(1), &version(2), &changes, "mongodb");
+        assert_eq!(path, "src/migrations/20260301_093000_blog_v1_to_v2.ts");
+        assert!(content.contains("data: { price: Number(doc.price) }"));
+        assert!(content.contains("where: { 'status': { in: ['archived'] } }"));
+        assert!(content.contains("where: { 'summary': { exists: false } }"));
+        assert!(diff_schemas(&to, &to).is_empty());
+    }
+}
@@ -1,28 +1,430 @@
+use schemars::JsonSchema;
+use serde::Serialize;
 use serde_json::{Map, Value};
 
-pub const FIELD_TYPES: &[&str] = &[
-    "text",
-    "textarea",
-    "email",
-    "code",
-    "number",
-    "date",
-    "checkbox",
-    "select",
-    "relationship",
-    "upload",
-    "array",
-    "blocks",
-    "group",
-    "row",
-    "collapsible",
-    "tabs",
-    "richText",
-    "json",
-    "radio",
-    "point",
+/// Options every data-storing field accepts, in addition to its own.
+pub const COMMON_OPTIONS: &[&str] = &[
+    "name",
+    "type",
+    "label",
+    "required",
+    "unique",
+    "index",
+    "localized",
+    "defaultValue",
+    "hidden",
+    "saveToJWT",
+    "access",
+    "hooks",
+    "admin",
+    "validate",
+    "custom",
+    "typescriptSchema",
+    "virtual",
 ];
 
+/// Options of presentational fields (`row`, `collapsible`, `tabs`), which
+/// store nothing themselves.
+pub const LAYOUT_OPTIONS: &[&str] = &["type", "admin", "custom"];
+
+/// `admin` options every field type accepts.
+pub const COMMON_ADMIN_OPTIONS: &[&str] = &[
+    "description",
+    "readOnly",
+    "hidden",
+    "position",
+    "width",
+    "condition",
+    "className",
+    "style",
+    "components",
+    "disabled",
+    "disableBulkEdit",
+    "disableListColumn",
+    "disableListFilter",
+];
+
+/// Per-type metadata for the supported field types.
+#[derive(Clone, Debug, Serialize, JsonSchema)]
+pub struct FieldTypeInfo {
+    pub name: &'static str,
+    pub category: &'static str,
+    pub description: &'static str,
+    pub stores_data: bool,
+    pub has_subfields: bool,
+    /// Type-specific options, beyond [`COMMON_OPTIONS`] or [`LAYOUT_OPTIONS`]
+    pub options: &'static [&'static str],
+    /// Options Payload rejects the field without
+    pub required_options: &'static [&'static str],
+    /// Type-specific `admin` options, beyond [`COMMON_ADMIN_OPTIONS`]
+    pub admin_options: &'static [&'static str],
+    /// How the SQL adapters store the value
+    pub db_type: &'static str,
+    /// Rules the value or configuration must satisfy
+    pub constraints: &'static [&'static str],
+    /// Type-specific properties written by the field generator
+    pub example: &'static str,
+}
+
+impl FieldTypeInfo {
+    /// Whether `option` is valid at the top level of this field type.
+    pub fn accepts_option(&self, option: &str) -> bool {
+        let common = if self.stores_data { COMMON_OPTIONS } else { LAYOUT_OPTIONS };
+        common.contains(&option) || self.options.contains(&option)
+    }
+
+    /// Whether `option` is valid inside this field type's `admin` object.
+    pub fn accepts_admin_option(&self, option: &str) -> bool {
+        COMMON_ADMIN_OPTIONS.contains(&option) || self.admin_options.contains(&option)
+    }
+}
+
+pub const FIELD_TYPE_REGISTRY: &[FieldTypeInfo] = &[
+    FieldTypeInfo {
+        name: "text",
+        category: "text",
+        description: "Single-line string",
+        stores_data: true,
+        has_subfields: false,
+        options: &["minLength", "maxLength", "hasMany", "minRows", "maxRows"],
+        required_options: &[],
+        admin_options: &["placeholder", "autoComplete", "rtl"],
+        db_type: "varchar",
+        constraints: &["minLength must not exceed maxLength"],
+        example: "\n    minLength: 1,\n    maxLength: 255,",
+    },
+    FieldTypeInfo {
+        name: "textarea",
+        category: "text",
+        description: "Multi-line string",
+        stores_data: true,
+        has_subfields: false,
+        options: &["minLength", "maxLength"],
+        required_options: &[],
+        admin_options: &["placeholder", "autoComplete", "rows", "rtl"],
+        db_type: "text",
+        constraints: &["minLength must not exceed maxLength"],
+        example: "\n    minLength: 1,\n    maxLength: 255,",
+    },
+    FieldTypeInfo {
+        name: "email",
+        category: "text",
+        description: "String validated as an email address",
+        stores_data: true,
+        has_subfields: false,
+        options: &["minLength", "maxLength"],
+        required_options: &[],
+        admin_options: &["placeholder", "autoComplete"],
+        db_type: "varchar",
+        constraints: &["value must be a valid email address"],
+        example: "\n    minLength: 1,\n    maxLength: 255,",
+    },
+    FieldTypeInfo {
+        name: "code",
+        category: "text",
+        description: "String edited with a code editor",
+        stores_data: true,
+        has_subfields: false,
+        options: &["minLength", "maxLength"],
+        required_options: &[],
+        admin_options: &["language", "editorOptions"],
+        db_type: "text",
+        constraints: &["minLength must not exceed maxLength"],
+        example: "\n    minLength: 1,\n    maxLength: 255,",
+    },
+    FieldTypeInfo {
+        name: "number",
+        category: "numeric",
+        description: "Numeric value with optional min/max",
+        stores_data: true,
+        has_subfields: false,
+        options: &["min", "max", "hasMany", "minRows", "maxRows"],
+        required_options: &[],
+        admin_options: &["step", "placeholder", "autoComplete"],
+        db_type: "numeric",
+        constraints: &["min must not exceed max"],
+        example: "\n    min: 0,\n    max: 1000,",
+    },
+    FieldTypeInfo {
+        name: "date",
+        category: "temporal",
+        description: "ISO date with a date picker",
+        stores_data: true,
+        has_subfields: false,
+        options: &["timezone"],
+        required_options: &[],
+        admin_options: &["date", "placeholder"],
+        db_type: "timestamp(3) with time zone",
+        constraints: &["value must be an ISO 8601 date"],
+        example: "",
+    },
+    FieldTypeInfo {
+        name: "checkbox",
+        category: "choice",
+        description: "Boolean value",
+        stores_data: true,
+        has_subfields: false,
+        options: &[],
+        required_options: &[],
+        admin_options: &[],
+        db_type: "boolean",
+        constraints: &[],
+        example: "",
+    },
+    FieldTypeInfo {
+        name: "select",
+        category: "choice",
+        description: "One or many values from a fixed option list",
+        stores_data: true,
+        has_subfields: false,
+        options: &["options", "hasMany", "enumName", "dbName", "interfaceName"],
+        required_options: &["options"],
+        admin_options: &["isClearable", "isSortable"],
+        db_type: "enum (a join table when hasMany)",
+        constraints: &[
+            "options must be a non-empty array of strings or { label, value } objects",
+            "option values must be unique",
+        ],
+        example: "\n    options: [\n      { label: 'Option 1', value: 'option1' },\n      { label: 'Option 2', value: 'option2' },\n    ],\n    hasMany: false,",
+    },
+    FieldTypeInfo {
+        name: "radio",
+        category: "choice",
+        description: "Single value from a fixed option list",
+        stores_data: true,
+        has_subfields: false,
+        options: &["options", "enumName", "dbName", "interfaceName"],
+        required_options: &["options"],
+        admin_options: &["layout"],
+        db_type: "enum",
+        constraints: &[
+            "options must be a non-empty array of strings or { label, value } objects",
+            "option values must be unique",
+        ],
+        example: "\n    options: [\n      { label: 'Option 1', value: 'option1' },\n      { label: 'Option 2', value: 'option2' },\n    ],",
+    },
+    FieldTypeInfo {
+        name: "relationship",
+        category: "relational",
+        description: "Reference to documents in other collections",
+        stores_data: true,
+        has_subfields: false,
+        options: &[
+            "relationTo",
+            "hasMany",
+            "maxDepth",
+            "filterOptions",
+            "min",
+            "max",
+            "minRows",
+            "maxRows",
+        ],
+        required_options: &["relationTo"],
+        admin_options: &["isSortable", "allowCreate", "allowEdit", "sortOptions", "appearance"],
+        db_type: "foreign key (a join table when hasMany or polymorphic)",
+        constraints: &[
+            "relationTo must be a collection slug or an array of slugs",
+            "set maxDepth to bound how deep documents are populated",
+        ],
+        example: "\n    relationTo: 'collection-name',\n    hasMany: false,\n    maxDepth: 1,",
+    },
+    FieldTypeInfo {
+        name: "upload",
+        category: "relational",
+        description: "Reference to an upload-enabled collection",
+        stores_data: true,
+        has_subfields: false,
+        options: &[
+            "relationTo",
+            "hasMany",
+            "maxDepth",
+            "filterOptions",
+            "displayPreview",
+            "minRows",
+            "maxRows",
+        ],
+        required_options: &["relationTo"],
+        admin_options: &["allowCreate"],
+        db_type: "foreign key to the upload collection",
+        constraints: &["relationTo must name an upload-enabled collection"],
+        example: "\n    relationTo: 'media',",
+    },
+    FieldTypeInfo {
+        name: "array",
+        category: "structured",
+        description: "Repeating rows of sub-fields",
+        stores_data: true,
+        has_subfields: true,
+        options: &["fields", "minRows", "maxRows", "labels", "interfaceName", "dbName"],
+        required_options: &["fields"],
+        admin_options: &["initCollapsed", "isSortable"],
+        db_type: "child table with one row per item",
+        constraints: &["minRows must not exceed maxRows"],
+        example: "\n    minRows: 0,\n    maxRows: 10,\n    fields: [\n      {\n        name: 'subField',\n        type: 'text',\n        required: true,\n      },\n    ],",
+    },
+    FieldTypeInfo {
+        name: "blocks",
+        category: "structured",
+        description: "Ordered list of heterogeneous blocks",
+        stores_data: true,
+        has_subfields: true,
+        options: &["blocks", "minRows", "maxRows", "labels"],
+        required_options: &["blocks"],
+        admin_options: &["initCollapsed", "isSortable"],
+        db_type: "child table per block type",
+        constraints: &["each block needs a unique slug and a fields array"],
+        example: "\n    blocks: [\n      {\n        slug: 'block-name',\n        fields: [\n          {\n            name: 'blockField',\n            type: 'text',\n            required: true,\n          },\n        ],\n      },\n    ],",
+    },
+    FieldTypeInfo {
+        name: "group",
+        category: "structured",
+        description: "Nested object of sub-fields",
+        stores_data: true,
+        has_subfields: true,
+        options: &["fields", "interfaceName"],
+        required_options: &["fields"],
+        admin_options: &["hideGutter"],
+        db_type: "columns prefixed with the group name",
+        constraints: &[],
+        example: "\n    fields: [\n      {\n        name: 'subField',\n        type: 'text',\n      },\n    ],",
+    },
+    FieldTypeInfo {
+        name: "json",
+        category: "structured",
+        description: "Arbitrary JSON value",
+        stores_data: true,
+        has_subfields: false,
+        options: &["jsonSchema"],
+        required_options: &[],
+        admin_options: &["editorOptions"],
+        db_type: "jsonb",
+        constraints: &["value must match jsonSchema when one is set"],
+        example: "",
+    },
+    FieldTypeInfo {
+        name: "point",
+        category: "numeric",
+        description: "Geographic [longitude, latitude] pair",
+        stores_data: true,
+        has_subfields: false,
+        options: &[],
+        required_options: &[],
+        admin_options: &["step", "placeholder"],
+        db_type: "geometry(Point)",
+        constraints: &["value must be [longitude, latitude]"],
+        example: "",
+    },
+    FieldTypeInfo {
+        name: "richText",
+        category: "text",
+        description: "Structured rich text content",
+        stores_data: true,
+        has_subfields: false,
+        options: &["editor"],
+        required_options: &[],
+        admin_options: &[],
+        db_type: "jsonb",
+        constraints: &[],
+        example: "",
+    },
+    FieldTypeInfo {
+        name: "row",
+        category: "layout",
+        description: "Presentational row of sub-fields",
+        stores_data: false,
+        has_subfields: true,
+        options: &["fields"],
+        required_options: &["fields"],
+        admin_options: &[],
+        db_type: "none; sub-fields are stored on the parent",
+        constraints: &[],
+        example: "\n    fields: [\n      {\n        name: 'subField',\n        type: 'text',\n      },\n    ],",
+    },
+    FieldTypeInfo {
+        name: "collapsible",
+        category: "layout",
+        description: "Presentational collapsible section",
+        stores_data: false,
+        has_subfields: true,
+        options: &["fields", "label"],
+        required_options: &["fields", "label"],
+        admin_options: &["initCollapsed"],
+        db_type: "none; sub-fields are stored on the parent",
+        constraints: &[],
+        example: "\n    label: 'Details',\n    fields: [\n      {\n        name: 'subField',\n        type: 'text',\n      },\n    ],",
+    },
+    FieldTypeInfo {
+        name: "tabs",
+        category: "layout",
+        description: "Presentational tabs of sub-fields",
+        stores_data: false,
+        has_subfields: true,
+        options: &["tabs"],
+        required_options: &["tabs"],
+        admin_options: &[],
+        db_type: "none; named tabs are stored like a group",
+        constraints: &["each tab needs a label and a fields array"],
+        example: "\n    tabs: [\n      {\n        label: 'Tab 1',\n        fields: [\n          {\n            name: 'subField',\n            type: 'text',\n          },\n        ],\n      },\n    ],",
+    },
+];
+
+pub fn field_type_metadata() -> &'static [FieldTypeInfo] {
+    FIELD_TYPE_REGISTRY
+}
+
+/// Registry entry for `name`.
+pub fn field_type(name: &str) -> Option<&'static FieldTypeInfo> {
+    FIELD_TYPE_REGISTRY.iter().find(|info| info.name == name)
+}
+
+pub fn field_type_names() -> impl Iterator<Item = &'static str> {
+    FIELD_TYPE_REGISTRY.iter().map(|info| info.name)
+}
+
+/// Registry entry for `name`, or an error listing the supported types.
+pub fn require_field_type(name: &str) -> Result<&'static FieldTypeInfo, String> {
+    field_type(name).ok_or_else(|| {
+        format!(
+            "Unsupported field type '{name}'. Supported types: {}",
+            field_type_names().collect::<Vec<_>>().join(", ")
+        )
+    })
+}
+
+/// Options of `field` that its type does not accept, as warnings; `admin`
+/// options are reported as `admin.<option>`.
+pub fn unknown_field_options(field: &Value) -> Vec<String> {
+    let Some(map) = field.as_object() else {
+        return Vec::new();
+    };
+    let Some(info) = map.get("type").and_then(Value::as_str).and_then(field_type) else {
+        return Vec::new();
+    };
+    let mut unknown: Vec<String> = map
+        .keys()
+        .filter(|key| !info.accepts_option(key))
+        .cloned()
+        .collect();
+    if let Some(admin) = map.get("admin").and_then(Value::as_object) {
+        unknown.extend(
+            admin
+                .keys()
+                .filter(|key| !info.accepts_admin_option(key))
+                .map(|key| format!("admin.{key}")),
+        );
+    }
+    let name = map.get("name").and_then(Value::as_str).unwrap_or(info.name);
+    unknown
+        .into_iter()
+        .map(|option| {
+            format!(
+                "Field \"{name}\" sets '{option}', which {} fields do not support.",
+                info.name
+            )
+        })
+        .collect()
+}
+
 fn expect_object<'a>(value: &'a Value, context: &str) -> Result<&'a Map<String, Value>, String> {
     value
         .as_object()
@@ -48,14 +450,18 @@ fn validate_fields_array(fields: &[Value]) -> Result<(), String> {
 pub fn validate_field_schema(value: &Value) -> Result<(), String> {
     let map = expect_object(value, "Field")?;
 
-    require_string(map, "name")?;
     let field_type = require_string(map, "type")?;
-
-    if !FIELD_TYPES.contains(&field_type.as_str()) {
-        return Err(format!(
-            "Unsupported field type '{field_type}'. Supported types: {}",
-            FIELD_TYPES.join(", ")
-        ));
+    let info = require_field_type(&field_type)?;
+    // Presentational fields store nothing, so they need no name.
+    if info.stores_data {
+        require_string(map, "name")?;
+    }
+    if let Some(missing) = info
+        .required_options
+        .iter()
+        .find(|option| !map.contains_key(**option))
+    {
+        return Err(format!("{field_type} fields require '{missing}'"));
     }
 
     if let Some(admin) = map.get("admin") {
@@ -67,7 +473,7 @@ pub fn validate_field_schema(value: &Value) -> Result<(), String> {
     }
 
     match field_type.as_str() {
-        "select" => {
+        "select" | "radio" => {
             if let Some(options) = map.get("options") {
                 let opts = options
                     .as_array()
@@ -77,14 +483,14 @@ pub fn validate_field_schema(value: &Value) -> Result<(), String> {
                 }
             }
         }
-        "relationship" => {
+        "relationship" | "upload" => {
             if let Some(relation_to) = map.get("relationTo") {
                 if !(relation_to.is_string() || relation_to.is_array()) {
                     return Err("Field.relationTo must be a string or array".to_string());
                 }
             }
         }
-        "array" | "group" | "tabs" => {
+        "array" | "group" | "row" | "collapsible" | "tabs" => {
             if let Some(fields) = map.get("fields") {
                 let arr = fields
                     .as_array()
@@ -142,6 +548,19 @@ pub fn validate_global_schema(value: &Value) -> Result<(), String> {
     Ok(())
 }
 
+/// A block definition used by `blocks` fields: a slug and its fields.
+pub fn validate_block_schema(value: &Value) -> Result<(), String> {
+    let map = expect_object(value, "Block")?;
+    require_string(map, "slug")?;
+
+    let fields = map
+        .get("fields")
+        .and_then(|v| v.as_array())
+        .ok_or_else(|| "Block must include a 'fields' array".to_string())?;
+
+    validate_fields_array(fields)
+}
+
 pub fn validate_config_schema(value: &Value) -> Result<(), String> {
     let map = expect_object(value, "Config")?;
 
@@ -176,3 +595,29 @@ pub fn validate_config_schema(value: &Value) -> Result<(), String> {
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn test_field_registry_checks_options() {
+        assert!(validate_field_schema(&json!({ "name": "status", "type": "select" })).is_err());
+        assert!(
+            validate_field_schema(&json!({ "type": "row", "fields": [{ "name": "a", "type": "text" }] }))
+                .is_ok()
+        );
+        assert!(require_field_type("string").is_err());
+
+        let field = json!({
+            "name": "title",
+            "type": "text",
+            "maxLength": 80,
+            "relationTo": "posts",
+            "admin": { "placeholder": "Title", "step": 1 },
+        });
+        assert_eq!(unknown_field_options(&field).len(), 2);
+    }
+}
@@ -0,0 +1,483 @@
+//! Full-text search over validation rules, server instructions, template
+//! options, Payload best-practice snippets and the bundled guides.
+//!
+//! Documents are indexed once into an in-memory inverted index and ranked
+//! with BM25. Title terms count double, and the last query term also matches
+//! as a prefix so partially typed words still find results.
+
+use std::collections::HashMap;
+use std::sync::OnceLock;
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::payload_tools::generator::TemplateType;
+use crate::payload_tools::guides::{GUIDES, guide_uri, require_guide};
+use crate::payload_tools::validator::validation_rules;
+
+const K1: f64 = 1.2;
+const B: f64 = 0.75;
+const TITLE_WEIGHT: usize = 2;
+const PREFIX_PENALTY: f64 = 0.5;
+const SNIPPET_RADIUS: usize = 80;
+pub const DEFAULT_SEARCH_LIMIT: usize = 10;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum SearchKind {
+    Rule,
+    Instruction,
+    Template,
+    BestPractice,
+    /// Section of a `docs://payload/{topic}` guide
+    Doc,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
+pub struct SearchResult {
+    pub id: String,
+    pub kind: SearchKind,
+    pub title: String,
+    pub score: f64,
+    /// Excerpt around the best match with matched terms wrapped in `**`
+    pub snippet: String,
+}
+
+/// Short, searchable Payload guidance that is not tied to a validation rule.
+pub const BEST_PRACTICES: &[(&str, &str, &str)] = &[
+    (
+        "use-as-title",
+        "Set admin.useAsTitle",
+        "Point admin.useAsTitle at a human readable text field so relationship pickers and list views show meaningful labels instead of document IDs.",
+    ),
+    (
+        "access-defaults",
+        "Define access control explicitly",
+        "Every collection should declare read, create, update and delete access functions. Relying on defaults exposes documents to any authenticated user; return a query constraint to scope reads per user or tenant.",
+    ),
+    (
+        "depth-limits",
+        "Keep relationship depth low",
+        "Request depth=0 or depth=1 from the REST and GraphQL APIs unless populated documents are needed. Deep population multiplies queries and response size.",
+    ),
+    (
+        "index-queried-fields",
+        "Index fields you filter on",
+        "Add index: true to fields used in where clauses or sorts, and unique: true for natural keys such as slugs or emails, so the database adapter creates indexes.",
+    ),
+    (
+        "hooks-side-effects",
+        "Keep hooks idempotent",
+        "beforeChange and afterChange hooks can run more than once per request. Avoid non-idempotent side effects, pass req to nested Local API calls so they share the transaction, and guard against recursion with context flags.",
+    ),
+    (
+        "versions-drafts",
+        "Use versions for editorial content",
+        "Enable versions with drafts for content edited by several people. Drafts allow previewing and scheduled publishing, and versions give an audit trail with restore.",
+    ),
+    (
+        "localization",
+        "Localize only what varies",
+        "Mark fields localized only when their value differs per locale. Localizing relationship or upload fields duplicates references and complicates queries.",
+    ),
+    (
+        "secrets-env",
+        "Keep secrets in the environment",
+        "Read PAYLOAD_SECRET, database URIs and API keys from environment variables. Never commit them to payload.config.ts or expose them through admin components.",
+    ),
+    (
+        "field-validation",
+        "Validate on the server",
+        "Use the validate function on fields for rules that must always hold. Client-side admin validation is bypassed by API writes.",
+    ),
+    (
+        "slugs",
+        "Generate slugs in hooks",
+        "Derive slug fields in a beforeValidate hook from the title, mark them unique and indexed, and keep them editable so URLs can be corrected.",
+    ),
+];
+
+#[derive(Clone, Debug)]
+struct Document {
+    id: String,
+    kind: SearchKind,
+    title: String,
+    body: String,
+    length: usize,
+}
+
+struct SearchIndex {
+    documents: Vec<Document>,
+    /// term -> (document index, weighted term frequency)
+    postings: HashMap<String, Vec<(usize, usize)>>,
+    average_length: f64,
+}
+
+fn index() -> &'static SearchIndex {
+    static INDEX: OnceLock<SearchIndex> = OnceLock::new();
+    INDEX.get_or_init(|| SearchIndex::build(collect_documents()))
+}
+
+fn collect_documents() -> Vec<Document> {
+    let mut documents = Vec::new();
+    let mut push = |id: String, kind: SearchKind, title: String, body: String| {
+        documents.push(Document {
+            id,
+            kind,
+            title,
+            body,
+            length: 0,
+        });
+    };
+
+    for rule in validation_rules() {
+        let body = format!(
+            "{} Category: {}. Applies to: {}.",
+            rule.description,
+            rule.category,
+            rule.file_types
+                .iter()
+                .map(|ft| ft.as_str())
+                .collect::<Vec<_>>()
+                .join(", ")
+        );
+        push(rule.id, SearchKind::Rule, rule.name, body);
+    }
+
+    for (source, text) in [
+        ("instructions", include_str!("../docs/instructions.md")),
+        ("tool-instructions", include_str!("../../docs/tool-instructions.md")),
+    ] {
+        for (index, (heading, body)) in markdown_sections(text).into_iter().enumerate() {
+            push(
+                format!("{source}#{index}"),
+                SearchKind::Instruction,
+                heading,
+                body,
+            );
+        }
+    }
+
+    for template in TemplateType::ALL {
+        let options = template
+            .options()
+            .iter()
+            .map(|(key, description)| format!("{key}: {description}."))
+            .collect::<Vec<_>>()
+            .join(" ");
+        push(
+            template.as_str().to_string(),
+            SearchKind::Template,
+            format!("{} template", template.as_str()),
+            format!("{}. Options: {options}", template.description()),
+        );
+    }
+
+    for (id, title, body) in BEST_PRACTICES {
+        push(
+            id.to_string(),
+            SearchKind::BestPractice,
+            title.to_string(),
+            body.to_string(),
+        );
+    }
+
+    for guide in GUIDES {
+        for (index, (heading, body)) in markdown_sections(guide.content).into_iter().enumerate() {
+            push(
+                format!("{}#{index}", guide.topic),
+                SearchKind::Doc,
+                heading,
+                body,
+            );
+        }
+    }
+
+    documents
+}
+
+/// Split markdown into `(heading, body)` sections. Text before the first
+/// heading is attached to an untitled section.
+fn markdown_sections(text: &str) -> Vec<(String, String)> {
+    let mut sections = Vec::new();
+    let mut heading = String::new();
+    let mut body = String::new();
+    let mut in_fence = false;
+    for line in text.lines() {
+        if line.trim_start().starts_with("```") {
+            in_fence = !in_fence;
+        }
+        if let Some(title) = line.strip_prefix('#').filter(|_| !in_fence) {
+            if !body.trim().is_empty() {
+                sections.push((heading.clone(), body.trim().to_string()));
+            }
+            heading = title.trim_start_matches('#').trim().to_string();
+            body.clear();
+        } else {
+            body.push_str(line);
+            body.push('\n');
+        }
+    }
+    if !body.trim().is_empty() {
+        sections.push((heading, body.trim().to_string()));
+    }
+    sections
+}
+
+impl SearchIndex {
+    fn build(mut documents: Vec<Document>) -> Self {
+        let mut postings: HashMap<String, Vec<(usize, usize)>> = HashMap::new();
+        let mut total_length = 0;
+
+        for (doc_index, document) in documents.iter_mut().enumerate() {
+            let mut frequencies: HashMap<String, usize> = HashMap::new();
+            for term in tokenize(&document.title) {
+                *frequencies.entry(term).or_default() += TITLE_WEIGHT;
+            }
+            for term in tokenize(&document.body) {
+                *frequencies.entry(term).or_default() += 1;
+            }
+            document.length = frequencies.values().sum();
+            total_length += document.length;
+            for (term, frequency) in frequencies {
+                postings.entry(term).or_default().push((doc_index, frequency));
+            }
+        }
+
+        let average_length = if documents.is_empty() {
+            0.0
+        } else {
+            total_length as f64 / documents.len() as f64
+        };
+        Self {
+            documents,
+            postings,
+            average_length,
+        }
+    }
+
+    fn idf(&self, document_frequency: usize) -> f64 {
+        let n = self.documents.len() as f64;
+        let df = document_frequency as f64;
+        ((n - df + 0.5) / (df + 0.5) + 1.0).ln()
+    }
+
+    fn score_term(&self, term: &str, weight: f64, scores: &mut HashMap<usize, f64>) {
+        let Some(postings) = self.postings.get(term) else {
+            return;
+        };
+        let idf = self.idf(postings.len());
+        for &(doc_index, frequency) in postings {
+            let tf = frequency as f64;
+            let length = self.documents[doc_index].length as f64;
+            let norm = K1 * (1.0 - B + B * length / self.average_length.max(1.0));
+            *scores.entry(doc_index).or_default() += weight * idf * tf * (K1 + 1.0) / (tf + norm);
+        }
+    }
+}
+
+/// Rank documents against `query`, optionally restricted to some kinds.
+pub fn search(query: &str, kinds: Option<&[SearchKind]>, limit: usize) -> Vec<SearchResult> {
+    let index = index();
+    let terms = tokenize(query);
+    let Some(last) = terms.last() else {
+        return Vec::new();
+    };
+
+    let mut scores: HashMap<usize, f64> = HashMap::new();
+    for term in &terms {
+        index.score_term(term, 1.0, &mut scores);
+    }
+    // Prefix-expand the last term, e.g. while a word is still being typed.
+    if last.len() >= 3 {
+        for term in index.postings.keys() {
+            if term.len() > last.len() && term.starts_with(last.as_str()) {
+                index.score_term(term, PREFIX_PENALTY, &mut scores);
+            }
+        }
+    }
+
+    let mut ranked: Vec<(usize, f64)> = scores
+        .into_iter()
+        .filter(|(doc_index, _)| {
+            kinds.is_none_or(|kinds| kinds.contains(&index.documents[*doc_index].kind))
+        })
+        .collect();
+    ranked.sort_by(|a, b| {
+        b.1.partial_cmp(&a.1)
+            .unwrap_or(std::cmp::Ordering::Equal)
+            .then(a.0.cmp(&b.0))
+    });
+
+    ranked
+        .into_iter()
+        .take(limit)
+        .map(|(doc_index, score)| {
+            let document = &index.documents[doc_index];
+            SearchResult {
+                id: document.id.clone(),
+                kind: document.kind,
+                title: highlight(&document.title, &terms),
+                score: (score * 1000.0).round() / 1000.0,
+                snippet: snippet(&document.body, &terms),
+            }
+        })
+        .collect()
+}
+
+/// A guide section matching a `search_docs` query.
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
+pub struct DocMatch {
+    /// `docs://payload/{topic}` resource holding the section
+    pub uri: String,
+    pub topic: String,
+    /// Section heading with matched terms wrapped in `**`
+    pub section: String,
+    pub score: f64,
+    pub snippet: String,
+}
+
+/// Rank guide sections against `query`, optionally within one topic.
+pub fn search_docs(query: &str, topic: Option<&str>, limit: usize) -> Result<Vec<DocMatch>, String> {
+    if let Some(topic) = topic {
+        require_guide(topic)?;
+    }
+    Ok(search(query, Some(&[SearchKind::Doc]), usize::MAX)
+        .into_iter()
+        .filter_map(|result| {
+            let (section_topic, _) = result.id.split_once('#')?;
+            if topic.is_some_and(|topic| topic != section_topic) {
+                return None;
+            }
+            Some(DocMatch {
+                uri: guide_uri(section_topic),
+                topic: section_topic.to_string(),
+                section: result.title,
+                score: result.score,
+                snippet: result.snippet,
+            })
+        })
+        .take(limit)
+        .collect())
+}
+
+const STOPWORDS: &[&str] = &[
+    "a", "an", "and", "are", "as", "at", "be", "by", "for", "from", "how", "i", "in", "is", "it",
+    "of", "on", "or", "the", "this", "to", "with",
+];
+
+fn tokenize(text: &str) -> Vec<String> {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(str::to_lowercase)
+        .filter(|word| !STOPWORDS.contains(&word.as_str()))
+        .collect()
+}
+
+/// Whether a word from the document matches one of the query terms, either
+/// exactly or (for the last term) as a prefix.
+fn matches_term(word: &str, terms: &[String]) -> bool {
+    let word = word.to_lowercase();
+    terms.iter().enumerate().any(|(index, term)| {
+        word == *term || (index + 1 == terms.len() && term.len() >= 3 && word.starts_with(term.as_str()))
+    })
+}
+
+/// Wrap every word of `text` that matches a query term in `**`.
+fn highlight(text: &str, terms: &[String]) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut word = String::new();
+    let flush = |word: &mut String, out: &mut String| {
+        if word.is_empty() {
+            return;
+        }
+        if matches_term(word, terms) {
+            out.push_str("**");
+            out.push_str(word);
+            out.push_str("**");
+        } else {
+            out.push_str(word);
+        }
+        word.clear();
+    };
+    for c in text.chars() {
+        if c.is_alphanumeric() {
+            word.push(c);
+        } else {
+            flush(&mut word, &mut out);
+            out.push(c);
+        }
+    }
+    flush(&mut word, &mut out);
+    out
+}
+
+/// Excerpt of `body` centred on the first matching word, collapsed onto one
+/// line and highlighted.
+fn snippet(body: &str, terms: &[String]) -> String {
+    let flat = body.split_whitespace().collect::<Vec<_>>().join(" ");
+    let chars: Vec<char> = flat.chars().collect();
+
+    let mut first_match = None;
+    let mut start = None;
+    for (i, c) in chars.iter().enumerate() {
+        if c.is_alphanumeric() {
+            start.get_or_insert(i);
+        } else if let Some(s) = start.take() {
+            let word: String = chars[s..i].iter().collect();
+            if matches_term(&word, terms) {
+                first_match = Some(s);
+                break;
+            }
+        }
+    }
+    if first_match.is_none() {
+        if let Some(s) = start {
+            let word: String = chars[s..].iter().collect();
+            if matches_term(&word, terms) {
+                first_match = Some(s);
+            }
+        }
+    }
+
+    let center = first_match.unwrap_or(0);
+    let from = center.saturating_sub(SNIPPET_RADIUS);
+    let to = (center + SNIPPET_RADIUS).min(chars.len());
+    let mut excerpt: String = chars[from..to].iter().collect();
+    if from > 0 {
+        excerpt.insert_str(0, "...");
+    }
+    if to < chars.len() {
+        excerpt.push_str("...");
+    }
+    highlight(&excerpt, terms)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ranks_rules_and_highlights() {
+        let results = search("access control", None, 5);
+        assert!(!results.is_empty());
+        assert!(results[0].snippet.contains("**") || results[0].title.contains("**"));
+        assert!(results.windows(2).all(|w| w[0].score >= w[1].score));
+    }
+
+    #[test]
+    fn test_kind_filter_and_prefix() {
+        let results = search("versi", Some(&[SearchKind::Template]), 10);
+        assert!(!results.is_empty());
+        assert!(results.iter().all(|r| r.kind == SearchKind::Template));
+        assert!(search("", None, 10).is_empty());
+    }
+
+    #[test]
+    fn test_search_docs_by_topic() {
+        let results = search_docs("transaction req", Some("hooks"), 3).unwrap();
+        assert!(!results.is_empty());
+        assert!(results.iter().all(|r| r.uri == "docs://payload/hooks"));
+        assert!(search_docs("roles", Some("missing"), 3).is_err());
+    }
+}
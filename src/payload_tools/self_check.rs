@@ -0,0 +1,184 @@
+//! Round trip of everything the server generates: template output, scaffold
+//! presets and reference examples are parsed back from TypeScript and
+//! validated, so generated code never breaks the validator's own rules.
+
+use std::collections::BTreeMap;
+
+use serde_json::{Map, Value, json};
+
+use crate::{
+    generate_cli::{SCAFFOLD_PRESETS, preset_options},
+    payload_tools::{
+        examples::EXAMPLES,
+        generator::{TemplateType, generate_template},
+        parallel,
+        plugins::{PayloadVersion, plugin_config, plugin_names},
+        scaffolder::{ScaffoldOptions, scaffold_project, validate_scaffold},
+        schemas::field_type_names,
+        types::{FileType, ValidationResult},
+        validator::validate_payload_code,
+    },
+};
+
+/// Generated sources as (path, options) per template; paths follow the
+/// scaffold layout so the file type can be told from them.
+fn template_samples(template_type: TemplateType) -> Vec<(String, Value)> {
+    match template_type {
+        TemplateType::Collection => vec![(
+            "templates/collections/posts.ts".to_string(),
+            json!({
+                "slug": "posts",
+                "fields": field_type_names()
+                    .map(|field_type| json!({ "name": format!("{field_type}Field"), "type": field_type }))
+                    .collect::<Vec<_>>(),
+                "timestamps": true,
+                "hooks": true,
+                "access": true,
+                "versions": true,
+                "admin": { "useAsTitle": "textField", "defaultColumns": ["textField"], "group": "Content" },
+            }),
+        ), (
+            "templates/collections/trash.ts".to_string(),
+            json!({
+                "slug": "trash",
+                "fields": [{ "name": "title", "type": "text" }],
+                "softDelete": true,
+            }),
+        )],
+        TemplateType::Field => field_type_names()
+            .map(|field_type| {
+                (
+                    format!("templates/fields/{field_type}.ts"),
+                    json!({ "name": format!("{field_type}Field"), "type": field_type }),
+                )
+            })
+            .collect(),
+        TemplateType::Global => vec![(
+            "templates/globals/settings.ts".to_string(),
+            json!({
+                "slug": "settings",
+                "fields": [{ "name": "siteName", "type": "text" }],
+                "access": true,
+                "versions": true,
+                "admin": { "group": "Settings" },
+            }),
+        )],
+        TemplateType::Config => {
+            // Registry plugins that work with their default options
+            let plugins: Vec<&str> = plugin_names()
+                .filter(|name| plugin_config(name, PayloadVersion::V2, &Map::new(), "").is_ok())
+                .collect();
+            ["mongodb", "postgres"]
+                .into_iter()
+                .map(|db| {
+                    (
+                        format!("templates/{db}/payload.config.ts"),
+                        json!({
+                            "serverURL": "http://localhost:3000",
+                            "collections": ["posts"],
+                            "globals": ["settings"],
+                            "plugins": plugins,
+                            "db": db,
+                            "admin": { "user": "users" },
+                        }),
+                    )
+                })
+                .collect()
+        }
+        TemplateType::Block => vec![(
+            "templates/blocks/hero.ts".to_string(),
+            json!({ "name": "hero", "fields": [{ "name": "heading", "type": "text" }] }),
+        )],
+        _ => Vec::new(),
+    }
+}
+
+/// Validate the output of every template (each field type on its own), every
+/// scaffold preset and every reference example on up to `jobs` threads (0 for
+/// one per CPU), keyed by path under `templates/`, `presets/{name}/` and
+/// `examples/{id}/`.
+pub fn self_check(jobs: usize) -> BTreeMap<String, ValidationResult> {
+    let mut results = BTreeMap::new();
+    let mut sources: Vec<(String, String, FileType)> = Vec::new();
+
+    for template_type in TemplateType::ALL {
+        let Some(file_type) = template_type.file_type() else {
+            continue;
+        };
+        for (path, options) in template_samples(template_type) {
+            match generate_template(template_type, &options) {
+                Ok(code) => sources.push((path, code, file_type)),
+                Err(err) => {
+                    results.insert(path, ValidationResult::with_errors(vec![err]));
+                }
+            }
+        }
+    }
+
+    for example in EXAMPLES {
+        match example.code() {
+            Ok(code) => sources.extend(code.files.into_iter().filter_map(|file| {
+                let path = format!("examples/{}/{}", example.id, file.path);
+                Some((path, file.code, file.file_type?))
+            })),
+            Err(err) => {
+                results.insert(
+                    format!("examples/{}", example.id),
+                    ValidationResult::with_errors(vec![err]),
+                );
+            }
+        }
+    }
+
+    let validated = parallel::map(&sources, jobs, |(_, code, file_type)| {
+        validate_payload_code(code, *file_type)
+    });
+    results.extend(sources.into_iter().map(|(path, _, _)| path).zip(validated));
+
+    for (name, _) in SCAFFOLD_PRESETS {
+        let (collections, globals) = preset_options(name);
+        let files = scaffold_project(&ScaffoldOptions {
+            project_name: format!("{name}-self-check"),
+            collections: Some(collections),
+            globals: Some(globals),
+            ..Default::default()
+        });
+        results.extend(
+            validate_scaffold(&files, jobs)
+                .into_iter()
+                .map(|(path, result)| (format!("presets/{name}/{path}"), result)),
+        );
+    }
+
+    results
+}
+
+/// Whether `results` pass: all valid and, with `deny_warnings`, warning-free.
+pub fn self_check_passed(
+    results: &BTreeMap<String, ValidationResult>,
+    deny_warnings: bool,
+) -> bool {
+    results
+        .values()
+        .all(|result| result.is_valid && !(deny_warnings && !result.warnings.is_empty()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_generated_code_passes_validation() {
+        let results = self_check(2);
+        assert!(results.contains_key("templates/collections/posts.ts"));
+        assert!(results.contains_key("templates/fields/relationship.ts"));
+        assert!(results.contains_key("presets/blog/src/payload.config.ts"));
+        assert!(results.contains_key("examples/saas/src/collections/organizations.ts"));
+        let failures: Vec<_> = results
+            .iter()
+            .filter(|(_, result)| !result.is_valid || !result.warnings.is_empty())
+            .collect();
+        assert!(failures.is_empty(), "{failures:#?}");
+        assert!(self_check_passed(&results, true));
+    }
+}
@@ -0,0 +1,442 @@
+//! SEO for a Payload 3 site on Next.js: a `meta` group with title,
+//! description and image on each selected collection, either from the seo
+//! plugin or as a plain field group, plus `sitemap.xml` and `robots.txt`
+//! route handlers and a helper turning `meta` into Next.js metadata.
+
+use std::collections::BTreeMap;
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value, json};
+
+use crate::payload_tools::{
+    email::server_url_env,
+    plugins::{PayloadVersion, plugin_config},
+};
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct SeoOptions {
+    /// Collections that get SEO fields
+    pub collections: Vec<String>,
+    /// Collections listed in the sitemap (default all of `collections`)
+    pub sitemap: Option<Vec<String>>,
+    /// Frontend path per collection, with `{slug}` for the document's slug;
+    /// `/<collection>/{slug}` by default
+    pub paths: Option<BTreeMap<String, String>>,
+    /// Field holding the document's slug (default `slug`)
+    pub slug_field: Option<String>,
+    /// Upload collection for the meta image (default `media`)
+    pub uploads_collection: Option<String>,
+    /// Appended to meta titles, e.g. `Title | Site`
+    pub site_name: Option<String>,
+    /// Add the fields with the seo plugin (default true) rather than the
+    /// generated field group
+    pub plugin: Option<bool>,
+    /// Paths robots.txt disallows (default `/admin` and `/api`)
+    pub disallow: Option<Vec<String>>,
+}
+
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct SeoConfig {
+    pub imports: Vec<String>,
+    /// Entry for the config's `plugins` array; set unless `plugin` is false
+    pub plugin: Option<String>,
+    /// Entry for each collection's `fields` array, by slug; set when
+    /// `plugin` is false
+    pub collections: BTreeMap<String, String>,
+    /// Field group, route handlers and helpers by path from the project root
+    pub files: BTreeMap<String, String>,
+    /// npm packages and version ranges to add to `package.json`
+    pub dependencies: BTreeMap<String, String>,
+    /// Environment variables read by the generated code
+    pub env: Vec<String>,
+}
+
+/// Lengths search results show in full; longer values are cut off there.
+const TITLE_LENGTH: (u32, u32) = (50, 60);
+const DESCRIPTION_LENGTH: (u32, u32) = (100, 160);
+
+fn is_slug(value: &str) -> bool {
+    !value.is_empty()
+        && value
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
+
+fn quote(value: &str) -> String {
+    format!("'{}'", value.replace('\\', "\\\\").replace('\'', "\\'"))
+}
+
+fn seo_field_group(uploads: &str) -> String {
+    let (title_min, title_max) = TITLE_LENGTH;
+    let (description_min, description_max) = DESCRIPTION_LENGTH;
+    format!(
+        "import type {{ Field }} from 'payload';
+
+const length = (value: unknown): number => (typeof value === 'string' ? value.length : 0);
+
+/** Meta title, description and image, with search-result lengths in the admin descriptions. */
+export const seoField: Field = {{
+  name: 'meta',
+  label: 'SEO',
+  type: 'group',
+  fields: [
+    {{
+      name: 'title',
+      type: 'text',
+      maxLength: {title_max},
+      admin: {{
+        description: 'Aim for {title_min}–{title_max} characters; longer titles are cut off in search results.',
+      }},
+      validate: (value: unknown) =>
+        length(value) <= {title_max} || `${{length(value)}}/{title_max} characters: shorten the title`,
+    }},
+    {{
+      name: 'description',
+      type: 'textarea',
+      maxLength: {description_max},
+      admin: {{
+        description: 'Aim for {description_min}–{description_max} characters.',
+      }},
+      validate: (value: unknown) =>
+        length(value) <= {description_max} || `${{length(value)}}/{description_max} characters: shorten the description`,
+    }},
+    {{
+      name: 'image',
+      type: 'upload',
+      relationTo: '{uploads}',
+      admin: {{
+        description: 'Shown when the page is shared; 1200×630 works best.',
+      }},
+    }},
+  ],
+}};
+"
+    )
+}
+
+pub(crate) fn check_slug_field(slug_field: &str) -> Result<(), String> {
+    let identifier = slug_field
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || c == '_');
+    if slug_field.is_empty() || !identifier || slug_field.starts_with(|c: char| c.is_ascii_digit())
+    {
+        return Err(format!("Invalid slug field '{slug_field}'"));
+    }
+    Ok(())
+}
+
+/// Frontend path of each collection's pages: the one in `paths`, or
+/// `/<collection>/{slug}`.
+pub(crate) fn page_paths<'a>(
+    collections: &'a [String],
+    paths: Option<&BTreeMap<String, String>>,
+) -> Result<Vec<(&'a str, String)>, String> {
+    let configured = paths.cloned().unwrap_or_default();
+    if let Some(slug) = configured.keys().find(|slug| !collections.contains(slug)) {
+        return Err(format!(
+            "Path given for '{slug}', which is not in collections"
+        ));
+    }
+    if let Some((slug, path)) = configured.iter().find(|(_, path)| !path.starts_with('/')) {
+        return Err(format!("Path '{path}' for '{slug}' must start with /"));
+    }
+    Ok(collections
+        .iter()
+        .map(|slug| {
+            let path = configured
+                .get(slug)
+                .cloned()
+                .unwrap_or_else(|| format!("/{slug}/{{slug}}"));
+            (slug.as_str(), path)
+        })
+        .collect())
+}
+
+/// `pagePath` and `absoluteURL`, shared by the SEO and redirects helpers.
+pub(crate) fn page_path_helper(paths: &[(&str, String)]) -> String {
+    let paths: String = paths
+        .iter()
+        .map(|(slug, path)| format!("  '{slug}': {},\n", quote(path)))
+        .collect();
+    format!(
+        "/** Frontend path of each collection's pages; `{{slug}}` is the document's slug. */
+const paths: Record<string, string> = {{
+{paths}}};
+
+/** Path of a document's page, or null when `collection` has no pages. */
+export const pagePath = (collection: string, slug?: string | null): string | null => {{
+  const pattern = paths[collection];
+  if (pattern === undefined || !slug) return null;
+  return pattern.replace('{{slug}}', encodeURIComponent(slug));
+}};
+
+/** Absolute URL of `path` on the site. */
+export const absoluteURL = (path: string): string =>
+  `${{(process.env.NEXT_PUBLIC_SERVER_URL ?? '').replace(/\\/$/, '')}}${{path}}`;
+"
+    )
+}
+
+fn sitemap_route(collections: &[String], slug_field: &str) -> String {
+    let collections = collections
+        .iter()
+        .map(|slug| quote(slug))
+        .collect::<Vec<_>>()
+        .join(", ");
+    format!(
+        "import config from '@payload-config';
+import type {{ MetadataRoute }} from 'next';
+import {{ getPayload }} from 'payload';
+
+import {{ absoluteURL, pagePath }} from '@/utilities/pagePath';
+
+const collections = [{collections}] as const;
+
+/** Published documents of the listed collections; regenerated hourly. */
+export const revalidate = 3600;
+
+export default async function sitemap(): Promise<MetadataRoute.Sitemap> {{
+  const payload = await getPayload({{ config }});
+  const entries: MetadataRoute.Sitemap = [{{ url: absoluteURL('/'), changeFrequency: 'daily', priority: 1 }}];
+  for (const collection of collections) {{
+    const {{ docs }} = await payload.find({{
+      collection,
+      depth: 0,
+      pagination: false,
+      select: {{ {slug_field}: true, updatedAt: true, _status: true }},
+    }});
+    for (const doc of docs as {{ {slug_field}?: string; updatedAt?: string; _status?: string }}[]) {{
+      const path = pagePath(collection, doc.{slug_field});
+      if (!path || doc._status === 'draft') continue;
+      entries.push({{ url: absoluteURL(path), lastModified: doc.updatedAt }});
+    }}
+  }}
+  return entries;
+}}
+"
+    )
+}
+
+fn robots_route(disallow: &[String]) -> String {
+    let disallow = disallow
+        .iter()
+        .map(|path| quote(path))
+        .collect::<Vec<_>>()
+        .join(", ");
+    format!(
+        "import type {{ MetadataRoute }} from 'next';
+
+import {{ absoluteURL }} from '@/utilities/pagePath';
+
+export default function robots(): MetadataRoute.Robots {{
+  return {{
+    rules: {{ userAgent: '*', allow: '/', disallow: [{disallow}] }},
+    sitemap: absoluteURL('/sitemap.xml'),
+  }};
+}}
+"
+    )
+}
+
+fn generate_meta_helper(site_name: Option<&str>, slug_field: &str) -> String {
+    let title = match site_name {
+        Some(site) => format!(
+            "const SITE_NAME = {};\n\nconst fullTitle = (title?: string | null): string =>\n  title ? `${{title}} | ${{SITE_NAME}}` : SITE_NAME;\n",
+            quote(site)
+        ),
+        None => "const fullTitle = (title?: string | null): string => title ?? '';\n".to_string(),
+    };
+    format!(
+        "import type {{ Metadata }} from 'next';
+
+import {{ absoluteURL, pagePath }} from '@/utilities/pagePath';
+
+type Meta = {{
+  title?: string | null;
+  description?: string | null;
+  image?: {{ url?: string | null; alt?: string | null }} | string | number | null;
+}};
+
+{title}
+/** Next.js metadata for a document's page from its `meta` group; use in generateMetadata. */
+export const generateMeta = (collection: string, doc: {{ {slug_field}?: string | null; meta?: Meta | null }}): Metadata => {{
+  const meta = doc.meta ?? {{}};
+  const image = typeof meta.image === 'object' && meta.image?.url ? absoluteURL(meta.image.url) : undefined;
+  const path = pagePath(collection, doc.{slug_field});
+  const title = fullTitle(meta.title);
+  return {{
+    title,
+    description: meta.description ?? undefined,
+    alternates: path ? {{ canonical: absoluteURL(path) }} : undefined,
+    openGraph: {{
+      title,
+      description: meta.description ?? undefined,
+      url: path ? absoluteURL(path) : undefined,
+      images: image ? [{{ url: image }}] : undefined,
+    }},
+  }};
+}};
+"
+    )
+}
+
+/// Plugin call or field group, sitemap and robots routes and metadata helper
+/// for the selected collections.
+pub fn seo_config(options: &SeoOptions) -> Result<SeoConfig, String> {
+    if options.collections.is_empty() {
+        return Err("At least one collection is required".to_string());
+    }
+    let uploads = options.uploads_collection.as_deref().unwrap_or("media");
+    for slug in options
+        .collections
+        .iter()
+        .map(String::as_str)
+        .chain([uploads])
+    {
+        if !is_slug(slug) {
+            return Err(format!("Invalid collection slug '{slug}'"));
+        }
+    }
+    let sitemap = options
+        .sitemap
+        .clone()
+        .unwrap_or_else(|| options.collections.clone());
+    if let Some(slug) = sitemap
+        .iter()
+        .find(|slug| !options.collections.contains(slug))
+    {
+        return Err(format!("Sitemap collection '{slug}' is not in collections"));
+    }
+    let slug_field = options.slug_field.as_deref().unwrap_or("slug");
+    check_slug_field(slug_field)?;
+    let paths = page_paths(&options.collections, options.paths.as_ref())?;
+    let disallow = options
+        .disallow
+        .clone()
+        .unwrap_or_else(|| vec!["/admin".to_string(), "/api".to_string()]);
+    if let Some(path) = disallow.iter().find(|path| !path.starts_with('/')) {
+        return Err(format!("Path '{path}' for robots.txt must start with /"));
+    }
+    let server_url = server_url_env(PayloadVersion::V3);
+    let mut files = BTreeMap::from([
+        (
+            "src/utilities/pagePath.ts".to_string(),
+            page_path_helper(&paths),
+        ),
+        (
+            "src/utilities/generateMeta.ts".to_string(),
+            generate_meta_helper(options.site_name.as_deref(), slug_field),
+        ),
+        (
+            "src/app/(frontend)/sitemap.ts".to_string(),
+            sitemap_route(&sitemap, slug_field),
+        ),
+        (
+            "src/app/(frontend)/robots.ts".to_string(),
+            robots_route(&disallow),
+        ),
+    ]);
+
+    if !options.plugin.unwrap_or(true) {
+        files.insert("src/fields/seo.ts".to_string(), seo_field_group(uploads));
+        return Ok(SeoConfig {
+            imports: vec!["import { seoField } from '../fields/seo';".to_string()],
+            plugin: None,
+            collections: options
+                .collections
+                .iter()
+                .map(|slug| (slug.clone(), "seoField,".to_string()))
+                .collect(),
+            files,
+            dependencies: BTreeMap::new(),
+            env: vec![server_url.to_string()],
+        });
+    }
+
+    let title = match &options.site_name {
+        Some(site) => format!(
+            "({{ doc }}) => (doc?.title ? `${{doc.title}} | {0}` : {1})",
+            site.replace('`', "\\`").replace("${", "\\${"),
+            quote(site)
+        ),
+        None => "({ doc }) => doc?.title ?? ''".to_string(),
+    };
+    let mut plugin_options = Map::new();
+    plugin_options.insert("collections".to_string(), json!(options.collections));
+    plugin_options.insert("uploadsCollection".to_string(), json!(uploads));
+    plugin_options.insert("tabbedUI".to_string(), Value::Bool(true));
+    plugin_options.insert("generateTitle".to_string(), Value::String(title));
+    plugin_options.insert(
+        "generateURL".to_string(),
+        Value::String(format!(
+            "({{ doc, collectionSlug }}) => absoluteURL(pagePath(collectionSlug, doc?.{slug_field}) ?? '/')"
+        )),
+    );
+    let plugin = plugin_config("seo", PayloadVersion::V3, &plugin_options, "")?;
+    let mut imports = plugin.imports;
+    imports.push("import { absoluteURL, pagePath } from './utilities/pagePath';".to_string());
+    let mut env = plugin.env;
+    env.push(server_url.to_string());
+
+    Ok(SeoConfig {
+        imports,
+        plugin: Some(plugin.code),
+        collections: BTreeMap::new(),
+        files,
+        dependencies: plugin.dependencies,
+        env,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_seo_config_plugin_and_field_group() {
+        let options = SeoOptions {
+            collections: vec!["pages".to_string(), "posts".to_string()],
+            sitemap: Some(vec!["posts".to_string()]),
+            paths: Some(BTreeMap::from([(
+                "pages".to_string(),
+                "/{slug}".to_string(),
+            )])),
+            site_name: Some("Acme".to_string()),
+            ..Default::default()
+        };
+        let config = seo_config(&options).unwrap();
+        let plugin = config.plugin.as_deref().unwrap();
+        assert!(plugin.starts_with("seoPlugin({"));
+        assert!(plugin.contains("collections: ['pages', 'posts'],"));
+        assert!(plugin.contains("`${doc.title} | Acme`"));
+        assert!(
+            config.files["src/utilities/pagePath.ts"]
+                .contains("  'pages': '/{slug}',\n  'posts': '/posts/{slug}',\n")
+        );
+        assert!(
+            config.files["src/app/(frontend)/sitemap.ts"]
+                .contains("const collections = ['posts'] as const;")
+        );
+        assert!(
+            config.files["src/app/(frontend)/robots.ts"].contains("disallow: ['/admin', '/api']")
+        );
+        assert!(!config.files.contains_key("src/fields/seo.ts"));
+
+        let field_group = SeoOptions {
+            plugin: Some(false),
+            ..options.clone()
+        };
+        let config = seo_config(&field_group).unwrap();
+        assert!(config.plugin.is_none());
+        assert_eq!(config.collections["posts"], "seoField,");
+        assert!(config.files["src/fields/seo.ts"].contains("maxLength: 60,"));
+
+        let unknown = SeoOptions {
+            sitemap: Some(vec!["authors".to_string()]),
+            ..options
+        };
+        assert!(seo_config(&unknown).is_err());
+    }
+}
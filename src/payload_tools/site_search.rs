@@ -0,0 +1,449 @@
+//! Site search for a Payload 3 app on one of three backends: the official
+//! search plugin's collection, or an Algolia or Meilisearch index kept in
+//! sync by afterChange and afterDelete hooks. Each comes with a
+//! `GET /api/site-search` endpoint returning the same typed results.
+
+use std::collections::BTreeMap;
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value, json};
+
+use crate::payload_tools::plugins::{PayloadVersion, plugin_config};
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum SearchBackend {
+    /// The search plugin's `search` collection
+    #[default]
+    Plugin,
+    Algolia,
+    Meilisearch,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct SiteSearchOptions {
+    /// Collections to search
+    pub collections: Vec<String>,
+    /// Where documents are indexed (default `plugin`)
+    pub backend: Option<SearchBackend>,
+    /// Fields indexed per collection, the first used as the result title
+    /// (default `title`)
+    pub fields: Option<BTreeMap<String, Vec<String>>>,
+    /// Algolia or Meilisearch index (default `content`)
+    pub index_name: Option<String>,
+    /// Ranking priority per collection for the plugin, higher first
+    pub priorities: Option<BTreeMap<String, u32>>,
+}
+
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct SiteSearchConfig {
+    pub imports: Vec<String>,
+    /// Entry for the config's `plugins` array, for the plugin backend
+    pub plugin: Option<String>,
+    /// `hooks` property to merge into each collection's config, by slug,
+    /// for the Algolia and Meilisearch backends
+    pub collections: BTreeMap<String, String>,
+    /// Endpoints for the config's `endpoints` array, imported by `imports`
+    pub endpoints: Vec<String>,
+    /// Endpoint and sync module by path from the project root
+    pub files: BTreeMap<String, String>,
+    /// npm packages and version ranges to add to `package.json`
+    pub dependencies: BTreeMap<String, String>,
+    /// Environment variables read by the generated code
+    pub env: Vec<String>,
+}
+
+const RESULT_TYPE: &str = "/** One search hit, the same for every backend. */
+export type SearchResult = {
+  collection: string;
+  id: string;
+  title: string;
+};
+
+export type SearchResponse = {
+  query: string;
+  results: SearchResult[];
+};
+";
+
+fn is_slug(value: &str) -> bool {
+    !value.is_empty()
+        && value
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
+
+fn is_field(value: &str) -> bool {
+    !value.is_empty()
+        && !value.starts_with(|c: char| c.is_ascii_digit())
+        && value.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+/// `{ 'slug': [...fields] }` as a TypeScript object literal.
+fn fields_literal(fields: &[(&str, Vec<String>)]) -> String {
+    let entries: String = fields
+        .iter()
+        .map(|(slug, fields)| {
+            let fields = fields
+                .iter()
+                .map(|field| format!("'{field}'"))
+                .collect::<Vec<_>>()
+                .join(", ");
+            format!("  '{slug}': [{fields}],\n")
+        })
+        .collect();
+    format!("{{\n{entries}}}")
+}
+
+/// The endpoint reading `q` and `limit`, with `search` returning the hits.
+fn search_endpoint(imports: &str, search: &str) -> String {
+    format!(
+        "import type {{ Endpoint }} from 'payload';
+{imports}
+{RESULT_TYPE}
+const MAX_LIMIT = 50;
+
+/** GET /api/site-search?q=...&limit=10 */
+export const searchEndpoint: Endpoint = {{
+  path: '/site-search',
+  method: 'get',
+  handler: async (req) => {{
+    const query = String(req.query?.q ?? '').trim();
+    const limit = Math.min(Math.max(Number(req.query?.limit) || 10, 1), MAX_LIMIT);
+    if (!query) {{
+      return Response.json({{ query, results: [] }} satisfies SearchResponse);
+    }}
+    try {{
+{search}
+      return Response.json({{ query, results }} satisfies SearchResponse);
+    }} catch (error) {{
+      req.payload.logger.error(`Search failed: ${{error}}`);
+      return Response.json({{ error: 'Search is unavailable' }}, {{ status: 502 }});
+    }}
+  }},
+}};
+
+export default searchEndpoint;
+"
+    )
+}
+
+fn plugin_endpoint() -> String {
+    search_endpoint(
+        "",
+        "      const { docs } = await req.payload.find({
+        collection: 'search',
+        where: { title: { like: query } },
+        sort: '-priority',
+        limit,
+        depth: 0,
+        req,
+      });
+      const results: SearchResult[] = docs.map((doc) => ({
+        collection: doc.doc.relationTo,
+        id: String(typeof doc.doc.value === 'object' ? doc.doc.value.id : doc.doc.value),
+        title: doc.title ?? '',
+      }));",
+    )
+}
+
+/// Client setup and index calls for an external backend.
+struct External {
+    package: (&'static str, &'static str),
+    env: [&'static str; 3],
+    client: &'static str,
+    save: &'static str,
+    delete: &'static str,
+    search: &'static str,
+}
+
+const ALGOLIA: External = External {
+    package: ("algoliasearch", "^5.0.0"),
+    env: [
+        "ALGOLIA_APP_ID",
+        "ALGOLIA_ADMIN_API_KEY",
+        "ALGOLIA_SEARCH_API_KEY",
+    ],
+    client: "import { algoliasearch } from 'algoliasearch';
+
+export const client = (key: 'ALGOLIA_ADMIN_API_KEY' | 'ALGOLIA_SEARCH_API_KEY') =>
+  algoliasearch(process.env.ALGOLIA_APP_ID ?? '', process.env[key] ?? '');
+",
+    save: "client('ALGOLIA_ADMIN_API_KEY').saveObjects({ indexName: INDEX, objects: records })",
+    delete: "client('ALGOLIA_ADMIN_API_KEY').deleteObject({ indexName: INDEX, objectID })",
+    search: "      const { hits } = await client('ALGOLIA_SEARCH_API_KEY').searchSingleIndex<SearchRecord>({
+        indexName: INDEX,
+        searchParams: { query, hitsPerPage: limit },
+      });",
+};
+
+const MEILISEARCH: External = External {
+    package: ("meilisearch", "^0.44.0"),
+    env: [
+        "MEILISEARCH_HOST",
+        "MEILISEARCH_ADMIN_API_KEY",
+        "MEILISEARCH_SEARCH_API_KEY",
+    ],
+    client: "import { MeiliSearch } from 'meilisearch';
+
+export const client = (key: 'MEILISEARCH_ADMIN_API_KEY' | 'MEILISEARCH_SEARCH_API_KEY') =>
+  new MeiliSearch({ host: process.env.MEILISEARCH_HOST ?? '', apiKey: process.env[key] });
+",
+    save: "client('MEILISEARCH_ADMIN_API_KEY').index(INDEX).addDocuments(records, { primaryKey: 'objectID' })",
+    delete: "client('MEILISEARCH_ADMIN_API_KEY').index(INDEX).deleteDocument(objectID)",
+    search: "      const { hits } = await client('MEILISEARCH_SEARCH_API_KEY')
+        .index(INDEX)
+        .search<SearchRecord>(query, { limit });",
+};
+
+fn sync_module(external: &External, index: &str, fields: &[(&str, Vec<String>)]) -> String {
+    let client = external.client;
+    let save = external.save;
+    let delete = external.delete;
+    let fields = fields_literal(fields);
+    format!(
+        "import type {{ CollectionAfterChangeHook, CollectionAfterDeleteHook, Payload }} from 'payload';
+{client}
+export const INDEX = '{index}';
+
+/** Fields indexed per collection; the first is the result title. */
+const FIELDS: Record<string, string[]> = {fields};
+
+export type SearchRecord = {{
+  objectID: string;
+  collection: string;
+  id: string;
+  title: string;
+  [field: string]: unknown;
+}};
+
+/** `<collection>_<id>`, valid as both an Algolia and a Meilisearch ID. */
+const objectIDOf = (collection: string, id: string | number): string => `${{collection}}_${{id}}`;
+
+const toRecord = (collection: string, doc: Record<string, unknown>): SearchRecord => {{
+  const fields = FIELDS[collection] ?? ['title'];
+  const record: SearchRecord = {{
+    objectID: objectIDOf(collection, doc.id as string | number),
+    collection,
+    id: String(doc.id),
+    title: String(doc[fields[0]] ?? ''),
+  }};
+  for (const field of fields) {{
+    record[field] = doc[field];
+  }}
+  return record;
+}};
+
+const saveRecords = (records: SearchRecord[]) => {save};
+
+const deleteRecord = (objectID: string) => {delete};
+
+/** Indexes published documents and removes drafts; a failed call is logged, not thrown. */
+export const searchAfterChange: CollectionAfterChangeHook = async ({{ collection, doc, req }}) => {{
+  try {{
+    if (doc._status === 'draft') {{
+      await deleteRecord(objectIDOf(collection.slug, doc.id));
+    }} else {{
+      await saveRecords([toRecord(collection.slug, doc)]);
+    }}
+  }} catch (error) {{
+    req.payload.logger.error(`Could not index ${{collection.slug}} ${{doc.id}}: ${{error}}`);
+  }}
+  return doc;
+}};
+
+export const searchAfterDelete: CollectionAfterDeleteHook = async ({{ collection, doc, req }}) => {{
+  try {{
+    await deleteRecord(objectIDOf(collection.slug, doc.id));
+  }} catch (error) {{
+    req.payload.logger.error(`Could not remove ${{collection.slug}} ${{doc.id}} from the index: ${{error}}`);
+  }}
+  return doc;
+}};
+
+/** Index every published document, e.g. from a script after adding the hooks. */
+export const reindexAll = async (payload: Payload): Promise<number> => {{
+  let count = 0;
+  for (const collection of Object.keys(FIELDS)) {{
+    const {{ docs }} = await payload.find({{ collection, depth: 0, pagination: false }});
+    const records = docs
+      .filter((doc) => (doc as {{ _status?: string }})._status !== 'draft')
+      .map((doc) => toRecord(collection, doc as Record<string, unknown>));
+    if (records.length > 0) {{
+      await saveRecords(records);
+    }}
+    count += records.length;
+  }}
+  return count;
+}};
+"
+    )
+}
+
+fn external_endpoint(external: &External) -> String {
+    let search = format!(
+        "{}
+      const results: SearchResult[] = hits.map((hit) => ({{
+        collection: hit.collection,
+        id: hit.id,
+        title: hit.title,
+      }}));",
+        external.search
+    );
+    search_endpoint(
+        "\nimport { INDEX, type SearchRecord, client } from '../search/sync';\n",
+        &search,
+    )
+}
+
+/// Plugin call or sync hooks, and the search endpoint, for `options.backend`.
+pub fn site_search_config(options: &SiteSearchOptions) -> Result<SiteSearchConfig, String> {
+    if options.collections.is_empty() {
+        return Err("At least one collection to search is required".to_string());
+    }
+    if let Some(slug) = options.collections.iter().find(|slug| !is_slug(slug)) {
+        return Err(format!("Invalid collection slug '{slug}'"));
+    }
+    let configured = options.fields.clone().unwrap_or_default();
+    if let Some(slug) = configured
+        .keys()
+        .chain(
+            options
+                .priorities
+                .iter()
+                .flat_map(|priorities| priorities.keys()),
+        )
+        .find(|slug| !options.collections.contains(slug))
+    {
+        return Err(format!("'{slug}' is not one of the searched collections"));
+    }
+    let fields: Vec<(&str, Vec<String>)> = options
+        .collections
+        .iter()
+        .map(|slug| {
+            let fields = configured
+                .get(slug)
+                .filter(|fields| !fields.is_empty())
+                .cloned()
+                .unwrap_or_else(|| vec!["title".to_string()]);
+            (slug.as_str(), fields)
+        })
+        .collect();
+    if let Some(field) = fields
+        .iter()
+        .flat_map(|(_, fields)| fields)
+        .find(|field| !is_field(field))
+    {
+        return Err(format!("Invalid field name '{field}'"));
+    }
+    let index = options.index_name.as_deref().unwrap_or("content");
+    if !is_slug(index) {
+        return Err(format!("Invalid index name '{index}'"));
+    }
+
+    let mut imports = vec!["import { searchEndpoint } from './endpoints/search';".to_string()];
+    let endpoints = vec!["searchEndpoint".to_string()];
+    let external = match options.backend.unwrap_or_default() {
+        SearchBackend::Plugin => {
+            let titles: String = fields
+                .iter()
+                .map(|(slug, fields)| format!("'{slug}': '{}', ", fields[0]))
+                .collect();
+            let mut plugin_options = Map::new();
+            plugin_options.insert("collections".to_string(), json!(options.collections));
+            if let Some(priorities) = &options.priorities {
+                plugin_options.insert("defaultPriorities".to_string(), json!(priorities));
+            }
+            plugin_options.insert(
+                "beforeSync".to_string(),
+                Value::String(format!(
+                    "({{ originalDoc, searchDoc }}) => ({{\n    ...searchDoc,\n    title: originalDoc?.[({{ {} }} as Record<string, string>)[searchDoc.doc.relationTo]] ?? searchDoc.title,\n  }})",
+                    titles.trim_end_matches(", ")
+                )),
+            );
+            let plugin = plugin_config("search", PayloadVersion::V3, &plugin_options, "")?;
+            imports.extend(plugin.imports);
+            return Ok(SiteSearchConfig {
+                imports,
+                plugin: Some(plugin.code),
+                collections: BTreeMap::new(),
+                endpoints,
+                files: BTreeMap::from([("src/endpoints/search.ts".to_string(), plugin_endpoint())]),
+                dependencies: plugin.dependencies,
+                env: plugin.env,
+            });
+        }
+        SearchBackend::Algolia => ALGOLIA,
+        SearchBackend::Meilisearch => MEILISEARCH,
+    };
+
+    let hooks = "hooks: {\n    afterChange: [searchAfterChange],\n    afterDelete: [searchAfterDelete],\n  },";
+    Ok(SiteSearchConfig {
+        imports,
+        plugin: None,
+        collections: options
+            .collections
+            .iter()
+            .map(|slug| (slug.clone(), hooks.to_string()))
+            .collect(),
+        endpoints,
+        files: BTreeMap::from([
+            (
+                "src/search/sync.ts".to_string(),
+                sync_module(&external, index, &fields),
+            ),
+            (
+                "src/endpoints/search.ts".to_string(),
+                external_endpoint(&external),
+            ),
+        ]),
+        dependencies: BTreeMap::from([(
+            external.package.0.to_string(),
+            external.package.1.to_string(),
+        )]),
+        env: external.env.iter().map(|var| var.to_string()).collect(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_site_search_config_per_backend() {
+        let options = SiteSearchOptions {
+            collections: vec!["posts".to_string(), "pages".to_string()],
+            fields: Some(BTreeMap::from([(
+                "posts".to_string(),
+                vec!["headline".to_string(), "excerpt".to_string()],
+            )])),
+            ..Default::default()
+        };
+        let config = site_search_config(&options).unwrap();
+        let plugin = config.plugin.as_deref().unwrap();
+        assert!(plugin.starts_with("searchPlugin({"));
+        assert!(plugin.contains("{ 'posts': 'headline', 'pages': 'title' }"));
+        assert!(config.files["src/endpoints/search.ts"].contains("collection: 'search',"));
+
+        let algolia = SiteSearchOptions {
+            backend: Some(SearchBackend::Algolia),
+            ..options.clone()
+        };
+        let config = site_search_config(&algolia).unwrap();
+        assert!(config.plugin.is_none());
+        assert!(config.collections["pages"].contains("afterDelete: [searchAfterDelete]"));
+        let sync = &config.files["src/search/sync.ts"];
+        assert!(sync.contains("  'posts': ['headline', 'excerpt'],\n  'pages': ['title'],\n"));
+        assert!(sync.contains("export const client = "));
+        assert_eq!(config.dependencies["algoliasearch"], "^5.0.0");
+        assert_eq!(config.env.len(), 3);
+
+        let unknown = SiteSearchOptions {
+            priorities: Some(BTreeMap::from([("authors".to_string(), 10)])),
+            ..options
+        };
+        assert!(site_search_config(&unknown).is_err());
+    }
+}
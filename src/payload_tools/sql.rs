@@ -1,253 +1,462 @@
+//! The SQL engine behind the `mcp_query` and `query` tools.
+//!
+//! Queries are parsed by [`sql_parser`](crate::payload_tools::sql_parser),
+//! which documents the accepted grammar, and evaluated over rows of JSON
+//! objects. Rows come from the built-in tables in [`BUILTIN_TABLES`] or, when a
+//! [`PayloadClient`] is supplied, from live collections on a Payload instance,
+//! which shadow built-in tables of the same name.
+
+use std::cmp::Ordering;
+use std::sync::OnceLock;
+
+use chrono::{DateTime, NaiveDate, Utc};
 use regex::Regex;
-use serde_json::{json, Value};
-
-use crate::payload_tools::types::{SqlQueryResult, ValidationRule};
+use serde::Serialize;
+use serde_json::{json, Map, Value};
+
+use crate::payload_tools::cache::{CacheStats, MemoCache};
+use crate::payload_tools::client::PayloadClient;
+use crate::payload_tools::generator::TemplateType;
+use crate::payload_tools::mcp::tool_definitions;
+use crate::payload_tools::query::get_categories;
+use crate::payload_tools::schemas::field_type_metadata;
+use crate::payload_tools::sql_parser::{
+    like_contains, parse_query_with_bindings, Condition, Join, JoinKind, Operator, OrderBy, Query,
+    SortDirection, TableRef, LIKE_ESCAPE,
+};
+use crate::payload_tools::types::{FileType, SqlBindings, SqlQueryResult};
 use crate::payload_tools::validator::validation_rules;
 
-#[derive(Debug)]
-enum Query {
-    Select {
-        columns: Vec<String>,
-        table: String,
-        where_clause: Option<Condition>,
-        order_by: Vec<OrderBy>,
-        limit: Option<usize>,
-    },
-    Describe { table: String },
-}
+/// Documents requested per page from a live collection when the query cannot
+/// be fully pushed down to the REST API. Every page is fetched.
+const LIVE_PAGE_SIZE: usize = 1000;
 
-#[derive(Debug, Clone)]
-enum Condition {
-    And(Vec<Condition>),
-    Or(Vec<Condition>),
-    Comparison { column: String, operator: Operator, value: Value },
-}
+/// Compiled `LIKE` patterns kept across rows and queries.
+const PATTERN_CACHE_SIZE: usize = 128;
 
-#[derive(Debug, Clone, Copy)]
-enum Operator {
-    Eq,
-    Neq,
-    Gt,
-    Lt,
-    Gte,
-    Lte,
-    Like,
-    In,
-}
+type Row = Map<String, Value>;
 
-#[derive(Debug, Clone)]
-struct OrderBy {
-    column: String,
-    direction: SortDirection,
+pub fn execute_sql_query(sql: &str) -> Result<SqlQueryResult, String> {
+    execute_sql_query_with_client(sql, None)
 }
 
-#[derive(Debug, Clone, Copy)]
-enum SortDirection {
-    Asc,
-    Desc,
+/// Execute a query, resolving tables that are not built in as collections on a
+/// live Payload instance when a client is provided.
+pub fn execute_sql_query_with_client(
+    sql: &str,
+    client: Option<&PayloadClient>,
+) -> Result<SqlQueryResult, String> {
+    execute_prepared_query(sql, None, client)
 }
 
-pub fn execute_sql_query(sql: &str) -> Result<SqlQueryResult, String> {
-    let query = parse_query(sql)?;
+/// Execute a query whose `?` or `:name` placeholders are filled from
+/// `bindings`. Bound values are used as-is and never parsed as SQL.
+pub fn execute_prepared_query(
+    sql: &str,
+    bindings: Option<&SqlBindings>,
+    client: Option<&PayloadClient>,
+) -> Result<SqlQueryResult, String> {
+    let query = parse_query_with_bindings(sql, bindings)?;
     match query {
         Query::Select {
             columns,
             table,
+            joins,
             where_clause,
             order_by,
             limit,
-        } => execute_select_query(columns, table, where_clause, order_by, limit),
-        Query::Describe { table } => execute_describe_query(table),
+        } => {
+            let rows = if joins.is_empty() {
+                load_table(&table.name, where_clause.as_ref(), &order_by, limit, client)?
+            } else {
+                join_tables(&table, &joins, client)?
+            };
+            execute_select_query(columns, rows, where_clause, order_by, limit)
+        }
+        Query::Describe { table } => {
+            let rows = load_table(&table, None, &[], Some(1), client)?;
+            execute_describe_query(rows)
+        }
     }
 }
 
-fn parse_query(sql: &str) -> Result<Query, String> {
-    let trimmed = sql.trim();
-    let select_re = Regex::new(
-        r"(?i)^SELECT\s+(.*?)\s+FROM\s+(.*?)(?:\s+WHERE\s+(.*?))?(?:\s+ORDER\s+BY\s+(.*?))?(?:\s+LIMIT\s+(\d+))?$",
-    )
-    .map_err(|err| format!("Failed to compile SELECT regex: {err}"))?;
+/// Tables served from data compiled into the server.
+pub const BUILTIN_TABLES: &[&str] = &[
+    "validation_rules",
+    "file_types",
+    "field_types",
+    "template_types",
+    "categories",
+    "tools",
+];
+
+/// Prefix that always selects a built-in table, e.g. `builtin.categories`
+/// when a live collection is also named `categories`.
+pub const BUILTIN_PREFIX: &str = "builtin.";
+
+/// Where the rows of a table come from.
+#[derive(Debug, PartialEq)]
+enum TableSource<'a> {
+    Builtin(&'a str),
+    Live,
+}
 
-    if let Some(caps) = select_re.captures(trimmed) {
-        let columns = caps
-            .get(1)
-            .map(|m| m.as_str())
-            .unwrap_or_default()
-            .split(',')
-            .map(|c| c.trim().to_string())
-            .collect::<Vec<_>>();
-        let table = caps.get(2).map(|m| m.as_str()).unwrap_or_default();
-        let where_clause = caps
-            .get(3)
-            .and_then(|m| parse_where_clause(m.as_str()).ok());
-        let order_by = caps
-            .get(4)
-            .map(|m| parse_order_by_clause(m.as_str()))
-            .unwrap_or_else(Vec::new);
-        let limit = caps
-            .get(5)
-            .and_then(|m| m.as_str().parse::<usize>().ok());
-
-        return Ok(Query::Select {
-            columns,
-            table: table.trim().to_string(),
-            where_clause,
-            order_by,
-            limit,
-        });
-    }
+fn is_builtin(table: &str) -> bool {
+    BUILTIN_TABLES.iter().any(|name| name.eq_ignore_ascii_case(table))
+}
 
-    let describe_re =
-        Regex::new(r"(?i)^DESCRIBE\s+(.*?)$").map_err(|err| format!("Regex error: {err}"))?;
-    if let Some(caps) = describe_re.captures(trimmed) {
-        let table = caps.get(1).map(|m| m.as_str()).unwrap_or_default();
-        return Ok(Query::Describe {
-            table: table.trim().to_string(),
-        });
+/// `table` without [`BUILTIN_PREFIX`], when it has it.
+fn strip_builtin_prefix(table: &str) -> Option<&str> {
+    table
+        .get(..BUILTIN_PREFIX.len())
+        .filter(|prefix| prefix.eq_ignore_ascii_case(BUILTIN_PREFIX))
+        .map(|_| &table[BUILTIN_PREFIX.len()..])
+}
+
+/// Resolve `table` given the collections of the live instance, if any. A live
+/// collection takes precedence over a built-in table of the same name, and
+/// any other name is taken as a live collection.
+fn table_source<'a>(
+    table: &'a str,
+    live_collections: Option<&[String]>,
+) -> Option<TableSource<'a>> {
+    if let Some(name) = strip_builtin_prefix(table) {
+        return is_builtin(name).then_some(TableSource::Builtin(name));
+    }
+    let builtin = is_builtin(table);
+    match live_collections {
+        Some(collections) if !builtin || collections.iter().any(|slug| slug == table) => {
+            Some(TableSource::Live)
+        }
+        _ => builtin.then_some(TableSource::Builtin(table)),
     }
+}
 
-    Err("Unsupported query type. Only SELECT and DESCRIBE are supported.".to_string())
+fn builtin_table(table: &str) -> Option<Vec<Row>> {
+    let rows = match table.to_ascii_lowercase().as_str() {
+        "validation_rules" => validation_rules().into_iter().map(to_row).collect(),
+        "file_types" => {
+            let rules = validation_rules();
+            FileType::ALL
+                .iter()
+                .map(|file_type| {
+                    let rule_count = rules
+                        .iter()
+                        .filter(|r| r.file_types.contains(file_type))
+                        .count();
+                    to_row(json!({
+                        "name": file_type.as_str(),
+                        "description": file_type.description(),
+                        "rule_count": rule_count,
+                    }))
+                })
+                .collect()
+        }
+        "field_types" => field_type_metadata().iter().map(to_row).collect(),
+        "template_types" => TemplateType::ALL
+            .iter()
+            .map(|t| {
+                to_row(json!({
+                    "name": t.as_str(),
+                    "description": t.description(),
+                    "file_type": t.file_type().map(|f| f.as_str()),
+                }))
+            })
+            .collect(),
+        "categories" => {
+            let rules = validation_rules();
+            get_categories()
+                .into_iter()
+                .map(|category| {
+                    let rule_count = rules.iter().filter(|r| r.category == category).count();
+                    to_row(json!({
+                        "name": category,
+                        "rule_count": rule_count,
+                    }))
+                })
+                .collect()
+        }
+        "tools" => tool_definitions()
+            .into_iter()
+            .map(|tool| {
+                let parameters = tool
+                    .input_schema
+                    .get("properties")
+                    .and_then(|p| p.as_object())
+                    .map(|p| p.keys().cloned().collect::<Vec<_>>())
+                    .unwrap_or_default();
+                to_row(json!({
+                    "name": tool.name,
+                    "description": tool.description,
+                    "parameters": parameters,
+                }))
+            })
+            .collect(),
+        _ => return None,
+    };
+    Some(rows)
 }
 
-fn parse_where_clause(where_clause: &str) -> Result<Condition, String> {
-    let and_parts = split_case_insensitive(where_clause, "AND");
-    let mut and_conditions = Vec::new();
+fn to_row<T: Serialize>(item: T) -> Row {
+    match serde_json::to_value(item) {
+        Ok(Value::Object(map)) => map,
+        _ => Row::new(),
+    }
+}
 
-    for part in and_parts {
-        let trimmed = part.trim();
-        if trimmed.is_empty() {
-            continue;
+fn load_table(
+    table: &str,
+    where_clause: Option<&Condition>,
+    order_by: &[OrderBy],
+    limit: Option<usize>,
+    client: Option<&PayloadClient>,
+) -> Result<Vec<Row>, String> {
+    // Only a built-in name can be shadowed, so only then are the live
+    // collections listed.
+    let live_collections = match client {
+        Some(client) if is_builtin(table) => {
+            Some(client.list_collections().map_err(|err| err.to_string())?)
         }
-        let or_parts = split_case_insensitive(&part, "OR");
-        if or_parts.len() > 1 {
-            let mut or_conditions = Vec::new();
-            for or in or_parts {
-                let or_trimmed = or.trim();
-                if or_trimmed.is_empty() {
-                    continue;
-                }
-                or_conditions.push(parse_condition(or_trimmed)?);
-            }
-            and_conditions.push(Condition::Or(or_conditions));
-        } else {
-            and_conditions.push(parse_condition(trimmed)?);
+        Some(_) => Some(Vec::new()),
+        None => None,
+    };
+    match (table_source(table, live_collections.as_deref()), client) {
+        (Some(TableSource::Builtin(name)), _) => {
+            builtin_table(name).ok_or_else(|| format!("Unknown table: {table}"))
+        }
+        (Some(TableSource::Live), Some(client)) => {
+            fetch_live_rows(client, table, where_clause, order_by, limit)
         }
+        _ => Err(format!("Unknown table: {table}")),
     }
+}
 
-    if and_conditions.is_empty() {
-        return Err("Failed to parse WHERE clause".to_string());
+/// Load the FROM table and each joined table with columns keyed as
+/// `qualifier.column`, combining them with nested-loop joins.
+fn join_tables(
+    table: &TableRef,
+    joins: &[Join],
+    client: Option<&PayloadClient>,
+) -> Result<Vec<Row>, String> {
+    let mut qualifiers = vec![table.qualifier()];
+    for join in joins {
+        let qualifier = join.table.qualifier();
+        if qualifiers.iter().any(|q| q.eq_ignore_ascii_case(qualifier)) {
+            return Err(format!(
+                "Table name or alias '{qualifier}' is used more than once; give each table a distinct alias"
+            ));
+        }
+        qualifiers.push(qualifier);
     }
 
-    if and_conditions.len() == 1 {
-        Ok(and_conditions.remove(0))
-    } else {
-        Ok(Condition::And(and_conditions))
+    let mut rows = load_qualified(table, client)?;
+    for join in joins {
+        let right_rows = load_qualified(&join.table, client)?;
+        let right_columns: Vec<String> = right_rows
+            .first()
+            .map(|row| row.keys().cloned().collect())
+            .unwrap_or_default();
+
+        let mut joined = Vec::new();
+        for left in rows {
+            let mut matched = false;
+            for right in &right_rows {
+                let mut row = left.clone();
+                row.extend(right.clone());
+                if evaluate_where_clause(&row, &join.on) {
+                    matched = true;
+                    joined.push(row);
+                }
+            }
+            if !matched && join.kind == JoinKind::Left {
+                let mut row = left;
+                for column in &right_columns {
+                    row.insert(column.clone(), Value::Null);
+                }
+                joined.push(row);
+            }
+        }
+        rows = joined;
     }
+    Ok(rows)
 }
 
-fn split_case_insensitive(input: &str, delimiter: &str) -> Vec<String> {
-    let pattern = format!(r"(?i)\s*{}\s*", regex::escape(delimiter));
-    let re = Regex::new(&pattern).unwrap();
-    re.split(input).map(|s| s.to_string()).collect()
+fn load_qualified(table: &TableRef, client: Option<&PayloadClient>) -> Result<Vec<Row>, String> {
+    let qualifier = table.qualifier();
+    Ok(load_table(&table.name, None, &[], None, client)?
+        .into_iter()
+        .map(|row| {
+            row.into_iter()
+                .map(|(key, value)| (format!("{qualifier}.{key}"), value))
+                .collect()
+        })
+        .collect())
 }
 
-fn parse_condition(condition: &str) -> Result<Condition, String> {
-    let re = Regex::new(r"(?i)\s*(.*?)\s*(=|!=|>|<|>=|<=|LIKE|IN)\s*(.*)\s*$")
-        .map_err(|err| format!("Regex error: {err}"))?;
-    let caps = re
-        .captures(condition)
-        .ok_or_else(|| format!("Invalid condition format: {condition}"))?;
-
-    let column = caps.get(1).map(|m| m.as_str().trim().to_string()).unwrap_or_default();
-    let operator = caps.get(2).map(|m| m.as_str()).unwrap_or("=");
-    let value_raw = caps.get(3).map(|m| m.as_str().trim()).unwrap_or("");
-
-    let operator = match operator.to_ascii_uppercase().as_str() {
-        "=" => Operator::Eq,
-        "!=" => Operator::Neq,
-        ">" => Operator::Gt,
-        "<" => Operator::Lt,
-        ">=" => Operator::Gte,
-        "<=" => Operator::Lte,
-        "LIKE" => Operator::Like,
-        "IN" => Operator::In,
-        other => return Err(format!("Unsupported operator: {other}")),
-    };
-
-    let value = if matches!(operator, Operator::In) {
-        let values = value_raw
-            .trim_matches(|c| c == '(' || c == ')')
-            .split(',')
-            .filter_map(|v| parse_value(v.trim()).ok())
-            .collect::<Vec<_>>();
-        Value::Array(values)
-    } else {
-        parse_value(value_raw)?
+/// Fetch documents for a live collection, pushing the WHERE clause, the primary
+/// sort and the limit down to the REST API when they translate exactly. Rows
+/// are still filtered and sorted locally afterwards, so without an exact
+/// pushdown every page of the collection is fetched.
+fn fetch_live_rows(
+    client: &PayloadClient,
+    slug: &str,
+    where_clause: Option<&Condition>,
+    order_by: &[OrderBy],
+    limit: Option<usize>,
+) -> Result<Vec<Row>, String> {
+    let mut params = Vec::new();
+    let where_pushed = match where_clause {
+        Some(condition) => {
+            let mut where_params = Vec::new();
+            let translated = condition_to_params(condition, "where", &mut where_params);
+            if translated {
+                params.extend(where_params);
+            }
+            translated
+        }
+        None => true,
     };
 
-    Ok(Condition::Comparison {
-        column,
-        operator,
-        value,
-    })
-}
-
-fn parse_value(raw: &str) -> Result<Value, String> {
-    if raw.starts_with('"') && raw.ends_with('"') || raw.starts_with('\'') && raw.ends_with('\'') {
-        let trimmed = raw.trim_matches(|c| c == '"' || c == '\'');
-        return Ok(Value::String(trimmed.to_string()));
+    if let Some(order) = order_by.first() {
+        let sort = match order.direction {
+            SortDirection::Asc => order.column.clone(),
+            SortDirection::Desc => format!("-{}", order.column),
+        };
+        params.push(("sort".to_string(), sort));
     }
 
-    if let Ok(num) = raw.parse::<f64>() {
-        return Ok(Value::from(num));
-    }
+    let pushed_limit = limit.filter(|_| where_pushed && order_by.len() <= 1);
+    params.push((
+        "limit".to_string(),
+        pushed_limit.unwrap_or(LIVE_PAGE_SIZE).to_string(),
+    ));
+    params.push(("depth".to_string(), "0".to_string()));
 
-    if raw.eq_ignore_ascii_case("true") {
-        return Ok(Value::Bool(true));
-    }
-    if raw.eq_ignore_ascii_case("false") {
-        return Ok(Value::Bool(false));
-    }
-    if raw.eq_ignore_ascii_case("null") {
-        return Ok(Value::Null);
+    let mut rows = Vec::new();
+    let mut page = 1;
+    loop {
+        let mut page_params = params.clone();
+        page_params.push(("page".to_string(), page.to_string()));
+        let body = client
+            .find_page(slug, &page_params)
+            .map_err(|err| err.to_string())?;
+        let docs = body["docs"].as_array().cloned().unwrap_or_default();
+        let done = docs.is_empty()
+            || pushed_limit.is_some()
+            || !body["hasNextPage"].as_bool().unwrap_or(false);
+        rows.extend(docs.into_iter().filter_map(|doc| match doc {
+            Value::Object(map) => Some(map),
+            _ => None,
+        }));
+        if done {
+            break;
+        }
+        page += 1;
     }
+    tracing::info!("Fetched {} documents from collection {slug}", rows.len());
+    Ok(rows)
+}
 
-    Ok(Value::String(raw.to_string()))
+/// Translate a condition into Payload REST `where` query parameters. Returns
+/// `false` when any part of the condition has no exact REST equivalent.
+fn condition_to_params(
+    condition: &Condition,
+    prefix: &str,
+    out: &mut Vec<(String, String)>,
+) -> bool {
+    match condition {
+        Condition::And(conditions) => conditions.iter().enumerate().all(|(index, c)| {
+            condition_to_params(c, &format!("{prefix}[and][{index}]"), out)
+        }),
+        Condition::Or(conditions) => conditions.iter().enumerate().all(|(index, c)| {
+            condition_to_params(c, &format!("{prefix}[or][{index}]"), out)
+        }),
+        Condition::IsNull { column } => {
+            out.push((format!("{prefix}[{column}][exists]"), "false".to_string()));
+            true
+        }
+        Condition::Not(inner) => match inner.as_ref() {
+            Condition::IsNull { column } => {
+                out.push((format!("{prefix}[{column}][exists]"), "true".to_string()));
+                true
+            }
+            Condition::Comparison {
+                column,
+                operator: Operator::Eq,
+                value,
+            } => match param_value(value) {
+                Some(value) => {
+                    out.push((format!("{prefix}[{column}][not_equals]"), value));
+                    true
+                }
+                None => false,
+            },
+            Condition::Comparison {
+                column,
+                operator: Operator::In,
+                value: Value::Array(values),
+            } => match values.iter().map(param_value).collect::<Option<Vec<_>>>() {
+                Some(values) => {
+                    out.push((format!("{prefix}[{column}][not_in]"), values.join(",")));
+                    true
+                }
+                None => false,
+            },
+            _ => false,
+        },
+        Condition::Comparison {
+            column,
+            operator,
+            value,
+        } => {
+            let translated = match (operator, value) {
+                (Operator::Eq, Value::Null) => Some(("exists", "false".to_string())),
+                (Operator::Neq, Value::Null) => Some(("exists", "true".to_string())),
+                (Operator::Eq, v) => param_value(v).map(|v| ("equals", v)),
+                (Operator::Neq, v) => param_value(v).map(|v| ("not_equals", v)),
+                (Operator::Gt, v) => param_value(v).map(|v| ("greater_than", v)),
+                (Operator::Gte, v) => param_value(v).map(|v| ("greater_than_equal", v)),
+                (Operator::Lt, v) => param_value(v).map(|v| ("less_than", v)),
+                (Operator::Lte, v) => param_value(v).map(|v| ("less_than_equal", v)),
+                (Operator::Like, v) => v
+                    .as_str()
+                    .and_then(like_contains)
+                    .map(|text| ("contains", text)),
+                (Operator::In, Value::Array(values)) => values
+                    .iter()
+                    .map(param_value)
+                    .collect::<Option<Vec<_>>>()
+                    .map(|values| ("in", values.join(","))),
+                (Operator::In, _) => None,
+            };
+            match translated {
+                Some((op, value)) => {
+                    out.push((format!("{prefix}[{column}][{op}]"), value));
+                    true
+                }
+                None => false,
+            }
+        }
+        Condition::ColumnComparison { .. } => false,
+    }
 }
 
-fn parse_order_by_clause(order_by_clause: &str) -> Vec<OrderBy> {
-    order_by_clause
-        .split(',')
-        .filter_map(|part| {
-            let mut split = part.trim().split_whitespace();
-            let column = split.next()?.to_string();
-            let direction = split
-                .next()
-                .map(|dir| {
-                    if dir.eq_ignore_ascii_case("DESC") {
-                        SortDirection::Desc
-                    } else {
-                        SortDirection::Asc
-                    }
-                })
-                .unwrap_or(SortDirection::Asc);
-            Some(OrderBy { column, direction })
-        })
-        .collect()
+fn param_value(value: &Value) -> Option<String> {
+    match value {
+        Value::String(s) => Some(s.clone()),
+        Value::Number(n) => Some(n.to_string()),
+        Value::Bool(b) => Some(b.to_string()),
+        _ => None,
+    }
 }
 
 fn execute_select_query(
     columns: Vec<String>,
-    table: String,
+    mut data: Vec<Row>,
     where_clause: Option<Condition>,
     order_by: Vec<OrderBy>,
     limit: Option<usize>,
 ) -> Result<SqlQueryResult, String> {
-    let mut data: Vec<ValidationRule> = match table.to_ascii_lowercase().as_str() {
-        "validation_rules" => validation_rules(),
-        _ => return Err(format!("Unknown table: {table}")),
-    };
-
     if let Some(condition) = where_clause {
         data.retain(|item| evaluate_where_clause(item, &condition));
     }
@@ -265,24 +474,11 @@ fn execute_select_query(
         .into_iter()
         .map(|item| {
             if select_all {
-                serde_json::to_value(&item).unwrap_or_else(|_| Value::Null)
+                Value::Object(item)
             } else {
-                let mut map = serde_json::Map::new();
+                let mut map = Map::new();
                 for column in &columns {
-                    let value = match column.as_str() {
-                        "id" => Value::String(item.id.clone()),
-                        "name" => Value::String(item.name.clone()),
-                        "description" => Value::String(item.description.clone()),
-                        "category" => Value::String(item.category.clone()),
-                        "fileTypes" | "file_types" => json!(
-                            item.file_types.iter().map(|ft| ft.as_str()).collect::<Vec<_>>()
-                        ),
-                        "examples" => json!({
-                            "valid": item.examples.valid,
-                            "invalid": item.examples.invalid,
-                        }),
-                        _ => Value::Null,
-                    };
+                    let value = lookup_column(&item, column).cloned().unwrap_or(Value::Null);
                     map.insert(column.clone(), value);
                 }
                 Value::Object(map)
@@ -302,13 +498,8 @@ fn execute_select_query(
     Ok(SqlQueryResult { columns, rows })
 }
 
-fn execute_describe_query(table: String) -> Result<SqlQueryResult, String> {
-    let rules = match table.to_ascii_lowercase().as_str() {
-        "validation_rules" => validation_rules(),
-        _ => return Err(format!("Unknown table: {table}")),
-    };
-
-    let Some(sample) = rules.first() else {
+fn execute_describe_query(rows: Vec<Row>) -> Result<SqlQueryResult, String> {
+    let Some(sample) = rows.first() else {
         return Ok(SqlQueryResult {
             columns: Vec::new(),
             rows: Vec::new(),
@@ -322,72 +513,132 @@ fn execute_describe_query(table: String) -> Result<SqlQueryResult, String> {
     ];
 
     let mut rows = Vec::new();
-    let value = serde_json::to_value(sample).map_err(|e| e.to_string())?;
-    if let Some(obj) = value.as_object() {
-        for (key, val) in obj {
-            let type_str = match val {
-                Value::Array(_) => "object",
-                Value::Null => "object",
-                Value::Bool(_) => "boolean",
-                Value::Number(_) => "number",
-                Value::String(_) => "string",
-                Value::Object(_) => "object",
-            };
-            rows.push(json!({
-                "Field": key,
-                "Type": type_str,
-                "Description": format!("Field {key} of type {type_str}"),
-            }));
-        }
+    for (key, val) in sample {
+        let type_str = match val {
+            Value::Array(_) => "object",
+            Value::Null => "object",
+            Value::Bool(_) => "boolean",
+            Value::Number(_) => "number",
+            Value::String(_) => "string",
+            Value::Object(_) => "object",
+        };
+        rows.push(json!({
+            "Field": key,
+            "Type": type_str,
+            "Description": format!("Field {key} of type {type_str}"),
+        }));
     }
 
     Ok(SqlQueryResult { columns, rows })
 }
 
-fn evaluate_where_clause(item: &ValidationRule, clause: &Condition) -> bool {
+/// Resolve a column against a row. Dotted paths descend into nested objects
+/// from the longest matching key, so both `alias.column` in joined rows and
+/// `column.nested` work. An unqualified name in a joined row matches the
+/// first table that has it, and `fileTypes` is an alias for `file_types`.
+fn lookup_column<'a>(row: &'a Row, column: &str) -> Option<&'a Value> {
+    if let Some(value) = row.get(column) {
+        return Some(value);
+    }
+    if column == "fileTypes" {
+        return row.get("file_types");
+    }
+    for (index, _) in column.rmatch_indices('.') {
+        if let Some(value) = row.get(&column[..index]) {
+            return column[index + 1..]
+                .split('.')
+                .try_fold(value, |current, part| current.get(part));
+        }
+    }
+    let suffix = format!(".{column}");
+    row.iter()
+        .find(|(key, _)| {
+            key.strip_suffix(suffix.as_str())
+                .is_some_and(|qualifier| !qualifier.contains('.'))
+        })
+        .map(|(_, value)| value)
+}
+
+fn evaluate_where_clause(item: &Row, clause: &Condition) -> bool {
     match clause {
         Condition::And(conditions) => conditions.iter().all(|c| evaluate_where_clause(item, c)),
         Condition::Or(conditions) => conditions.iter().any(|c| evaluate_where_clause(item, c)),
+        Condition::Not(condition) => !evaluate_where_clause(item, condition),
         Condition::Comparison {
             column,
             operator,
             value,
         } => evaluate_condition(item, column, *operator, value),
+        Condition::IsNull { column } => lookup_column(item, column).is_none_or(Value::is_null),
+        Condition::ColumnComparison {
+            left,
+            operator,
+            right,
+        } => {
+            let left = lookup_column(item, left).cloned().unwrap_or(Value::Null);
+            let right = lookup_column(item, right).cloned().unwrap_or(Value::Null);
+            compare_columns(&left, *operator, &right)
+        }
     }
 }
 
-fn evaluate_condition(
-    item: &ValidationRule,
-    column: &str,
-    operator: Operator,
-    value: &Value,
-) -> bool {
-    let item_value = match column {
-        "id" => Value::String(item.id.clone()),
-        "name" => Value::String(item.name.clone()),
-        "description" => Value::String(item.description.clone()),
-        "category" => Value::String(item.category.clone()),
-        "fileTypes" | "file_types" => json!(
-            item.file_types.iter().map(|ft| ft.as_str()).collect::<Vec<_>>()
-        ),
-        _ => Value::Null,
-    };
+/// Compare two column values. Unlike a comparison with a literal, NULL on
+/// either side never matches, so LEFT JOINs keep unmatched rows.
+fn compare_columns(left: &Value, operator: Operator, right: &Value) -> bool {
+    if left.is_null() || right.is_null() {
+        return false;
+    }
+    evaluate_value(left, operator, right)
+}
+
+fn evaluate_condition(item: &Row, column: &str, operator: Operator, value: &Value) -> bool {
+    let item_value = lookup_column(item, column).cloned().unwrap_or(Value::Null);
+    evaluate_value(&item_value, operator, value)
+}
+
+fn pattern_cache() -> &'static MemoCache<Regex> {
+    static CACHE: OnceLock<MemoCache<Regex>> = OnceLock::new();
+    CACHE.get_or_init(|| MemoCache::new(PATTERN_CACHE_SIZE))
+}
 
+pub fn pattern_cache_stats() -> CacheStats {
+    pattern_cache().stats()
+}
+
+/// Case-insensitive regex for a `LIKE` pattern (`%` any run, `_` any char,
+/// [`LIKE_ESCAPE`] before either for the literal), compiled once per distinct
+/// pattern.
+fn like_regex(pattern: &str) -> Result<Regex, regex::Error> {
+    pattern_cache().get_or_try_insert(pattern, || {
+        let mut regex = String::from("(?is)^");
+        let mut chars = pattern.chars();
+        while let Some(c) = chars.next() {
+            match c {
+                '%' => regex.push_str(".*"),
+                '_' => regex.push('.'),
+                LIKE_ESCAPE => {
+                    let literal = chars.next().unwrap_or(LIKE_ESCAPE);
+                    regex.push_str(&regex::escape(&literal.to_string()));
+                }
+                c => regex.push_str(&regex::escape(&c.to_string())),
+            }
+        }
+        regex.push('$');
+        Regex::new(&regex)
+    })
+}
+
+fn evaluate_value(item_value: &Value, operator: Operator, value: &Value) -> bool {
     match operator {
-        Operator::Eq => item_value == *value,
-        Operator::Neq => item_value != *value,
-        Operator::Gt => compare_numbers(&item_value, value, |a, b| a > b),
-        Operator::Lt => compare_numbers(&item_value, value, |a, b| a < b),
-        Operator::Gte => compare_numbers(&item_value, value, |a, b| a >= b),
-        Operator::Lte => compare_numbers(&item_value, value, |a, b| a <= b),
+        Operator::Eq => values_match(item_value, value),
+        Operator::Neq => !values_match(item_value, value),
+        Operator::Gt => compare_ordered(item_value, value, Ordering::is_gt),
+        Operator::Lt => compare_ordered(item_value, value, Ordering::is_lt),
+        Operator::Gte => compare_ordered(item_value, value, Ordering::is_ge),
+        Operator::Lte => compare_ordered(item_value, value, Ordering::is_le),
         Operator::Like => {
             if let Some(text) = item_value.as_str() {
-                let pattern = value
-                    .as_str()
-                    .unwrap_or("")
-                    .replace('%', ".*")
-                    .replace('_', ".");
-                Regex::new(&format!("(?i)^{pattern}$"))
+                like_regex(value.as_str().unwrap_or(""))
                     .map(|re| re.is_match(text))
                     .unwrap_or(false)
             } else {
@@ -396,7 +647,7 @@ fn evaluate_condition(
         }
         Operator::In => {
             if let Some(arr) = value.as_array() {
-                arr.iter().any(|v| v == &item_value)
+                arr.iter().any(|v| values_equal(v, item_value))
             } else {
                 false
             }
@@ -404,50 +655,240 @@ fn evaluate_condition(
     }
 }
 
-fn compare_numbers<F>(left: &Value, right: &Value, cmp: F) -> bool
+/// Equality used by `=` and `!=`. Against an array the comparison holds when
+/// the array contains the scalar, so `file_types = 'field'` and
+/// `r.file_types = t.file_type` both test membership.
+fn values_match(left: &Value, right: &Value) -> bool {
+    match (left, right) {
+        (Value::Array(items), other) | (other, Value::Array(items)) if !other.is_array() => {
+            items.iter().any(|item| values_equal(item, other))
+        }
+        _ => values_equal(left, right),
+    }
+}
+
+/// Equality that treats integer and float representations of a number alike.
+fn values_equal(left: &Value, right: &Value) -> bool {
+    match (left.as_f64(), right.as_f64()) {
+        (Some(l), Some(r)) => l == r,
+        _ => left == right,
+    }
+}
+
+/// Ordering used by `>`, `<`, `>=` and `<=`. Numbers compare numerically,
+/// strings that both parse as ISO 8601 dates or timestamps compare in time,
+/// and other strings compare lexicographically. Any other pair never matches.
+fn compare_ordered<F>(left: &Value, right: &Value, cmp: F) -> bool
 where
-    F: Fn(f64, f64) -> bool,
+    F: Fn(Ordering) -> bool,
 {
-    let Some(l) = left.as_f64() else { return false };
-    let Some(r) = right.as_f64() else { return false };
-    cmp(l, r)
+    let ordering = match (left, right) {
+        (Value::Number(_), Value::Number(_)) => left
+            .as_f64()
+            .zip(right.as_f64())
+            .and_then(|(l, r)| l.partial_cmp(&r)),
+        (Value::String(l), Value::String(r)) => match (parse_timestamp(l), parse_timestamp(r)) {
+            (Some(l), Some(r)) => Some(l.cmp(&r)),
+            _ => Some(l.cmp(r)),
+        },
+        _ => None,
+    };
+    ordering.is_some_and(cmp)
+}
+
+/// An RFC 3339 timestamp, or a `YYYY-MM-DD` date taken as midnight UTC.
+fn parse_timestamp(text: &str) -> Option<DateTime<Utc>> {
+    if let Ok(timestamp) = DateTime::parse_from_rfc3339(text) {
+        return Some(timestamp.with_timezone(&Utc));
+    }
+    NaiveDate::parse_from_str(text, "%Y-%m-%d")
+        .ok()
+        .and_then(|date| date.and_hms_opt(0, 0, 0))
+        .map(|midnight| midnight.and_utc())
 }
 
-fn sort_data(data: &mut [ValidationRule], order_by: &[OrderBy]) {
+fn sort_data(data: &mut [Row], order_by: &[OrderBy]) {
     data.sort_by(|a, b| {
         for order in order_by {
-            let a_val = field_to_value(a, &order.column);
-            let b_val = field_to_value(b, &order.column);
+            let a_val = lookup_column(a, &order.column).filter(|v| !v.is_null());
+            let b_val = lookup_column(b, &order.column).filter(|v| !v.is_null());
             let ord = match (a_val, b_val) {
-                (Some(a), Some(b)) => a.cmp(&b),
-                (Some(_), None) => std::cmp::Ordering::Less,
-                (None, Some(_)) => std::cmp::Ordering::Greater,
-                _ => std::cmp::Ordering::Equal,
+                (Some(a), Some(b)) => compare_values(a, b),
+                (Some(_), None) => Ordering::Less,
+                (None, Some(_)) => Ordering::Greater,
+                _ => Ordering::Equal,
             };
-            if ord != std::cmp::Ordering::Equal {
+            if ord != Ordering::Equal {
                 return match order.direction {
                     SortDirection::Asc => ord,
                     SortDirection::Desc => ord.reverse(),
                 };
             }
         }
-        std::cmp::Ordering::Equal
+        Ordering::Equal
     });
 }
 
-fn field_to_value(rule: &ValidationRule, field: &str) -> Option<String> {
-    match field {
-        "id" => Some(rule.id.clone()),
-        "name" => Some(rule.name.clone()),
-        "description" => Some(rule.description.clone()),
-        "category" => Some(rule.category.clone()),
-        "fileTypes" | "file_types" => Some(
-            rule.file_types
-                .iter()
-                .map(|ft| ft.as_str())
-                .collect::<Vec<_>>()
-                .join(","),
-        ),
-        _ => None,
+fn compare_values(left: &Value, right: &Value) -> std::cmp::Ordering {
+    match (left.as_f64(), right.as_f64()) {
+        (Some(l), Some(r)) => l.partial_cmp(&r).unwrap_or(Ordering::Equal),
+        _ => sort_key(left).cmp(&sort_key(right)),
+    }
+}
+
+fn sort_key(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        Value::Array(items) => items.iter().map(sort_key).collect::<Vec<_>>().join(","),
+        other => other.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::payload_tools::query::query_validation_rules;
+    use crate::payload_tools::sql_parser::parse_condition;
+
+    #[test]
+    fn test_select_validation_rules() {
+        let result =
+            execute_sql_query("SELECT id, category FROM validation_rules WHERE category = 'security'")
+                .unwrap();
+        assert_eq!(result.columns, vec!["id", "category"]);
+        assert!(!result.rows.is_empty());
+        assert!(result.rows.iter().all(|row| row["category"] == "security"));
+    }
+
+    #[test]
+    fn test_builtin_tables_describe() {
+        for table in BUILTIN_TABLES {
+            let result = execute_sql_query(&format!("DESCRIBE {table}")).unwrap();
+            assert!(!result.rows.is_empty(), "{table} has no columns");
+        }
+    }
+
+    #[test]
+    fn test_join_rules_to_template_file_types() {
+        let result = execute_sql_query(
+            "SELECT r.id, r.category, t.file_type FROM validation_rules r \
+             JOIN template_types t ON r.file_types = t.file_type \
+             JOIN categories c ON c.name = r.category \
+             WHERE t.name = 'block' ORDER BY r.id",
+        )
+        .unwrap();
+        assert_eq!(result.columns, vec!["r.id", "r.category", "t.file_type"]);
+        assert!(!result.rows.is_empty());
+        assert!(result.rows.iter().all(|row| row["t.file_type"] == "field"));
+
+        let hooks = execute_sql_query(
+            "SELECT t.name, f.name FROM template_types t \
+             LEFT JOIN file_types f ON f.name = t.file_type WHERE t.name = 'hook'",
+        )
+        .unwrap();
+        assert_eq!(hooks.rows.len(), 1);
+        assert!(hooks.rows[0]["f.name"].is_null());
+    }
+
+    #[test]
+    fn test_prepared_query() {
+        let bindings = SqlBindings::Positional(vec![Value::from("security"), Value::from(2)]);
+        let result = execute_prepared_query(
+            "SELECT id FROM validation_rules WHERE category = ? LIMIT ?",
+            Some(&bindings),
+            None,
+        )
+        .unwrap();
+        assert_eq!(result.rows.len(), 2);
+    }
+
+    #[test]
+    fn test_array_equality_matches_membership() {
+        let result =
+            execute_sql_query("SELECT id FROM validation_rules WHERE file_types = 'global'").unwrap();
+        let expected = validation_rules()
+            .into_iter()
+            .filter(|rule| rule.file_types.contains(&FileType::Global))
+            .count();
+        assert_eq!(result.rows.len(), expected);
+        assert_eq!(
+            query_validation_rules("", Some(FileType::Global)).unwrap().len(),
+            expected
+        );
+    }
+
+    #[test]
+    fn test_like_wildcards_in_search_text_match_literally() {
+        let expected = validation_rules()
+            .into_iter()
+            .filter(|rule| {
+                [&rule.id, &rule.name, &rule.description, &rule.category]
+                    .iter()
+                    .any(|text| text.contains('_'))
+            })
+            .count();
+        assert_eq!(query_validation_rules("_", None).unwrap().len(), expected);
+        assert!(query_validation_rules("%%", None).unwrap().is_empty());
+
+        let row = to_row(json!({ "title": "50% off" }));
+        let matches = |sql: &str| evaluate_where_clause(&row, &parse_condition(sql).unwrap());
+        assert!(matches(r"title LIKE '50\% %'"));
+        assert!(matches("title LIKE '50!%%' ESCAPE '!'"));
+        assert!(!matches(r"title LIKE '50\_%'"));
+    }
+
+    #[test]
+    fn test_unknown_table_without_client() {
+        let err = execute_sql_query("SELECT * FROM posts").unwrap_err();
+        assert_eq!(err, "Unknown table: posts");
+    }
+
+    #[test]
+    fn test_range_comparisons_on_dates_and_strings() {
+        let row = to_row(json!({
+            "title": "Launch",
+            "createdAt": "2024-03-05T10:00:00.000Z",
+        }));
+        let matches = |sql: &str| evaluate_where_clause(&row, &parse_condition(sql).unwrap());
+
+        assert!(matches("createdAt > '2024-01-01'"));
+        assert!(matches("createdAt >= '2024-03-05' AND createdAt < '2024-03-06'"));
+        assert!(!matches("createdAt <= '2024-03-05T09:59:59+00:00'"));
+        assert!(matches("title >= 'L' AND title < 'M'"));
+        assert!(!matches("title > 10"));
+    }
+
+    #[test]
+    fn test_live_collections_shadow_builtin_tables() {
+        let live = vec!["categories".to_string(), "posts".to_string()];
+        assert_eq!(table_source("categories", Some(&live)), Some(TableSource::Live));
+        assert_eq!(table_source("posts", Some(&live)), Some(TableSource::Live));
+        assert_eq!(
+            table_source("builtin.categories", Some(&live)),
+            Some(TableSource::Builtin("categories"))
+        );
+        assert_eq!(table_source("tools", Some(&live)), Some(TableSource::Builtin("tools")));
+        assert_eq!(table_source("categories", None), Some(TableSource::Builtin("categories")));
+        assert_eq!(table_source("posts", None), None);
+
+        let result = execute_sql_query("SELECT c.name FROM builtin.categories c").unwrap();
+        assert_eq!(result.rows.len(), get_categories().len());
+    }
+
+    #[test]
+    fn test_condition_to_params() {
+        let condition = parse_condition("status = 'draft' AND title LIKE '%launch%'").unwrap();
+        let mut params = Vec::new();
+        assert!(condition_to_params(&condition, "where", &mut params));
+        assert_eq!(
+            params,
+            vec![
+                ("where[and][0][status][equals]".to_string(), "draft".to_string()),
+                ("where[and][1][title][contains]".to_string(), "launch".to_string()),
+            ]
+        );
+
+        let prefix_like = parse_condition("title LIKE 'launch%'").unwrap();
+        assert!(!condition_to_params(&prefix_like, "where", &mut Vec::new()));
     }
 }
@@ -0,0 +1,1041 @@
+//! Tokenizer and recursive-descent parser for the SQL dialect served by `mcp_query`.
+//!
+//! ```text
+//! query     := select | describe [';']
+//! select    := SELECT columns FROM table_ref join* [WHERE expr]
+//!              [ORDER BY ident [ASC|DESC] (',' ident [ASC|DESC])*] [LIMIT number]
+//! table_ref := ident [[AS] ident]
+//! join      := [INNER | LEFT [OUTER]] JOIN table_ref ON expr
+//! describe  := (DESCRIBE | DESC) ident
+//! columns   := '*' | ident (',' ident)*
+//! expr      := and_expr (OR and_expr)*
+//! and_expr  := not_expr (AND not_expr)*
+//! not_expr  := NOT not_expr | '(' expr ')' | predicate
+//! predicate := ident ( cmp_op (value | column_ref)
+//!                    | [NOT] LIKE value [ESCAPE 'char']
+//!                    | [NOT] IN '(' value (',' value)* ')'
+//!                    | [NOT] BETWEEN value AND value
+//!                    | IS [NOT] NULL )
+//! cmp_op    := '=' | '!=' | '<>' | '<' | '<=' | '>' | '>='
+//! value     := 'string' | number | TRUE | FALSE | NULL | bare-word | placeholder
+//! placeholder := '?' | ':' bare-word
+//! column_ref:= bare-word '.' bare-word                (e.g. `rules.category`)
+//! ident     := bare-word | "quoted" | `quoted`      (dots select nested fields)
+//! ```
+//!
+//! Keywords are case-insensitive. Strings use single quotes with `''` as the
+//! escape for a literal quote. In `LIKE` patterns `\%`, `\_` and `\\` match
+//! `%`, `_` and `\` literally; `ESCAPE` names another escape character. Once
+//! a query joins tables, columns are addressed as `alias.column`; an
+//! unqualified name resolves against whichever table has it. Conditions nest
+//! at most 128 levels of `NOT` and parentheses.
+//!
+//! Placeholders take their values from the bindings supplied with the query
+//! and are never re-parsed as SQL, so bound strings need no quoting. A bound
+//! array inside `IN (...)` expands to its elements, and `LIMIT` also accepts a
+//! placeholder.
+
+use serde_json::Value;
+
+use crate::payload_tools::types::SqlBindings;
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum Query {
+    Select {
+        columns: Vec<String>,
+        table: TableRef,
+        joins: Vec<Join>,
+        where_clause: Option<Condition>,
+        order_by: Vec<OrderBy>,
+        limit: Option<usize>,
+    },
+    Describe {
+        table: String,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum Condition {
+    And(Vec<Condition>),
+    Or(Vec<Condition>),
+    Not(Box<Condition>),
+    Comparison {
+        column: String,
+        operator: Operator,
+        value: Value,
+    },
+    IsNull {
+        column: String,
+    },
+    /// Comparison between two columns, as used in JOIN conditions.
+    ColumnComparison {
+        left: String,
+        operator: Operator,
+        right: String,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct TableRef {
+    pub name: String,
+    pub alias: Option<String>,
+}
+
+impl TableRef {
+    /// The name columns of this table are qualified with.
+    pub fn qualifier(&self) -> &str {
+        self.alias.as_deref().unwrap_or(&self.name)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Join {
+    pub kind: JoinKind,
+    pub table: TableRef,
+    pub on: Condition,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum JoinKind {
+    Inner,
+    Left,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum Operator {
+    Eq,
+    Neq,
+    Gt,
+    Lt,
+    Gte,
+    Lte,
+    Like,
+    In,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct OrderBy {
+    pub column: String,
+    pub direction: SortDirection,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum SortDirection {
+    Asc,
+    Desc,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Word(String),
+    QuotedIdent(String),
+    Str(String),
+    Number(Value),
+    Op(&'static str),
+    Comma,
+    LParen,
+    RParen,
+    Star,
+    Semicolon,
+    Positional,
+    Named(String),
+}
+
+impl Token {
+    fn describe(&self) -> String {
+        match self {
+            Token::Word(w) => format!("'{w}'"),
+            Token::QuotedIdent(w) => format!("\"{w}\""),
+            Token::Str(s) => format!("string '{s}'"),
+            Token::Number(n) => format!("number {n}"),
+            Token::Op(op) => format!("'{op}'"),
+            Token::Comma => "','".to_string(),
+            Token::LParen => "'('".to_string(),
+            Token::RParen => "')'".to_string(),
+            Token::Star => "'*'".to_string(),
+            Token::Semicolon => "';'".to_string(),
+            Token::Positional => "'?'".to_string(),
+            Token::Named(name) => format!("':{name}'"),
+        }
+    }
+}
+
+fn tokenize(input: &str) -> Result<Vec<Token>, String> {
+    let chars: Vec<char> = input.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+
+    while i < chars.len() {
+        let c = chars[i];
+        match c {
+            c if c.is_whitespace() => i += 1,
+            ',' => {
+                tokens.push(Token::Comma);
+                i += 1;
+            }
+            '(' => {
+                tokens.push(Token::LParen);
+                i += 1;
+            }
+            ')' => {
+                tokens.push(Token::RParen);
+                i += 1;
+            }
+            '*' => {
+                tokens.push(Token::Star);
+                i += 1;
+            }
+            ';' => {
+                tokens.push(Token::Semicolon);
+                i += 1;
+            }
+            '?' => {
+                tokens.push(Token::Positional);
+                i += 1;
+            }
+            ':' if chars.get(i + 1).is_some_and(|&n| is_name_char(n)) => {
+                let start = i + 1;
+                i = start;
+                while i < chars.len() && is_name_char(chars[i]) {
+                    i += 1;
+                }
+                tokens.push(Token::Named(chars[start..i].iter().collect()));
+            }
+            '=' => {
+                tokens.push(Token::Op("="));
+                i += if chars.get(i + 1) == Some(&'=') { 2 } else { 1 };
+            }
+            '!' if chars.get(i + 1) == Some(&'=') => {
+                tokens.push(Token::Op("!="));
+                i += 2;
+            }
+            '<' => match chars.get(i + 1) {
+                Some('=') => {
+                    tokens.push(Token::Op("<="));
+                    i += 2;
+                }
+                Some('>') => {
+                    tokens.push(Token::Op("!="));
+                    i += 2;
+                }
+                _ => {
+                    tokens.push(Token::Op("<"));
+                    i += 1;
+                }
+            },
+            '>' => {
+                if chars.get(i + 1) == Some(&'=') {
+                    tokens.push(Token::Op(">="));
+                    i += 2;
+                } else {
+                    tokens.push(Token::Op(">"));
+                    i += 1;
+                }
+            }
+            '\'' => {
+                let (text, next) = read_quoted(&chars, i, '\'')
+                    .ok_or_else(|| format!("Unterminated string starting at position {i}"))?;
+                tokens.push(Token::Str(text));
+                i = next;
+            }
+            '"' | '`' => {
+                let (text, next) = read_quoted(&chars, i, c)
+                    .ok_or_else(|| format!("Unterminated identifier starting at position {i}"))?;
+                tokens.push(Token::QuotedIdent(text));
+                i = next;
+            }
+            c if c.is_ascii_digit()
+                || (c == '-' && chars.get(i + 1).is_some_and(|n| n.is_ascii_digit())) =>
+            {
+                let start = i;
+                i += 1;
+                while i < chars.len() && (chars[i].is_ascii_digit() || chars[i] == '.') {
+                    i += 1;
+                }
+                let raw: String = chars[start..i].iter().collect();
+                tokens.push(Token::Number(parse_number(&raw)?));
+            }
+            c if is_word_start(c) => {
+                let start = i;
+                while i < chars.len() && is_word_char(chars[i]) {
+                    i += 1;
+                }
+                tokens.push(Token::Word(chars[start..i].iter().collect()));
+            }
+            other => return Err(format!("Unexpected character '{other}' at position {i}")),
+        }
+    }
+
+    Ok(tokens)
+}
+
+fn is_word_start(c: char) -> bool {
+    c.is_alphanumeric() || c == '_' || c == '%'
+}
+
+fn is_name_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_'
+}
+
+fn is_word_char(c: char) -> bool {
+    is_word_start(c) || c == '-' || c == '.'
+}
+
+/// Read a quoted run starting at `start` (the opening quote). A doubled quote
+/// inside the run is an escaped literal quote.
+fn read_quoted(chars: &[char], start: usize, quote: char) -> Option<(String, usize)> {
+    let mut out = String::new();
+    let mut i = start + 1;
+    while i < chars.len() {
+        if chars[i] == quote {
+            if chars.get(i + 1) == Some(&quote) {
+                out.push(quote);
+                i += 2;
+                continue;
+            }
+            return Some((out, i + 1));
+        }
+        out.push(chars[i]);
+        i += 1;
+    }
+    None
+}
+
+fn parse_number(raw: &str) -> Result<Value, String> {
+    if let Ok(int) = raw.parse::<i64>() {
+        return Ok(Value::from(int));
+    }
+    raw.parse::<f64>()
+        .map(Value::from)
+        .map_err(|_| format!("Invalid number: {raw}"))
+}
+
+/// Deepest nesting of `NOT` and parentheses a condition may have.
+const MAX_NESTING_DEPTH: usize = 128;
+
+struct Parser<'a> {
+    tokens: Vec<Token>,
+    pos: usize,
+    bindings: Option<&'a SqlBindings>,
+    positional_used: usize,
+    depth: usize,
+}
+
+/// Parse a query, substituting placeholders with the supplied bindings.
+pub(crate) fn parse_query_with_bindings(
+    sql: &str,
+    bindings: Option<&SqlBindings>,
+) -> Result<Query, String> {
+    let mut parser = Parser {
+        tokens: tokenize(sql)?,
+        pos: 0,
+        bindings,
+        positional_used: 0,
+        depth: 0,
+    };
+    let query = parser.parse_statement()?;
+    parser.eat(&Token::Semicolon);
+    if let Some(token) = parser.peek() {
+        return Err(format!("Unexpected {} after end of query", token.describe()));
+    }
+    if let Some(SqlBindings::Positional(values)) = bindings {
+        if values.len() != parser.positional_used {
+            return Err(format!(
+                "Query has {} '?' placeholder(s) but {} parameter(s) were supplied",
+                parser.positional_used,
+                values.len()
+            ));
+        }
+    }
+    Ok(query)
+}
+
+/// Parse a standalone boolean expression (the body of a WHERE clause).
+pub(crate) fn parse_condition(expr: &str) -> Result<Condition, String> {
+    let mut parser = Parser {
+        tokens: tokenize(expr)?,
+        pos: 0,
+        bindings: None,
+        positional_used: 0,
+        depth: 0,
+    };
+    let condition = parser.parse_expr()?;
+    if let Some(token) = parser.peek() {
+        return Err(format!("Unexpected {} in condition", token.describe()));
+    }
+    Ok(condition)
+}
+
+impl Parser<'_> {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.pos).cloned();
+        if token.is_some() {
+            self.pos += 1;
+        }
+        token
+    }
+
+    fn eat(&mut self, expected: &Token) -> bool {
+        if self.peek() == Some(expected) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn peek_keyword(&self, keyword: &str) -> bool {
+        matches!(self.peek(), Some(Token::Word(w)) if w.eq_ignore_ascii_case(keyword))
+    }
+
+    fn eat_keyword(&mut self, keyword: &str) -> bool {
+        if self.peek_keyword(keyword) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect_keyword(&mut self, keyword: &str) -> Result<(), String> {
+        if self.eat_keyword(keyword) {
+            Ok(())
+        } else {
+            Err(format!("Expected {keyword}, found {}", self.found()))
+        }
+    }
+
+    fn expect(&mut self, expected: &Token) -> Result<(), String> {
+        if self.eat(expected) {
+            Ok(())
+        } else {
+            Err(format!(
+                "Expected {}, found {}",
+                expected.describe(),
+                self.found()
+            ))
+        }
+    }
+
+    fn found(&self) -> String {
+        self.peek()
+            .map(Token::describe)
+            .unwrap_or_else(|| "end of query".to_string())
+    }
+
+    fn parse_statement(&mut self) -> Result<Query, String> {
+        if self.eat_keyword("SELECT") {
+            return self.parse_select();
+        }
+        if self.eat_keyword("DESCRIBE") || self.eat_keyword("DESC") {
+            let table = self.parse_ident()?;
+            return Ok(Query::Describe { table });
+        }
+        Err("Unsupported query type. Only SELECT and DESCRIBE are supported.".to_string())
+    }
+
+    fn parse_select(&mut self) -> Result<Query, String> {
+        let columns = if self.eat(&Token::Star) {
+            vec!["*".to_string()]
+        } else {
+            let mut columns = vec![self.parse_ident()?];
+            while self.eat(&Token::Comma) {
+                columns.push(self.parse_ident()?);
+            }
+            columns
+        };
+
+        self.expect_keyword("FROM")?;
+        let table = self.parse_table_ref()?;
+
+        let mut joins = Vec::new();
+        while let Some(kind) = self.parse_join_kind()? {
+            let table = self.parse_table_ref()?;
+            self.expect_keyword("ON")?;
+            let on = self.parse_expr()?;
+            joins.push(Join { kind, table, on });
+        }
+
+        let where_clause = if self.eat_keyword("WHERE") {
+            Some(self.parse_expr()?)
+        } else {
+            None
+        };
+
+        let mut order_by = Vec::new();
+        if self.eat_keyword("ORDER") {
+            self.expect_keyword("BY")?;
+            loop {
+                let column = self.parse_ident()?;
+                let direction = if self.eat_keyword("DESC") {
+                    SortDirection::Desc
+                } else {
+                    self.eat_keyword("ASC");
+                    SortDirection::Asc
+                };
+                order_by.push(OrderBy { column, direction });
+                if !self.eat(&Token::Comma) {
+                    break;
+                }
+            }
+        }
+
+        let limit = if self.eat_keyword("LIMIT") {
+            let found = self.found();
+            let limit = match self.next() {
+                Some(Token::Number(n)) => n.as_u64(),
+                Some(token @ (Token::Positional | Token::Named(_))) => {
+                    self.resolve_placeholder(token)?.as_u64()
+                }
+                _ => None,
+            }
+            .ok_or_else(|| format!("LIMIT expects a non-negative integer, found {found}"))?;
+            Some(limit as usize)
+        } else {
+            None
+        };
+
+        Ok(Query::Select {
+            columns,
+            table,
+            joins,
+            where_clause,
+            order_by,
+            limit,
+        })
+    }
+
+    fn parse_table_ref(&mut self) -> Result<TableRef, String> {
+        let name = self.parse_ident()?;
+        let alias = if self.eat_keyword("AS") {
+            Some(self.parse_ident()?)
+        } else {
+            match self.peek() {
+                Some(Token::Word(w)) if !is_reserved(w) => Some(self.parse_ident()?),
+                Some(Token::QuotedIdent(_)) => Some(self.parse_ident()?),
+                _ => None,
+            }
+        };
+        Ok(TableRef { name, alias })
+    }
+
+    fn parse_join_kind(&mut self) -> Result<Option<JoinKind>, String> {
+        let kind = if self.eat_keyword("INNER") || self.peek_keyword("JOIN") {
+            JoinKind::Inner
+        } else if self.eat_keyword("LEFT") {
+            self.eat_keyword("OUTER");
+            JoinKind::Left
+        } else {
+            return Ok(None);
+        };
+        self.expect_keyword("JOIN")?;
+        Ok(Some(kind))
+    }
+
+    fn parse_ident(&mut self) -> Result<String, String> {
+        let found = self.found();
+        match self.next() {
+            Some(Token::Word(w)) if !is_reserved(&w) => Ok(w),
+            Some(Token::QuotedIdent(w)) => Ok(w),
+            _ => Err(format!("Expected identifier, found {found}")),
+        }
+    }
+
+    fn parse_expr(&mut self) -> Result<Condition, String> {
+        let mut parts = vec![self.parse_and()?];
+        while self.eat_keyword("OR") {
+            parts.push(self.parse_and()?);
+        }
+        Ok(if parts.len() == 1 {
+            parts.remove(0)
+        } else {
+            Condition::Or(parts)
+        })
+    }
+
+    fn parse_and(&mut self) -> Result<Condition, String> {
+        let mut parts = vec![self.parse_not()?];
+        while self.eat_keyword("AND") {
+            parts.push(self.parse_not()?);
+        }
+        Ok(if parts.len() == 1 {
+            parts.remove(0)
+        } else {
+            Condition::And(parts)
+        })
+    }
+
+    fn parse_not(&mut self) -> Result<Condition, String> {
+        if self.eat_keyword("NOT") {
+            return Ok(Condition::Not(Box::new(self.nested(Self::parse_not)?)));
+        }
+        if self.eat(&Token::LParen) {
+            let inner = self.nested(Self::parse_expr)?;
+            self.expect(&Token::RParen)?;
+            return Ok(inner);
+        }
+        self.parse_predicate()
+    }
+
+    /// Rewrite `pattern` from the escape character that follows `ESCAPE` to
+    /// [`LIKE_ESCAPE`], the one patterns are evaluated with.
+    fn parse_like_escape(&mut self, pattern: Value) -> Result<Value, String> {
+        let escape = match self.parse_value()? {
+            Value::String(escape) if escape.chars().count() == 1 => escape.chars().next(),
+            _ => None,
+        }
+        .ok_or("ESCAPE takes a single character")?;
+        let Value::String(pattern) = pattern else {
+            return Err("LIKE with ESCAPE needs a string pattern".to_string());
+        };
+        let mut out = String::new();
+        let mut chars = pattern.chars();
+        while let Some(c) = chars.next() {
+            if c == escape {
+                let escaped = chars.next().ok_or_else(|| {
+                    format!("LIKE pattern '{pattern}' ends with its escape character")
+                })?;
+                out.push_str(&escape_like(&escaped.to_string()));
+            } else if c == LIKE_ESCAPE {
+                out.push_str(&escape_like(&c.to_string()));
+            } else {
+                out.push(c);
+            }
+        }
+        Ok(Value::String(out))
+    }
+
+    /// Run `parse` one nesting level deeper, failing past
+    /// [`MAX_NESTING_DEPTH`] rather than overflowing the stack.
+    fn nested(
+        &mut self,
+        parse: fn(&mut Self) -> Result<Condition, String>,
+    ) -> Result<Condition, String> {
+        if self.depth == MAX_NESTING_DEPTH {
+            return Err(format!("Condition is nested more than {MAX_NESTING_DEPTH} levels deep"));
+        }
+        self.depth += 1;
+        let condition = parse(self);
+        self.depth -= 1;
+        condition
+    }
+
+    fn parse_predicate(&mut self) -> Result<Condition, String> {
+        let column = self.parse_ident()?;
+
+        if self.eat_keyword("IS") {
+            let negated = self.eat_keyword("NOT");
+            self.expect_keyword("NULL")?;
+            let condition = Condition::IsNull { column };
+            return Ok(negate_if(condition, negated));
+        }
+
+        let negated = self.eat_keyword("NOT");
+        if self.eat_keyword("LIKE") {
+            let mut value = self.parse_value()?;
+            if self.eat_keyword("ESCAPE") {
+                value = self.parse_like_escape(value)?;
+            }
+            let condition = Condition::Comparison {
+                column,
+                operator: Operator::Like,
+                value,
+            };
+            return Ok(negate_if(condition, negated));
+        }
+        if self.eat_keyword("IN") {
+            self.expect(&Token::LParen)?;
+            let mut values = Vec::new();
+            loop {
+                let is_placeholder =
+                    matches!(self.peek(), Some(Token::Positional | Token::Named(_)));
+                match self.parse_value()? {
+                    Value::Array(items) if is_placeholder => values.extend(items),
+                    value => values.push(value),
+                }
+                if !self.eat(&Token::Comma) {
+                    break;
+                }
+            }
+            self.expect(&Token::RParen)?;
+            let condition = Condition::Comparison {
+                column,
+                operator: Operator::In,
+                value: Value::Array(values),
+            };
+            return Ok(negate_if(condition, negated));
+        }
+        if self.eat_keyword("BETWEEN") {
+            let low = self.parse_value()?;
+            self.expect_keyword("AND")?;
+            let high = self.parse_value()?;
+            let condition = Condition::And(vec![
+                Condition::Comparison {
+                    column: column.clone(),
+                    operator: Operator::Gte,
+                    value: low,
+                },
+                Condition::Comparison {
+                    column,
+                    operator: Operator::Lte,
+                    value: high,
+                },
+            ]);
+            return Ok(negate_if(condition, negated));
+        }
+        if negated {
+            return Err(format!(
+                "Expected LIKE, IN or BETWEEN after NOT, found {}",
+                self.found()
+            ));
+        }
+
+        let found = self.found();
+        let operator = match self.next() {
+            Some(Token::Op("=")) => Operator::Eq,
+            Some(Token::Op("!=")) => Operator::Neq,
+            Some(Token::Op("<")) => Operator::Lt,
+            Some(Token::Op("<=")) => Operator::Lte,
+            Some(Token::Op(">")) => Operator::Gt,
+            Some(Token::Op(">=")) => Operator::Gte,
+            _ => {
+                return Err(format!(
+                    "Expected comparison operator after '{column}', found {found}"
+                ));
+            }
+        };
+        // A dotted bare word on the right-hand side refers to another column.
+        if matches!(self.peek(), Some(Token::Word(w)) if w.contains('.') && !is_reserved(w)) {
+            let right = self.parse_ident()?;
+            return Ok(Condition::ColumnComparison {
+                left: column,
+                operator,
+                right,
+            });
+        }
+        let value = self.parse_value()?;
+        Ok(Condition::Comparison {
+            column,
+            operator,
+            value,
+        })
+    }
+
+    fn parse_value(&mut self) -> Result<Value, String> {
+        let found = self.found();
+        match self.next() {
+            Some(Token::Str(s)) => Ok(Value::String(s)),
+            Some(Token::Number(n)) => Ok(n),
+            Some(Token::Word(w)) if w.eq_ignore_ascii_case("true") => Ok(Value::Bool(true)),
+            Some(Token::Word(w)) if w.eq_ignore_ascii_case("false") => Ok(Value::Bool(false)),
+            Some(Token::Word(w)) if w.eq_ignore_ascii_case("null") => Ok(Value::Null),
+            // Bare words are accepted as strings for compatibility with
+            // unquoted values (e.g. `category = security`).
+            Some(Token::Word(w)) if !is_reserved(&w) => Ok(Value::String(w)),
+            Some(token @ (Token::Positional | Token::Named(_))) => self.resolve_placeholder(token),
+            _ => Err(format!("Expected a value, found {found}")),
+        }
+    }
+
+    fn resolve_placeholder(&mut self, token: Token) -> Result<Value, String> {
+        match (token, self.bindings) {
+            (Token::Positional, Some(SqlBindings::Positional(values))) => {
+                let index = self.positional_used;
+                self.positional_used += 1;
+                values.get(index).cloned().ok_or_else(|| {
+                    format!(
+                        "Placeholder ?{} has no value; {} parameter(s) were supplied",
+                        index + 1,
+                        values.len()
+                    )
+                })
+            }
+            (Token::Named(name), Some(SqlBindings::Named(values))) => values
+                .get(&name)
+                .cloned()
+                .ok_or_else(|| format!("No value supplied for placeholder :{name}")),
+            (Token::Positional, _) => Err(
+                "'?' placeholders require `params` to be an array of values".to_string(),
+            ),
+            (Token::Named(name), _) => Err(format!(
+                "Placeholder :{name} requires `params` to be an object of named values"
+            )),
+            (token, _) => Err(format!("Expected a placeholder, found {}", token.describe())),
+        }
+    }
+}
+
+fn negate_if(condition: Condition, negated: bool) -> Condition {
+    if negated {
+        Condition::Not(Box::new(condition))
+    } else {
+        condition
+    }
+}
+
+/// Escape character of `LIKE` patterns.
+pub(crate) const LIKE_ESCAPE: char = '\\';
+
+/// `text` with `%`, `_` and [`LIKE_ESCAPE`] escaped, so it matches literally
+/// inside a `LIKE` pattern.
+pub(crate) fn escape_like(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        if matches!(c, '%' | '_' | LIKE_ESCAPE) {
+            out.push(LIKE_ESCAPE);
+        }
+        out.push(c);
+    }
+    out
+}
+
+/// The literal text of a `'%text%'` pattern without other wildcards, the
+/// only `LIKE` with a Payload equivalent (`contains`).
+pub(crate) fn like_contains(pattern: &str) -> Option<String> {
+    let inner = pattern.strip_prefix('%')?;
+    let mut text = String::new();
+    let mut chars = inner.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            LIKE_ESCAPE => text.push(chars.next()?),
+            '%' if chars.as_str().is_empty() => return Some(text),
+            '%' | '_' => return None,
+            c => text.push(c),
+        }
+    }
+    None
+}
+
+const RESERVED: &[&str] = &[
+    "SELECT", "FROM", "WHERE", "AND", "OR", "NOT", "ORDER", "BY", "LIMIT", "LIKE", "IN",
+    "BETWEEN", "IS", "ASC", "DESC", "DESCRIBE", "JOIN", "INNER", "LEFT", "OUTER", "ON", "AS",
+];
+
+fn is_reserved(word: &str) -> bool {
+    RESERVED.iter().any(|k| k.eq_ignore_ascii_case(word))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse_query(sql: &str) -> Result<Query, String> {
+        parse_query_with_bindings(sql, None)
+    }
+
+    fn cmp(column: &str, operator: Operator, value: Value) -> Condition {
+        Condition::Comparison {
+            column: column.to_string(),
+            operator,
+            value,
+        }
+    }
+
+    #[test]
+    fn test_quoted_strings_with_keywords() {
+        let condition = parse_condition("description = 'read AND write' OR name = 'it''s'").unwrap();
+        assert_eq!(
+            condition,
+            Condition::Or(vec![
+                cmp("description", Operator::Eq, Value::from("read AND write")),
+                cmp("name", Operator::Eq, Value::from("it's")),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_parentheses_and_not() {
+        let condition =
+            parse_condition("NOT (category = 'security' OR category = 'performance') AND id != 'x'")
+                .unwrap();
+        assert_eq!(
+            condition,
+            Condition::And(vec![
+                Condition::Not(Box::new(Condition::Or(vec![
+                    cmp("category", Operator::Eq, Value::from("security")),
+                    cmp("category", Operator::Eq, Value::from("performance")),
+                ]))),
+                cmp("id", Operator::Neq, Value::from("x")),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_in_between_is_null() {
+        let condition =
+            parse_condition("status IN ( 'draft' , 'published' ) AND views BETWEEN 1 AND 10 AND \"meta.title\" IS NOT NULL")
+                .unwrap();
+        assert_eq!(
+            condition,
+            Condition::And(vec![
+                cmp(
+                    "status",
+                    Operator::In,
+                    Value::Array(vec![Value::from("draft"), Value::from("published")])
+                ),
+                Condition::And(vec![
+                    cmp("views", Operator::Gte, Value::from(1)),
+                    cmp("views", Operator::Lte, Value::from(10)),
+                ]),
+                Condition::Not(Box::new(Condition::IsNull {
+                    column: "meta.title".to_string()
+                })),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_select_clauses() {
+        let query =
+            parse_query("select id, name from validation_rules order by category desc, id limit 5;")
+                .unwrap();
+        assert_eq!(
+            query,
+            Query::Select {
+                columns: vec!["id".to_string(), "name".to_string()],
+                table: TableRef {
+                    name: "validation_rules".to_string(),
+                    alias: None,
+                },
+                joins: Vec::new(),
+                where_clause: None,
+                order_by: vec![
+                    OrderBy {
+                        column: "category".to_string(),
+                        direction: SortDirection::Desc
+                    },
+                    OrderBy {
+                        column: "id".to_string(),
+                        direction: SortDirection::Asc
+                    },
+                ],
+                limit: Some(5),
+            }
+        );
+    }
+
+    #[test]
+    fn test_joins() {
+        let query = parse_query(
+            "SELECT r.id, t.name FROM validation_rules AS r \
+             JOIN template_types t ON r.file_types = t.file_type \
+             LEFT OUTER JOIN categories c ON c.name = r.category WHERE t.name = 'block'",
+        )
+        .unwrap();
+        let Query::Select { table, joins, .. } = query else {
+            panic!("expected SELECT");
+        };
+        assert_eq!(table.qualifier(), "r");
+        assert_eq!(
+            joins,
+            vec![
+                Join {
+                    kind: JoinKind::Inner,
+                    table: TableRef {
+                        name: "template_types".to_string(),
+                        alias: Some("t".to_string()),
+                    },
+                    on: Condition::ColumnComparison {
+                        left: "r.file_types".to_string(),
+                        operator: Operator::Eq,
+                        right: "t.file_type".to_string(),
+                    },
+                },
+                Join {
+                    kind: JoinKind::Left,
+                    table: TableRef {
+                        name: "categories".to_string(),
+                        alias: Some("c".to_string()),
+                    },
+                    on: Condition::ColumnComparison {
+                        left: "c.name".to_string(),
+                        operator: Operator::Eq,
+                        right: "r.category".to_string(),
+                    },
+                },
+            ]
+        );
+        assert!(parse_query("SELECT * FROM a LEFT b ON a.x = b.y").is_err());
+        assert!(parse_query("SELECT * FROM a JOIN b").is_err());
+    }
+
+    #[test]
+    fn test_placeholders() {
+        let bindings = SqlBindings::Positional(vec![
+            Value::from("it's; DROP TABLE x"),
+            Value::from(vec!["a", "b"]),
+            Value::from(3),
+        ]);
+        let query = parse_query_with_bindings(
+            "SELECT * FROM t WHERE name = ? AND status IN (?) LIMIT ?",
+            Some(&bindings),
+        )
+        .unwrap();
+        let Query::Select {
+            where_clause, limit, ..
+        } = query
+        else {
+            panic!("expected SELECT");
+        };
+        assert_eq!(
+            where_clause,
+            Some(Condition::And(vec![
+                cmp("name", Operator::Eq, Value::from("it's; DROP TABLE x")),
+                cmp("status", Operator::In, Value::from(vec!["a", "b"])),
+            ]))
+        );
+        assert_eq!(limit, Some(3));
+
+        let named: SqlBindings =
+            serde_json::from_value(serde_json::json!({ "category": "security" })).unwrap();
+        assert!(parse_query_with_bindings("SELECT * FROM t WHERE category = :category", Some(&named)).is_ok());
+        assert!(parse_query_with_bindings("SELECT * FROM t WHERE category = :other", Some(&named)).is_err());
+        assert!(parse_query_with_bindings("SELECT * FROM t WHERE a = ?", Some(&named)).is_err());
+        assert!(parse_query_with_bindings("SELECT * FROM t WHERE a = ?", Some(&bindings)).is_err());
+        assert!(parse_query("SELECT * FROM t WHERE a = ?").is_err());
+    }
+
+    #[test]
+    fn test_errors() {
+        assert!(parse_query("SELECT FROM x").is_err());
+        assert!(parse_query("SELECT * FROM x WHERE name = 'open").is_err());
+        assert!(parse_query("SELECT * FROM x WHERE (a = 1").is_err());
+        assert!(parse_query("DROP TABLE x").is_err());
+    }
+
+    #[test]
+    fn test_like_escape() {
+        let pattern = |sql: &str| match parse_condition(sql).unwrap() {
+            Condition::Comparison { value, .. } => value,
+            other => panic!("unexpected {other:?}"),
+        };
+        assert_eq!(pattern(r"a LIKE '50!%\_%' ESCAPE '!'"), Value::from(r"50\%\\_%"));
+        assert_eq!(pattern(r"a LIKE '50\%%' ESCAPE '\'"), Value::from(r"50\%%"));
+        assert!(parse_condition("a LIKE 'x!' ESCAPE '!'").is_err());
+        assert!(parse_condition("a LIKE 'x' ESCAPE 'ab'").is_err());
+
+        assert_eq!(escape_like(r"100%_\"), r"100\%\_\\");
+        assert_eq!(like_contains(r"%50\%%").as_deref(), Some("50%"));
+        assert_eq!(like_contains("%a_b%"), None);
+        assert_eq!(like_contains("launch%"), None);
+        assert_eq!(like_contains("%"), None);
+    }
+
+    #[test]
+    fn test_nesting_depth_limit() {
+        let nested = |depth: usize, open: &str, close: &str| {
+            format!("{}a = 1{}", open.repeat(depth), close.repeat(depth))
+        };
+        assert!(parse_condition(&nested(MAX_NESTING_DEPTH, "(", ")")).is_ok());
+        assert!(parse_condition(&nested(MAX_NESTING_DEPTH, "NOT ", "")).is_ok());
+
+        let err = parse_condition(&nested(MAX_NESTING_DEPTH + 1, "(", ")")).unwrap_err();
+        assert_eq!(err, "Condition is nested more than 128 levels deep");
+        assert!(parse_condition(&nested(100_000, "NOT ", "")).is_err());
+        assert!(parse_query(&format!("SELECT * FROM x WHERE {}", nested(100_000, "(", ")"))).is_err());
+    }
+}
@@ -0,0 +1,908 @@
+//! Persistent planning state: goals, their implementation plans and todos.
+//!
+//! Data lives in an SQLite database, by default
+//! `~/.software-planning-tool/planning.db`, opened in WAL mode so several
+//! transports or server processes can share it. Every change to a plan
+//! records a snapshot in `plan_history`.
+//!
+//! Schema snapshots registered with `register_schema` are kept in
+//! `schema_versions`, numbered per registry name.
+//!
+//! Tool results can be attached to a plan or todo as [`Artifact`]s: file
+//! paths of a scaffold, validation reports and generated code.
+//!
+//! The JSON document written by earlier versions (`data.json` next to the
+//! database) is imported on first open and renamed to `data.json.migrated`.
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::Duration;
+
+use chrono::Utc;
+use rusqlite::{params, Connection, OptionalExtension, Transaction};
+use serde::Deserialize;
+use serde_json::Value;
+use ulid::Ulid;
+
+use crate::payload_tools::{
+    schema_model::SchemaModel,
+    schema_registry::SchemaVersion,
+    types::{Artifact, ArtifactKind, Goal, GoalTodo, ImplementationPlan, PlanRevision, Todo},
+};
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS goals (
+    id TEXT PRIMARY KEY,
+    description TEXT NOT NULL,
+    created_at TEXT NOT NULL
+);
+CREATE TABLE IF NOT EXISTS plans (
+    goal_id TEXT PRIMARY KEY REFERENCES goals(id),
+    summary TEXT,
+    updated_at TEXT NOT NULL
+);
+CREATE TABLE IF NOT EXISTS todos (
+    id TEXT PRIMARY KEY,
+    goal_id TEXT NOT NULL REFERENCES goals(id),
+    position INTEGER NOT NULL,
+    title TEXT NOT NULL,
+    description TEXT NOT NULL,
+    complexity INTEGER NOT NULL,
+    code_example TEXT,
+    is_complete INTEGER NOT NULL,
+    created_at TEXT NOT NULL,
+    updated_at TEXT NOT NULL
+);
+CREATE INDEX IF NOT EXISTS todos_goal ON todos (goal_id, position);
+CREATE INDEX IF NOT EXISTS todos_incomplete ON todos (is_complete);
+CREATE TABLE IF NOT EXISTS artifacts (
+    id TEXT PRIMARY KEY,
+    goal_id TEXT NOT NULL REFERENCES goals(id),
+    todo_id TEXT REFERENCES todos(id),
+    kind TEXT NOT NULL,
+    reference TEXT NOT NULL,
+    content TEXT,
+    source_tool TEXT NOT NULL,
+    created_at TEXT NOT NULL
+);
+CREATE INDEX IF NOT EXISTS artifacts_goal ON artifacts (goal_id, todo_id);
+CREATE TABLE IF NOT EXISTS plan_history (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    goal_id TEXT NOT NULL REFERENCES goals(id),
+    event TEXT NOT NULL,
+    recorded_at TEXT NOT NULL,
+    plan TEXT NOT NULL
+);
+CREATE TABLE IF NOT EXISTS schema_versions (
+    name TEXT NOT NULL,
+    version INTEGER NOT NULL,
+    label TEXT,
+    recorded_at TEXT NOT NULL,
+    schema TEXT NOT NULL,
+    PRIMARY KEY (name, version)
+);
+";
+
+/// Layout of the legacy `data.json` document.
+#[derive(Debug, Default, Deserialize)]
+struct LegacyData {
+    goals: HashMap<String, Goal>,
+    plans: HashMap<String, ImplementationPlan>,
+}
+
+/// Fields of a todo supplied by the caller.
+#[derive(Clone, Debug)]
+pub struct NewTodo {
+    pub title: String,
+    pub description: String,
+    pub complexity: u8,
+    pub code_example: Option<String>,
+}
+
+/// Artifact fields derived from a tool result.
+#[derive(Clone, Debug)]
+pub struct NewArtifact {
+    pub kind: ArtifactKind,
+    pub reference: String,
+    pub content: Option<Value>,
+}
+
+/// Turn the structured result of `tool` into artifacts: one file artifact per
+/// path of a `fileStructure`, a report for validation results, a snippet for
+/// generated `code`, and the whole result otherwise.
+pub fn artifacts_from_result(tool: &str, result: &Value) -> Vec<NewArtifact> {
+    if let Some(tree) = result.get("fileStructure") {
+        let mut paths = Vec::new();
+        file_paths(tree, "", &mut paths);
+        paths.sort();
+        return paths
+            .into_iter()
+            .map(|path| NewArtifact {
+                kind: ArtifactKind::File,
+                reference: path,
+                content: None,
+            })
+            .collect();
+    }
+    if result.get("is_valid").is_some() {
+        let reference = result
+            .get("project_name")
+            .and_then(Value::as_str)
+            .map(|project| format!("{tool} report for {project}"))
+            .unwrap_or_else(|| format!("{tool} report"));
+        return vec![NewArtifact {
+            kind: ArtifactKind::ValidationReport,
+            reference,
+            content: Some(result.clone()),
+        }];
+    }
+    if let Some(code) = result.get("code") {
+        return vec![NewArtifact {
+            kind: ArtifactKind::CodeSnippet,
+            reference: format!("{tool} output"),
+            content: Some(code.clone()),
+        }];
+    }
+    vec![NewArtifact {
+        kind: ArtifactKind::ToolResult,
+        reference: format!("{tool} result"),
+        content: Some(result.clone()),
+    }]
+}
+
+fn file_paths(tree: &Value, prefix: &str, paths: &mut Vec<String>) {
+    let Value::Object(entries) = tree else {
+        return;
+    };
+    for (name, entry) in entries {
+        let path = format!("{prefix}{name}");
+        match entry {
+            Value::Object(_) => file_paths(entry, &format!("{path}/"), paths),
+            _ => paths.push(path),
+        }
+    }
+}
+
+/// A registered schema and its version.
+pub(crate) type StoredSchema = (SchemaVersion, SchemaModel);
+
+pub struct Storage {
+    path: PathBuf,
+    conn: Mutex<Connection>,
+}
+
+fn now() -> String {
+    Utc::now().to_rfc3339()
+}
+
+fn db_err(err: rusqlite::Error) -> String {
+    format!("Planning database error: {err}")
+}
+
+impl Storage {
+    pub fn default_dir() -> Result<PathBuf, String> {
+        dirs::home_dir()
+            .map(|home| home.join(".software-planning-tool"))
+            .ok_or_else(|| "Could not determine the home directory".to_string())
+    }
+
+    /// Open the default database, importing a legacy `data.json` if present.
+    pub fn open_default() -> Result<Self, String> {
+        let dir = Self::default_dir()?;
+        let storage = Self::open(dir.join("planning.db"))?;
+        let legacy = dir.join("data.json");
+        if legacy.exists() {
+            storage.import_json(&legacy)?;
+            let migrated = dir.join("data.json.migrated");
+            fs::rename(&legacy, &migrated)
+                .map_err(|err| format!("Failed to rename {}: {err}", legacy.display()))?;
+            tracing::info!("Imported planning data from {}", legacy.display());
+        }
+        Ok(storage)
+    }
+
+    /// Open (creating if needed) the database at `path`.
+    pub fn open(path: impl Into<PathBuf>) -> Result<Self, String> {
+        let path = path.into();
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir).map_err(|err| format!("Failed to create {}: {err}", dir.display()))?;
+        }
+        let conn = Connection::open(&path).map_err(db_err)?;
+        conn.busy_timeout(Duration::from_secs(5)).map_err(db_err)?;
+        conn.pragma_update(None, "journal_mode", "WAL").map_err(db_err)?;
+        conn.pragma_update(None, "foreign_keys", true).map_err(db_err)?;
+        conn.execute_batch(SCHEMA).map_err(db_err)?;
+        Ok(Self {
+            path,
+            conn: Mutex::new(conn),
+        })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Run `change` in a transaction, committing if it succeeds.
+    fn write<T>(&self, change: impl FnOnce(&Transaction) -> Result<T, String>) -> Result<T, String> {
+        let mut conn = self
+            .conn
+            .lock()
+            .map_err(|_| "Planning storage poisoned".to_string())?;
+        let tx = conn.transaction().map_err(db_err)?;
+        let result = change(&tx)?;
+        tx.commit().map_err(db_err)?;
+        Ok(result)
+    }
+
+    fn read<T>(&self, view: impl FnOnce(&Connection) -> Result<T, String>) -> Result<T, String> {
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|_| "Planning storage poisoned".to_string())?;
+        view(&conn)
+    }
+
+    /// Import goals and plans from a legacy JSON document. Goals already in
+    /// the database are left untouched.
+    pub fn import_json(&self, path: &Path) -> Result<usize, String> {
+        let text = fs::read_to_string(path)
+            .map_err(|err| format!("Failed to read {}: {err}", path.display()))?;
+        let data: LegacyData = serde_json::from_str(&text)
+            .map_err(|err| format!("Invalid planning data in {}: {err}", path.display()))?;
+        self.write(|tx| {
+            let mut imported = 0;
+            for goal in data.goals.values() {
+                if goal_exists(tx, &goal.id)? {
+                    continue;
+                }
+                insert_goal(tx, goal)?;
+                let plan = data.plans.get(&goal.id);
+                tx.execute(
+                    "INSERT INTO plans (goal_id, summary, updated_at) VALUES (?1, ?2, ?3)",
+                    params![
+                        goal.id,
+                        plan.and_then(|p| p.summary.clone()),
+                        plan.map(|p| p.updated_at.clone()).unwrap_or_else(now)
+                    ],
+                )
+                .map_err(db_err)?;
+                for (position, todo) in plan.map(|p| p.todos.as_slice()).unwrap_or_default().iter().enumerate() {
+                    insert_todo(tx, &goal.id, position, todo)?;
+                }
+                record_history(tx, &goal.id, "import")?;
+                imported += 1;
+            }
+            Ok(imported)
+        })
+    }
+
+    /// Create a goal with an empty plan.
+    pub fn create_goal(&self, description: &str) -> Result<(Goal, ImplementationPlan), String> {
+        let goal = Goal {
+            id: Ulid::new().to_string(),
+            description: description.to_string(),
+            created_at: now(),
+        };
+        self.write(|tx| {
+            insert_goal(tx, &goal)?;
+            tx.execute(
+                "INSERT INTO plans (goal_id, summary, updated_at) VALUES (?1, NULL, ?2)",
+                params![goal.id, now()],
+            )
+            .map_err(db_err)?;
+            record_history(tx, &goal.id, "start_planning")?;
+            Ok((goal.clone(), load_plan(tx, &goal.id)?))
+        })
+    }
+
+    pub fn get_plan(&self, goal_id: &str) -> Result<(Goal, ImplementationPlan), String> {
+        self.read(|conn| Ok((load_goal(conn, goal_id)?, load_plan(conn, goal_id)?)))
+    }
+
+    pub fn list_goals(&self) -> Result<Vec<Goal>, String> {
+        self.read(|conn| {
+            let mut stmt = conn
+                .prepare("SELECT id, description, created_at FROM goals ORDER BY created_at")
+                .map_err(db_err)?;
+            let goals = stmt
+                .query_map([], goal_from_row)
+                .map_err(db_err)?
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(db_err)?;
+            Ok(goals)
+        })
+    }
+
+    /// Todos across all goals, optionally only incomplete ones, in goal and
+    /// plan order.
+    pub fn list_todos(&self, incomplete_only: bool) -> Result<Vec<GoalTodo>, String> {
+        self.read(|conn| {
+            let mut stmt = conn
+                .prepare(
+                    "SELECT g.id, g.description, g.created_at, t.id, t.title, t.description, t.complexity,
+                            t.code_example, t.is_complete, t.created_at, t.updated_at
+                     FROM todos t JOIN goals g ON g.id = t.goal_id
+                     WHERE ?1 = 0 OR t.is_complete = 0
+                     ORDER BY g.created_at, t.position",
+                )
+                .map_err(db_err)?;
+            let todos = stmt
+                .query_map([incomplete_only], |row| {
+                    Ok(GoalTodo {
+                        goal: goal_from_row(row)?,
+                        todo: Todo {
+                            id: row.get(3)?,
+                            title: row.get(4)?,
+                            description: row.get(5)?,
+                            complexity: row.get(6)?,
+                            code_example: row.get(7)?,
+                            is_complete: row.get(8)?,
+                            artifacts: Vec::new(),
+                            created_at: row.get(9)?,
+                            updated_at: row.get(10)?,
+                        },
+                    })
+                })
+                .map_err(db_err)?
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(db_err)?;
+            Ok(todos)
+        })
+    }
+
+    /// Snapshots of a goal's plan after each change, oldest first.
+    pub fn plan_history(&self, goal_id: &str) -> Result<Vec<PlanRevision>, String> {
+        self.read(|conn| {
+            load_goal(conn, goal_id)?;
+            let mut stmt = conn
+                .prepare("SELECT event, recorded_at, plan FROM plan_history WHERE goal_id = ?1 ORDER BY id")
+                .map_err(db_err)?;
+            let rows = stmt
+                .query_map([goal_id], |row| {
+                    Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?, row.get::<_, String>(2)?))
+                })
+                .map_err(db_err)?
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(db_err)?;
+            rows.into_iter()
+                .map(|(event, recorded_at, plan)| {
+                    Ok(PlanRevision {
+                        event,
+                        recorded_at,
+                        plan: serde_json::from_str(&plan).map_err(|err| err.to_string())?,
+                    })
+                })
+                .collect()
+        })
+    }
+
+    pub fn add_todo(&self, goal_id: &str, todo: NewTodo) -> Result<Todo, String> {
+        if todo.complexity > 10 {
+            return Err("complexity must be between 0 and 10".to_string());
+        }
+        self.write(|tx| {
+            load_goal(tx, goal_id)?;
+            let todo = new_todo(todo);
+            insert_todo(tx, goal_id, next_position(tx, goal_id)?, &todo)?;
+            touch_plan(tx, goal_id, "add_todo")?;
+            Ok(todo)
+        })
+    }
+
+    /// Attach artifacts to a todo, or to the plan itself when `todo_id` is `None`.
+    pub fn attach_artifacts(
+        &self,
+        goal_id: &str,
+        todo_id: Option<&str>,
+        source_tool: &str,
+        artifacts: Vec<NewArtifact>,
+    ) -> Result<Vec<Artifact>, String> {
+        self.write(|tx| {
+            load_goal(tx, goal_id)?;
+            if let Some(todo_id) = todo_id {
+                let found = tx
+                    .query_row(
+                        "SELECT 1 FROM todos WHERE goal_id = ?1 AND id = ?2",
+                        [goal_id, todo_id],
+                        |_| Ok(()),
+                    )
+                    .optional()
+                    .map_err(db_err)?;
+                if found.is_none() {
+                    return Err(format!("Unknown todo: {todo_id}"));
+                }
+            }
+            let mut attached = Vec::new();
+            for artifact in artifacts {
+                let artifact = Artifact {
+                    id: Ulid::new().to_string(),
+                    kind: artifact.kind,
+                    reference: artifact.reference,
+                    content: artifact.content,
+                    source_tool: source_tool.to_string(),
+                    created_at: now(),
+                };
+                tx.execute(
+                    "INSERT INTO artifacts (id, goal_id, todo_id, kind, reference, content, source_tool, created_at)
+                     VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+                    params![
+                        artifact.id,
+                        goal_id,
+                        todo_id,
+                        artifact.kind.as_str(),
+                        artifact.reference,
+                        artifact.content.as_ref().map(Value::to_string),
+                        artifact.source_tool,
+                        artifact.created_at
+                    ],
+                )
+                .map_err(db_err)?;
+                attached.push(artifact);
+            }
+            touch_plan(tx, goal_id, "attach_result")?;
+            Ok(attached)
+        })
+    }
+
+    pub fn update_todo_status(&self, goal_id: &str, todo_id: &str, is_complete: bool) -> Result<Todo, String> {
+        self.write(|tx| {
+            let updated = tx
+                .execute(
+                    "UPDATE todos SET is_complete = ?1, updated_at = ?2 WHERE goal_id = ?3 AND id = ?4",
+                    params![is_complete, now(), goal_id, todo_id],
+                )
+                .map_err(db_err)?;
+            if updated == 0 {
+                return Err(format!("Unknown todo: {todo_id}"));
+            }
+            touch_plan(tx, goal_id, "update_todo_status")?;
+            load_plan(tx, goal_id)?
+                .todos
+                .into_iter()
+                .find(|todo| todo.id == todo_id)
+                .ok_or_else(|| format!("Unknown todo: {todo_id}"))
+        })
+    }
+
+    /// Store `text` as the plan summary and add a todo for each top-level
+    /// numbered item (`1. Title` or `1. Title: description`).
+    pub fn save_plan(&self, goal_id: &str, text: &str) -> Result<ImplementationPlan, String> {
+        self.write(|tx| {
+            load_goal(tx, goal_id)?;
+            tx.execute(
+                "UPDATE plans SET summary = ?1 WHERE goal_id = ?2",
+                params![text, goal_id],
+            )
+            .map_err(db_err)?;
+            let first = next_position(tx, goal_id)?;
+            for (position, item) in (first..).zip(numbered_items(text)) {
+                let (title, description) = item
+                    .split_once(": ")
+                    .map(|(title, description)| (title.to_string(), description.to_string()))
+                    .unwrap_or_else(|| (item.clone(), String::new()));
+                let todo = new_todo(NewTodo {
+                    title,
+                    description,
+                    complexity: 0,
+                    code_example: None,
+                });
+                insert_todo(tx, goal_id, position, &todo)?;
+            }
+            touch_plan(tx, goal_id, "save_plan")?;
+            load_plan(tx, goal_id)
+        })
+    }
+
+    /// Record `schema` as the next version of `name`. A schema identical to
+    /// the latest version is not stored again; that version is returned with
+    /// `false`. The latest version before the call is returned alongside.
+    pub fn register_schema(
+        &self,
+        name: &str,
+        label: Option<&str>,
+        schema: &SchemaModel,
+    ) -> Result<(StoredSchema, bool, Option<StoredSchema>), String> {
+        let text = serde_json::to_string(schema).map_err(|err| err.to_string())?;
+        self.write(|tx| {
+            let latest = load_schema(tx, name, None)?;
+            if let Some((version, stored)) = &latest {
+                if stored == schema {
+                    return Ok(((version.clone(), stored.clone()), false, latest));
+                }
+            }
+            let next = latest.as_ref().map_or(1, |(version, _)| version.version + 1);
+            tx.execute(
+                "INSERT INTO schema_versions (name, version, label, recorded_at, schema) VALUES (?1, ?2, ?3, ?4, ?5)",
+                params![name, next, label, now(), text],
+            )
+            .map_err(db_err)?;
+            let stored = load_schema(tx, name, Some(next))?.ok_or("Schema version was not stored")?;
+            Ok((stored, true, latest))
+        })
+    }
+
+    /// Registered versions, of one name or all, oldest first.
+    pub fn schema_versions(&self, name: Option<&str>) -> Result<Vec<SchemaVersion>, String> {
+        self.read(|conn| {
+            let mut stmt = conn
+                .prepare(
+                    "SELECT name, version, label, recorded_at, schema FROM schema_versions
+                     WHERE ?1 IS NULL OR name = ?1 ORDER BY name, version",
+                )
+                .map_err(db_err)?;
+            let rows = stmt
+                .query_map([name], schema_from_row)
+                .map_err(db_err)?
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(db_err)?;
+            rows.into_iter()
+                .map(|row| parse_schema(row).map(|(version, _)| version))
+                .collect()
+        })
+    }
+
+    /// A version of `name`, by default the latest.
+    pub fn schema_version(&self, name: &str, version: Option<u32>) -> Result<StoredSchema, String> {
+        self.read(|conn| load_schema(conn, name, version))?.ok_or_else(|| match version {
+            Some(version) => format!("Schema {name} has no version {version}"),
+            None => format!("No schema registered as {name}"),
+        })
+    }
+}
+
+type SchemaRow = (String, u32, Option<String>, String, String);
+
+fn schema_from_row(row: &rusqlite::Row) -> rusqlite::Result<SchemaRow> {
+    Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?, row.get(4)?))
+}
+
+fn parse_schema((name, version, label, recorded_at, schema): SchemaRow) -> Result<StoredSchema, String> {
+    let schema: SchemaModel = serde_json::from_str(&schema).map_err(|err| err.to_string())?;
+    let version = SchemaVersion {
+        name,
+        version,
+        label,
+        recorded_at,
+        collections: schema.collections.len(),
+        globals: schema.globals.len(),
+    };
+    Ok((version, schema))
+}
+
+fn load_schema(conn: &Connection, name: &str, version: Option<u32>) -> Result<Option<StoredSchema>, String> {
+    conn.query_row(
+        "SELECT name, version, label, recorded_at, schema FROM schema_versions
+         WHERE name = ?1 AND (?2 IS NULL OR version = ?2) ORDER BY version DESC LIMIT 1",
+        params![name, version],
+        schema_from_row,
+    )
+    .optional()
+    .map_err(db_err)?
+    .map(parse_schema)
+    .transpose()
+}
+
+fn new_todo(todo: NewTodo) -> Todo {
+    Todo {
+        id: Ulid::new().to_string(),
+        title: todo.title,
+        description: todo.description,
+        complexity: todo.complexity,
+        code_example: todo.code_example,
+        is_complete: false,
+        artifacts: Vec::new(),
+        created_at: now(),
+        updated_at: now(),
+    }
+}
+
+fn goal_from_row(row: &rusqlite::Row) -> rusqlite::Result<Goal> {
+    Ok(Goal {
+        id: row.get(0)?,
+        description: row.get(1)?,
+        created_at: row.get(2)?,
+    })
+}
+
+fn goal_exists(conn: &Connection, goal_id: &str) -> Result<bool, String> {
+    conn.query_row("SELECT 1 FROM goals WHERE id = ?1", [goal_id], |_| Ok(()))
+        .optional()
+        .map(|row| row.is_some())
+        .map_err(db_err)
+}
+
+fn load_goal(conn: &Connection, goal_id: &str) -> Result<Goal, String> {
+    conn.query_row(
+        "SELECT id, description, created_at FROM goals WHERE id = ?1",
+        [goal_id],
+        goal_from_row,
+    )
+    .optional()
+    .map_err(db_err)?
+    .ok_or_else(|| format!("Unknown goal: {goal_id}"))
+}
+
+fn load_plan(conn: &Connection, goal_id: &str) -> Result<ImplementationPlan, String> {
+    let (summary, updated_at) = conn
+        .query_row(
+            "SELECT summary, updated_at FROM plans WHERE goal_id = ?1",
+            [goal_id],
+            |row| Ok((row.get::<_, Option<String>>(0)?, row.get::<_, String>(1)?)),
+        )
+        .optional()
+        .map_err(db_err)?
+        .ok_or_else(|| format!("No plan for goal: {goal_id}"))?;
+    let mut stmt = conn
+        .prepare(
+            "SELECT id, title, description, complexity, code_example, is_complete, created_at, updated_at
+             FROM todos WHERE goal_id = ?1 ORDER BY position",
+        )
+        .map_err(db_err)?;
+    let todos = stmt
+        .query_map([goal_id], |row| {
+            Ok(Todo {
+                id: row.get(0)?,
+                title: row.get(1)?,
+                description: row.get(2)?,
+                complexity: row.get(3)?,
+                code_example: row.get(4)?,
+                is_complete: row.get(5)?,
+                artifacts: Vec::new(),
+                created_at: row.get(6)?,
+                updated_at: row.get(7)?,
+            })
+        })
+        .map_err(db_err)?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(db_err)?;
+    let mut todos = todos;
+    let mut plan_artifacts = Vec::new();
+    for (todo_id, artifact) in load_artifacts(conn, goal_id)? {
+        match todo_id.and_then(|id| todos.iter_mut().find(|todo| todo.id == id)) {
+            Some(todo) => todo.artifacts.push(artifact),
+            None => plan_artifacts.push(artifact),
+        }
+    }
+    Ok(ImplementationPlan {
+        goal_id: goal_id.to_string(),
+        summary,
+        todos,
+        artifacts: plan_artifacts,
+        updated_at,
+    })
+}
+
+/// Artifacts of a goal with the todo each is attached to.
+fn load_artifacts(conn: &Connection, goal_id: &str) -> Result<Vec<(Option<String>, Artifact)>, String> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT todo_id, id, kind, reference, content, source_tool, created_at
+             FROM artifacts WHERE goal_id = ?1 ORDER BY created_at, id",
+        )
+        .map_err(db_err)?;
+    let rows = stmt
+        .query_map([goal_id], |row| {
+            Ok((
+                row.get::<_, Option<String>>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, String>(2)?,
+                row.get::<_, String>(3)?,
+                row.get::<_, Option<String>>(4)?,
+                row.get::<_, String>(5)?,
+                row.get::<_, String>(6)?,
+            ))
+        })
+        .map_err(db_err)?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(db_err)?;
+    rows.into_iter()
+        .map(|(todo_id, id, kind, reference, content, source_tool, created_at)| {
+            let content = content
+                .map(|content| serde_json::from_str(&content))
+                .transpose()
+                .map_err(|err| err.to_string())?;
+            Ok((
+                todo_id,
+                Artifact {
+                    id,
+                    kind: kind.parse()?,
+                    reference,
+                    content,
+                    source_tool,
+                    created_at,
+                },
+            ))
+        })
+        .collect()
+}
+
+fn insert_goal(conn: &Connection, goal: &Goal) -> Result<(), String> {
+    conn.execute(
+        "INSERT INTO goals (id, description, created_at) VALUES (?1, ?2, ?3)",
+        params![goal.id, goal.description, goal.created_at],
+    )
+    .map(|_| ())
+    .map_err(db_err)
+}
+
+fn insert_todo(conn: &Connection, goal_id: &str, position: usize, todo: &Todo) -> Result<(), String> {
+    conn.execute(
+        "INSERT INTO todos (id, goal_id, position, title, description, complexity, code_example,
+                            is_complete, created_at, updated_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
+        params![
+            todo.id,
+            goal_id,
+            position as i64,
+            todo.title,
+            todo.description,
+            todo.complexity,
+            todo.code_example,
+            todo.is_complete,
+            todo.created_at,
+            todo.updated_at
+        ],
+    )
+    .map(|_| ())
+    .map_err(db_err)
+}
+
+fn next_position(conn: &Connection, goal_id: &str) -> Result<usize, String> {
+    conn.query_row(
+        "SELECT COALESCE(MAX(position) + 1, 0) FROM todos WHERE goal_id = ?1",
+        [goal_id],
+        |row| row.get::<_, i64>(0),
+    )
+    .map(|position| position as usize)
+    .map_err(db_err)
+}
+
+/// Bump the plan's `updated_at` and snapshot it.
+fn touch_plan(conn: &Connection, goal_id: &str, event: &str) -> Result<(), String> {
+    conn.execute(
+        "UPDATE plans SET updated_at = ?1 WHERE goal_id = ?2",
+        params![now(), goal_id],
+    )
+    .map_err(db_err)?;
+    record_history(conn, goal_id, event)
+}
+
+fn record_history(conn: &Connection, goal_id: &str, event: &str) -> Result<(), String> {
+    let plan = serde_json::to_string(&load_plan(conn, goal_id)?).map_err(|err| err.to_string())?;
+    conn.execute(
+        "INSERT INTO plan_history (goal_id, event, recorded_at, plan) VALUES (?1, ?2, ?3, ?4)",
+        params![goal_id, event, now(), plan],
+    )
+    .map(|_| ())
+    .map_err(db_err)
+}
+
+fn numbered_items(text: &str) -> Vec<String> {
+    text.lines()
+        .filter(|line| !line.starts_with(char::is_whitespace))
+        .filter_map(|line| {
+            let (number, rest) = line.split_once(". ")?;
+            (!number.is_empty() && number.chars().all(|c| c.is_ascii_digit()))
+                .then(|| rest.trim().to_string())
+        })
+        .filter(|item| !item.is_empty())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_path(ext: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("planning-{}.{ext}", Ulid::new()))
+    }
+
+    #[test]
+    fn test_plan_round_trip() {
+        let path = temp_path("db");
+        let storage = Storage::open(&path).unwrap();
+        let (goal, _) = storage.create_goal("Blog with authors").unwrap();
+        let plan = storage
+            .save_plan(&goal.id, "1. Posts collection: title, body\n   1. nested\n2. Authors")
+            .unwrap();
+        assert_eq!(plan.todos.len(), 2);
+        assert_eq!(plan.todos[0].description, "title, body");
+
+        let todo = storage.update_todo_status(&goal.id, &plan.todos[1].id, true).unwrap();
+        assert!(todo.is_complete);
+
+        let reopened = Storage::open(&path).unwrap();
+        let (_, plan) = reopened.get_plan(&goal.id).unwrap();
+        assert!(plan.todos[1].is_complete);
+        let incomplete = reopened.list_todos(true).unwrap();
+        assert_eq!(incomplete.len(), 1);
+        assert_eq!(incomplete[0].todo.title, "Posts collection");
+        let history = reopened.plan_history(&goal.id).unwrap();
+        assert_eq!(history.len(), 3);
+        assert!(history[0].plan.todos.is_empty());
+        let _ = fs::remove_file(path);
+    }
+
+    #[test]
+    fn test_attach_artifacts() {
+        let path = temp_path("db");
+        let storage = Storage::open(&path).unwrap();
+        let (goal, _) = storage.create_goal("Docs site").unwrap();
+        let plan = storage.save_plan(&goal.id, "1. Scaffold").unwrap();
+        let todo_id = plan.todos[0].id.clone();
+
+        let scaffold = serde_json::json!({ "fileStructure": { "src": { "payload.config.ts": "" }, "package.json": "{}" } });
+        let artifacts = artifacts_from_result("scaffold_project", &scaffold);
+        assert_eq!(artifacts[1].reference, "src/payload.config.ts");
+        storage
+            .attach_artifacts(&goal.id, Some(&todo_id), "scaffold_project", artifacts)
+            .unwrap();
+        let report = serde_json::json!({ "is_valid": true, "errors": [] });
+        storage
+            .attach_artifacts(&goal.id, None, "validate", artifacts_from_result("validate", &report))
+            .unwrap();
+
+        let (_, plan) = storage.get_plan(&goal.id).unwrap();
+        assert_eq!(plan.todos[0].artifacts.len(), 2);
+        assert_eq!(plan.artifacts[0].kind, ArtifactKind::ValidationReport);
+        assert!(storage.attach_artifacts(&goal.id, Some("missing"), "validate", Vec::new()).is_err());
+        let _ = fs::remove_file(path);
+    }
+
+    #[test]
+    fn test_schema_versions() {
+        use crate::payload_tools::schema_model::{SchemaCollection, SchemaField};
+
+        let path = temp_path("db");
+        let storage = Storage::open(&path).unwrap();
+        let mut schema = SchemaModel {
+            collections: vec![SchemaCollection {
+                slug: "posts".to_string(),
+                fields: vec![SchemaField::new("title", "text")],
+                ..Default::default()
+            }],
+            globals: Vec::new(),
+        };
+        let ((first, _), created, previous) = storage.register_schema("blog", Some("launch"), &schema).unwrap();
+        assert_eq!((first.version, created, previous), (1, true, None));
+        let ((again, _), created, _) = storage.register_schema("blog", None, &schema).unwrap();
+        assert_eq!((again.version, created), (1, false));
+
+        schema.collections[0].fields.push(SchemaField::new("body", "richText"));
+        let ((second, _), created, previous) = storage.register_schema("blog", None, &schema).unwrap();
+        assert_eq!((second.version, created), (2, true));
+        assert_eq!(previous.unwrap().0, first);
+
+        assert_eq!(storage.schema_versions(Some("blog")).unwrap().len(), 2);
+        assert!(storage.schema_versions(Some("shop")).unwrap().is_empty());
+        let (_, stored) = storage.schema_version("blog", Some(1)).unwrap();
+        assert_eq!(stored.collections[0].fields.len(), 1);
+        assert_eq!(storage.schema_version("blog", None).unwrap().0.label, None);
+        assert!(storage.schema_version("blog", Some(3)).is_err());
+        let _ = fs::remove_file(path);
+    }
+
+    #[test]
+    fn test_import_json() {
+        let json = temp_path("json");
+        fs::write(
+            &json,
+            r#"{"goals": {"g1": {"id": "g1", "description": "Shop", "createdAt": "2025-01-01T00:00:00Z"}},
+                "plans": {"g1": {"goalId": "g1", "summary": null, "updatedAt": "2025-01-01T00:00:00Z", "todos": [
+                    {"id": "t1", "title": "Products", "description": "", "complexity": 3, "codeExample": null,
+                     "isComplete": false, "createdAt": "2025-01-01T00:00:00Z", "updatedAt": "2025-01-01T00:00:00Z"}
+                ]}}}"#,
+        )
+        .unwrap();
+        let db = temp_path("db");
+        let storage = Storage::open(&db).unwrap();
+        assert_eq!(storage.import_json(&json).unwrap(), 1);
+        assert_eq!(storage.import_json(&json).unwrap(), 0);
+        let (_, plan) = storage.get_plan("g1").unwrap();
+        assert_eq!(plan.todos[0].complexity, 3);
+        let _ = fs::remove_file(json);
+        let _ = fs::remove_file(db);
+    }
+}
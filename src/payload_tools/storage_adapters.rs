@@ -0,0 +1,393 @@
+//! Cloud storage adapters for upload collections: S3, Cloudflare R2 (through
+//! the S3 adapter), Google Cloud Storage and Azure Blob Storage.
+//!
+//! Payload 2 wraps an adapter in `@payloadcms/plugin-cloud-storage`; Payload 3
+//! has one `@payloadcms/storage-*` plugin per provider. Credentials are always
+//! read from environment variables, which the scaffolder writes to `.env`.
+
+use std::collections::BTreeMap;
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::payload_tools::plugins::{
+    PayloadVersion, PluginConfig, env_used, render_call, to_literal,
+};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum StorageProvider {
+    S3,
+    R2,
+    Gcs,
+    Azure,
+}
+
+impl StorageProvider {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            StorageProvider::S3 => "s3",
+            StorageProvider::R2 => "r2",
+            StorageProvider::Gcs => "gcs",
+            StorageProvider::Azure => "azure",
+        }
+    }
+
+    /// Environment variables the generated config reads, with example values
+    /// for `.env`.
+    pub fn env(&self) -> &'static [(&'static str, &'static str)] {
+        match self {
+            StorageProvider::S3 => &[
+                ("S3_BUCKET", "my-bucket"),
+                ("S3_ACCESS_KEY_ID", ""),
+                ("S3_SECRET_ACCESS_KEY", ""),
+                ("S3_REGION", "us-east-1"),
+            ],
+            StorageProvider::R2 => &[
+                ("R2_BUCKET", "my-bucket"),
+                ("R2_ACCESS_KEY_ID", ""),
+                ("R2_SECRET_ACCESS_KEY", ""),
+                (
+                    "R2_ENDPOINT",
+                    "https://<account-id>.r2.cloudflarestorage.com",
+                ),
+            ],
+            StorageProvider::Gcs => &[
+                ("GCS_BUCKET", "my-bucket"),
+                ("GCS_PROJECT_ID", ""),
+                ("GCS_CREDENTIALS", "{}"),
+            ],
+            StorageProvider::Azure => &[
+                ("AZURE_STORAGE_CONTAINER_NAME", "media"),
+                ("AZURE_STORAGE_CONNECTION_STRING", ""),
+                (
+                    "AZURE_STORAGE_ACCOUNT_BASEURL",
+                    "https://<account>.blob.core.windows.net",
+                ),
+                ("AZURE_STORAGE_ALLOW_CONTAINER_CREATE", "false"),
+            ],
+        }
+    }
+
+    /// Variable holding the bucket (or Azure container) name.
+    fn bucket_env(&self) -> &'static str {
+        self.env()[0].0
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "kebab-case")]
+pub enum StorageAcl {
+    Private,
+    PublicRead,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct StorageAdapterOptions {
+    pub provider: StorageProvider,
+    /// Upload collections stored in the bucket (default `["media"]`)
+    pub collections: Option<Vec<String>>,
+    /// Bucket or Azure container name; read from the environment when omitted
+    pub bucket: Option<String>,
+    /// Key prefix for stored files
+    pub prefix: Option<String>,
+    /// Object ACL (S3 and GCS only)
+    pub acl: Option<StorageAcl>,
+    /// Serve files through short-lived signed URLs (S3 and R2 on Payload 3)
+    pub signed_urls: Option<bool>,
+    /// Lifetime of signed URLs in seconds (default 3600)
+    pub signed_url_expires_in: Option<u64>,
+    /// Payload major version, "2" (default) or "3"
+    pub payload_version: Option<PayloadVersion>,
+}
+
+/// npm packages and their version ranges.
+type Packages = &'static [(&'static str, &'static str)];
+
+fn env_ref(var: &str) -> String {
+    format!("process.env.{var}")
+}
+
+/// Per-collection options; `true` when there are none (Payload 3 only).
+fn collection_entry(prefix: Option<&str>, signed: Option<u64>, adapter: Option<&str>) -> String {
+    let mut parts = Vec::new();
+    if let Some(adapter) = adapter {
+        parts.push(format!("adapter: {adapter}"));
+    }
+    if let Some(prefix) = prefix {
+        parts.push(format!(
+            "prefix: {}",
+            to_literal(&Value::String(prefix.to_string()))
+        ));
+    }
+    if let Some(expires_in) = signed {
+        parts.push(format!(
+            "signedDownloads: {{ shouldUseSignedURL: () => true, expiresIn: {expires_in} }}"
+        ));
+    }
+    if parts.is_empty() {
+        "true".to_string()
+    } else {
+        format!("{{ {} }}", parts.join(", "))
+    }
+}
+
+/// S3 client config for S3 or R2.
+fn s3_client_config(provider: StorageProvider) -> String {
+    match provider {
+        StorageProvider::R2 => "{ credentials: { accessKeyId: process.env.R2_ACCESS_KEY_ID, secretAccessKey: process.env.R2_SECRET_ACCESS_KEY }, region: 'auto', endpoint: process.env.R2_ENDPOINT }".to_string(),
+        _ => "{ credentials: { accessKeyId: process.env.S3_ACCESS_KEY_ID, secretAccessKey: process.env.S3_SECRET_ACCESS_KEY }, region: process.env.S3_REGION }".to_string(),
+    }
+}
+
+/// Plugin setup storing `options.collections` with the chosen provider,
+/// indented for an entry of a `plugins` array at `indent`.
+pub fn storage_adapter_config(
+    options: &StorageAdapterOptions,
+    indent: &str,
+) -> Result<PluginConfig, String> {
+    let provider = options.provider;
+    let version = options.payload_version.unwrap_or_default();
+    let collections = options
+        .collections
+        .clone()
+        .filter(|collections| !collections.is_empty())
+        .unwrap_or_else(|| vec!["media".to_string()]);
+    let bucket = match &options.bucket {
+        Some(bucket) => to_literal(&Value::String(bucket.clone())),
+        None => env_ref(provider.bucket_env()),
+    };
+
+    let signed = match (options.signed_urls, options.signed_url_expires_in) {
+        (_, Some(expires_in)) => Some(expires_in),
+        (Some(true), None) => Some(3600),
+        _ => None,
+    };
+    if signed.is_some()
+        && !(version == PayloadVersion::V3
+            && matches!(provider, StorageProvider::S3 | StorageProvider::R2))
+    {
+        return Err(
+            "Signed URLs are only supported by the S3 and R2 adapters on Payload 3".to_string(),
+        );
+    }
+    let acl = match (options.acl, provider) {
+        (None, _) => None,
+        (Some(acl), StorageProvider::S3) => Some(match acl {
+            StorageAcl::Private => "'private'",
+            StorageAcl::PublicRead => "'public-read'",
+        }),
+        (Some(acl), StorageProvider::Gcs) => Some(match acl {
+            StorageAcl::Private => "'Private'",
+            StorageAcl::PublicRead => "'Public'",
+        }),
+        (Some(_), StorageProvider::R2) => {
+            return Err("R2 does not support object ACLs; make the bucket public or serve it through a custom domain".to_string());
+        }
+        (Some(_), StorageProvider::Azure) => {
+            return Err(
+                "Azure sets access per container; configure it on the container instead of acl"
+                    .to_string(),
+            );
+        }
+    };
+
+    // Options of the provider's client, shared by both versions.
+    let mut client: Vec<(String, String)> = match provider {
+        StorageProvider::S3 | StorageProvider::R2 => vec![
+            ("bucket".to_string(), bucket),
+            ("config".to_string(), s3_client_config(provider)),
+        ],
+        StorageProvider::Gcs => vec![
+            ("bucket".to_string(), bucket),
+            (
+                "options".to_string(),
+                "{ projectId: process.env.GCS_PROJECT_ID, credentials: JSON.parse(process.env.GCS_CREDENTIALS || '{}') }".to_string(),
+            ),
+        ],
+        StorageProvider::Azure => vec![
+            ("containerName".to_string(), bucket),
+            ("connectionString".to_string(), env_ref("AZURE_STORAGE_CONNECTION_STRING")),
+            ("baseURL".to_string(), env_ref("AZURE_STORAGE_ACCOUNT_BASEURL")),
+            (
+                "allowContainerCreate".to_string(),
+                "process.env.AZURE_STORAGE_ALLOW_CONTAINER_CREATE === 'true'".to_string(),
+            ),
+        ],
+    };
+    if let Some(acl) = acl {
+        client.push(("acl".to_string(), acl.to_string()));
+    }
+
+    let (imports, function, entries, dependencies): (Vec<&str>, &str, Vec<(String, String)>, Packages) = match version {
+        PayloadVersion::V3 => {
+            let collection_map = collections
+                .iter()
+                .map(|slug| {
+                    format!(
+                        "'{slug}': {}",
+                        collection_entry(options.prefix.as_deref(), signed, None)
+                    )
+                })
+                .collect::<Vec<_>>()
+                .join(", ");
+            let mut entries = vec![("collections".to_string(), format!("{{ {collection_map} }}"))];
+            entries.extend(client);
+            let (import, function, dependencies): (&str, &str, &[(&str, &str)]) = match provider {
+                StorageProvider::S3 | StorageProvider::R2 => (
+                    "import { s3Storage } from '@payloadcms/storage-s3';",
+                    "s3Storage",
+                    &[("@payloadcms/storage-s3", "^3.0.0")],
+                ),
+                StorageProvider::Gcs => (
+                    "import { gcsStorage } from '@payloadcms/storage-gcs';",
+                    "gcsStorage",
+                    &[("@payloadcms/storage-gcs", "^3.0.0")],
+                ),
+                StorageProvider::Azure => (
+                    "import { azureStorage } from '@payloadcms/storage-azure';",
+                    "azureStorage",
+                    &[("@payloadcms/storage-azure", "^3.0.0")],
+                ),
+            };
+            (vec![import], function, entries, dependencies)
+        }
+        PayloadVersion::V2 => {
+            let (import, adapter_function, dependencies): (&str, &str, &[(&str, &str)]) =
+                match provider {
+                    StorageProvider::S3 | StorageProvider::R2 => (
+                        "import { s3Adapter } from '@payloadcms/plugin-cloud-storage/s3';",
+                        "s3Adapter",
+                        &[
+                            ("@payloadcms/plugin-cloud-storage", "^1.0.0"),
+                            ("@aws-sdk/client-s3", "^3.0.0"),
+                            ("@aws-sdk/lib-storage", "^3.0.0"),
+                        ],
+                    ),
+                    StorageProvider::Gcs => (
+                        "import { gcsAdapter } from '@payloadcms/plugin-cloud-storage/gcs';",
+                        "gcsAdapter",
+                        &[
+                            ("@payloadcms/plugin-cloud-storage", "^1.0.0"),
+                            ("@google-cloud/storage", "^7.0.0"),
+                        ],
+                    ),
+                    StorageProvider::Azure => (
+                        "import { azureBlobStorageAdapter } from '@payloadcms/plugin-cloud-storage/azure';",
+                        "azureBlobStorageAdapter",
+                        &[
+                            ("@payloadcms/plugin-cloud-storage", "^1.0.0"),
+                            ("@azure/storage-blob", "^12.0.0"),
+                            ("@azure/abort-controller", "^1.0.0"),
+                        ],
+                    ),
+                };
+            let adapter = format!(
+                "{adapter_function}({{ {} }})",
+                client
+                    .iter()
+                    .map(|(key, code)| format!("{key}: {code}"))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            );
+            let collection_map = collections
+                .iter()
+                .map(|slug| {
+                    format!(
+                        "'{slug}': {}",
+                        collection_entry(options.prefix.as_deref(), None, Some(&adapter))
+                    )
+                })
+                .collect::<Vec<_>>()
+                .join(", ");
+            // Payload 2 configures the client on each collection's adapter.
+            (
+                vec![
+                    "import { cloudStorage } from '@payloadcms/plugin-cloud-storage';",
+                    import,
+                ],
+                "cloudStorage",
+                vec![("collections".to_string(), format!("{{ {collection_map} }}"))],
+                dependencies,
+            )
+        }
+    };
+
+    let env_vars: Vec<&str> = provider.env().iter().map(|(var, _)| *var).collect();
+    Ok(PluginConfig {
+        imports: imports.into_iter().map(str::to_string).collect(),
+        code: render_call(function, &entries, indent),
+        dependencies: dependencies
+            .iter()
+            .map(|(package, range)| (package.to_string(), range.to_string()))
+            .collect::<BTreeMap<_, _>>(),
+        env: env_used(&env_vars, &entries),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn options(provider: StorageProvider, version: PayloadVersion) -> StorageAdapterOptions {
+        StorageAdapterOptions {
+            provider,
+            collections: Some(vec!["media".to_string()]),
+            bucket: None,
+            prefix: Some("uploads".to_string()),
+            acl: None,
+            signed_urls: None,
+            signed_url_expires_in: None,
+            payload_version: Some(version),
+        }
+    }
+
+    #[test]
+    fn test_storage_adapter_config_per_provider() {
+        let r2 = StorageAdapterOptions {
+            signed_urls: Some(true),
+            ..options(StorageProvider::R2, PayloadVersion::V3)
+        };
+        let config = storage_adapter_config(&r2, "").unwrap();
+        assert_eq!(
+            config.imports,
+            vec!["import { s3Storage } from '@payloadcms/storage-s3';"]
+        );
+        assert!(config.code.contains("'media': { prefix: 'uploads', signedDownloads: { shouldUseSignedURL: () => true, expiresIn: 3600 } }"));
+        assert_eq!(
+            config.env,
+            vec![
+                "R2_BUCKET",
+                "R2_ACCESS_KEY_ID",
+                "R2_SECRET_ACCESS_KEY",
+                "R2_ENDPOINT"
+            ]
+        );
+
+        let gcs = StorageAdapterOptions {
+            acl: Some(StorageAcl::PublicRead),
+            ..options(StorageProvider::Gcs, PayloadVersion::V2)
+        };
+        let config = storage_adapter_config(&gcs, "").unwrap();
+        assert!(config.code.starts_with("cloudStorage({"));
+        assert!(
+            config
+                .code
+                .contains("adapter: gcsAdapter({ bucket: process.env.GCS_BUCKET")
+        );
+        assert!(config.code.contains("acl: 'Public'"));
+
+        let signed_v2 = StorageAdapterOptions {
+            signed_urls: Some(true),
+            ..options(StorageProvider::S3, PayloadVersion::V2)
+        };
+        assert!(storage_adapter_config(&signed_v2, "").is_err());
+        let azure_acl = StorageAdapterOptions {
+            acl: Some(StorageAcl::Private),
+            ..options(StorageProvider::Azure, PayloadVersion::V3)
+        };
+        assert!(storage_adapter_config(&azure_acl, "").is_err());
+    }
+}
@@ -0,0 +1,193 @@
+//! User templates that replace the built-in generator output.
+//!
+//! A file named after a template type (`collection.hbs`, `field.tera`, ...)
+//! in the templates directory is rendered instead of the built-in template
+//! for that type. `.hbs` files use Handlebars and `.tera` files use Tera;
+//! when both exist the Handlebars one is used. Files are read on every call,
+//! so edits apply without a restart.
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    sync::OnceLock,
+};
+
+use schemars::JsonSchema;
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::payload_tools::{
+    generator::{TemplateType, builtin_template, fields_code},
+    naming::Case,
+};
+
+/// Extensions of override files, in lookup order.
+pub const EXTENSIONS: [&str; 2] = ["hbs", "tera"];
+
+/// Variables every override can use besides the template's own options.
+const COMMON_VARIABLES: [(&str, &str); 3] = [
+    (
+        "templateType",
+        "Template type being rendered, e.g. collection",
+    ),
+    (
+        "options",
+        "All options passed to the generator, as one object",
+    ),
+    (
+        "builtin",
+        "Code the built-in template generates for the same options (null when it rejects them)",
+    ),
+];
+
+static DIR: OnceLock<Option<PathBuf>> = OnceLock::new();
+
+/// Set the templates directory (empty disables overrides); the first call
+/// wins.
+pub fn configure(dir: &str) {
+    let _ = DIR.set((!dir.is_empty()).then(|| PathBuf::from(dir)));
+}
+
+/// The configured templates directory, if overrides are enabled.
+pub fn templates_dir() -> Option<&'static Path> {
+    DIR.get_or_init(|| None).as_deref()
+}
+
+#[derive(Clone, Debug, Serialize, JsonSchema)]
+pub struct TemplateVariable {
+    pub name: &'static str,
+    pub description: &'static str,
+}
+
+/// Variables available to an override of `template_type`: its options, the
+/// common variables and the ones derived from the options.
+pub fn template_variables(template_type: TemplateType) -> Vec<TemplateVariable> {
+    let derived: &[(&'static str, &'static str)] = match template_type {
+        TemplateType::Collection | TemplateType::Global => &[
+            ("className", "Slug in PascalCase, for the config constant"),
+            (
+                "fieldsCode",
+                "Built-in code of each entry in fields, joined for a fields array",
+            ),
+        ],
+        TemplateType::Block => &[
+            ("className", "Name in PascalCase, for the block constant"),
+            (
+                "fieldsCode",
+                "Built-in code of each entry in fields, joined for a fields array",
+            ),
+        ],
+        TemplateType::Plugin | TemplateType::Migration | TemplateType::AccessControl => {
+            &[("className", "Name in PascalCase, when a name is given")]
+        }
+        _ => &[],
+    };
+    template_type
+        .options()
+        .iter()
+        .chain(&COMMON_VARIABLES)
+        .chain(derived)
+        .map(|&(name, description)| TemplateVariable { name, description })
+        .collect()
+}
+
+/// Override file for `template_type` in `dir`, if there is one.
+pub fn override_path(dir: &Path, template_type: TemplateType) -> Option<PathBuf> {
+    EXTENSIONS
+        .iter()
+        .map(|ext| dir.join(format!("{}.{ext}", template_type.as_str())))
+        .find(|path| path.is_file())
+}
+
+/// Render the override of `template_type` from the configured directory;
+/// `None` when there is none.
+pub fn render(template_type: TemplateType, options: &Value) -> Result<Option<String>, String> {
+    let Some(path) = templates_dir().and_then(|dir| override_path(dir, template_type)) else {
+        return Ok(None);
+    };
+    let source = fs::read_to_string(&path)
+        .map_err(|e| format!("Could not read template override {}: {e}", path.display()))?;
+    let context = template_context(template_type, options)?;
+    render_source(&path, &source, &context).map(Some)
+}
+
+/// Options merged with the common and derived variables.
+fn template_context(template_type: TemplateType, options: &Value) -> Result<Value, String> {
+    let map = options
+        .as_object()
+        .ok_or_else(|| "Template options must be an object".to_string())?;
+    let mut context = map.clone();
+    context.insert("templateType".into(), template_type.as_str().into());
+    context.insert("options".into(), options.clone());
+    context.insert(
+        "builtin".into(),
+        builtin_template(template_type, options).map_or(Value::Null, Value::String),
+    );
+    if let Some(name) = ["slug", "name"]
+        .iter()
+        .find_map(|key| map.get(*key)?.as_str())
+    {
+        context.insert("className".into(), Case::Pascal.convert(name).into());
+    }
+    if let Some(fields) = map.get("fields").and_then(Value::as_array) {
+        context.insert("fieldsCode".into(), fields_code(fields)?.into());
+    }
+    Ok(Value::Object(context))
+}
+
+fn render_source(path: &Path, source: &str, context: &Value) -> Result<String, String> {
+    let error = |e: &dyn std::fmt::Display| format!("Template override {}: {e}", path.display());
+    if path.extension().is_some_and(|ext| ext == "tera") {
+        let context = tera::Context::from_value(context.clone()).map_err(|e| error(&e))?;
+        tera::Tera::one_off(source, &context, false).map_err(|e| error(&e))
+    } else {
+        let mut registry = handlebars::Handlebars::new();
+        // Generated code is TypeScript, not HTML
+        registry.register_escape_fn(handlebars::no_escape);
+        registry
+            .render_template(source, context)
+            .map_err(|e| error(&e))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn test_render_source_with_both_engines() {
+        let context = template_context(
+            TemplateType::Collection,
+            &json!({ "slug": "blog-posts", "fields": [] }),
+        )
+        .unwrap();
+        assert_eq!(context["className"], "BlogPosts");
+        assert!(
+            context["builtin"]
+                .as_str()
+                .unwrap()
+                .contains("slug: 'blog-posts'")
+        );
+
+        let hbs = render_source(
+            Path::new("collection.hbs"),
+            "// {{templateType}} <{{slug}}>\nexport const {{className}} = {};",
+            &context,
+        )
+        .unwrap();
+        assert_eq!(
+            hbs,
+            "// collection <blog-posts>\nexport const BlogPosts = {};"
+        );
+
+        let tera = render_source(
+            Path::new("collection.tera"),
+            "{% if auth %}auth{% else %}{{ className }}{% endif %}",
+            &json!({ "auth": false, "className": "BlogPosts" }),
+        )
+        .unwrap();
+        assert_eq!(tera, "BlogPosts");
+    }
+}
@@ -0,0 +1,502 @@
+//! Reads the config object of a TypeScript (or JavaScript) source as JSON so
+//! the validator can check `.ts` files and generated code. Only the object
+//! literal is interpreted: other expressions (functions, identifiers, calls)
+//! become strings of their source, array elements that are expressions or
+//! spreads are dropped, and `undefined` properties are omitted.
+
+use std::sync::OnceLock;
+
+use regex::Regex;
+use serde_json::{Map, Number, Value};
+
+/// Where the config object can start, in order of preference: the object
+/// passed to `buildConfig`, one annotated as a Payload config type (generic
+/// arguments allowed), and the default export.
+fn config_starts() -> &'static [Regex] {
+    static STARTS: OnceLock<Vec<Regex>> = OnceLock::new();
+    STARTS.get_or_init(|| {
+        [
+            r"buildConfig\s*\(\s*\{",
+            r":\s*(?:CollectionConfig|GlobalConfig)\s*(?:<[^>=]*>)?\s*=\s*\{",
+            r":\s*(?:Block|Field)\s*(?:<[^>=]*>)?\s*=\s*\{",
+            r"export\s+default\s+\{",
+        ]
+        .into_iter()
+        .map(|pattern| Regex::new(pattern).expect("valid config start pattern"))
+        .collect()
+    })
+}
+
+/// The config object of `code` as JSON: the object passed to `buildConfig`,
+/// the one annotated as a Payload config type, the default export, or the
+/// object `code` consists of.
+pub fn parse_config_object(code: &str) -> Result<Value, String> {
+    let start = match config_starts().iter().find_map(|start| start.find(code)) {
+        Some(found) => found.end() - 1,
+        None => {
+            let trimmed = code.trim_start();
+            if !trimmed.starts_with('{') {
+                return Err("No config object found in TypeScript source".to_string());
+            }
+            code.len() - trimmed.len()
+        }
+    };
+    parse_object_at(code, start)
+}
+
+/// Byte offset of the object passed to `buildConfig` in `code`.
+pub fn build_config_start(code: &str) -> Option<usize> {
+    config_starts()[0].find(code).map(|found| found.end() - 1)
+}
+
+/// A property of an object literal or an element of an array literal, as
+/// byte offsets into the source.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LiteralItem {
+    /// Property name; `None` for array elements and spreads
+    pub key: Option<String>,
+    pub start: usize,
+    /// Start of the value: after `key:`, or `start` for shorthand
+    /// properties, methods and array elements
+    pub value_start: usize,
+    pub end: usize,
+}
+
+/// The items of the object or array literal starting at byte `start` of
+/// `code`, and the offset of its closing bracket.
+pub fn literal_items(code: &str, start: usize) -> Result<(Vec<LiteralItem>, usize), String> {
+    let mut parser = Parser {
+        src: code,
+        pos: start,
+    };
+    let close = match parser.peek() {
+        Some(b'{') => b'}',
+        Some(b'[') => b']',
+        _ => return Err(parser.error("expected an object or array literal")),
+    };
+    parser.pos += 1;
+    let mut items = Vec::new();
+    loop {
+        parser.skip_trivia();
+        if parser.peek() == Some(close) {
+            return Ok((items, parser.pos));
+        }
+        let item_start = parser.pos;
+        let mut key = None;
+        let mut value_start = item_start;
+        if close == b'}' && !parser.rest().starts_with("...") {
+            key = Some(parser.key()?);
+            if parser.eat(b':') {
+                parser.skip_trivia();
+                value_start = parser.pos;
+            }
+        }
+        let source = parser.expression(value_start)?;
+        items.push(LiteralItem {
+            key,
+            start: item_start,
+            value_start,
+            end: value_start + source.len(),
+        });
+        if !parser.eat(b',') {
+            return if parser.eat(close) {
+                Ok((items, parser.pos - 1))
+            } else {
+                Err(parser.error("expected ',' or a closing bracket"))
+            };
+        }
+    }
+}
+
+/// The object literal starting at byte `start` of `code`, ignoring what
+/// follows it.
+pub fn parse_object_at(code: &str, start: usize) -> Result<Value, String> {
+    let mut parser = Parser {
+        src: code,
+        pos: start,
+    };
+    match parser.value()? {
+        Literal::Value(value @ Value::Object(_)) => Ok(value),
+        _ => Err(parser.error("expected an object literal")),
+    }
+}
+
+enum Literal {
+    Value(Value),
+    /// An expression that is not a literal, with its source
+    Expression(String),
+    Undefined,
+}
+
+struct Parser<'a> {
+    src: &'a str,
+    pos: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn peek(&self) -> Option<u8> {
+        self.src.as_bytes().get(self.pos).copied()
+    }
+
+    fn rest(&self) -> &'a str {
+        &self.src[self.pos..]
+    }
+
+    fn error(&self, message: &str) -> String {
+        let line = self.src[..self.pos.min(self.src.len())]
+            .matches('\n')
+            .count()
+            + 1;
+        format!("Failed to parse TypeScript at line {line}: {message}")
+    }
+
+    fn skip_trivia(&mut self) {
+        loop {
+            let rest = self.rest();
+            let trimmed = rest.trim_start();
+            self.pos += rest.len() - trimmed.len();
+            if trimmed.starts_with("//") {
+                self.pos += trimmed.find('\n').unwrap_or(trimmed.len());
+            } else if trimmed.starts_with("/*") {
+                self.pos += trimmed.find("*/").map_or(trimmed.len(), |end| end + 2);
+            } else {
+                return;
+            }
+        }
+    }
+
+    fn eat(&mut self, byte: u8) -> bool {
+        self.skip_trivia();
+        if self.peek() == Some(byte) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Whether the next token ends a value: `,`, `}`, `]`, `)`, `;` or the end.
+    fn at_value_end(&mut self) -> bool {
+        self.skip_trivia();
+        matches!(self.peek(), None | Some(b',' | b'}' | b']' | b')' | b';'))
+    }
+
+    fn value(&mut self) -> Result<Literal, String> {
+        self.skip_trivia();
+        let start = self.pos;
+        let literal = match self.peek() {
+            None => return Err(self.error("unexpected end of source")),
+            Some(b'{') => Literal::Value(self.object()?),
+            Some(b'[') => Literal::Value(self.array()?),
+            Some(quote @ (b'\'' | b'"' | b'`')) => match self.string(quote)? {
+                Some(text) => Literal::Value(Value::String(text)),
+                None => Literal::Expression(self.src[start..self.pos].to_string()),
+            },
+            Some(_) => {
+                let source = self.expression(start)?;
+                if source.is_empty() {
+                    return Err(self.error("expected a value"));
+                }
+                return Ok(match source.as_str() {
+                    "true" => Literal::Value(Value::Bool(true)),
+                    "false" => Literal::Value(Value::Bool(false)),
+                    "null" => Literal::Value(Value::Null),
+                    "undefined" => Literal::Undefined,
+                    _ => match serde_json::from_str::<Number>(&source) {
+                        Ok(number) => Literal::Value(Value::Number(number)),
+                        Err(_) => Literal::Expression(source),
+                    },
+                });
+            }
+        };
+        // A literal followed by more code (`'a' + b`, `[...].map(...)`) is an
+        // expression after all.
+        if self.at_value_end() {
+            Ok(literal)
+        } else {
+            Ok(Literal::Expression(self.expression(start)?))
+        }
+    }
+
+    fn object(&mut self) -> Result<Value, String> {
+        self.pos += 1;
+        let mut map = Map::new();
+        loop {
+            if self.eat(b'}') {
+                return Ok(Value::Object(map));
+            }
+            self.skip_trivia();
+            if self.rest().starts_with("...") {
+                let start = self.pos;
+                self.expression(start)?;
+            } else {
+                let key = self.key()?;
+                if self.eat(b':') {
+                    match self.value()? {
+                        Literal::Value(value) => {
+                            map.insert(key, value);
+                        }
+                        Literal::Expression(source) => {
+                            map.insert(key, Value::String(source));
+                        }
+                        Literal::Undefined => {}
+                    }
+                } else if self.at_value_end() {
+                    // Shorthand property: `{ slug }`
+                    map.insert(key.clone(), Value::String(key));
+                } else {
+                    // Method: `read() { ... }`
+                    let start = self.pos;
+                    let source = self.expression(start)?;
+                    let separator = if source.starts_with('(') { "" } else { " " };
+                    map.insert(
+                        key.clone(),
+                        Value::String(format!("{key}{separator}{source}")),
+                    );
+                }
+            }
+            if !self.eat(b',') {
+                return if self.eat(b'}') {
+                    Ok(Value::Object(map))
+                } else {
+                    Err(self.error("expected ',' or '}' in object"))
+                };
+            }
+        }
+    }
+
+    fn key(&mut self) -> Result<String, String> {
+        match self.peek() {
+            Some(quote @ (b'\'' | b'"')) => self
+                .string(quote)?
+                .ok_or_else(|| self.error("invalid property name")),
+            Some(b'[') => {
+                let start = self.pos;
+                self.balanced(b'[', b']')?;
+                Ok(self.src[start..self.pos].to_string())
+            }
+            _ => {
+                let rest = self.rest();
+                let len = rest
+                    .find(|c: char| !(c.is_alphanumeric() || c == '_' || c == '$'))
+                    .unwrap_or(rest.len());
+                if len == 0 {
+                    return Err(self.error("expected a property name"));
+                }
+                self.pos += len;
+                Ok(rest[..len].to_string())
+            }
+        }
+    }
+
+    fn array(&mut self) -> Result<Value, String> {
+        self.pos += 1;
+        let mut items = Vec::new();
+        loop {
+            if self.eat(b']') {
+                return Ok(Value::Array(items));
+            }
+            self.skip_trivia();
+            if self.rest().starts_with("...") {
+                let start = self.pos;
+                self.expression(start)?;
+            } else if let Literal::Value(value) = self.value()? {
+                items.push(value);
+            }
+            if !self.eat(b',') {
+                return if self.eat(b']') {
+                    Ok(Value::Array(items))
+                } else {
+                    Err(self.error("expected ',' or ']' in array"))
+                };
+            }
+        }
+    }
+
+    /// A quoted string, or `None` for a template literal with
+    /// substitutions, which is an expression.
+    fn string(&mut self, quote: u8) -> Result<Option<String>, String> {
+        let start = self.pos;
+        self.pos += 1;
+        let mut text = String::new();
+        let mut interpolated = false;
+        loop {
+            let Some(c) = self.rest().chars().next() else {
+                self.pos = start;
+                return Err(self.error("unterminated string"));
+            };
+            self.pos += c.len_utf8();
+            match c {
+                '\\' => {
+                    let Some(escaped) = self.rest().chars().next() else {
+                        continue;
+                    };
+                    self.pos += escaped.len_utf8();
+                    text.push(match escaped {
+                        'n' => '\n',
+                        't' => '\t',
+                        'r' => '\r',
+                        other => other,
+                    });
+                }
+                '$' if quote == b'`' && self.peek() == Some(b'{') => {
+                    interpolated = true;
+                    self.balanced(b'{', b'}')?;
+                }
+                c if c as u32 == quote as u32 => {
+                    return Ok((!interpolated).then_some(text));
+                }
+                c => text.push(c),
+            }
+        }
+    }
+
+    /// Skip a bracketed span starting at `open`, including nested brackets,
+    /// strings and comments.
+    fn balanced(&mut self, open: u8, close: u8) -> Result<(), String> {
+        let mut depth = 0usize;
+        while let Some(byte) = self.peek() {
+            match byte {
+                b'\'' | b'"' | b'`' => {
+                    self.string(byte)?;
+                    continue;
+                }
+                b'/' if self.rest().starts_with("//") || self.rest().starts_with("/*") => {
+                    self.skip_trivia();
+                    continue;
+                }
+                b if b == open => depth += 1,
+                b if b == close => {
+                    depth -= 1;
+                    if depth == 0 {
+                        self.pos += 1;
+                        return Ok(());
+                    }
+                }
+                _ => {}
+            }
+            self.pos += 1;
+        }
+        Err(self.error(&format!("unclosed '{}'", open as char)))
+    }
+
+    /// Skip an expression from `start` to the next `,`, `}`, `]` or `)` at
+    /// its own nesting level and return its source.
+    fn expression(&mut self, start: usize) -> Result<String, String> {
+        self.pos = start;
+        while let Some(byte) = self.peek() {
+            match byte {
+                b'(' => self.balanced(b'(', b')')?,
+                b'[' => self.balanced(b'[', b']')?,
+                b'{' => self.balanced(b'{', b'}')?,
+                b'\'' | b'"' | b'`' => {
+                    self.string(byte)?;
+                }
+                b'/' if self.rest().starts_with("//") || self.rest().starts_with("/*") => {
+                    self.skip_trivia()
+                }
+                b',' | b'}' | b']' | b')' | b';' => break,
+                _ => self.pos += 1,
+            }
+        }
+        Ok(self.src[start..self.pos].trim_end().to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn test_parse_config_object() {
+        let code = r#"import { CollectionConfig } from 'payload/types';
+import { admins } from '../access';
+
+// Posts
+const Posts: CollectionConfig = {
+  slug: 'posts',
+  admin: { useAsTitle: 'title', defaultColumns: ['title', "status"] },
+  access: {
+    read: () => true,
+    update: admins,
+    delete({ req }) { return Boolean(req.user) },
+  },
+  hooks: {
+    beforeChange: [
+      // Add your hooks here
+    ],
+  },
+  fields: [
+    ...sharedFields,
+    titleField,
+    {
+      name: 'title',
+      type: 'text',
+      required: true,
+      maxLength: 1_000,
+      label: `Title ${suffix}`,
+      defaultValue: undefined,
+      validate: (value) => {
+        if (!value) return 'Required, really';
+        return true;
+      },
+    },
+    { name: 'order', type: 'number', min: -1, max: 2.5 },
+  ],
+};
+
+export default Posts;"#;
+        let value = parse_config_object(code).unwrap();
+        assert_eq!(value["slug"], "posts");
+        assert_eq!(value["admin"]["defaultColumns"], json!(["title", "status"]));
+        assert_eq!(value["access"]["read"], "() => true");
+        assert_eq!(value["access"]["update"], "admins");
+        assert!(
+            value["access"]["delete"]
+                .as_str()
+                .unwrap()
+                .starts_with("delete(")
+        );
+        assert_eq!(value["hooks"]["beforeChange"], json!([]));
+        let fields = value["fields"].as_array().unwrap();
+        assert_eq!(fields.len(), 2);
+        assert_eq!(fields[0]["required"], true);
+        assert_eq!(fields[0]["maxLength"], "1_000");
+        assert_eq!(fields[0]["label"], "`Title ${suffix}`");
+        assert!(fields[0].get("defaultValue").is_none());
+        assert_eq!(fields[1]["min"], -1);
+
+        let config = parse_config_object(
+            "export default buildConfig({\n  serverURL: 'http://localhost:3000',\n  collections: [Posts],\n  db: mongooseAdapter({ url: process.env.MONGODB_URI }),\n});",
+        )
+        .unwrap();
+        assert_eq!(config["collections"], json!([]));
+        assert_eq!(
+            config["db"],
+            "mongooseAdapter({ url: process.env.MONGODB_URI })"
+        );
+
+        assert_eq!(
+            parse_config_object("{ name: 'a', type: 'text' }").unwrap()["name"],
+            "a"
+        );
+        assert!(parse_config_object("const x = 1;").is_err());
+        assert!(parse_config_object("export default {\n  slug: 'a',\n").is_err());
+    }
+
+    #[test]
+    fn test_literal_items() {
+        let code = "export default buildConfig({\n  collections: [Posts, Media], // all\n  ...shared,\n  db,\n});";
+        let start = build_config_start(code).unwrap();
+        let (items, close) = literal_items(code, start).unwrap();
+        let keys: Vec<Option<&str>> = items.iter().map(|item| item.key.as_deref()).collect();
+        assert_eq!(keys, vec![Some("collections"), None, Some("db")]);
+        assert_eq!(&code[items[0].value_start..items[0].end], "[Posts, Media]");
+        assert_eq!(&code[items[2].start..items[2].end], "db");
+        assert_eq!(&code[close..], "});");
+        let (elements, _) = literal_items(code, items[0].value_start).unwrap();
+        assert_eq!(&code[elements[1].start..elements[1].end], "Media");
+    }
+}
@@ -2,6 +2,7 @@ use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use std::fmt;
+use std::path::Path;
 use std::str::FromStr;
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize, JsonSchema)]
@@ -14,6 +15,22 @@ pub enum FileType {
 }
 
 impl FileType {
+    pub const ALL: [FileType; 4] = [
+        FileType::Collection,
+        FileType::Field,
+        FileType::Global,
+        FileType::Config,
+    ];
+
+    pub fn description(&self) -> &'static str {
+        match self {
+            FileType::Collection => "Collection config",
+            FileType::Field => "Field definition",
+            FileType::Global => "Global config",
+            FileType::Config => "Root payload.config.ts",
+        }
+    }
+
     pub fn as_str(&self) -> &'static str {
         match self {
             FileType::Collection => "collection",
@@ -22,6 +39,40 @@ impl FileType {
             FileType::Config => "config",
         }
     }
+
+    /// File type named by a path: a name segment (`posts.collection.ts`,
+    /// `collection.json`, `payload.config.ts`), or the conventional
+    /// `collections`, `globals`, `blocks` and `fields` directories.
+    pub fn from_path(path: &Path) -> Option<FileType> {
+        let name = path.file_name()?.to_string_lossy().to_lowercase();
+        if let Some(file_type) = name.split('.').find_map(|part| part.parse().ok()) {
+            return Some(file_type);
+        }
+        match path.parent()?.file_name()?.to_string_lossy().as_ref() {
+            "collections" => Some(FileType::Collection),
+            "globals" => Some(FileType::Global),
+            "blocks" | "fields" => Some(FileType::Field),
+            _ => None,
+        }
+    }
+
+    /// File type of a source file, from its path and failing that from the
+    /// Payload types and calls in its code.
+    pub fn detect(path: &Path, code: &str) -> Option<FileType> {
+        FileType::from_path(path).or_else(|| {
+            if code.contains("buildConfig(") {
+                Some(FileType::Config)
+            } else if code.contains("GlobalConfig") {
+                Some(FileType::Global)
+            } else if code.contains("CollectionConfig") {
+                Some(FileType::Collection)
+            } else if code.contains(": Field") || code.contains(": Block") {
+                Some(FileType::Field)
+            } else {
+                None
+            }
+        })
+    }
 }
 
 impl fmt::Display for FileType {
@@ -64,6 +115,144 @@ pub struct Reference {
     pub url: String,
 }
 
+/// SARIF 2.1.0 log of validation results keyed by file path, for code
+/// scanning uploads. Errors become `error` results and warnings `warning`
+/// results, each located at its file.
+pub fn sarif_report<'a>(results: impl IntoIterator<Item = (&'a str, &'a ValidationResult)>) -> Value {
+    let mut entries = Vec::new();
+    for (path, result) in results {
+        let location = serde_json::json!([{
+            "physicalLocation": { "artifactLocation": { "uri": path.replace('\\', "/") } }
+        }]);
+        for (level, messages) in [("error", &result.errors), ("warning", &result.warnings)] {
+            for message in messages {
+                entries.push(serde_json::json!({
+                    "ruleId": format!("payload-{level}"),
+                    "level": level,
+                    "message": { "text": message },
+                    "locations": location,
+                }));
+            }
+        }
+    }
+    serde_json::json!({
+        "$schema": "https://json.schemastore.org/sarif-2.1.0.json",
+        "version": "2.1.0",
+        "runs": [{
+            "tool": {
+                "driver": {
+                    "name": crate::metadata::PKG_NAME,
+                    "version": crate::metadata::PKG_VERSION,
+                    "informationUri": "https://payloadcms.com/docs",
+                    "rules": [
+                        { "id": "payload-error", "shortDescription": { "text": "Payload CMS validation error" } },
+                        { "id": "payload-warning", "shortDescription": { "text": "Payload CMS validation warning" } },
+                    ],
+                }
+            },
+            "results": entries,
+        }],
+    })
+}
+
+/// How validation tools render their results besides the structured output.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum ValidationReportFormat {
+    /// Structured output only
+    #[default]
+    Json,
+    /// SARIF 2.1.0, for GitHub code scanning
+    Sarif,
+    /// JUnit XML, for CI test reports
+    Junit,
+}
+
+impl ValidationReportFormat {
+    /// The report for this format, or `None` for plain JSON.
+    pub fn render<'a>(
+        self,
+        results: impl IntoIterator<Item = (&'a str, &'a ValidationResult)>,
+    ) -> Option<String> {
+        match self {
+            ValidationReportFormat::Json => None,
+            ValidationReportFormat::Sarif => {
+                Some(serde_json::to_string_pretty(&sarif_report(results)).unwrap_or_default())
+            }
+            ValidationReportFormat::Junit => Some(junit_report(results)),
+        }
+    }
+}
+
+fn xml_escape(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&apos;"),
+            c if c.is_control() && !matches!(c, '\n' | '\t' | '\r') => {}
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+/// JUnit XML report of validation results keyed by file path: one test case
+/// per file, failing with its errors. Warnings go to the case's
+/// `system-out` so they show in CI without failing the build.
+pub fn junit_report<'a>(results: impl IntoIterator<Item = (&'a str, &'a ValidationResult)>) -> String {
+    let mut cases = Vec::new();
+    let mut failures = 0;
+    for (path, result) in results {
+        let path = xml_escape(&path.replace('\\', "/"));
+        let mut case = format!("    <testcase classname=\"payload\" name=\"{path}\" file=\"{path}\"");
+        if result.errors.is_empty() && result.warnings.is_empty() {
+            case.push_str("/>");
+            cases.push(case);
+            continue;
+        }
+        case.push_str(">\n");
+        if !result.errors.is_empty() {
+            failures += 1;
+            let message = match result.errors.len() {
+                1 => "1 validation error".to_string(),
+                count => format!("{count} validation errors"),
+            };
+            case.push_str(&format!(
+                "      <failure type=\"payload-error\" message=\"{message}\">{}</failure>\n",
+                xml_escape(&result.errors.join("\n"))
+            ));
+        }
+        if !result.warnings.is_empty() {
+            let warnings: Vec<String> = result
+                .warnings
+                .iter()
+                .map(|warning| format!("warning: {warning}"))
+                .collect();
+            case.push_str(&format!(
+                "      <system-out>{}</system-out>\n",
+                xml_escape(&warnings.join("\n"))
+            ));
+        }
+        case.push_str("    </testcase>");
+        cases.push(case);
+    }
+    let name = crate::metadata::PKG_NAME;
+    let tests = cases.len();
+    let mut xml = format!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<testsuites name=\"{name}\" tests=\"{tests}\" failures=\"{failures}\">\n  <testsuite name=\"payload-validation\" tests=\"{tests}\" failures=\"{failures}\" errors=\"0\" skipped=\"0\">\n"
+    );
+    for case in cases {
+        xml.push_str(&case);
+        xml.push('\n');
+    }
+    xml.push_str("  </testsuite>\n</testsuites>\n");
+    xml
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
 pub struct ValidationResult {
     pub is_valid: bool,
@@ -95,6 +284,24 @@ impl ValidationResult {
     }
 }
 
+/// A source file handed to a tool that reads a project's config.
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
+pub struct SourceFile {
+    /// Where the code came from; its directory tells collections from globals
+    pub path: Option<String>,
+    /// Config, collection, global or helper source, as TypeScript or JSON
+    pub code: String,
+}
+
+/// Values bound to placeholders in a SQL query: an array for positional `?`
+/// placeholders or an object for `:name` placeholders.
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
+#[serde(untagged)]
+pub enum SqlBindings {
+    Positional(Vec<Value>),
+    Named(serde_json::Map<String, Value>),
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
 pub struct SqlQueryResult {
     pub columns: Vec<String>,
@@ -116,3 +323,112 @@ pub struct ValidationRule {
     pub file_types: Vec<FileType>,
     pub examples: Examples,
 }
+
+// Planning: goals, implementation plans and todos kept by `Storage`.
+
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct Goal {
+    pub id: String,
+    pub description: String,
+    /// RFC 3339 timestamp
+    pub created_at: String,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ImplementationPlan {
+    pub goal_id: String,
+    /// Free-form plan text from the last `save_plan`
+    pub summary: Option<String>,
+    pub todos: Vec<Todo>,
+    /// Artifacts attached to the plan rather than to one todo
+    #[serde(default)]
+    pub artifacts: Vec<Artifact>,
+    pub updated_at: String,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct Todo {
+    pub id: String,
+    pub title: String,
+    pub description: String,
+    /// Estimated complexity from 0 (trivial) to 10
+    pub complexity: u8,
+    pub code_example: Option<String>,
+    pub is_complete: bool,
+    #[serde(default)]
+    pub artifacts: Vec<Artifact>,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ArtifactKind {
+    /// A scaffolded or generated file; `reference` is its path
+    File,
+    /// A validation report; `content` holds the result
+    ValidationReport,
+    /// Generated code; `content` holds the code
+    CodeSnippet,
+    /// Any other tool result; `content` holds it
+    ToolResult,
+}
+
+impl ArtifactKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ArtifactKind::File => "file",
+            ArtifactKind::ValidationReport => "validation_report",
+            ArtifactKind::CodeSnippet => "code_snippet",
+            ArtifactKind::ToolResult => "tool_result",
+        }
+    }
+}
+
+impl FromStr for ArtifactKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "file" => Ok(ArtifactKind::File),
+            "validation_report" => Ok(ArtifactKind::ValidationReport),
+            "code_snippet" => Ok(ArtifactKind::CodeSnippet),
+            "tool_result" => Ok(ArtifactKind::ToolResult),
+            other => Err(format!("Unknown artifact kind: {other}")),
+        }
+    }
+}
+
+/// Output of a tool linked to a plan or todo.
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct Artifact {
+    pub id: String,
+    pub kind: ArtifactKind,
+    /// File path or a short description of the result
+    pub reference: String,
+    pub content: Option<Value>,
+    /// Tool that produced the result
+    pub source_tool: String,
+    pub created_at: String,
+}
+
+/// A todo together with the goal it belongs to.
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
+pub struct GoalTodo {
+    pub goal: Goal,
+    pub todo: Todo,
+}
+
+/// Snapshot of a plan taken after a change.
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct PlanRevision {
+    /// Tool that made the change, e.g. `add_todo`
+    pub event: String,
+    pub recorded_at: String,
+    pub plan: ImplementationPlan,
+}
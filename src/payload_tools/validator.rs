@@ -1,14 +1,34 @@
 use serde_json::Value;
 
+use crate::payload_tools::email::server_url_env;
+use crate::payload_tools::naming::{self, NamingProfile};
+use crate::payload_tools::plugins::PayloadVersion;
+use crate::payload_tools::relationship_depth::{analyze_depth, DepthOptions};
+use crate::payload_tools::schema_graph::{schema_graph, SchemaGraphOptions};
+use crate::payload_tools::schema_model::SchemaModel;
 use crate::payload_tools::schemas::{
-    validate_collection_schema, validate_config_schema, validate_field_schema, validate_global_schema,
+    unknown_field_options, validate_block_schema, validate_collection_schema,
+    validate_config_schema, validate_field_schema, validate_global_schema,
 };
+use crate::payload_tools::ts_parser::parse_config_object;
 use crate::payload_tools::types::{
     Examples, FileType, Reference, Suggestion, ValidationResult, ValidationRule,
 };
 
-fn parse_payload_object(code: &str) -> Result<Value, String> {
-    serde_json::from_str(code.trim()).map_err(|err| format!("Failed to parse code as JSON: {err}"))
+/// The object to validate: `code` as JSON, or the config object of a
+/// TypeScript source. Sources that are neither report the JSON error when
+/// they start like JSON.
+pub(crate) fn parse_payload_object(code: &str) -> Result<Value, String> {
+    let code = code.trim();
+    serde_json::from_str(code).or_else(|json_err| {
+        parse_config_object(code).map_err(|ts_err| {
+            if code.starts_with('{') {
+                format!("Failed to parse code as JSON: {json_err}")
+            } else {
+                ts_err
+            }
+        })
+    })
 }
 
 fn naming_conventions(name: &str) -> Vec<String> {
@@ -44,6 +64,59 @@ fn reserved_words(name: &str) -> Vec<String> {
     }
 }
 
+/// Name fragments of fields holding personal data worth encrypting at rest,
+/// matched against the lowercase name without separators.
+const PII_FRAGMENTS: [&str; 14] = [
+    "socialsecurity",
+    "passport",
+    "taxid",
+    "nationalid",
+    "iban",
+    "bankaccount",
+    "accountnumber",
+    "routingnumber",
+    "creditcard",
+    "cardnumber",
+    "dateofbirth",
+    "birthdate",
+    "driverlicense",
+    "driverslicense",
+];
+
+/// Short PII names only matched whole, e.g. `ssn` but not `lesson`.
+const PII_NAMES: [&str; 4] = ["ssn", "dob", "tin", "sin"];
+
+fn looks_like_pii(name: &str) -> bool {
+    let name: String = name
+        .chars()
+        .filter(char::is_ascii_alphanumeric)
+        .map(|c| c.to_ascii_lowercase())
+        .collect();
+    PII_NAMES.contains(&name.as_str()) || PII_FRAGMENTS.iter().any(|fragment| name.contains(fragment))
+}
+
+/// Suggest the field-encryption template for a text field that looks like
+/// PII and has no beforeChange hook yet.
+fn encryption_suggestion(field: &Value) -> Option<Suggestion> {
+    let name = field.get("name").and_then(|v| v.as_str())?;
+    let field_type = field.get("type").and_then(|v| v.as_str()).unwrap_or_default();
+    let hooked = field
+        .get("hooks")
+        .and_then(|hooks| hooks.get("beforeChange"))
+        .is_some();
+    if !matches!(field_type, "text" | "textarea" | "email") || hooked || !looks_like_pii(name) {
+        return None;
+    }
+    Some(Suggestion {
+        message: format!(
+            "Field \"{name}\" looks like personal data. Consider encrypting it at rest with the field-encryption template."
+        ),
+        code: Some(format!(
+            "import {{ encrypted }} from '../hooks/fieldEncryption';\n\nencrypted({{ name: '{name}', type: '{field_type}' }})"
+        )),
+    })
+}
+
 fn collection_reference() -> Reference {
     Reference {
         title: "Payload CMS Collections Documentation".to_string(),
@@ -95,6 +168,18 @@ pub fn validation_rules() -> Vec<ValidationRule> {
                 ],
             },
         },
+        ValidationRule {
+            id: "naming-profile".to_string(),
+            name: "Naming Profile".to_string(),
+            description: "Slugs, field names and block slugs should follow the selected naming profile (none, payload, snake or a custom one)"
+                .to_string(),
+            category: "best-practices".to_string(),
+            file_types: vec![FileType::Collection, FileType::Field, FileType::Global],
+            examples: Examples {
+                valid: vec![r#"{ slug: "blog-posts", fields: [{ name: "heroImage", type: "upload" }] }"#.into()],
+                invalid: vec![r#"{ slug: "BlogPost", fields: [{ name: "hero_image", type: "upload" }] }"#.into()],
+            },
+        },
         ValidationRule {
             id: "reserved-words".to_string(),
             name: "Reserved Words".to_string(),
@@ -143,6 +228,50 @@ pub fn validation_rules() -> Vec<ValidationRule> {
                 invalid: vec![r#"{ name: "password", type: "text" }"#.into()],
             },
         },
+        ValidationRule {
+            id: "field-encryption".to_string(),
+            name: "Field Encryption".to_string(),
+            description: "Fields holding personal data such as tax IDs, bank accounts or dates of birth should be encrypted at rest".to_string(),
+            category: "security".to_string(),
+            file_types: vec![FileType::Collection, FileType::Field],
+            examples: Examples {
+                valid: vec![r#"encrypted({ name: "ssn", type: "text" })"#.into()],
+                invalid: vec![r#"{ name: "ssn", type: "text" }"#.into()],
+            },
+        },
+        ValidationRule {
+            id: "csrf-protection".to_string(),
+            name: "CSRF Protection".to_string(),
+            description: "In production, configs should list the origins allowed to send cookie-authenticated requests in csrf".to_string(),
+            category: "security".to_string(),
+            file_types: vec![FileType::Config],
+            examples: Examples {
+                valid: vec![r#"{ csrf: ["https://example.com"] }"#.into()],
+                invalid: vec![r#"{ serverURL: "https://example.com" }"#.into()],
+            },
+        },
+        ValidationRule {
+            id: "cors-origins".to_string(),
+            name: "CORS Origins".to_string(),
+            description: "In production, configs should limit cors to known origins rather than leaving it out or allowing every origin".to_string(),
+            category: "security".to_string(),
+            file_types: vec![FileType::Config],
+            examples: Examples {
+                valid: vec![r#"{ cors: ["https://example.com"] }"#.into()],
+                invalid: vec![r#"{ cors: "*" }"#.into()],
+            },
+        },
+        ValidationRule {
+            id: "rate-limiting".to_string(),
+            name: "Rate Limiting".to_string(),
+            description: "In production, Payload 2 configs should set rateLimit; Payload 3 has no such option and rate limits in middleware".to_string(),
+            category: "security".to_string(),
+            file_types: vec![FileType::Config],
+            examples: Examples {
+                valid: vec![r#"{ rateLimit: { window: 900000, max: 500, trustProxy: true } }"#.into()],
+                invalid: vec![r#"{ serverURL: "https://example.com" }"#.into()],
+            },
+        },
         ValidationRule {
             id: "indexed-fields".to_string(),
             name: "Indexed Fields".to_string(),
@@ -166,6 +295,18 @@ pub fn validation_rules() -> Vec<ValidationRule> {
                 invalid: vec![r#"{ type: "relationship", relationTo: "posts" }"#.into()],
             },
         },
+        ValidationRule {
+            id: "relationship-cycles".to_string(),
+            name: "Relationship Cycles".to_string(),
+            description: "Relationship cycles should set maxDepth so default-depth reads don't populate exponentially many documents"
+                .to_string(),
+            category: "performance".to_string(),
+            file_types: vec![FileType::Config],
+            examples: Examples {
+                valid: vec![r#"{ name: "parent", type: "relationship", relationTo: "categories", maxDepth: 1 }"#.into()],
+                invalid: vec![r#"{ name: "related", type: "relationship", relationTo: "posts", hasMany: true }"#.into()],
+            },
+        },
         ValidationRule {
             id: "field-validation".to_string(),
             name: "Field Validation".to_string(),
@@ -245,6 +386,7 @@ pub fn validate_collection(code: &str) -> ValidationResult {
                 errors.extend(naming_conventions(name));
                 errors.extend(reserved_words(name));
             }
+            warnings.extend(unknown_field_options(field));
 
             let field_name = field
                 .get("name")
@@ -265,16 +407,17 @@ pub fn validate_collection(code: &str) -> ValidationResult {
                 ));
             }
 
+            suggestions.extend(encryption_suggestion(field));
+
             let field_type = field.get("type").and_then(|v| v.as_str()).unwrap_or("");
-            if matches!(field_type, "text" | "email" | "textarea") {
-                if field.get("unique").and_then(|v| v.as_bool()).unwrap_or(false)
-                    && !field.get("index").and_then(|v| v.as_bool()).unwrap_or(false)
-                {
-                    warnings.push(format!(
-                        "Field \"{}\" is unique but not indexed. Consider adding 'index: true' for better performance.",
-                        field.get("name").and_then(|v| v.as_str()).unwrap_or("field")
-                    ));
-                }
+            if matches!(field_type, "text" | "email" | "textarea")
+                && field.get("unique").and_then(|v| v.as_bool()).unwrap_or(false)
+                && !field.get("index").and_then(|v| v.as_bool()).unwrap_or(false)
+            {
+                warnings.push(format!(
+                    "Field \"{}\" is unique but not indexed. Consider adding 'index: true' for better performance.",
+                    field.get("name").and_then(|v| v.as_str()).unwrap_or("field")
+                ));
             }
         }
     }
@@ -334,7 +477,14 @@ pub fn validate_field(code: &str) -> ValidationResult {
         }
     };
 
-    if let Err(err) = validate_field_schema(&value) {
+    // Block definitions (`blocks/*.ts`) have a slug instead of a name and type.
+    let is_block = value.get("type").is_none() && value.get("slug").is_some();
+    let schema = if is_block {
+        validate_block_schema(&value)
+    } else {
+        validate_field_schema(&value)
+    };
+    if let Err(err) = schema {
         return ValidationResult {
             is_valid: false,
             errors: vec![err],
@@ -348,10 +498,16 @@ pub fn validate_field(code: &str) -> ValidationResult {
     let mut warnings = Vec::new();
     let mut suggestions = Vec::new();
 
-    if let Some(name) = value.get("name").and_then(|v| v.as_str()) {
+    if let Some(name) = value
+        .get("name")
+        .or_else(|| value.get("slug"))
+        .and_then(|v| v.as_str())
+    {
         errors.extend(naming_conventions(name));
         errors.extend(reserved_words(name));
     }
+    warnings.extend(unknown_field_options(&value));
+    suggestions.extend(encryption_suggestion(&value));
 
     let field_type = value
         .get("type")
@@ -485,6 +641,10 @@ pub fn validate_config(code: &str) -> ValidationResult {
         });
     }
 
+    // Only collections defined inline are visible here.
+    let graph = schema_graph(&SchemaModel::from_config(&value), &SchemaGraphOptions::default());
+    warnings.extend(analyze_depth(&graph, &DepthOptions::default()).warnings);
+
     if value.get("admin").is_none() {
         suggestions.push(Suggestion {
             message: "Consider configuring the admin panel".to_string(),
@@ -504,11 +664,104 @@ pub fn validate_config(code: &str) -> ValidationResult {
     }
 }
 
+/// Warnings for settings a production deployment needs that the config
+/// `value` leaves out: `csrf`, `cors` and, on Payload 2, `rateLimit`. Only
+/// Payload 3 configs set `secret` in the config itself.
+pub fn production_checks(value: &Value) -> (Vec<String>, Vec<Suggestion>) {
+    let version = if value.get("secret").is_some() {
+        PayloadVersion::V3
+    } else {
+        PayloadVersion::V2
+    };
+    let origins = format!("[process.env.{}]", server_url_env(version));
+    let listed = |key: &str| {
+        value
+            .get(key)
+            .and_then(Value::as_array)
+            .is_some_and(|origins| !origins.is_empty())
+    };
+    let mut warnings = Vec::new();
+    let mut suggestions = Vec::new();
+    if !listed("csrf") {
+        warnings.push(
+            "Missing csrf in a production config. List the origins allowed to send cookie-authenticated requests."
+                .to_string(),
+        );
+        suggestions.push(Suggestion {
+            message: "Set csrf to the site's origins".to_string(),
+            code: Some(format!("csrf: {origins}")),
+        });
+    }
+    match value.get("cors") {
+        Some(Value::String(cors)) if cors == "*" => warnings.push(
+            "cors allows every origin in a production config. List the origins that call the API from browsers."
+                .to_string(),
+        ),
+        Some(_) => {}
+        None => warnings.push(
+            "Missing cors in a production config. List the origins that call the API from browsers."
+                .to_string(),
+        ),
+    }
+    if !matches!(value.get("cors"), Some(Value::Array(_))) {
+        suggestions.push(Suggestion {
+            message: "Set cors to the site's origins".to_string(),
+            code: Some(format!("cors: {origins}")),
+        });
+    }
+    if version == PayloadVersion::V2 && value.get("rateLimit").is_none() {
+        warnings.push(
+            "Missing rateLimit in a production config. Limit requests per client IP.".to_string(),
+        );
+        suggestions.push(Suggestion {
+            message: "Rate limit API requests".to_string(),
+            code: Some("rateLimit: {\n  window: 15 * 60 * 1000,\n  max: 500,\n  trustProxy: true,\n}".to_string()),
+        });
+    }
+    if !warnings.is_empty() {
+        suggestions.push(Suggestion {
+            message: "The security template generates these settings with security header middleware".to_string(),
+            code: None,
+        });
+    }
+    (warnings, suggestions)
+}
+
+/// Add [`production_checks`] to `result` when `code` is a config.
+pub fn check_production(code: &str, file_type: FileType, result: &mut ValidationResult) {
+    if file_type != FileType::Config {
+        return;
+    }
+    if let Ok(value) = parse_payload_object(code) {
+        let (warnings, suggestions) = production_checks(&value);
+        result.warnings.extend(warnings);
+        result.suggestions.extend(suggestions);
+    }
+}
+
+/// Validate with the configured naming profile.
 pub fn validate_payload_code(code: &str, file_type: FileType) -> ValidationResult {
-    match file_type {
+    validate_payload_code_with_naming(code, file_type, &naming::active())
+}
+
+/// Validate, reporting names that break `naming` as errors.
+pub fn validate_payload_code_with_naming(
+    code: &str,
+    file_type: FileType,
+    naming: &NamingProfile,
+) -> ValidationResult {
+    let mut result = match file_type {
         FileType::Collection => validate_collection(code),
         FileType::Field => validate_field(code),
         FileType::Global => validate_global(code),
         FileType::Config => validate_config(code),
+    };
+    if let Ok(value) = parse_payload_object(code) {
+        let errors = naming.check(&value, file_type);
+        if !errors.is_empty() {
+            result.is_valid = false;
+            result.errors.extend(errors);
+        }
     }
+    result
 }
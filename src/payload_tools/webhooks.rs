@@ -0,0 +1,487 @@
+//! Outgoing webhooks for a Payload 3 app: a collection of endpoints
+//! subscribed to document events, afterChange and afterDelete hooks queueing
+//! one delivery job per subscribed endpoint, a jobs task posting the
+//! HMAC-signed event with retries, and a log of every delivery.
+
+use std::collections::BTreeMap;
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::payload_tools::json_schema::schema_name;
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct WebhooksOptions {
+    /// Collections whose creates, updates and deletes can be subscribed to
+    pub collections: Vec<String>,
+    /// Slug of the webhooks collection (default `webhooks`)
+    pub slug: Option<String>,
+    /// Slug of the delivery log collection (default `webhook-deliveries`)
+    pub deliveries_slug: Option<String>,
+    /// Jobs queue deliveries run on (default `webhooks`)
+    pub queue: Option<String>,
+    /// Retries after a failed delivery, with exponential backoff (default 5)
+    pub max_retries: Option<u32>,
+    /// Seconds to wait for the receiver to answer (default 10)
+    pub timeout_seconds: Option<u32>,
+}
+
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct WebhooksConfig {
+    pub imports: Vec<String>,
+    /// Entries for the config's `collections` array
+    pub collections: Vec<String>,
+    /// Value for the config's `jobs`, or to spread into an existing one
+    pub jobs: String,
+    /// `hooks` property to merge into each source collection's config, by
+    /// slug
+    pub hooks: BTreeMap<String, String>,
+    /// Collections, hooks, the delivery task and signing helpers by path
+    /// from the project root
+    pub files: BTreeMap<String, String>,
+}
+
+const OPERATIONS: [&str; 3] = ["create", "update", "delete"];
+
+fn is_slug(value: &str) -> bool {
+    !value.is_empty()
+        && value
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
+
+fn webhooks_collection(slug: &str, name: &str, events: &[String]) -> String {
+    let options: String = events
+        .iter()
+        .map(|event| format!("        '{event}',\n"))
+        .collect();
+    format!(
+        "import {{ randomBytes }} from 'crypto';
+import type {{ CollectionConfig }} from 'payload';
+
+const isAdmin = ({{ req: {{ user }} }}: {{ req: {{ user?: {{ role?: string }} | null }} }}) => user?.role === 'admin';
+
+/** Receivers subscribed to document events; admin-only, since each holds its signing secret. */
+const {name}: CollectionConfig = {{
+  slug: '{slug}',
+  admin: {{
+    useAsTitle: 'url',
+    defaultColumns: ['url', 'events', 'active'],
+    group: 'Admin',
+  }},
+  access: {{
+    read: isAdmin,
+    create: isAdmin,
+    update: isAdmin,
+    delete: isAdmin,
+  }},
+  fields: [
+    {{
+      name: 'url',
+      type: 'text',
+      required: true,
+      validate: (value: unknown) => {{
+        try {{
+          const url = new URL(String(value));
+          return url.protocol === 'https:' || url.protocol === 'http:' || 'Must be an http(s) URL';
+        }} catch {{
+          return 'Must be an http(s) URL';
+        }}
+      }},
+    }},
+    {{
+      name: 'events',
+      type: 'select',
+      hasMany: true,
+      required: true,
+      options: [
+{options}      ],
+    }},
+    {{
+      name: 'secret',
+      type: 'text',
+      required: true,
+      defaultValue: () => randomBytes(32).toString('hex'),
+      admin: {{ description: 'Signs each delivery; share it with the receiver to verify X-Webhook-Signature' }},
+    }},
+    {{
+      name: 'active',
+      type: 'checkbox',
+      defaultValue: true,
+      admin: {{ position: 'sidebar' }},
+    }},
+  ],
+}};
+
+export default {name};
+"
+    )
+}
+
+fn deliveries_collection(slug: &str, name: &str, webhooks: &str) -> String {
+    format!(
+        "import type {{ CollectionConfig }} from 'payload';
+
+/** One entry per event sent to a webhook, updated after every attempt; written only by the webhook hooks and task. */
+const {name}: CollectionConfig = {{
+  slug: '{slug}',
+  admin: {{
+    useAsTitle: 'event',
+    defaultColumns: ['event', 'webhook', 'status', 'attempts', 'responseStatus', 'updatedAt'],
+    group: 'Admin',
+  }},
+  access: {{
+    read: ({{ req: {{ user }} }}) => (user as {{ role?: string }} | null)?.role === 'admin',
+    create: () => false,
+    update: () => false,
+    delete: ({{ req: {{ user }} }}) => (user as {{ role?: string }} | null)?.role === 'admin',
+  }},
+  defaultSort: '-createdAt',
+  fields: [
+    {{ name: 'webhook', type: 'relationship', relationTo: '{webhooks}', required: true, index: true }},
+    {{ name: 'event', type: 'text', required: true, index: true }},
+    {{ name: 'documentId', type: 'text', required: true, index: true }},
+    {{
+      name: 'status',
+      type: 'select',
+      required: true,
+      defaultValue: 'pending',
+      index: true,
+      options: ['pending', 'succeeded', 'retrying', 'failed'],
+    }},
+    {{ name: 'attempts', type: 'number', required: true, defaultValue: 0 }},
+    {{ name: 'responseStatus', type: 'number' }},
+    {{ name: 'responseBody', type: 'textarea', admin: {{ description: 'First 2000 characters' }} }},
+    {{ name: 'error', type: 'text' }},
+    {{ name: 'deliveredAt', type: 'date' }},
+    {{
+      name: 'body',
+      type: 'json',
+      required: true,
+      admin: {{ description: 'The JSON posted to the webhook, the same on every attempt' }},
+    }},
+  ],
+}};
+
+export default {name};
+"
+    )
+}
+
+const SIGNATURE: &str = "import { createHmac, timingSafeEqual } from 'crypto';
+
+/** `sha256=<hex>` of `<timestamp>.<body>`, sent as X-Webhook-Signature with X-Webhook-Timestamp. */
+export const signWebhook = (secret: string, timestamp: string, body: string): string =>
+  `sha256=${createHmac('sha256', secret).update(`${timestamp}.${body}`).digest('hex')}`;
+
+/**
+ * For receivers: checks the signature and rejects requests older than
+ * `toleranceSeconds`, which stops replays.
+ */
+export const verifyWebhook = (
+  secret: string,
+  headers: { signature: string | null; timestamp: string | null },
+  body: string,
+  toleranceSeconds = 300,
+): boolean => {
+  const { signature, timestamp } = headers;
+  if (!signature || !timestamp) return false;
+  if (Math.abs(Date.now() / 1000 - Number(timestamp)) > toleranceSeconds) return false;
+  const expected = Buffer.from(signWebhook(secret, timestamp, body));
+  const actual = Buffer.from(signature);
+  return expected.length === actual.length && timingSafeEqual(expected, actual);
+};
+";
+
+fn dispatch_module(webhooks: &str, deliveries: &str, queue: &str) -> String {
+    format!(
+        "import type {{ CollectionAfterChangeHook, CollectionAfterDeleteHook, PayloadRequest }} from 'payload';
+
+const WEBHOOKS = '{webhooks}';
+const DELIVERIES = '{deliveries}';
+export const WEBHOOK_QUEUE = '{queue}';
+
+/** Logs one delivery per active webhook subscribed to `event` and queues it. */
+const dispatch = async (
+  req: PayloadRequest,
+  collection: string,
+  operation: 'create' | 'update' | 'delete',
+  doc: Record<string, unknown>,
+): Promise<void> => {{
+  const event = `${{collection}}.${{operation}}`;
+  try {{
+    const {{ docs: subscribed }} = await req.payload.find({{
+      collection: WEBHOOKS,
+      where: {{ and: [{{ active: {{ equals: true }} }}, {{ events: {{ in: [event] }} }}] }},
+      depth: 0,
+      pagination: false,
+      req,
+    }});
+    const body = {{ event, collection, occurredAt: new Date().toISOString(), doc }};
+    for (const webhook of subscribed) {{
+      const delivery = await req.payload.create({{
+        collection: DELIVERIES,
+        data: {{ webhook: webhook.id, event, documentId: String(doc.id), status: 'pending', attempts: 0, body }},
+        req,
+      }});
+      await req.payload.jobs.queue({{
+        task: 'deliverWebhook',
+        input: {{ delivery: String(delivery.id) }},
+        queue: WEBHOOK_QUEUE,
+        req,
+      }});
+    }}
+  }} catch (error) {{
+    // Webhooks never fail the change that triggered them.
+    req.payload.logger.error(`Could not queue webhooks for ${{event}} ${{doc.id}}: ${{error}}`);
+  }}
+}};
+
+export const webhooksAfterChange: CollectionAfterChangeHook = async ({{ collection, doc, operation, req }}) => {{
+  await dispatch(req, collection.slug, operation, doc);
+  return doc;
+}};
+
+export const webhooksAfterDelete: CollectionAfterDeleteHook = async ({{ collection, doc, req }}) => {{
+  await dispatch(req, collection.slug, 'delete', doc);
+  return doc;
+}};
+"
+    )
+}
+
+fn delivery_task(
+    webhooks: &str,
+    deliveries: &str,
+    max_retries: u32,
+    timeout_seconds: u32,
+) -> String {
+    format!(
+        "import type {{ JobsConfig, TaskConfig }} from 'payload';
+
+import {{ WEBHOOK_QUEUE }} from './dispatch';
+import {{ signWebhook }} from './signature';
+
+const WEBHOOKS = '{webhooks}';
+const DELIVERIES = '{deliveries}';
+const MAX_RETRIES = {max_retries};
+const TIMEOUT_MS = {timeout_seconds} * 1000;
+const MAX_RESPONSE_LENGTH = 2000;
+
+/**
+ * Posts a logged delivery to its webhook. Any non-2xx answer, timeout or
+ * network error throws, so the jobs queue retries with exponential backoff up
+ * to MAX_RETRIES times; the delivery records each attempt.
+ */
+export const deliverWebhookTask: TaskConfig<{{
+  input: {{ delivery: string }};
+  output: {{ responseStatus: number }};
+}}> = {{
+  slug: 'deliverWebhook',
+  inputSchema: [{{ name: 'delivery', type: 'text', required: true }}],
+  outputSchema: [{{ name: 'responseStatus', type: 'number' }}],
+  retries: {{ attempts: MAX_RETRIES, backoff: {{ type: 'exponential', delay: 10_000 }} }},
+  handler: async ({{ input, req }}) => {{
+    const {{ payload }} = req;
+    const delivery = await payload.findByID({{ collection: DELIVERIES, id: input.delivery, depth: 0 }});
+    const webhook = await payload.findByID({{
+      collection: WEBHOOKS,
+      id: delivery.webhook as string | number,
+      depth: 0,
+      disableErrors: true,
+    }});
+    const attempts = (delivery.attempts ?? 0) + 1;
+    const record = (data: Record<string, unknown>) =>
+      payload.update({{ collection: DELIVERIES, id: delivery.id, data: {{ attempts, ...data }} }});
+    if (!webhook || !webhook.active) {{
+      await record({{ status: 'failed', error: 'Webhook was deleted or deactivated' }});
+      return {{ output: {{ responseStatus: 0 }} }};
+    }}
+
+    const body = JSON.stringify({{ id: String(delivery.id), ...(delivery.body as object) }});
+    const timestamp = String(Math.floor(Date.now() / 1000));
+    const failed = attempts > MAX_RETRIES ? 'failed' : 'retrying';
+    let response: Response;
+    try {{
+      response = await fetch(webhook.url, {{
+        method: 'POST',
+        headers: {{
+          'Content-Type': 'application/json',
+          'User-Agent': 'Payload-Webhooks',
+          'X-Webhook-Event': delivery.event,
+          'X-Webhook-Delivery': String(delivery.id),
+          'X-Webhook-Timestamp': timestamp,
+          'X-Webhook-Signature': signWebhook(webhook.secret, timestamp, body),
+        }},
+        body,
+        signal: AbortSignal.timeout(TIMEOUT_MS),
+      }});
+    }} catch (error) {{
+      await record({{ status: failed, error: String(error) }});
+      throw error;
+    }}
+    const responseBody = (await response.text().catch(() => '')).slice(0, MAX_RESPONSE_LENGTH);
+    if (!response.ok) {{
+      await record({{ status: failed, responseStatus: response.status, responseBody, error: `HTTP ${{response.status}}` }});
+      throw new Error(`Webhook ${{webhook.url}} answered ${{response.status}}`);
+    }}
+    await record({{
+      status: 'succeeded',
+      responseStatus: response.status,
+      responseBody,
+      error: null,
+      deliveredAt: new Date().toISOString(),
+    }});
+    return {{ output: {{ responseStatus: response.status }} }};
+  }},
+}};
+
+/**
+ * The config's `jobs`, or spread into an existing one. autoRun works the queue
+ * every minute in long-running servers; on serverless hosts call
+ * /api/payload-jobs/run from a platform cron instead.
+ */
+export const webhookJobs: Partial<JobsConfig> = {{
+  tasks: [deliverWebhookTask],
+  autoRun: [{{ cron: '* * * * *', queue: WEBHOOK_QUEUE, limit: 50 }}],
+}};
+"
+    )
+}
+
+/// The webhooks and delivery log collections, dispatch hooks for the source
+/// collections and the delivery task.
+pub fn webhooks_config(options: &WebhooksOptions) -> Result<WebhooksConfig, String> {
+    let slug = options.slug.as_deref().unwrap_or("webhooks");
+    let deliveries = options
+        .deliveries_slug
+        .as_deref()
+        .unwrap_or("webhook-deliveries");
+    let queue = options.queue.as_deref().unwrap_or("webhooks");
+    if options.collections.is_empty() {
+        return Err("At least one collection to send events for is required".to_string());
+    }
+    for value in options
+        .collections
+        .iter()
+        .map(String::as_str)
+        .chain([slug, deliveries])
+    {
+        if !is_slug(value) {
+            return Err(format!("Invalid collection slug '{value}'"));
+        }
+    }
+    if !is_slug(queue) {
+        return Err(format!("Invalid queue name '{queue}'"));
+    }
+    if slug == deliveries {
+        return Err(format!(
+            "The webhooks and delivery log collections can't share the slug '{slug}'"
+        ));
+    }
+    if let Some(own) = options
+        .collections
+        .iter()
+        .find(|collection| *collection == slug || *collection == deliveries)
+    {
+        return Err(format!("'{own}' can't send webhooks about itself"));
+    }
+    let timeout_seconds = options.timeout_seconds.unwrap_or(10);
+    if timeout_seconds == 0 {
+        return Err("timeoutSeconds must be at least 1".to_string());
+    }
+
+    let events: Vec<String> = options
+        .collections
+        .iter()
+        .flat_map(|collection| {
+            OPERATIONS
+                .iter()
+                .map(move |operation| format!("{collection}.{operation}"))
+        })
+        .collect();
+    let name = schema_name(slug);
+    let deliveries_name = schema_name(deliveries);
+    let imports = vec![
+        format!("import {name} from './collections/{name}';"),
+        format!("import {deliveries_name} from './collections/{deliveries_name}';"),
+        "import { webhookJobs } from './webhooks/deliverWebhook';".to_string(),
+    ];
+    let files = BTreeMap::from([
+        (
+            format!("src/collections/{name}.ts"),
+            webhooks_collection(slug, &name, &events),
+        ),
+        (
+            format!("src/collections/{deliveries_name}.ts"),
+            deliveries_collection(deliveries, &deliveries_name, slug),
+        ),
+        (
+            "src/webhooks/dispatch.ts".to_string(),
+            dispatch_module(slug, deliveries, queue),
+        ),
+        (
+            "src/webhooks/deliverWebhook.ts".to_string(),
+            delivery_task(
+                slug,
+                deliveries,
+                options.max_retries.unwrap_or(5),
+                timeout_seconds,
+            ),
+        ),
+        ("src/webhooks/signature.ts".to_string(), SIGNATURE.to_string()),
+    ]);
+    let hooks = options
+        .collections
+        .iter()
+        .map(|collection| {
+            (
+                collection.clone(),
+                "hooks: {\n    afterChange: [webhooksAfterChange],\n    afterDelete: [webhooksAfterDelete],\n  },"
+                    .to_string(),
+            )
+        })
+        .collect();
+
+    Ok(WebhooksConfig {
+        imports,
+        collections: vec![name, deliveries_name],
+        jobs: "webhookJobs".to_string(),
+        hooks,
+        files,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_webhooks_config_events_and_task() {
+        let options = WebhooksOptions {
+            collections: vec!["posts".to_string(), "orders".to_string()],
+            max_retries: Some(3),
+            ..Default::default()
+        };
+        let config = webhooks_config(&options).unwrap();
+        assert_eq!(config.collections, vec!["Webhooks", "WebhookDeliveries"]);
+        assert_eq!(config.hooks.len(), 2);
+        let collection = &config.files["src/collections/Webhooks.ts"];
+        assert!(collection.contains("        'posts.create',\n"));
+        assert!(collection.contains("        'orders.delete',\n"));
+        assert!(
+            config.files["src/collections/WebhookDeliveries.ts"].contains("relationTo: 'webhooks'")
+        );
+        let task = &config.files["src/webhooks/deliverWebhook.ts"];
+        assert!(task.contains("const MAX_RETRIES = 3;"));
+        assert!(task.contains("const TIMEOUT_MS = 10 * 1000;"));
+        assert!(config.files["src/webhooks/dispatch.ts"].contains("export const WEBHOOK_QUEUE = 'webhooks';"));
+
+        let recursive = WebhooksOptions {
+            collections: vec!["webhook-deliveries".to_string()],
+            ..options
+        };
+        assert!(webhooks_config(&recursive).is_err());
+    }
+}
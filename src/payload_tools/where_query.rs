@@ -0,0 +1,557 @@
+//! Conversion of Payload `where` queries between the JSON object the Local
+//! API takes, the query-string encoding of the REST API, and the SQL
+//! condition the Postgres adapter runs.
+//!
+//! The JSON form is the hub: the other two are parsed into it and rendered
+//! from it. Column names follow the Postgres adapter, which stores
+//! `publishedAt` as `published_at` and a group's fields as `group_field`.
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value, json};
+
+use crate::payload_tools::{
+    naming::Case,
+    sql_parser::{Condition, Operator, escape_like, like_contains, parse_condition},
+};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum QueryFormat {
+    /// `{ "status": { "equals": "draft" } }`
+    Json,
+    /// `where[status][equals]=draft`
+    Qs,
+    /// `status = 'draft'`
+    Sql,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ConvertedQuery {
+    pub from: QueryFormat,
+    /// The `where` object
+    pub json: Value,
+    /// REST query string, brackets left unencoded for readability
+    pub qs: String,
+    /// Condition for the Postgres adapter's tables
+    pub sql: String,
+    pub warnings: Vec<String>,
+}
+
+const OPERATORS: [&str; 15] = [
+    "equals",
+    "not_equals",
+    "greater_than",
+    "greater_than_equal",
+    "less_than",
+    "less_than_equal",
+    "like",
+    "contains",
+    "in",
+    "not_in",
+    "all",
+    "exists",
+    "near",
+    "within",
+    "intersects",
+];
+
+/// The format of `query` when the caller doesn't say.
+fn infer_format(query: &Value) -> QueryFormat {
+    match query {
+        Value::String(text) => {
+            let text = text.trim();
+            if text.starts_with('{') {
+                QueryFormat::Json
+            } else if text.starts_with('?')
+                || text.starts_with("where[")
+                || text.starts_with("where%5B")
+            {
+                QueryFormat::Qs
+            } else {
+                QueryFormat::Sql
+            }
+        }
+        _ => QueryFormat::Json,
+    }
+}
+
+pub fn convert_query(query: &Value, from: Option<QueryFormat>) -> Result<ConvertedQuery, String> {
+    let from = from.unwrap_or_else(|| infer_format(query));
+    let mut warnings = Vec::new();
+    let json = match (from, query) {
+        (QueryFormat::Json, Value::String(text)) => {
+            serde_json::from_str(text).map_err(|err| format!("Invalid where JSON: {err}"))?
+        }
+        (QueryFormat::Json, value) => value.clone(),
+        (QueryFormat::Qs, Value::String(text)) => {
+            warnings.push(
+                "Query-string values are strings; Payload casts them to each field's type"
+                    .to_string(),
+            );
+            qs_to_where(text, &mut warnings)?
+        }
+        (QueryFormat::Sql, Value::String(text)) => {
+            let text = text.trim().trim_end_matches(';');
+            let text = strip_keyword(text, "WHERE").unwrap_or(text);
+            condition_to_where(&parse_condition(text)?)?
+        }
+        (format, _) => return Err(format!("A {format:?} query must be a string")),
+    };
+    let Value::Object(map) = &json else {
+        return Err("The where query must be an object".to_string());
+    };
+    check_where(map, "where")?;
+    let qs = where_to_qs(&json);
+    let sql = where_to_sql(map, &mut warnings)?;
+    Ok(ConvertedQuery {
+        from,
+        json,
+        qs,
+        sql,
+        warnings,
+    })
+}
+
+fn strip_keyword<'a>(text: &'a str, keyword: &str) -> Option<&'a str> {
+    let head = text.get(..keyword.len())?;
+    let rest = &text[keyword.len()..];
+    (head.eq_ignore_ascii_case(keyword) && rest.starts_with(char::is_whitespace))
+        .then(|| rest.trim_start())
+}
+
+/// Reject operators Payload doesn't know, naming where they appear.
+fn check_where(map: &Map<String, Value>, path: &str) -> Result<(), String> {
+    for (key, value) in map {
+        if key == "and" || key == "or" {
+            let Value::Array(items) = value else {
+                return Err(format!("{path}.{key} must be an array"));
+            };
+            for (index, item) in items.iter().enumerate() {
+                let Value::Object(item) = item else {
+                    return Err(format!("{path}.{key}[{index}] must be an object"));
+                };
+                check_where(item, &format!("{path}.{key}[{index}]"))?;
+            }
+            continue;
+        }
+        let Value::Object(operators) = value else {
+            return Err(format!(
+                "{path}.{key} must map operators to values, e.g. {{ \"equals\": ... }}"
+            ));
+        };
+        if let Some(operator) = operators
+            .keys()
+            .find(|op| !OPERATORS.contains(&op.as_str()))
+        {
+            return Err(format!(
+                "Unknown operator {operator} at {path}.{key}; expected one of {}",
+                OPERATORS.join(", ")
+            ));
+        }
+    }
+    Ok(())
+}
+
+// --- query string ---
+
+fn percent_encode(value: &str) -> String {
+    let mut out = String::new();
+    for byte in value.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' | b',' => {
+                out.push(byte as char)
+            }
+            _ => out.push_str(&format!("%{byte:02X}")),
+        }
+    }
+    out
+}
+
+fn percent_decode(value: &str) -> String {
+    let bytes = value.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'+' => out.push(b' '),
+            b'%' if i + 2 < bytes.len() => {
+                match u8::from_str_radix(value.get(i + 1..i + 3).unwrap_or_default(), 16) {
+                    Ok(byte) => {
+                        out.push(byte);
+                        i += 3;
+                        continue;
+                    }
+                    Err(_) => out.push(b'%'),
+                }
+            }
+            byte => out.push(byte),
+        }
+        i += 1;
+    }
+    String::from_utf8_lossy(&out).into_owned()
+}
+
+fn qs_pairs(value: &Value, prefix: &str, out: &mut Vec<(String, String)>) {
+    match value {
+        Value::Object(map) => {
+            for (key, value) in map {
+                qs_pairs(value, &format!("{prefix}[{key}]"), out);
+            }
+        }
+        Value::Array(items) => {
+            for (index, value) in items.iter().enumerate() {
+                qs_pairs(value, &format!("{prefix}[{index}]"), out);
+            }
+        }
+        Value::String(text) => out.push((prefix.to_string(), text.clone())),
+        other => out.push((prefix.to_string(), other.to_string())),
+    }
+}
+
+/// The `where` object as REST query parameters, values unencoded, for
+/// [`PayloadClient::find_page`](crate::payload_tools::client::PayloadClient::find_page).
+pub fn where_params(json: &Value) -> Vec<(String, String)> {
+    let mut pairs = Vec::new();
+    qs_pairs(json, "where", &mut pairs);
+    pairs
+}
+
+fn where_to_qs(json: &Value) -> String {
+    where_params(json)
+        .into_iter()
+        .map(|(key, value)| format!("{key}={}", percent_encode(&value)))
+        .collect::<Vec<_>>()
+        .join("&")
+}
+
+fn qs_to_where(text: &str, warnings: &mut Vec<String>) -> Result<Value, String> {
+    let text = text.trim().trim_start_matches('?');
+    let text = text.rsplit_once('?').map_or(text, |(_, query)| query);
+    let mut root = Value::Object(Map::new());
+    for pair in text.split('&').filter(|pair| !pair.is_empty()) {
+        let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+        let key = percent_decode(key);
+        let value = percent_decode(value);
+        let Some(rest) = key.strip_prefix("where") else {
+            warnings.push(format!("Ignored {key}, which is not part of where"));
+            continue;
+        };
+        let segments: Vec<&str> = rest
+            .trim_start_matches('[')
+            .trim_end_matches(']')
+            .split("][")
+            .collect();
+        if rest.is_empty() || segments.iter().any(|segment| segment.is_empty()) {
+            return Err(format!("Malformed query-string key {key}"));
+        }
+        let mut node = &mut root;
+        for segment in &segments {
+            if !node.is_object() {
+                return Err(format!("{key} conflicts with another parameter"));
+            }
+            node = node
+                .as_object_mut()
+                .map(|map| map.entry(segment.to_string()).or_insert_with(|| json!({})))
+                .ok_or_else(|| format!("{key} conflicts with another parameter"))?;
+        }
+        *node = Value::String(value);
+    }
+    Ok(index_maps_to_arrays(root))
+}
+
+/// `{ "0": a, "1": b }` -> `[a, b]`, as qs parses indexed keys.
+fn index_maps_to_arrays(value: Value) -> Value {
+    match value {
+        Value::Object(map) => {
+            let indexed = !map.is_empty() && map.keys().all(|key| key.parse::<usize>().is_ok());
+            let mut entries: Vec<(String, Value)> = map
+                .into_iter()
+                .map(|(key, value)| (key, index_maps_to_arrays(value)))
+                .collect();
+            if indexed {
+                entries.sort_by_key(|(key, _)| key.parse::<usize>().unwrap_or_default());
+                Value::Array(entries.into_iter().map(|(_, value)| value).collect())
+            } else {
+                Value::Object(entries.into_iter().collect())
+            }
+        }
+        other => other,
+    }
+}
+
+// --- SQL ---
+
+/// `name` in `case`, keeping the leading underscore of columns like `_status`.
+fn recase(case: Case, name: &str) -> String {
+    let rest = name.trim_start_matches('_');
+    format!("{}{}", &name[..name.len() - rest.len()], case.convert(rest))
+}
+
+fn column(path: &str, warnings: &mut Vec<String>) -> String {
+    if path.contains('.') {
+        warnings.push(format!(
+            "{path}: groups are stored as prefixed columns, but paths through arrays, blocks or relationships need a join"
+        ));
+    }
+    let name: Vec<String> = path.split('.').map(|part| recase(Case::Snake, part)).collect();
+    format!("\"{}\"", name.join("_"))
+}
+
+fn literal(value: &Value) -> Result<String, String> {
+    match value {
+        Value::String(text) => Ok(format!("'{}'", text.replace('\'', "''"))),
+        Value::Number(number) => Ok(number.to_string()),
+        Value::Bool(true) => Ok("TRUE".to_string()),
+        Value::Bool(false) => Ok("FALSE".to_string()),
+        Value::Null => Ok("NULL".to_string()),
+        other => Err(format!("{other} is not a scalar value")),
+    }
+}
+
+/// Values of `in`, `not_in` and `all`: an array or a comma-separated string.
+fn list(value: &Value) -> Result<Vec<String>, String> {
+    match value {
+        Value::Array(items) => items.iter().map(literal).collect(),
+        Value::String(text) => text
+            .split(',')
+            .map(|item| literal(&json!(item.trim())))
+            .collect(),
+        other => Ok(vec![literal(other)?]),
+    }
+}
+
+fn like_pattern(text: &str) -> String {
+    format!("'%{}%'", escape_like(text).replace('\'', "''"))
+}
+
+fn truthy(value: &Value) -> bool {
+    matches!(value, Value::Bool(true)) || value.as_str() == Some("true")
+}
+
+fn predicate(
+    path: &str,
+    operator: &str,
+    value: &Value,
+    warnings: &mut Vec<String>,
+) -> Result<String, String> {
+    let col = column(path, warnings);
+    let compare =
+        |op: &str| -> Result<String, String> { Ok(format!("{col} {op} {}", literal(value)?)) };
+    match operator {
+        "equals" if value.is_null() => Ok(format!("{col} IS NULL")),
+        "equals" => compare("="),
+        // Like MongoDB's $ne, Payload's not_equals also matches unset values.
+        "not_equals" if value.is_null() => Ok(format!("{col} IS NOT NULL")),
+        "not_equals" => Ok(format!("({col} != {} OR {col} IS NULL)", literal(value)?)),
+        "greater_than" => compare(">"),
+        "greater_than_equal" => compare(">="),
+        "less_than" => compare("<"),
+        "less_than_equal" => compare("<="),
+        "contains" => Ok(format!("{col} ILIKE {}", like_pattern(&scalar_text(value)))),
+        // Every word must appear, in any order.
+        "like" => {
+            let text = scalar_text(value);
+            let words: Vec<String> = text
+                .split_whitespace()
+                .map(|word| format!("{col} ILIKE {}", like_pattern(word)))
+                .collect();
+            match words.len() {
+                0 => Ok("TRUE".to_string()),
+                1 => Ok(words.join("")),
+                _ => Ok(format!("({})", words.join(" AND "))),
+            }
+        }
+        "in" => Ok(format!("{col} IN ({})", list(value)?.join(", "))),
+        "not_in" => Ok(format!("{col} NOT IN ({})", list(value)?.join(", "))),
+        "all" => {
+            warnings.push(format!(
+                "{path}: hasMany values live in a separate table in the Postgres adapter; `all` is shown as an array containment"
+            ));
+            Ok(format!("{col} @> ARRAY[{}]", list(value)?.join(", ")))
+        }
+        "exists" if truthy(value) => Ok(format!("{col} IS NOT NULL")),
+        "exists" => Ok(format!("{col} IS NULL")),
+        "near" => {
+            let parts: Vec<String> = match value {
+                Value::Array(items) => items.iter().map(|item| item.to_string()).collect(),
+                other => scalar_text(other)
+                    .split(',')
+                    .map(|part| part.trim().to_string())
+                    .collect(),
+            };
+            let [lng, lat, rest @ ..] = parts.as_slice() else {
+                return Err(format!(
+                    "{path}: near takes \"longitude,latitude[,maxDistance[,minDistance]]\""
+                ));
+            };
+            let point = format!("ST_SetSRID(ST_MakePoint({lng}, {lat}), 4326)::geography");
+            match rest {
+                [] => Ok(format!("ST_DWithin({col}::geography, {point}, 'Infinity')")),
+                [max] => Ok(format!("ST_DWithin({col}::geography, {point}, {max})")),
+                [max, min, ..] => Ok(format!(
+                    "(ST_DWithin({col}::geography, {point}, {max}) AND NOT ST_DWithin({col}::geography, {point}, {min}))"
+                )),
+            }
+        }
+        "within" | "intersects" => {
+            let function = if operator == "within" {
+                "ST_Within"
+            } else {
+                "ST_Intersects"
+            };
+            let geojson = match value {
+                Value::String(text) => text.clone(),
+                other => other.to_string(),
+            };
+            Ok(format!(
+                "{function}({col}, ST_GeomFromGeoJSON({}))",
+                literal(&json!(geojson))?
+            ))
+        }
+        other => Err(format!("Unknown operator {other} at {path}")),
+    }
+}
+
+fn scalar_text(value: &Value) -> String {
+    match value {
+        Value::String(text) => text.clone(),
+        other => other.to_string(),
+    }
+}
+
+fn where_to_sql(map: &Map<String, Value>, warnings: &mut Vec<String>) -> Result<String, String> {
+    let mut parts = Vec::new();
+    for (key, value) in map {
+        match (key.as_str(), value) {
+            ("and" | "or", Value::Array(items)) => {
+                let joined: Vec<String> = items
+                    .iter()
+                    .filter_map(Value::as_object)
+                    .map(|item| where_to_sql(item, warnings))
+                    .collect::<Result<_, _>>()?;
+                let separator = if key == "and" { " AND " } else { " OR " };
+                match joined.len() {
+                    0 => {}
+                    1 => parts.extend(joined),
+                    _ => parts.push(format!("({})", joined.join(separator))),
+                }
+            }
+            (path, Value::Object(operators)) => {
+                for (operator, value) in operators {
+                    parts.push(predicate(path, operator, value, warnings)?);
+                }
+            }
+            (path, _) => return Err(format!("{path} must map operators to values")),
+        }
+    }
+    Ok(if parts.is_empty() {
+        "TRUE".to_string()
+    } else {
+        parts.join(" AND ")
+    })
+}
+
+fn operand(column: &str, operator: &str, value: Value) -> Value {
+    json!({ recase(Case::Camel, column): { operator: value } })
+}
+
+fn condition_to_where(condition: &Condition) -> Result<Value, String> {
+    match condition {
+        Condition::And(parts) => Ok(json!({
+            "and": parts.iter().map(condition_to_where).collect::<Result<Vec<_>, _>>()?
+        })),
+        Condition::Or(parts) => Ok(json!({
+            "or": parts.iter().map(condition_to_where).collect::<Result<Vec<_>, _>>()?
+        })),
+        Condition::IsNull { column } => Ok(operand(column, "exists", json!(false))),
+        Condition::Comparison {
+            column,
+            operator,
+            value,
+        } => {
+            let operator = match (operator, value) {
+                (Operator::Eq, Value::Null) => return Ok(operand(column, "exists", json!(false))),
+                (Operator::Neq, Value::Null) => return Ok(operand(column, "exists", json!(true))),
+                (Operator::Like, value) => {
+                    let inner = value
+                        .as_str()
+                        .and_then(like_contains)
+                        .ok_or_else(|| {
+                            format!("{column} LIKE {value}: only '%text%' patterns have a Payload equivalent (contains)")
+                        })?;
+                    return Ok(operand(column, "contains", json!(inner)));
+                }
+                (Operator::Eq, _) => "equals",
+                (Operator::Neq, _) => "not_equals",
+                (Operator::Gt, _) => "greater_than",
+                (Operator::Gte, _) => "greater_than_equal",
+                (Operator::Lt, _) => "less_than",
+                (Operator::Lte, _) => "less_than_equal",
+                (Operator::In, _) => "in",
+            };
+            Ok(operand(column, operator, value.clone()))
+        }
+        Condition::Not(inner) => match inner.as_ref() {
+            Condition::IsNull { column } => Ok(operand(column, "exists", json!(true))),
+            Condition::Not(inner) => condition_to_where(inner),
+            Condition::Comparison {
+                column,
+                operator: Operator::Eq,
+                value,
+            } => Ok(operand(column, "not_equals", value.clone())),
+            Condition::Comparison {
+                column,
+                operator: Operator::In,
+                value,
+            } => Ok(operand(column, "not_in", value.clone())),
+            _ => {
+                Err("Payload has no negation; only NOT NULL, NOT = and NOT IN convert".to_string())
+            }
+        },
+        Condition::ColumnComparison { left, right, .. } => Err(format!(
+            "{left} compared with column {right}: Payload compares fields with values only"
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trips_between_formats() {
+        let query = json!({
+            "status": { "equals": "published" },
+            "or": [
+                { "publishedAt": { "greater_than": "2024-01-01" } },
+                { "tags": { "in": ["news", "tips"] } },
+            ],
+        });
+        let converted = convert_query(&query, None).unwrap();
+        assert_eq!(converted.from, QueryFormat::Json);
+        assert_eq!(
+            converted.sql,
+            "(\"published_at\" > '2024-01-01' OR \"tags\" IN ('news', 'tips')) AND \"status\" = 'published'"
+        );
+        assert!(converted.qs.contains("where[or][1][tags][in][0]=news"));
+
+        let from_qs = convert_query(&json!(format!("?{}", converted.qs)), None).unwrap();
+        assert_eq!(from_qs.from, QueryFormat::Qs);
+        assert_eq!(from_qs.json, query);
+
+        let from_sql = convert_query(
+            &json!("WHERE published_at >= 10 AND title LIKE '%rust%'"),
+            None,
+        )
+        .unwrap();
+        assert_eq!(
+            from_sql.json,
+            json!({ "and": [
+                { "publishedAt": { "greater_than_equal": 10 } },
+                { "title": { "contains": "rust" } },
+            ] })
+        );
+        assert!(convert_query(&json!({ "title": { "startsWith": "a" } }), None).is_err());
+    }
+}
@@ -0,0 +1,1101 @@
+//! A guided path from WordPress to Payload: collections for post types,
+//! taxonomies and ACF field groups, and a Local API seed script for the
+//! content of a WXR export (Tools → Export in the WordPress admin).
+//!
+//! Post types and taxonomies map to collection slugs by their REST base
+//! (`post_tag` becomes `tags`) unless `collections` says otherwise; the
+//! schema and the seed script use the same mapping, so the script fills the
+//! collections the schema defines.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::payload_tools::{
+    external_schema::{choice, collection_slug, field, finish, relation, ExternalSchemaImport},
+    naming::Case,
+    plugins::PayloadVersion,
+    schema_model::{SchemaBlock, SchemaCollection, SchemaField, SchemaGlobal, SchemaModel},
+};
+
+/// Collection that authors become.
+const USERS: &str = "users";
+/// Upload collection that attachments become.
+const MEDIA: &str = "media";
+/// Statuses a seeded post can have, as WordPress names them.
+const STATUSES: [&str; 5] = ["publish", "draft", "pending", "private", "future"];
+/// REST bases of the built-in post types and taxonomies.
+const REST_BASES: [(&str, &str); 5] = [
+    ("post", "posts"),
+    ("page", "pages"),
+    ("attachment", MEDIA),
+    ("category", "categories"),
+    ("post_tag", "tags"),
+];
+/// Post types that hold site structure or plugin state rather than content.
+const INTERNAL_TYPES: [&str; 18] = [
+    "attachment",
+    "revision",
+    "nav_menu_item",
+    "custom_css",
+    "customize_changeset",
+    "oembed_cache",
+    "user_request",
+    "wp_block",
+    "wp_template",
+    "wp_template_part",
+    "wp_global_styles",
+    "wp_navigation",
+    "wp_font_family",
+    "wp_font_face",
+    "acf-field-group",
+    "acf-field",
+    "acf-post-type",
+    "acf-taxonomy",
+];
+/// Taxonomies that group menus, formats and templates rather than content.
+const INTERNAL_TAXONOMIES: [&str; 6] = [
+    "nav_menu",
+    "post_format",
+    "link_category",
+    "wp_theme",
+    "wp_template_part_area",
+    "wp_pattern_category",
+];
+
+/// The collection slug of a post type or taxonomy.
+fn wp_slug(name: &str, rest_base: Option<&str>, overrides: Option<&BTreeMap<String, String>>) -> String {
+    if let Some(slug) = overrides.and_then(|overrides| overrides.get(name)) {
+        return slug.clone();
+    }
+    match rest_base.or_else(|| REST_BASES.iter().find(|(known, _)| *known == name).map(|(_, base)| *base)) {
+        Some(base) => Case::Kebab.convert(base),
+        None => collection_slug(name),
+    }
+}
+
+/// The post field relating to a taxonomy's collection.
+fn taxonomy_field(slug: &str) -> String {
+    Case::Camel.convert(slug)
+}
+
+// Schema
+
+#[derive(Debug, Clone, Default, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct WpSchemaOptions {
+    /// Post types as `/wp-json/wp/v2/types` returns them, or their names;
+    /// default `post` and `page`
+    pub post_types: Option<Value>,
+    /// Taxonomies as `/wp-json/wp/v2/taxonomies` returns them, or their
+    /// names; default `category` and `post_tag`
+    pub taxonomies: Option<Value>,
+    /// ACF field groups as exported to JSON: one group or an array
+    pub field_groups: Option<Value>,
+    /// Collection slugs by post type or taxonomy, in place of their REST base
+    pub collections: Option<BTreeMap<String, String>>,
+}
+
+/// A post type or taxonomy: its name, REST base, whether it is
+/// hierarchical, and the names it relates to (taxonomies of a post type or
+/// post types of a taxonomy).
+struct WpType {
+    name: String,
+    rest_base: Option<String>,
+    hierarchical: bool,
+    related: Option<Vec<String>>,
+}
+
+/// Entries of a REST listing keyed by name, or plain names.
+fn wp_types(value: Option<&Value>, defaults: &[&str], related_key: &str) -> Vec<WpType> {
+    let strings = |value: &Value| -> Option<Vec<String>> {
+        value
+            .as_array()
+            .map(|items| items.iter().filter_map(Value::as_str).map(str::to_string).collect())
+    };
+    match value {
+        None => defaults
+            .iter()
+            .map(|name| WpType {
+                name: name.to_string(),
+                rest_base: None,
+                hierarchical: *name == "page" || *name == "category",
+                related: None,
+            })
+            .collect(),
+        Some(Value::Object(map)) => map
+            .iter()
+            .map(|(name, entry)| WpType {
+                name: entry["slug"].as_str().unwrap_or(name).to_string(),
+                rest_base: entry["rest_base"].as_str().map(str::to_string),
+                hierarchical: entry["hierarchical"].as_bool().unwrap_or(false),
+                related: entry.get(related_key).and_then(strings),
+            })
+            .collect(),
+        Some(other) => strings(other)
+            .unwrap_or_default()
+            .into_iter()
+            .map(|name| WpType {
+                hierarchical: name == "page" || name == "category",
+                name,
+                rest_base: None,
+                related: None,
+            })
+            .collect(),
+    }
+}
+
+/// ACF stores flags as booleans, 0 and 1, or "0" and "1".
+fn truthy(value: &Value) -> bool {
+    match value {
+        Value::Bool(set) => *set,
+        Value::Number(number) => number.as_i64().is_some_and(|number| number != 0),
+        Value::String(text) => text == "1",
+        _ => false,
+    }
+}
+
+/// Choices of a select, checkbox or radio field: an object of values to
+/// labels, or a list.
+fn acf_choices(value: &Value) -> Vec<Value> {
+    match value {
+        Value::Object(map) => map.keys().cloned().map(Value::String).collect(),
+        Value::Array(items) => items.clone(),
+        _ => Vec::new(),
+    }
+}
+
+/// Converts ACF fields, given the collection slugs of post types and
+/// taxonomies.
+struct Acf<'a> {
+    slugs: &'a BTreeMap<String, String>,
+}
+
+impl Acf<'_> {
+    fn slug(&self, name: &str) -> String {
+        self.slugs.get(name).cloned().unwrap_or_else(|| wp_slug(name, None, None))
+    }
+
+    fn fields(&self, specs: &Value, path: &str, warnings: &mut Vec<String>) -> Vec<SchemaField> {
+        let specs: Vec<&Value> = match specs {
+            Value::Object(map) => map.values().collect(),
+            other => other.as_array().map(|items| items.iter().collect()).unwrap_or_default(),
+        };
+        specs
+            .into_iter()
+            .filter_map(|spec| self.field(spec, path, warnings))
+            .collect()
+    }
+
+    fn field(&self, spec: &Value, path: &str, warnings: &mut Vec<String>) -> Option<SchemaField> {
+        let kind = spec["type"].as_str().unwrap_or_default();
+        if matches!(kind, "tab" | "message" | "accordion") {
+            return None;
+        }
+        let Some(name) = spec["name"].as_str().filter(|name| !name.is_empty()) else {
+            warnings.push(format!("{path}: skipped a {kind} field without a name"));
+            return None;
+        };
+        let at = format!("{path}.{name}");
+        let multiple = truthy(&spec["multiple"]);
+        let targets = |key: &str| -> Vec<String> {
+            match &spec[key] {
+                Value::String(name) if !name.is_empty() => vec![self.slug(name)],
+                Value::Array(names) => names.iter().filter_map(Value::as_str).map(|name| self.slug(name)).collect(),
+                _ => Vec::new(),
+            }
+        };
+        let mut converted = match kind {
+            "text" | "url" | "color_picker" | "oembed" => field(name, "text"),
+            "textarea" => field(name, "textarea"),
+            "number" | "range" => field(name, "number"),
+            "email" => field(name, "email"),
+            "wysiwyg" => field(name, "richText"),
+            "image" | "file" => relation(name, "upload", vec![MEDIA.to_string()], false),
+            "gallery" => relation(name, "upload", vec![MEDIA.to_string()], true),
+            "select" => SchemaField {
+                has_many: multiple,
+                ..choice(name, &acf_choices(&spec["choices"]))
+            },
+            "checkbox" => SchemaField {
+                has_many: true,
+                ..choice(name, &acf_choices(&spec["choices"]))
+            },
+            "radio" | "button_group" => SchemaField {
+                field_type: "radio".to_string(),
+                ..choice(name, &acf_choices(&spec["choices"]))
+            },
+            "true_false" => field(name, "checkbox"),
+            "date_picker" | "date_time_picker" | "time_picker" => field(name, "date"),
+            "google_map" => field(name, "point"),
+            "link" => SchemaField {
+                fields: Some(vec![
+                    field("url", "text"),
+                    field("title", "text"),
+                    field("target", "text"),
+                ]),
+                ..field(name, "group")
+            },
+            "post_object" | "page_link" | "relationship" => {
+                let mut to = targets("post_type");
+                if to.is_empty() {
+                    warnings.push(format!("{at}: links any post type; relationTo lists posts and pages"));
+                    to = vec![self.slug("post"), self.slug("page")];
+                }
+                relation(name, "relationship", to, multiple || kind == "relationship")
+            }
+            "taxonomy" => {
+                let has_many = matches!(spec["field_type"].as_str(), Some("checkbox" | "multi_select"));
+                relation(name, "relationship", targets("taxonomy"), has_many)
+            }
+            "user" => relation(name, "relationship", vec![USERS.to_string()], multiple),
+            "group" => SchemaField {
+                fields: Some(self.fields(&spec["sub_fields"], &at, warnings)),
+                ..field(name, "group")
+            },
+            "repeater" => SchemaField {
+                fields: Some(self.fields(&spec["sub_fields"], &at, warnings)),
+                ..field(name, "array")
+            },
+            "flexible_content" => {
+                let layouts: Vec<&Value> = match &spec["layouts"] {
+                    Value::Object(map) => map.values().collect(),
+                    Value::Array(items) => items.iter().collect(),
+                    _ => Vec::new(),
+                };
+                let blocks = layouts
+                    .into_iter()
+                    .filter_map(|layout| {
+                        let slug = layout["name"].as_str()?;
+                        Some(SchemaBlock {
+                            slug: Case::Kebab.convert(slug),
+                            fields: self.fields(&layout["sub_fields"], &format!("{at}.{slug}"), warnings),
+                        })
+                    })
+                    .collect();
+                SchemaField {
+                    blocks: Some(blocks),
+                    ..field(name, "blocks")
+                }
+            }
+            "password" => {
+                warnings.push(format!("{at}: password fields aren't converted"));
+                return None;
+            }
+            other => {
+                warnings.push(format!("{at}: ACF type '{other}' became json"));
+                field(name, "json")
+            }
+        };
+        converted.required = truthy(&spec["required"]);
+        Some(converted)
+    }
+}
+
+fn post_type_fields(slug: &str, hierarchical: bool) -> Vec<SchemaField> {
+    let mut fields = vec![
+        SchemaField {
+            required: true,
+            ..field("title", "text")
+        },
+        SchemaField {
+            unique: true,
+            ..field("slug", "text")
+        },
+        field("content", "richText"),
+        field("excerpt", "textarea"),
+        choice("status", &STATUSES.map(Value::from)),
+        field("publishedAt", "date"),
+        relation("author", "relationship", vec![USERS.to_string()], false),
+        relation("featuredImage", "upload", vec![MEDIA.to_string()], false),
+    ];
+    if hierarchical {
+        fields.push(relation("parent", "relationship", vec![slug.to_string()], false));
+    }
+    fields
+}
+
+fn taxonomy_fields(slug: &str, hierarchical: bool) -> Vec<SchemaField> {
+    let mut fields = vec![
+        SchemaField {
+            required: true,
+            ..field("name", "text")
+        },
+        SchemaField {
+            unique: true,
+            ..field("slug", "text")
+        },
+        field("description", "textarea"),
+    ];
+    if hierarchical {
+        fields.push(relation("parent", "relationship", vec![slug.to_string()], false));
+    }
+    fields
+}
+
+/// Collections for the post types, taxonomies and ACF field groups of a
+/// WordPress site, with a `users` collection for authors.
+pub fn wp_to_payload_schema(options: &WpSchemaOptions) -> Result<ExternalSchemaImport, String> {
+    let overrides = options.collections.as_ref();
+    let mut warnings = Vec::new();
+    let post_types: Vec<WpType> = wp_types(options.post_types.as_ref(), &["post", "page"], "taxonomies")
+        .into_iter()
+        .filter(|post_type| !INTERNAL_TYPES.contains(&post_type.name.as_str()))
+        .collect();
+    let taxonomies: Vec<WpType> = wp_types(options.taxonomies.as_ref(), &["category", "post_tag"], "types")
+        .into_iter()
+        .filter(|taxonomy| !INTERNAL_TAXONOMIES.contains(&taxonomy.name.as_str()))
+        .collect();
+    if post_types.is_empty() {
+        return Err("No content post types given".to_string());
+    }
+    let slugs: BTreeMap<String, String> = post_types
+        .iter()
+        .chain(&taxonomies)
+        .map(|entry| (entry.name.clone(), wp_slug(&entry.name, entry.rest_base.as_deref(), overrides)))
+        .collect();
+
+    let mut schema = SchemaModel::default();
+    for post_type in &post_types {
+        let slug = &slugs[&post_type.name];
+        let mut fields = post_type_fields(slug, post_type.hierarchical);
+        for taxonomy in &taxonomies {
+            // Without the REST listings, taxonomies go on every post type but pages.
+            let attached = match (&post_type.related, &taxonomy.related) {
+                (_, Some(types)) => types.contains(&post_type.name),
+                (Some(names), None) => names.contains(&taxonomy.name),
+                (None, None) => post_type.name != "page",
+            };
+            if attached {
+                let target = &slugs[&taxonomy.name];
+                fields.push(relation(&taxonomy_field(target), "relationship", vec![target.clone()], true));
+            }
+        }
+        schema.collections.push(SchemaCollection {
+            slug: slug.clone(),
+            fields,
+            timestamps: true,
+            ..Default::default()
+        });
+    }
+    for taxonomy in &taxonomies {
+        let slug = &slugs[&taxonomy.name];
+        schema.collections.push(SchemaCollection {
+            slug: slug.clone(),
+            fields: taxonomy_fields(slug, taxonomy.hierarchical),
+            timestamps: true,
+            ..Default::default()
+        });
+    }
+
+    let acf = Acf { slugs: &slugs };
+    let groups: Vec<&Value> = match &options.field_groups {
+        Some(Value::Array(groups)) => groups.iter().collect(),
+        Some(group @ Value::Object(_)) => vec![group],
+        Some(_) => return Err("fieldGroups must be an ACF field group or an array of them".to_string()),
+        None => Vec::new(),
+    };
+    for group in groups {
+        let title = group["title"].as_str().unwrap_or("field group");
+        let fields = acf.fields(&group["fields"], &Case::Camel.convert(title), &mut warnings);
+        let mut placed = false;
+        for rule in group["location"].as_array().into_iter().flatten().flat_map(|rules| {
+            rules.as_array().into_iter().flatten()
+        }) {
+            let (param, value) = (rule["param"].as_str().unwrap_or_default(), rule["value"].as_str().unwrap_or_default());
+            if rule["operator"].as_str() != Some("==") {
+                continue;
+            }
+            let target = match param {
+                "post_type" | "taxonomy" => schema.collections.iter_mut().find(|collection| {
+                    slugs.get(value).is_some_and(|slug| *slug == collection.slug)
+                }),
+                "page_template" | "page_type" | "page_parent" => schema
+                    .collections
+                    .iter_mut()
+                    .find(|collection| slugs.get("page").is_some_and(|slug| *slug == collection.slug)),
+                "options_page" => {
+                    let slug = Case::Kebab.convert(value);
+                    let index = match schema.globals.iter().position(|global| global.slug == slug) {
+                        Some(index) => index,
+                        None => {
+                            schema.globals.push(SchemaGlobal { slug, fields: Vec::new() });
+                            schema.globals.len() - 1
+                        }
+                    };
+                    extend_fields(&mut schema.globals[index].fields, &fields);
+                    placed = true;
+                    continue;
+                }
+                _ => None,
+            };
+            match target {
+                Some(collection) => {
+                    extend_fields(&mut collection.fields, &fields);
+                    if param != "post_type" && param != "taxonomy" {
+                        warnings.push(format!(
+                            "{title}: shown by {param} in WordPress, added to every document of {}",
+                            collection.slug
+                        ));
+                    }
+                    placed = true;
+                }
+                None => warnings.push(format!("{title}: location {param} == {value} is not a converted collection")),
+            }
+        }
+        if !placed {
+            warnings.push(format!("{title}: no location rule matched a collection or options page; fields not added"));
+        }
+    }
+
+    schema.collections.push(SchemaCollection {
+        slug: USERS.to_string(),
+        fields: vec![field("name", "text")],
+        timestamps: true,
+        auth: true,
+        upload: false,
+    });
+    finish(schema, warnings)
+}
+
+/// Append the fields not yet in `fields`, as several location rules can
+/// place a group on the same collection.
+fn extend_fields(fields: &mut Vec<SchemaField>, added: &[SchemaField]) {
+    for field in added {
+        if !fields.iter().any(|existing| existing.name == field.name) {
+            fields.push(field.clone());
+        }
+    }
+}
+
+// WXR export
+
+/// Where `needle` first occurs in `xml` outside CDATA sections.
+fn find_markup(xml: &str, needle: &str) -> Option<usize> {
+    let mut pos = 0;
+    loop {
+        let rest = &xml[pos..];
+        let at = rest.find(needle)?;
+        match rest[..at].find("<![CDATA[") {
+            Some(cdata) => pos += cdata + rest[cdata..].find("]]>")? + 3,
+            None => return Some(pos + at),
+        }
+    }
+}
+
+/// The elements named `name` in `xml`, as their attributes and content.
+fn elements<'a>(xml: &'a str, name: &str) -> Vec<(&'a str, &'a str)> {
+    let open = format!("<{name}");
+    let close = format!("</{name}>");
+    let mut found = Vec::new();
+    let mut rest = xml;
+    while let Some(start) = find_markup(rest, &open) {
+        let after = &rest[start + open.len()..];
+        let Some(tag_end) = after.find('>') else {
+            break;
+        };
+        let attributes = &after[..tag_end];
+        // `<wp:author>` but not `<wp:author_login>`
+        if !(attributes.is_empty() || attributes.starts_with(char::is_whitespace) || attributes == "/") {
+            rest = after;
+            continue;
+        }
+        let body = &after[tag_end + 1..];
+        if let Some(attributes) = attributes.strip_suffix('/') {
+            found.push((attributes.trim(), ""));
+            rest = body;
+            continue;
+        }
+        let Some(end) = find_markup(body, &close) else {
+            break;
+        };
+        found.push((attributes.trim(), &body[..end]));
+        rest = &body[end + close.len()..];
+    }
+    found
+}
+
+/// Text of an element's content: CDATA as is, otherwise with entities decoded.
+fn content_text(content: &str) -> String {
+    let content = content.trim();
+    if content.contains("<![CDATA[") {
+        return content.replace("<![CDATA[", "").replace("]]>", "");
+    }
+    decode_entities(content)
+}
+
+/// `text` with XML entities decoded. WordPress keeps term names
+/// HTML-escaped, so those are decoded even when they come as CDATA.
+fn decode_entities(text: &str) -> String {
+    text.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&#039;", "'")
+        .replace("&amp;", "&")
+}
+
+/// Text of the first `name` element of `xml`, unless empty.
+fn child(xml: &str, name: &str) -> Option<String> {
+    elements(xml, name)
+        .first()
+        .map(|(_, content)| content_text(content))
+        .filter(|text| !text.is_empty())
+}
+
+fn attribute(attributes: &str, name: &str) -> Option<String> {
+    let start = attributes.find(&format!("{name}=\""))? + name.len() + 2;
+    let end = attributes[start..].find('"')?;
+    Some(content_text(&attributes[start..start + end]))
+}
+
+/// `2024-05-01 10:00:00` in UTC as ISO 8601; drafts have zeros.
+fn wxr_date(date: Option<String>) -> Option<String> {
+    let date = date?;
+    if date.starts_with("0000") {
+        return None;
+    }
+    Some(format!("{}.000Z", date.replacen(' ', "T", 1)))
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SeedUser {
+    login: String,
+    email: String,
+    name: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SeedTerm {
+    taxonomy: String,
+    slug: String,
+    name: String,
+    parent: Option<String>,
+    description: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SeedMedia {
+    wp_id: u64,
+    title: String,
+    url: String,
+    alt: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SeedPost {
+    wp_id: u64,
+    #[serde(rename = "type")]
+    post_type: String,
+    title: String,
+    slug: Option<String>,
+    status: String,
+    date: Option<String>,
+    content: String,
+    excerpt: Option<String>,
+    author: Option<String>,
+    parent: Option<u64>,
+    featured_image: Option<u64>,
+    /// Term slugs by taxonomy
+    terms: BTreeMap<String, Vec<String>>,
+    /// Custom fields, including ACF values, as WordPress stored them
+    meta: BTreeMap<String, String>,
+}
+
+#[derive(Debug, Default, Serialize)]
+struct SeedData {
+    users: Vec<SeedUser>,
+    terms: Vec<SeedTerm>,
+    media: Vec<SeedMedia>,
+    posts: Vec<SeedPost>,
+}
+
+/// `items` reordered so each comes after its parent; those whose parent is
+/// missing keep their place at the end and are counted.
+fn parents_first<T, K: Ord + Clone>(
+    mut items: Vec<T>,
+    key: impl Fn(&T) -> K,
+    parent: impl Fn(&T) -> Option<K>,
+) -> (Vec<T>, usize) {
+    let keys: BTreeSet<K> = items.iter().map(&key).collect();
+    let mut placed = BTreeSet::new();
+    let mut ordered = Vec::with_capacity(items.len());
+    loop {
+        let (ready, waiting): (Vec<T>, Vec<T>) = items.into_iter().partition(|item| {
+            parent(item).is_none_or(|parent| placed.contains(&parent) || !keys.contains(&parent))
+        });
+        items = waiting;
+        if ready.is_empty() {
+            break;
+        }
+        placed.extend(ready.iter().map(&key));
+        ordered.extend(ready);
+    }
+    let orphans = items.len();
+    ordered.extend(items);
+    (ordered, orphans)
+}
+
+fn read_wxr(xml: &str, warnings: &mut Vec<String>) -> Result<SeedData, String> {
+    let channel = elements(xml, "channel")
+        .into_iter()
+        .next()
+        .map(|(_, content)| content)
+        .ok_or("Not a WXR export: no <channel> element")?;
+    let mut data = SeedData::default();
+    for (_, author) in elements(channel, "wp:author") {
+        let Some(login) = child(author, "wp:author_login") else {
+            continue;
+        };
+        data.users.push(SeedUser {
+            email: child(author, "wp:author_email").unwrap_or_else(|| format!("{login}@example.com")),
+            name: child(author, "wp:author_display_name").unwrap_or_else(|| login.clone()),
+            login,
+        });
+    }
+
+    let mut terms: BTreeMap<(String, String), SeedTerm> = BTreeMap::new();
+    let mut add_term = |term: SeedTerm| {
+        if !INTERNAL_TAXONOMIES.contains(&term.taxonomy.as_str()) {
+            terms.entry((term.taxonomy.clone(), term.slug.clone())).or_insert(term);
+        }
+    };
+    for (_, category) in elements(channel, "wp:category") {
+        if let Some(slug) = child(category, "wp:category_nicename") {
+            add_term(SeedTerm {
+                taxonomy: "category".to_string(),
+                name: child(category, "wp:cat_name").map(|name| decode_entities(&name)).unwrap_or_else(|| slug.clone()),
+                parent: child(category, "wp:category_parent"),
+                description: child(category, "wp:category_description"),
+                slug,
+            });
+        }
+    }
+    for (_, tag) in elements(channel, "wp:tag") {
+        if let Some(slug) = child(tag, "wp:tag_slug") {
+            add_term(SeedTerm {
+                taxonomy: "post_tag".to_string(),
+                name: child(tag, "wp:tag_name").map(|name| decode_entities(&name)).unwrap_or_else(|| slug.clone()),
+                parent: None,
+                description: child(tag, "wp:tag_description"),
+                slug,
+            });
+        }
+    }
+    for (_, term) in elements(channel, "wp:term") {
+        if let (Some(taxonomy), Some(slug)) = (child(term, "wp:term_taxonomy"), child(term, "wp:term_slug")) {
+            add_term(SeedTerm {
+                taxonomy,
+                name: child(term, "wp:term_name").map(|name| decode_entities(&name)).unwrap_or_else(|| slug.clone()),
+                parent: child(term, "wp:term_parent"),
+                description: child(term, "wp:term_description"),
+                slug,
+            });
+        }
+    }
+
+    let mut skipped: BTreeMap<String, usize> = BTreeMap::new();
+    let mut posts = Vec::new();
+    for (_, item) in elements(channel, "item") {
+        let post_type = child(item, "wp:post_type").unwrap_or_else(|| "post".to_string());
+        let status = child(item, "wp:status").unwrap_or_else(|| "publish".to_string());
+        let Some(wp_id) = child(item, "wp:post_id").and_then(|id| id.parse().ok()) else {
+            continue;
+        };
+        let title = child(item, "title").unwrap_or_default();
+        let meta: BTreeMap<String, String> = elements(item, "wp:postmeta")
+            .into_iter()
+            .filter_map(|(_, meta)| Some((child(meta, "wp:meta_key")?, child(meta, "wp:meta_value").unwrap_or_default())))
+            .collect();
+        if post_type == "attachment" {
+            if let Some(url) = child(item, "wp:attachment_url") {
+                data.media.push(SeedMedia {
+                    wp_id,
+                    title,
+                    url,
+                    alt: meta.get("_wp_attachment_image_alt").filter(|alt| !alt.is_empty()).cloned(),
+                });
+            }
+            continue;
+        }
+        if INTERNAL_TYPES.contains(&post_type.as_str()) || !STATUSES.contains(&status.as_str()) {
+            *skipped.entry(format!("{post_type} ({status})")).or_default() += 1;
+            continue;
+        }
+        let mut post_terms: BTreeMap<String, Vec<String>> = BTreeMap::new();
+        for (attributes, name) in elements(item, "category") {
+            let (Some(taxonomy), Some(slug)) = (attribute(attributes, "domain"), attribute(attributes, "nicename")) else {
+                continue;
+            };
+            add_term(SeedTerm {
+                taxonomy: taxonomy.clone(),
+                name: decode_entities(&content_text(name)),
+                parent: None,
+                description: None,
+                slug: slug.clone(),
+            });
+            if !INTERNAL_TAXONOMIES.contains(&taxonomy.as_str()) {
+                post_terms.entry(taxonomy).or_default().push(slug);
+            }
+        }
+        posts.push(SeedPost {
+            wp_id,
+            post_type,
+            title,
+            slug: child(item, "wp:post_name"),
+            status,
+            date: wxr_date(child(item, "wp:post_date_gmt")),
+            content: child(item, "content:encoded").unwrap_or_default(),
+            excerpt: child(item, "excerpt:encoded"),
+            author: child(item, "dc:creator"),
+            parent: child(item, "wp:post_parent").and_then(|id| id.parse().ok()).filter(|id| *id != 0),
+            featured_image: meta.get("_thumbnail_id").and_then(|id| id.parse().ok()),
+            terms: post_terms,
+            meta: meta.into_iter().filter(|(key, _)| !key.starts_with('_')).collect(),
+        });
+    }
+    for (kind, count) in skipped {
+        warnings.push(format!("Skipped {count} {kind} items"));
+    }
+
+    let (terms, orphans) = parents_first(
+        terms.into_values().collect(),
+        |term| (term.taxonomy.clone(), term.slug.clone()),
+        |term| term.parent.clone().map(|parent| (term.taxonomy.clone(), parent)),
+    );
+    if orphans > 0 {
+        warnings.push(format!("{orphans} terms are part of a parent cycle and are seeded before their parents"));
+    }
+    data.terms = terms;
+    let (posts, orphans) = parents_first(posts, |post| post.wp_id, |post| post.parent);
+    if orphans > 0 {
+        warnings.push(format!("{orphans} posts are part of a parent cycle and are seeded before their parents"));
+    }
+    data.posts = posts;
+    let shortcodes = data
+        .posts
+        .iter()
+        .filter(|post| post.content.contains("[/") || post.content.contains(" /]"))
+        .count();
+    if shortcodes > 0 {
+        warnings.push(format!("{shortcodes} posts contain shortcodes, which come through as text"));
+    }
+    Ok(data)
+}
+
+#[derive(Debug, Clone, Default, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct WpSeedOptions {
+    /// Collection slugs by post type or taxonomy, as given to
+    /// `wp_to_payload_schema`
+    pub collections: Option<BTreeMap<String, String>>,
+    /// Payload major version the script is written for, default 2
+    pub payload_version: Option<PayloadVersion>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, JsonSchema)]
+pub struct WpSeed {
+    /// The seed script and its data by path from the project root
+    pub files: BTreeMap<String, String>,
+    /// Items read per post type and taxonomy, plus `attachment` and `authors`
+    pub counts: BTreeMap<String, usize>,
+    /// Packages the script imports beyond payload
+    pub dependencies: Vec<String>,
+    pub warnings: Vec<String>,
+}
+
+fn seed_script(data: &SeedData, options: &WpSeedOptions) -> String {
+    let overrides = options.collections.as_ref();
+    let types: BTreeSet<&str> = data
+        .posts
+        .iter()
+        .map(|post| post.post_type.as_str())
+        .chain(data.terms.iter().map(|term| term.taxonomy.as_str()))
+        .collect();
+    let collections: Vec<String> = types
+        .iter()
+        .map(|name| format!("  '{name}': '{}',", wp_slug(name, None, overrides)))
+        .collect();
+    let fields: Vec<String> = data
+        .terms
+        .iter()
+        .map(|term| term.taxonomy.as_str())
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .map(|name| format!("  '{name}': '{}',", taxonomy_field(&wp_slug(name, None, overrides))))
+        .collect();
+    let (imports, slug_type, rich_text) = match options.payload_version.unwrap_or_default() {
+        PayloadVersion::V2 => (
+            "import type { Payload } from 'payload';\nimport { randomUUID } from 'crypto';\n",
+            "string",
+            "  // Slate paragraphs of the text; formatting, images and embeds are dropped.\n  const richText = (html: string) =>\n    html\n      .split(/<\\/p>|\\n\\s*\\n/)\n      .map((block) => block.replace(/<[^>]+>/g, '').trim())\n      .filter(Boolean)\n      .map((text) => ({ children: [{ text }] }));\n",
+        ),
+        PayloadVersion::V3 => (
+            "import type { CollectionSlug, Payload } from 'payload';\nimport { convertHTMLToLexical, editorConfigFactory } from '@payloadcms/richtext-lexical';\nimport { randomUUID } from 'crypto';\nimport { JSDOM } from 'jsdom';\n",
+            "CollectionSlug",
+            "  const editorConfig = await editorConfigFactory.default({ config: payload.config });\n  const richText = (html: string) => convertHTMLToLexical({ editorConfig, html, JSDOM });\n",
+        ),
+    };
+    format!(
+        "{imports}
+// Needs `resolveJsonModule` in tsconfig.json.
+import data from './wordpress.json';
+
+type ID = number | string;
+
+// Collections of the WordPress post types and taxonomies, and the post
+// field relating to each taxonomy.
+const collections: Record<string, {slug_type}> = {{
+{collections}
+}};
+const taxonomyFields: Record<string, string> = {{
+{fields}
+}};
+
+// Custom fields, including ACF values, are in `meta` as WordPress stored
+// them; return the ones the collection defines.
+const fromMeta = (_type: string, _meta: Record<string, string>): Record<string, unknown> => ({{}});
+
+export const seedWordPress = async (payload: Payload): Promise<void> => {{
+{rich_text}
+  const users = new Map<string, ID>();
+  for (const user of data.users) {{
+    const existing = await payload.find({{
+      collection: '{USERS}',
+      where: {{ email: {{ equals: user.email }} }},
+      limit: 1,
+    }});
+    const doc =
+      existing.docs[0] ??
+      (await payload.create({{
+        collection: '{USERS}',
+        data: {{ email: user.email, name: user.name, password: randomUUID() }},
+      }}));
+    users.set(user.login, doc.id);
+  }}
+
+  // Parents come before their children.
+  const terms = new Map<string, ID>();
+  for (const term of data.terms) {{
+    const doc = await payload.create({{
+      collection: collections[term.taxonomy],
+      data: {{
+        name: term.name,
+        slug: term.slug,
+        description: term.description ?? undefined,
+        parent: term.parent ? terms.get(`${{term.taxonomy}}:${{term.parent}}`) : undefined,
+      }},
+    }});
+    terms.set(`${{term.taxonomy}}:${{term.slug}}`, doc.id);
+  }}
+
+  const media = new Map<number, ID>();
+  for (const item of data.media) {{
+    const response = await fetch(item.url);
+    if (!response.ok) {{
+      payload.logger.warn(`Skipped ${{item.url}}: HTTP ${{response.status}}`);
+      continue;
+    }}
+    const buffer = Buffer.from(await response.arrayBuffer());
+    const doc = await payload.create({{
+      collection: '{MEDIA}',
+      data: {{ alt: item.alt ?? item.title }},
+      file: {{
+        data: buffer,
+        mimetype: response.headers.get('content-type') ?? 'application/octet-stream',
+        name: item.url.split('/').pop() ?? String(item.wpId),
+        size: buffer.length,
+      }},
+    }});
+    media.set(item.wpId, doc.id);
+  }}
+
+  // Parents come before their children.
+  const posts = new Map<number, ID>();
+  for (const post of data.posts) {{
+    const related = Object.fromEntries(
+      Object.entries(post.terms).map(([taxonomy, slugs]) => [
+        taxonomyFields[taxonomy],
+        slugs.map((slug) => terms.get(`${{taxonomy}}:${{slug}}`)).filter((id) => id !== undefined),
+      ]),
+    );
+    const doc = await payload.create({{
+      collection: collections[post.type],
+      data: {{
+        title: post.title,
+        slug: post.slug ?? undefined,
+        content: richText(post.content),
+        excerpt: post.excerpt ?? undefined,
+        status: post.status,
+        publishedAt: post.date ?? undefined,
+        author: post.author ? users.get(post.author) : undefined,
+        featuredImage: post.featuredImage ? media.get(post.featuredImage) : undefined,
+        parent: post.parent ? posts.get(post.parent) : undefined,
+        ...related,
+        ...fromMeta(post.type, post.meta),
+      }},
+    }});
+    posts.set(post.wpId, doc.id);
+  }}
+
+  payload.logger.info(
+    `Seeded ${{users.size}} users, ${{terms.size}} terms, ${{media.size}} media and ${{posts.size}} posts from WordPress`,
+  );
+}};
+
+export default seedWordPress;
+",
+        collections = collections.join("\n"),
+        fields = fields.join("\n"),
+    )
+}
+
+/// A Local API seed script for the content of a WXR export: authors,
+/// terms, media (downloaded from the site) and posts of every content type,
+/// with parents before children.
+pub fn wp_export_to_seed(export: &str, options: &WpSeedOptions) -> Result<WpSeed, String> {
+    let mut seed = WpSeed::default();
+    let data = read_wxr(export, &mut seed.warnings)?;
+    if data.posts.is_empty() && data.terms.is_empty() && data.media.is_empty() {
+        return Err("The export holds no posts, terms or attachments".to_string());
+    }
+    for post in &data.posts {
+        *seed.counts.entry(post.post_type.clone()).or_default() += 1;
+    }
+    for term in &data.terms {
+        *seed.counts.entry(term.taxonomy.clone()).or_default() += 1;
+    }
+    if !data.media.is_empty() {
+        seed.counts.insert("attachment".to_string(), data.media.len());
+    }
+    seed.counts.insert("authors".to_string(), data.users.len());
+    if options.payload_version.unwrap_or_default() == PayloadVersion::V3 {
+        seed.dependencies = vec!["@payloadcms/richtext-lexical".to_string(), "jsdom".to_string()];
+    }
+    seed.files.insert("src/seed/wordpress.ts".to_string(), seed_script(&data, options));
+    seed.files.insert(
+        "src/seed/wordpress.json".to_string(),
+        serde_json::to_string_pretty(&data).map_err(|err| err.to_string())?,
+    );
+    Ok(seed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn maps_post_types_taxonomies_and_acf_groups() {
+        let import = wp_to_payload_schema(&WpSchemaOptions {
+            post_types: Some(json!({
+                "post": { "slug": "post", "rest_base": "posts", "hierarchical": false, "taxonomies": ["category", "post_tag"] },
+                "book": { "slug": "book", "rest_base": "books", "hierarchical": false, "taxonomies": ["genre"] },
+                "attachment": { "slug": "attachment", "rest_base": "media" },
+            })),
+            taxonomies: Some(json!({
+                "category": { "slug": "category", "rest_base": "categories", "hierarchical": true, "types": ["post"] },
+                "post_tag": { "slug": "post_tag", "rest_base": "tags", "hierarchical": false, "types": ["post"] },
+                "genre": { "slug": "genre", "rest_base": "genres", "hierarchical": false, "types": ["book"] },
+            })),
+            field_groups: Some(json!([{
+                "title": "Book details",
+                "location": [[{ "param": "post_type", "operator": "==", "value": "book" }]],
+                "fields": [
+                    { "name": "isbn", "type": "text", "required": 1 },
+                    { "name": "format", "type": "select", "choices": { "hardcover": "Hardcover", "ebook": "E-book" } },
+                    { "name": "related_books", "type": "relationship", "post_type": ["book"] },
+                    { "name": "chapters", "type": "repeater", "sub_fields": [{ "name": "heading", "type": "text" }] },
+                    { "name": "layout", "type": "tab" },
+                ],
+            }])),
+            collections: None,
+        })
+        .unwrap();
+        let posts = import.schema.collection("posts").unwrap();
+        assert!(posts.fields.iter().any(|field| field.name == "categories" && field.has_many));
+        assert!(posts.fields.iter().all(|field| field.name != "genres"));
+        let books = import.schema.collection("books").unwrap();
+        let names: Vec<&str> = books.fields.iter().map(|field| field.name.as_str()).collect();
+        assert!(names.ends_with(&["genres", "isbn", "format", "relatedBooks", "chapters"]));
+        let related = books.fields.iter().find(|field| field.name == "relatedBooks").unwrap();
+        assert_eq!(related.relation_to, Some(vec!["books".to_string()]));
+        assert!(related.has_many);
+        assert!(import.schema.collection("categories").unwrap().fields.iter().any(|field| field.name == "parent"));
+        assert!(import.schema.collection("users").is_some_and(|users| users.auth));
+        assert!(import.schema.collection("media").is_some_and(|media| media.upload));
+        assert!(import.schema.collection("attachments").is_none());
+        assert!(import.warnings.is_empty(), "{:?}", import.warnings);
+    }
+
+    const WXR: &str = r#"<?xml version="1.0" encoding="UTF-8" ?>
+<rss version="2.0" xmlns:wp="http://wordpress.org/export/1.2/">
+<channel>
+  <title>Example</title>
+  <wp:author><wp:author_id>1</wp:author_id><wp:author_login><![CDATA[ana]]></wp:author_login><wp:author_email><![CDATA[ana@example.com]]></wp:author_email><wp:author_display_name><![CDATA[Ana]]></wp:author_display_name></wp:author>
+  <wp:category><wp:term_id>3</wp:term_id><wp:category_nicename><![CDATA[recipes]]></wp:category_nicename><wp:category_parent><![CDATA[food]]></wp:category_parent><wp:cat_name><![CDATA[Recipes]]></wp:cat_name></wp:category>
+  <wp:category><wp:term_id>2</wp:term_id><wp:category_nicename><![CDATA[food]]></wp:category_nicename><wp:category_parent><![CDATA[]]></wp:category_parent><wp:cat_name><![CDATA[Food &amp; Drink]]></wp:cat_name></wp:category>
+  <item>
+    <title><![CDATA[Cover]]></title>
+    <wp:post_id>10</wp:post_id>
+    <wp:post_type><![CDATA[attachment]]></wp:post_type>
+    <wp:status><![CDATA[inherit]]></wp:status>
+    <wp:attachment_url><![CDATA[https://example.com/wp-content/uploads/cover.jpg]]></wp:attachment_url>
+  </item>
+  <item>
+    <title><![CDATA[Soup & bread]]></title>
+    <dc:creator><![CDATA[ana]]></dc:creator>
+    <content:encoded><![CDATA[<p>Warm <title>soup</title>.</p>]]></content:encoded>
+    <excerpt:encoded><![CDATA[]]></excerpt:encoded>
+    <wp:post_id>11</wp:post_id>
+    <wp:post_date_gmt><![CDATA[2024-05-01 10:00:00]]></wp:post_date_gmt>
+    <wp:post_name><![CDATA[soup-bread]]></wp:post_name>
+    <wp:status><![CDATA[publish]]></wp:status>
+    <wp:post_parent>0</wp:post_parent>
+    <wp:post_type><![CDATA[post]]></wp:post_type>
+    <category domain="category" nicename="recipes"><![CDATA[Recipes]]></category>
+    <category domain="post_tag" nicename="winter"><![CDATA[Winter]]></category>
+    <wp:postmeta><wp:meta_key><![CDATA[_thumbnail_id]]></wp:meta_key><wp:meta_value><![CDATA[10]]></wp:meta_value></wp:postmeta>
+    <wp:postmeta><wp:meta_key><![CDATA[servings]]></wp:meta_key><wp:meta_value><![CDATA[4]]></wp:meta_value></wp:postmeta>
+  </item>
+  <item>
+    <title>Old</title>
+    <wp:post_id>12</wp:post_id>
+    <wp:status><![CDATA[trash]]></wp:status>
+    <wp:post_type><![CDATA[post]]></wp:post_type>
+  </item>
+</channel>
+</rss>"#;
+
+    #[test]
+    fn reads_wxr_exports() {
+        let mut warnings = Vec::new();
+        let data = read_wxr(WXR, &mut warnings).unwrap();
+        assert_eq!(data.users[0].email, "ana@example.com");
+        let terms: Vec<(&str, &str)> = data.terms.iter().map(|term| (term.taxonomy.as_str(), term.slug.as_str())).collect();
+        assert_eq!(terms, vec![("category", "food"), ("post_tag", "winter"), ("category", "recipes")]);
+        assert_eq!(data.terms[0].name, "Food & Drink");
+        assert_eq!(data.media[0].wp_id, 10);
+        let post = &data.posts[0];
+        assert_eq!(data.posts.len(), 1);
+        assert_eq!(post.title, "Soup & bread");
+        assert_eq!(post.content, "<p>Warm <title>soup</title>.</p>");
+        assert_eq!(post.date.as_deref(), Some("2024-05-01T10:00:00.000Z"));
+        assert_eq!((post.featured_image, post.parent, post.excerpt.as_deref()), (Some(10), None, None));
+        assert_eq!(post.terms["category"], vec!["recipes"]);
+        assert_eq!(post.meta, BTreeMap::from([("servings".to_string(), "4".to_string())]));
+        assert_eq!(warnings, vec!["Skipped 1 post (trash) items"]);
+
+        let seed = wp_export_to_seed(WXR, &WpSeedOptions::default()).unwrap();
+        let script = &seed.files["src/seed/wordpress.ts"];
+        assert!(script.contains("  'post_tag': 'tags',"));
+        assert!(script.contains("  'category': 'categories',"));
+        assert_eq!(seed.counts["post"], 1);
+    }
+}
@@ -0,0 +1,415 @@
+//! Approval workflows as a state machine on a collection: a status select
+//! field, a beforeChange hook rejecting transitions that aren't allowed or
+//! that the user's role can't make, and an optional afterChange hook
+//! emailing users of the roles to notify.
+//!
+//! Roles are read from `user.role`, as in the access-control template. Local
+//! API calls without a user skip the checks.
+
+use std::collections::{BTreeMap, BTreeSet, VecDeque};
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::payload_tools::{generator::capitalize, json_schema::schema_name, naming::Case};
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct WorkflowTransition {
+    pub from: String,
+    pub to: String,
+    /// Roles allowed to make the transition; anyone logged in when not given
+    pub roles: Option<Vec<String>>,
+    /// Roles whose users are emailed when the transition is made
+    pub notify: Option<Vec<String>>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct WorkflowOptions {
+    /// Collection the workflow applies to
+    pub collection: String,
+    /// States in order, e.g. draft, review, published
+    pub states: Vec<String>,
+    pub transitions: Vec<WorkflowTransition>,
+    /// State new documents start in (default the first state)
+    pub initial: Option<String>,
+    /// Name of the status field (default `status`)
+    pub field_name: Option<String>,
+    /// Auth collection searched for users to notify (default `users`)
+    pub users_slug: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct WorkflowConfig {
+    /// Imports for the collection file
+    pub imports: Vec<String>,
+    /// Entry for the collection's `fields` array
+    pub field: String,
+    /// `hooks` property to merge into the collection config
+    pub hooks: String,
+    /// The workflow module by path from the project root
+    pub files: BTreeMap<String, String>,
+    /// Mermaid state diagram of the workflow
+    pub diagram: String,
+    /// States that can't be reached from the initial state
+    pub warnings: Vec<String>,
+}
+
+fn is_value(value: &str) -> bool {
+    !value.is_empty()
+        && value
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
+
+fn string_list(values: &[String]) -> String {
+    values
+        .iter()
+        .map(|value| format!("'{value}'"))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+fn label(state: &str) -> String {
+    capitalize(&state.replace(['-', '_'], " "))
+}
+
+fn check(options: &WorkflowOptions) -> Result<(), String> {
+    if !is_value(&options.collection) {
+        return Err(format!("Invalid collection slug '{}'", options.collection));
+    }
+    if options.states.len() < 2 {
+        return Err("A workflow needs at least two states".to_string());
+    }
+    let mut seen = BTreeSet::new();
+    for state in &options.states {
+        if !is_value(state) {
+            return Err(format!("Invalid state '{state}'"));
+        }
+        if !seen.insert(state) {
+            return Err(format!("State '{state}' is listed twice"));
+        }
+    }
+    if let Some(initial) = &options.initial {
+        if !seen.contains(initial) {
+            return Err(format!(
+                "Initial state '{initial}' is not one of the states"
+            ));
+        }
+    }
+    if options.transitions.is_empty() {
+        return Err("At least one transition is required".to_string());
+    }
+    let mut pairs = BTreeSet::new();
+    for transition in &options.transitions {
+        for state in [&transition.from, &transition.to] {
+            if !seen.contains(state) {
+                return Err(format!("Transition uses unknown state '{state}'"));
+            }
+        }
+        if transition.from == transition.to {
+            return Err(format!("Transition from '{}' to itself", transition.from));
+        }
+        if !pairs.insert((&transition.from, &transition.to)) {
+            return Err(format!(
+                "Transition from '{}' to '{}' is listed twice",
+                transition.from, transition.to
+            ));
+        }
+        for role in transition.roles.iter().chain(&transition.notify).flatten() {
+            if !is_value(role) {
+                return Err(format!("Invalid role '{role}'"));
+            }
+        }
+    }
+    let field = options.field_name.as_deref().unwrap_or("status");
+    if field.starts_with(|c: char| c.is_ascii_digit())
+        || !field.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+    {
+        return Err(format!("Invalid field name '{field}'"));
+    }
+    if !is_value(options.users_slug.as_deref().unwrap_or("users")) {
+        return Err("Invalid users collection slug".to_string());
+    }
+    Ok(())
+}
+
+/// States no chain of transitions leads to from `initial`.
+fn warnings(options: &WorkflowOptions, initial: &str) -> Vec<String> {
+    let mut reached = BTreeSet::from([initial]);
+    let mut queue = VecDeque::from([initial]);
+    while let Some(state) = queue.pop_front() {
+        for transition in options.transitions.iter().filter(|t| t.from == state) {
+            if reached.insert(&transition.to) {
+                queue.push_back(&transition.to);
+            }
+        }
+    }
+    options
+        .states
+        .iter()
+        .filter(|state| !reached.contains(state.as_str()))
+        .map(|state| format!("State '{state}' can't be reached from '{initial}'"))
+        .collect()
+}
+
+fn diagram(options: &WorkflowOptions, initial: &str) -> String {
+    let mut out = format!("stateDiagram-v2\n  [*] --> {initial}\n");
+    for transition in &options.transitions {
+        let roles = transition.roles.as_deref().unwrap_or_default();
+        if roles.is_empty() {
+            out.push_str(&format!("  {} --> {}\n", transition.from, transition.to));
+        } else {
+            out.push_str(&format!(
+                "  {} --> {}: {}\n",
+                transition.from,
+                transition.to,
+                roles.join(", ")
+            ));
+        }
+    }
+    out
+}
+
+fn workflow_module(options: &WorkflowOptions, name: &str, initial: &str, field: &str) -> String {
+    let states = string_list(&options.states);
+    let select_options: String = options
+        .states
+        .iter()
+        .map(|state| format!("    {{ label: '{}', value: '{state}' }},\n", label(state)))
+        .collect();
+    let transitions: String = options
+        .transitions
+        .iter()
+        .map(|transition| {
+            let mut entry = format!("  {{ from: '{}', to: '{}'", transition.from, transition.to);
+            if let Some(roles) = &transition.roles {
+                entry.push_str(&format!(", roles: [{}]", string_list(roles)));
+            }
+            if let Some(notify) = transition
+                .notify
+                .as_ref()
+                .filter(|notify| !notify.is_empty())
+            {
+                entry.push_str(&format!(", notify: [{}]", string_list(notify)));
+            }
+            entry + " },\n"
+        })
+        .collect();
+    let notifies = options.transitions.iter().any(|transition| {
+        transition
+            .notify
+            .as_ref()
+            .is_some_and(|notify| !notify.is_empty())
+    });
+    let users = options.users_slug.as_deref().unwrap_or("users");
+    let hook_imports = if notifies {
+        "CollectionAfterChangeHook, CollectionBeforeChangeHook, Field"
+    } else {
+        "CollectionBeforeChangeHook, Field"
+    };
+
+    let mut code = format!(
+        "import {{ APIError }} from 'payload/errors';
+import {{ {hook_imports} }} from 'payload/types';
+
+export const {name}States = [{states}] as const;
+export type {name}State = (typeof {name}States)[number];
+
+type Transition = {{ from: {name}State; to: {name}State; roles?: string[]; notify?: string[] }};
+type WorkflowUser = {{ role?: string }} | null | undefined;
+
+export const {name}Transitions: Transition[] = [
+{transitions}];
+
+/** Whether `user` may move a document from `from` to `to`; staying put is always allowed. */
+export const canTransition{name} = (from: {name}State, to: {name}State, user: WorkflowUser): boolean =>
+  from === to ||
+  {name}Transitions.some(
+    (transition) =>
+      transition.from === from &&
+      transition.to === to &&
+      (!transition.roles || (user?.role !== undefined && transition.roles.includes(user.role))),
+  );
+
+export const {name}StatusField: Field = {{
+  name: '{field}',
+  type: 'select',
+  required: true,
+  index: true,
+  defaultValue: '{initial}',
+  options: [
+{select_options}  ],
+  admin: {{
+    position: 'sidebar',
+  }},
+}};
+
+/** Rejects transitions that aren't in the workflow or that the user's role can't make. */
+export const validate{name}Transition: CollectionBeforeChangeHook = ({{ data, originalDoc, operation, req }}) => {{
+  if (req.payloadAPI === 'local' && !req.user) {{
+    return data;
+  }}
+  if (operation === 'create') {{
+    if (data.{field} !== undefined && data.{field} !== '{initial}') {{
+      throw new APIError(`New documents start as {initial}`, 400);
+    }}
+    return data;
+  }}
+  const from = (originalDoc?.{field} ?? '{initial}') as {name}State;
+  const to = (data.{field} ?? from) as {name}State;
+  if (!canTransition{name}(from, to, req.user)) {{
+    throw new APIError(`Moving from ${{from}} to ${{to}} is not allowed`, 403);
+  }}
+  return data;
+}};
+"
+    );
+    if notifies {
+        code.push_str(&format!(
+            "
+/** Emails users of the transition's `notify` roles after a status change. */
+export const notify{name}Transition: CollectionAfterChangeHook = async ({{ collection, doc, previousDoc, operation, req }}) => {{
+  if (operation !== 'update' || previousDoc?.{field} === doc.{field}) {{
+    return doc;
+  }}
+  const transition = {name}Transitions.find(
+    (candidate) => candidate.from === previousDoc?.{field} && candidate.to === doc.{field},
+  );
+  if (!transition?.notify?.length) {{
+    return doc;
+  }}
+  const recipients = await req.payload.find({{
+    collection: '{users}',
+    where: {{ role: {{ in: transition.notify }} }},
+    depth: 0,
+    limit: 100,
+    req,
+  }});
+  const title = doc.title ?? doc.id;
+  await Promise.all(
+    recipients.docs
+      .filter((user) => user.email)
+      .map((user) =>
+        req.payload
+          .sendEmail({{
+            to: user.email,
+            subject: `${{collection.slug}} \"${{title}}\" moved to ${{doc.{field}}}`,
+            html: `<p>\"${{title}}\" moved from <b>${{transition.from}}</b> to <b>${{transition.to}}</b>.</p>`,
+          }})
+          .catch((error) => req.payload.logger.error(`Could not send workflow email: ${{error}}`)),
+      ),
+  );
+  return doc;
+}};
+"
+        ));
+    }
+    code
+}
+
+/// Status field, hooks and module for the workflow on `options.collection`.
+pub fn workflow_config(options: &WorkflowOptions) -> Result<WorkflowConfig, String> {
+    check(options)?;
+    let initial = options
+        .initial
+        .as_deref()
+        .unwrap_or(options.states[0].as_str());
+    let field = options.field_name.as_deref().unwrap_or("status");
+    let name = schema_name(&options.collection);
+    let module = Case::Camel.convert(&options.collection);
+    let notifies = options.transitions.iter().any(|transition| {
+        transition
+            .notify
+            .as_ref()
+            .is_some_and(|notify| !notify.is_empty())
+    });
+
+    let mut exported = vec![
+        format!("{name}StatusField"),
+        format!("validate{name}Transition"),
+    ];
+    let mut hooks = format!("hooks: {{\n    beforeChange: [validate{name}Transition],");
+    if notifies {
+        exported.push(format!("notify{name}Transition"));
+        hooks.push_str(&format!("\n    afterChange: [notify{name}Transition],"));
+    }
+    hooks.push_str("\n  },");
+
+    let files = BTreeMap::from([(
+        format!("src/workflows/{module}.ts"),
+        workflow_module(options, &name, initial, field),
+    )]);
+    Ok(WorkflowConfig {
+        imports: vec![format!(
+            "import {{ {} }} from '../workflows/{module}';",
+            exported.join(", ")
+        )],
+        field: format!("{name}StatusField"),
+        hooks,
+        files,
+        diagram: diagram(options, initial),
+        warnings: warnings(options, initial),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn transition(from: &str, to: &str, roles: &[&str]) -> WorkflowTransition {
+        WorkflowTransition {
+            from: from.to_string(),
+            to: to.to_string(),
+            roles: (!roles.is_empty()).then(|| roles.iter().map(|role| role.to_string()).collect()),
+            notify: None,
+        }
+    }
+
+    #[test]
+    fn test_workflow_config() {
+        let mut options = WorkflowOptions {
+            collection: "blog-posts".to_string(),
+            states: ["draft", "review", "published", "archived"]
+                .map(String::from)
+                .to_vec(),
+            transitions: vec![
+                transition("draft", "review", &[]),
+                transition("review", "draft", &["editor"]),
+                transition("review", "published", &["editor", "admin"]),
+            ],
+            ..Default::default()
+        };
+        options.transitions[2].notify = Some(vec!["author".to_string()]);
+
+        let config = workflow_config(&options).unwrap();
+        assert_eq!(config.field, "BlogPostsStatusField");
+        assert_eq!(
+            config.imports,
+            vec![
+                "import { BlogPostsStatusField, validateBlogPostsTransition, notifyBlogPostsTransition } from '../workflows/blogPosts';"
+            ]
+        );
+        assert!(
+            config
+                .hooks
+                .contains("afterChange: [notifyBlogPostsTransition]")
+        );
+        let module = &config.files["src/workflows/blogPosts.ts"];
+        assert!(module.contains("  { from: 'review', to: 'published', roles: ['editor', 'admin'], notify: ['author'] },\n"));
+        assert!(module.contains("defaultValue: 'draft',"));
+        assert!(
+            config
+                .diagram
+                .contains("  review --> published: editor, admin\n")
+        );
+        assert_eq!(
+            config.warnings,
+            vec!["State 'archived' can't be reached from 'draft'"]
+        );
+
+        options.transitions.push(transition("draft", "gone", &[]));
+        assert!(workflow_config(&options).is_err());
+    }
+}
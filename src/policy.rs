@@ -0,0 +1,75 @@
+//! Which tools each transport may list and call.
+
+use std::{collections::BTreeMap, str::FromStr};
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// Tool rules keyed by transport name (`stdio`, `tcp`, `unix`, `pipe`,
+/// `http`, `sse`, `ws`). The `*` entry applies to transports without their
+/// own entry; with no matching entry every tool is available.
+///
+/// ```json
+/// { "http": { "deny": ["scaffold_project"] }, "ws": { "allow": ["validate", "query"] } }
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(transparent)]
+pub struct ToolPolicy(BTreeMap<String, ToolRule>);
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct ToolRule {
+    /// Only these tools are available; all tools when omitted
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub allow: Option<Vec<String>>,
+    /// Tools that are never available, applied after `allow`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub deny: Vec<String>,
+}
+
+impl ToolPolicy {
+    pub fn allows(&self, transport: &str, tool: &str) -> bool {
+        let Some(rule) = self.0.get(transport).or_else(|| self.0.get("*")) else {
+            return true;
+        };
+        let allowed = rule
+            .allow
+            .as_ref()
+            .is_none_or(|allow| allow.iter().any(|name| name == tool));
+        allowed && !rule.deny.iter().any(|name| name == tool)
+    }
+}
+
+impl FromStr for ToolPolicy {
+    type Err = String;
+
+    /// Parse the JSON form used by `MCP_TOOL_POLICY`.
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        if value.trim().is_empty() {
+            return Ok(Self::default());
+        }
+        serde_json::from_str(value).map_err(|e| format!("Invalid tool policy: {e}"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tool_policy() {
+        let policy: ToolPolicy = r#"{
+            "http": { "deny": ["scaffold_project"] },
+            "*": { "allow": ["validate", "scaffold_project"], "deny": ["scaffold_project"] }
+        }"#
+        .parse()
+        .unwrap();
+        assert!(policy.allows("http", "validate"));
+        assert!(policy.allows("http", "query"));
+        assert!(!policy.allows("http", "scaffold_project"));
+        assert!(policy.allows("ws", "validate"));
+        assert!(!policy.allows("ws", "query"));
+        assert!(!policy.allows("ws", "scaffold_project"));
+        assert!(ToolPolicy::default().allows("stdio", "scaffold_project"));
+    }
+}
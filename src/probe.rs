@@ -0,0 +1,157 @@
+//! Live reachability checks for the endpoints in a runtime info file, used by
+//! the `status` command.
+
+use std::{
+    io::{BufRead, BufReader, Read, Write},
+    net::{SocketAddr, TcpStream},
+    time::{Duration, Instant},
+};
+
+use crate::server::Endpoint;
+
+#[derive(Debug, Clone)]
+pub struct ProbeResult {
+    pub reachable: bool,
+    pub detail: String,
+}
+
+impl ProbeResult {
+    fn ok(detail: impl Into<String>, started: Instant) -> Self {
+        Self {
+            reachable: true,
+            detail: format!("{} ({} ms)", detail.into(), started.elapsed().as_millis()),
+        }
+    }
+
+    fn failed(detail: impl Into<String>) -> Self {
+        Self {
+            reachable: false,
+            detail: detail.into(),
+        }
+    }
+}
+
+/// Probe one endpoint: `GET /healthz` for streamable HTTP, an MCP
+/// `initialize` round trip for TCP and Unix sockets, and a TCP connect for
+/// SSE and websockets. stdio and named pipes are not probed and return
+/// `None`.
+pub fn probe(endpoint: &Endpoint, timeout: Duration) -> Option<ProbeResult> {
+    let result = match endpoint.transport.as_str() {
+        "http" => with_addr(&endpoint.address, |addr| probe_http(addr, timeout)),
+        "tcp" => with_addr(&endpoint.address, |addr| {
+            let started = Instant::now();
+            TcpStream::connect_timeout(&addr, timeout)
+                .map_err(|e| format!("connect failed: {e}"))
+                .and_then(|stream| mcp_initialize(stream, timeout))
+                .map_or_else(ProbeResult::failed, |detail| ProbeResult::ok(detail, started))
+        }),
+        #[cfg(unix)]
+        "unix" => probe_unix(&endpoint.address, timeout),
+        "sse" | "ws" => with_addr(&endpoint.address, |addr| {
+            let started = Instant::now();
+            match TcpStream::connect_timeout(&addr, timeout) {
+                Ok(_) => ProbeResult::ok("accepting connections", started),
+                Err(err) => ProbeResult::failed(format!("connect failed: {err}")),
+            }
+        }),
+        _ => return None,
+    };
+    Some(result)
+}
+
+/// Parse a bound address, mapping wildcard binds to loopback.
+fn with_addr(address: &str, probe: impl FnOnce(SocketAddr) -> ProbeResult) -> ProbeResult {
+    match address.parse::<SocketAddr>() {
+        Ok(mut addr) => {
+            if addr.ip().is_unspecified() {
+                addr.set_ip(if addr.is_ipv4() {
+                    [127, 0, 0, 1].into()
+                } else {
+                    std::net::Ipv6Addr::LOCALHOST.into()
+                });
+            }
+            probe(addr)
+        }
+        Err(err) => ProbeResult::failed(format!("invalid address '{address}': {err}")),
+    }
+}
+
+fn probe_http(addr: SocketAddr, timeout: Duration) -> ProbeResult {
+    let started = Instant::now();
+    let agent = ureq::AgentBuilder::new().timeout(timeout).build();
+    match agent.get(&format!("http://{addr}/healthz")).call() {
+        Ok(response) => ProbeResult::ok(format!("healthz {}", response.status()), started),
+        Err(ureq::Error::Status(code, _)) => ProbeResult::failed(format!("healthz returned {code}")),
+        Err(err) => ProbeResult::failed(format!("request failed: {err}")),
+    }
+}
+
+#[cfg(unix)]
+fn probe_unix(path: &str, timeout: Duration) -> ProbeResult {
+    let started = Instant::now();
+    std::os::unix::net::UnixStream::connect(path)
+        .map_err(|e| format!("connect failed: {e}"))
+        .and_then(|stream| mcp_initialize(stream, timeout))
+        .map_or_else(ProbeResult::failed, |detail| ProbeResult::ok(detail, started))
+}
+
+trait SetTimeouts {
+    fn set_timeouts(&self, timeout: Duration) -> std::io::Result<()>;
+}
+
+impl SetTimeouts for TcpStream {
+    fn set_timeouts(&self, timeout: Duration) -> std::io::Result<()> {
+        self.set_read_timeout(Some(timeout))?;
+        self.set_write_timeout(Some(timeout))
+    }
+}
+
+#[cfg(unix)]
+impl SetTimeouts for std::os::unix::net::UnixStream {
+    fn set_timeouts(&self, timeout: Duration) -> std::io::Result<()> {
+        self.set_read_timeout(Some(timeout))?;
+        self.set_write_timeout(Some(timeout))
+    }
+}
+
+/// Send a newline-delimited MCP `initialize` request and return the server
+/// name and version from the response.
+fn mcp_initialize<S: Read + Write + SetTimeouts>(
+    mut stream: S,
+    timeout: Duration,
+) -> Result<String, String> {
+    stream.set_timeouts(timeout).map_err(|e| e.to_string())?;
+    let request = serde_json::json!({
+        "jsonrpc": "2.0",
+        "id": 1,
+        "method": "initialize",
+        "params": {
+            "protocolVersion": rmcp::model::ProtocolVersion::default(),
+            "capabilities": {},
+            "clientInfo": {
+                "name": format!("{}-status", crate::metadata::PKG_NAME),
+                "version": crate::metadata::PKG_VERSION,
+            },
+        },
+    });
+    writeln!(stream, "{request}").map_err(|e| format!("write failed: {e}"))?;
+    stream.flush().map_err(|e| format!("write failed: {e}"))?;
+
+    let mut line = String::new();
+    BufReader::new(&mut stream)
+        .read_line(&mut line)
+        .map_err(|e| format!("no initialize response: {e}"))?;
+    let response: serde_json::Value =
+        serde_json::from_str(&line).map_err(|e| format!("invalid initialize response: {e}"))?;
+    if let Some(error) = response.get("error") {
+        return Err(format!("initialize failed: {error}"));
+    }
+    let info = &response["result"]["serverInfo"];
+    Ok(format!(
+        "initialize ok: {} {}",
+        info["name"].as_str().unwrap_or("unknown"),
+        info["version"].as_str().unwrap_or("")
+    )
+    .trim_end()
+    .to_string())
+}
@@ -0,0 +1,342 @@
+//! Dev servers of scaffolded projects, run as managed child processes:
+//! `pnpm install`, then `pnpm dev`.
+//!
+//! Like the server's own pid file, each project records its dev server's pid
+//! in `.mcp-dev.pid`, so a second run is refused while it is alive and a
+//! later server can still stop it. Output lines are logged through `tracing`
+//! and so reach MCP clients that set a log level.
+
+use std::{
+    collections::{HashMap, VecDeque},
+    fs,
+    path::{Path, PathBuf},
+    process::Stdio,
+    sync::{Arc, Mutex, OnceLock},
+    time::Duration,
+};
+
+use regex::Regex;
+use schemars::JsonSchema;
+use serde::Serialize;
+use sysinfo::{Pid, ProcessesToUpdate, System};
+use tokio::{
+    io::{AsyncBufReadExt, AsyncRead, BufReader},
+    process::{Child, Command},
+    sync::watch,
+};
+
+/// File in the project recording the dev server's pid.
+pub const PID_FILE: &str = ".mcp-dev.pid";
+const DEFAULT_TIMEOUT_SECS: u64 = 120;
+/// Output lines kept per project for results and errors.
+const LOG_TAIL: usize = 40;
+
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ProjectStatus {
+    pub path: String,
+    pub pid: u32,
+    /// Where the dev server listens, once it printed it
+    pub url: Option<String>,
+    pub admin_url: Option<String>,
+    /// The dev server reported ready within the timeout
+    pub ready: bool,
+    /// Last lines of output
+    pub log_tail: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct StoppedProject {
+    pub path: String,
+    /// Pid of the dev server stopped, if one was running
+    pub pid: Option<u32>,
+    pub stopped: bool,
+}
+
+/// A dev server started by this process.
+struct Running {
+    child: Child,
+    pid: u32,
+    log: Arc<Mutex<VecDeque<String>>>,
+    ready: watch::Receiver<Option<String>>,
+}
+
+fn running() -> &'static Mutex<HashMap<PathBuf, Running>> {
+    static RUNNING: OnceLock<Mutex<HashMap<PathBuf, Running>>> = OnceLock::new();
+    RUNNING.get_or_init(Default::default)
+}
+
+fn ansi() -> &'static Regex {
+    static ANSI: OnceLock<Regex> = OnceLock::new();
+    ANSI.get_or_init(|| Regex::new(r"\x1b\[[0-9;?]*[A-Za-z]").expect("valid regex"))
+}
+
+fn local_url() -> &'static Regex {
+    static URL: OnceLock<Regex> = OnceLock::new();
+    URL.get_or_init(|| {
+        Regex::new(r"https?://(?:localhost|127\.0\.0\.1|0\.0\.0\.0|\[::1?\]):\d+").expect("valid regex")
+    })
+}
+
+/// The local URL in a line of dev server output, and whether the line says
+/// the server is ready (`✓ Ready in 2.1s` from Next.js, `Server listening`
+/// and the like from Express).
+fn scan_line(line: &str) -> (Option<String>, bool) {
+    let url = local_url()
+        .find(line)
+        .map(|found| found.as_str().replace("0.0.0.0", "localhost").replace("[::]", "localhost"));
+    let lower = line.to_lowercase();
+    let ready = lower.contains("ready in") || lower.contains("ready on") || lower.contains("listening");
+    (url, ready)
+}
+
+/// Pid recorded in the project's pid file, if that process is still running.
+fn live_pid(dir: &Path) -> Option<u32> {
+    let pid: u32 = fs::read_to_string(dir.join(PID_FILE)).ok()?.trim().parse().ok()?;
+    let mut sys = System::new();
+    sys.refresh_processes(ProcessesToUpdate::Some(&[Pid::from_u32(pid)]), true);
+    sys.process(Pid::from_u32(pid)).map(|_| pid)
+}
+
+/// Terminate `pid` and its descendants; `pnpm dev` runs the framework's dev
+/// server as a child that would otherwise keep the port.
+fn kill_tree(pid: u32) {
+    let mut sys = System::new();
+    sys.refresh_processes(ProcessesToUpdate::All, true);
+    let mut tree = vec![Pid::from_u32(pid)];
+    let mut index = 0;
+    while index < tree.len() {
+        let parent = tree[index];
+        tree.extend(
+            sys.processes()
+                .iter()
+                .filter(|(_, process)| process.parent() == Some(parent))
+                .map(|(pid, _)| *pid),
+        );
+        index += 1;
+    }
+    for pid in tree.into_iter().rev() {
+        if let Some(process) = sys.process(pid) {
+            if process.kill_with(sysinfo::Signal::Term).is_none() {
+                process.kill();
+            }
+        }
+    }
+}
+
+/// Log each line of `output` and keep the last [`LOG_TAIL`], reporting the
+/// URL on `ready` once the server says it is up.
+fn forward<R: AsyncRead + Unpin + Send + 'static>(
+    name: String,
+    output: R,
+    log: Arc<Mutex<VecDeque<String>>>,
+    ready: Arc<watch::Sender<Option<String>>>,
+) {
+    tokio::spawn(async move {
+        let mut lines = BufReader::new(output).lines();
+        let mut url = None;
+        while let Ok(Some(line)) = lines.next_line().await {
+            let line = ansi().replace_all(&line, "").trim_end().to_string();
+            if line.is_empty() {
+                continue;
+            }
+            tracing::info!("[{name}] {line}");
+            let (found, is_ready) = scan_line(&line);
+            url = found.or(url);
+            if is_ready && ready.borrow().is_none() {
+                let _ = ready.send(Some(url.clone().unwrap_or_default()));
+            }
+            if let Ok(mut log) = log.lock() {
+                if log.len() == LOG_TAIL {
+                    log.pop_front();
+                }
+                log.push_back(line);
+            }
+        }
+    });
+}
+
+fn tail(log: &Mutex<VecDeque<String>>) -> Vec<String> {
+    log.lock().map(|log| log.iter().cloned().collect()).unwrap_or_default()
+}
+
+fn project_status(dir: &Path, running: &Running, ready: bool) -> ProjectStatus {
+    let url = running.ready.borrow().clone().filter(|url| !url.is_empty());
+    ProjectStatus {
+        path: dir.display().to_string(),
+        pid: running.pid,
+        admin_url: url.as_ref().map(|url| format!("{url}/admin")),
+        url,
+        ready,
+        log_tail: tail(&running.log),
+    }
+}
+
+/// Run `pnpm install` in `dir`, logging its output.
+async fn install(dir: &Path) -> Result<(), String> {
+    let log = Arc::new(Mutex::new(VecDeque::new()));
+    let (ready, _) = watch::channel(None);
+    let ready = Arc::new(ready);
+    let mut child = Command::new("pnpm")
+        .arg("install")
+        .current_dir(dir)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true)
+        .spawn()
+        .map_err(|err| format!("Could not run pnpm install: {err}"))?;
+    if let (Some(stdout), Some(stderr)) = (child.stdout.take(), child.stderr.take()) {
+        forward("pnpm install".to_string(), stdout, log.clone(), ready.clone());
+        forward("pnpm install".to_string(), stderr, log.clone(), ready);
+    }
+    let exit = child.wait().await.map_err(|err| format!("pnpm install failed: {err}"))?;
+    if !exit.success() {
+        return Err(format!("pnpm install failed ({exit}):\n{}", tail(&log).join("\n")));
+    }
+    Ok(())
+}
+
+/// Install the project at `path` unless `skip_install`, start `pnpm dev` and
+/// wait up to `timeout` seconds (default 120) for it to report ready.
+pub async fn run_project(
+    path: &str,
+    skip_install: bool,
+    port: Option<u16>,
+    timeout: Option<u64>,
+) -> Result<ProjectStatus, String> {
+    let dir = fs::canonicalize(path).map_err(|err| format!("Could not open project {path}: {err}"))?;
+    if !dir.join("package.json").is_file() {
+        return Err(format!("{path} has no package.json; scaffold the project first"));
+    }
+    {
+        let mut projects = running().lock().map_err(|_| "Project registry poisoned".to_string())?;
+        if let Some(project) = projects.get_mut(&dir) {
+            if matches!(project.child.try_wait(), Ok(None)) {
+                let ready = project.ready.borrow().is_some();
+                return Ok(project_status(&dir, project, ready));
+            }
+            projects.remove(&dir);
+        }
+    }
+    if let Some(pid) = live_pid(&dir) {
+        return Err(format!("A dev server is already running for {path} (pid {pid}); stop_project first"));
+    }
+
+    if !skip_install {
+        install(&dir).await?;
+    }
+
+    let mut command = Command::new("pnpm");
+    command
+        .arg("dev")
+        .current_dir(&dir)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true);
+    if let Some(port) = port {
+        command.env("PORT", port.to_string());
+    }
+    let mut child = command.spawn().map_err(|err| format!("Could not run pnpm dev: {err}"))?;
+    let pid = child.id().ok_or("pnpm dev exited immediately")?;
+    fs::write(dir.join(PID_FILE), pid.to_string())
+        .map_err(|err| format!("Could not write {PID_FILE}: {err}"))?;
+
+    let log = Arc::new(Mutex::new(VecDeque::new()));
+    let (sender, mut ready) = watch::channel(None);
+    let sender = Arc::new(sender);
+    if let (Some(stdout), Some(stderr)) = (child.stdout.take(), child.stderr.take()) {
+        forward("pnpm dev".to_string(), stdout, log.clone(), sender.clone());
+        forward("pnpm dev".to_string(), stderr, log.clone(), sender);
+    }
+    tracing::info!("Started pnpm dev for {} (pid {pid})", dir.display());
+
+    let wait = Duration::from_secs(timeout.unwrap_or(DEFAULT_TIMEOUT_SECS));
+    let became_ready = tokio::select! {
+        ready = tokio::time::timeout(wait, ready.wait_for(Option::is_some)) => matches!(ready, Ok(Ok(_))),
+        exit = child.wait() => {
+            let _ = fs::remove_file(dir.join(PID_FILE));
+            let exit = exit.map(|exit| exit.to_string()).unwrap_or_else(|err| err.to_string());
+            return Err(format!("pnpm dev exited ({exit}):\n{}", tail(&log).join("\n")));
+        }
+    };
+
+    let mut project = Running {
+        child,
+        pid,
+        log,
+        ready,
+    };
+    let mut status = project_status(&dir, &project, became_ready);
+    if status.url.is_none() {
+        if let Some(port) = port {
+            status.url = Some(format!("http://localhost:{port}"));
+            status.admin_url = Some(format!("http://localhost:{port}/admin"));
+        }
+    }
+    // The registry owns the child from here; dropping it stops the server.
+    if let Ok(mut projects) = running().lock() {
+        projects.insert(dir, project);
+    } else {
+        let _ = project.child.start_kill();
+    }
+    Ok(status)
+}
+
+/// Stop the dev server of the project at `path`, whether this process
+/// started it or an earlier one recorded it in the pid file.
+pub async fn stop_project(path: &str) -> Result<StoppedProject, String> {
+    let dir = fs::canonicalize(path).map_err(|err| format!("Could not open project {path}: {err}"))?;
+    let started = running()
+        .lock()
+        .map_err(|_| "Project registry poisoned".to_string())?
+        .remove(&dir);
+    let pid = match &started {
+        Some(project) => Some(project.pid),
+        None => live_pid(&dir),
+    };
+    if let Some(pid) = pid {
+        kill_tree(pid);
+    }
+    if let Some(mut project) = started {
+        let _ = project.child.kill().await;
+    }
+    let _ = fs::remove_file(dir.join(PID_FILE));
+    if let Some(pid) = pid {
+        tracing::info!("Stopped the dev server of {} (pid {pid})", dir.display());
+    }
+    Ok(StoppedProject {
+        path: dir.display().to_string(),
+        pid,
+        stopped: pid.is_some(),
+    })
+}
+
+/// Stop every dev server this process started; the registry is never
+/// dropped, so its children would otherwise outlive the server.
+pub async fn stop_all() {
+    let projects: Vec<PathBuf> = match running().lock() {
+        Ok(projects) => projects.keys().cloned().collect(),
+        Err(_) => return,
+    };
+    for dir in projects {
+        let _ = stop_project(&dir.to_string_lossy()).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_scan_line() {
+        let line = ansi().replace_all("   \x1b[1m- Local:\x1b[22m        http://localhost:3000", "");
+        assert_eq!(scan_line(&line), (Some("http://localhost:3000".to_string()), false));
+        assert_eq!(scan_line(" ✓ Ready in 2.4s"), (None, true));
+        assert_eq!(
+            scan_line("Server listening on http://0.0.0.0:4000"),
+            (Some("http://localhost:4000".to_string()), true)
+        );
+    }
+}
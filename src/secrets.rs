@@ -0,0 +1,177 @@
+//! `${...}` references in settings and connection strings: `${NAME}` and
+//! `${NAME:-default}` read environment variables, `${secret:NAME}` reads the
+//! OS keyring, and `$${` is a literal `${`.
+
+use serde_json::Value;
+
+/// Keyring service under which secrets are stored.
+#[cfg(feature = "keyring")]
+const KEYRING_SERVICE: &str = "mcp-payloadcms";
+
+/// Expand every reference in `input`. Fails on an unset variable without a
+/// default, a missing secret, or an unterminated `${`.
+pub fn expand(input: &str) -> Result<String, String> {
+    expand_with(input, |_| true)
+}
+
+/// Expand a value that arrived in a tool call. Only `PAYLOAD_*` variables and
+/// secrets may be referenced, so a client cannot read arbitrary environment
+/// variables by sending them to a host it controls.
+pub fn expand_argument(input: &str) -> Result<String, String> {
+    expand_with(input, |name| name.starts_with("PAYLOAD_"))
+}
+
+fn expand_with(input: &str, env_allowed: impl Fn(&str) -> bool) -> Result<String, String> {
+    let mut out = String::with_capacity(input.len());
+    let mut rest = input;
+    while let Some(start) = rest.find('$') {
+        out.push_str(&rest[..start]);
+        let tail = &rest[start..];
+        if let Some(escaped) = tail.strip_prefix("$${") {
+            out.push_str("${");
+            rest = escaped;
+        } else if let Some(body) = tail.strip_prefix("${") {
+            let end = body
+                .find('}')
+                .ok_or_else(|| format!("Unterminated reference in '{input}'"))?;
+            out.push_str(&resolve(&body[..end], &env_allowed)?);
+            rest = &body[end + 1..];
+        } else {
+            out.push('$');
+            rest = &tail[1..];
+        }
+    }
+    out.push_str(rest);
+    Ok(out)
+}
+
+fn resolve(reference: &str, env_allowed: &impl Fn(&str) -> bool) -> Result<String, String> {
+    if let Some(name) = reference.strip_prefix("secret:") {
+        return get(name)?.ok_or_else(|| format!("Secret '{name}' is not in the keyring"));
+    }
+    let (name, default) = match reference.split_once(":-") {
+        Some((name, default)) => (name, Some(default)),
+        None => (reference, None),
+    };
+    if !env_allowed(name) {
+        return Err(format!("Environment variable {name} may not be referenced here"));
+    }
+    match std::env::var(name) {
+        Ok(value) if !value.is_empty() => Ok(value),
+        _ => default
+            .map(str::to_string)
+            .ok_or_else(|| format!("Environment variable {name} is not set")),
+    }
+}
+
+/// Expand references in every string inside a JSON value.
+pub fn expand_json(value: &mut Value) -> Result<(), String> {
+    match value {
+        Value::String(text) if text.contains('$') => *text = expand(text)?,
+        Value::Array(items) => items.iter_mut().try_for_each(expand_json)?,
+        Value::Object(map) => map.values_mut().try_for_each(expand_json)?,
+        _ => {}
+    }
+    Ok(())
+}
+
+/// Put the references from `raw` back into `expanded` wherever the value
+/// has not changed, so saving settings never writes resolved secrets or
+/// environment values to disk.
+pub fn preserve_references(raw: &Value, expanded: &mut Value) {
+    match (raw, expanded) {
+        (Value::String(reference), value) if reference.contains("${") => {
+            if expand(reference).is_ok_and(|resolved| Value::String(resolved) == *value) {
+                *value = Value::String(reference.clone());
+            }
+        }
+        (Value::Array(raw_items), Value::Array(items)) if raw_items.len() == items.len() => {
+            for (raw_item, item) in raw_items.iter().zip(items) {
+                preserve_references(raw_item, item);
+            }
+        }
+        (Value::Object(raw_map), Value::Object(map)) => {
+            for (key, value) in map.iter_mut() {
+                if let Some(raw_value) = raw_map.get(key) {
+                    preserve_references(raw_value, value);
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Read a secret from the OS keyring; `None` when it is not stored.
+#[cfg(feature = "keyring")]
+pub fn get(name: &str) -> Result<Option<String>, String> {
+    let entry = keyring::Entry::new(KEYRING_SERVICE, name).map_err(|e| e.to_string())?;
+    match entry.get_password() {
+        Ok(secret) => Ok(Some(secret)),
+        Err(keyring::Error::NoEntry) => Ok(None),
+        Err(err) => Err(format!("Could not read secret '{name}': {err}")),
+    }
+}
+
+/// Store a secret in the OS keyring, replacing any previous value.
+#[cfg(feature = "keyring")]
+pub fn set(name: &str, secret: &str) -> Result<(), String> {
+    keyring::Entry::new(KEYRING_SERVICE, name)
+        .and_then(|entry| entry.set_password(secret))
+        .map_err(|e| format!("Could not store secret '{name}': {e}"))
+}
+
+/// Remove a secret from the OS keyring; `false` when it was not stored.
+#[cfg(feature = "keyring")]
+pub fn delete(name: &str) -> Result<bool, String> {
+    let entry = keyring::Entry::new(KEYRING_SERVICE, name).map_err(|e| e.to_string())?;
+    match entry.delete_credential() {
+        Ok(()) => Ok(true),
+        Err(keyring::Error::NoEntry) => Ok(false),
+        Err(err) => Err(format!("Could not delete secret '{name}': {err}")),
+    }
+}
+
+#[cfg(not(feature = "keyring"))]
+const NO_KEYRING: &str = "This build has no keyring support; rebuild with `--features keyring`";
+
+#[cfg(not(feature = "keyring"))]
+pub fn get(_name: &str) -> Result<Option<String>, String> {
+    Err(NO_KEYRING.to_string())
+}
+
+#[cfg(not(feature = "keyring"))]
+pub fn set(_name: &str, _secret: &str) -> Result<(), String> {
+    Err(NO_KEYRING.to_string())
+}
+
+#[cfg(not(feature = "keyring"))]
+pub fn delete(_name: &str) -> Result<bool, String> {
+    Err(NO_KEYRING.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_expand_env_references() {
+        // SAFETY: no other test reads or writes this variable.
+        unsafe { std::env::set_var("MCP_SECRETS_TEST_HOST", "cms.example.com") };
+        assert_eq!(
+            expand("https://${MCP_SECRETS_TEST_HOST}/api").unwrap(),
+            "https://cms.example.com/api"
+        );
+        assert_eq!(expand("${MCP_SECRETS_TEST_UNSET:-3000}").unwrap(), "3000");
+        assert_eq!(expand("cost: $5, literal $${HOME}").unwrap(), "cost: $5, literal ${HOME}");
+        assert!(expand("${MCP_SECRETS_TEST_UNSET}").is_err());
+        assert!(expand("${MCP_SECRETS_TEST_HOST").is_err());
+        assert!(expand_argument("${MCP_SECRETS_TEST_HOST}").is_err());
+
+        let raw = serde_json::json!({ "http_addr": "${MCP_SECRETS_TEST_HOST}", "name": "x" });
+        let mut expanded = raw.clone();
+        expand_json(&mut expanded).unwrap();
+        assert_eq!(expanded["http_addr"], "cms.example.com");
+        preserve_references(&raw, &mut expanded);
+        assert_eq!(expanded, raw);
+    }
+}
@@ -1,10 +1,25 @@
 use std::{
+    collections::{HashMap, VecDeque},
+    convert::Infallible,
     fs,
+    future::Future,
     net::SocketAddr,
-    sync::Arc,
-    time::{Duration, SystemTime},
+    path::Path,
+    sync::{
+        Arc, Mutex, OnceLock,
+        atomic::{AtomicU64, Ordering},
+    },
+    time::{Duration, Instant, SystemTime},
 };
 
+use bytes::Bytes;
+use http_body_util::{BodyExt, Full, combinators::BoxBody};
+use hyper::{
+    Method, Request, Response, StatusCode,
+    body::Incoming,
+    header::{self, CONTENT_TYPE, HeaderValue},
+    service::Service as _,
+};
 use hyper_util::{
     rt::{TokioExecutor, TokioIo},
     server::conn::auto::Builder as HyperBuilder,
@@ -12,9 +27,13 @@ use hyper_util::{
 };
 use pin_project_lite::pin_project;
 use rmcp::{
-    ServiceExt,
+    ErrorData, RoleServer, Service, ServiceExt,
     handler::server::router::Router,
-    model::{Implementation, InitializeResult, ProtocolVersion, ServerCapabilities},
+    model::{
+        ClientNotification, ClientRequest, ErrorCode, Implementation, InitializeResult,
+        ProtocolVersion, ServerCapabilities, ServerInfo, ServerResult,
+    },
+    service::{NotificationContext, RequestContext},
     transport::{
         sse_server::{SseServer, SseServerConfig},
         streamable_http_server::{
@@ -25,17 +44,31 @@ use rmcp::{
 use serde::{Deserialize, Serialize};
 #[cfg(unix)]
 use tokio::net::UnixListener;
-use tokio::{net::TcpListener, task::JoinSet};
+use tokio::{
+    net::TcpListener,
+    sync::{OwnedSemaphorePermit, Semaphore},
+    task::JoinSet,
+};
 use tokio_tungstenite::tungstenite;
-use tokio_util::sync::CancellationToken;
+use tokio_util::{sync::CancellationToken, task::TaskTracker};
 
-use crate::{cli::CommandArguments, error::{ServiceError, ServiceResult}, handler::ToolBoxHandler};
+use crate::{
+    cli::CommandArguments,
+    config::{Config, Setting},
+    error::{ServiceError, ServiceResult},
+    handler::ToolBoxHandler,
+    instance::{self, InstanceLock},
+    metrics,
+    payload_tools::{scaffolder::ScaffoldOptions, storage::Storage},
+    policy::ToolPolicy,
+};
 
 #[derive(Clone)]
 pub struct TransportState {
     pub stdio: bool,
     pub tcp: Option<SocketAddr>,
     pub unix_path: Option<String>,
+    pub pipe_name: Option<String>,
     pub http: Option<SocketAddr>,
     pub sse: Option<SocketAddr>,
     pub ws: Option<SocketAddr>,
@@ -76,6 +109,7 @@ impl TransportState {
             stdio: args.enable_stdio,
             tcp,
             unix_path: args.enable_unix.then_some(args.unix_path.clone()),
+            pipe_name: args.enable_pipe.then_some(args.pipe_name.clone()),
             http,
             sse,
             ws,
@@ -86,11 +120,33 @@ impl TransportState {
         self.stdio
             || self.tcp.is_some()
             || self.unix_path.is_some()
+            || self.pipe_name.is_some()
             || self.http.is_some()
             || self.sse.is_some()
             || self.ws.is_some()
     }
 
+    /// Whether any transport other than stdio is enabled.
+    pub fn has_network(&self) -> bool {
+        self.enabled_count() > usize::from(self.stdio)
+    }
+
+    /// Number of enabled transports, including stdio.
+    pub fn enabled_count(&self) -> usize {
+        [
+            self.stdio,
+            self.tcp.is_some(),
+            self.unix_path.is_some(),
+            self.pipe_name.is_some(),
+            self.http.is_some(),
+            self.sse.is_some(),
+            self.ws.is_some(),
+        ]
+        .into_iter()
+        .filter(|enabled| *enabled)
+        .count()
+    }
+
     pub fn active_endpoints(&self) -> Vec<String> {
         let mut endpoints = Vec::new();
         if self.stdio {
@@ -102,6 +158,9 @@ impl TransportState {
         if let Some(path) = &self.unix_path {
             endpoints.push(format!("unix@{path}"));
         }
+        if let Some(name) = &self.pipe_name {
+            endpoints.push(format!("pipe@{name}"));
+        }
         if let Some(addr) = &self.http {
             endpoints.push(format!("http+streamable-sse@{addr}"));
         }
@@ -115,13 +174,334 @@ impl TransportState {
     }
 }
 
-#[derive(Clone)]
+/// Cross-origin policy for the streamable HTTP and SSE transports. CORS
+/// headers are only emitted when at least one origin is allowed.
+#[derive(Clone, Debug, Default)]
+pub struct CorsConfig {
+    pub allowed_origins: Vec<String>,
+    pub allowed_headers: Vec<String>,
+    pub allowed_methods: Vec<String>,
+}
+
+impl CorsConfig {
+    pub fn from_args(args: &CommandArguments) -> Self {
+        let clean = |values: &[String]| {
+            values
+                .iter()
+                .map(|value| value.trim().to_string())
+                .filter(|value| !value.is_empty())
+                .collect()
+        };
+        Self {
+            allowed_origins: clean(&args.cors_allowed_origins),
+            allowed_headers: clean(&args.cors_allowed_headers),
+            allowed_methods: clean(&args.cors_allowed_methods),
+        }
+    }
+
+    pub fn enabled(&self) -> bool {
+        !self.allowed_origins.is_empty()
+    }
+
+    /// `Access-Control-Allow-Origin` value for a request from `origin`, or
+    /// `None` when the origin is not allowed.
+    fn allow_origin<'a>(&self, origin: &'a str) -> Option<&'a str> {
+        if self.allowed_origins.iter().any(|allowed| allowed == "*") {
+            Some("*")
+        } else {
+            self.allowed_origins
+                .iter()
+                .any(|allowed| allowed.trim_end_matches('/').eq_ignore_ascii_case(origin))
+                .then_some(origin)
+        }
+    }
+
+    fn preflight<B: Default>(&self, origin: Option<&str>) -> Response<B> {
+        let Some(allowed) = origin.and_then(|origin| self.allow_origin(origin)) else {
+            return Response::builder()
+                .status(StatusCode::FORBIDDEN)
+                .body(B::default())
+                .expect("static response parts are valid");
+        };
+        let mut response = Response::builder()
+            .status(StatusCode::NO_CONTENT)
+            .header(header::ACCESS_CONTROL_ALLOW_METHODS, self.allowed_methods.join(", "))
+            .header(header::ACCESS_CONTROL_ALLOW_HEADERS, self.allowed_headers.join(", "))
+            .header(header::ACCESS_CONTROL_MAX_AGE, "600")
+            .body(B::default())
+            .expect("static response parts are valid");
+        set_allow_origin(&mut response, allowed);
+        response
+    }
+
+    fn apply<B>(&self, origin: &str, response: &mut Response<B>) {
+        if let Some(allowed) = self.allow_origin(origin) {
+            set_allow_origin(response, allowed);
+            response.headers_mut().insert(
+                header::ACCESS_CONTROL_EXPOSE_HEADERS,
+                HeaderValue::from_static("Mcp-Session-Id"),
+            );
+        }
+    }
+}
+
+fn set_allow_origin<B>(response: &mut Response<B>, allowed: &str) {
+    let headers = response.headers_mut();
+    if let Ok(value) = HeaderValue::from_str(allowed) {
+        headers.insert(header::ACCESS_CONTROL_ALLOW_ORIGIN, value);
+    }
+    headers.append(header::VARY, HeaderValue::from_static("Origin"));
+}
+
+/// Answer CORS preflights and add CORS headers to the response of `next`.
+async fn with_cors<B, F, Fut>(cors: &CorsConfig, req: Request<Incoming>, next: F) -> Response<B>
+where
+    B: Default,
+    F: FnOnce(Request<Incoming>) -> Fut,
+    Fut: Future<Output = Response<B>>,
+{
+    if !cors.enabled() {
+        return next(req).await;
+    }
+    let origin = req
+        .headers()
+        .get(header::ORIGIN)
+        .and_then(|value| value.to_str().ok())
+        .map(str::to_string);
+    if req.method() == Method::OPTIONS
+        && req.headers().contains_key(header::ACCESS_CONTROL_REQUEST_METHOD)
+    {
+        return cors.preflight(origin.as_deref());
+    }
+    let mut response = next(req).await;
+    if let Some(origin) = origin {
+        cors.apply(&origin, &mut response);
+    }
+    response
+}
+
+/// JSON-RPC error code returned for requests rejected by [`Throttled`].
+pub const THROTTLED: ErrorCode = ErrorCode(-32029);
+
+const RATE_WINDOW: Duration = Duration::from_secs(60);
+
+/// Request limits enforced on every connection by [`Throttled`].
+#[derive(Clone, Debug, Default)]
+pub struct Limits {
+    /// Requests a connection may send per minute; 0 disables the limit
+    pub requests_per_minute: u32,
+    /// Permits shared by all connections for executing tool calls
+    tool_permits: Option<Arc<Semaphore>>,
+    /// Close TCP, Unix, pipe and websocket sessions idle for this long
+    pub idle_timeout: Option<Duration>,
+    /// Open connections across all transports; 0 disables the cap
+    pub max_connections: usize,
+    /// Threads validating files within one tool call; 0 uses one per CPU
+    pub validation_jobs: usize,
+}
+
+impl Limits {
+    pub fn new(requests_per_minute: u32, max_concurrent_tools: usize) -> Self {
+        Self {
+            requests_per_minute,
+            tool_permits: (max_concurrent_tools > 0)
+                .then(|| Arc::new(Semaphore::new(max_concurrent_tools))),
+            idle_timeout: None,
+            max_connections: 0,
+            validation_jobs: 0,
+        }
+    }
+
+    pub fn from_args(args: &CommandArguments) -> Self {
+        Self {
+            idle_timeout: (args.idle_timeout_secs > 0)
+                .then(|| Duration::from_secs(args.idle_timeout_secs)),
+            max_connections: args.max_connections,
+            validation_jobs: args.validation_jobs,
+            ..Self::new(args.rate_limit_per_minute, args.max_concurrent_tools)
+        }
+    }
+
+    fn tool_permit(&self) -> Result<Option<OwnedSemaphorePermit>, ErrorData> {
+        match &self.tool_permits {
+            Some(permits) => permits.clone().try_acquire_owned().map(Some).map_err(|_| {
+                throttled(
+                    "Too many tool calls in progress; retry shortly".to_string(),
+                    Duration::from_secs(1),
+                )
+            }),
+            None => Ok(None),
+        }
+    }
+}
+
+fn throttled(message: String, retry_after: Duration) -> ErrorData {
+    ErrorData::new(
+        THROTTLED,
+        message,
+        Some(serde_json::json!({ "retry_after_ms": retry_after.as_millis() as u64 })),
+    )
+}
+
+/// Middleware around one connection's MCP service: records session activity,
+/// then applies the per-connection request rate and the global tool-call cap
+/// before delegating. `ping` and `initialize` are never throttled.
+pub struct Throttled<S> {
+    inner: S,
+    limits: Limits,
+    window: Mutex<VecDeque<Instant>>,
+    state: Arc<ServerState>,
+    session_id: u64,
+}
+
+impl<S> Throttled<S> {
+    pub fn new(inner: S, state: Arc<ServerState>, session_id: u64) -> Self {
+        Self {
+            inner,
+            limits: state.limits.clone(),
+            window: Mutex::new(VecDeque::new()),
+            state,
+            session_id,
+        }
+    }
+
+    pub fn session_id(&self) -> u64 {
+        self.session_id
+    }
+
+    /// Count a request against the sliding one-minute window, or return how
+    /// long until the window has room again.
+    fn admit(&self) -> Result<(), Duration> {
+        let limit = self.limits.requests_per_minute as usize;
+        if limit == 0 {
+            return Ok(());
+        }
+        let now = Instant::now();
+        let Ok(mut window) = self.window.lock() else {
+            return Ok(());
+        };
+        while window
+            .front()
+            .is_some_and(|seen| now.duration_since(*seen) >= RATE_WINDOW)
+        {
+            window.pop_front();
+        }
+        if let Some(oldest) = window.front().filter(|_| window.len() >= limit) {
+            return Err(RATE_WINDOW.saturating_sub(now.duration_since(*oldest)));
+        }
+        window.push_back(now);
+        Ok(())
+    }
+}
+
+impl<S: Service<RoleServer>> Service<RoleServer> for Throttled<S> {
+    async fn handle_request(
+        &self,
+        request: ClientRequest,
+        context: RequestContext<RoleServer>,
+    ) -> Result<ServerResult, ErrorData> {
+        self.state.touch_session(self.session_id);
+        let exempt = matches!(
+            request,
+            ClientRequest::PingRequest(_) | ClientRequest::InitializeRequest(_)
+        );
+        if !exempt {
+            self.admit().map_err(|retry_after| {
+                throttled(
+                    format!(
+                        "Rate limit of {} requests per minute exceeded",
+                        self.limits.requests_per_minute
+                    ),
+                    retry_after,
+                )
+            })?;
+        }
+        let _permit = match request {
+            ClientRequest::CallToolRequest(_) => self.limits.tool_permit()?,
+            _ => None,
+        };
+        self.inner.handle_request(request, context).await
+    }
+
+    async fn handle_notification(
+        &self,
+        notification: ClientNotification,
+        context: NotificationContext<RoleServer>,
+    ) -> Result<(), ErrorData> {
+        self.state.touch_session(self.session_id);
+        self.inner.handle_notification(notification, context).await
+    }
+
+    fn get_info(&self) -> ServerInfo {
+        self.inner.get_info()
+    }
+}
+
+/// An address a transport is actually serving on (after auto-assigned ports
+/// are resolved).
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Endpoint {
+    pub transport: String,
+    pub address: String,
+}
+
+impl std::fmt::Display for Endpoint {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}@{}", self.transport, self.address)
+    }
+}
+
 pub struct ServerState {
     pub started_at: SystemTime,
     pub version: String,
     pub transports: TransportState,
+    pub cors: CorsConfig,
+    pub limits: Limits,
+    pub tool_policy: ToolPolicy,
     pub name: String,
     pub description: String,
+    /// Effective settings and the layer that supplied each, for `explain_config`
+    pub settings: Vec<Setting>,
+    /// Workspace of each open MCP session, keyed by session id
+    sessions: Mutex<HashMap<u64, SessionWorkspace>>,
+    /// Lifecycle of each open MCP session, keyed by session id
+    activity: Mutex<HashMap<u64, SessionActivity>>,
+    next_session_id: AtomicU64,
+    planning: OnceLock<Result<Storage, String>>,
+    /// Endpoints recorded by transports as they start
+    endpoints: Mutex<Vec<Endpoint>>,
+    /// Runtime info file rewritten whenever an endpoint is recorded
+    runtime_info_file: Option<String>,
+}
+
+/// Context a session builds up across tool calls so follow-up calls can omit it.
+#[derive(Clone, Debug, Default)]
+pub struct SessionWorkspace {
+    /// Set by a successful `connect_payload`
+    pub connection: Option<PayloadConnection>,
+    /// Options of the last `scaffold_project` or `add_to_project`
+    pub project: Option<ScaffoldOptions>,
+    /// Goal opened by the last `start_planning`
+    pub goal_id: Option<String>,
+    /// Last successful structured result of each tool, for `attach_result`
+    pub last_results: HashMap<String, serde_json::Value>,
+}
+
+#[derive(Clone, Debug)]
+pub struct SessionActivity {
+    pub transport: &'static str,
+    pub opened_at: SystemTime,
+    pub last_activity: SystemTime,
+}
+
+#[derive(Clone, Debug)]
+pub enum PayloadConnection {
+    /// A connection stored in settings, looked up again on each use
+    Named(String),
+    Direct {
+        connection_string: String,
+        api_key: Option<String>,
+    },
 }
 
 impl ServerState {
@@ -130,22 +510,200 @@ impl ServerState {
             started_at: SystemTime::now(),
             version: env!("CARGO_PKG_VERSION").to_string(),
             transports,
+            cors: CorsConfig::default(),
+            limits: Limits::default(),
+            tool_policy: ToolPolicy::default(),
             name,
             description,
+            settings: Vec::new(),
+            sessions: Mutex::new(HashMap::new()),
+            activity: Mutex::new(HashMap::new()),
+            next_session_id: AtomicU64::new(1),
+            planning: OnceLock::new(),
+            endpoints: Mutex::new(Vec::new()),
+            runtime_info_file: None,
+        }
+    }
+
+    pub fn with_runtime_info_file(mut self, path: String) -> Self {
+        self.runtime_info_file = Some(path);
+        self
+    }
+
+    /// Record a transport's bound address and refresh the runtime info file.
+    pub fn record_endpoint(&self, transport: &str, address: impl ToString) {
+        if let Ok(mut endpoints) = self.endpoints.lock() {
+            endpoints.push(Endpoint {
+                transport: transport.to_string(),
+                address: address.to_string(),
+            });
+            self.persist_runtime_info(&endpoints);
+        }
+    }
+
+    /// Endpoints recorded so far, in start order.
+    pub fn endpoints(&self) -> Vec<Endpoint> {
+        self.endpoints
+            .lock()
+            .map(|endpoints| endpoints.clone())
+            .unwrap_or_default()
+    }
+
+    fn write_runtime_info(&self) {
+        if let Ok(endpoints) = self.endpoints.lock() {
+            self.persist_runtime_info(&endpoints);
         }
     }
 
+    /// Called with the endpoint lock held so concurrent writers cannot leave
+    /// a stale file behind.
+    fn persist_runtime_info(&self, endpoints: &[Endpoint]) {
+        let Some(path) = &self.runtime_info_file else {
+            return;
+        };
+        let info = RuntimeInfo {
+            pid: std::process::id(),
+            started_at: chrono::DateTime::<chrono::Utc>::from(self.started_at).to_rfc3339(),
+            endpoints: endpoints.to_vec(),
+        };
+        if let Err(err) = write_runtime_info(path, &info) {
+            tracing::warn!("{err}");
+        }
+    }
+
+    pub fn with_cors(mut self, cors: CorsConfig) -> Self {
+        self.cors = cors;
+        self
+    }
+
+    pub fn with_tool_policy(mut self, tool_policy: ToolPolicy) -> Self {
+        self.tool_policy = tool_policy;
+        self
+    }
+
+    pub fn with_settings(mut self, settings: Vec<Setting>) -> Self {
+        self.settings = settings;
+        self
+    }
+
+    pub fn with_limits(mut self, limits: Limits) -> Self {
+        self.limits = limits;
+        self
+    }
+
     pub fn uptime(&self) -> Duration {
         SystemTime::now()
             .duration_since(self.started_at)
             .unwrap_or_else(|_| Duration::from_secs(0))
     }
+
+    /// Planning storage shared by all sessions, opened on first use.
+    pub fn planning(&self) -> Result<&Storage, String> {
+        self.planning
+            .get_or_init(Storage::open_default)
+            .as_ref()
+            .map_err(Clone::clone)
+    }
+
+    /// Allocate an empty workspace for a new session.
+    pub fn open_session(&self) -> u64 {
+        let id = self.next_session_id.fetch_add(1, Ordering::Relaxed);
+        if let Ok(mut sessions) = self.sessions.lock() {
+            sessions.insert(id, SessionWorkspace::default());
+        }
+        if let Ok(mut activity) = self.activity.lock() {
+            let now = SystemTime::now();
+            activity.insert(
+                id,
+                SessionActivity {
+                    transport: "stdio",
+                    opened_at: now,
+                    last_activity: now,
+                },
+            );
+        }
+        id
+    }
+
+    pub fn close_session(&self, id: u64) {
+        if let Ok(mut sessions) = self.sessions.lock() {
+            sessions.remove(&id);
+        }
+        if let Ok(mut activity) = self.activity.lock() {
+            activity.remove(&id);
+        }
+    }
+
+    pub fn set_session_transport(&self, id: u64, transport: &'static str) {
+        if let Ok(mut activity) = self.activity.lock() {
+            if let Some(session) = activity.get_mut(&id) {
+                session.transport = transport;
+            }
+        }
+    }
+
+    /// Mark a session as active now.
+    pub fn touch_session(&self, id: u64) {
+        if let Ok(mut activity) = self.activity.lock() {
+            if let Some(session) = activity.get_mut(&id) {
+                session.last_activity = SystemTime::now();
+            }
+        }
+    }
+
+    /// How long a session has been idle, or `None` once it is closed.
+    pub fn session_idle_for(&self, id: u64) -> Option<Duration> {
+        let activity = self.activity.lock().ok()?;
+        let session = activity.get(&id)?;
+        Some(session.last_activity.elapsed().unwrap_or_default())
+    }
+
+    /// Open sessions ordered by id.
+    pub fn session_activity(&self) -> Vec<(u64, SessionActivity)> {
+        let mut sessions: Vec<_> = self
+            .activity
+            .lock()
+            .map(|activity| {
+                activity
+                    .iter()
+                    .map(|(id, session)| (*id, session.clone()))
+                    .collect()
+            })
+            .unwrap_or_default();
+        sessions.sort_by_key(|(id, _)| *id);
+        sessions
+    }
+
+    /// Snapshot of a session's workspace.
+    pub fn workspace(&self, id: u64) -> SessionWorkspace {
+        self.sessions
+            .lock()
+            .ok()
+            .and_then(|sessions| sessions.get(&id).cloned())
+            .unwrap_or_default()
+    }
+
+    pub fn update_workspace(&self, id: u64, update: impl FnOnce(&mut SessionWorkspace)) {
+        if let Ok(mut sessions) = self.sessions.lock() {
+            update(sessions.entry(id).or_default());
+        }
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct RuntimeInfo {
     pub pid: u32,
-    pub endpoints: Vec<String>,
+    pub started_at: String,
+    pub endpoints: Vec<Endpoint>,
+}
+
+impl RuntimeInfo {
+    /// Read the runtime info file written by a running server.
+    pub fn read(path: &str) -> Option<Self> {
+        fs::read_to_string(path)
+            .ok()
+            .and_then(|contents| serde_json::from_str(&contents).ok())
+    }
 }
 
 fn write_runtime_info(path: &str, info: &RuntimeInfo) -> ServiceResult<()> {
@@ -167,24 +725,100 @@ pub fn server_details(state: &ServerState) -> InitializeResult {
         },
         capabilities: ServerCapabilities {
             experimental: None,
-            logging: None,
-            prompts: None,
-            resources: None,
+            logging: Some(rmcp::model::JsonObject::default()),
+            prompts: Some(rmcp::model::PromptsCapability::default()),
+            resources: Some(rmcp::model::ResourcesCapability {
+                subscribe: Some(true),
+                list_changed: None,
+            }),
             tools: Some(rmcp::model::ToolsCapability::default()),
-            completions: None,
+            completions: Some(rmcp::model::JsonObject::default()),
         },
         instructions: ToolBoxHandler::instructions(),
         protocol_version: ProtocolVersion::default(),
     }
 }
 
-fn make_service(state: Arc<ServerState>) -> std::io::Result<Router<ToolBoxHandler>> {
-    Ok(Router::new(ToolBoxHandler::new(state)))
+fn make_service(
+    state: Arc<ServerState>,
+    transport: &'static str,
+) -> std::io::Result<Throttled<Router<ToolBoxHandler>>> {
+    let handler = ToolBoxHandler::new(state.clone()).with_transport(transport);
+    let session_id = handler.session_id();
+    Ok(Throttled::new(Router::new(handler), state, session_id))
 }
 
-pub async fn start_server(args: CommandArguments) -> ServiceResult<()> {
+/// Serve one MCP session over a connection until the client leaves, it sits
+/// idle past the idle timeout, or `ct` is cancelled.
+async fn serve_session<T, E, A>(
+    state: Arc<ServerState>,
+    transport: &'static str,
+    io: T,
+    ct: CancellationToken,
+) where
+    T: rmcp::transport::IntoTransport<RoleServer, E, A>,
+    E: std::error::Error + Send + Sync + 'static,
+{
+    let service = match make_service(state.clone(), transport) {
+        Ok(service) => service,
+        Err(err) => {
+            tracing::warn!("Failed to init service for {transport}: {err}");
+            return;
+        }
+    };
+    if let Some(timeout) = state.limits.idle_timeout {
+        tokio::spawn(close_when_idle(
+            state.clone(),
+            service.session_id(),
+            timeout,
+            ct.clone(),
+        ));
+    }
+    match service.serve_with_ct(io, ct.clone()).await {
+        Ok(running) => {
+            let _ = running.waiting().await;
+        }
+        Err(err) => tracing::warn!("{transport} connection error: {err}"),
+    }
+    // Stops the idle watchdog.
+    ct.cancel();
+}
+
+async fn close_when_idle(
+    state: Arc<ServerState>,
+    session_id: u64,
+    timeout: Duration,
+    ct: CancellationToken,
+) {
+    while let Some(idle) = state.session_idle_for(session_id) {
+        if idle >= timeout {
+            tracing::info!(
+                "Closing session {session_id} after {}s without requests",
+                idle.as_secs()
+            );
+            ct.cancel();
+            return;
+        }
+        tokio::select! {
+            _ = ct.cancelled() => return,
+            _ = tokio::time::sleep(timeout - idle) => {}
+        }
+    }
+}
+
+/// Whether accepting another connection would exceed `max_connections`.
+fn at_capacity(state: &ServerState, connections: &TaskTracker, transport: &str) -> bool {
+    let max = state.limits.max_connections;
+    let full = max > 0 && connections.len() >= max;
+    if full {
+        tracing::warn!("Refusing {transport} connection: {max} connections already open");
+    }
+    full
+}
+
+pub async fn start_server(config: Config) -> ServiceResult<()> {
+    let Config { args, settings } = config;
     let mut tasks: JoinSet<ServiceResult<()>> = JoinSet::new();
-    let mut endpoints: Vec<String> = Vec::new();
 
     let transports = TransportState::from_args(&args)?;
     if !transports.any_enabled() {
@@ -192,30 +826,99 @@ pub async fn start_server(args: CommandArguments) -> ServiceResult<()> {
             "No transports enabled; toggle MCP_ENABLE_* env vars or CLI flags".to_string(),
         ));
     }
-    let state = Arc::new(ServerState::new(
-        transports,
-        args.server_name.clone(),
-        args.server_description.clone(),
-    ));
+    for path in [&args.pid_file, &args.runtime_info_file] {
+        crate::paths::ensure_parent(Path::new(path)).map_err(|e| {
+            crate::error::ServiceError::FromString(format!("Could not create directory for {path}: {e}"))
+        })?;
+    }
+    #[cfg(unix)]
+    if args.enable_unix {
+        let _ = crate::paths::ensure_parent(Path::new(&args.unix_path));
+    }
+    crate::payload_tools::schema_cache::configure(
+        args.schema_cache_ttl_secs,
+        args.schema_cache_on_disk,
+    );
+    crate::payload_tools::template_overrides::configure(&args.templates_dir);
+    crate::payload_tools::naming::configure(&args.naming_profile, &args.naming_profiles);
+    let state = Arc::new(
+        ServerState::new(
+            transports,
+            args.server_name.clone(),
+            args.server_description.clone(),
+        )
+        .with_cors(CorsConfig::from_args(&args))
+        .with_limits(Limits::from_args(&args))
+        .with_tool_policy(args.tool_policy.clone())
+        .with_settings(settings)
+        .with_runtime_info_file(args.runtime_info_file.clone()),
+    );
     tracing::info!(
         "Starting MCP server v{} on {}",
         state.version,
         state.transports.active_endpoints().join(", ")
     );
-    endpoints.extend(state.transports.active_endpoints());
+
+    // stdio-only servers belong to their client and are not tracked by
+    // `status`/`shutdown`, nor limited to one instance.
+    let track_pid = state.transports.has_network();
+    let _instance_lock = if track_pid {
+        Some(
+            InstanceLock::acquire(&instance::lock_path(&args.pid_file))
+                .map_err(ServiceError::FromString)?,
+        )
+    } else {
+        None
+    };
+
+    // `shutdown` stops the listeners; `sessions` closes open sessions once
+    // in-flight tool calls have drained.
+    let shutdown = CancellationToken::new();
+    let sessions = CancellationToken::new();
+    let connections = TaskTracker::new();
+    let stop_file = stop_file_path(&args.pid_file);
+    let _ = fs::remove_file(&stop_file);
+    tokio::spawn({
+        let shutdown = shutdown.clone();
+        let stop_file = stop_file.clone();
+        async move {
+            tokio::select! {
+                _ = shutdown_signal() => {
+                    tracing::info!("Shutdown signal received; no longer accepting connections");
+                }
+                _ = stop_requested(&stop_file) => {
+                    tracing::info!("Shutdown requested; no longer accepting connections");
+                }
+            }
+            shutdown.cancel();
+        }
+    });
+
+    if !args.watch.is_empty() {
+        tokio::spawn(crate::watch::watch_in_background(
+            args.watch.clone(),
+            args.validation_jobs,
+            Duration::from_millis(args.watch_interval_ms.max(100)),
+            shutdown.clone(),
+        ));
+    }
 
     // stdio
     if state.transports.stdio {
         let state = state.clone();
-        endpoints.push("stdio".to_string());
+        let sessions = sessions.clone();
         tasks.spawn(async move {
             tracing::info!("stdio transport listening on stdin/stdout");
-            let router = make_service(state)
+            state.record_endpoint("stdio", "stdin/stdout");
+            metrics::transport_ready();
+            metrics::connection_accepted("stdio");
+            let router = make_service(state, "stdio")
                 .map_err(|e| crate::error::ServiceError::FromString(e.to_string()))?;
-            router
-                .serve((tokio::io::stdin(), tokio::io::stdout()))
+            let running = router
+                .serve_with_ct((tokio::io::stdin(), tokio::io::stdout()), sessions)
                 .await
                 .map_err(|e| crate::error::ServiceError::FromString(format!("Stdio error: {e}")))?;
+            let _ = running.waiting().await;
             Ok(())
         });
     }
@@ -223,33 +926,36 @@ pub async fn start_server(args: CommandArguments) -> ServiceResult<()> {
     // TCP
     if let Some(tcp_addr) = state.transports.tcp {
         let state = state.clone();
+        let shutdown = shutdown.clone();
+        let sessions = sessions.clone();
+        let connections = connections.clone();
         tasks.spawn(async move {
             tracing::info!("TCP transport binding to {tcp_addr}");
             let listener = TcpListener::bind(tcp_addr).await.map_err(|e| {
                 crate::error::ServiceError::FromString(format!("TCP bind error: {e}"))
             })?;
-            if let Ok(actual) = listener.local_addr() {
-                endpoints_lock_push(&state, format!("tcp@{actual}"));
+            match listener.local_addr() {
+                Ok(actual) => state.record_endpoint("tcp", actual),
+                Err(_) => state.record_endpoint("tcp", tcp_addr),
             }
+            metrics::transport_ready();
             loop {
-                let (stream, _) = listener.accept().await.map_err(|e| {
-                    crate::error::ServiceError::FromString(format!("TCP accept error: {e}"))
-                })?;
-                let state_for_conn = state.clone();
-                tokio::spawn(async move {
-                    match make_service(state_for_conn) {
-                        Ok(router) => {
-                            if let Err(err) = router.serve(stream).await.map_err(|e| {
-                                crate::error::ServiceError::FromString(format!(
-                                    "TCP server error: {e}"
-                                ))
-                            }) {
-                                tracing::warn!("TCP connection error: {err}");
-                            }
-                        }
-                        Err(e) => tracing::warn!("Failed to init service for TCP: {e}"),
-                    }
-                });
+                let (stream, _) = tokio::select! {
+                    _ = shutdown.cancelled() => return Ok(()),
+                    accepted = listener.accept() => accepted.map_err(|e| {
+                        crate::error::ServiceError::FromString(format!("TCP accept error: {e}"))
+                    })?,
+                };
+                if at_capacity(&state, &connections, "tcp") {
+                    continue;
+                }
+                metrics::connection_accepted("tcp");
+                connections.spawn(serve_session(
+                    state.clone(),
+                    "tcp",
+                    stream,
+                    sessions.child_token(),
+                ));
             }
         });
     }
@@ -258,8 +964,10 @@ pub async fn start_server(args: CommandArguments) -> ServiceResult<()> {
     #[cfg(unix)]
     if let Some(unix_path) = state.transports.unix_path.clone() {
         let state = state.clone();
+        let shutdown = shutdown.clone();
+        let sessions = sessions.clone();
+        let connections = connections.clone();
         tasks.spawn(async move {
-            use std::path::Path;
             let path = unix_path;
             if Path::new(&path).exists() {
                 let _ = std::fs::remove_file(&path);
@@ -268,25 +976,70 @@ pub async fn start_server(args: CommandArguments) -> ServiceResult<()> {
             let listener = UnixListener::bind(&path).map_err(|e| {
                 crate::error::ServiceError::FromString(format!("Unix bind error: {e}"))
             })?;
+            state.record_endpoint("unix", &path);
+            metrics::transport_ready();
             loop {
-                let (stream, _) = listener.accept().await.map_err(|e| {
-                    crate::error::ServiceError::FromString(format!("Unix accept error: {e}"))
-                })?;
-                let state_for_conn = state.clone();
-                tokio::spawn(async move {
-                    match make_service(state_for_conn) {
-                        Ok(router) => {
-                            if let Err(err) = router.serve(stream).await.map_err(|e| {
-                                crate::error::ServiceError::FromString(format!(
-                                    "Unix server error: {e}"
-                                ))
-                            }) {
-                                tracing::warn!("Unix connection error: {err}");
-                            }
-                        }
-                        Err(e) => tracing::warn!("Failed to init service for Unix: {e}"),
+                let (stream, _) = tokio::select! {
+                    _ = shutdown.cancelled() => {
+                        let _ = std::fs::remove_file(&path);
+                        return Ok(());
                     }
-                });
+                    accepted = listener.accept() => accepted.map_err(|e| {
+                        crate::error::ServiceError::FromString(format!("Unix accept error: {e}"))
+                    })?,
+                };
+                if at_capacity(&state, &connections, "unix") {
+                    continue;
+                }
+                metrics::connection_accepted("unix");
+                connections.spawn(serve_session(
+                    state.clone(),
+                    "unix",
+                    stream,
+                    sessions.child_token(),
+                ));
+            }
+        });
+    }
+
+    // Named pipe
+    #[cfg(windows)]
+    if let Some(pipe_name) = state.transports.pipe_name.clone() {
+        let state = state.clone();
+        let shutdown = shutdown.clone();
+        let sessions = sessions.clone();
+        let connections = connections.clone();
+        tasks.spawn(async move {
+            use tokio::net::windows::named_pipe::ServerOptions;
+            tracing::info!("Named pipe transport listening on {pipe_name}");
+            let pipe_error = |e: std::io::Error| {
+                crate::error::ServiceError::FromString(format!("Named pipe error: {e}"))
+            };
+            let mut server = ServerOptions::new()
+                .first_pipe_instance(true)
+                .create(&pipe_name)
+                .map_err(pipe_error)?;
+            state.record_endpoint("pipe", &pipe_name);
+            metrics::transport_ready();
+            loop {
+                tokio::select! {
+                    _ = shutdown.cancelled() => return Ok(()),
+                    connected = server.connect() => connected.map_err(pipe_error)?,
+                }
+                // A pipe instance serves one client; open the next before
+                // handing this one off.
+                let stream = server;
+                server = ServerOptions::new().create(&pipe_name).map_err(pipe_error)?;
+                if at_capacity(&state, &connections, "pipe") {
+                    continue;
+                }
+                metrics::connection_accepted("pipe");
+                connections.spawn(serve_session(
+                    state.clone(),
+                    "pipe",
+                    stream,
+                    sessions.child_token(),
+                ));
             }
         });
     }
@@ -294,12 +1047,15 @@ pub async fn start_server(args: CommandArguments) -> ServiceResult<()> {
     // Streamable HTTP+SSE
     if let Some(http_addr) = state.transports.http {
         let state_for_service = state.clone();
+        let shutdown = shutdown.clone();
+        let sessions = sessions.clone();
+        let connections = connections.clone();
         tasks.spawn(async move {
             let state_for_factory = state_for_service.clone();
             let service = StreamableHttpService::new(
                 move || {
-                    make_service(state_for_factory.clone())
-                        .map_err(|e| std::io::Error::new(std::io::ErrorKind::Other, format!("{e}")))
+                    make_service(state_for_factory.clone(), "http")
+                        .map_err(|e| std::io::Error::other(format!("{e}")))
                 },
                 Arc::new(LocalSessionManager::default()),
                 StreamableHttpServerConfig::default(),
@@ -309,30 +1065,50 @@ pub async fn start_server(args: CommandArguments) -> ServiceResult<()> {
                 crate::error::ServiceError::FromString(format!("Streamable HTTP bind error: {e}"))
             })?;
             let actual_addr = listener.local_addr().ok();
-            if let Some(addr) = actual_addr {
-                endpoints_lock_push(&state_for_service, format!("streamable-http@{addr}"));
-            }
+            state_for_service.record_endpoint("http", actual_addr.unwrap_or(http_addr));
             tracing::info!(
                 "Streamable HTTP listening on {}",
                 actual_addr
                     .map(|a| a.to_string())
                     .unwrap_or_else(|| http_addr.to_string())
             );
+            metrics::transport_ready();
 
             loop {
-                let (stream, _) = listener.accept().await.map_err(|e| {
-                    crate::error::ServiceError::FromString(format!(
-                        "Streamable HTTP accept error: {e}"
-                    ))
-                })?;
+                let (stream, _) = tokio::select! {
+                    _ = shutdown.cancelled() => return Ok(()),
+                    accepted = listener.accept() => accepted.map_err(|e| {
+                        crate::error::ServiceError::FromString(format!(
+                            "Streamable HTTP accept error: {e}"
+                        ))
+                    })?,
+                };
+                if at_capacity(&state_for_service, &connections, "http") {
+                    continue;
+                }
+                metrics::connection_accepted("http");
                 let svc = service.clone();
-                tokio::spawn(async move {
+                let state = state_for_service.clone();
+                let ct = sessions.child_token();
+                connections.spawn(async move {
                     let io = TokioIo::new(stream);
-                    let hyper_svc = TowerToHyperService::new(svc);
-                    if let Err(err) = HyperBuilder::new(TokioExecutor::new())
-                        .serve_connection(io, hyper_svc)
-                        .await
-                    {
+                    let hyper_svc = hyper::service::service_fn(move |req: Request<Incoming>| {
+                        let svc = svc.clone();
+                        let state = state.clone();
+                        async move {
+                            let ops_state = state.clone();
+                            Ok::<_, Infallible>(
+                                with_cors(&state.cors, req, move |req| async move {
+                                    match ops_response(&ops_state, req.uri().path()) {
+                                        Some(response) => response,
+                                        None => svc.handle(req).await,
+                                    }
+                                })
+                                .await,
+                            )
+                        }
+                    });
+                    if let Err(err) = serve_http_connection(io, hyper_svc, ct).await {
                         tracing::warn!("Streamable HTTP connection error: {err}");
                     }
                 });
@@ -343,66 +1119,105 @@ pub async fn start_server(args: CommandArguments) -> ServiceResult<()> {
     // Dedicated SSE
     if let Some(sse_addr) = state.transports.sse {
         let state = state.clone();
+        let shutdown = shutdown.clone();
+        let sessions = sessions.clone();
+        let connections = connections.clone();
         tasks.spawn(async move {
             let config = SseServerConfig {
                 bind: sse_addr,
                 sse_path: "/sse".to_string(),
                 post_path: "/message".to_string(),
-                ct: CancellationToken::new(),
+                ct: sessions.child_token(),
                 sse_keep_alive: Some(std::time::Duration::from_secs(15)),
             };
 
-            let sse_server = SseServer::serve_with_config(config).await.map_err(|e| {
-                crate::error::ServiceError::FromString(format!("SSE server setup error: {e}"))
+            // Serve the SSE router ourselves so CORS can wrap it.
+            let (sse_server, router) = SseServer::new(config);
+            let listener = TcpListener::bind(sse_addr).await.map_err(|e| {
+                crate::error::ServiceError::FromString(format!("SSE bind error: {e}"))
             })?;
-            tracing::info!("SSE transport binding to {sse_addr}");
+            let actual_addr = listener.local_addr().unwrap_or(sse_addr);
+            tracing::info!("SSE transport listening on {actual_addr}");
+            state.record_endpoint("sse", actual_addr);
+            metrics::transport_ready();
 
+            let factory_state = state.clone();
             sse_server.with_service_directly(move || {
-                make_service(state.clone()).expect("Failed to init service for SSE")
+                metrics::connection_accepted("sse");
+                make_service(factory_state.clone(), "sse").expect("Failed to init service for SSE")
             });
 
-            Ok(())
+            let router = TowerToHyperService::new(router);
+            loop {
+                let (stream, _) = tokio::select! {
+                    _ = shutdown.cancelled() => return Ok(()),
+                    accepted = listener.accept() => accepted.map_err(|e| {
+                        crate::error::ServiceError::FromString(format!("SSE accept error: {e}"))
+                    })?,
+                };
+                if at_capacity(&state, &connections, "sse") {
+                    continue;
+                }
+                let router = router.clone();
+                let state = state.clone();
+                let ct = sessions.child_token();
+                connections.spawn(async move {
+                    let io = TokioIo::new(stream);
+                    let hyper_svc = hyper::service::service_fn(move |req: Request<Incoming>| {
+                        let router = router.clone();
+                        let state = state.clone();
+                        async move {
+                            Ok::<_, Infallible>(
+                                with_cors(&state.cors, req, move |req| async move {
+                                    match router.call(req).await {
+                                        Ok(response) => response,
+                                        Err(never) => match never {},
+                                    }
+                                })
+                                .await,
+                            )
+                        }
+                    });
+                    if let Err(err) = serve_http_connection(io, hyper_svc, ct).await {
+                        tracing::warn!("SSE connection error: {err}");
+                    }
+                });
+            }
         });
     }
 
     // Websocket
     if let Some(ws_addr) = state.transports.ws {
         let state = state.clone();
+        let shutdown = shutdown.clone();
+        let sessions = sessions.clone();
+        let connections = connections.clone();
         tasks.spawn(async move {
             let listener = TcpListener::bind(ws_addr).await.map_err(|e| {
                 crate::error::ServiceError::FromString(format!("Websocket bind error: {e}"))
             })?;
-            if let Ok(actual) = listener.local_addr() {
-                tracing::info!("Websocket transport listening on {actual}");
-                endpoints_lock_push(&state, format!("ws@{actual}"));
-            } else {
-                tracing::info!("Websocket transport binding to {ws_addr}");
-            }
+            let actual = listener.local_addr().unwrap_or(ws_addr);
+            tracing::info!("Websocket transport listening on {actual}");
+            state.record_endpoint("ws", actual);
+            metrics::transport_ready();
             loop {
-                let (stream, peer) = listener.accept().await.map_err(|e| {
-                    crate::error::ServiceError::FromString(format!("Websocket accept error: {e}"))
-                })?;
+                let (stream, peer) = tokio::select! {
+                    _ = shutdown.cancelled() => return Ok(()),
+                    accepted = listener.accept() => accepted.map_err(|e| {
+                        crate::error::ServiceError::FromString(format!("Websocket accept error: {e}"))
+                    })?,
+                };
+                if at_capacity(&state, &connections, "ws") {
+                    continue;
+                }
+                metrics::connection_accepted("ws");
                 let state_for_conn = state.clone();
-                tokio::spawn(async move {
+                let ct = sessions.child_token();
+                connections.spawn(async move {
                     match tokio_tungstenite::accept_async(stream).await {
                         Ok(ws_stream) => {
                             let transport = WebsocketTransport::new(ws_stream);
-                            match make_service(state_for_conn) {
-                                Ok(router) => {
-                                    if let Err(err) = router.serve(transport).await.map_err(|e| {
-                                        crate::error::ServiceError::FromString(format!(
-                                            "Websocket server error: {e}"
-                                        ))
-                                    }) {
-                                        tracing::warn!(
-                                            "Websocket connection error (peer {peer}): {err}"
-                                        );
-                                    }
-                                }
-                                Err(e) => {
-                                    tracing::warn!("Failed to init service for Websocket: {e}")
-                                }
-                            }
+                            serve_session(state_for_conn, "ws", transport, ct).await;
                         }
                         Err(err) => tracing::warn!("Websocket handshake error from {peer}: {err}"),
                     }
@@ -411,73 +1226,314 @@ pub async fn start_server(args: CommandArguments) -> ServiceResult<()> {
         });
     }
 
-    while let Some(res) = tasks.join_next().await {
-        res.map_err(|e| crate::error::ServiceError::FromString(format!("Task join error: {e}")))??;
+    // The pid file doubles as the readiness signal a daemonizing parent
+    // waits for, so it is only written once every transport is listening.
+    if track_pid {
+        let state = state.clone();
+        let shutdown = shutdown.clone();
+        let pid_file = args.pid_file.clone();
+        tokio::spawn(async move {
+            while metrics::ready_transports() < state.transports.enabled_count() {
+                if shutdown.is_cancelled() {
+                    return;
+                }
+                tokio::time::sleep(Duration::from_millis(50)).await;
+            }
+            if let Err(err) = fs::write(&pid_file, std::process::id().to_string()) {
+                tracing::warn!("Could not write pid file {pid_file}: {err}");
+            }
+        });
     }
+    // Written again as each transport records its endpoint.
+    state.write_runtime_info();
 
-    // Write runtime info (best-effort)
-    let info = RuntimeInfo {
-        pid: std::process::id(),
-        endpoints,
+    // Run until a signal arrives, every transport has finished, or one fails.
+    let result = loop {
+        tokio::select! {
+            _ = shutdown.cancelled() => break Ok(()),
+            joined = tasks.join_next() => match joined {
+                None => break Ok(()),
+                Some(Ok(Ok(()))) => {}
+                Some(Ok(Err(err))) => break Err(err),
+                Some(Err(err)) => {
+                    break Err(crate::error::ServiceError::FromString(format!(
+                        "Task join error: {err}"
+                    )));
+                }
+            },
+        }
     };
-    let _ = write_runtime_info(&args.runtime_info_file, &info);
 
-    Ok(())
+    shutdown.cancel();
+    let drain_timeout = Duration::from_secs(args.shutdown_timeout_secs);
+    if !drain_tool_calls(drain_timeout).await {
+        tracing::warn!(
+            "{} tool call(s) still running after {}s; closing sessions anyway",
+            metrics::tool_calls_in_flight(),
+            drain_timeout.as_secs()
+        );
+    }
+    sessions.cancel();
+    connections.close();
+    if tokio::time::timeout(CONNECTION_CLOSE_TIMEOUT, connections.wait())
+        .await
+        .is_err()
+    {
+        tracing::warn!("Some connections did not close in time");
+    }
+    tasks.shutdown().await;
+    crate::project_runner::stop_all().await;
+
+    if track_pid {
+        let _ = fs::remove_file(&args.pid_file);
+    }
+    let _ = fs::remove_file(&args.runtime_info_file);
+    let _ = fs::remove_file(&stop_file);
+    tracing::info!("MCP server stopped");
+    result
+}
+
+const CONNECTION_CLOSE_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Resolves on Ctrl+C, SIGTERM on unix, or the console close/shutdown events
+/// Windows sends to services and console apps.
+async fn shutdown_signal() {
+    #[cfg(unix)]
+    {
+        use tokio::signal::unix::{SignalKind, signal};
+        match signal(SignalKind::terminate()) {
+            Ok(mut terminate) => {
+                tokio::select! {
+                    _ = tokio::signal::ctrl_c() => {}
+                    _ = terminate.recv() => {}
+                }
+            }
+            Err(err) => {
+                tracing::warn!("Could not listen for SIGTERM: {err}");
+                let _ = tokio::signal::ctrl_c().await;
+            }
+        }
+    }
+    #[cfg(windows)]
+    {
+        use tokio::signal::windows::{ctrl_break, ctrl_close, ctrl_shutdown};
+        match (ctrl_break(), ctrl_close(), ctrl_shutdown()) {
+            (Ok(mut brk), Ok(mut close), Ok(mut shutdown)) => {
+                tokio::select! {
+                    _ = tokio::signal::ctrl_c() => {}
+                    _ = brk.recv() => {}
+                    _ = close.recv() => {}
+                    _ = shutdown.recv() => {}
+                }
+            }
+            _ => {
+                tracing::warn!("Could not listen for console control events");
+                let _ = tokio::signal::ctrl_c().await;
+            }
+        }
+    }
+    #[cfg(not(any(unix, windows)))]
+    {
+        let _ = tokio::signal::ctrl_c().await;
+    }
+}
+
+/// File whose creation asks the server to shut down; `shutdown` uses it where
+/// signals cannot be delivered to another process (Windows).
+pub fn stop_file_path(pid_file: &str) -> String {
+    format!("{pid_file}.stop")
+}
+
+async fn stop_requested(stop_file: &str) {
+    loop {
+        if fs::metadata(stop_file).is_ok() {
+            return;
+        }
+        tokio::time::sleep(Duration::from_millis(500)).await;
+    }
+}
+
+/// Wait for executing tool calls to finish; `false` if `timeout` elapsed first.
+async fn drain_tool_calls(timeout: Duration) -> bool {
+    let deadline = Instant::now() + timeout;
+    while metrics::tool_calls_in_flight() > 0 {
+        if Instant::now() >= deadline {
+            return false;
+        }
+        tokio::time::sleep(Duration::from_millis(50)).await;
+    }
+    true
 }
 
-fn endpoints_lock_push(state: &ServerState, _ep: String) {
-    let _ = state; // endpoints are collected in outer scope; this is a placeholder to keep signature symmetrical
+/// Serve one HTTP connection, finishing in-flight requests and then closing
+/// it once `ct` is cancelled.
+async fn serve_http_connection<S, B>(
+    io: TokioIo<tokio::net::TcpStream>,
+    service: S,
+    ct: CancellationToken,
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>>
+where
+    S: hyper::service::Service<Request<Incoming>, Response = Response<B>, Error = Infallible>
+        + Send
+        + 'static,
+    S::Future: Send + 'static,
+    B: hyper::body::Body + Send + 'static,
+    B::Data: Send,
+    B::Error: Into<Box<dyn std::error::Error + Send + Sync>>,
+{
+    let builder = HyperBuilder::new(TokioExecutor::new());
+    let connection = builder.serve_connection(io, service);
+    tokio::pin!(connection);
+    tokio::select! {
+        result = connection.as_mut() => result,
+        _ = ct.cancelled() => {
+            connection.as_mut().graceful_shutdown();
+            connection.await
+        }
+    }
 }
 
+/// Operational endpoints served next to the MCP endpoint on the HTTP
+/// transport, or `None` for paths handled by MCP.
+fn ops_response(state: &ServerState, path: &str) -> Option<Response<BoxBody<Bytes, Infallible>>> {
+    let (status, content_type, body) = match path {
+        "/healthz" => (StatusCode::OK, "text/plain", "ok".to_string()),
+        "/readyz" => {
+            if metrics::ready_transports() >= state.transports.enabled_count() {
+                (StatusCode::OK, "text/plain", "ready".to_string())
+            } else {
+                (StatusCode::SERVICE_UNAVAILABLE, "text/plain", "starting".to_string())
+            }
+        }
+        "/metrics" => (
+            StatusCode::OK,
+            "text/plain; version=0.0.4",
+            metrics::render(state.uptime()),
+        ),
+        "/info" => (
+            StatusCode::OK,
+            "application/json",
+            serde_json::json!({
+                "name": state.name,
+                "version": state.version,
+                "uptime_seconds": state.uptime().as_secs(),
+                "transports": state.transports.active_endpoints(),
+                "endpoints": state.endpoints(),
+                "active_sessions": metrics::active_sessions(),
+            })
+            .to_string(),
+        ),
+        _ => return None,
+    };
+    Some(
+        Response::builder()
+            .status(status)
+            .header(CONTENT_TYPE, content_type)
+            .body(Full::new(Bytes::from(body)).boxed())
+            .expect("static response parts are valid"),
+    )
+}
+
+/// Frames skipped (control frames, unparsable messages) in one `poll_next`
+/// before yielding, so a flood cannot starve other tasks.
+const WS_SKIP_BUDGET: usize = 64;
+
 pin_project! {
     struct WebsocketTransport<S> {
         #[pin]
         stream: S,
+        // Set once the peer sent Close or the connection failed
+        closed: bool,
     }
 }
 
 impl<S> WebsocketTransport<S> {
     fn new(stream: S) -> Self {
-        Self { stream }
+        Self {
+            stream,
+            closed: false,
+        }
+    }
+}
+
+/// JSON-RPC payload of a data frame; binary frames must hold UTF-8 JSON.
+fn ws_payload(message: &tungstenite::Message) -> Option<Result<&str, std::str::Utf8Error>> {
+    match message {
+        tungstenite::Message::Text(text) => Some(Ok(text.as_str())),
+        tungstenite::Message::Binary(bytes) => Some(std::str::from_utf8(bytes)),
+        _ => None,
     }
 }
 
 impl<S> futures::Stream for WebsocketTransport<S>
 where
-    S: futures::Stream<Item = Result<tungstenite::Message, tungstenite::Error>> + Unpin,
+    S: futures::Stream<Item = Result<tungstenite::Message, tungstenite::Error>>
+        + futures::Sink<tungstenite::Message, Error = tungstenite::Error>
+        + Unpin,
 {
     type Item = rmcp::service::RxJsonRpcMessage<rmcp::RoleServer>;
 
     fn poll_next(
-        mut self: std::pin::Pin<&mut Self>,
+        self: std::pin::Pin<&mut Self>,
         cx: &mut std::task::Context<'_>,
     ) -> std::task::Poll<Option<Self::Item>> {
-        let this = self.as_mut().project();
-        match this.stream.poll_next(cx) {
-            std::task::Poll::Ready(Some(Ok(message))) => {
-                let message = match message {
-                    tungstenite::Message::Text(json) => json,
-                    _ => return self.poll_next(cx),
-                };
-                let parsed = match serde_json::from_str::<
-                    rmcp::service::RxJsonRpcMessage<rmcp::RoleServer>,
-                >(&message)
-                {
-                    Ok(msg) => msg,
-                    Err(err) => {
-                        tracing::warn!("Websocket JSON parse error: {err}");
-                        return self.poll_next(cx);
+        use std::task::Poll;
+
+        let mut this = self.project();
+        // tungstenite reassembles fragmented messages, so only complete
+        // messages and control frames arrive here.
+        for _ in 0..WS_SKIP_BUDGET {
+            if *this.closed {
+                return Poll::Ready(None);
+            }
+            let message = match std::task::ready!(this.stream.as_mut().poll_next(cx)) {
+                Some(Ok(message)) => message,
+                Some(Err(tungstenite::Error::ConnectionClosed | tungstenite::Error::AlreadyClosed))
+                | None => {
+                    *this.closed = true;
+                    continue;
+                }
+                Some(Err(err)) => {
+                    tracing::warn!("Websocket read error: {err}");
+                    *this.closed = true;
+                    continue;
+                }
+            };
+            match &message {
+                tungstenite::Message::Ping(_) => {
+                    // tungstenite queues the Pong reply; flush so it goes out
+                    // now instead of with the next outgoing message.
+                    if let Poll::Ready(Err(err)) = this.stream.as_mut().poll_flush(cx) {
+                        tracing::warn!("Websocket pong error: {err}");
                     }
-                };
-                std::task::Poll::Ready(Some(parsed))
+                    continue;
+                }
+                tungstenite::Message::Close(frame) => {
+                    // The Close reply is queued by tungstenite and flushed
+                    // when the transport is closed.
+                    tracing::debug!("Websocket closed by peer: {frame:?}");
+                    *this.closed = true;
+                    continue;
+                }
+                _ => {}
             }
-            std::task::Poll::Ready(Some(Err(err))) => {
-                tracing::warn!("Websocket read error: {err}");
-                self.poll_next(cx)
+            let parsed = match ws_payload(&message) {
+                Some(Ok(json)) => serde_json::from_str::<
+                    rmcp::service::RxJsonRpcMessage<rmcp::RoleServer>,
+                >(json)
+                .map_err(|err| err.to_string()),
+                Some(Err(err)) => Err(format!("binary frame is not UTF-8: {err}")),
+                // Pong and raw frames carry nothing for MCP.
+                None => continue,
+            };
+            match parsed {
+                Ok(parsed) => return Poll::Ready(Some(parsed)),
+                Err(err) => tracing::warn!("Websocket JSON parse error: {err}"),
             }
-            std::task::Poll::Ready(None) => std::task::Poll::Ready(None),
-            std::task::Poll::Pending => std::task::Poll::Pending,
         }
+        // Budget spent: yield, but ask to be polled again right away.
+        cx.waker().wake_by_ref();
+        Poll::Pending
     }
 }
 
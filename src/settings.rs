@@ -0,0 +1,611 @@
+//! The settings file (`settings.json`) and its validation.
+//!
+//! The file is checked against the JSON Schema of [`SettingsFile`], so every
+//! unknown key, wrong type and out-of-range number is reported at once with
+//! its JSON pointer, followed by checks of values the schema can't express,
+//! such as bind addresses. The `connections` section belongs to
+//! [`crate::connections`] and is checked when a connection is used.
+
+use std::{fmt, net::SocketAddr};
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::{
+    cli::{CommandArguments, LogFormat, LogRotation},
+    connections,
+    payload_tools::naming::NamingProfiles,
+    policy::ToolPolicy,
+    secrets,
+};
+
+/// Keys holding `host:port` bind addresses.
+const ADDRESS_KEYS: [&str; 4] = ["tcp_addr", "http_addr", "sse_addr", "ws_addr"];
+
+#[derive(Debug, Serialize, Deserialize, Default, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct SettingsFile {
+    pub server_name: Option<String>,
+    pub server_description: Option<String>,
+    pub enable_stdio: Option<bool>,
+    pub enable_tcp: Option<bool>,
+    pub enable_unix: Option<bool>,
+    pub enable_http: Option<bool>,
+    pub enable_sse: Option<bool>,
+    pub enable_ws: Option<bool>,
+    pub tcp_addr: Option<String>,
+    pub http_addr: Option<String>,
+    pub sse_addr: Option<String>,
+    pub ws_addr: Option<String>,
+    pub unix_path: Option<String>,
+    pub enable_pipe: Option<bool>,
+    pub pipe_name: Option<String>,
+    pub pid_file: Option<String>,
+    pub cors_allowed_origins: Option<Vec<String>>,
+    pub cors_allowed_headers: Option<Vec<String>>,
+    pub cors_allowed_methods: Option<Vec<String>>,
+    pub rate_limit_per_minute: Option<u32>,
+    pub max_concurrent_tools: Option<usize>,
+    pub idle_timeout_secs: Option<u64>,
+    pub max_connections: Option<usize>,
+    pub validation_jobs: Option<usize>,
+    pub schema_cache_ttl_secs: Option<u64>,
+    pub schema_cache_on_disk: Option<bool>,
+    pub templates_dir: Option<String>,
+    pub naming_profile: Option<String>,
+    pub naming_profiles: Option<NamingProfiles>,
+    pub shutdown_timeout_secs: Option<u64>,
+    pub watch: Option<Vec<String>>,
+    pub watch_interval_ms: Option<u64>,
+    pub tool_policy: Option<ToolPolicy>,
+    pub log_file: Option<String>,
+    pub log_format: Option<LogFormat>,
+    pub log_rotation: Option<LogRotation>,
+    pub log_max_files: Option<usize>,
+}
+
+impl From<&CommandArguments> for SettingsFile {
+    /// Every setting of `args`, as [`save`](crate::settings) writes them.
+    fn from(args: &CommandArguments) -> Self {
+        Self {
+            server_name: Some(args.server_name.clone()),
+            server_description: Some(args.server_description.clone()),
+            enable_stdio: Some(args.enable_stdio),
+            enable_tcp: Some(args.enable_tcp),
+            enable_unix: Some(args.enable_unix),
+            enable_http: Some(args.enable_http),
+            enable_sse: Some(args.enable_sse),
+            enable_ws: Some(args.enable_ws),
+            tcp_addr: Some(args.tcp_addr.clone()),
+            http_addr: Some(args.http_addr.clone()),
+            sse_addr: Some(args.sse_addr.clone()),
+            ws_addr: Some(args.ws_addr.clone()),
+            unix_path: Some(args.unix_path.clone()),
+            enable_pipe: Some(args.enable_pipe),
+            pipe_name: Some(args.pipe_name.clone()),
+            pid_file: Some(args.pid_file.clone()),
+            cors_allowed_origins: Some(args.cors_allowed_origins.clone()),
+            cors_allowed_headers: Some(args.cors_allowed_headers.clone()),
+            cors_allowed_methods: Some(args.cors_allowed_methods.clone()),
+            rate_limit_per_minute: Some(args.rate_limit_per_minute),
+            max_concurrent_tools: Some(args.max_concurrent_tools),
+            idle_timeout_secs: Some(args.idle_timeout_secs),
+            max_connections: Some(args.max_connections),
+            validation_jobs: Some(args.validation_jobs),
+            schema_cache_ttl_secs: Some(args.schema_cache_ttl_secs),
+            schema_cache_on_disk: Some(args.schema_cache_on_disk),
+            templates_dir: Some(args.templates_dir.clone()),
+            naming_profile: Some(args.naming_profile.clone()),
+            naming_profiles: Some(args.naming_profiles.clone()),
+            shutdown_timeout_secs: Some(args.shutdown_timeout_secs),
+            watch: Some(args.watch.clone()),
+            watch_interval_ms: Some(args.watch_interval_ms),
+            tool_policy: Some(args.tool_policy.clone()),
+            log_file: Some(args.log_file.clone()),
+            log_format: Some(args.log_format),
+            log_rotation: Some(args.log_rotation),
+            log_max_files: Some(args.log_max_files),
+        }
+    }
+}
+
+impl SettingsFile {
+    /// `base` with the settings that are set applied over it.
+    pub fn apply(self, mut base: CommandArguments) -> CommandArguments {
+        if let Some(v) = self.server_name {
+            base.server_name = v;
+        }
+        if let Some(v) = self.server_description {
+            base.server_description = v;
+        }
+        if let Some(v) = self.enable_stdio {
+            base.enable_stdio = v;
+        }
+        if let Some(v) = self.enable_tcp {
+            base.enable_tcp = v;
+        }
+        if let Some(v) = self.enable_unix {
+            base.enable_unix = v;
+        }
+        if let Some(v) = self.enable_http {
+            base.enable_http = v;
+        }
+        if let Some(v) = self.enable_sse {
+            base.enable_sse = v;
+        }
+        if let Some(v) = self.enable_ws {
+            base.enable_ws = v;
+        }
+        if let Some(v) = self.tcp_addr {
+            base.tcp_addr = v;
+        }
+        if let Some(v) = self.http_addr {
+            base.http_addr = v;
+        }
+        if let Some(v) = self.sse_addr {
+            base.sse_addr = v;
+        }
+        if let Some(v) = self.ws_addr {
+            base.ws_addr = v;
+        }
+        if let Some(v) = self.unix_path {
+            base.unix_path = v;
+        }
+        if let Some(v) = self.enable_pipe {
+            base.enable_pipe = v;
+        }
+        if let Some(v) = self.pipe_name {
+            base.pipe_name = v;
+        }
+        if let Some(v) = self.pid_file {
+            base.pid_file = v;
+        }
+        if let Some(v) = self.cors_allowed_origins {
+            base.cors_allowed_origins = v;
+        }
+        if let Some(v) = self.cors_allowed_headers {
+            base.cors_allowed_headers = v;
+        }
+        if let Some(v) = self.cors_allowed_methods {
+            base.cors_allowed_methods = v;
+        }
+        if let Some(v) = self.rate_limit_per_minute {
+            base.rate_limit_per_minute = v;
+        }
+        if let Some(v) = self.max_concurrent_tools {
+            base.max_concurrent_tools = v;
+        }
+        if let Some(v) = self.idle_timeout_secs {
+            base.idle_timeout_secs = v;
+        }
+        if let Some(v) = self.max_connections {
+            base.max_connections = v;
+        }
+        if let Some(v) = self.validation_jobs {
+            base.validation_jobs = v;
+        }
+        if let Some(v) = self.schema_cache_ttl_secs {
+            base.schema_cache_ttl_secs = v;
+        }
+        if let Some(v) = self.schema_cache_on_disk {
+            base.schema_cache_on_disk = v;
+        }
+        if let Some(v) = self.templates_dir {
+            base.templates_dir = v;
+        }
+        if let Some(v) = self.naming_profile {
+            base.naming_profile = v;
+        }
+        if let Some(v) = self.naming_profiles {
+            base.naming_profiles = v;
+        }
+        if let Some(v) = self.shutdown_timeout_secs {
+            base.shutdown_timeout_secs = v;
+        }
+        if let Some(v) = self.watch {
+            base.watch = v;
+        }
+        if let Some(v) = self.watch_interval_ms {
+            base.watch_interval_ms = v;
+        }
+        if let Some(v) = self.tool_policy {
+            base.tool_policy = v;
+        }
+        if let Some(v) = self.log_file {
+            base.log_file = v;
+        }
+        if let Some(v) = self.log_format {
+            base.log_format = v;
+        }
+        if let Some(v) = self.log_rotation {
+            base.log_rotation = v;
+        }
+        if let Some(v) = self.log_max_files {
+            base.log_max_files = v;
+        }
+        base
+    }
+}
+
+/// A problem with the settings file, at a JSON pointer into it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SettingsIssue {
+    /// JSON pointer such as `/tool_policy/http/deny`; empty for the file itself
+    pub path: String,
+    pub message: String,
+}
+
+impl SettingsIssue {
+    fn new(path: &str, message: impl Into<String>) -> Self {
+        Self {
+            path: path.to_string(),
+            message: message.into(),
+        }
+    }
+}
+
+impl fmt::Display for SettingsIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.path.is_empty() {
+            write!(f, "{}", self.message)
+        } else {
+            write!(f, "{}: {}", self.path, self.message)
+        }
+    }
+}
+
+/// JSON Schema of the settings file, without the `connections` section.
+pub fn settings_schema() -> Value {
+    serde_json::to_value(schemars::schema_for!(SettingsFile)).unwrap_or_default()
+}
+
+/// Parse the settings file with `${...}` references expanded, reporting
+/// every problem found. Connections are resolved when used, so their
+/// references are left alone here.
+pub fn read_settings(contents: &str) -> Result<SettingsFile, Vec<SettingsIssue>> {
+    let mut raw: Value = serde_json::from_str(contents)
+        .map_err(|err| vec![SettingsIssue::new("", format!("not valid JSON: {err}"))])?;
+    if let Some(map) = raw.as_object_mut() {
+        map.remove(connections::SETTINGS_KEY);
+    }
+    secrets::expand_json(&mut raw).map_err(|err| vec![SettingsIssue::new("", err)])?;
+    let issues = validate_settings(&raw);
+    if !issues.is_empty() {
+        return Err(issues);
+    }
+    serde_json::from_value(raw).map_err(|err| vec![SettingsIssue::new("", err.to_string())])
+}
+
+/// Check expanded settings against [`settings_schema`] and the address
+/// formats of the bind settings.
+pub fn validate_settings(raw: &Value) -> Vec<SettingsIssue> {
+    let schema = settings_schema();
+    let mut issues = Vec::new();
+    check(&schema, raw, "", &schema, &mut issues);
+
+    for key in ADDRESS_KEYS {
+        let Some(address) = raw.get(key).and_then(Value::as_str) else {
+            continue;
+        };
+        if let Err(err) = address.parse::<SocketAddr>() {
+            issues.push(SettingsIssue::new(
+                &format!("/{key}"),
+                format!("`{address}` is not a bind address such as 127.0.0.1:8080 ({err})"),
+            ));
+        }
+    }
+    if let Some(pipe) = raw
+        .get("pipe_name")
+        .and_then(Value::as_str)
+        .filter(|pipe| !pipe.starts_with(r"\\.\pipe\"))
+    {
+        issues.push(SettingsIssue::new(
+            "/pipe_name",
+            format!(r"`{pipe}` must start with \\.\pipe\"),
+        ));
+    }
+    for (index, method) in raw
+        .get("cors_allowed_methods")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+        .enumerate()
+        .filter_map(|(index, method)| Some((index, method.as_str()?)))
+    {
+        if method.parse::<hyper::Method>().is_err() {
+            issues.push(SettingsIssue::new(
+                &format!("/cors_allowed_methods/{index}"),
+                format!("`{method}` is not an HTTP method"),
+            ));
+        }
+    }
+    issues.sort_by(|a, b| a.path.cmp(&b.path));
+    issues
+}
+
+fn type_name(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "boolean",
+        Value::Number(number) if number.is_i64() || number.is_u64() => "integer",
+        Value::Number(_) => "number",
+        Value::String(_) => "string",
+        Value::Array(_) => "array",
+        Value::Object(_) => "object",
+    }
+}
+
+fn has_type(value: &Value, expected: &str) -> bool {
+    let actual = type_name(value);
+    actual == expected || (expected == "number" && actual == "integer")
+}
+
+/// `#/$defs/Name` in `root`.
+fn resolve<'a>(root: &'a Value, reference: &str) -> Option<&'a Value> {
+    let name = reference
+        .strip_prefix("#/$defs/")
+        .or_else(|| reference.strip_prefix("#/definitions/"))?;
+    root.get("$defs")
+        .or_else(|| root.get("definitions"))?
+        .get(name)
+}
+
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut previous = row[0];
+        row[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let current = row[j + 1];
+            row[j + 1] = (previous + usize::from(ca != *cb))
+                .min(row[j] + 1)
+                .min(current + 1);
+            previous = current;
+        }
+    }
+    row[b.len()]
+}
+
+fn unknown_key(key: &str, known: Option<&serde_json::Map<String, Value>>) -> String {
+    let closest = known
+        .into_iter()
+        .flat_map(|known| known.keys())
+        .map(|candidate| (edit_distance(key, candidate), candidate))
+        .filter(|(distance, _)| *distance <= 3)
+        .min();
+    match closest {
+        Some((_, candidate)) => format!("unknown key; did you mean `{candidate}`?"),
+        None => "unknown key".to_string(),
+    }
+}
+
+/// Integer range of the `format`s schemars writes for unsigned integers.
+fn format_max(format: &str) -> Option<u64> {
+    match format {
+        "uint8" => Some(u8::MAX.into()),
+        "uint16" => Some(u16::MAX.into()),
+        "uint32" => Some(u32::MAX.into()),
+        "uint" | "uint64" => Some(u64::MAX),
+        _ => None,
+    }
+}
+
+/// Validate `value` against the subset of JSON Schema that schemars writes
+/// for the settings types.
+fn check(schema: &Value, value: &Value, path: &str, root: &Value, issues: &mut Vec<SettingsIssue>) {
+    let schema = match schema {
+        Value::Bool(true) => return,
+        Value::Bool(false) => {
+            issues.push(SettingsIssue::new(path, "not allowed here"));
+            return;
+        }
+        Value::Object(schema) => schema,
+        _ => return,
+    };
+    if let Some(target) = schema
+        .get("$ref")
+        .and_then(Value::as_str)
+        .and_then(|r| resolve(root, r))
+    {
+        check(target, value, path, root, issues);
+    }
+    for keyword in ["anyOf", "oneOf"] {
+        let Some(branches) = schema.get(keyword).and_then(Value::as_array) else {
+            continue;
+        };
+        // Documented enum variants are one `const` branch each.
+        let constants: Option<Vec<&Value>> =
+            branches.iter().map(|branch| branch.get("const")).collect();
+        if let Some(constants) = constants {
+            if !constants.contains(&value) {
+                let allowed: Vec<String> = constants.iter().map(ToString::to_string).collect();
+                issues.push(SettingsIssue::new(
+                    path,
+                    format!("{value} is not one of {}", allowed.join(", ")),
+                ));
+            }
+            continue;
+        }
+        let mut best: Option<Vec<SettingsIssue>> = None;
+        for branch in branches {
+            // `Option<T>` is `T` or null; a non-null value is only compared with `T`.
+            if !value.is_null() && branch.get("type") == Some(&Value::from("null")) {
+                continue;
+            }
+            let mut found = Vec::new();
+            check(branch, value, path, root, &mut found);
+            if found.is_empty() {
+                best = None;
+                break;
+            }
+            if best.as_ref().is_none_or(|best| found.len() < best.len()) {
+                best = Some(found);
+            }
+        }
+        issues.extend(best.into_iter().flatten());
+    }
+
+    match schema.get("type") {
+        Some(Value::String(expected)) if !has_type(value, expected) => {
+            issues.push(SettingsIssue::new(
+                path,
+                format!("expected {expected}, found {}", type_name(value)),
+            ));
+            return;
+        }
+        Some(Value::Array(expected)) => {
+            let expected: Vec<&str> = expected.iter().filter_map(Value::as_str).collect();
+            if !expected.iter().any(|expected| has_type(value, expected)) {
+                issues.push(SettingsIssue::new(
+                    path,
+                    format!(
+                        "expected {}, found {}",
+                        expected
+                            .iter()
+                            .filter(|name| **name != "null")
+                            .copied()
+                            .collect::<Vec<_>>()
+                            .join(" or "),
+                        type_name(value)
+                    ),
+                ));
+                return;
+            }
+        }
+        _ => {}
+    }
+    if let Some(allowed) = schema
+        .get("enum")
+        .and_then(Value::as_array)
+        .filter(|allowed| !allowed.contains(value))
+    {
+        let allowed: Vec<String> = allowed.iter().map(Value::to_string).collect();
+        issues.push(SettingsIssue::new(
+            path,
+            format!("{value} is not one of {}", allowed.join(", ")),
+        ));
+    }
+    if let Some(constant) = schema.get("const").filter(|constant| *constant != value) {
+        issues.push(SettingsIssue::new(
+            path,
+            format!("expected {constant}, found {value}"),
+        ));
+    }
+
+    let number = value.as_f64();
+    let bound = |keyword: &str| schema.get(keyword).and_then(Value::as_f64);
+    if let Some((_, minimum)) = number
+        .zip(bound("minimum"))
+        .filter(|(number, minimum)| number < minimum)
+    {
+        issues.push(SettingsIssue::new(
+            path,
+            format!("must be at least {minimum}"),
+        ));
+    }
+    let format_max = schema
+        .get("format")
+        .and_then(Value::as_str)
+        .and_then(format_max);
+    let too_large = match (number, bound("maximum")) {
+        (Some(number), Some(maximum)) if number > maximum => Some(maximum.to_string()),
+        _ => value
+            .as_u64()
+            .zip(format_max)
+            .filter(|(number, maximum)| number > maximum)
+            .map(|(_, maximum)| maximum.to_string()),
+    };
+    if let Some(maximum) = too_large {
+        issues.push(SettingsIssue::new(
+            path,
+            format!("must be at most {maximum}"),
+        ));
+    }
+
+    if let Value::Object(map) = value {
+        let properties = schema.get("properties").and_then(Value::as_object);
+        for (key, child) in map {
+            let child_path = format!("{path}/{key}");
+            match (
+                properties.and_then(|properties| properties.get(key)),
+                schema.get("additionalProperties"),
+            ) {
+                (Some(property), _) => check(property, child, &child_path, root, issues),
+                (None, Some(Value::Bool(false))) => {
+                    issues.push(SettingsIssue::new(
+                        &child_path,
+                        unknown_key(key, properties),
+                    ));
+                }
+                (None, Some(additional)) => check(additional, child, &child_path, root, issues),
+                (None, None) => {}
+            }
+        }
+        for required in schema
+            .get("required")
+            .and_then(Value::as_array)
+            .into_iter()
+            .flatten()
+        {
+            if let Some(required) = required.as_str().filter(|key| !map.contains_key(*key)) {
+                issues.push(SettingsIssue::new(
+                    path,
+                    format!("missing required key `{required}`"),
+                ));
+            }
+        }
+    }
+    if let (Value::Array(items), Some(item_schema)) = (value, schema.get("items")) {
+        for (index, item) in items.iter().enumerate() {
+            check(item_schema, item, &format!("{path}/{index}"), root, issues);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn test_validate_settings_reports_each_problem() {
+        let raw = json!({
+            "enable_tpc": true,
+            "tcp_addr": "localhost",
+            "http_addr": "0.0.0.0:8080",
+            "rate_limit_per_minute": -1,
+            "log_format": "xml",
+            "cors_allowed_methods": ["GET", "NOT A METHOD"],
+            "tool_policy": { "http": { "deny": "scaffold_project", "alow": [] } },
+            "watch": ["src"]
+        });
+        let issues: Vec<String> = validate_settings(&raw)
+            .iter()
+            .map(ToString::to_string)
+            .collect();
+        assert_eq!(
+            issues,
+            vec![
+                "/cors_allowed_methods/1: `NOT A METHOD` is not an HTTP method",
+                "/enable_tpc: unknown key; did you mean `enable_tcp`?",
+                "/log_format: \"xml\" is not one of \"pretty\", \"json\"",
+                "/rate_limit_per_minute: must be at least 0",
+                "/tcp_addr: `localhost` is not a bind address such as 127.0.0.1:8080 (invalid socket address syntax)",
+                "/tool_policy/http/alow: unknown key; did you mean `allow`?",
+                "/tool_policy/http/deny: expected array, found string",
+            ]
+        );
+
+        assert!(
+            read_settings(r#"{ "enable_http": true, }"#).unwrap_err()[0]
+                .message
+                .contains("line 1")
+        );
+        assert!(
+            read_settings(r#"{ "enable_http": false, "http_addr": "127.0.0.1:3000" }"#).is_ok()
+        );
+    }
+}
@@ -0,0 +1,196 @@
+//! The `tool` command: list, describe and call MCP tools in-process, without
+//! an MCP client.
+
+use std::fs;
+
+use rmcp::model::{CallToolResult, RawContent, Tool};
+use serde_json::{Map, Value, json};
+
+use crate::{
+    cli::{OutputFormat, ToolCallArguments},
+    payload_tools::{
+        mcp::{run_tool, tool_definitions},
+        types::FileType,
+    },
+};
+
+fn find_tool(name: &str) -> Result<Tool, String> {
+    tool_definitions()
+        .into_iter()
+        .find(|tool| tool.name == name)
+        .ok_or_else(|| format!("Unknown tool: {name} (see `tool list`)"))
+}
+
+fn description(tool: &Tool) -> String {
+    json!(tool.description).as_str().unwrap_or_default().to_string()
+}
+
+fn to_json(value: &impl serde::Serialize) -> String {
+    serde_json::to_string_pretty(value).unwrap_or_default()
+}
+
+/// Every tool with its description.
+pub fn list(format: OutputFormat) -> String {
+    let tools = tool_definitions();
+    match format {
+        OutputFormat::Json => to_json(
+            &tools
+                .iter()
+                .map(|tool| json!({ "name": tool.name, "description": description(tool) }))
+                .collect::<Vec<_>>(),
+        ),
+        OutputFormat::Pretty => {
+            let width = tools.iter().map(|tool| tool.name.len()).max().unwrap_or(0);
+            tools
+                .iter()
+                .map(|tool| format!("{:width$}  {}", tool.name, description(tool)))
+                .collect::<Vec<_>>()
+                .join("\n")
+        }
+    }
+}
+
+/// Input and output schema of one tool.
+pub fn schema(name: &str, format: OutputFormat) -> Result<String, String> {
+    let tool = find_tool(name)?;
+    Ok(match format {
+        OutputFormat::Json => to_json(&tool),
+        OutputFormat::Pretty => {
+            let mut out = format!(
+                "{}: {}\n\ninput schema:\n{}",
+                tool.name,
+                description(&tool),
+                to_json(&tool.input_schema)
+            );
+            if let Some(output) = &tool.output_schema {
+                out.push_str(&format!("\n\noutput schema:\n{}", to_json(output)));
+            }
+            out
+        }
+    })
+}
+
+/// Assemble a tool's arguments: `--args` first, then a `--file` (its contents
+/// become `code` for tools that take code, otherwise it must hold a JSON
+/// object), then each `--arg`. A file named after a file type, such as
+/// `collection.json` or `posts.global.ts`, also supplies `file_type`.
+pub fn build_arguments(tool: &Tool, call: &ToolCallArguments) -> Result<Value, String> {
+    let properties = tool
+        .input_schema
+        .get("properties")
+        .and_then(Value::as_object)
+        .cloned()
+        .unwrap_or_default();
+    let mut arguments = match &call.args {
+        Some(raw) => match serde_json::from_str(raw) {
+            Ok(Value::Object(map)) => map,
+            Ok(_) => return Err("--args must be a JSON object".to_string()),
+            Err(e) => return Err(format!("Invalid --args JSON: {e}")),
+        },
+        None => Map::new(),
+    };
+
+    if let Some(path) = &call.file {
+        let contents = fs::read_to_string(path)
+            .map_err(|e| format!("Could not read {}: {e}", path.display()))?;
+        if properties.contains_key("code") {
+            arguments
+                .entry("code")
+                .or_insert_with(|| Value::String(contents));
+        } else {
+            match serde_json::from_str(&contents) {
+                Ok(Value::Object(map)) => {
+                    for (key, value) in map {
+                        arguments.entry(key).or_insert(value);
+                    }
+                }
+                _ => {
+                    return Err(format!(
+                        "{} must contain a JSON object of arguments for {}",
+                        path.display(),
+                        tool.name
+                    ));
+                }
+            }
+        }
+        if properties.contains_key("file_type") && !arguments.contains_key("file_type") {
+            if let Some(file_type) = FileType::from_path(path) {
+                arguments.insert("file_type".to_string(), json!(file_type));
+            }
+        }
+    }
+
+    for pair in &call.arg {
+        let (key, value) = parse_assignment(pair)?;
+        arguments.insert(key, value);
+    }
+    Ok(Value::Object(arguments))
+}
+
+/// Split a `KEY=VALUE` flag. Values that are not JSON are taken as strings.
+pub(crate) fn parse_assignment(pair: &str) -> Result<(String, Value), String> {
+    let (key, value) = pair
+        .split_once('=')
+        .ok_or_else(|| format!("Invalid argument '{pair}': expected KEY=VALUE"))?;
+    let value = serde_json::from_str(value).unwrap_or_else(|_| Value::String(value.to_string()));
+    Ok((key.to_string(), value))
+}
+
+/// Call a tool and render its result. The flag is set when the tool
+/// reported an error.
+pub async fn call(call: &ToolCallArguments, format: OutputFormat) -> Result<(String, bool), String> {
+    let tool = find_tool(&call.name)?;
+    let arguments = build_arguments(&tool, call)?;
+    let result = run_tool(&call.name, arguments)
+        .await
+        .map_err(|err| format!("{}: {}", call.name, err.message))?;
+    let is_error = result.is_error.unwrap_or(false);
+    Ok((render(&result, format), is_error))
+}
+
+fn render(result: &CallToolResult, format: OutputFormat) -> String {
+    match format {
+        OutputFormat::Json => to_json(result),
+        OutputFormat::Pretty => {
+            if let Some(structured) = &result.structured_content {
+                return to_json(structured);
+            }
+            result
+                .content
+                .iter()
+                .map(|content| match &content.raw {
+                    RawContent::Text(text) => text.text.clone(),
+                    other => to_json(other),
+                })
+                .collect::<Vec<_>>()
+                .join("\n")
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::Parser;
+
+    #[test]
+    fn test_build_arguments_from_file() {
+        let path = std::env::temp_dir().join(format!("{}.collection.ts", std::process::id()));
+        fs::write(&path, "export const Posts = { slug: 'posts', fields: [] }").unwrap();
+        let call = ToolCallArguments::try_parse_from([
+            "tool",
+            "validate",
+            "--file",
+            path.to_str().unwrap(),
+            "--arg",
+            "extra=3",
+        ])
+        .unwrap();
+
+        let arguments = build_arguments(&find_tool("validate").unwrap(), &call).unwrap();
+        assert_eq!(arguments["file_type"], "collection");
+        assert!(arguments["code"].as_str().unwrap().contains("slug: 'posts'"));
+        assert_eq!(arguments["extra"], 3);
+        let _ = fs::remove_file(&path);
+    }
+}
@@ -0,0 +1,41 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::{config::Setting, server::ServerState};
+
+#[derive(Debug, Deserialize, JsonSchema, Default)]
+pub struct ExplainConfigParams {
+    /// Settings to report, e.g. `["http_addr", "tool_policy"]`; all when
+    /// omitted
+    #[serde(default)]
+    pub keys: Option<Vec<String>>,
+}
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct ConfigExplanation {
+    pub settings: Vec<Setting>,
+}
+
+/// The running server's settings with the layer that supplied each.
+pub fn explain_config(
+    state: &ServerState,
+    params: &ExplainConfigParams,
+) -> Result<ConfigExplanation, String> {
+    let Some(keys) = &params.keys else {
+        return Ok(ConfigExplanation {
+            settings: state.settings.clone(),
+        });
+    };
+    let settings = keys
+        .iter()
+        .map(|key| {
+            state
+                .settings
+                .iter()
+                .find(|setting| setting.key == *key)
+                .cloned()
+                .ok_or_else(|| format!("Unknown setting `{key}`"))
+        })
+        .collect::<Result<_, _>>()?;
+    Ok(ConfigExplanation { settings })
+}
@@ -1,7 +1,10 @@
 use schemars::JsonSchema;
 use serde::Deserialize;
 
-use crate::server::ServerState;
+use crate::{
+    payload_tools::{generator::template_cache_stats, schema_cache, sql::pattern_cache_stats},
+    server::ServerState,
+};
 
 #[derive(Debug, Deserialize, JsonSchema, Default)]
 pub struct HealthParams {
@@ -11,25 +14,60 @@ pub struct HealthParams {
 
 pub fn health_summary(state: &ServerState, verbose: bool) -> String {
     let uptime = state.uptime();
-    let active = state.transports.active_endpoints();
+    let recorded = state.endpoints();
+    let active = if recorded.is_empty() {
+        state.transports.active_endpoints()
+    } else {
+        recorded.iter().map(ToString::to_string).collect()
+    };
     let transports = if active.is_empty() {
         "none".to_string()
     } else {
         active.join(", ")
     };
+    let sessions = state.session_activity();
     if verbose {
-        format!(
-            "status: ok\nversion: {}\nuptime_seconds: {}\ntransports: {}",
+        let mut summary = format!(
+            "status: ok\nversion: {}\nuptime_seconds: {}\ntransports: {}\nconnections: {}",
             state.version,
             uptime.as_secs(),
-            transports
-        )
+            transports,
+            sessions.len()
+        );
+        for (name, stats) in [
+            ("template_cache", template_cache_stats()),
+            ("pattern_cache", pattern_cache_stats()),
+        ] {
+            summary.push_str(&format!(
+                "\n{name}: {} of {} entries, {} hits, {} misses",
+                stats.entries, stats.capacity, stats.hits, stats.misses
+            ));
+        }
+        let schemas = schema_cache::stats();
+        summary.push_str(&format!(
+            "\nschema_cache: {} entries, ttl {}s, {} hits, {} revalidated, {} misses",
+            schemas.entries, schemas.ttl_secs, schemas.hits, schemas.revalidated, schemas.misses
+        ));
+        for (id, session) in &sessions {
+            summary.push_str(&format!(
+                "\n  - session: {id}\n    transport: {}\n    age_seconds: {}\n    last_activity: {}\n    idle_seconds: {}",
+                session.transport,
+                session.opened_at.elapsed().unwrap_or_default().as_secs(),
+                chrono::DateTime::<chrono::Utc>::from(session.last_activity).to_rfc3339(),
+                session.last_activity.elapsed().unwrap_or_default().as_secs()
+            ));
+        }
+        summary
     } else {
+        let templates = template_cache_stats();
         format!(
-            "ok (v{}, uptime {}s, transports: {})",
+            "ok (v{}, uptime {}s, transports: {}, connections: {}, template cache: {} hits / {} misses)",
             state.version,
             uptime.as_secs(),
-            transports
+            transports,
+            sessions.len(),
+            templates.hits,
+            templates.misses
         )
     }
 }
@@ -1,5 +1,7 @@
 pub mod echo;
+pub mod explain_config;
 pub mod health;
 
 pub use echo::EchoParams;
+pub use explain_config::ExplainConfigParams;
 pub use health::HealthParams;
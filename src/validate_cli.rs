@@ -0,0 +1,240 @@
+//! The `validate` command: validate Payload sources on disk without a server.
+
+use std::{
+    collections::BTreeSet,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use colored::Colorize;
+use serde::Serialize;
+
+use crate::{
+    cli::ReportFormat,
+    payload_tools::{
+        parallel,
+        types::{FileType, ValidationResult, junit_report, sarif_report},
+        validator::{check_production, validate_payload_code},
+    },
+};
+
+/// Extensions of files picked up when walking a directory.
+const SOURCE_EXTENSIONS: [&str; 6] = ["ts", "tsx", "js", "mjs", "cjs", "json"];
+/// Directories never walked into.
+const SKIPPED_DIRS: [&str; 4] = ["node_modules", "dist", "build", "target"];
+
+#[derive(Debug, Clone, Serialize)]
+pub struct FileReport {
+    pub path: String,
+    /// `None` when the file type could not be detected; the file is skipped
+    pub file_type: Option<FileType>,
+    pub result: Option<ValidationResult>,
+}
+
+impl FileReport {
+    pub(crate) fn failed(&self, deny_warnings: bool) -> bool {
+        self.result
+            .as_ref()
+            .is_some_and(|result| !result.is_valid || (deny_warnings && !result.warnings.is_empty()))
+    }
+}
+
+/// Expand file paths, directories (walked recursively for source files) and
+/// glob patterns into a sorted list of files.
+pub fn collect_files(patterns: &[String]) -> Result<Vec<PathBuf>, String> {
+    let mut files = BTreeSet::new();
+    for pattern in patterns {
+        let path = Path::new(pattern);
+        if path.is_dir() {
+            walk(path, &mut files)?;
+        } else if path.is_file() {
+            files.insert(path.to_path_buf());
+        } else if pattern.contains(['*', '?', '[']) {
+            let matches = glob::glob(pattern).map_err(|e| format!("Invalid pattern '{pattern}': {e}"))?;
+            for entry in matches.filter_map(Result::ok) {
+                if entry.is_dir() {
+                    walk(&entry, &mut files)?;
+                } else {
+                    files.insert(entry);
+                }
+            }
+        } else {
+            return Err(format!("No such file or directory: {pattern}"));
+        }
+    }
+    Ok(files.into_iter().collect())
+}
+
+fn walk(dir: &Path, files: &mut BTreeSet<PathBuf>) -> Result<(), String> {
+    let entries = fs::read_dir(dir).map_err(|e| format!("Could not read {}: {e}", dir.display()))?;
+    for entry in entries.filter_map(Result::ok) {
+        let path = entry.path();
+        let name = entry.file_name().to_string_lossy().into_owned();
+        if path.is_dir() {
+            if !name.starts_with('.') && !SKIPPED_DIRS.contains(&name.as_str()) {
+                walk(&path, files)?;
+            }
+        } else if path
+            .extension()
+            .is_some_and(|ext| SOURCE_EXTENSIONS.contains(&ext.to_string_lossy().as_ref()))
+            && !name.ends_with(".d.ts")
+        {
+            files.insert(path);
+        }
+    }
+    Ok(())
+}
+
+/// Validate each file as `file_type`, or as the type detected from its path
+/// and contents, on up to `jobs` threads (0 for one per CPU), adding the
+/// production checks to configs when `production` is set. Reports keep the
+/// order of `files`.
+pub fn validate_files(files: &[PathBuf], file_type: Option<FileType>, jobs: usize, production: bool) -> Vec<FileReport> {
+    parallel::map(files, jobs, |path| {
+        let display = path.display().to_string();
+        match fs::read_to_string(path) {
+            Ok(code) => {
+                let file_type = file_type.or_else(|| FileType::detect(path, &code));
+                FileReport {
+                    path: display,
+                    file_type,
+                    result: file_type.map(|file_type| {
+                        let mut result = validate_payload_code(&code, file_type);
+                        if production {
+                            check_production(&code, file_type, &mut result);
+                        }
+                        result
+                    }),
+                }
+            }
+            Err(err) => FileReport {
+                path: display,
+                file_type,
+                result: Some(ValidationResult::with_errors(vec![format!(
+                    "Could not read file: {err}"
+                )])),
+            },
+        }
+    })
+}
+
+/// Whether the reports should fail a CI run.
+pub fn has_failures(reports: &[FileReport], deny_warnings: bool) -> bool {
+    reports.iter().any(|report| report.failed(deny_warnings))
+}
+
+pub fn render(reports: &[FileReport], format: ReportFormat) -> String {
+    match format {
+        ReportFormat::Json => serde_json::to_string_pretty(reports).unwrap_or_default(),
+        ReportFormat::Sarif => serde_json::to_string_pretty(&sarif_report(validated(reports)))
+            .unwrap_or_default(),
+        ReportFormat::Junit => junit_report(validated(reports)),
+        ReportFormat::Pretty => render_pretty(reports),
+    }
+}
+
+/// Reports of files whose type was detected, as (path, result).
+fn validated(reports: &[FileReport]) -> impl Iterator<Item = (&str, &ValidationResult)> {
+    reports
+        .iter()
+        .filter_map(|report| Some((report.path.as_str(), report.result.as_ref()?)))
+}
+
+fn render_pretty(reports: &[FileReport]) -> String {
+    let mut out = Vec::new();
+    let (mut valid, mut invalid, mut skipped) = (0, 0, 0);
+    for report in reports {
+        let Some(result) = &report.result else {
+            skipped += 1;
+            out.push(format!(
+                "{} {} (could not detect file type; pass --file-type)",
+                "SKIP".yellow().bold(),
+                report.path
+            ));
+            continue;
+        };
+        let file_type = report.file_type.map(|t| format!(" [{t}]")).unwrap_or_default();
+        if result.is_valid {
+            valid += 1;
+            out.push(format!("{} {}{file_type}", "PASS".green().bold(), report.path));
+        } else {
+            invalid += 1;
+            out.push(format!("{} {}{file_type}", "FAIL".red().bold(), report.path));
+        }
+        for error in &result.errors {
+            out.push(format!("  {} {error}", "error:".red()));
+        }
+        for warning in &result.warnings {
+            out.push(format!("  {} {warning}", "warning:".yellow()));
+        }
+        for suggestion in &result.suggestions {
+            out.push(format!("  {} {}", "hint:".cyan(), suggestion.message));
+        }
+    }
+    out.push(format!(
+        "\n{} files: {valid} valid, {invalid} invalid, {skipped} skipped",
+        reports.len()
+    ));
+    out.join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_collect_and_detect() {
+        let dir = std::env::temp_dir().join(format!("mcp-validate-test-{}", std::process::id()));
+        fs::create_dir_all(dir.join("collections")).unwrap();
+        fs::create_dir_all(dir.join("node_modules")).unwrap();
+        fs::write(dir.join("collections/Posts.ts"), "export const Posts = {}").unwrap();
+        fs::write(dir.join("node_modules/ignored.ts"), "").unwrap();
+        fs::write(dir.join("notes.md"), "").unwrap();
+        fs::write(
+            dir.join("site.ts"),
+            "export const Site: GlobalConfig = { slug: 'site', fields: [] }",
+        )
+        .unwrap();
+
+        let files = collect_files(&[dir.to_string_lossy().into_owned()]).unwrap();
+        let reports = validate_files(&files, None, 2, false);
+        let types: Vec<_> = reports.iter().map(|report| report.file_type).collect();
+        assert_eq!(types, vec![Some(FileType::Collection), Some(FileType::Global)]);
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_render_junit() {
+        let reports = vec![
+            FileReport {
+                path: "collections/Posts.ts".to_string(),
+                file_type: Some(FileType::Collection),
+                result: Some(ValidationResult::with_errors(vec!["Missing <slug>".to_string()])),
+            },
+            FileReport {
+                path: "globals/Site.ts".to_string(),
+                file_type: Some(FileType::Global),
+                result: Some(ValidationResult::ok()),
+            },
+        ];
+        let xml = render(&reports, ReportFormat::Junit);
+        assert!(xml.contains(r#"<testsuite name="payload-validation" tests="2" failures="1""#));
+        assert!(xml.contains(r#"message="1 validation error">Missing &lt;slug&gt;</failure>"#));
+        assert!(xml.contains(r#"<testcase classname="payload" name="globals/Site.ts" file="globals/Site.ts"/>"#));
+    }
+
+    #[test]
+    fn test_production_checks() {
+        let config = "export default buildConfig({ secret: process.env.PAYLOAD_SECRET, cors: '*', collections: [] })";
+        let mut result = ValidationResult::ok();
+        check_production(config, FileType::Config, &mut result);
+        assert_eq!(result.warnings.len(), 2);
+        assert!(result.warnings[0].starts_with("Missing csrf"));
+        assert!(result.warnings[1].starts_with("cors allows every origin"));
+
+        let v2 = "export default buildConfig({ csrf: ['https://example.com'], cors: ['https://example.com'], collections: [] })";
+        let mut result = ValidationResult::ok();
+        check_production(v2, FileType::Config, &mut result);
+        assert_eq!(result.warnings, vec!["Missing rateLimit in a production config. Limit requests per client IP."]);
+    }
+}
@@ -0,0 +1,208 @@
+//! Watch mode: re-validate a project's sources whenever they change. Used by
+//! the `watch` command and, with `--watch`, by a server task whose
+//! diagnostics reach clients as MCP log notifications.
+//!
+//! Files are polled by modification time, so no platform file-event API is
+//! needed and network or container mounts behave the same as local disks.
+
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fs,
+    path::PathBuf,
+    time::{Duration, SystemTime},
+};
+
+use tokio_util::sync::CancellationToken;
+
+use crate::{
+    payload_tools::types::FileType,
+    validate_cli::{FileReport, collect_files, validate_files},
+};
+
+/// Target of the log records emitted by the server's watch task.
+pub const WATCH_LOG_TARGET: &str = "payload_watch";
+
+/// Files changed, added or removed since the previous poll.
+#[derive(Debug, Default)]
+pub struct WatchUpdate {
+    /// Reports for files that changed or appeared, in path order
+    pub reports: Vec<FileReport>,
+    pub removed: Vec<PathBuf>,
+    /// Watched files currently failing validation
+    pub failing: usize,
+    /// Watched files
+    pub total: usize,
+}
+
+impl WatchUpdate {
+    pub fn is_empty(&self) -> bool {
+        self.reports.is_empty() && self.removed.is_empty()
+    }
+}
+
+pub struct ProjectWatcher {
+    patterns: Vec<String>,
+    file_type: Option<FileType>,
+    jobs: usize,
+    /// Modification time of each watched file at the last poll
+    seen: BTreeMap<PathBuf, Option<SystemTime>>,
+    failing: BTreeSet<String>,
+}
+
+impl ProjectWatcher {
+    /// Watch files, directories and glob patterns as `validate` takes them.
+    pub fn new(patterns: Vec<String>, file_type: Option<FileType>, jobs: usize) -> Self {
+        Self {
+            patterns,
+            file_type,
+            jobs,
+            seen: BTreeMap::new(),
+            failing: BTreeSet::new(),
+        }
+    }
+
+    /// Validate the files changed since the last poll; the first poll
+    /// validates everything.
+    pub fn poll(&mut self) -> Result<WatchUpdate, String> {
+        let mut current = BTreeMap::new();
+        let mut changed = Vec::new();
+        for path in collect_files(&self.patterns)? {
+            let modified = fs::metadata(&path).and_then(|meta| meta.modified()).ok();
+            if self.seen.get(&path) != Some(&modified) {
+                changed.push(path.clone());
+            }
+            current.insert(path, modified);
+        }
+        let removed: Vec<PathBuf> = self
+            .seen
+            .keys()
+            .filter(|path| !current.contains_key(*path))
+            .cloned()
+            .collect();
+        for path in &removed {
+            self.failing.remove(&path.display().to_string());
+        }
+        self.seen = current;
+
+        let reports = validate_files(&changed, self.file_type, self.jobs, false);
+        for report in &reports {
+            if report.failed(false) {
+                self.failing.insert(report.path.clone());
+            } else {
+                self.failing.remove(&report.path);
+            }
+        }
+        Ok(WatchUpdate {
+            reports,
+            removed,
+            failing: self.failing.len(),
+            total: self.seen.len(),
+        })
+    }
+}
+
+/// Log an update under [`WATCH_LOG_TARGET`]: problems as warnings, files
+/// that now pass as info.
+pub fn log_update(update: &WatchUpdate) {
+    for report in &update.reports {
+        let Some(result) = &report.result else {
+            continue;
+        };
+        if result.errors.is_empty() && result.warnings.is_empty() {
+            tracing::info!(target: WATCH_LOG_TARGET, "{}: valid", report.path);
+        }
+        for error in &result.errors {
+            tracing::warn!(target: WATCH_LOG_TARGET, "{}: error: {error}", report.path);
+        }
+        for warning in &result.warnings {
+            tracing::warn!(target: WATCH_LOG_TARGET, "{}: warning: {warning}", report.path);
+        }
+    }
+    for path in &update.removed {
+        tracing::info!(target: WATCH_LOG_TARGET, "{}: removed", path.display());
+    }
+    tracing::info!(
+        target: WATCH_LOG_TARGET,
+        "{} of {} watched files failing",
+        update.failing,
+        update.total
+    );
+}
+
+/// Poll `patterns` every `interval` until `shutdown`, logging each change.
+pub async fn watch_in_background(
+    patterns: Vec<String>,
+    jobs: usize,
+    interval: Duration,
+    shutdown: CancellationToken,
+) {
+    tracing::info!(target: WATCH_LOG_TARGET, "Watching {}", patterns.join(", "));
+    let mut watcher = ProjectWatcher::new(patterns, None, jobs);
+    let mut ticker = tokio::time::interval(interval);
+    let mut last_error = None;
+    loop {
+        tokio::select! {
+            _ = shutdown.cancelled() => break,
+            _ = ticker.tick() => {}
+        }
+        let polled = tokio::task::spawn_blocking(move || {
+            let update = watcher.poll();
+            (watcher, update)
+        })
+        .await;
+        let (returned, update) = match polled {
+            Ok(polled) => polled,
+            Err(err) => {
+                tracing::error!(target: WATCH_LOG_TARGET, "Watch task failed: {err}");
+                break;
+            }
+        };
+        watcher = returned;
+        match update {
+            Ok(update) if !update.is_empty() => log_update(&update),
+            Ok(_) => {}
+            // A missing directory is reported once, not on every poll.
+            Err(err) if last_error.as_ref() != Some(&err) => {
+                tracing::warn!(target: WATCH_LOG_TARGET, "{err}");
+                last_error = Some(err);
+                continue;
+            }
+            Err(_) => continue,
+        }
+        last_error = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reports_only_changed_files() {
+        let dir = std::env::temp_dir().join(format!("mcp-watch-test-{}", std::process::id()));
+        fs::create_dir_all(dir.join("collections")).unwrap();
+        let posts = dir.join("collections/Posts.ts");
+        fs::write(
+            &posts,
+            "export const Posts: CollectionConfig = { slug: 'posts', fields: [] }",
+        )
+        .unwrap();
+        fs::write(
+            dir.join("collections/Tags.ts"),
+            "export const Tags: CollectionConfig = { slug: 'tags', fields: [] }",
+        )
+        .unwrap();
+
+        let mut watcher = ProjectWatcher::new(vec![dir.to_string_lossy().into_owned()], None, 1);
+        let first = watcher.poll().unwrap();
+        assert_eq!(first.reports.len(), 2);
+        assert!(watcher.poll().unwrap().is_empty());
+
+        fs::remove_file(&posts).unwrap();
+        let update = watcher.poll().unwrap();
+        assert!(update.reports.is_empty());
+        assert_eq!(update.removed, vec![posts]);
+        assert_eq!(update.total, 1);
+        let _ = fs::remove_dir_all(&dir);
+    }
+}